@@ -40,6 +40,7 @@ use std::fs::{create_dir_all, File};
 use std::io::Write;
 use std::num::NonZeroU32;
 use std::rc::Rc;
+use style_traits::media_queries::MediaEnvironment;
 use style_traits::viewport::ViewportConstraints;
 use style_traits::{CSSPixel, DevicePixel, PinchZoomFactor};
 use time::{now, precise_time_ns, precise_time_s};
@@ -194,6 +195,10 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// The coordinates of the native window, its view and the screen.
     embedder_coordinates: EmbedderCoordinates,
 
+    /// The preferences of the user and the capabilities of their device which
+    /// media queries are evaluated against.
+    media_environment: MediaEnvironment,
+
     /// Current mouse cursor.
     cursor: Cursor,
 
@@ -289,6 +294,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
 
         IOCompositor {
             embedder_coordinates: window.get_coordinates(),
+            media_environment: state.media_environment,
             window,
             port: state.receiver,
             root_pipeline: None,
@@ -664,6 +670,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         let data = WindowSizeData {
             device_pixel_ratio: dppx,
             initial_viewport: initial_viewport,
+            media_environment: self.media_environment,
         };
 
         let top_level_browsing_context_id = self
@@ -697,6 +704,14 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.composite_if_necessary(CompositingReason::Resize);
     }
 
+    pub fn on_media_environment_changed(&mut self, media_environment: MediaEnvironment) {
+        if self.media_environment == media_environment {
+            return;
+        }
+        self.media_environment = media_environment;
+        self.send_window_size(WindowSizeType::Resize);
+    }
+
     pub fn on_mouse_window_event_class(&mut self, mouse_window_event: MouseWindowEvent) {
         if self.convert_mouse_to_touch {
             match mouse_window_event {
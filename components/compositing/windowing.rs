@@ -18,6 +18,7 @@ use std::fmt::{Debug, Error, Formatter};
 #[cfg(feature = "gl")]
 use std::rc::Rc;
 use std::time::Duration;
+use style_traits::media_queries::MediaEnvironment;
 use style_traits::DevicePixel;
 
 use webrender_api::units::DevicePoint;
@@ -56,6 +57,9 @@ pub enum WindowEvent {
     Refresh,
     /// Sent when the window is resized.
     Resize,
+    /// Sent when the preferences of the user or the capabilities of their
+    /// device that media queries depend on change.
+    MediaEnvironmentChanged(MediaEnvironment),
     /// Sent when a navigation request from script is allowed/refused.
     AllowNavigationResponse(PipelineId, bool),
     /// Sent when a new URL is to be loaded.
@@ -87,6 +91,8 @@ pub enum WindowEvent {
     Keyboard(KeyboardEvent),
     /// Sent when Ctr+R/Apple+R is called to reload the current page.
     Reload(TopLevelBrowsingContextId),
+    /// Sent when the current page should be printed to a PDF document.
+    PrintToPdf(TopLevelBrowsingContextId),
     /// Create a new top level browsing context
     NewBrowser(ServoUrl, TopLevelBrowsingContextId),
     /// Close a top level browsing context
@@ -113,6 +119,7 @@ impl Debug for WindowEvent {
             WindowEvent::Idle => write!(f, "Idle"),
             WindowEvent::Refresh => write!(f, "Refresh"),
             WindowEvent::Resize => write!(f, "Resize"),
+            WindowEvent::MediaEnvironmentChanged(..) => write!(f, "MediaEnvironmentChanged"),
             WindowEvent::Keyboard(..) => write!(f, "Keyboard"),
             WindowEvent::AllowNavigationResponse(..) => write!(f, "AllowNavigationResponse"),
             WindowEvent::LoadUrl(..) => write!(f, "LoadUrl"),
@@ -127,6 +134,7 @@ impl Debug for WindowEvent {
             WindowEvent::Navigation(..) => write!(f, "Navigation"),
             WindowEvent::Quit => write!(f, "Quit"),
             WindowEvent::Reload(..) => write!(f, "Reload"),
+            WindowEvent::PrintToPdf(..) => write!(f, "PrintToPdf"),
             WindowEvent::NewBrowser(..) => write!(f, "NewBrowser"),
             WindowEvent::SendError(..) => write!(f, "SendError"),
             WindowEvent::CloseBrowser(..) => write!(f, "CloseBrowser"),
@@ -62,6 +62,10 @@ pub struct Opts {
 
     pub output_file: Option<String>,
 
+    /// Where to write the document printed to PDF once it has loaded, if anywhere
+    /// (`--print-to-pdf`).
+    pub print_to_pdf: Option<String>,
+
     /// Replace unpaired surrogates in DOM strings with U+FFFD.
     /// See <https://github.com/servo/servo/issues/6564>
     pub replace_surrogates: bool,
@@ -527,6 +531,7 @@ pub fn default_opts() -> Opts {
         userscripts: None,
         user_stylesheets: Vec::new(),
         output_file: None,
+        print_to_pdf: None,
         replace_surrogates: false,
         gc_profile: false,
         load_webfonts_synchronously: false,
@@ -581,6 +586,12 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
     opts.optflag("c", "cpu", "CPU painting");
     opts.optflag("g", "gpu", "GPU painting");
     opts.optopt("o", "output", "Output file", "output.png");
+    opts.optopt(
+        "",
+        "print-to-pdf",
+        "Print the page to a PDF file once it has loaded",
+        "output.pdf",
+    );
     opts.optopt("s", "size", "Size of tiles", "512");
     opts.optflagopt(
         "p",
@@ -950,6 +961,7 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
         userscripts: opt_match.opt_default("userscripts", ""),
         user_stylesheets: user_stylesheets,
         output_file: opt_match.opt_str("o"),
+        print_to_pdf: opt_match.opt_str("print-to-pdf"),
         replace_surrogates: debug_options.replace_surrogates,
         gc_profile: debug_options.gc_profile,
         load_webfonts_synchronously: debug_options.load_webfonts_synchronously,
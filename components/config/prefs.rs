@@ -193,6 +193,9 @@ mod gen {
                 gamepad: {
                     enabled: bool,
                 },
+                intersection_observer: {
+                    enabled: bool,
+                },
                 microdata: {
                     testing: {
                         enabled: bool,
@@ -216,6 +219,9 @@ mod gen {
                         allowed_in_nonsecure_contexts: bool,
                     }
                 },
+                resize_observer: {
+                    enabled: bool,
+                },
                 serviceworker: {
                     enabled: bool,
                     timeout_seconds: i64,
@@ -440,6 +446,10 @@ mod gen {
                 columns: {
                     enabled: bool,
                 },
+                #[serde(rename = "layout.prefers-color-scheme")]
+                prefers_color_scheme: String,
+                #[serde(rename = "layout.prefers-reduced-motion")]
+                prefers_reduced_motion: bool,
                 #[serde(default = "default_layout_threads")]
                 threads: i64,
                 viewport: {
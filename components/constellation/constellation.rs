@@ -127,8 +127,8 @@ use log::{Level, LevelFilter, Log, Metadata, Record};
 use media::{GLPlayerThreads, WindowGLContext};
 use msg::constellation_msg::{BackgroundHangMonitorRegister, HangMonitorAlert, SamplerControlMsg};
 use msg::constellation_msg::{
-    BrowsingContextGroupId, BrowsingContextId, HistoryStateId, PipelineId,
-    TopLevelBrowsingContextId,
+    BroadcastChannelRouterId, BrowsingContextGroupId, BrowsingContextId, HistoryStateId,
+    PipelineId, TopLevelBrowsingContextId,
 };
 use msg::constellation_msg::{
     MessagePortId, MessagePortRouterId, PipelineNamespace, PipelineNamespaceId,
@@ -143,7 +143,8 @@ use profile_traits::time;
 use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent};
 use script_traits::{webdriver_msg, LogEntry, ScriptToConstellationChan, ServiceWorkerMsg};
 use script_traits::{
-    AnimationState, AnimationTickType, AuxiliaryBrowsingContextLoadInfo, CompositorEvent,
+    AnimationState, AnimationTickType, AuxiliaryBrowsingContextLoadInfo, BroadcastMsg,
+    CompositorEvent,
 };
 use script_traits::{
     ConstellationControlMsg, ConstellationMsg as FromCompositorMsg, DiscardBrowsingContext,
@@ -398,6 +399,13 @@ pub struct Constellation<Message, LTF, STF> {
     /// A map of router-id to ipc-sender, to route messages to ports.
     message_port_routers: HashMap<MessagePortRouterId, IpcSender<MessagePortMsg>>,
 
+    /// A map of broadcast routers to their IPC sender, and the origin they were created for.
+    broadcast_routers:
+        HashMap<BroadcastChannelRouterId, (IpcSender<BroadcastMsg>, ImmutableOrigin)>,
+
+    /// A map of origin to a map of channel-name to a list of relevant routers.
+    broadcast_channels: HashMap<ImmutableOrigin, HashMap<String, Vec<BroadcastChannelRouterId>>>,
+
     /// The set of all the pipelines in the browser.  (See the `pipeline` module
     /// for more details.)
     pipelines: HashMap<PipelineId, Pipeline>,
@@ -940,6 +948,8 @@ where
                     browsing_context_group_next_id: Default::default(),
                     message_ports: HashMap::new(),
                     message_port_routers: HashMap::new(),
+                    broadcast_routers: HashMap::new(),
+                    broadcast_channels: HashMap::new(),
                     pipelines: HashMap::new(),
                     browsing_contexts: HashMap::new(),
                     pending_changes: vec![],
@@ -1219,6 +1229,7 @@ where
             window_size: WindowSizeData {
                 initial_viewport: initial_window_size,
                 device_pixel_ratio: self.window_size.device_pixel_ratio,
+                media_environment: self.window_size.media_environment,
             },
             event_loop,
             load_data,
@@ -1660,6 +1671,9 @@ where
             FromCompositorMsg::Reload(top_level_browsing_context_id) => {
                 self.handle_reload_msg(top_level_browsing_context_id);
             },
+            FromCompositorMsg::PrintToPdf(top_level_browsing_context_id) => {
+                self.handle_print_to_pdf_msg(top_level_browsing_context_id);
+            },
             FromCompositorMsg::LogEntry(top_level_browsing_context_id, thread_name, entry) => {
                 self.handle_log_entry(top_level_browsing_context_id, thread_name, entry);
             },
@@ -1738,6 +1752,25 @@ where
             FromScriptMsg::EntanglePorts(port1, port2) => {
                 self.handle_entangle_messageports(port1, port2);
             },
+            FromScriptMsg::NewBroadcastChannelRouter(router_id, ipc_sender, origin) => {
+                self.handle_new_broadcast_channel_router(router_id, ipc_sender, origin);
+            },
+            FromScriptMsg::RemoveBroadcastChannelRouter(router_id, origin) => {
+                self.handle_remove_broadcast_channel_router(router_id, origin);
+            },
+            FromScriptMsg::NewBroadcastChannelNameInRouter(router_id, channel_name, origin) => {
+                self.handle_new_broadcast_channel_name_in_router(router_id, channel_name, origin);
+            },
+            FromScriptMsg::RemoveBroadcastChannelNameInRouter(router_id, channel_name, origin) => {
+                self.handle_remove_broadcast_channel_name_in_router(
+                    router_id,
+                    channel_name,
+                    origin,
+                );
+            },
+            FromScriptMsg::ScheduleBroadcast(router_id, message) => {
+                self.handle_schedule_broadcast(router_id, message);
+            },
             FromScriptMsg::ForwardToEmbedder(embedder_msg) => {
                 self.embedder_proxy
                     .send((Some(source_top_ctx_id), embedder_msg));
@@ -2336,6 +2369,137 @@ where
         }
     }
 
+    /// Is the given broadcast router known, and was it created for the given origin?
+    fn is_broadcast_router_for_origin(
+        &self,
+        router_id: &BroadcastChannelRouterId,
+        origin: &ImmutableOrigin,
+    ) -> bool {
+        match self.broadcast_routers.get(router_id) {
+            Some((_sender, router_origin)) => router_origin == origin,
+            None => false,
+        }
+    }
+
+    /// Broadcast a message to all routers managing a channel with the same name and origin,
+    /// excluding the router of the source of the broadcast.
+    /// <https://html.spec.whatwg.org/multipage/#dom-broadcastchannel-postmessage>
+    fn handle_schedule_broadcast(
+        &self,
+        router_id: BroadcastChannelRouterId,
+        message: BroadcastMsg,
+    ) {
+        if !self.is_broadcast_router_for_origin(&router_id, &message.origin) {
+            return warn!(
+                "Broadcast from {:?} does not match the origin of its router.",
+                router_id
+            );
+        }
+        let routers = match self
+            .broadcast_channels
+            .get(&message.origin)
+            .and_then(|channels| channels.get(&message.channel_name))
+        {
+            Some(routers) => routers,
+            None => return,
+        };
+        for router in routers.iter() {
+            // Channels in the source router are handled by the global itself.
+            if *router == router_id {
+                continue;
+            }
+            match self.broadcast_routers.get(router) {
+                Some((sender, _origin)) => {
+                    if sender.send(message.clone()).is_err() {
+                        warn!("Failed to broadcast message to router: {:?}", router);
+                    }
+                },
+                None => warn!("No sender for broadcast router: {:?}", router),
+            }
+        }
+    }
+
+    fn handle_new_broadcast_channel_router(
+        &mut self,
+        router_id: BroadcastChannelRouterId,
+        broadcast_ipc_sender: IpcSender<BroadcastMsg>,
+        origin: ImmutableOrigin,
+    ) {
+        if self
+            .broadcast_routers
+            .insert(router_id, (broadcast_ipc_sender, origin))
+            .is_some()
+        {
+            warn!("Multiple attempts to add BroadcastChannel router.");
+        }
+    }
+
+    fn handle_remove_broadcast_channel_router(
+        &mut self,
+        router_id: BroadcastChannelRouterId,
+        origin: ImmutableOrigin,
+    ) {
+        if !self.is_broadcast_router_for_origin(&router_id, &origin) {
+            return warn!(
+                "Attempt to remove unknown or mismatched broadcast router {:?}.",
+                router_id
+            );
+        }
+        self.broadcast_routers.remove(&router_id);
+        // Drop any channel-name the router might still be registered for.
+        if let Some(channels) = self.broadcast_channels.get_mut(&origin) {
+            for routers in channels.values_mut() {
+                routers.retain(|router| *router != router_id);
+            }
+            channels.retain(|_name, routers| !routers.is_empty());
+            if channels.is_empty() {
+                self.broadcast_channels.remove(&origin);
+            }
+        }
+    }
+
+    fn handle_new_broadcast_channel_name_in_router(
+        &mut self,
+        router_id: BroadcastChannelRouterId,
+        channel_name: String,
+        origin: ImmutableOrigin,
+    ) {
+        if !self.is_broadcast_router_for_origin(&router_id, &origin) {
+            return warn!(
+                "Attempt to add channel name to unknown or mismatched broadcast router {:?}.",
+                router_id
+            );
+        }
+        let routers = self
+            .broadcast_channels
+            .entry(origin)
+            .or_insert_with(HashMap::new)
+            .entry(channel_name)
+            .or_insert_with(Vec::new);
+        if !routers.contains(&router_id) {
+            routers.push(router_id);
+        }
+    }
+
+    fn handle_remove_broadcast_channel_name_in_router(
+        &mut self,
+        router_id: BroadcastChannelRouterId,
+        channel_name: String,
+        origin: ImmutableOrigin,
+    ) {
+        if let Entry::Occupied(mut channels) = self.broadcast_channels.entry(origin) {
+            if let Entry::Occupied(mut routers) = channels.get_mut().entry(channel_name) {
+                routers.get_mut().retain(|router| *router != router_id);
+                if routers.get().is_empty() {
+                    routers.remove();
+                }
+            }
+            if channels.get().is_empty() {
+                channels.remove();
+            }
+        }
+    }
+
     fn handle_register_serviceworker(&self, scope_things: ScopeThings, scope: ServoUrl) {
         if let Some(ref mgr) = self.swmanager_chan {
             let _ = mgr.send(ServiceWorkerMsg::RegisterServiceWorker(scope_things, scope));
@@ -2811,6 +2975,7 @@ where
             let window_size = WindowSizeData {
                 initial_viewport: data.size,
                 device_pixel_ratio: self.window_size.device_pixel_ratio,
+                media_environment: self.window_size.media_environment,
             };
 
             self.resize_browsing_context(window_size, type_, data.id);
@@ -3870,6 +4035,30 @@ where
         }
     }
 
+    fn handle_print_to_pdf_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return warn!(
+                    "Browsing context {} got print event after closure.",
+                    browsing_context_id
+                );
+            },
+        };
+        let msg = ConstellationControlMsg::PrintToPdf(pipeline_id);
+        let result = match self.pipelines.get(&pipeline_id) {
+            None => return warn!("Pipeline {} got print event after closure.", pipeline_id),
+            Some(pipeline) => pipeline.event_loop.send(msg),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn handle_post_message_msg(
         &mut self,
         browsing_context_id: BrowsingContextId,
@@ -4647,6 +4836,25 @@ where
             self.resize_browsing_context(new_size, size_type, browsing_context_id);
         }
 
+        // Nested browsing contexts are otherwise only resized by their
+        // parent's layout, which doesn't know about the media environment.
+        if new_size.media_environment != self.window_size.media_environment {
+            let nested_browsing_contexts: Vec<_> = self
+                .browsing_contexts
+                .values()
+                .filter(|browsing_context| browsing_context.parent_pipeline_id.is_some())
+                .map(|browsing_context| (browsing_context.id, browsing_context.size))
+                .collect();
+            for (browsing_context_id, size) in nested_browsing_contexts {
+                let window_size = WindowSizeData {
+                    initial_viewport: size,
+                    device_pixel_ratio: new_size.device_pixel_ratio,
+                    media_environment: new_size.media_environment,
+                };
+                self.resize_browsing_context(window_size, size_type, browsing_context_id);
+            }
+        }
+
         if let Some(resize_channel) = self.webdriver.resize_channel.take() {
             let _ = resize_channel.send(new_size);
         }
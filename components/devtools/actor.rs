@@ -175,6 +175,13 @@ impl ActorRegistry {
                 }
             },
         }
+        self.process_later_actors();
+        Ok(())
+    }
+
+    /// Register the actors passed to `register_later`, and drop those passed to
+    /// `drop_actor_later`.
+    pub fn process_later_actors(&mut self) {
         let new_actors = replace(&mut *self.new_actors.borrow_mut(), vec![]);
         for actor in new_actors.into_iter() {
             self.actors.insert(actor.name().to_owned(), actor);
@@ -184,7 +191,6 @@ impl ActorRegistry {
         for name in old_actors {
             self.drop_actor(name);
         }
-        Ok(())
     }
 
     pub fn drop_actor(&mut self, name: String) {
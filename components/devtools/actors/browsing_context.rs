@@ -78,6 +78,7 @@ pub struct BrowsingContextActorMsg {
     profilerActor: String,
     performanceActor: String,
     styleSheetsActor: String,
+    storageActor: String,
 }
 
 pub struct BrowsingContextActor {
@@ -92,6 +93,7 @@ pub struct BrowsingContextActor {
     pub performance: String,
     pub styleSheets: String,
     pub thread: String,
+    pub storage: String,
 }
 
 impl Actor for BrowsingContextActor {
@@ -200,6 +202,7 @@ impl BrowsingContextActor {
             profilerActor: self.profiler.clone(),
             performanceActor: self.performance.clone(),
             styleSheetsActor: self.styleSheets.clone(),
+            storageActor: self.storage.clone(),
         }
     }
 }
@@ -8,18 +8,16 @@
 //! inspection, JS evaluation, autocompletion) in Servo.
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
-use crate::actors::object::ObjectActor;
+use crate::actors::object::grip;
 use crate::protocol::JsonPacketStream;
 use crate::{ConsoleAPICall, ConsoleMessage, ConsoleMsg, PageErrorMsg};
 use devtools_traits::CachedConsoleMessage;
-use devtools_traits::EvaluateJSReply::{ActorValue, BooleanValue, StringValue};
-use devtools_traits::EvaluateJSReply::{NullValue, NumberValue, VoidValue};
 use devtools_traits::{
     CachedConsoleMessageTypes, ConsoleAPI, DevtoolScriptControlMsg, LogLevel, PageError,
 };
 use ipc_channel::ipc::{self, IpcSender};
 use msg::constellation_msg::PipelineId;
-use serde_json::{self, Map, Number, Value};
+use serde_json::{self, Map, Value};
 use std::cell::RefCell;
 use std::net::TcpStream;
 use time::precise_time_ns;
@@ -130,55 +128,7 @@ impl ConsoleActor {
             ))
             .unwrap();
 
-        //TODO: extract conversion into protocol module or some other useful place
-        let result = match port.recv().map_err(|_| ())? {
-            VoidValue => {
-                let mut m = Map::new();
-                m.insert("type".to_owned(), Value::String("undefined".to_owned()));
-                Value::Object(m)
-            },
-            NullValue => {
-                let mut m = Map::new();
-                m.insert("type".to_owned(), Value::String("null".to_owned()));
-                Value::Object(m)
-            },
-            BooleanValue(val) => Value::Bool(val),
-            NumberValue(val) => {
-                if val.is_nan() {
-                    let mut m = Map::new();
-                    m.insert("type".to_owned(), Value::String("NaN".to_owned()));
-                    Value::Object(m)
-                } else if val.is_infinite() {
-                    let mut m = Map::new();
-                    if val < 0. {
-                        m.insert("type".to_owned(), Value::String("-Infinity".to_owned()));
-                    } else {
-                        m.insert("type".to_owned(), Value::String("Infinity".to_owned()));
-                    }
-                    Value::Object(m)
-                } else if val == 0. && val.is_sign_negative() {
-                    let mut m = Map::new();
-                    m.insert("type".to_owned(), Value::String("-0".to_owned()));
-                    Value::Object(m)
-                } else {
-                    Value::Number(Number::from_f64(val).unwrap())
-                }
-            },
-            StringValue(s) => Value::String(s),
-            ActorValue { class, uuid } => {
-                //TODO: make initial ActorValue message include these properties?
-                let mut m = Map::new();
-                let actor = ObjectActor::new(registry, uuid);
-
-                m.insert("type".to_owned(), Value::String("object".to_owned()));
-                m.insert("class".to_owned(), Value::String(class));
-                m.insert("actor".to_owned(), Value::String(actor));
-                m.insert("extensible".to_owned(), Value::Bool(true));
-                m.insert("frozen".to_owned(), Value::Bool(false));
-                m.insert("sealed".to_owned(), Value::Bool(false));
-                Value::Object(m)
-            },
-        };
+        let result = grip(registry, port.recv().map_err(|_| ())?, None);
 
         //TODO: catch and return exception values from JS evaluation
         let reply = EvaluateJSReply {
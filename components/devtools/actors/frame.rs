@@ -0,0 +1,163 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/frame.js).
+//! Represents a stack frame of a script thread paused by the debugger, and lets the
+//! client inspect the variables in scope.
+
+use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::object::{grip, Debuggee};
+use crate::protocol::JsonPacketStream;
+use devtools_traits::{DevtoolScriptControlMsg, EnvironmentInfo};
+use ipc_channel::ipc;
+use serde_json::{Map, Value};
+use std::net::TcpStream;
+
+#[derive(Serialize)]
+pub struct WhereMsg {
+    pub actor: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Serialize)]
+pub struct FrameForm {
+    pub actor: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub depth: u32,
+    pub displayName: Option<String>,
+    pub this: Value,
+    #[serde(rename = "where")]
+    pub where_: WhereMsg,
+    pub arguments: Vec<Value>,
+}
+
+#[derive(Serialize)]
+struct FunctionMsg {
+    displayName: Option<String>,
+}
+
+#[derive(Serialize)]
+struct BindingsMsg {
+    arguments: Vec<Value>,
+    variables: Map<String, Value>,
+}
+
+#[derive(Serialize)]
+struct EnvironmentForm {
+    actor: String,
+    #[serde(rename = "type")]
+    type_: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function: Option<FunctionMsg>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    object: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bindings: Option<BindingsMsg>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<Box<EnvironmentForm>>,
+}
+
+#[derive(Serialize)]
+struct GetEnvironmentReply {
+    from: String,
+    #[serde(flatten)]
+    environment: EnvironmentForm,
+}
+
+pub struct FrameActor {
+    pub name: String,
+    pub depth: u32,
+    pub debuggee: Debuggee,
+}
+
+impl FrameActor {
+    fn encode_environment(
+        &self,
+        registry: &ActorRegistry,
+        environment: EnvironmentInfo,
+    ) -> EnvironmentForm {
+        let function = environment.function_name.map(|name| FunctionMsg {
+            displayName: Some(name),
+        });
+        // The protocol calls declarative environments of function calls "function"
+        // environments, and all other declarative environments "block" environments.
+        let type_ = match &*environment.kind {
+            "declarative" if function.is_some() => "function".to_owned(),
+            "declarative" => "block".to_owned(),
+            kind => kind.to_owned(),
+        };
+        let bindings = if environment.object.is_none() {
+            let mut variables = Map::new();
+            for (name, value) in environment.bindings {
+                let mut binding = Map::new();
+                binding.insert(
+                    "value".to_owned(),
+                    grip(registry, value, Some(&self.debuggee)),
+                );
+                variables.insert(name, Value::Object(binding));
+            }
+            Some(BindingsMsg {
+                arguments: vec![],
+                variables: variables,
+            })
+        } else {
+            None
+        };
+        EnvironmentForm {
+            actor: registry.new_name("environment"),
+            type_: type_,
+            function: function,
+            object: environment
+                .object
+                .map(|object| grip(registry, object, Some(&self.debuggee))),
+            bindings: bindings,
+            parent: environment
+                .parent
+                .map(|parent| Box::new(self.encode_environment(registry, *parent))),
+        }
+    }
+}
+
+impl Actor for FrameActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(
+        &self,
+        registry: &ActorRegistry,
+        msg_type: &str,
+        _msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "getEnvironment" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.debuggee
+                    .script_chan
+                    .send(DevtoolScriptControlMsg::GetFrameEnvironment(
+                        self.debuggee.pipeline,
+                        self.depth,
+                        tx,
+                    ))
+                    .unwrap();
+                let environment = match rx.recv().map_err(|_| ())? {
+                    Some(environment) => environment,
+                    None => return Ok(ActorMessageStatus::Ignored),
+                };
+                let msg = GetEnvironmentReply {
+                    from: self.name(),
+                    environment: self.encode_environment(registry, environment),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
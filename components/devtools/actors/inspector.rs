@@ -6,10 +6,13 @@
 //! (http://mxr.mozilla.org/mozilla-central/source/toolkit/devtools/server/actors/inspector.js).
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::stylesheets::{StyleSheetActor, StyleSheetForm};
 use crate::protocol::JsonPacketStream;
+use devtools_traits::DevtoolScriptControlMsg::{GetAppliedRules, ModifyRule};
 use devtools_traits::DevtoolScriptControlMsg::{GetChildren, GetDocumentElement, GetRootNode};
 use devtools_traits::DevtoolScriptControlMsg::{GetLayout, ModifyAttribute};
-use devtools_traits::{ComputedNodeLayout, DevtoolScriptControlMsg, NodeInfo};
+use devtools_traits::{AppliedRuleInfo, ComputedNodeLayout, DevtoolScriptControlMsg};
+use devtools_traits::{NodeInfo, RuleModification};
 use ipc_channel::ipc::{self, IpcSender};
 use msg::constellation_msg::PipelineId;
 use serde_json::{self, Map, Value};
@@ -372,7 +375,7 @@ struct PageStyleActor {
 struct GetAppliedReply {
     entries: Vec<AppliedEntry>,
     rules: Vec<AppliedRule>,
-    sheets: Vec<AppliedSheet>,
+    sheets: Vec<StyleSheetForm>,
     from: String,
 }
 
@@ -394,26 +397,25 @@ struct AppliedEntry {
 struct AppliedRule {
     actor: String,
     #[serde(rename = "type")]
-    type_: String,
-    href: String,
+    type_: u32,
+    href: Option<String>,
     cssText: String,
     line: u32,
     column: u32,
-    parentStyleSheet: String,
+    parentStyleSheet: Option<String>,
+    selectors: Vec<String>,
 }
 
 #[derive(Serialize)]
-struct AppliedSheet {
-    actor: String,
-    href: String,
-    nodeHref: String,
-    disabled: bool,
-    title: String,
-    system: bool,
-    styleSheetIndex: isize,
-    ruleCount: usize,
+struct ModifyPropertiesReply {
+    from: String,
+    rule: AppliedRule,
 }
 
+// https://searchfox.org/mozilla-central/source/devtools/shared/css/constants.js
+const CSS_STYLE_RULE: u32 = 1;
+const ELEMENT_STYLE: u32 = 100;
+
 #[derive(Serialize)]
 struct GetLayoutReply {
     from: String,
@@ -473,11 +475,54 @@ impl Actor for PageStyleActor {
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
             "getApplied" => {
-                //TODO: query script for relevant applied styles to node (msg.node)
+                let target = msg.get("node").and_then(Value::as_str).ok_or(())?;
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(GetAppliedRules(
+                        self.pipeline,
+                        registry.actor_to_script(target.to_owned()),
+                        tx,
+                    ))
+                    .unwrap();
+                let applied = rx.recv().map_err(|_| ())?.ok_or(())?;
+
+                let mut entries = vec![];
+                let mut rules = vec![];
+                let mut sheets: Vec<StyleSheetForm> = vec![];
+                for info in applied {
+                    let sheet = info.stylesheet.clone().map(|sheet| {
+                        StyleSheetActor::encodable(
+                            registry,
+                            self.pipeline,
+                            self.script_chan.clone(),
+                            sheet,
+                        )
+                    });
+                    let rule = StyleRuleActor::encodable(
+                        registry,
+                        self.pipeline,
+                        self.script_chan.clone(),
+                        info,
+                        sheet.as_ref().map(StyleSheetForm::actor),
+                    );
+                    entries.push(AppliedEntry {
+                        rule: rule.actor.clone(),
+                        pseudoElement: Value::Null,
+                        isSystem: false,
+                        matchedSelectors: rule.selectors.clone(),
+                    });
+                    rules.push(rule);
+                    if let Some(sheet) = sheet {
+                        if !sheets.iter().any(|known| known.actor() == sheet.actor()) {
+                            sheets.push(sheet);
+                        }
+                    }
+                }
+
                 let msg = GetAppliedReply {
-                    entries: vec![],
-                    rules: vec![],
-                    sheets: vec![],
+                    entries: entries,
+                    rules: rules,
+                    sheets: sheets,
                     from: self.name(),
                 };
                 stream.write_json_packet(&msg);
@@ -584,6 +629,127 @@ impl Actor for PageStyleActor {
     }
 }
 
+/// A style rule applying to a node, whose declarations can be edited from the rule view.
+struct StyleRuleActor {
+    name: String,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pipeline: PipelineId,
+    info: RefCell<AppliedRuleInfo>,
+    parent_sheet: RefCell<Option<String>>,
+}
+
+impl Actor for StyleRuleActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        msg_type: &str,
+        msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "modifyProperties" => {
+                let modifications = msg
+                    .get("modifications")
+                    .and_then(Value::as_array)
+                    .ok_or(())?
+                    .iter()
+                    .filter_map(|modification| {
+                        let name = modification.get("name")?.as_str()?.to_owned();
+                        let value = match modification.get("type")?.as_str()? {
+                            "set" => Some(modification.get("value")?.as_str()?.to_owned()),
+                            "remove" => None,
+                            _ => return None,
+                        };
+                        let priority = modification
+                            .get("priority")
+                            .and_then(Value::as_str)
+                            .unwrap_or("")
+                            .to_owned();
+                        Some(RuleModification {
+                            name: name,
+                            value: value,
+                            priority: priority,
+                        })
+                    })
+                    .collect();
+
+                let id = self.info.borrow().id.clone();
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(ModifyRule(self.pipeline, id, modifications, tx))
+                    .unwrap();
+                let css_text = rx.recv().map_err(|_| ())?.ok_or(())?;
+                self.info.borrow_mut().css_text = css_text;
+
+                let msg = ModifyPropertiesReply {
+                    from: self.name(),
+                    rule: self.form(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+impl StyleRuleActor {
+    /// Describe a rule, creating an actor for it the first time it is seen.
+    fn encodable(
+        registry: &ActorRegistry,
+        pipeline: PipelineId,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+        info: AppliedRuleInfo,
+        parent_sheet: Option<String>,
+    ) -> AppliedRule {
+        let key = format!("rule:{}:{}", pipeline, info.id);
+        if registry.script_actor_registered(key.clone()) {
+            let actor = registry.find::<StyleRuleActor>(&registry.script_to_actor(key));
+            *actor.info.borrow_mut() = info;
+            *actor.parent_sheet.borrow_mut() = parent_sheet;
+            return actor.form();
+        }
+
+        let actor = StyleRuleActor {
+            name: registry.new_name("domstylerule"),
+            script_chan: script_chan,
+            pipeline: pipeline,
+            info: RefCell::new(info),
+            parent_sheet: RefCell::new(parent_sheet),
+        };
+        let form = actor.form();
+        registry.register_script_actor(key, actor.name());
+        registry.register_later(Box::new(actor));
+        form
+    }
+
+    fn form(&self) -> AppliedRule {
+        let info = self.info.borrow();
+        AppliedRule {
+            actor: self.name(),
+            type_: if info.selector.is_some() {
+                CSS_STYLE_RULE
+            } else {
+                ELEMENT_STYLE
+            },
+            href: info
+                .stylesheet
+                .as_ref()
+                .and_then(|sheet| sheet.href.clone()),
+            cssText: info.css_text.clone(),
+            line: info.line,
+            column: info.column,
+            parentStyleSheet: self.parent_sheet.borrow().clone(),
+            selectors: info.selector.iter().cloned().collect(),
+        }
+    }
+}
+
 impl Actor for InspectorActor {
     fn name(&self) -> String {
         self.name.clone()
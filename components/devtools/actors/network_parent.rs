@@ -0,0 +1,199 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/network-monitor/network-parent.js).
+//! Emulates network conditions for every fetch made by the browser.
+
+use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::protocol::JsonPacketStream;
+use devtools_traits::{NetworkControlMsg, NetworkThrottle};
+use ipc_channel::ipc::IpcSender;
+use serde_json::{Map, Value};
+use servo_url::ServoUrl;
+use std::cell::RefCell;
+use std::fs;
+use std::net::TcpStream;
+
+#[derive(Serialize)]
+struct EmptyReply {
+    from: String,
+}
+
+#[derive(Serialize)]
+struct ThrottleMsg {
+    latency: u64,
+    downloadThroughput: u64,
+    uploadThroughput: u64,
+}
+
+#[derive(Serialize)]
+struct GetNetworkThrottlingReply {
+    from: String,
+    state: Option<ThrottleMsg>,
+}
+
+#[derive(Serialize)]
+struct GetBlockedUrlsReply {
+    from: String,
+    urls: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct GetOverridesReply {
+    from: String,
+    urls: Vec<String>,
+}
+
+pub struct NetworkParentActor {
+    pub name: String,
+    /// The channel to the resource thread, once it has started.
+    pub control_chan: RefCell<Option<IpcSender<NetworkControlMsg>>>,
+    throttle: RefCell<Option<NetworkThrottle>>,
+    blocked_urls: RefCell<Vec<String>>,
+    overrides: RefCell<Vec<ServoUrl>>,
+}
+
+impl Actor for NetworkParentActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        msg_type: &str,
+        msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "setNetworkThrottling" => {
+                let options = msg.get("options").and_then(Value::as_object).ok_or(())?;
+                let value = |name: &str| options.get(name).and_then(Value::as_u64).unwrap_or(0);
+                let throttle = NetworkThrottle {
+                    latency: value("latency"),
+                    download_throughput: value("downloadThroughput"),
+                    upload_throughput: value("uploadThroughput"),
+                };
+                *self.throttle.borrow_mut() = Some(throttle);
+                self.send(NetworkControlMsg::SetThrottle(Some(throttle)));
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "getNetworkThrottling" => {
+                let msg = GetNetworkThrottlingReply {
+                    from: self.name(),
+                    state: self.throttle.borrow().map(|throttle| ThrottleMsg {
+                        latency: throttle.latency,
+                        downloadThroughput: throttle.download_throughput,
+                        uploadThroughput: throttle.upload_throughput,
+                    }),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "clearNetworkThrottling" => {
+                *self.throttle.borrow_mut() = None;
+                self.send(NetworkControlMsg::SetThrottle(None));
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "setBlockedUrls" => {
+                let urls: Vec<String> = msg
+                    .get("urls")
+                    .and_then(Value::as_array)
+                    .ok_or(())?
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_owned)
+                    .collect();
+                *self.blocked_urls.borrow_mut() = urls.clone();
+                self.send(NetworkControlMsg::SetBlockedUrls(urls));
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "getBlockedUrls" => {
+                let msg = GetBlockedUrlsReply {
+                    from: self.name(),
+                    urls: self.blocked_urls.borrow().clone(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            // Serve the contents of a local file instead of the resource at the given URL.
+            "override" => {
+                let url = msg.get("url").and_then(Value::as_str).ok_or(())?;
+                let url = ServoUrl::parse(url).map_err(|_| ())?;
+                let path = msg.get("path").and_then(Value::as_str).ok_or(())?;
+                let body = match fs::read(path) {
+                    Ok(body) => body,
+                    Err(error) => {
+                        warn!("Couldn't read response override {}: {}", path, error);
+                        return Err(());
+                    },
+                };
+                {
+                    let mut overrides = self.overrides.borrow_mut();
+                    if !overrides.contains(&url) {
+                        overrides.push(url.clone());
+                    }
+                }
+                self.send(NetworkControlMsg::SetResponseOverride(url, Some(body)));
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "removeOverride" => {
+                let url = msg.get("url").and_then(Value::as_str).ok_or(())?;
+                let url = ServoUrl::parse(url).map_err(|_| ())?;
+                self.overrides.borrow_mut().retain(|known| *known != url);
+                self.send(NetworkControlMsg::SetResponseOverride(url, None));
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "getOverrides" => {
+                let msg = GetOverridesReply {
+                    from: self.name(),
+                    urls: self
+                        .overrides
+                        .borrow()
+                        .iter()
+                        .map(|url| url.to_string())
+                        .collect(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+impl NetworkParentActor {
+    pub fn new(name: String) -> NetworkParentActor {
+        NetworkParentActor {
+            name: name,
+            control_chan: RefCell::new(None),
+            throttle: RefCell::new(None),
+            blocked_urls: RefCell::new(vec![]),
+            overrides: RefCell::new(vec![]),
+        }
+    }
+
+    fn send(&self, msg: NetworkControlMsg) {
+        match *self.control_chan.borrow() {
+            Some(ref chan) => {
+                let _ = chan.send(msg);
+            },
+            None => warn!("Network emulation requested before the resource thread started"),
+        }
+    }
+}
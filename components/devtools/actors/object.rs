@@ -3,12 +3,43 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
-use serde_json::{Map, Value};
+use crate::protocol::JsonPacketStream;
+use devtools_traits::EvaluateJSReply::{ActorValue, BooleanValue, StringValue};
+use devtools_traits::EvaluateJSReply::{NullValue, NumberValue, VoidValue};
+use devtools_traits::{DevtoolScriptControlMsg, EvaluateJSReply};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
+use serde_json::{Map, Number, Value};
 use std::net::TcpStream;
 
+/// A script thread paused by the debugger, which can be asked about the objects it exposes
+/// until it resumes.
+#[derive(Clone)]
+pub struct Debuggee {
+    pub pipeline: PipelineId,
+    pub script_chan: IpcSender<DevtoolScriptControlMsg>,
+}
+
+#[derive(Serialize)]
+struct PropertyDescriptorMsg {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<Value>,
+    enumerable: bool,
+    writable: bool,
+    configurable: bool,
+}
+
+#[derive(Serialize)]
+struct PrototypeAndPropertiesReply {
+    from: String,
+    prototype: Value,
+    ownProperties: Map<String, Value>,
+}
+
 pub struct ObjectActor {
     pub name: String,
     pub uuid: String,
+    pub debuggee: Option<Debuggee>,
 }
 
 impl Actor for ObjectActor {
@@ -17,12 +48,61 @@ impl Actor for ObjectActor {
     }
     fn handle_message(
         &self,
-        _: &ActorRegistry,
-        _: &str,
-        _: &Map<String, Value>,
-        _: &mut TcpStream,
+        registry: &ActorRegistry,
+        msg_type: &str,
+        _msg: &Map<String, Value>,
+        stream: &mut TcpStream,
     ) -> Result<ActorMessageStatus, ()> {
-        Ok(ActorMessageStatus::Ignored)
+        Ok(match msg_type {
+            "prototypeAndProperties" => {
+                let debuggee = match self.debuggee {
+                    Some(ref debuggee) => debuggee,
+                    None => return Ok(ActorMessageStatus::Ignored),
+                };
+                let (tx, rx) = ipc::channel().unwrap();
+                debuggee
+                    .script_chan
+                    .send(DevtoolScriptControlMsg::GetObjectProperties(
+                        debuggee.pipeline,
+                        self.uuid.clone(),
+                        tx,
+                    ))
+                    .unwrap();
+                let (prototype, properties) = match rx.recv().map_err(|_| ())? {
+                    Some(object) => (
+                        grip(registry, object.prototype, Some(debuggee)),
+                        object.properties,
+                    ),
+                    None => (grip(registry, NullValue, None), vec![]),
+                };
+
+                let mut ownProperties = Map::new();
+                for property in properties {
+                    let descriptor = PropertyDescriptorMsg {
+                        value: property
+                            .value
+                            .map(|value| grip(registry, value, Some(debuggee))),
+                        enumerable: property.enumerable,
+                        writable: property.writable,
+                        configurable: property.configurable,
+                    };
+                    ownProperties.insert(
+                        property.name,
+                        serde_json::to_value(descriptor).map_err(|_| ())?,
+                    );
+                }
+
+                let msg = PrototypeAndPropertiesReply {
+                    from: self.name(),
+                    prototype: prototype,
+                    ownProperties: ownProperties,
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
     }
 }
 
@@ -33,6 +113,7 @@ impl ObjectActor {
             let actor = ObjectActor {
                 name: name.clone(),
                 uuid: uuid.clone(),
+                debuggee: None,
             };
 
             registry.register_script_actor(uuid, name.clone());
@@ -43,4 +124,77 @@ impl ObjectActor {
             registry.script_to_actor(uuid)
         }
     }
+
+    /// Create an actor for an object of a paused script thread. The ids of such objects
+    /// are only meaningful for the current pause, so they get a new actor every time.
+    pub fn new_for_debuggee(registry: &ActorRegistry, id: String, debuggee: &Debuggee) -> String {
+        let name = registry.new_name("object");
+        let actor = ObjectActor {
+            name: name.clone(),
+            uuid: id,
+            debuggee: Some(debuggee.clone()),
+        };
+        registry.register_later(Box::new(actor));
+        name
+    }
+}
+
+/// Convert a value received from script into its protocol representation, creating an
+/// object actor for object values.
+pub fn grip(
+    registry: &ActorRegistry,
+    value: EvaluateJSReply,
+    debuggee: Option<&Debuggee>,
+) -> Value {
+    match value {
+        VoidValue => {
+            let mut m = Map::new();
+            m.insert("type".to_owned(), Value::String("undefined".to_owned()));
+            Value::Object(m)
+        },
+        NullValue => {
+            let mut m = Map::new();
+            m.insert("type".to_owned(), Value::String("null".to_owned()));
+            Value::Object(m)
+        },
+        BooleanValue(val) => Value::Bool(val),
+        NumberValue(val) => {
+            if val.is_nan() {
+                let mut m = Map::new();
+                m.insert("type".to_owned(), Value::String("NaN".to_owned()));
+                Value::Object(m)
+            } else if val.is_infinite() {
+                let mut m = Map::new();
+                if val < 0. {
+                    m.insert("type".to_owned(), Value::String("-Infinity".to_owned()));
+                } else {
+                    m.insert("type".to_owned(), Value::String("Infinity".to_owned()));
+                }
+                Value::Object(m)
+            } else if val == 0. && val.is_sign_negative() {
+                let mut m = Map::new();
+                m.insert("type".to_owned(), Value::String("-0".to_owned()));
+                Value::Object(m)
+            } else {
+                Value::Number(Number::from_f64(val).unwrap())
+            }
+        },
+        StringValue(s) => Value::String(s),
+        ActorValue { class, uuid } => {
+            //TODO: make initial ActorValue message include these properties?
+            let mut m = Map::new();
+            let actor = match debuggee {
+                Some(debuggee) => ObjectActor::new_for_debuggee(registry, uuid, debuggee),
+                None => ObjectActor::new(registry, uuid),
+            };
+
+            m.insert("type".to_owned(), Value::String("object".to_owned()));
+            m.insert("class".to_owned(), Value::String(class));
+            m.insert("actor".to_owned(), Value::String(actor));
+            m.insert("extensible".to_owned(), Value::Bool(true));
+            m.insert("frozen".to_owned(), Value::Bool(false));
+            m.insert("sealed".to_owned(), Value::Bool(false));
+            Value::Object(m)
+        },
+    }
 }
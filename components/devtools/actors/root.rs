@@ -37,6 +37,7 @@ struct GetRootReply {
     selected: u32,
     performanceActor: String,
     deviceActor: String,
+    networkParentActor: String,
 }
 
 #[derive(Serialize)]
@@ -69,6 +70,7 @@ pub struct RootActor {
     pub tabs: Vec<String>,
     pub performance: String,
     pub device: String,
+    pub networkParent: String,
 }
 
 impl Actor for RootActor {
@@ -99,6 +101,7 @@ impl Actor for RootActor {
                     selected: 0,
                     performanceActor: self.performance.clone(),
                     deviceActor: self.device.clone(),
+                    networkParentActor: self.networkParent.clone(),
                 };
                 stream.write_json_packet(&actor);
                 ActorMessageStatus::Processed
@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/source.js).
+//! Represents a single script source known to the debugger of a global.
+
+use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::protocol::JsonPacketStream;
+use devtools_traits::{DevtoolScriptControlMsg, SourceInfo};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
+use serde_json::{Map, Value};
+use std::net::TcpStream;
+
+#[derive(Serialize)]
+pub struct SourceForm {
+    pub actor: String,
+    pub url: String,
+    pub isBlackBoxed: bool,
+    pub introductionType: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SourceReply {
+    from: String,
+    contentType: String,
+    source: String,
+}
+
+pub struct SourceActor {
+    pub name: String,
+    pub pipeline: PipelineId,
+    pub script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pub source: SourceInfo,
+}
+
+impl Actor for SourceActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        msg_type: &str,
+        _msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "source" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::GetSourceText(
+                        self.pipeline,
+                        self.source.id.clone(),
+                        tx,
+                    ))
+                    .unwrap();
+                let msg = SourceReply {
+                    from: self.name(),
+                    contentType: "text/javascript".to_owned(),
+                    source: rx.recv().map_err(|_| ())?.unwrap_or_default(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
@@ -0,0 +1,515 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/storage.js).
+//! Lists and edits the cookies, local storage and session storage of the origin of a
+//! browsing context.
+
+use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::protocol::JsonPacketStream;
+use devtools_traits::{CookieAttributes, CookieScope, DevtoolScriptControlMsg, StorageArea};
+use devtools_traits::{StorageChange, StorageItem};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::net::TcpStream;
+
+/// The separator the client expects between the parts of the unique key of a cookie.
+const SEPARATOR_GUID: &'static str = "{9d414cc5-8319-0a04-0586-c0a6ae01670a}";
+
+/// The value of items added through the storage inspector.
+const DEFAULT_VALUE: &'static str = "value";
+
+fn store_type(area: StorageArea) -> &'static str {
+    match area {
+        StorageArea::Cookies => "cookies",
+        StorageArea::LocalStorage => "localStorage",
+        StorageArea::SessionStorage => "sessionStorage",
+    }
+}
+
+#[derive(Serialize)]
+struct StoreForm {
+    actor: String,
+    hosts: Map<String, Value>,
+    traits: Map<String, Value>,
+}
+
+#[derive(Serialize)]
+struct ListStoresReply {
+    from: String,
+    cookies: StoreForm,
+    localStorage: StoreForm,
+    sessionStorage: StoreForm,
+}
+
+#[derive(Serialize)]
+struct StoresUpdateMsg {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+    data: Map<String, Value>,
+}
+
+#[derive(Serialize)]
+struct StoreObjectsReply {
+    from: String,
+    offset: u64,
+    total: usize,
+    data: Vec<Value>,
+}
+
+#[derive(Serialize)]
+struct FieldMsg {
+    name: &'static str,
+    editable: bool,
+}
+
+#[derive(Serialize)]
+struct FieldsReply {
+    from: String,
+    value: Vec<FieldMsg>,
+}
+
+#[derive(Serialize)]
+struct EmptyReply {
+    from: String,
+}
+
+#[derive(Serialize)]
+struct CookieMsg {
+    uniqueKey: String,
+    name: String,
+    value: String,
+    host: String,
+    path: String,
+    expires: u64,
+    size: usize,
+    isHttpOnly: bool,
+    isSecure: bool,
+    sameSite: String,
+    hostOnly: bool,
+    creationTime: u64,
+    lastAccessed: u64,
+}
+
+#[derive(Serialize)]
+struct StorageEntryMsg {
+    name: String,
+    value: String,
+}
+
+pub struct StorageActor {
+    name: String,
+    pipeline: PipelineId,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    cookies: String,
+    local_storage: String,
+    session_storage: String,
+    streams: RefCell<Vec<TcpStream>>,
+}
+
+impl StorageActor {
+    pub fn new(
+        registry: &mut ActorRegistry,
+        pipeline: PipelineId,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+    ) -> StorageActor {
+        let mut store = |area| {
+            let store = StoreActor {
+                name: registry.new_name(store_type(area)),
+                area: area,
+                pipeline: pipeline,
+                script_chan: script_chan.clone(),
+            };
+            let name = store.name();
+            registry.register(Box::new(store));
+            name
+        };
+        let cookies = store(StorageArea::Cookies);
+        let local_storage = store(StorageArea::LocalStorage);
+        let session_storage = store(StorageArea::SessionStorage);
+        StorageActor {
+            name: registry.new_name("storage"),
+            pipeline: pipeline,
+            script_chan: script_chan,
+            cookies: cookies,
+            local_storage: local_storage,
+            session_storage: session_storage,
+            streams: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Notify the clients that script modified a storage area of the given origin.
+    pub fn handle_storage_change(&self, origin: String, area: StorageArea, change: StorageChange) {
+        let (type_, key, names) = match change {
+            StorageChange::Added(name) => ("storesUpdate", Some("added"), vec![name]),
+            StorageChange::Changed(name) => ("storesUpdate", Some("changed"), vec![name]),
+            StorageChange::Removed(name) => ("storesUpdate", Some("deleted"), vec![name]),
+            StorageChange::Cleared => ("storesCleared", None, vec![]),
+        };
+        let mut hosts = Map::new();
+        hosts.insert(
+            origin,
+            Value::Array(names.into_iter().map(Value::String).collect()),
+        );
+        let mut stores = Map::new();
+        stores.insert(store_type(area).to_owned(), Value::Object(hosts));
+        let data = match key {
+            Some(key) => {
+                let mut data = Map::new();
+                data.insert(key.to_owned(), Value::Object(stores));
+                data
+            },
+            None => stores,
+        };
+        let msg = StoresUpdateMsg {
+            from: self.name(),
+            type_: type_.to_owned(),
+            data: data,
+        };
+        for stream in &mut *self.streams.borrow_mut() {
+            stream.write_json_packet(&msg);
+        }
+    }
+
+    fn store_form(&self, actor: &str, origin: &Option<String>) -> StoreForm {
+        let mut hosts = Map::new();
+        if let Some(ref origin) = *origin {
+            hosts.insert(origin.clone(), Value::Array(vec![]));
+        }
+        StoreForm {
+            actor: actor.to_owned(),
+            hosts: hosts,
+            traits: Map::new(),
+        }
+    }
+}
+
+impl Actor for StorageActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        msg_type: &str,
+        _msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "listStores" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::GetStorageOrigin(self.pipeline, tx))
+                    .unwrap();
+                let origin = rx.recv().map_err(|_| ())?;
+                self.streams.borrow_mut().push(stream.try_clone().unwrap());
+                let msg = ListStoresReply {
+                    from: self.name(),
+                    cookies: self.store_form(&self.cookies, &origin),
+                    localStorage: self.store_form(&self.local_storage, &origin),
+                    sessionStorage: self.store_form(&self.session_storage, &origin),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+/// The items of a single storage area.
+pub struct StoreActor {
+    name: String,
+    area: StorageArea,
+    pipeline: PipelineId,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+}
+
+impl StoreActor {
+    fn items(&self) -> Result<Vec<StorageItem>, ()> {
+        let (tx, rx) = ipc::channel().unwrap();
+        self.script_chan
+            .send(DevtoolScriptControlMsg::GetStorageItems(
+                self.pipeline,
+                self.area,
+                tx,
+            ))
+            .unwrap();
+        rx.recv().map_err(|_| ())
+    }
+
+    fn set_item(&self, item: StorageItem) {
+        self.script_chan
+            .send(DevtoolScriptControlMsg::SetStorageItem(
+                self.pipeline,
+                self.area,
+                item,
+            ))
+            .unwrap();
+    }
+
+    /// Remove the item with the given key, which is the unique key of the row for cookies.
+    fn remove_item(&self, key: &str) {
+        let mut parts = key.split(SEPARATOR_GUID);
+        let name = parts.next().unwrap_or(key).to_owned();
+        let scope = match self.area {
+            StorageArea::Cookies => Some(CookieScope {
+                domain: parts.next().unwrap_or("").to_owned(),
+                path: parts.next().unwrap_or("/").to_owned(),
+            }),
+            StorageArea::LocalStorage | StorageArea::SessionStorage => None,
+        };
+        self.script_chan
+            .send(DevtoolScriptControlMsg::RemoveStorageItem(
+                self.pipeline,
+                self.area,
+                name,
+                scope,
+            ))
+            .unwrap();
+    }
+
+    fn encode_item(&self, item: StorageItem) -> Value {
+        let value = match item.cookie {
+            Some(cookie) => serde_json::to_value(CookieMsg {
+                uniqueKey: cookie_key(&item.name, &cookie.domain, &cookie.path),
+                size: item.name.len() + item.value.len(),
+                name: item.name,
+                value: item.value,
+                host: cookie.domain,
+                path: cookie.path,
+                expires: cookie.expires.unwrap_or(0),
+                isHttpOnly: cookie.http_only,
+                isSecure: cookie.secure,
+                sameSite: "".to_owned(),
+                hostOnly: false,
+                creationTime: 0,
+                lastAccessed: 0,
+            }),
+            None => serde_json::to_value(StorageEntryMsg {
+                name: item.name,
+                value: item.value,
+            }),
+        };
+        value.unwrap_or(Value::Null)
+    }
+
+    fn fields(&self) -> Vec<FieldMsg> {
+        let field = |name, editable| FieldMsg {
+            name: name,
+            editable: editable,
+        };
+        match self.area {
+            StorageArea::Cookies => vec![
+                field("uniqueKey", false),
+                field("name", true),
+                field("value", true),
+                field("host", true),
+                field("path", true),
+                field("expires", true),
+                field("size", false),
+                field("isHttpOnly", true),
+                field("isSecure", true),
+                field("sameSite", false),
+                field("hostOnly", false),
+                field("creationTime", false),
+                field("lastAccessed", false),
+            ],
+            StorageArea::LocalStorage | StorageArea::SessionStorage => {
+                vec![field("name", true), field("value", true)]
+            },
+        }
+    }
+
+    /// The key of the item described by the `items` of an `editItem` request, before the edit.
+    fn edited_key(&self, data: &Map<String, Value>) -> Option<String> {
+        let fields = data.get("items")?.as_object()?;
+        let string = |name: &str| fields.get(name).and_then(|v| v.as_str()).unwrap_or("");
+        Some(match self.area {
+            StorageArea::Cookies => cookie_key(string("name"), string("host"), string("path")),
+            StorageArea::LocalStorage | StorageArea::SessionStorage => string("name").to_owned(),
+        })
+    }
+
+    /// Build the item described by the `items` of an `editItem` request, with the
+    /// edited field set to its new value.
+    fn edited_item(&self, data: &Map<String, Value>) -> Option<StorageItem> {
+        let mut fields = data.get("items")?.as_object()?.clone();
+        let field = data.get("field")?.as_str()?;
+        fields.insert(field.to_owned(), data.get("newValue")?.clone());
+        let string = |name: &str| {
+            fields
+                .get(name)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_owned()
+        };
+        let boolean = |name: &str| match fields.get(name) {
+            Some(&Value::Bool(value)) => value,
+            Some(&Value::String(ref value)) => value == "true",
+            _ => false,
+        };
+        let cookie = match self.area {
+            StorageArea::Cookies => Some(CookieAttributes {
+                domain: string("host"),
+                path: string("path"),
+                expires: fields
+                    .get("expires")
+                    .and_then(|v| v.as_u64())
+                    .filter(|expires| *expires != 0),
+                http_only: boolean("isHttpOnly"),
+                secure: boolean("isSecure"),
+            }),
+            StorageArea::LocalStorage | StorageArea::SessionStorage => None,
+        };
+        Some(StorageItem {
+            name: string("name"),
+            value: string("value"),
+            cookie: cookie,
+        })
+    }
+}
+
+/// The unique key of the row of a cookie, which identifies it by name, domain and path.
+fn cookie_key(name: &str, domain: &str, path: &str) -> String {
+    format!(
+        "{}{}{}{}{}",
+        name, SEPARATOR_GUID, domain, SEPARATOR_GUID, path
+    )
+}
+
+/// Extract the item name from a name sent by the client, which is the unique key of the
+/// row for cookies.
+fn item_name(name: &str) -> String {
+    name.split(SEPARATOR_GUID).next().unwrap_or(name).to_owned()
+}
+
+impl Actor for StoreActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        msg_type: &str,
+        msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "getStoreObjects" => {
+                let names: Option<Vec<String>> =
+                    msg.get("names").and_then(|v| v.as_array()).map(|names| {
+                        names
+                            .iter()
+                            .filter_map(|name| name.as_str())
+                            .map(item_name)
+                            .collect()
+                    });
+                let options = msg.get("options");
+                let offset = options
+                    .and_then(|o| o.get("offset"))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0);
+                let size = options
+                    .and_then(|o| o.get("size"))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(u64::max_value());
+
+                let items: Vec<_> = self
+                    .items()?
+                    .into_iter()
+                    .filter(|item| names.as_ref().map_or(true, |n| n.contains(&item.name)))
+                    .collect();
+                let msg = StoreObjectsReply {
+                    from: self.name(),
+                    offset: offset,
+                    total: items.len(),
+                    data: items
+                        .into_iter()
+                        .skip(offset as usize)
+                        .take(size as usize)
+                        .map(|item| self.encode_item(item))
+                        .collect(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "getFields" => {
+                let msg = FieldsReply {
+                    from: self.name(),
+                    value: self.fields(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "addItem" => {
+                let name = msg.get("guid").and_then(|v| v.as_str()).ok_or(())?;
+                let cookie = match self.area {
+                    StorageArea::Cookies => Some(CookieAttributes {
+                        domain: "".to_owned(),
+                        path: "/".to_owned(),
+                        expires: None,
+                        http_only: false,
+                        secure: false,
+                    }),
+                    StorageArea::LocalStorage | StorageArea::SessionStorage => None,
+                };
+                self.set_item(StorageItem {
+                    name: name.to_owned(),
+                    value: DEFAULT_VALUE.to_owned(),
+                    cookie: cookie,
+                });
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "editItem" => {
+                let data = msg.get("data").and_then(|v| v.as_object()).ok_or(())?;
+                let item = self.edited_item(data).ok_or(())?;
+                // Renaming an item, or moving a cookie to another host or path, replaces it
+                // with a new one.
+                match data.get("field").and_then(|v| v.as_str()) {
+                    Some("name") | Some("host") | Some("path") => {
+                        if let Some(old_key) = self.edited_key(data) {
+                            self.remove_item(&old_key);
+                        }
+                    },
+                    _ => {},
+                }
+                self.set_item(item);
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "removeItem" => {
+                let name = msg.get("name").and_then(|v| v.as_str()).ok_or(())?;
+                self.remove_item(name);
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "removeAll" => {
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::ClearStorage(
+                        self.pipeline,
+                        self.area,
+                    ))
+                    .unwrap();
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
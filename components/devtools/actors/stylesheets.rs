@@ -2,12 +2,65 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/stylesheets.js).
+
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::protocol::JsonPacketStream;
+use devtools_traits::DevtoolScriptControlMsg::{GetStyleSheetText, GetStyleSheets};
+use devtools_traits::DevtoolScriptControlMsg::{ToggleStyleSheet, UpdateStyleSheet};
+use devtools_traits::{DevtoolScriptControlMsg, StyleSheetInfo};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
 use serde_json::{Map, Value};
 use std::net::TcpStream;
 
+#[derive(Serialize)]
+pub struct StyleSheetForm {
+    actor: String,
+    href: Option<String>,
+    nodeHref: Option<String>,
+    disabled: bool,
+    title: String,
+    system: bool,
+    styleSheetIndex: isize,
+    ruleCount: usize,
+}
+
+#[derive(Serialize)]
+struct GetStyleSheetsReply {
+    from: String,
+    styleSheets: Vec<StyleSheetForm>,
+}
+
+#[derive(Serialize)]
+struct GetTextReply {
+    from: String,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct UpdateReply {
+    from: String,
+}
+
+#[derive(Serialize)]
+struct StyleAppliedEvent {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+}
+
+#[derive(Serialize)]
+struct ToggleDisabledReply {
+    from: String,
+    disabled: bool,
+}
+
 pub struct StyleSheetsActor {
     pub name: String,
+    pipeline: PipelineId,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
 }
 
 impl Actor for StyleSheetsActor {
@@ -16,17 +69,168 @@ impl Actor for StyleSheetsActor {
     }
     fn handle_message(
         &self,
-        _: &ActorRegistry,
-        _: &str,
-        _: &Map<String, Value>,
-        _: &mut TcpStream,
+        registry: &ActorRegistry,
+        msg_type: &str,
+        _msg: &Map<String, Value>,
+        stream: &mut TcpStream,
     ) -> Result<ActorMessageStatus, ()> {
-        Ok(ActorMessageStatus::Ignored)
+        Ok(match msg_type {
+            "getStyleSheets" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(GetStyleSheets(self.pipeline, tx))
+                    .unwrap();
+                let sheets = rx.recv().map_err(|_| ())?;
+
+                let msg = GetStyleSheetsReply {
+                    from: self.name(),
+                    styleSheets: sheets
+                        .into_iter()
+                        .map(|sheet| {
+                            StyleSheetActor::encodable(
+                                registry,
+                                self.pipeline,
+                                self.script_chan.clone(),
+                                sheet,
+                            )
+                        })
+                        .collect(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+impl StyleSheetForm {
+    pub fn actor(&self) -> String {
+        self.actor.clone()
     }
 }
 
 impl StyleSheetsActor {
-    pub fn new(name: String) -> StyleSheetsActor {
-        StyleSheetsActor { name: name }
+    pub fn new(
+        name: String,
+        pipeline: PipelineId,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+    ) -> StyleSheetsActor {
+        StyleSheetsActor {
+            name: name,
+            pipeline: pipeline,
+            script_chan: script_chan,
+        }
+    }
+}
+
+/// A single style sheet of a document, which can be read, replaced and disabled.
+pub struct StyleSheetActor {
+    name: String,
+    id: String,
+    pipeline: PipelineId,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+}
+
+impl Actor for StyleSheetActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        msg_type: &str,
+        msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "getText" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(GetStyleSheetText(self.pipeline, self.id.clone(), tx))
+                    .unwrap();
+                let text = rx.recv().map_err(|_| ())?.ok_or(())?;
+
+                let msg = GetTextReply {
+                    from: self.name(),
+                    text: text,
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "update" => {
+                let text = msg.get("text").and_then(Value::as_str).ok_or(())?;
+                self.script_chan
+                    .send(UpdateStyleSheet(
+                        self.pipeline,
+                        self.id.clone(),
+                        text.to_owned(),
+                    ))
+                    .unwrap();
+
+                stream.write_json_packet(&StyleAppliedEvent {
+                    from: self.name(),
+                    type_: "styleApplied".to_owned(),
+                });
+                stream.write_json_packet(&UpdateReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "toggleDisabled" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(ToggleStyleSheet(self.pipeline, self.id.clone(), tx))
+                    .unwrap();
+                let disabled = rx.recv().map_err(|_| ())?.ok_or(())?;
+
+                let msg = ToggleDisabledReply {
+                    from: self.name(),
+                    disabled: disabled,
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+impl StyleSheetActor {
+    /// Describe a style sheet, creating an actor for it the first time it is seen.
+    pub fn encodable(
+        registry: &ActorRegistry,
+        pipeline: PipelineId,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+        sheet: StyleSheetInfo,
+    ) -> StyleSheetForm {
+        let key = format!("stylesheet:{}:{}", pipeline, sheet.id);
+        let name = if !registry.script_actor_registered(key.clone()) {
+            let name = registry.new_name("stylesheet");
+            let actor = StyleSheetActor {
+                name: name.clone(),
+                id: sheet.id,
+                pipeline: pipeline,
+                script_chan: script_chan,
+            };
+            registry.register_script_actor(key, name.clone());
+            registry.register_later(Box::new(actor));
+            name
+        } else {
+            registry.script_to_actor(key)
+        };
+
+        StyleSheetForm {
+            actor: name,
+            href: sheet.href,
+            nodeHref: None,
+            disabled: sheet.disabled,
+            title: sheet.title.unwrap_or_default(),
+            system: false,
+            styleSheetIndex: sheet.index.map_or(-1, |index| index as isize),
+            ruleCount: sheet.rule_count,
+        }
     }
 }
@@ -2,9 +2,22 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/thread.js).
+//! Drives the script debugger of a global: sources, breakpoints, pausing and stepping.
+
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::frame::{FrameActor, FrameForm, WhereMsg};
+use crate::actors::object::{grip, Debuggee};
+use crate::actors::source::{SourceActor, SourceForm};
 use crate::protocol::JsonPacketStream;
+use devtools_traits::{DebuggerPause, DevtoolScriptControlMsg, FrameInfo, PauseReason};
+use devtools_traits::{ResumeLimit, SourceInfo};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
 use serde_json::{Map, Value};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::net::TcpStream;
 
 #[derive(Serialize)]
@@ -17,6 +30,17 @@ struct ThreadAttachedReply {
     why: WhyMsg,
 }
 
+#[derive(Serialize)]
+struct ThreadPausedMsg {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+    actor: String,
+    frame: Option<FrameForm>,
+    poppedFrames: Vec<PoppedFrameMsg>,
+    why: WhyMsg,
+}
+
 #[derive(Serialize)]
 enum PoppedFrameMsg {}
 
@@ -24,6 +48,8 @@ enum PoppedFrameMsg {}
 struct WhyMsg {
     #[serde(rename = "type")]
     type_: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exception: Option<Value>,
 }
 
 #[derive(Serialize)]
@@ -40,6 +66,13 @@ struct ThreadInterruptedReply {
     type_: String,
 }
 
+#[derive(Serialize)]
+struct ThreadDetachedReply {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+}
+
 #[derive(Serialize)]
 struct ReconfigureReply {
     from: String,
@@ -48,22 +81,163 @@ struct ReconfigureReply {
 #[derive(Serialize)]
 struct SourcesReply {
     from: String,
-    sources: Vec<Source>,
+    sources: Vec<SourceForm>,
+}
+
+#[derive(Serialize)]
+struct FramesReply {
+    from: String,
+    frames: Vec<FrameForm>,
 }
 
 #[derive(Serialize)]
-enum Source {}
+struct EmptyReply {
+    from: String,
+}
 
 pub struct ThreadActor {
     name: String,
+    pipeline: PipelineId,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    streams: RefCell<Vec<TcpStream>>,
+    /// Whether the script thread is currently paused by the debugger.
+    paused: Cell<bool>,
+    /// Actors that are only valid until the script thread resumes.
+    pause_actors: RefCell<Vec<String>>,
+    /// Source actors, by the id of their source.
+    sources: RefCell<HashMap<String, String>>,
 }
 
 impl ThreadActor {
-    pub fn new(name: String) -> ThreadActor {
-        ThreadActor { name: name }
+    pub fn new(
+        name: String,
+        pipeline: PipelineId,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+    ) -> ThreadActor {
+        ThreadActor {
+            name: name,
+            pipeline: pipeline,
+            script_chan: script_chan,
+            streams: RefCell::new(Vec::new()),
+            paused: Cell::new(false),
+            pause_actors: RefCell::new(Vec::new()),
+            sources: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn debuggee(&self) -> Debuggee {
+        Debuggee {
+            pipeline: self.pipeline,
+            script_chan: self.script_chan.clone(),
+        }
+    }
+
+    fn source_actor(&self, registry: &ActorRegistry, source: SourceInfo) -> String {
+        let mut sources = self.sources.borrow_mut();
+        if let Some(name) = sources.get(&source.id) {
+            return name.clone();
+        }
+        let name = registry.new_name("source");
+        sources.insert(source.id.clone(), name.clone());
+        registry.register_later(Box::new(SourceActor {
+            name: name.clone(),
+            pipeline: self.pipeline,
+            script_chan: self.script_chan.clone(),
+            source: source,
+        }));
+        name
+    }
+
+    fn encode_frame(&self, registry: &ActorRegistry, frame: FrameInfo) -> FrameForm {
+        let debuggee = self.debuggee();
+        let name = registry.new_name("frame");
+        let form = FrameForm {
+            actor: name.clone(),
+            type_: frame.kind,
+            depth: frame.depth,
+            displayName: frame.display_name,
+            this: grip(registry, frame.this, Some(&debuggee)),
+            where_: WhereMsg {
+                actor: self.source_actor(registry, frame.location.source),
+                line: frame.location.line,
+                column: frame.location.column,
+            },
+            arguments: vec![],
+        };
+        registry.register_later(Box::new(FrameActor {
+            name: name.clone(),
+            depth: frame.depth,
+            debuggee: debuggee,
+        }));
+        self.pause_actors.borrow_mut().push(name);
+        form
+    }
+
+    /// Notify the attached clients that the script thread paused.
+    pub fn handle_paused(&self, registry: &ActorRegistry, pause: DebuggerPause) {
+        self.paused.set(true);
+        let (type_, exception) = match pause.reason {
+            PauseReason::Interrupted => ("interrupted", None),
+            PauseReason::Breakpoint => ("breakpoint", None),
+            PauseReason::DebuggerStatement => ("debuggerStatement", None),
+            PauseReason::ResumeLimit => ("resumeLimit", None),
+            PauseReason::Exception(value) => (
+                "exception",
+                Some(grip(registry, value, Some(&self.debuggee()))),
+            ),
+        };
+        let msg = ThreadPausedMsg {
+            from: self.name(),
+            type_: "paused".to_owned(),
+            actor: registry.new_name("pause"),
+            frame: pause.frame.map(|frame| self.encode_frame(registry, frame)),
+            poppedFrames: vec![],
+            why: WhyMsg {
+                type_: type_.to_owned(),
+                exception: exception,
+            },
+        };
+        for stream in &mut *self.streams.borrow_mut() {
+            stream.write_json_packet(&msg);
+        }
+    }
+
+    fn resume(&self, registry: &ActorRegistry, limit: Option<ResumeLimit>) {
+        if !self.paused.replace(false) {
+            return;
+        }
+        for name in self.pause_actors.borrow_mut().drain(..) {
+            registry.drop_actor_later(name);
+        }
+        self.script_chan
+            .send(DevtoolScriptControlMsg::Resume(self.pipeline, limit))
+            .unwrap();
+    }
+
+    fn send_pause_on_exceptions(&self, msg: &Map<String, Value>) {
+        let pause = msg.get("pauseOnExceptions").and_then(|v| v.as_bool());
+        let ignore_caught = msg.get("ignoreCaughtExceptions").and_then(|v| v.as_bool());
+        if let Some(pause) = pause {
+            self.script_chan
+                .send(DevtoolScriptControlMsg::PauseOnExceptions(
+                    self.pipeline,
+                    pause,
+                    ignore_caught.unwrap_or(false),
+                ))
+                .unwrap();
+        }
     }
 }
 
+/// Parse the `location` of a breakpoint request into a source url, line and column.
+fn breakpoint_location(msg: &Map<String, Value>) -> Option<(String, u32, u32)> {
+    let location = msg.get("location")?.as_object()?;
+    let url = location.get("sourceUrl")?.as_str()?.to_owned();
+    let line = location.get("line")?.as_u64()? as u32;
+    let column = location.get("column").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    Some((url, line, column))
+}
+
 impl Actor for ThreadActor {
     fn name(&self) -> String {
         self.name.clone()
@@ -73,11 +247,15 @@ impl Actor for ThreadActor {
         &self,
         registry: &ActorRegistry,
         msg_type: &str,
-        _msg: &Map<String, Value>,
+        msg: &Map<String, Value>,
         stream: &mut TcpStream,
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
             "attach" => {
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::AttachDebugger(self.pipeline, true))
+                    .unwrap();
+                self.streams.borrow_mut().push(stream.try_clone().unwrap());
                 let msg = ThreadAttachedReply {
                     from: self.name(),
                     type_: "paused".to_owned(),
@@ -85,13 +263,49 @@ impl Actor for ThreadActor {
                     poppedFrames: vec![],
                     why: WhyMsg {
                         type_: "attached".to_owned(),
+                        exception: None,
                     },
                 };
                 stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
             },
 
+            "detach" => {
+                // Clients are told apart by the address of their end of the connection.
+                let client = stream.peer_addr().ok();
+                self.streams
+                    .borrow_mut()
+                    .retain(|stream| stream.peer_addr().ok() != client);
+                // Keep debugging for the clients that are still attached.
+                if self.streams.borrow().is_empty() {
+                    self.resume(registry, None);
+                    self.script_chan
+                        .send(DevtoolScriptControlMsg::AttachDebugger(
+                            self.pipeline,
+                            false,
+                        ))
+                        .unwrap();
+                }
+                let msg = ThreadDetachedReply {
+                    from: self.name(),
+                    type_: "detached".to_owned(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
             "resume" => {
+                let limit = msg
+                    .get("resumeLimit")
+                    .and_then(|limit| limit.get("type"))
+                    .and_then(|limit| limit.as_str())
+                    .and_then(|limit| match limit {
+                        "next" => Some(ResumeLimit::Next),
+                        "step" => Some(ResumeLimit::Step),
+                        "finish" => Some(ResumeLimit::Finish),
+                        _ => None,
+                    });
+                self.resume(registry, limit);
                 let msg = ThreadResumedReply {
                     from: self.name(),
                     type_: "resumed".to_owned(),
@@ -101,6 +315,9 @@ impl Actor for ThreadActor {
             },
 
             "interrupt" => {
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::Interrupt(self.pipeline))
+                    .unwrap();
                 let msg = ThreadInterruptedReply {
                     from: self.name(),
                     type_: "interrupted".to_owned(),
@@ -110,20 +327,174 @@ impl Actor for ThreadActor {
             },
 
             "reconfigure" => {
+                if let Some(options) = msg.get("options").and_then(|v| v.as_object()) {
+                    self.send_pause_on_exceptions(options);
+                }
                 stream.write_json_packet(&ReconfigureReply { from: self.name() });
                 ActorMessageStatus::Processed
             },
 
+            "pauseOnExceptions" => {
+                self.send_pause_on_exceptions(msg);
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
             "sources" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::GetSources(self.pipeline, tx))
+                    .unwrap();
+                let sources = rx.recv().map_err(|_| ())?;
+                let sources = sources
+                    .into_iter()
+                    .map(|source| SourceForm {
+                        actor: self.source_actor(registry, source.clone()),
+                        url: source.url,
+                        isBlackBoxed: false,
+                        introductionType: source.introduction_type,
+                    })
+                    .collect();
                 let msg = SourcesReply {
                     from: self.name(),
-                    sources: vec![],
+                    sources: sources,
                 };
                 stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
             },
 
+            "frames" => {
+                let start = msg.get("start").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+                let count = msg
+                    .get("count")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(u32::max_value() as u64) as u32;
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::GetFrames(
+                        self.pipeline,
+                        start,
+                        count,
+                        tx,
+                    ))
+                    .unwrap();
+                let frames = rx
+                    .recv()
+                    .map_err(|_| ())?
+                    .into_iter()
+                    .map(|frame| self.encode_frame(registry, frame))
+                    .collect();
+                let msg = FramesReply {
+                    from: self.name(),
+                    frames: frames,
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "setBreakpoint" => {
+                let (url, line, column) = breakpoint_location(msg).ok_or(())?;
+                let condition = msg
+                    .get("options")
+                    .and_then(|options| options.get("condition"))
+                    .and_then(|condition| condition.as_str())
+                    .map(|condition| condition.to_owned());
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::SetBreakpoint(
+                        self.pipeline,
+                        url,
+                        line,
+                        column,
+                        condition,
+                    ))
+                    .unwrap();
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "removeBreakpoint" => {
+                let (url, line, column) = breakpoint_location(msg).ok_or(())?;
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::RemoveBreakpoint(
+                        self.pipeline,
+                        url,
+                        line,
+                        column,
+                    ))
+                    .unwrap();
+                stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
             _ => ActorMessageStatus::Ignored,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ipc_channel::ipc::IpcReceiver;
+    use msg::constellation_msg::TEST_PIPELINE_ID;
+    use std::net::TcpListener;
+
+    /// Returns the server side of a new connection to `listener`, along with the client side,
+    /// which has to be kept alive for the duration of the test.
+    fn connect(listener: &TcpListener) -> (TcpStream, TcpStream) {
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (server, client)
+    }
+
+    fn attach_debugger_msg(port: &IpcReceiver<DevtoolScriptControlMsg>) -> Option<bool> {
+        match port.try_recv() {
+            Ok(DevtoolScriptControlMsg::AttachDebugger(pipeline, attach)) => {
+                assert_eq!(pipeline, TEST_PIPELINE_ID);
+                Some(attach)
+            },
+            Ok(_) => panic!("unexpected message to the script thread"),
+            Err(_) => None,
+        }
+    }
+
+    #[test]
+    fn test_detach_only_removes_the_detaching_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let (mut first, _first_client) = connect(&listener);
+        let (mut second, _second_client) = connect(&listener);
+
+        let (script_chan, script_port) = ipc::channel().unwrap();
+        let registry = ActorRegistry::new();
+        let actor = ThreadActor::new("thread0".to_owned(), TEST_PIPELINE_ID, script_chan);
+        let msg = Map::new();
+
+        actor
+            .handle_message(&registry, "attach", &msg, &mut first)
+            .unwrap();
+        actor
+            .handle_message(&registry, "attach", &msg, &mut second)
+            .unwrap();
+        assert_eq!(attach_debugger_msg(&script_port), Some(true));
+        assert_eq!(attach_debugger_msg(&script_port), Some(true));
+
+        actor
+            .handle_message(&registry, "detach", &msg, &mut first)
+            .unwrap();
+        assert_eq!(actor.streams.borrow().len(), 1);
+        assert_eq!(
+            actor.streams.borrow()[0].peer_addr().unwrap(),
+            second.peer_addr().unwrap()
+        );
+        assert_eq!(
+            attach_debugger_msg(&script_port),
+            None,
+            "the debugger should stay attached while a client remains"
+        );
+
+        actor
+            .handle_message(&registry, "detach", &msg, &mut second)
+            .unwrap();
+        assert!(actor.streams.borrow().is_empty());
+        assert_eq!(attach_debugger_msg(&script_port), Some(false));
+    }
+}
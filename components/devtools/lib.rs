@@ -25,9 +25,11 @@ use crate::actors::emulation::EmulationActor;
 use crate::actors::framerate::FramerateActor;
 use crate::actors::inspector::InspectorActor;
 use crate::actors::network_event::{EventActor, NetworkEventActor, ResponseStartMsg};
+use crate::actors::network_parent::NetworkParentActor;
 use crate::actors::performance::PerformanceActor;
 use crate::actors::profiler::ProfilerActor;
 use crate::actors::root::RootActor;
+use crate::actors::storage::StorageActor;
 use crate::actors::stylesheets::StyleSheetsActor;
 use crate::actors::thread::ThreadActor;
 use crate::actors::timeline::TimelineActor;
@@ -35,8 +37,9 @@ use crate::actors::worker::WorkerActor;
 use crate::protocol::JsonPacketStream;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::{ChromeToDevtoolsControlMsg, ConsoleMessage, DevtoolsControlMsg};
+use devtools_traits::{DebuggerPause, PageError, ScriptToDevtoolsControlMsg, StorageArea};
 use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo, LogLevel, NetworkEvent};
-use devtools_traits::{PageError, ScriptToDevtoolsControlMsg, WorkerId};
+use devtools_traits::{StorageChange, WorkerId};
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
 use std::borrow::ToOwned;
@@ -54,14 +57,18 @@ mod actors {
     pub mod console;
     pub mod device;
     pub mod emulation;
+    pub mod frame;
     pub mod framerate;
     pub mod inspector;
     pub mod memory;
     pub mod network_event;
+    pub mod network_parent;
     pub mod object;
     pub mod performance;
     pub mod profiler;
     pub mod root;
+    pub mod source;
+    pub mod storage;
     pub mod stylesheets;
     pub mod thread;
     pub mod timeline;
@@ -156,15 +163,20 @@ fn run_server(
 
     let device = DeviceActor::new(registry.new_name("device"));
 
+    let network_parent = NetworkParentActor::new(registry.new_name("networkParent"));
+    let network_parent_name = network_parent.name();
+
     let root = Box::new(RootActor {
         tabs: vec![],
         device: device.name(),
         performance: performance.name(),
+        networkParent: network_parent.name(),
     });
 
     registry.register(root);
     registry.register(Box::new(performance));
     registry.register(Box::new(device));
+    registry.register(Box::new(network_parent));
     registry.find::<RootActor>("root");
 
     let actors = registry.create_shareable();
@@ -242,6 +254,7 @@ fn run_server(
             performance,
             styleSheets,
             thread,
+            storage,
         ) = {
             let console = ConsoleActor {
                 name: actors.new_name("console"),
@@ -262,15 +275,22 @@ fn run_server(
                 pipeline: pipeline,
             };
 
-            let timeline = TimelineActor::new(actors.new_name("timeline"), pipeline, script_sender);
+            let timeline =
+                TimelineActor::new(actors.new_name("timeline"), pipeline, script_sender.clone());
 
             let profiler = ProfilerActor::new(actors.new_name("profiler"));
             let performance = PerformanceActor::new(actors.new_name("performance"));
 
             // the strange switch between styleSheets and stylesheets is due
             // to an inconsistency in devtools. See Bug #1498893 in bugzilla
-            let styleSheets = StyleSheetsActor::new(actors.new_name("stylesheets"));
-            let thread = ThreadActor::new(actors.new_name("context"));
+            let styleSheets = StyleSheetsActor::new(
+                actors.new_name("stylesheets"),
+                pipeline,
+                script_sender.clone(),
+            );
+            let thread =
+                ThreadActor::new(actors.new_name("context"), pipeline, script_sender.clone());
+            let storage = StorageActor::new(&mut actors, pipeline, script_sender);
 
             let DevtoolsPageInfo { title, url } = page_info;
             let target = BrowsingContextActor {
@@ -285,6 +305,7 @@ fn run_server(
                 performance: performance.name(),
                 styleSheets: styleSheets.name(),
                 thread: thread.name(),
+                storage: storage.name(),
             };
 
             let root = actors.find_mut::<RootActor>("root");
@@ -300,6 +321,7 @@ fn run_server(
                 performance,
                 styleSheets,
                 thread,
+                storage,
             )
         };
 
@@ -323,6 +345,49 @@ fn run_server(
         actors.register(Box::new(performance));
         actors.register(Box::new(styleSheets));
         actors.register(Box::new(thread));
+        actors.register(Box::new(storage));
+    }
+
+    fn handle_debugger_pause(
+        actors: Arc<Mutex<ActorRegistry>>,
+        id: PipelineId,
+        pause: DebuggerPause,
+        actor_pipelines: &HashMap<PipelineId, String>,
+    ) {
+        let actor_name = match actor_pipelines.get(&id) {
+            Some(name) => name,
+            None => return,
+        };
+        let mut actors = actors.lock().unwrap();
+        let thread = actors
+            .find::<BrowsingContextActor>(actor_name)
+            .thread
+            .clone();
+        actors
+            .find::<ThreadActor>(&thread)
+            .handle_paused(&actors, pause);
+        // The paused packet refers to new actors, which must be known before the client
+        // sends its next message.
+        actors.process_later_actors();
+    }
+
+    fn handle_storage_change(
+        actors: Arc<Mutex<ActorRegistry>>,
+        id: PipelineId,
+        origin: String,
+        area: StorageArea,
+        change: StorageChange,
+        actor_pipelines: &HashMap<PipelineId, String>,
+    ) {
+        let actor_name = match actor_pipelines.get(&id) {
+            Some(name) => name,
+            None => return,
+        };
+        let actors = actors.lock().unwrap();
+        let storage = &actors.find::<BrowsingContextActor>(actor_name).storage;
+        actors
+            .find::<StorageActor>(storage)
+            .handle_storage_change(origin, area, change);
     }
 
     fn handle_page_error(
@@ -593,6 +658,16 @@ fn run_server(
                 id,
                 page_error,
             )) => handle_page_error(actors.clone(), id, page_error, &actor_pipelines),
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::DebuggerPaused(
+                id,
+                pause,
+            )) => handle_debugger_pause(actors.clone(), id, pause, &actor_pipelines),
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::StorageChanged(
+                id,
+                origin,
+                area,
+                change,
+            )) => handle_storage_change(actors.clone(), id, origin, area, change, &actor_pipelines),
             DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::ReportCSSError(
                 id,
                 css_error,
@@ -638,6 +713,13 @@ fn run_server(
                     network_event,
                 );
             },
+            DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkControlChannel(
+                chan,
+            )) => {
+                let actors = actors.lock().unwrap();
+                let network_parent = actors.find::<NetworkParentActor>(&network_parent_name);
+                *network_parent.control_chan.borrow_mut() = Some(chan);
+            },
             DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::ServerExitMsg) => break,
         }
     }
@@ -62,6 +62,34 @@ pub enum ChromeToDevtoolsControlMsg {
     /// A network event occurred (request, reply, etc.). The actor with the
     /// provided name should be notified.
     NetworkEvent(String, NetworkEvent),
+    /// The resource thread started, and accepts network emulation instructions
+    /// over the provided channel.
+    NetworkControlChannel(IpcSender<NetworkControlMsg>),
+}
+
+/// Instructions from the devtools server to the resource thread, used to emulate
+/// network conditions for all fetches.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum NetworkControlMsg {
+    /// Emulate the given bandwidth and latency, or stop throttling.
+    SetThrottle(Option<NetworkThrottle>),
+    /// Fail every request whose URL matches one of the given patterns. A pattern
+    /// matches URLs that contain it, and may use `*` as a wildcard.
+    SetBlockedUrls(Vec<String>),
+    /// Serve the given body instead of fetching the resource at the given URL,
+    /// or fetch it from the network again.
+    SetResponseOverride(ServoUrl, Option<Vec<u8>>),
+}
+
+/// A network profile emulated by the resource thread.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NetworkThrottle {
+    /// Delay before each request is sent, in milliseconds.
+    pub latency: u64,
+    /// Download bandwidth in bytes per second, or zero for no limit.
+    pub download_throughput: u64,
+    /// Upload bandwidth in bytes per second, or zero for no limit.
+    pub upload_throughput: u64,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -85,6 +113,13 @@ pub enum ScriptToDevtoolsControlMsg {
 
     /// Report a page error for the given pipeline
     ReportPageError(PipelineId, PageError),
+
+    /// The script debugger paused execution in the given pipeline. The script thread
+    /// will only process devtools messages until it is told to resume.
+    DebuggerPaused(PipelineId, DebuggerPause),
+
+    /// Script in the given pipeline modified a storage area of the given origin.
+    StorageChanged(PipelineId, String, StorageArea, StorageChange),
 }
 
 /// Serialized JS return values
@@ -99,6 +134,95 @@ pub enum EvaluateJSReply {
     ActorValue { class: String, uuid: String },
 }
 
+/// A script source known to the debugger of a particular global.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SourceInfo {
+    /// An identifier for this source that is unique within its script thread.
+    pub id: String,
+    pub url: String,
+    /// How the source was introduced, e.g. `scriptElement` or `eval`.
+    pub introduction_type: Option<String>,
+}
+
+/// A position in a script source.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SourceLocation {
+    pub source: SourceInfo,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// How far execution should proceed before pausing again after a resume.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ResumeLimit {
+    /// Step over the current line.
+    Next,
+    /// Step into the next function call.
+    Step,
+    /// Step out of the current frame.
+    Finish,
+}
+
+/// Why the script debugger paused execution.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum PauseReason {
+    Interrupted,
+    Breakpoint,
+    DebuggerStatement,
+    ResumeLimit,
+    Exception(EvaluateJSReply),
+}
+
+/// A stack frame of a paused script.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FrameInfo {
+    /// The depth of this frame, where the youngest frame has a depth of zero.
+    pub depth: u32,
+    /// The kind of frame, e.g. `call`, `global` or `eval`.
+    pub kind: String,
+    pub display_name: Option<String>,
+    pub location: SourceLocation,
+    pub this: EvaluateJSReply,
+}
+
+/// A scope in the environment chain of a paused frame.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EnvironmentInfo {
+    /// The kind of environment, e.g. `declarative`, `object` or `with`.
+    pub kind: String,
+    /// The name of the function whose call created this environment, if any.
+    pub function_name: Option<String>,
+    /// For object and with environments, the object whose properties are the bindings.
+    pub object: Option<EvaluateJSReply>,
+    pub bindings: Vec<(String, EvaluateJSReply)>,
+    pub parent: Option<Box<EnvironmentInfo>>,
+}
+
+/// An own property of an object inspected through the debugger.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PropertyInfo {
+    pub name: String,
+    /// The value of a data property, or `None` for accessors.
+    pub value: Option<EvaluateJSReply>,
+    pub enumerable: bool,
+    pub writable: bool,
+    pub configurable: bool,
+}
+
+/// The prototype and own properties of an object inspected through the debugger.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ObjectProperties {
+    pub prototype: EvaluateJSReply,
+    pub properties: Vec<PropertyInfo>,
+}
+
+/// The state of a script thread that has been paused by the debugger.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DebuggerPause {
+    pub reason: PauseReason,
+    pub frame: Option<FrameInfo>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AttrInfo {
     pub namespace: String,
@@ -216,6 +340,146 @@ pub enum DevtoolScriptControlMsg {
     RequestAnimationFrame(PipelineId, String),
     /// Direct the given pipeline to reload the current page.
     Reload(PipelineId),
+    /// Start (true) or stop (false) debugging the scripts of the given pipeline.
+    AttachDebugger(PipelineId, bool),
+    /// Retrieve the script sources of the given pipeline.
+    GetSources(PipelineId, IpcSender<Vec<SourceInfo>>),
+    /// Retrieve the text of the source with the given id.
+    GetSourceText(PipelineId, String, IpcSender<Option<String>>),
+    /// Set a breakpoint at the given url, line and column, with an optional condition
+    /// that must evaluate to a truthy value for the breakpoint to pause.
+    SetBreakpoint(PipelineId, String, u32, u32, Option<String>),
+    /// Remove the breakpoint at the given url, line and column.
+    RemoveBreakpoint(PipelineId, String, u32, u32),
+    /// Configure whether thrown exceptions pause execution, and whether caught
+    /// exceptions are ignored.
+    PauseOnExceptions(PipelineId, bool, bool),
+    /// Pause as soon as script next runs in the given pipeline.
+    Interrupt(PipelineId),
+    /// Resume execution of a paused pipeline, optionally pausing again once the
+    /// given limit is reached.
+    Resume(PipelineId, Option<ResumeLimit>),
+    /// Retrieve the stack frames of a paused pipeline, starting at the given depth.
+    GetFrames(PipelineId, u32, u32, IpcSender<Vec<FrameInfo>>),
+    /// Retrieve the environment of the frame at the given depth of a paused pipeline.
+    GetFrameEnvironment(PipelineId, u32, IpcSender<Option<EnvironmentInfo>>),
+    /// Retrieve the prototype and own properties of an object seen while paused.
+    GetObjectProperties(PipelineId, String, IpcSender<Option<ObjectProperties>>),
+    /// Retrieve the serialized origin whose storage is used by the given pipeline, and start
+    /// reporting the changes script makes to that storage.
+    GetStorageOrigin(PipelineId, IpcSender<Option<String>>),
+    /// Retrieve the items of a storage area of the given pipeline.
+    GetStorageItems(PipelineId, StorageArea, IpcSender<Vec<StorageItem>>),
+    /// Add an item to a storage area of the given pipeline, replacing any item with the
+    /// same name.
+    SetStorageItem(PipelineId, StorageArea, StorageItem),
+    /// Remove the item with the given name from a storage area of the given pipeline. Cookies
+    /// are also identified by their domain and path.
+    RemoveStorageItem(PipelineId, StorageArea, String, Option<CookieScope>),
+    /// Remove all the items of a storage area of the given pipeline.
+    ClearStorage(PipelineId, StorageArea),
+    /// Retrieve the style sheets of the document of the given pipeline, including the
+    /// sheets they import.
+    GetStyleSheets(PipelineId, IpcSender<Vec<StyleSheetInfo>>),
+    /// Retrieve the text of the style sheet with the given id.
+    GetStyleSheetText(PipelineId, String, IpcSender<Option<String>>),
+    /// Replace the contents of the style sheet with the given id by parsing the given text.
+    UpdateStyleSheet(PipelineId, String, String),
+    /// Enable or disable the style sheet with the given id, replying with whether it is
+    /// now disabled.
+    ToggleStyleSheet(PipelineId, String, IpcSender<Option<bool>>),
+    /// Retrieve the style rules that apply to the given node, most specific first.
+    GetAppliedRules(PipelineId, String, IpcSender<Option<Vec<AppliedRuleInfo>>>),
+    /// Modify the declarations of the rule with the given id, replying with the new
+    /// text of its declarations.
+    ModifyRule(
+        PipelineId,
+        String,
+        Vec<RuleModification>,
+        IpcSender<Option<String>>,
+    ),
+}
+
+/// A style sheet of a document, as shown in the style editor.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StyleSheetInfo {
+    /// An identifier for this sheet that is unique within its document.
+    pub id: String,
+    pub href: Option<String>,
+    pub title: Option<String>,
+    pub disabled: bool,
+    /// The index of this sheet in the style sheets of the document, or `None` for
+    /// imported sheets.
+    pub index: Option<usize>,
+    pub rule_count: usize,
+}
+
+/// A style rule that applies to a node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AppliedRuleInfo {
+    /// An identifier for this rule that is unique within its document.
+    pub id: String,
+    /// The selector of the rule, or `None` for the style attribute of the node.
+    pub selector: Option<String>,
+    /// The text of the declarations of the rule.
+    pub css_text: String,
+    pub line: u32,
+    pub column: u32,
+    /// The sheet containing the rule, or `None` for the style attribute of the node.
+    pub stylesheet: Option<StyleSheetInfo>,
+}
+
+/// A change to a declaration of a style rule.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RuleModification {
+    pub name: String,
+    /// The new value of the property, or `None` to remove it.
+    pub value: Option<String>,
+    pub priority: String,
+}
+
+/// A storage area that can be inspected through the storage actor.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum StorageArea {
+    Cookies,
+    LocalStorage,
+    SessionStorage,
+}
+
+/// The attributes of a cookie, as shown in the storage inspector.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CookieAttributes {
+    pub domain: String,
+    pub path: String,
+    /// The expiry time in milliseconds since the epoch, or `None` for session cookies.
+    pub expires: Option<u64>,
+    pub http_only: bool,
+    pub secure: bool,
+}
+
+/// The domain and path that identify a cookie, together with its name.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CookieScope {
+    pub domain: String,
+    pub path: String,
+}
+
+/// An item of a storage area.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StorageItem {
+    pub name: String,
+    pub value: String,
+    /// The attributes of the item, if it is a cookie.
+    pub cookie: Option<CookieAttributes>,
+}
+
+/// A modification made by script to a storage area.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum StorageChange {
+    Added(String),
+    Changed(String),
+    Removed(String),
+    Cleared,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
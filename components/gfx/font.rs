@@ -10,6 +10,7 @@ pub use crate::platform::font_list::fallback_font_families;
 use crate::platform::font_template::FontTemplateData;
 use crate::text::glyph::{ByteIndex, GlyphData, GlyphId, GlyphStore};
 use crate::text::shaping::ShaperMethods;
+use crate::text::util::{fixed_to_float, float_to_fixed};
 use crate::text::Shaper;
 use app_units::Au;
 use euclid::default::{Point2D, Rect, Size2D};
@@ -26,7 +27,12 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use style::computed_values::{font_stretch, font_style, font_variant_caps, font_weight};
 use style::properties::style_structs::Font as FontStyleStruct;
+use style::shared_lock::StylesheetGuards;
+use style::stylesheets::font_feature_values_rule::FFVDeclaration;
+use style::stylesheets::FontFeatureValuesRule;
+use style::stylist::Stylist;
 use style::values::computed::font::{GenericFontFamily, SingleFontFamily};
+use style::values::specified::font::VariantAlternates;
 use unicode_script::Script;
 
 macro_rules! ot_tag {
@@ -38,6 +44,31 @@ macro_rules! ot_tag {
 pub const GPOS: u32 = ot_tag!('G', 'P', 'O', 'S');
 pub const GSUB: u32 = ot_tag!('G', 'S', 'U', 'B');
 pub const KERN: u32 = ot_tag!('k', 'e', 'r', 'n');
+pub const LIGA: u32 = ot_tag!('l', 'i', 'g', 'a');
+const AFRC: u32 = ot_tag!('a', 'f', 'r', 'c');
+const C2PC: u32 = ot_tag!('c', '2', 'p', 'c');
+const C2SC: u32 = ot_tag!('c', '2', 's', 'c');
+const CALT: u32 = ot_tag!('c', 'a', 'l', 't');
+const CLIG: u32 = ot_tag!('c', 'l', 'i', 'g');
+const CSWH: u32 = ot_tag!('c', 's', 'w', 'h');
+const DLIG: u32 = ot_tag!('d', 'l', 'i', 'g');
+const FRAC: u32 = ot_tag!('f', 'r', 'a', 'c');
+const HIST: u32 = ot_tag!('h', 'i', 's', 't');
+const HLIG: u32 = ot_tag!('h', 'l', 'i', 'g');
+const LNUM: u32 = ot_tag!('l', 'n', 'u', 'm');
+const NALT: u32 = ot_tag!('n', 'a', 'l', 't');
+const ONUM: u32 = ot_tag!('o', 'n', 'u', 'm');
+const ORDN: u32 = ot_tag!('o', 'r', 'd', 'n');
+const ORNM: u32 = ot_tag!('o', 'r', 'n', 'm');
+const PCAP: u32 = ot_tag!('p', 'c', 'a', 'p');
+const PNUM: u32 = ot_tag!('p', 'n', 'u', 'm');
+const SALT: u32 = ot_tag!('s', 'a', 'l', 't');
+const SMCP: u32 = ot_tag!('s', 'm', 'c', 'p');
+const SWSH: u32 = ot_tag!('s', 'w', 's', 'h');
+const TITL: u32 = ot_tag!('t', 'i', 't', 'l');
+const TNUM: u32 = ot_tag!('t', 'n', 'u', 'm');
+const UNIC: u32 = ot_tag!('u', 'n', 'i', 'c');
+const ZERO: u32 = ot_tag!('z', 'e', 'r', 'o');
 
 static TEXT_SHAPING_PERFORMANCE_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
@@ -47,10 +78,14 @@ static TEXT_SHAPING_PERFORMANCE_COUNTER: AtomicUsize = AtomicUsize::new(0);
 // resources needed by the graphics layer to draw glyphs.
 
 pub trait FontHandleMethods: Sized {
+    /// Creates a handle for the given template at the given size. `variations` are the
+    /// coordinates to apply to the axes of a variable font; only the FreeType backend applies
+    /// them, the macOS and Windows backends ignore them and always use the default instance.
     fn new_from_template(
         fctx: &FontContextHandle,
         template: Arc<FontTemplateData>,
         pt_size: Option<Au>,
+        variations: &[FontVariation],
     ) -> Result<Self, ()>;
 
     fn template(&self) -> Arc<FontTemplateData>;
@@ -115,6 +150,237 @@ pub struct FontMetrics {
     pub line_gap: Au,
 }
 
+/// A value for one of the axes of a variable font, as specified by `font-variation-settings`.
+/// The value is stored in 16.16 fixed point, which is what FreeType expects and which lets us use
+/// it as part of a cache key.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct FontVariation {
+    pub tag: FontTableTag,
+    pub(crate) value: i32,
+}
+
+impl FontVariation {
+    pub fn new(tag: FontTableTag, value: f32) -> FontVariation {
+        FontVariation {
+            tag,
+            value: float_to_fixed(16, value as f64),
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        fixed_to_float(16, self.value) as f32
+    }
+}
+
+/// An OpenType feature to enable or disable while shaping a run of text.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FontFeature {
+    pub tag: FontTableTag,
+    pub value: u32,
+}
+
+impl FontFeature {
+    fn new(tag: FontTableTag, value: u32) -> FontFeature {
+        FontFeature { tag, value }
+    }
+
+    /// Returns the OpenType features requested by the `font-variant-*` and `font-feature-settings`
+    /// properties of the given style. Features that appear later in the list take precedence.
+    ///
+    /// The names used by `font-variant-alternates` are resolved against the `@font-feature-values`
+    /// rules of the given stylist.
+    ///
+    /// https://drafts.csswg.org/css-fonts-3/#feature-precedence
+    pub fn list_from_style(
+        style: &FontStyleStruct,
+        stylist: &Stylist,
+        guards: &StylesheetGuards,
+    ) -> Vec<FontFeature> {
+        use style::computed_values::font_variant_ligatures::T as Ligatures;
+        use style::computed_values::font_variant_numeric::T as Numeric;
+
+        let mut features = vec![];
+
+        let ligatures = style.font_variant_ligatures;
+        if ligatures.contains(Ligatures::NONE) {
+            for &tag in &[LIGA, CLIG, DLIG, HLIG, CALT] {
+                features.push(FontFeature::new(tag, 0));
+            }
+        }
+        for &(on, off, tags) in &[
+            (
+                Ligatures::COMMON_LIGATURES,
+                Ligatures::NO_COMMON_LIGATURES,
+                &[LIGA, CLIG][..],
+            ),
+            (
+                Ligatures::DISCRETIONARY_LIGATURES,
+                Ligatures::NO_DISCRETIONARY_LIGATURES,
+                &[DLIG][..],
+            ),
+            (
+                Ligatures::HISTORICAL_LIGATURES,
+                Ligatures::NO_HISTORICAL_LIGATURES,
+                &[HLIG][..],
+            ),
+            (Ligatures::CONTEXTUAL, Ligatures::NO_CONTEXTUAL, &[CALT][..]),
+        ] {
+            if ligatures.intersects(on | off) {
+                let value = ligatures.contains(on) as u32;
+                features.extend(tags.iter().map(|&tag| FontFeature::new(tag, value)));
+            }
+        }
+
+        let numeric = style.font_variant_numeric;
+        for &(flag, tag) in &[
+            (Numeric::LINING_NUMS, LNUM),
+            (Numeric::OLDSTYLE_NUMS, ONUM),
+            (Numeric::PROPORTIONAL_NUMS, PNUM),
+            (Numeric::TABULAR_NUMS, TNUM),
+            (Numeric::DIAGONAL_FRACTIONS, FRAC),
+            (Numeric::STACKED_FRACTIONS, AFRC),
+            (Numeric::ORDINAL, ORDN),
+            (Numeric::SLASHED_ZERO, ZERO),
+        ] {
+            if numeric.contains(flag) {
+                features.push(FontFeature::new(tag, 1));
+            }
+        }
+
+        // `small-caps` is still synthesized from uppercase glyphs, see `Font::glyph_index`.
+        let caps: &[FontTableTag] = match style.font_variant_caps {
+            font_variant_caps::T::Normal | font_variant_caps::T::SmallCaps => &[],
+            font_variant_caps::T::AllSmallCaps => &[SMCP, C2SC],
+            font_variant_caps::T::PetiteCaps => &[PCAP],
+            font_variant_caps::T::AllPetiteCaps => &[PCAP, C2PC],
+            font_variant_caps::T::Unicase => &[UNIC],
+            font_variant_caps::T::TitlingCaps => &[TITL],
+        };
+        features.extend(caps.iter().map(|&tag| FontFeature::new(tag, 1)));
+
+        let lookup = FeatureValuesLookup {
+            style,
+            stylist,
+            guards,
+        };
+        for alternate in style.font_variant_alternates.iter() {
+            match *alternate {
+                VariantAlternates::HistoricalForms => features.push(FontFeature::new(HIST, 1)),
+                VariantAlternates::Stylistic(ref name) => {
+                    if let Some(value) = lookup.find(&name.0, |rule| &rule.stylistic) {
+                        features.push(FontFeature::new(SALT, value.0));
+                    }
+                },
+                VariantAlternates::Swash(ref name) => {
+                    if let Some(value) = lookup.find(&name.0, |rule| &rule.swash) {
+                        features.push(FontFeature::new(SWSH, value.0));
+                        features.push(FontFeature::new(CSWH, value.0));
+                    }
+                },
+                VariantAlternates::Ornaments(ref name) => {
+                    if let Some(value) = lookup.find(&name.0, |rule| &rule.ornaments) {
+                        features.push(FontFeature::new(ORNM, value.0));
+                    }
+                },
+                VariantAlternates::Annotation(ref name) => {
+                    if let Some(value) = lookup.find(&name.0, |rule| &rule.annotation) {
+                        features.push(FontFeature::new(NALT, value.0));
+                    }
+                },
+                VariantAlternates::Styleset(ref names) => {
+                    for name in names.iter() {
+                        if let Some(sets) = lookup.find(&name.0, |rule| &rule.styleset) {
+                            features.extend(
+                                sets.0
+                                    .iter()
+                                    .filter_map(|&set| numbered_tag(b's', b's', set))
+                                    .map(|tag| FontFeature::new(tag, 1)),
+                            );
+                        }
+                    }
+                },
+                VariantAlternates::CharacterVariant(ref names) => {
+                    for name in names.iter() {
+                        let value = lookup.find(&name.0, |rule| &rule.character_variant);
+                        if let Some(value) = value {
+                            if let Some(tag) = numbered_tag(b'c', b'v', value.0) {
+                                features.push(FontFeature::new(tag, value.1.unwrap_or(1)));
+                            }
+                        }
+                    }
+                },
+            }
+        }
+
+        features.extend(
+            style
+                .font_feature_settings
+                .0
+                .iter()
+                .map(|setting| FontFeature::new(setting.tag.0, setting.value as u32)),
+        );
+        features
+    }
+}
+
+/// The tag of the numbered feature made of the two given letters and the two digits of `number`,
+/// such as `ss03` or `cv42`. Only numbers from 1 to 99 name such a feature.
+fn numbered_tag(first: u8, second: u8, number: u32) -> Option<FontTableTag> {
+    if number == 0 || number > 99 {
+        return None;
+    }
+    let (tens, units) = (b'0' + (number / 10) as u8, b'0' + (number % 10) as u8);
+    Some(ot_tag!(first, second, tens, units))
+}
+
+/// Looks up the feature value names of `font-variant-alternates` in `@font-feature-values` rules.
+///
+/// https://drafts.csswg.org/css-fonts-4/#font-feature-values
+struct FeatureValuesLookup<'a> {
+    style: &'a FontStyleStruct,
+    stylist: &'a Stylist,
+    guards: &'a StylesheetGuards<'a>,
+}
+
+impl<'a> FeatureValuesLookup<'a> {
+    /// Returns the value that `name` is given in the block of `@font-feature-values` rules
+    /// selected by `block`. The families of `font-family` are tried in order, and for a given
+    /// family the last definition in cascade order wins. Generic families never have values.
+    fn find<T: Clone>(
+        &self,
+        name: &Atom,
+        block: fn(&FontFeatureValuesRule) -> &Vec<FFVDeclaration<T>>,
+    ) -> Option<T> {
+        for family in self.style.font_family.families.iter() {
+            let family = match *family {
+                SingleFontFamily::FamilyName(ref family) => &family.name,
+                SingleFontFamily::Generic(_) => continue,
+            };
+            let mut value = None;
+            for (data, origin) in self.stylist.iter_extra_data_origins_rev() {
+                let guard = self.guards.for_origin(origin);
+                for rule in &data.font_feature_values {
+                    let rule = rule.read_with(guard);
+                    if !rule
+                        .family_names
+                        .iter()
+                        .any(|rule_family| rule_family.name.eq_ignore_ascii_case(family))
+                    {
+                        continue;
+                    }
+                    if let Some(declaration) = block(rule).iter().find(|d| d.name == *name) {
+                        value = Some(declaration.value.clone());
+                    }
+                }
+            }
+            if value.is_some() {
+                return value;
+            }
+        }
+        None
+    }
+}
+
 /// `FontDescriptor` describes the parameters of a `Font`. It represents rendering a given font
 /// template at a particular size, with a particular font-variant-caps applied, etc. This contrasts
 /// with `FontTemplateDescriptor` in that the latter represents only the parameters inherent in the
@@ -124,6 +390,7 @@ pub struct FontDescriptor {
     pub template_descriptor: FontTemplateDescriptor,
     pub variant: font_variant_caps::T,
     pub pt_size: Au,
+    pub variations: Vec<FontVariation>,
 }
 
 impl<'a> From<&'a FontStyleStruct> for FontDescriptor {
@@ -132,6 +399,12 @@ impl<'a> From<&'a FontStyleStruct> for FontDescriptor {
             template_descriptor: FontTemplateDescriptor::from(style),
             variant: style.font_variant_caps,
             pt_size: style.font_size.size(),
+            variations: style
+                .font_variation_settings
+                .0
+                .iter()
+                .map(|setting| FontVariation::new(setting.tag.0, setting.value))
+                .collect(),
         }
     }
 }
@@ -191,7 +464,7 @@ bitflags! {
 }
 
 /// Various options that control text shaping.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct ShapingOptions {
     /// Spacing to add between each letter. Corresponds to the CSS 2.1 `letter-spacing` property.
     /// NB: You will probably want to set the `IGNORE_LIGATURES_SHAPING_FLAG` if this is non-null.
@@ -202,6 +475,8 @@ pub struct ShapingOptions {
     pub script: Script,
     /// Various flags.
     pub flags: ShapingFlags,
+    /// OpenType features to apply, in increasing order of precedence.
+    pub features: Vec<FontFeature>,
 }
 
 /// An entry in the shape cache.
@@ -218,7 +493,7 @@ impl Font {
 
         let lookup_key = ShapeCacheEntry {
             text: text.to_owned(),
-            options: *options,
+            options: options.clone(),
         };
         let result = self
             .shape_cache
@@ -261,6 +536,7 @@ impl Font {
     fn can_do_fast_shaping(&self, text: &str, options: &ShapingOptions) -> bool {
         options.script == Script::Latin &&
             !options.flags.contains(ShapingFlags::RTL_FLAG) &&
+            options.features.is_empty() &&
             self.handle.can_do_fast_shaping() &&
             text.is_ascii()
     }
@@ -322,7 +598,7 @@ impl Font {
     pub fn glyph_index(&self, codepoint: char) -> Option<GlyphId> {
         let codepoint = match self.descriptor.variant {
             font_variant_caps::T::SmallCaps => codepoint.to_uppercase().next().unwrap(), //FIXME: #5938
-            font_variant_caps::T::Normal => codepoint,
+            _ => codepoint,
         };
         self.handle.glyph_index(codepoint)
     }
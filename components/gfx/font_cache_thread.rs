@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::font::{FontFamilyDescriptor, FontFamilyName, FontSearchScope};
+use crate::font::{FontFamilyDescriptor, FontFamilyName, FontSearchScope, FontVariation};
 use crate::font_context::FontSource;
 use crate::font_template::{FontTemplate, FontTemplateDescriptor};
 use crate::platform::font_context::FontContextHandle;
@@ -88,16 +88,34 @@ impl FontTemplates {
     }
 
     pub fn add_template(&mut self, identifier: Atom, maybe_data: Option<Vec<u8>>) {
+        self.add_font_face_template(identifier, maybe_data, None)
+    }
+
+    /// Adds a template for the font face created by script with the given id, if any, so that
+    /// it can be removed along with that font face.
+    pub fn add_font_face_template(
+        &mut self,
+        identifier: Atom,
+        maybe_data: Option<Vec<u8>>,
+        font_face: Option<Atom>,
+    ) {
         for template in &self.templates {
-            if *template.identifier() == identifier {
+            if *template.identifier() == identifier && template.font_face() == font_face.as_ref() {
                 return;
             }
         }
 
-        if let Ok(template) = FontTemplate::new(identifier, maybe_data) {
+        if let Ok(mut template) = FontTemplate::new(identifier, maybe_data) {
+            template.set_font_face(font_face);
             self.templates.push(template);
         }
     }
+
+    /// Removes the templates of the font face created by script with the given id.
+    pub fn remove_font_face_templates(&mut self, font_face: &Atom) {
+        self.templates
+            .retain(|template| template.font_face() != Some(font_face));
+    }
 }
 
 /// Commands that the FontContext sends to the font cache thread.
@@ -111,10 +129,24 @@ pub enum Command {
     GetFontInstance(
         webrender_api::FontKey,
         Au,
+        Vec<FontVariation>,
         IpcSender<webrender_api::FontInstanceKey>,
     ),
-    AddWebFont(LowercaseString, EffectiveSources, IpcSender<()>),
-    AddDownloadedWebFont(LowercaseString, ServoUrl, Vec<u8>, IpcSender<()>),
+    AddWebFont(
+        LowercaseString,
+        EffectiveSources,
+        Option<Atom>,
+        IpcSender<bool>,
+    ),
+    AddDownloadedWebFont(
+        LowercaseString,
+        ServoUrl,
+        Vec<u8>,
+        Option<Atom>,
+        IpcSender<bool>,
+    ),
+    AddWebFontData(LowercaseString, Vec<u8>, Atom, IpcSender<bool>),
+    RemoveWebFont(LowercaseString, Atom, IpcSender<()>),
     Exit(IpcSender<()>),
     Ping,
 }
@@ -137,7 +169,8 @@ struct FontCache {
     core_resource_thread: CoreResourceThread,
     webrender_api: webrender_api::RenderApi,
     webrender_fonts: HashMap<Atom, webrender_api::FontKey>,
-    font_instances: HashMap<(webrender_api::FontKey, Au), webrender_api::FontInstanceKey>,
+    font_instances:
+        HashMap<(webrender_api::FontKey, Au, Vec<FontVariation>), webrender_api::FontInstanceKey>,
 }
 
 fn populate_generic_fonts() -> HashMap<FontFamilyName, LowercaseString> {
@@ -178,29 +211,48 @@ impl FontCache {
                         self.find_font_template(&template_descriptor, &family_descriptor);
                     let _ = result.send(Reply::GetFontTemplateReply(maybe_font_template));
                 },
-                Command::GetFontInstance(font_key, size, result) => {
+                Command::GetFontInstance(font_key, size, variations, result) => {
                     let webrender_api = &self.webrender_api;
 
-                    let instance_key =
-                        *self
-                            .font_instances
-                            .entry((font_key, size))
-                            .or_insert_with(|| {
-                                let key = webrender_api.generate_font_instance_key();
-                                let mut txn = webrender_api::Transaction::new();
-                                txn.add_font_instance(key, font_key, size, None, None, Vec::new());
-                                webrender_api.update_resources(txn.resource_updates);
-                                key
-                            });
+                    let instance_key = *self
+                        .font_instances
+                        .entry((font_key, size, variations.clone()))
+                        .or_insert_with(|| {
+                            let key = webrender_api.generate_font_instance_key();
+                            let variations = variations
+                                .iter()
+                                .map(|variation| webrender_api::FontVariation {
+                                    tag: variation.tag,
+                                    value: variation.value(),
+                                })
+                                .collect();
+                            let mut txn = webrender_api::Transaction::new();
+                            txn.add_font_instance(key, font_key, size, None, None, variations);
+                            webrender_api.update_resources(txn.resource_updates);
+                            key
+                        });
 
                     let _ = result.send(instance_key);
                 },
-                Command::AddWebFont(family_name, sources, result) => {
-                    self.handle_add_web_font(family_name, sources, result);
+                Command::AddWebFont(family_name, sources, font_face, result) => {
+                    self.handle_add_web_font(family_name, sources, font_face, result);
                 },
-                Command::AddDownloadedWebFont(family_name, url, bytes, result) => {
+                Command::AddDownloadedWebFont(family_name, url, bytes, font_face, result) => {
                     let templates = &mut self.web_families.get_mut(&family_name).unwrap();
-                    templates.add_template(Atom::from(url.to_string()), Some(bytes));
+                    templates.add_font_face_template(
+                        Atom::from(url.to_string()),
+                        Some(bytes),
+                        font_face,
+                    );
+                    drop(result.send(true));
+                },
+                Command::AddWebFontData(family_name, bytes, font_face, result) => {
+                    self.handle_add_web_font_data(family_name, bytes, font_face, result);
+                },
+                Command::RemoveWebFont(family_name, font_face, result) => {
+                    if let Some(templates) = self.web_families.get_mut(&family_name) {
+                        templates.remove_font_face_templates(&font_face);
+                    }
                     drop(result.send(()));
                 },
                 Command::Ping => (),
@@ -216,12 +268,13 @@ impl FontCache {
         &mut self,
         family_name: LowercaseString,
         mut sources: EffectiveSources,
-        sender: IpcSender<()>,
+        font_face: Option<Atom>,
+        sender: IpcSender<bool>,
     ) {
         let src = if let Some(src) = sources.next() {
             src
         } else {
-            sender.send(()).unwrap();
+            sender.send(false).unwrap();
             return;
         };
 
@@ -235,7 +288,11 @@ impl FontCache {
                 // https://drafts.csswg.org/css-fonts/#font-fetching-requirements
                 let url = match url_source.url.url() {
                     Some(url) => url.clone(),
-                    None => return,
+                    None => {
+                        let msg = Command::AddWebFont(family_name, sources, font_face, sender);
+                        self.channel_to_self.send(msg).unwrap();
+                        return;
+                    },
                 };
 
                 let request = RequestBuilder::new(url.clone()).destination(Destination::Font);
@@ -268,6 +325,7 @@ impl FontCache {
                                 let msg = Command::AddWebFont(
                                     family_name.clone(),
                                     sources.clone(),
+                                    font_face.clone(),
                                     sender.clone(),
                                 );
                                 channel_to_self.send(msg).unwrap();
@@ -287,6 +345,7 @@ impl FontCache {
                                     let msg = Command::AddWebFont(
                                         family_name.clone(),
                                         sources.clone(),
+                                        font_face.clone(),
                                         sender.clone(),
                                     );
                                     channel_to_self.send(msg).unwrap();
@@ -297,6 +356,7 @@ impl FontCache {
                                 family_name.clone(),
                                 url.clone(),
                                 bytes,
+                                font_face.clone(),
                                 sender.clone(),
                             );
                             channel_to_self.send(command).unwrap();
@@ -310,18 +370,44 @@ impl FontCache {
                 let mut found = false;
                 for_each_variation(&font_face_name, |path| {
                     found = true;
-                    templates.add_template(Atom::from(&*path), None);
+                    templates.add_font_face_template(Atom::from(&*path), None, font_face.clone());
                 });
                 if found {
-                    sender.send(()).unwrap();
+                    sender.send(true).unwrap();
                 } else {
-                    let msg = Command::AddWebFont(family_name, sources, sender);
+                    let msg = Command::AddWebFont(family_name, sources, font_face, sender);
                     self.channel_to_self.send(msg).unwrap();
                 }
             },
         }
     }
 
+    fn handle_add_web_font_data(
+        &mut self,
+        family_name: LowercaseString,
+        bytes: Vec<u8>,
+        font_face: Atom,
+        sender: IpcSender<bool>,
+    ) {
+        let bytes = match fontsan::process(&bytes) {
+            Ok(san) => san,
+            Err(_) => {
+                debug!("Sanitiser rejected web font data: family={}", family_name);
+                sender.send(false).unwrap();
+                return;
+            },
+        };
+
+        // Fonts which were not fetched have no URL to be identified with, so they are
+        // identified with their font face.
+        let identifier = Atom::from(format!("font-face-data:{}", font_face));
+        self.web_families
+            .entry(family_name)
+            .or_insert_with(FontTemplates::new)
+            .add_font_face_template(identifier, Some(bytes), Some(font_face));
+        sender.send(true).unwrap();
+    }
+
     fn refresh_local_families(&mut self) {
         self.local_families.clear();
         for_each_available_family(|family_name| {
@@ -473,16 +559,54 @@ impl FontCacheThread {
         FontCacheThread { chan: chan }
     }
 
+    /// Loads a web font from the given sources, in order, replying on `sender` with whether
+    /// one of them could be loaded.
+    ///
+    /// Web fonts loaded for font faces created by script are tagged with the id of their font
+    /// face, so that `remove_web_font` can remove them again.
     pub fn add_web_font(
         &self,
         family: FamilyName,
         sources: EffectiveSources,
-        sender: IpcSender<()>,
+        font_face: Option<Atom>,
+        sender: IpcSender<bool>,
     ) {
         self.chan
             .send(Command::AddWebFont(
                 LowercaseString::new(&family.name),
                 sources,
+                font_face,
+                sender,
+            ))
+            .unwrap();
+    }
+
+    /// Adds the data of the font face created by script with the given id, replying on
+    /// `sender` with whether it is a valid font.
+    pub fn add_web_font_data(
+        &self,
+        family: FamilyName,
+        bytes: Vec<u8>,
+        font_face: Atom,
+        sender: IpcSender<bool>,
+    ) {
+        self.chan
+            .send(Command::AddWebFontData(
+                LowercaseString::new(&family.name),
+                bytes,
+                font_face,
+                sender,
+            ))
+            .unwrap();
+    }
+
+    /// Removes the web fonts of the font face created by script with the given id, replying on
+    /// `sender` once they are removed.
+    pub fn remove_web_font(&self, family: FamilyName, font_face: Atom, sender: IpcSender<()>) {
+        self.chan
+            .send(Command::RemoveWebFont(
+                LowercaseString::new(&family.name),
+                font_face,
                 sender,
             ))
             .unwrap();
@@ -504,10 +628,16 @@ impl FontSource for FontCacheThread {
         &mut self,
         key: webrender_api::FontKey,
         size: Au,
+        variations: Vec<FontVariation>,
     ) -> webrender_api::FontInstanceKey {
         let (response_chan, response_port) = ipc::channel().expect("failed to create IPC channel");
         self.chan
-            .send(Command::GetFontInstance(key, size, response_chan))
+            .send(Command::GetFontInstance(
+                key,
+                size,
+                variations,
+                response_chan,
+            ))
             .expect("failed to send message to font cache thread");
 
         let instance_key = response_port.recv();
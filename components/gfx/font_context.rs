@@ -4,6 +4,7 @@
 
 use crate::font::{
     Font, FontDescriptor, FontFamilyDescriptor, FontGroup, FontHandleMethods, FontRef,
+    FontVariation,
 };
 use crate::font_cache_thread::FontTemplateInfo;
 use crate::font_template::FontTemplateDescriptor;
@@ -33,6 +34,7 @@ pub trait FontSource {
         &mut self,
         key: webrender_api::FontKey,
         size: Au,
+        variations: Vec<FontVariation>,
     ) -> webrender_api::FontInstanceKey;
 
     fn font_template(
@@ -181,18 +183,26 @@ impl<S: FontSource> FontContext<S> {
         // font supports it) in the future.
         let actual_pt_size = match descriptor.variant {
             FontVariantCaps::SmallCaps => descriptor.pt_size.scale_by(SMALL_CAPS_SCALE_FACTOR),
-            FontVariantCaps::Normal => descriptor.pt_size,
+            _ => descriptor.pt_size,
         };
 
         let handle = FontHandle::new_from_template(
             &self.platform_handle,
             info.font_template,
             Some(actual_pt_size),
+            &descriptor.variations,
         )?;
 
-        let font_instance_key = self
-            .font_source
-            .get_font_instance(info.font_key, actual_pt_size);
+        // Only the FreeType backend applies variations when shaping, so don't let WebRender
+        // apply them elsewhere either, or glyphs would be drawn with mismatched advances.
+        let variations = if cfg!(any(target_os = "linux", target_os = "android")) {
+            descriptor.variations.clone()
+        } else {
+            vec![]
+        };
+        let font_instance_key =
+            self.font_source
+                .get_font_instance(info.font_key, actual_pt_size, variations);
         Ok(Font::new(
             handle,
             descriptor,
@@ -5,7 +5,6 @@
 use crate::font::FontHandleMethods;
 use crate::platform::font::FontHandle;
 use crate::platform::font_context::FontContextHandle;
-use crate::platform::font_template::FontTemplateData;
 use servo_atoms::Atom;
 use std::fmt::{Debug, Error, Formatter};
 use std::io::Error as IoError;
@@ -15,6 +14,8 @@ use style::computed_values::font_style::T as FontStyle;
 use style::properties::style_structs::Font as FontStyleStruct;
 use style::values::computed::font::FontWeight;
 
+pub use crate::platform::font_template::FontTemplateData;
+
 /// Describes how to select a font from a given family. This is very basic at the moment and needs
 /// to be expanded or refactored when we support more of the font styling parameters.
 ///
@@ -90,6 +91,8 @@ pub struct FontTemplate {
     // GWTODO: Add code path to unset the strong_ref for web fonts!
     strong_ref: Option<Arc<FontTemplateData>>,
     is_valid: bool,
+    /// The id of the font face created by script that this template was added for, if any.
+    font_face: Option<Atom>,
 }
 
 impl Debug for FontTemplate {
@@ -124,6 +127,7 @@ impl FontTemplate {
             weak_ref: maybe_weak_ref,
             strong_ref: maybe_strong_ref,
             is_valid: true,
+            font_face: None,
         })
     }
 
@@ -131,6 +135,15 @@ impl FontTemplate {
         &self.identifier
     }
 
+    /// The id of the font face created by script that this template was added for, if any.
+    pub fn font_face(&self) -> Option<&Atom> {
+        self.font_face.as_ref()
+    }
+
+    pub fn set_font_face(&mut self, font_face: Option<Atom>) {
+        self.font_face = font_face;
+    }
+
     /// Get the descriptor. Returns `None` when instantiating the data fails.
     pub fn descriptor(
         &mut self,
@@ -190,7 +203,7 @@ impl FontTemplate {
 
         let data = self.data().map_err(|_| ())?;
         let handle: Result<FontHandle, ()> =
-            FontHandleMethods::new_from_template(font_context, data, None);
+            FontHandleMethods::new_from_template(font_context, data, None, &[]);
         self.is_valid = handle.is_ok();
         let handle = handle?;
         self.descriptor = Some(FontTemplateDescriptor::new(
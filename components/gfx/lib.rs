@@ -26,5 +26,8 @@ pub mod font_template;
 #[allow(unsafe_code)]
 mod platform;
 
+// Printing
+pub mod print;
+
 // Text
 pub mod text;
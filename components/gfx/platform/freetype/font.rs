@@ -4,7 +4,7 @@
 
 use super::c_str_to_string;
 use crate::font::{FontHandleMethods, FontMetrics, FontTableMethods};
-use crate::font::{FontTableTag, FractionalPixel, GPOS, GSUB, KERN};
+use crate::font::{FontTableTag, FontVariation, FractionalPixel, GPOS, GSUB, KERN};
 use crate::platform::font_context::FontContextHandle;
 use crate::platform::font_template::FontTemplateData;
 use crate::text::glyph::GlyphId;
@@ -12,6 +12,7 @@ use crate::text::util::fixed_to_float;
 use app_units::Au;
 use freetype::freetype::FT_Sfnt_Tag;
 use freetype::freetype::{FT_Done_Face, FT_New_Face, FT_New_Memory_Face};
+use freetype::freetype::{FT_Error, FT_Fixed, FT_String};
 use freetype::freetype::{FT_F26Dot6, FT_Face, FT_FaceRec};
 use freetype::freetype::{FT_Get_Char_Index, FT_Get_Postscript_Name};
 use freetype::freetype::{FT_Get_Kerning, FT_Get_Sfnt_Table, FT_Load_Sfnt_Table};
@@ -23,9 +24,9 @@ use freetype::succeeded;
 use freetype::tt_os2::TT_OS2;
 use servo_atoms::Atom;
 use std::ffi::CString;
-use std::os::raw::{c_char, c_long};
+use std::os::raw::{c_char, c_long, c_void};
 use std::sync::Arc;
-use std::{mem, ptr};
+use std::{mem, ptr, slice};
 use style::computed_values::font_stretch::T as FontStretch;
 use style::computed_values::font_weight::T as FontWeight;
 use style::values::computed::font::FontStyle;
@@ -45,6 +46,39 @@ fn fixed_to_float_ft(f: i32) -> f64 {
     fixed_to_float(6, f)
 }
 
+// The multiple master API (ftmm.h) is not part of the freetype bindings, so
+// declare the parts of it that we need to support variable fonts here.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+struct FT_Var_Axis {
+    name: *mut FT_String,
+    minimum: FT_Fixed,
+    def: FT_Fixed,
+    maximum: FT_Fixed,
+    tag: FT_ULong,
+    strid: FT_UInt,
+}
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+struct FT_MM_Var {
+    num_axis: FT_UInt,
+    num_designs: FT_UInt,
+    num_namedstyles: FT_UInt,
+    axis: *mut FT_Var_Axis,
+    namedstyle: *mut c_void,
+}
+
+extern "C" {
+    fn FT_Get_MM_Var(face: FT_Face, amaster: *mut *mut FT_MM_Var) -> FT_Error;
+    fn FT_Done_MM_Var(library: FT_Library, amaster: *mut FT_MM_Var) -> FT_Error;
+    fn FT_Set_Var_Design_Coordinates(
+        face: FT_Face,
+        num_coords: FT_UInt,
+        coords: *mut FT_Fixed,
+    ) -> FT_Error;
+}
+
 #[derive(Debug)]
 pub struct FontTable {
     buffer: Vec<u8>,
@@ -92,6 +126,7 @@ fn create_face(
     lib: FT_Library,
     template: &FontTemplateData,
     pt_size: Option<Au>,
+    variations: &[FontVariation],
 ) -> Result<FT_Face, ()> {
     unsafe {
         let mut face: FT_Face = ptr::null_mut();
@@ -124,22 +159,58 @@ fn create_face(
             FontHandle::set_char_size(face, s).or(Err(()))?
         }
 
+        if !variations.is_empty() {
+            set_variations(lib, face, variations);
+        }
+
         Ok(face)
     }
 }
 
+/// Sets the design coordinates of a variable font from `font-variation-settings`. Axes that the
+/// font does not have are ignored, and values are clamped to the range that the font supports.
+///
+/// https://drafts.csswg.org/css-fonts-4/#font-variation-settings-def
+unsafe fn set_variations(lib: FT_Library, face: FT_Face, variations: &[FontVariation]) {
+    let mut mm_var: *mut FT_MM_Var = ptr::null_mut();
+    if !succeeded(FT_Get_MM_Var(face, &mut mm_var)) || mm_var.is_null() {
+        return;
+    }
+
+    let axes = slice::from_raw_parts((*mm_var).axis, (*mm_var).num_axis as usize);
+    let mut coords: Vec<FT_Fixed> = axes
+        .iter()
+        .map(|axis| {
+            // If the same axis is specified more than once, the last value wins.
+            match variations
+                .iter()
+                .rev()
+                .find(|variation| variation.tag as FT_ULong == axis.tag)
+            {
+                Some(variation) => (variation.value as FT_Fixed)
+                    .max(axis.minimum)
+                    .min(axis.maximum),
+                None => axis.def,
+            }
+        })
+        .collect();
+    FT_Set_Var_Design_Coordinates(face, coords.len() as FT_UInt, coords.as_mut_ptr());
+    FT_Done_MM_Var(lib, mm_var);
+}
+
 impl FontHandleMethods for FontHandle {
     fn new_from_template(
         fctx: &FontContextHandle,
         template: Arc<FontTemplateData>,
         pt_size: Option<Au>,
+        variations: &[FontVariation],
     ) -> Result<FontHandle, ()> {
         let ft_ctx: FT_Library = fctx.ctx.ctx;
         if ft_ctx.is_null() {
             return Err(());
         }
 
-        let face = create_face(ft_ctx, &template, pt_size)?;
+        let face = create_face(ft_ctx, &template, pt_size, variations)?;
 
         let mut handle = FontHandle {
             face: face,
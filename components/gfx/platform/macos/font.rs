@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::font::{
-    FontHandleMethods, FontMetrics, FontTableMethods, FontTableTag, FractionalPixel,
+    FontHandleMethods, FontMetrics, FontTableMethods, FontTableTag, FontVariation, FractionalPixel,
 };
 use crate::font::{GPOS, GSUB, KERN};
 use crate::platform::font_template::FontTemplateData;
@@ -162,10 +162,13 @@ impl fmt::Debug for CachedKernTable {
 }
 
 impl FontHandleMethods for FontHandle {
+    /// Font variations are not supported by this backend yet, so `font-variation-settings` is
+    /// ignored and the default instance of a variable font is used.
     fn new_from_template(
         _fctx: &FontContextHandle,
         template: Arc<FontTemplateData>,
         pt_size: Option<Au>,
+        _variations: &[FontVariation],
     ) -> Result<FontHandle, ()> {
         let size = match pt_size {
             Some(s) => s.to_f64_px(),
@@ -7,7 +7,7 @@
 // renderer moves to a sandboxed process.
 
 use crate::font::{FontHandleMethods, FontMetrics, FontTableMethods};
-use crate::font::{FontTableTag, FractionalPixel};
+use crate::font::{FontTableTag, FontVariation, FractionalPixel};
 use crate::platform::font_template::FontTemplateData;
 use crate::platform::windows::font_context::FontContextHandle;
 use crate::platform::windows::font_list::font_from_atom;
@@ -266,10 +266,13 @@ impl<T> Deref for Nondebug<T> {
 impl FontHandle {}
 
 impl FontHandleMethods for FontHandle {
+    /// Font variations are not supported by this backend yet, so `font-variation-settings` is
+    /// ignored and the default instance of a variable font is used.
     fn new_from_template(
         _: &FontContextHandle,
         template: Arc<FontTemplateData>,
         pt_size: Option<Au>,
+        _variations: &[FontVariation],
     ) -> Result<Self, ()> {
         let (info, face) = if let Some(ref raw_font) = template.bytes {
             let font_file = FontFile::new_from_data(Arc::new(raw_font.clone()));
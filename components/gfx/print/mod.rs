@@ -0,0 +1,191 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Printing of documents: the pages they are printed on, and their fragmentation into
+//! those pages.
+//!
+//! Documents are laid out as a single continuous page area as wide as the pages, and are
+//! then sliced into pages, honoring forced breaks (`break-before`, `break-after`) and
+//! trying not to split lines, replaced elements and boxes with `break-inside: avoid`.
+//!
+//! https://drafts.csswg.org/css-break/
+
+pub mod pdf;
+
+use app_units::Au;
+use euclid::{SideOffsets2D, Size2D};
+use std::ops::Range;
+use style::properties::PropertyDeclaration;
+use style::shared_lock::StylesheetGuards;
+use style::stylist::Stylist;
+use style::values::computed::BreakBetween;
+use style::values::generics::length::LengthPercentageOrAuto;
+use style::values::specified::length::LengthPercentage;
+use style::values::specified::page::PaperSize;
+use style::values::CSSFloat;
+use style_traits::CSSPixel;
+
+/// The margins of a page when no `@page` rule sets them: half an inch.
+const DEFAULT_PAGE_MARGIN: CSSFloat = 48.;
+
+/// The size and margins of the pages a document is printed on.
+#[derive(Clone, Debug)]
+pub struct PageGeometry {
+    /// The size of each page.
+    pub page_size: Size2D<f32, CSSPixel>,
+    /// The margins of each page, around the page area the document is painted in.
+    pub margins: SideOffsets2D<f32, CSSPixel>,
+}
+
+impl PageGeometry {
+    /// Compute the page geometry from the `@page` rules of the given stylist, in cascade
+    /// order. Pages are A4 sized unless a `size` descriptor says otherwise.
+    ///
+    /// Only absolute lengths and percentages are honored in the margins, since there is
+    /// no element to resolve other units against.
+    pub fn from_stylist(stylist: &Stylist, guards: &StylesheetGuards) -> PageGeometry {
+        let default_size = PaperSize::A4.size();
+        let mut page_size = default_size;
+        let mut margins = SideOffsets2D::new_all_same(DEFAULT_PAGE_MARGIN);
+
+        for (data, origin) in stylist.iter_extra_data_origins_rev() {
+            let guard = guards.for_origin(origin);
+            for rule in &data.pages {
+                let rule = rule.read_with(guard);
+                if let Some(ref size) = rule.size {
+                    page_size = size.resolve(default_size);
+                }
+                for declaration in rule.block.read_with(guard).declarations() {
+                    let (margin, value, basis) = match *declaration {
+                        PropertyDeclaration::MarginTop(ref value) => {
+                            (&mut margins.top, value, page_size.height)
+                        },
+                        PropertyDeclaration::MarginRight(ref value) => {
+                            (&mut margins.right, value, page_size.width)
+                        },
+                        PropertyDeclaration::MarginBottom(ref value) => {
+                            (&mut margins.bottom, value, page_size.height)
+                        },
+                        PropertyDeclaration::MarginLeft(ref value) => {
+                            (&mut margins.left, value, page_size.width)
+                        },
+                        _ => continue,
+                    };
+                    let resolved = match *value {
+                        LengthPercentageOrAuto::LengthPercentage(LengthPercentage::Length(
+                            ref length,
+                        )) => length.to_computed_pixel_length_without_context().ok(),
+                        LengthPercentageOrAuto::LengthPercentage(LengthPercentage::Percentage(
+                            percentage,
+                        )) => Some(percentage.0 * basis),
+                        _ => None,
+                    };
+                    if let Some(resolved) = resolved {
+                        *margin = resolved;
+                    }
+                }
+            }
+        }
+
+        PageGeometry { page_size, margins }
+    }
+
+    /// The size of the page area, where the document is laid out and painted.
+    pub fn content_size(&self) -> Size2D<f32, CSSPixel> {
+        Size2D::new(
+            (self.page_size.width - self.margins.horizontal()).max(1.),
+            (self.page_size.height - self.margins.vertical()).max(1.),
+        )
+    }
+}
+
+/// The places where a laid out document must, or should preferably not, be broken into
+/// pages.
+#[derive(Debug, Default)]
+pub struct PageBreaks {
+    /// The positions of forced page breaks.
+    forced: Vec<Au>,
+    /// The extents of content that should not be split across pages.
+    avoided: Vec<Range<Au>>,
+    /// The bottom of the lowest content.
+    document_height: Au,
+}
+
+impl PageBreaks {
+    pub fn new() -> PageBreaks {
+        PageBreaks::default()
+    }
+
+    /// Note a box with the given vertical extent and break properties.
+    pub fn add_box(
+        &mut self,
+        extent: Range<Au>,
+        break_before: BreakBetween,
+        break_after: BreakBetween,
+        avoid_break_inside: bool,
+    ) {
+        if is_forced_break(break_before) {
+            self.forced.push(extent.start);
+        }
+        if is_forced_break(break_after) {
+            self.forced.push(extent.end);
+        }
+        if avoid_break_inside {
+            self.add_monolithic(extent);
+        } else {
+            self.document_height = self.document_height.max(extent.end);
+        }
+    }
+
+    /// Note content with the given vertical extent that can't be split, like a line of
+    /// text or a replaced element.
+    pub fn add_monolithic(&mut self, extent: Range<Au>) {
+        self.document_height = self.document_height.max(extent.end);
+        self.avoided.push(extent);
+    }
+
+    /// Slice the document into pages with a page area of the given height, returning the
+    /// vertical extent of the document that goes on each page.
+    pub fn pages(&self, page_height: Au) -> Vec<Range<Au>> {
+        let page_height = page_height.max(Au::from_px(1));
+        let mut pages = vec![];
+        let mut start = Au(0);
+        while start < self.document_height || pages.is_empty() {
+            let mut end = start + page_height;
+            if let Some(forced) = self
+                .forced
+                .iter()
+                .filter(|position| **position > start && **position < end)
+                .min()
+            {
+                end = *forced;
+            }
+
+            // Move the break above any content it would split, as long as that leaves
+            // something on this page; content taller than a page is split anyway.
+            while let Some(top) = self
+                .avoided
+                .iter()
+                .filter(|range| range.start > start && range.start < end && range.end > end)
+                .map(|range| range.start)
+                .min()
+            {
+                end = top;
+            }
+
+            pages.push(start..end);
+            start = end;
+        }
+        pages
+    }
+}
+
+fn is_forced_break(value: BreakBetween) -> bool {
+    match value {
+        BreakBetween::Always | BreakBetween::Page | BreakBetween::Left | BreakBetween::Right => {
+            true
+        },
+        BreakBetween::Auto | BreakBetween::Avoid => false,
+    }
+}
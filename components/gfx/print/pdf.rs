@@ -0,0 +1,557 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Writing of paginated, vector PDF documents, for printing.
+//!
+//! Layout paints each page into a `PdfPage`, in CSS pixels with the origin at the top
+//! left of the document and the y axis pointing down, and the pages are then assembled
+//! into a document along with the fonts, images and graphics states they use.
+
+use crate::font_template::FontTemplateData;
+use crate::print::PageGeometry;
+use app_units::Au;
+use euclid::Size2D;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use net_traits::image::base::Image;
+use pixels::PixelFormat;
+use servo_atoms::Atom;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write as FmtWrite};
+use std::io::Write;
+use std::ops::Range;
+use std::sync::Arc;
+use style_traits::CSSPixel;
+use webrender_api::units::{LayoutPoint, LayoutRect, LayoutSideOffsets, LayoutSize};
+use webrender_api::{BorderStyle, ColorF, GlyphInstance, ImageKey, NormalBorder};
+
+/// The number of PDF points in a CSS pixel.
+pub const POINTS_PER_PX: f32 = 0.75;
+
+/// The fonts, images and graphics states that the pages of a document use.
+#[derive(Default)]
+pub struct PdfResources {
+    /// The resource names of the embedded fonts, by font file.
+    font_names: HashMap<Atom, String>,
+    /// The embedded font files, by resource name.
+    font_files: BTreeMap<String, Arc<FontTemplateData>>,
+    /// The resource names of the images.
+    image_names: HashMap<ImageKey, String>,
+    /// The embedded images, by resource name.
+    image_files: BTreeMap<String, Arc<Image>>,
+    /// The resource names of the graphics states setting the given alpha.
+    alpha_names: BTreeMap<u8, String>,
+}
+
+impl PdfResources {
+    pub fn new() -> PdfResources {
+        PdfResources::default()
+    }
+
+    fn font_name(&mut self, font: &Arc<FontTemplateData>) -> String {
+        let count = self.font_files.len();
+        let font_files = &mut self.font_files;
+        self.font_names
+            .entry(font.identifier.clone())
+            .or_insert_with(|| {
+                let name = format!("F{}", count + 1);
+                font_files.insert(name.clone(), font.clone());
+                name
+            })
+            .clone()
+    }
+
+    fn image_name(&mut self, key: ImageKey, image: &Arc<Image>) -> String {
+        let count = self.image_files.len();
+        let image_files = &mut self.image_files;
+        self.image_names
+            .entry(key)
+            .or_insert_with(|| {
+                let name = format!("Im{}", count + 1);
+                image_files.insert(name.clone(), image.clone());
+                name
+            })
+            .clone()
+    }
+
+    fn alpha_name(&mut self, alpha: u8) -> String {
+        let count = self.alpha_names.len();
+        self.alpha_names
+            .entry(alpha)
+            .or_insert_with(|| format!("GS{}", count + 1))
+            .clone()
+    }
+
+    /// Write the fonts, images and graphics states, returning the resource dictionary
+    /// that refers to them.
+    fn write(&self, writer: &mut PdfWriter) -> String {
+        let mut fonts = String::new();
+        for (name, font) in &self.font_files {
+            if let Some(id) = write_font(writer, name, &font.bytes()) {
+                let _ = write!(fonts, "/{} {} 0 R ", name, id);
+            }
+        }
+
+        let mut images = String::new();
+        for (name, image) in &self.image_files {
+            if let Some(id) = write_image(writer, image) {
+                let _ = write!(images, "/{} {} 0 R ", name, id);
+            }
+        }
+
+        let mut states = String::new();
+        for (alpha, name) in &self.alpha_names {
+            let alpha = Number(*alpha as f32 / 255.);
+            let _ = write!(states, "/{} << /ca {} /CA {} >> ", name, alpha, alpha);
+        }
+
+        format!(
+            "<< /Font << {}>> /XObject << {}>> /ExtGState << {}>> >>",
+            fonts, images, states
+        )
+    }
+}
+
+/// The content stream of a page, which shows a vertical extent of the document in the
+/// page area.
+pub struct PdfPage {
+    content: String,
+    area: LayoutRect,
+}
+
+impl PdfPage {
+    pub fn new(geometry: &PageGeometry, extent: &Range<Au>) -> PdfPage {
+        let top = extent.start.to_f32_px();
+        let height = (extent.end - extent.start).to_f32_px();
+        let width = geometry.content_size().width;
+        let area = LayoutRect::new(LayoutPoint::new(0., top), LayoutSize::new(width, height));
+
+        // Work in CSS pixels with the origin at the top left of the page, and the y axis
+        // pointing down, then move this page's extent of the document into the page area.
+        let mut content = String::new();
+        let _ = writeln!(
+            content,
+            "{} 0 0 {} 0 {} cm",
+            Number(POINTS_PER_PX),
+            Number(-POINTS_PER_PX),
+            Number(geometry.page_size.height * POINTS_PER_PX),
+        );
+        let _ = writeln!(
+            content,
+            "1 0 0 1 {} {} cm",
+            Number(geometry.margins.left),
+            Number(geometry.margins.top - top),
+        );
+        let _ = writeln!(content, "{} re W n", RectPath(&area));
+        PdfPage { content, area }
+    }
+
+    /// The part of the document shown on this page.
+    pub fn area(&self) -> &LayoutRect {
+        &self.area
+    }
+
+    /// Save the graphics state, which `restore` brings back.
+    pub fn save(&mut self) {
+        self.content.push_str("q\n");
+    }
+
+    pub fn restore(&mut self) {
+        self.content.push_str("Q\n");
+    }
+
+    /// Intersect the clip with the given path, like a `Quad` or a `RectPath`.
+    pub fn clip(&mut self, path: &dyn fmt::Display) {
+        let _ = writeln!(self.content, "{} W n", path);
+    }
+
+    /// Apply the given transformation matrix, in the `a b c d e f` form of PDF.
+    pub fn transform(&mut self, matrix: &dyn fmt::Display) {
+        let _ = writeln!(self.content, "{} cm", matrix);
+    }
+
+    /// Paint what follows with the given opacity, between zero and one.
+    pub fn set_alpha(&mut self, resources: &mut PdfResources, alpha: f32) {
+        let alpha = (alpha.max(0.).min(1.) * 255.).round() as u8;
+        if alpha < 255 {
+            let name = resources.alpha_name(alpha);
+            let _ = writeln!(self.content, "/{} gs", name);
+        }
+    }
+
+    pub fn fill_rect(&mut self, rect: &LayoutRect, color: &ColorF) {
+        let _ = writeln!(self.content, "{} {} re f", Fill(color), RectPath(rect));
+    }
+
+    pub fn fill_polygon(&mut self, points: &[LayoutPoint], color: &ColorF) {
+        let _ = write!(self.content, "{}", Fill(color));
+        for (index, point) in points.iter().enumerate() {
+            let operator = if index == 0 { "m" } else { "l" };
+            let _ = write!(
+                self.content,
+                " {} {} {}",
+                Number(point.x),
+                Number(point.y),
+                operator
+            );
+        }
+        self.content.push_str(" h f\n");
+    }
+
+    /// Show the given glyphs of a font, placed one by one where layout put them.
+    pub fn show_glyphs(
+        &mut self,
+        resources: &mut PdfResources,
+        font: &Arc<FontTemplateData>,
+        size: f32,
+        color: &ColorF,
+        glyphs: &[GlyphInstance],
+    ) {
+        let name = resources.font_name(font);
+        let _ = writeln!(
+            self.content,
+            "BT /{} {} Tf {}",
+            name,
+            Number(size),
+            Fill(color)
+        );
+        // The glyphs are flipped back up, since the y axis points down.
+        for glyph in glyphs {
+            let _ = writeln!(
+                self.content,
+                "1 0 0 -1 {} {} Tm <{:04X}> Tj",
+                Number(glyph.point.x),
+                Number(glyph.point.y),
+                glyph.index as u16,
+            );
+        }
+        self.content.push_str("ET\n");
+    }
+
+    pub fn draw_image(
+        &mut self,
+        resources: &mut PdfResources,
+        key: ImageKey,
+        image: &Arc<Image>,
+        bounds: &LayoutRect,
+    ) {
+        let name = resources.image_name(key, image);
+        let _ = writeln!(
+            self.content,
+            "{} 0 0 {} {} {} cm /{} Do",
+            Number(bounds.size.width),
+            Number(-bounds.size.height),
+            Number(bounds.origin.x),
+            Number(bounds.max_y()),
+            name,
+        );
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.content.into_bytes()
+    }
+}
+
+/// The visible sides of a border, as the color and the corners of the trapezoid that
+/// each one covers.
+pub fn border_sides(
+    bounds: &LayoutRect,
+    widths: &LayoutSideOffsets,
+    border: &NormalBorder,
+) -> Vec<(ColorF, [LayoutPoint; 4])> {
+    let inner = LayoutRect::new(
+        LayoutPoint::new(bounds.origin.x + widths.left, bounds.origin.y + widths.top),
+        LayoutSize::new(
+            bounds.size.width - widths.horizontal(),
+            bounds.size.height - widths.vertical(),
+        ),
+    );
+    let (outer, inner) = (corners(bounds), corners(&inner));
+    // Each side is the trapezoid between two adjacent outer and inner corners.
+    let sides = [
+        (&border.top, widths.top, 0, 1),
+        (&border.right, widths.right, 1, 2),
+        (&border.bottom, widths.bottom, 2, 3),
+        (&border.left, widths.left, 3, 0),
+    ];
+    sides
+        .iter()
+        .filter(|&&(side, width, _, _)| {
+            width > 0. &&
+                side.color.a > 0. &&
+                side.style != BorderStyle::None &&
+                side.style != BorderStyle::Hidden
+        })
+        .map(|&(side, _, start, end)| {
+            (
+                side.color,
+                [outer[start], outer[end], inner[end], inner[start]],
+            )
+        })
+        .collect()
+}
+
+/// The corners of a rectangle, clockwise from the top left.
+pub fn corners(rect: &LayoutRect) -> [LayoutPoint; 4] {
+    [
+        rect.origin,
+        LayoutPoint::new(rect.max_x(), rect.origin.y),
+        LayoutPoint::new(rect.max_x(), rect.max_y()),
+        LayoutPoint::new(rect.origin.x, rect.max_y()),
+    ]
+}
+
+/// Assemble a PDF document from its pages.
+pub fn write_document(
+    page_size: &Size2D<f32, CSSPixel>,
+    pages: Vec<PdfPage>,
+    resources: &PdfResources,
+) -> Vec<u8> {
+    let mut writer = PdfWriter::new();
+    let catalog_id = writer.reserve();
+    let page_tree_id = writer.reserve();
+    let resources_id = writer.reserve();
+
+    let page_ids: Vec<usize> = pages
+        .into_iter()
+        .map(|page| {
+            let content_id = writer.add_stream("", &page.finish());
+            writer.add(format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] \
+                 /Resources {} 0 R /Contents {} 0 R >>",
+                page_tree_id,
+                Number(page_size.width * POINTS_PER_PX),
+                Number(page_size.height * POINTS_PER_PX),
+                resources_id,
+                content_id,
+            ))
+        })
+        .collect();
+
+    let resource_dictionary = resources.write(&mut writer);
+    writer.set(resources_id, resource_dictionary);
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+    writer.set(
+        page_tree_id,
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            page_ids.len()
+        ),
+    );
+    writer.set(
+        catalog_id,
+        format!("<< /Type /Catalog /Pages {} 0 R >>", page_tree_id),
+    );
+    writer.finish(catalog_id)
+}
+
+/// Embed a TrueType or OpenType font, addressing its glyphs directly by index.
+fn write_font(writer: &mut PdfWriter, name: &str, data: &[u8]) -> Option<usize> {
+    let (subtype, file_key, file_subtype) = match data.get(0..4)? {
+        b"\x00\x01\x00\x00" | b"true" => ("CIDFontType2", "FontFile2", ""),
+        b"OTTO" => ("CIDFontType0", "FontFile3", "/Subtype /OpenType"),
+        _ => {
+            warn!("Can't embed font {} in a PDF document.", name);
+            return None;
+        },
+    };
+
+    let file_id = writer.add_stream(file_subtype, data);
+    let descriptor_id = writer.add(format!(
+        "<< /Type /FontDescriptor /FontName /{} /Flags 4 /FontBBox [0 -250 1000 1000] \
+         /ItalicAngle 0 /Ascent 1000 /Descent -250 /CapHeight 700 /StemV 80 /{} {} 0 R >>",
+        name, file_key, file_id
+    ));
+    let glyph_map = if subtype == "CIDFontType2" {
+        "/CIDToGIDMap /Identity"
+    } else {
+        ""
+    };
+    let descendant_id = writer.add(format!(
+        "<< /Type /Font /Subtype /{} /BaseFont /{} \
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+         /FontDescriptor {} 0 R /DW 0 {} >>",
+        subtype, name, descriptor_id, glyph_map
+    ));
+    Some(writer.add(format!(
+        "<< /Type /Font /Subtype /Type0 /BaseFont /{} /Encoding /Identity-H \
+         /DescendantFonts [{} 0 R] >>",
+        name, descendant_id
+    )))
+}
+
+/// Embed an image, with its alpha channel as a soft mask.
+fn write_image(writer: &mut PdfWriter, image: &Image) -> Option<usize> {
+    let pixel_count = (image.width * image.height) as usize;
+    let mut color = Vec::with_capacity(pixel_count * 3);
+    let mut alpha = Vec::with_capacity(pixel_count);
+    match image.format {
+        PixelFormat::BGRA8 => {
+            for pixel in image.bytes.chunks(4) {
+                color.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+                alpha.push(pixel[3]);
+            }
+        },
+        PixelFormat::RGBA8 => {
+            for pixel in image.bytes.chunks(4) {
+                color.extend_from_slice(&pixel[0..3]);
+                alpha.push(pixel[3]);
+            }
+        },
+        PixelFormat::RGB8 => color.extend_from_slice(&image.bytes),
+        PixelFormat::K8 | PixelFormat::KA8 => {
+            warn!("Can't embed {:?} images in a PDF document.", image.format);
+            return None;
+        },
+    }
+
+    let mask = if alpha.iter().any(|alpha| *alpha != 255) {
+        let mask_id = writer.add_stream(
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} \
+                 /ColorSpace /DeviceGray /BitsPerComponent 8",
+                image.width, image.height
+            ),
+            &alpha,
+        );
+        format!("/SMask {} 0 R", mask_id)
+    } else {
+        String::new()
+    };
+    Some(writer.add_stream(
+        &format!(
+            "/Type /XObject /Subtype /Image /Width {} /Height {} \
+             /ColorSpace /DeviceRGB /BitsPerComponent 8 {}",
+            image.width, image.height, mask
+        ),
+        &color,
+    ))
+}
+
+/// The operands of the PDF `re` operator for a rectangle.
+pub struct RectPath<'a>(pub &'a LayoutRect);
+
+impl<'a> fmt::Display for RectPath<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            Number(self.0.origin.x),
+            Number(self.0.origin.y),
+            Number(self.0.size.width),
+            Number(self.0.size.height)
+        )
+    }
+}
+
+/// The PDF operator that sets the fill color.
+struct Fill<'a>(&'a ColorF);
+
+impl<'a> fmt::Display for Fill<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} rg",
+            Number(self.0.r),
+            Number(self.0.g),
+            Number(self.0.b)
+        )
+    }
+}
+
+/// A number in a PDF document, which can't use exponents. Values are clamped, since items
+/// can be as large as the whole canvas.
+pub struct Number(pub f32);
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = self.0.max(-1e7).min(1e7);
+        if value.fract() == 0. {
+            return write!(f, "{}", value as i32);
+        }
+        let formatted = format!("{:.3}", value);
+        f.write_str(formatted.trim_end_matches('0').trim_end_matches('.'))
+    }
+}
+
+/// Assembles the objects of a PDF document.
+struct PdfWriter {
+    objects: Vec<String>,
+    streams: HashMap<usize, Vec<u8>>,
+}
+
+impl PdfWriter {
+    fn new() -> PdfWriter {
+        PdfWriter {
+            objects: vec![],
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Reserve an object number, for an object to be set later.
+    fn reserve(&mut self) -> usize {
+        self.objects.push(String::new());
+        self.objects.len()
+    }
+
+    fn set(&mut self, id: usize, object: String) {
+        self.objects[id - 1] = object;
+    }
+
+    fn add(&mut self, object: String) -> usize {
+        self.objects.push(object);
+        self.objects.len()
+    }
+
+    /// Add a compressed stream, with the given extra dictionary entries.
+    fn add_stream(&mut self, entries: &str, data: &[u8]) -> usize {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        let compressed = encoder
+            .write_all(data)
+            .and_then(|_| encoder.finish())
+            .expect("Compressing to memory can't fail");
+        let id = self.add(format!(
+            "<< {} /Filter /FlateDecode /Length {} >>",
+            entries,
+            compressed.len()
+        ));
+        self.streams.insert(id, compressed);
+        id
+    }
+
+    fn finish(self, catalog_id: usize) -> Vec<u8> {
+        let mut output = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(self.objects.len());
+        for (index, object) in self.objects.iter().enumerate() {
+            let id = index + 1;
+            offsets.push(output.len());
+            let _ = write!(output, "{} 0 obj\n{}\n", id, object);
+            if let Some(stream) = self.streams.get(&id) {
+                output.extend_from_slice(b"stream\n");
+                output.extend_from_slice(stream);
+                output.extend_from_slice(b"\nendstream\n");
+            }
+            output.extend_from_slice(b"endobj\n");
+        }
+
+        let xref_offset = output.len();
+        let _ = write!(
+            output,
+            "xref\n0 {}\n0000000000 65535 f \n",
+            self.objects.len() + 1
+        );
+        for offset in offsets {
+            let _ = write!(output, "{:010} 00000 n \n", offset);
+        }
+        let _ = write!(
+            output,
+            "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.objects.len() + 1,
+            catalog_id,
+            xref_offset
+        );
+        output
+    }
+}
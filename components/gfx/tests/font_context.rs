@@ -5,6 +5,7 @@
 use app_units::Au;
 use gfx::font::{
     fallback_font_families, FontDescriptor, FontFamilyDescriptor, FontFamilyName, FontSearchScope,
+    FontVariation,
 };
 use gfx::font_cache_thread::{FontTemplateInfo, FontTemplates};
 use gfx::font_context::{FontContext, FontContextHandle, FontSource};
@@ -22,7 +23,11 @@ use style::properties::style_structs::Font as FontStyleStruct;
 use style::values::computed::font::{
     FamilyName, FontFamily, FontFamilyList, FontFamilyNameSyntax, FontSize,
 };
+use style::values::computed::font::{FontFeatureSettings, FontVariationSettings};
 use style::values::computed::font::{FontStretch, FontWeight, SingleFontFamily};
+use style::values::computed::font::{
+    FontVariantAlternates, FontVariantLigatures, FontVariantNumeric,
+};
 use style::values::generics::font::FontStyle;
 
 struct TestFontSource {
@@ -72,6 +77,7 @@ impl FontSource for TestFontSource {
         &mut self,
         _key: webrender_api::FontKey,
         _size: Au,
+        _variations: Vec<FontVariation>,
     ) -> webrender_api::FontInstanceKey {
         webrender_api::FontInstanceKey(webrender_api::IdNamespace(0), 0)
     }
@@ -102,6 +108,11 @@ fn style() -> FontStyleStruct {
         font_weight: FontWeight::normal(),
         font_size: FontSize::medium(),
         font_stretch: FontStretch::hundred(),
+        font_variant_alternates: FontVariantAlternates::get_initial_value(),
+        font_variant_ligatures: FontVariantLigatures::empty(),
+        font_variant_numeric: FontVariantNumeric::empty(),
+        font_feature_settings: FontFeatureSettings::normal(),
+        font_variation_settings: FontVariationSettings::normal(),
         hash: 0,
     };
     style.compute_font_hash();
@@ -234,6 +245,7 @@ fn test_font_template_is_cached() {
         },
         variant: FontVariantCaps::Normal,
         pt_size: Au(10),
+        variations: vec![],
     };
 
     let family_descriptor =
@@ -256,3 +268,45 @@ fn test_font_template_is_cached() {
         "we should only have fetched the template data from the cache thread once"
     );
 }
+
+#[test]
+fn test_font_is_cached_by_variations() {
+    let source = TestFontSource::new();
+    let count = source.find_font_count.clone();
+    let mut context = FontContext::new(source);
+
+    let mut font_descriptor = FontDescriptor {
+        template_descriptor: FontTemplateDescriptor {
+            weight: FontWeight::normal(),
+            stretch: FontStretch::hundred(),
+            style: FontStyle::Normal,
+        },
+        variant: FontVariantCaps::Normal,
+        pt_size: Au(10),
+        variations: vec![],
+    };
+
+    let family_descriptor =
+        FontFamilyDescriptor::new(FontFamilyName::from("CSSTest Basic"), FontSearchScope::Any);
+
+    let font1 = context.font(&font_descriptor, &family_descriptor).unwrap();
+
+    font_descriptor.variations = vec![FontVariation::new(u32::from_be_bytes(*b"wght"), 700.)];
+    let font2 = context.font(&font_descriptor, &family_descriptor).unwrap();
+    let font3 = context.font(&font_descriptor, &family_descriptor).unwrap();
+
+    assert!(
+        !Rc::ptr_eq(&font1, &font2),
+        "fonts with different variations should not be shared"
+    );
+    assert!(
+        Rc::ptr_eq(&font2, &font3),
+        "fonts with the same variations should be shared"
+    );
+
+    assert_eq!(
+        count.get(),
+        1,
+        "we should only have fetched the template data from the cache thread once"
+    );
+}
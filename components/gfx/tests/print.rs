@@ -0,0 +1,119 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use app_units::Au;
+use euclid::{SideOffsets2D, Size2D};
+use gfx::print::pdf::{self, PdfPage, PdfResources};
+use gfx::print::{PageBreaks, PageGeometry};
+use std::ops::Range;
+use style::values::computed::BreakBetween;
+
+fn px(start: i32, end: i32) -> Range<Au> {
+    Au::from_px(start)..Au::from_px(end)
+}
+
+fn add_block(breaks: &mut PageBreaks, extent: Range<Au>) {
+    breaks.add_box(extent, BreakBetween::Auto, BreakBetween::Auto, false);
+}
+
+fn geometry() -> PageGeometry {
+    PageGeometry {
+        page_size: Size2D::new(800., 1000.),
+        margins: SideOffsets2D::new(50., 100., 50., 100.),
+    }
+}
+
+#[test]
+fn test_empty_document_has_one_page() {
+    let breaks = PageBreaks::new();
+    assert_eq!(breaks.pages(Au::from_px(100)), vec![px(0, 100)]);
+}
+
+#[test]
+fn test_document_is_sliced_into_pages() {
+    let mut breaks = PageBreaks::new();
+    add_block(&mut breaks, px(0, 250));
+    assert_eq!(
+        breaks.pages(Au::from_px(100)),
+        vec![px(0, 100), px(100, 200), px(200, 300)]
+    );
+}
+
+#[test]
+fn test_forced_breaks() {
+    let mut breaks = PageBreaks::new();
+    add_block(&mut breaks, px(0, 30));
+    breaks.add_box(px(30, 60), BreakBetween::Page, BreakBetween::Auto, false);
+    breaks.add_box(px(60, 90), BreakBetween::Auto, BreakBetween::Always, false);
+    add_block(&mut breaks, px(90, 120));
+    assert_eq!(
+        breaks.pages(Au::from_px(100)),
+        vec![px(0, 30), px(30, 90), px(90, 190)]
+    );
+}
+
+#[test]
+fn test_avoid_break_values_do_not_force_breaks() {
+    let mut breaks = PageBreaks::new();
+    breaks.add_box(px(0, 50), BreakBetween::Avoid, BreakBetween::Avoid, false);
+    breaks.add_box(px(50, 100), BreakBetween::Auto, BreakBetween::Auto, false);
+    assert_eq!(breaks.pages(Au::from_px(100)), vec![px(0, 100)]);
+}
+
+#[test]
+fn test_monolithic_content_moves_to_next_page() {
+    let mut breaks = PageBreaks::new();
+    add_block(&mut breaks, px(0, 200));
+    breaks.add_monolithic(px(80, 120));
+    assert_eq!(
+        breaks.pages(Au::from_px(100)),
+        vec![px(0, 80), px(80, 180), px(180, 280)]
+    );
+}
+
+#[test]
+fn test_break_inside_avoid() {
+    let mut breaks = PageBreaks::new();
+    add_block(&mut breaks, px(0, 60));
+    breaks.add_box(px(60, 130), BreakBetween::Auto, BreakBetween::Auto, true);
+    assert_eq!(breaks.pages(Au::from_px(100)), vec![px(0, 60), px(60, 160)]);
+}
+
+#[test]
+fn test_content_taller_than_a_page_is_split() {
+    let mut breaks = PageBreaks::new();
+    breaks.add_monolithic(px(0, 250));
+    assert_eq!(
+        breaks.pages(Au::from_px(100)),
+        vec![px(0, 100), px(100, 200), px(200, 300)]
+    );
+}
+
+#[test]
+fn test_page_area() {
+    let size = geometry().content_size();
+    assert_eq!(size.width, 600.);
+    assert_eq!(size.height, 900.);
+
+    let tiny = PageGeometry {
+        page_size: Size2D::new(100., 100.),
+        margins: SideOffsets2D::new_all_same(60.),
+    };
+    assert_eq!(tiny.content_size(), Size2D::new(1., 1.));
+}
+
+#[test]
+fn test_write_document() {
+    let geometry = geometry();
+    let pages = vec![
+        PdfPage::new(&geometry, &px(0, 900)),
+        PdfPage::new(&geometry, &px(900, 1200)),
+    ];
+    let document = pdf::write_document(&geometry.page_size, pages, &PdfResources::new());
+    let text = String::from_utf8_lossy(&document);
+    assert!(text.starts_with("%PDF-1.7\n"));
+    assert!(text.ends_with("%%EOF\n"));
+    assert!(text.contains("/Type /Pages /Kids [5 0 R 7 0 R] /Count 2"));
+    assert!(text.contains("/MediaBox [0 0 600 750]"));
+}
@@ -4,7 +4,8 @@
 
 #![allow(unsafe_code)]
 
-use crate::font::{Font, FontTableMethods, FontTableTag, ShapingFlags, ShapingOptions, KERN};
+use crate::font::{Font, FontTableMethods, FontTableTag, ShapingFlags, ShapingOptions};
+use crate::font::{KERN, LIGA};
 use crate::platform::font::FontTable;
 use crate::text::glyph::{ByteIndex, GlyphData, GlyphId, GlyphStore};
 use crate::text::shaping::ShaperMethods;
@@ -28,6 +29,7 @@ use harfbuzz_sys::hb_font_funcs_set_nominal_glyph_func;
 use harfbuzz_sys::hb_font_set_funcs;
 use harfbuzz_sys::hb_font_set_ppem;
 use harfbuzz_sys::hb_font_set_scale;
+use harfbuzz_sys::hb_font_set_variations;
 use harfbuzz_sys::hb_glyph_info_t;
 use harfbuzz_sys::hb_glyph_position_t;
 use harfbuzz_sys::{hb_blob_create, hb_face_create_for_tables};
@@ -36,13 +38,12 @@ use harfbuzz_sys::{hb_buffer_get_glyph_infos, hb_shape};
 use harfbuzz_sys::{hb_buffer_set_direction, hb_buffer_set_script};
 use harfbuzz_sys::{hb_buffer_t, hb_codepoint_t, hb_font_funcs_t};
 use harfbuzz_sys::{hb_face_t, hb_font_t};
-use harfbuzz_sys::{hb_position_t, hb_tag_t};
+use harfbuzz_sys::{hb_position_t, hb_tag_t, hb_variation_t};
 use harfbuzz_sys::{HB_DIRECTION_LTR, HB_DIRECTION_RTL, HB_MEMORY_MODE_READONLY};
 use std::os::raw::{c_char, c_int, c_uint, c_void};
 use std::{char, cmp, ptr};
 
 const NO_GLYPH: i32 = -1;
-const LIGA: u32 = ot_tag!('l', 'i', 'g', 'a');
 
 pub struct ShapedGlyphData {
     count: usize,
@@ -174,6 +175,21 @@ impl Shaper {
                 None,
             );
 
+            // Set the variation axes, so that HarfBuzz applies the matching deltas from the
+            // GPOS and GDEF tables.
+            let variations: Vec<hb_variation_t> = (*font)
+                .descriptor
+                .variations
+                .iter()
+                .map(|variation| hb_variation_t {
+                    tag: variation.tag,
+                    value: variation.value(),
+                })
+                .collect();
+            if !variations.is_empty() {
+                hb_font_set_variations(hb_font, variations.as_ptr(), variations.len() as c_uint);
+            }
+
             Shaper {
                 hb_face: hb_face,
                 hb_font: hb_font,
@@ -239,6 +255,14 @@ impl ShaperMethods for Shaper {
                     end: hb_buffer_get_length(hb_buffer),
                 })
             }
+            for feature in &options.features {
+                features.push(hb_feature_t {
+                    tag: feature.tag,
+                    value: feature.value,
+                    start: 0,
+                    end: hb_buffer_get_length(hb_buffer),
+                })
+            }
 
             hb_shape(
                 self.hb_font,
@@ -70,6 +70,8 @@ pub enum FragmentType {
     BeforePseudoContent,
     /// A StackingContext created to contain ::after pseudo-element content.
     AfterPseudoContent,
+    /// A StackingContext created to contain the ::backdrop of an element in the top layer.
+    BackdropPseudoContent,
 }
 
 /// The next ID that will be used for a special scroll root id.
@@ -32,7 +32,7 @@ use crate::fragment::{
 use crate::fragment::{
     TableColumnFragmentInfo, UnscannedTextFragmentInfo, WhitespaceStrippingResult,
 };
-use crate::inline::{InlineFlow, InlineFragmentNodeFlags, InlineFragmentNodeInfo};
+use crate::inline::{FirstLineStyle, InlineFlow, InlineFragmentNodeFlags, InlineFragmentNodeInfo};
 use crate::linked_list::prepend_from;
 use crate::list_item::{ListItemFlow, ListStyleTypeContent};
 use crate::multicol::{MulticolColumnFlow, MulticolFlow};
@@ -493,6 +493,30 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
             return;
         }
 
+        // The first line and letter of the block, if any, are in its first inline formatting
+        // context.
+        let element = match node.get_pseudo_element_type() {
+            PseudoElementType::Normal if flow.base().children.is_empty() => node.as_element(),
+            _ => None,
+        };
+        let first_letter_style = element.and_then(|element| element.first_letter_style());
+        if let Some(ref first_letter_style) = first_letter_style {
+            split_first_letter(
+                &mut fragments.fragments,
+                node.opaque(),
+                first_letter_style.clone(),
+            );
+        }
+        let first_line = element.and_then(|element| element.first_line_style());
+        let first_line_style = first_line.map(|first_line| {
+            self.first_line_style(
+                node,
+                first_line,
+                first_letter_style.as_ref(),
+                &fragments.fragments,
+            )
+        });
+
         // Build a list of all the inline-block fragments before fragments is moved.
         let mut inline_block_flows = vec![];
         for fragment in &fragments.fragments {
@@ -516,6 +540,7 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
         let scanned_fragments =
             with_thread_local_font_context(self.layout_context, |font_context| {
                 TextRunScanner::new().scan_for_runs(
+                    self.layout_context,
                     font_context,
                     mem::replace(&mut fragments.fragments, LinkedList::new()),
                 )
@@ -553,6 +578,7 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
                     inline_flow
                         .minimum_line_metrics(font_context, &node.style(self.style_context()))
                 });
+            inline_flow.first_line_style = first_line_style;
         }
 
         inline_flow_ref.finish();
@@ -563,6 +589,64 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
         )
     }
 
+    /// Computes the styles on the `::first-line` of the block `node` for the text and inline boxes
+    /// of `fragments`, its first inline formatting context.
+    fn first_line_style(
+        &self,
+        node: &ConcreteThreadSafeLayoutNode,
+        first_line: ServoArc<ComputedValues>,
+        first_letter: Option<&ServoArc<ComputedValues>>,
+        fragments: &LinkedList<Fragment>,
+    ) -> FirstLineStyle {
+        let context = self.style_context();
+        let block_style = node.style(context);
+        let mut first_line_style = FirstLineStyle::new(block_style.clone(), first_line.clone());
+        if let Some(first_letter) = first_letter {
+            style_on_first_line::<ConcreteThreadSafeLayoutNode::ConcreteElement>(
+                context,
+                &mut first_line_style,
+                Some(&PseudoElement::FirstLetter),
+                first_letter,
+                &first_line,
+                &block_style,
+            );
+        }
+        for fragment in fragments {
+            match fragment.specific {
+                SpecificFragmentInfo::UnscannedText(..) |
+                SpecificFragmentInfo::GeneratedContent(..) => {},
+                _ => continue,
+            }
+
+            // The inline boxes the text is in inherit from the `::first-line` too, from the
+            // outermost one in.
+            let mut parent = first_line.clone();
+            let mut parent_ignoring_first_line = block_style.clone();
+            if let Some(ref inline_context) = fragment.inline_context {
+                for inline_node in inline_context.nodes.iter().rev() {
+                    parent = style_on_first_line::<ConcreteThreadSafeLayoutNode::ConcreteElement>(
+                        context,
+                        &mut first_line_style,
+                        None,
+                        &inline_node.style,
+                        &parent,
+                        &parent_ignoring_first_line,
+                    );
+                    parent_ignoring_first_line = inline_node.style.clone();
+                }
+            }
+            style_on_first_line::<ConcreteThreadSafeLayoutNode::ConcreteElement>(
+                context,
+                &mut first_line_style,
+                None,
+                &fragment.style,
+                &parent,
+                &parent_ignoring_first_line,
+            );
+        }
+        first_line_style
+    }
+
     fn build_block_flow_using_construction_result_of_child(
         &mut self,
         flow: &mut FlowRef,
@@ -814,7 +898,14 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
                         &context.guards,
                         &PseudoElement::ServoInputText,
                         &style,
-                    )
+                    );
+                // The placeholder text, if shown, is styled by `::placeholder`.
+                if let Some(placeholder_style) = node
+                    .as_element()
+                    .and_then(|element| element.placeholder_style(context, &style))
+                {
+                    style = placeholder_style;
+                }
             }
 
             self.create_fragments_for_node_text_content(&mut fragments, node, &style)
@@ -1500,7 +1591,26 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
         flotation: Float,
     ) -> ConstructionResult {
         let flotation = FloatKind::from_property(flotation);
-        let marker_fragments = match node.style(self.style_context()).get_list().list_style_image {
+
+        // The marker of a pseudo-element is styled like the pseudo-element itself.
+        let marker_style = match node.as_element() {
+            Some(element) if node.get_pseudo_element_type() == PseudoElementType::Normal => {
+                element.marker_style(self.style_context())
+            },
+            _ => node.style(self.style_context()),
+        };
+        let new_marker_fragment = |specific| {
+            Fragment::from_opaque_node_and_style(
+                node.opaque(),
+                node.get_pseudo_element_type(),
+                marker_style.clone(),
+                node.selected_style(),
+                node.restyle_damage(),
+                specific,
+            )
+        };
+
+        let marker_fragments = match marker_style.get_list().list_style_image {
             ImageUrlOrNone::Url(ref url_value) => {
                 let image_info = Box::new(ImageFragmentInfo::new(
                     url_value.url().map(|u| u.clone()),
@@ -1508,37 +1618,32 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
                     node,
                     &self.layout_context,
                 ));
-                vec![Fragment::new(
-                    node,
-                    SpecificFragmentInfo::Image(image_info),
-                    self.layout_context,
-                )]
+                vec![new_marker_fragment(SpecificFragmentInfo::Image(image_info))]
             },
             ImageUrlOrNone::None => match ListStyleTypeContent::from_list_style_type(
-                node.style(self.style_context()).get_list().list_style_type,
+                marker_style.get_list().list_style_type,
             ) {
                 ListStyleTypeContent::None => Vec::new(),
                 ListStyleTypeContent::StaticText(ch) => {
                     let text = format!("{}\u{a0}", ch);
                     let mut unscanned_marker_fragments = LinkedList::new();
-                    unscanned_marker_fragments.push_back(Fragment::new(
-                        node,
+                    unscanned_marker_fragments.push_back(new_marker_fragment(
                         SpecificFragmentInfo::UnscannedText(Box::new(
                             UnscannedTextFragmentInfo::new(Box::<str>::from(text), None),
                         )),
-                        self.layout_context,
                     ));
                     let marker_fragments =
                         with_thread_local_font_context(self.layout_context, |mut font_context| {
-                            TextRunScanner::new()
-                                .scan_for_runs(&mut font_context, unscanned_marker_fragments)
+                            TextRunScanner::new().scan_for_runs(
+                                self.layout_context,
+                                &mut font_context,
+                                unscanned_marker_fragments,
+                            )
                         });
                     marker_fragments.fragments
                 },
-                ListStyleTypeContent::GeneratedContent(info) => vec![Fragment::new(
-                    node,
+                ListStyleTypeContent::GeneratedContent(info) => vec![new_marker_fragment(
                     SpecificFragmentInfo::GeneratedContent(info),
-                    self.layout_context,
                 )],
             },
         };
@@ -2018,6 +2123,7 @@ where
             PseudoElementType::After => &mut data.after_flow_construction_result,
             PseudoElementType::DetailsSummary => &mut data.details_summary_flow_construction_result,
             PseudoElementType::DetailsContent => &mut data.details_content_flow_construction_result,
+            PseudoElementType::Backdrop => &mut data.backdrop_flow_construction_result,
             PseudoElementType::Normal => &mut data.flow_construction_result,
         }
     }
@@ -2129,6 +2235,117 @@ pub fn strip_ignorable_whitespace_from_end(this: &mut LinkedList<Fragment>) {
     this.append(&mut trailing_fragments_consisting_of_solely_bidi_control_characters);
 }
 
+/// Returns the style on the first line of something styled with `style`, computing it if needed.
+/// `parent` and `parent_ignoring_first_line` are the styles of its parent on the first line and on
+/// the other lines.
+fn style_on_first_line<E: TElement>(
+    context: &SharedStyleContext,
+    first_line_style: &mut FirstLineStyle,
+    pseudo: Option<&PseudoElement>,
+    style: &ServoArc<ComputedValues>,
+    parent: &ServoArc<ComputedValues>,
+    parent_ignoring_first_line: &ServoArc<ComputedValues>,
+) -> ServoArc<ComputedValues> {
+    if let Some(style) = first_line_style.on_first_line(style) {
+        return style.clone();
+    }
+    let style_on_first_line = context.stylist.style_for_first_line_descendant::<E>(
+        &context.guards,
+        pseudo,
+        style,
+        parent,
+        parent_ignoring_first_line,
+    );
+    first_line_style
+        .styles
+        .push((style.clone(), style_on_first_line.clone()));
+    style_on_first_line
+}
+
+/// Whether the given fragment is unscanned text directly inside the block `block_node`, rather
+/// than inside an inline element or generated by the block itself, like its marker.
+fn is_text_directly_inside_block(fragment: &Fragment, block_node: OpaqueNode) -> bool {
+    match fragment.specific {
+        SpecificFragmentInfo::UnscannedText(..) => {
+            fragment.inline_context.is_none() && fragment.node != block_node
+        },
+        _ => false,
+    }
+}
+
+/// Splits the first letter off the start of a list of fragments into its own fragment, styled
+/// with `first_letter_style`. This is only done if the first fragment is text directly inside the
+/// block `block_node`.
+///
+/// https://drafts.csswg.org/css-pseudo/#first-letter-pseudo
+fn split_first_letter(
+    this: &mut LinkedList<Fragment>,
+    block_node: OpaqueNode,
+    first_letter_style: ServoArc<ComputedValues>,
+) {
+    let first_fragment = match this.front_mut() {
+        Some(fragment) => fragment,
+        None => return,
+    };
+    if !is_text_directly_inside_block(first_fragment, block_node) {
+        return;
+    }
+
+    let text = match first_fragment.specific {
+        SpecificFragmentInfo::UnscannedText(ref mut info) if info.selection.is_none() => {
+            &mut info.text
+        },
+        _ => return,
+    };
+    let length = match first_letter_length(text) {
+        Some(length) => length,
+        None => return,
+    };
+    if length == text.len() {
+        first_fragment.style = first_letter_style;
+        return;
+    }
+
+    let first_letter = Box::<str>::from(&text[..length]);
+    *text = Box::<str>::from(&text[length..]);
+    let first_letter_fragment = first_fragment.create_similar_anonymous_fragment(
+        first_letter_style,
+        SpecificFragmentInfo::UnscannedText(Box::new(UnscannedTextFragmentInfo::new(
+            first_letter,
+            None,
+        ))),
+    );
+    this.push_front(first_letter_fragment);
+}
+
+/// Returns the length in bytes of the first letter of `text`, including the punctuation around it,
+/// or `None` if `text` doesn't start with a letter.
+fn first_letter_length(text: &str) -> Option<usize> {
+    fn is_punctuation(ch: char) -> bool {
+        match ch {
+            '\u{a1}' | '\u{ab}' | '\u{bb}' | '\u{bf}' | '\u{2018}'..='\u{201f}' => true,
+            _ => ch.is_ascii_punctuation(),
+        }
+    }
+
+    let mut chars = text.char_indices().peekable();
+    while chars.peek().map_or(false, |&(_, ch)| is_punctuation(ch)) {
+        chars.next();
+    }
+    let (index, letter) = chars.next()?;
+    if letter.is_whitespace() || letter.is_control() {
+        return None;
+    }
+    let mut end = index + letter.len_utf8();
+    for (index, ch) in chars {
+        if !is_punctuation(ch) {
+            break;
+        }
+        end = index + ch.len_utf8();
+    }
+    Some(end)
+}
+
 /// If the 'unicode-bidi' property has a value other than 'normal', return the bidi control codes
 /// to inject before and after the text content of the element.
 fn bidi_control_chars(style: &ServoArc<ComputedValues>) -> Option<(&'static str, &'static str)> {
@@ -3,8 +3,11 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::construct::ConstructionResult;
+use app_units::Au;
 use atomic_refcell::AtomicRefCell;
+use euclid::default::Size2D;
 use script_layout_interface::StyleData;
+use std::sync::atomic::AtomicBool;
 
 #[repr(C)]
 pub struct StyleAndLayoutData {
@@ -13,6 +16,15 @@ pub struct StyleAndLayoutData {
     pub style_data: StyleData,
     /// The layout data associated with a node.
     pub layout_data: AtomicRefCell<LayoutData>,
+    /// The size of the content box of this node as of the last layout, if it
+    /// is a query container. This is read by the style system to evaluate
+    /// container queries, so it's kept apart from `layout_data`, which is
+    /// mutated during flow construction.
+    pub query_container_size: AtomicRefCell<Option<Size2D<Au>>>,
+    /// Whether the style of this node or one of its pseudo-elements depended
+    /// on the size of a query container when it was last computed. Like
+    /// selector flags, this is never reset.
+    pub depends_on_query_container_size: AtomicBool,
 }
 
 impl StyleAndLayoutData {
@@ -20,6 +32,8 @@ impl StyleAndLayoutData {
         Self {
             style_data: StyleData::new(),
             layout_data: AtomicRefCell::new(LayoutData::new()),
+            query_container_size: AtomicRefCell::new(None),
+            depends_on_query_container_size: AtomicBool::new(false),
         }
     }
 }
@@ -40,6 +54,8 @@ pub struct LayoutData {
 
     pub details_content_flow_construction_result: ConstructionResult,
 
+    pub backdrop_flow_construction_result: ConstructionResult,
+
     /// Various flags.
     pub flags: LayoutDataFlags,
 }
@@ -53,6 +69,7 @@ impl LayoutData {
             after_flow_construction_result: ConstructionResult::None,
             details_summary_flow_construction_result: ConstructionResult::None,
             details_content_flow_construction_result: ConstructionResult::None,
+            backdrop_flow_construction_result: ConstructionResult::None,
             flags: LayoutDataFlags::empty(),
         }
     }
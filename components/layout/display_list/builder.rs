@@ -43,10 +43,11 @@ use gfx_traits::{combine_id_with_fragment_type, FragmentType, StackingContextId}
 use ipc_channel::ipc;
 use msg::constellation_msg::PipelineId;
 use net_traits::image_cache::UsePlaceholder;
-use range::Range;
+use range::{Int, Range};
 use script_traits::IFrameSize;
 use servo_config::opts;
 use servo_geometry::{self, MaxRect};
+use std::cmp::max;
 use std::default::Default;
 use std::f32;
 use std::mem;
@@ -2071,6 +2072,7 @@ impl Fragment {
         if !glyphs.is_empty() {
             let indexable_text = IndexableTextItem {
                 origin: stacking_relative_content_box.origin,
+                size: stacking_relative_content_box.size,
                 text_run: text_fragment.run.clone(),
                 range: text_fragment.range,
                 baseline_origin,
@@ -2336,6 +2338,21 @@ impl BlockFlow {
     ) {
         let mut preserved_state = SavedStackingContextCollectionState::new(state);
 
+        // Elements in the top layer are painted as if they were children of the root
+        // stacking context, above everything else and unclipped by their ancestors.
+        // https://fullscreen.spec.whatwg.org/#top-layer
+        if self.fragment.style().in_top_layer() {
+            let root_clipping_and_scrolling =
+                ClippingAndScrolling::simple(ClipScrollNodeIndex::root_scroll_node());
+            state.current_stacking_context_id = StackingContextId::root();
+            state.current_real_stacking_context_id = StackingContextId::root();
+            state.current_parent_reference_frame_id = ClipScrollNodeIndex::root_reference_frame();
+            state.current_clipping_and_scrolling = root_clipping_and_scrolling;
+            state.containing_block_clipping_and_scrolling = root_clipping_and_scrolling;
+            preserved_state.push_clip(state, Rect::max_rect(), StylePosition::Fixed);
+        }
+        let parent_stacking_context_id = state.current_stacking_context_id;
+
         let stacking_context_type = self.stacking_context_type(flags);
         self.base.stacking_context_id = match stacking_context_type {
             None => state.current_stacking_context_id,
@@ -2379,7 +2396,7 @@ impl BlockFlow {
             None => self.base.collect_stacking_contexts_for_children(state),
             Some(StackingContextType::Real) => {
                 self.create_real_stacking_context_for_block(
-                    preserved_state.stacking_context_id,
+                    parent_stacking_context_id,
                     containing_clipping_and_scrolling,
                     established_reference_frame,
                     state,
@@ -2388,7 +2405,7 @@ impl BlockFlow {
             Some(stacking_context_type) => {
                 self.create_pseudo_stacking_context_for_block(
                     stacking_context_type,
-                    preserved_state.stacking_context_id,
+                    parent_stacking_context_id,
                     containing_clipping_and_scrolling,
                     state,
                 );
@@ -2975,6 +2992,8 @@ fn convert_text_run_to_glyphs(
 pub struct IndexableTextItem {
     /// The placement of the text item on the plane.
     pub origin: Point2D<Au>,
+    /// The size of the text item.
+    pub size: Size2D<Au>,
     /// The text run.
     pub text_run: Arc<TextRun>,
     /// The range of text within the text run.
@@ -3010,6 +3029,43 @@ impl IndexableText {
                 .range_index_of_advance(&item[0].range, offset.x),
         )
     }
+
+    /// Returns the text node and the character index within its rendered text of the caret
+    /// position closest to `point`, looking only at the text on the same line as the point.
+    pub fn caret_position(&self, point: Point2D<Au>) -> Option<(OpaqueNode, usize)> {
+        let mut closest: Option<(Au, OpaqueNode, &[IndexableTextItem], usize)> = None;
+        for (node, items) in &self.inner {
+            for (index, item) in items.iter().enumerate() {
+                if point.y < item.origin.y || point.y >= item.origin.y + item.size.height {
+                    continue;
+                }
+                let distance = if point.x < item.origin.x {
+                    item.origin.x - point.x
+                } else {
+                    max(point.x - (item.origin.x + item.size.width), Au(0))
+                };
+                if closest.map_or(true, |(closest_distance, ..)| distance < closest_distance) {
+                    closest = Some((distance, *node, items, index));
+                }
+            }
+        }
+        let (_, node, items, index) = closest?;
+
+        // Count from the start of the first item of the node, rather than of this one.
+        let preceding_chars: usize = items[..index]
+            .iter()
+            .map(|item| {
+                let range = item.range.begin().to_usize()..item.range.end().to_usize();
+                item.text_run.text[range].chars().count()
+            })
+            .sum();
+        let item = &items[index];
+        let offset = point - item.baseline_origin;
+        Some((
+            node,
+            preceding_chars + item.text_run.range_index_of_advance(&item.range, offset.x),
+        ))
+    }
 }
 
 trait ToF32Px {
@@ -0,0 +1,413 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Conversion of Servo display lists into paginated, vector PDF documents, for printing.
+//!
+//! Rectangles, borders, lines, text and images are painted. Text uses the fonts that layout
+//! shaped it with, embedded as-is in the document. Gradients, shadows, background images,
+//! border images and iframes are not painted yet.
+
+use crate::display_list::items::{ClipScrollNodeType, DisplayItem, DisplayList};
+use crate::flow::Flow;
+use crate::fragment::{Fragment, FragmentBorderBoxIterator, SpecificFragmentInfo};
+use crate::sequential;
+use app_units::Au;
+use euclid::default::Rect;
+use gfx::print::pdf::{self, Number, PdfPage, PdfResources};
+use gfx::print::PageGeometry;
+use gfx::text::text_run::TextRun;
+use net_traits::image::base::Image;
+use servo_geometry::MaxRect;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use webrender_api::units::{LayoutPoint, LayoutRect, LayoutTransform};
+use webrender_api::{BorderDetails, FilterOp, FontInstanceKey, ImageKey};
+
+/// The fonts and images the display items of a document refer to by key.
+#[derive(Default)]
+pub struct PrintResources {
+    /// A text run shaped with each font instance.
+    fonts: HashMap<FontInstanceKey, Arc<TextRun>>,
+    /// The decoded images.
+    images: HashMap<ImageKey, Arc<Image>>,
+}
+
+impl PrintResources {
+    /// Collect the fonts and images used by the fragments of the given flow tree.
+    pub fn collect(root: &mut dyn Flow) -> PrintResources {
+        let mut resources = PrintResources::default();
+        sequential::iterate_through_flow_tree_fragment_border_boxes(root, &mut resources);
+        resources
+    }
+}
+
+impl FragmentBorderBoxIterator for PrintResources {
+    fn process(&mut self, fragment: &Fragment, _: i32, _: &Rect<Au>) {
+        match fragment.specific {
+            SpecificFragmentInfo::ScannedText(ref info) => {
+                self.fonts
+                    .entry(info.run.font_key)
+                    .or_insert_with(|| info.run.clone());
+            },
+            SpecificFragmentInfo::Image(ref info) => {
+                if let Some(ref image) = info.image {
+                    if let Some(key) = image.id {
+                        self.images.insert(key, image.clone());
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn should_process(&mut self, _: &Fragment) -> bool {
+        true
+    }
+}
+
+impl DisplayList {
+    /// Paint this display list into a PDF document, with the given vertical extents of the
+    /// document on each page.
+    pub fn convert_to_pdf(
+        &self,
+        geometry: &PageGeometry,
+        pages: &[Range<Au>],
+        resources: &PrintResources,
+    ) -> Vec<u8> {
+        let items = self.place_items();
+        let mut pdf_resources = PdfResources::new();
+        let pages = pages
+            .iter()
+            .map(|extent| {
+                let mut page = PdfPage::new(geometry, extent);
+                for placed in &items {
+                    if placed.bounds.intersection(page.area()).is_some() {
+                        paint_item(&mut page, &mut pdf_resources, resources, placed);
+                    }
+                }
+                page
+            })
+            .collect();
+        pdf::write_document(&geometry.page_size, pages, &pdf_resources)
+    }
+
+    /// Resolve the coordinate space, clips and opacity of every paintable item, tracking
+    /// reference frames and clip nodes like the WebRender conversion does.
+    fn place_items(&self) -> Vec<PlacedItem> {
+        let node_count = self.clip_scroll_nodes.len();
+        let mut spaces = vec![None; node_count];
+        let mut clips: Vec<Option<Vec<Quad>>> = vec![None; node_count];
+        spaces[0] = Some(Affine::IDENTITY);
+        spaces[1] = Some(Affine::IDENTITY);
+        clips[0] = Some(vec![]);
+        clips[1] = Some(vec![]);
+
+        let mut opacities = vec![1.];
+        let mut placed = vec![];
+        for item in &self.list {
+            let indices = item.base().clipping_and_scrolling;
+            let transform = spaces[indices.scrolling.to_index()]
+                .expect("Tried to use a spatial node before it was defined.");
+            let clip_index = indices.clipping.unwrap_or(indices.scrolling).to_index();
+            let node_clips = clips[clip_index]
+                .clone()
+                .expect("Tried to use a clip node before it was defined.");
+            let opacity = *opacities.last().unwrap();
+
+            match *item {
+                DisplayItem::PushStackingContext(ref item) => {
+                    let stacking_context = &item.stacking_context;
+                    if let Some(frame_index) = stacking_context.established_reference_frame {
+                        let local = stacking_context
+                            .transform
+                            .map_or(Affine::IDENTITY, |transform| Affine::from(&transform))
+                            .then(&Affine::translation(
+                                stacking_context.bounds.origin.x,
+                                stacking_context.bounds.origin.y,
+                            ));
+                        spaces[frame_index.to_index()] = Some(local.then(&transform));
+                        clips[frame_index.to_index()] = Some(node_clips);
+                    }
+                    let alpha: f32 = stacking_context
+                        .filters
+                        .iter()
+                        .map(|filter| match *filter {
+                            FilterOp::Opacity(_, amount) => amount,
+                            _ => 1.,
+                        })
+                        .product();
+                    opacities.push(opacity * alpha);
+                },
+                DisplayItem::PopStackingContext(_) => {
+                    opacities.pop();
+                },
+                DisplayItem::DefineClipScrollNode(ref item) => {
+                    let node = &self.clip_scroll_nodes[item.node_index.to_index()];
+                    let parent_space = spaces[node.parent_index.to_index()]
+                        .expect("Tried to use a parent spatial node before it was defined.");
+                    let mut node_clips = clips[node.parent_index.to_index()]
+                        .clone()
+                        .expect("Tried to use a parent clip node before it was defined.");
+                    match node.node_type {
+                        ClipScrollNodeType::Clip | ClipScrollNodeType::ScrollFrame(..) => {
+                            node_clips.push(Quad::new(&node.clip.main, &parent_space));
+                        },
+                        ClipScrollNodeType::StickyFrame(_) => {},
+                        ClipScrollNodeType::Placeholder => {
+                            unreachable!("Found DefineClipScrollNode for Placeholder type node.");
+                        },
+                    }
+                    spaces[item.node_index.to_index()] = Some(parent_space);
+                    clips[item.node_index.to_index()] = Some(node_clips);
+                },
+                DisplayItem::Rectangle(_) |
+                DisplayItem::Text(_) |
+                DisplayItem::Image(_) |
+                DisplayItem::Border(_) |
+                DisplayItem::Line(_) => {
+                    let mut item_clips = node_clips;
+                    let clip_rect = item.base().clip_rect;
+                    if clip_rect != LayoutRect::max_rect() {
+                        item_clips.push(Quad::new(&clip_rect, &transform));
+                    }
+                    let bounds = match *item {
+                        DisplayItem::Rectangle(_) => clip_rect,
+                        _ => item.bounds(),
+                    };
+                    placed.push(PlacedItem {
+                        item,
+                        transform,
+                        clips: item_clips,
+                        opacity,
+                        bounds: Quad::new(&bounds, &transform).bounding_rect(),
+                    });
+                },
+                DisplayItem::RepeatingImage(_) |
+                DisplayItem::Gradient(_) |
+                DisplayItem::RadialGradient(_) |
+                DisplayItem::BoxShadow(_) |
+                DisplayItem::PushTextShadow(_) |
+                DisplayItem::PopAllTextShadows(_) |
+                DisplayItem::Iframe(_) => {},
+            }
+        }
+        placed
+    }
+}
+
+/// A display item with its placement in the coordinate space of the document.
+struct PlacedItem<'a> {
+    item: &'a DisplayItem,
+    /// The transform from the item's coordinate space to the document's.
+    transform: Affine,
+    /// The clips of the item, in the coordinate space of the document.
+    clips: Vec<Quad>,
+    /// The opacity of the stacking contexts the item is in.
+    opacity: f32,
+    /// The bounding box of the item in the coordinate space of the document.
+    bounds: LayoutRect,
+}
+
+/// Paint a placed display item into a page.
+fn paint_item(
+    page: &mut PdfPage,
+    pdf_resources: &mut PdfResources,
+    resources: &PrintResources,
+    placed: &PlacedItem,
+) {
+    match *placed.item {
+        DisplayItem::Rectangle(ref item) => {
+            let mut rect = item.base.clip_rect;
+            // Rectangles may cover the whole canvas, so only paint the visible part.
+            if let Some(offset) = placed.transform.as_translation() {
+                match rect.intersection(&page.area().translate(-offset)) {
+                    Some(visible) => rect = visible,
+                    None => return,
+                }
+            }
+            let color = item.item.color;
+            paint(page, pdf_resources, placed, color.a, |page, _| {
+                page.fill_rect(&rect, &color)
+            });
+        },
+        DisplayItem::Line(ref item) => {
+            let color = item.item.color;
+            paint(page, pdf_resources, placed, color.a, |page, _| {
+                page.fill_rect(&item.item.area, &color)
+            });
+        },
+        DisplayItem::Border(ref item) => {
+            let border = match item.item.details {
+                BorderDetails::Normal(ref border) => border,
+                BorderDetails::NinePatch(_) => return,
+            };
+            let sides = pdf::border_sides(&item.item.bounds, &item.item.widths, border);
+            for (color, points) in sides {
+                paint(page, pdf_resources, placed, color.a, |page, _| {
+                    page.fill_polygon(&points, &color)
+                });
+            }
+        },
+        DisplayItem::Text(ref item) => {
+            let run = match resources.fonts.get(&item.item.font_key) {
+                Some(run) => run,
+                None => return,
+            };
+            let (font, glyphs) = (&run.font_template, &item.data);
+            let color = item.item.color;
+            let size = run.actual_pt_size.to_f32_px();
+            paint(
+                page,
+                pdf_resources,
+                placed,
+                color.a,
+                |page, pdf_resources| page.show_glyphs(pdf_resources, font, size, &color, glyphs),
+            );
+        },
+        DisplayItem::Image(ref item) => {
+            let key = item.item.image_key;
+            let image = match resources.images.get(&key) {
+                Some(image) => image,
+                None => return,
+            };
+            paint(page, pdf_resources, placed, 1., |page, pdf_resources| {
+                page.draw_image(pdf_resources, key, image, &item.item.bounds)
+            });
+        },
+        _ => {},
+    }
+}
+
+/// Paint with the given function, in the coordinate space and with the clips and opacity
+/// of the given item.
+fn paint<F>(
+    page: &mut PdfPage,
+    pdf_resources: &mut PdfResources,
+    placed: &PlacedItem,
+    alpha: f32,
+    body: F,
+) where
+    F: FnOnce(&mut PdfPage, &mut PdfResources),
+{
+    page.save();
+    for clip in &placed.clips {
+        page.clip(clip);
+    }
+    if placed.transform != Affine::IDENTITY {
+        page.transform(&placed.transform);
+    }
+    page.set_alpha(pdf_resources, alpha * placed.opacity);
+    body(page, pdf_resources);
+    page.restore();
+}
+
+/// A 2D affine transform `[a b c d e f]`, which maps `(x, y)` to
+/// `(a x + c y + e, b x + d y + f)` like PDF transformation matrices do.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Affine([f32; 6]);
+
+impl Affine {
+    const IDENTITY: Affine = Affine([1., 0., 0., 1., 0., 0.]);
+
+    fn translation(x: f32, y: f32) -> Affine {
+        Affine([1., 0., 0., 1., x, y])
+    }
+
+    /// The transform that applies this transform, then the other one.
+    fn then(&self, other: &Affine) -> Affine {
+        let [a, b, c, d, e, f] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+        Affine([
+            a * a2 + b * c2,
+            a * b2 + b * d2,
+            c * a2 + d * c2,
+            c * b2 + d * d2,
+            e * a2 + f * c2 + e2,
+            e * b2 + f * d2 + f2,
+        ])
+    }
+
+    fn transform_point(&self, point: &LayoutPoint) -> LayoutPoint {
+        let [a, b, c, d, e, f] = self.0;
+        LayoutPoint::new(a * point.x + c * point.y + e, b * point.x + d * point.y + f)
+    }
+
+    /// The offset of this transform, if it is a translation.
+    fn as_translation(&self) -> Option<webrender_api::units::LayoutVector2D> {
+        match self.0 {
+            [a, b, c, d, e, f] if a == 1. && b == 0. && c == 0. && d == 1. => {
+                Some(webrender_api::units::LayoutVector2D::new(e, f))
+            },
+            _ => None,
+        }
+    }
+}
+
+impl<'a> From<&'a LayoutTransform> for Affine {
+    /// Flatten a 3D transform, dropping its depth and perspective components.
+    fn from(transform: &LayoutTransform) -> Affine {
+        Affine([
+            transform.m11,
+            transform.m12,
+            transform.m21,
+            transform.m22,
+            transform.m41,
+            transform.m42,
+        ])
+    }
+}
+
+impl fmt::Display for Affine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            Number(a),
+            Number(b),
+            Number(c),
+            Number(d),
+            Number(e),
+            Number(g)
+        )
+    }
+}
+
+/// A rectangle moved into another coordinate space, where it may no longer be axis-aligned.
+#[derive(Clone, Debug)]
+struct Quad([LayoutPoint; 4]);
+
+impl Quad {
+    fn new(rect: &LayoutRect, transform: &Affine) -> Quad {
+        let mut points = pdf::corners(rect);
+        for point in points.iter_mut() {
+            *point = transform.transform_point(point);
+        }
+        Quad(points)
+    }
+
+    fn bounding_rect(&self) -> LayoutRect {
+        LayoutRect::from_points(self.0.iter())
+    }
+}
+
+impl fmt::Display for Quad {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [first, second, third, fourth] = self.0;
+        write!(
+            f,
+            "{} {} m {} {} l {} {} l {} {} l h",
+            Number(first.x),
+            Number(first.y),
+            Number(second.x),
+            Number(second.y),
+            Number(third.x),
+            Number(third.y),
+            Number(fourth.x),
+            Number(fourth.y)
+        )
+    }
+}
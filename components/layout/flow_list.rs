@@ -131,6 +131,12 @@ impl FlowList {
         self.flows.len()
     }
 
+    /// O(1)
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.flows.is_empty()
+    }
+
     #[inline]
     pub fn split_off(&mut self, i: usize) -> Self {
         FlowList {
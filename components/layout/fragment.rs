@@ -838,7 +838,11 @@ impl Fragment {
         );
         unscanned_ellipsis_fragments.push_back(ellipsis_fragment);
         let ellipsis_fragments = with_thread_local_font_context(layout_context, |font_context| {
-            TextRunScanner::new().scan_for_runs(font_context, unscanned_ellipsis_fragments)
+            TextRunScanner::new().scan_for_runs(
+                layout_context,
+                font_context,
+                unscanned_ellipsis_fragments,
+            )
         });
         debug_assert_eq!(ellipsis_fragments.len(), 1);
         ellipsis_fragment = ellipsis_fragments.fragments.into_iter().next().unwrap();
@@ -2739,6 +2743,11 @@ impl Fragment {
             return true;
         }
 
+        // Elements in the top layer always create stacking contexts.
+        if self.style().in_top_layer() {
+            return true;
+        }
+
         // Fixed position and sticky position always create stacking contexts.
         if self.style().get_box().position == Position::Fixed ||
             self.style().get_box().position == Position::Sticky
@@ -495,7 +495,7 @@ fn render_text(
     // FIXME(pcwalton): This should properly handle multiple marker fragments. This could happen
     // due to text run splitting.
     let fragments = with_thread_local_font_context(layout_context, |font_context| {
-        TextRunScanner::new().scan_for_runs(font_context, fragments)
+        TextRunScanner::new().scan_for_runs(layout_context, font_context, fragments)
     });
     if fragments.is_empty() {
         None
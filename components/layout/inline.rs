@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::block::AbsoluteAssignBSizesTraversal;
-use crate::context::{LayoutContext, LayoutFontContext};
+use crate::context::{with_thread_local_font_context, LayoutContext, LayoutFontContext};
 use crate::display_list::items::{DisplayListSection, OpaqueNode};
 use crate::display_list::{
     BorderPaintingMode, DisplayListBuildState, StackingContextCollectionState,
@@ -14,10 +14,12 @@ use crate::flow::{EarlyAbsolutePositionInfo, FlowFlags, GetBaseFlow, OpaqueFlow}
 use crate::flow_ref::FlowRef;
 use crate::fragment::FragmentFlags;
 use crate::fragment::SpecificFragmentInfo;
+use crate::fragment::UnscannedTextFragmentInfo;
 use crate::fragment::{CoordinateSystem, Fragment, FragmentBorderBoxIterator, Overflow};
 use crate::layout_debug;
 use crate::model::IntrinsicISizesContribution;
 use crate::text;
+use crate::text::TextRunScanner;
 use crate::traversal::PreorderFlowTraversal;
 use crate::ServoArc;
 use app_units::{Au, MIN_AU};
@@ -28,7 +30,7 @@ use range::{Range, RangeIndex};
 use script_layout_interface::wrapper_traits::PseudoElementType;
 use servo_geometry::MaxRect;
 use std::cmp::max;
-use std::collections::VecDeque;
+use std::collections::{LinkedList, VecDeque};
 use std::sync::Arc;
 use std::{fmt, i32, isize, mem};
 use style::computed_values::display::T as Display;
@@ -430,6 +432,7 @@ impl LineBreaker {
                         candidate.border_padding.inline_start == Au(0) &&
                         result_info.selected() == candidate_info.selected() &&
                         Arc::ptr_eq(&result_info.run, &candidate_info.run) &&
+                        ServoArc::ptr_eq(&result.style, &candidate.style) &&
                         inline_contexts_are_equal(
                             &result.inline_context,
                             &candidate.inline_context,
@@ -971,6 +974,147 @@ pub struct InlineFlow {
     /// (because percentages are relative to the containing block, and we aren't in a position to
     /// compute things relative to our parent's containing block).
     pub first_line_indentation: Au,
+
+    /// The styles of the `::first-line` of our block parent, if this flow contains its first
+    /// line.
+    #[serde(skip_serializing)]
+    pub first_line_style: Option<FirstLineStyle>,
+}
+
+/// The styles used to render the `::first-line` of a block.
+///
+/// The text and inline boxes on the first line inherit from the `::first-line` rather than from
+/// the block, so their styles are recomputed for it. Since that can change the fonts of the text,
+/// which has been shaped by the time we know which fragments end up on the first line, the text
+/// on the first line may have to be shaped again.
+///
+/// https://drafts.csswg.org/css-pseudo/#first-line-pseudo
+pub struct FirstLineStyle {
+    /// The styles of the text and inline boxes of the flow, each paired with its style on the
+    /// first line. The style of the block itself is paired with its `::first-line`.
+    pub styles: Vec<(ServoArc<ComputedValues>, ServoArc<ComputedValues>)>,
+}
+
+impl FirstLineStyle {
+    pub fn new(
+        block_style: ServoArc<ComputedValues>,
+        first_line: ServoArc<ComputedValues>,
+    ) -> FirstLineStyle {
+        FirstLineStyle {
+            styles: vec![(block_style, first_line)],
+        }
+    }
+
+    /// Returns the style that something styled with `style` has on the first line, if known.
+    pub fn on_first_line(
+        &self,
+        style: &ServoArc<ComputedValues>,
+    ) -> Option<&ServoArc<ComputedValues>> {
+        self.styles
+            .iter()
+            .find(|&&(ref other_lines, _)| ServoArc::ptr_eq(other_lines, style))
+            .map(|&(_, ref first_line)| first_line)
+    }
+
+    /// Returns the style that something styled with `style` on the first line has on the other
+    /// lines, if known.
+    fn on_other_lines(
+        &self,
+        style: &ServoArc<ComputedValues>,
+    ) -> Option<&ServoArc<ComputedValues>> {
+        self.styles
+            .iter()
+            .find(|&&(_, ref first_line)| ServoArc::ptr_eq(first_line, style))
+            .map(|&(ref other_lines, _)| other_lines)
+    }
+
+    /// Styles the text fragments in `fragments` like on the first line if `first_line` is true,
+    /// or like on the other lines otherwise. Returns whether any text had to be shaped again.
+    fn restyle_fragments(
+        &self,
+        fragments: &mut [Fragment],
+        first_line: bool,
+        layout_context: &LayoutContext,
+    ) -> bool {
+        let restyled = |style: &ServoArc<ComputedValues>| {
+            if first_line {
+                self.on_first_line(style)
+            } else {
+                self.on_other_lines(style)
+            }
+        };
+
+        let mut reshaped = false;
+        for fragment in fragments {
+            match fragment.specific {
+                SpecificFragmentInfo::ScannedText(..) => {},
+                _ => continue,
+            }
+            let style = match restyled(&fragment.style) {
+                Some(style) => style.clone(),
+                None => continue,
+            };
+            if let Some(ref mut inline_context) = fragment.inline_context {
+                for node in &mut inline_context.nodes {
+                    if let Some(style) = restyled(&node.style) {
+                        node.style = style.clone();
+                    }
+                }
+            }
+
+            let needs_shaping = fragment.style.get_font() != style.get_font() ||
+                fragment.style.get_inherited_text() != style.get_inherited_text();
+            fragment.style = style;
+            if needs_shaping {
+                reshaped |= reshape_text_fragment(fragment, layout_context);
+            }
+        }
+        reshaped
+    }
+}
+
+/// Shapes the text of a scanned text fragment again, after its style changed. Returns whether it
+/// was shaped again.
+fn reshape_text_fragment(fragment: &mut Fragment, layout_context: &LayoutContext) -> bool {
+    let (text, flags, insertion_point) = match fragment.specific {
+        SpecificFragmentInfo::ScannedText(ref info) => {
+            // Include the whitespace stripped by line breaking, which may not happen at the same
+            // place anymore.
+            let begin = info.range.begin();
+            let end = info.range_end_including_stripped_whitespace;
+            let insertion_point = info
+                .insertion_point
+                .filter(|&point| point >= begin)
+                .map(|point| point - begin);
+            (
+                Box::<str>::from(&info.run.text[begin.to_usize()..end.to_usize()]),
+                info.flags,
+                insertion_point,
+            )
+        },
+        _ => return false,
+    };
+
+    let mut unscanned_fragments = LinkedList::new();
+    unscanned_fragments.push_back(fragment.transform(
+        fragment.border_box.size,
+        SpecificFragmentInfo::UnscannedText(Box::new(UnscannedTextFragmentInfo::new(text, None))),
+    ));
+    let mut scanned_fragments = with_thread_local_font_context(layout_context, |font_context| {
+        TextRunScanner::new().scan_for_runs(layout_context, font_context, unscanned_fragments)
+    });
+    if scanned_fragments.fragments.len() != 1 {
+        return false;
+    }
+
+    let mut new_fragment = scanned_fragments.fragments.pop().unwrap();
+    if let SpecificFragmentInfo::ScannedText(ref mut info) = new_fragment.specific {
+        info.flags = flags;
+        info.insertion_point = insertion_point;
+    }
+    new_fragment.flags = fragment.flags;
+    *fragment = new_fragment;
+    true
 }
 
 impl InlineFlow {
@@ -981,6 +1125,7 @@ impl InlineFlow {
             lines: Vec::new(),
             minimum_line_metrics: LineMetrics::new(Au(0), Au(0)),
             first_line_indentation: Au(0),
+            first_line_style: None,
         };
 
         if flow
@@ -997,6 +1142,13 @@ impl InlineFlow {
         flow
     }
 
+    /// Returns the index of the first fragment after the first line.
+    fn first_line_end(&self) -> usize {
+        self.lines
+            .first()
+            .map_or(0, |line| line.range.end().to_usize())
+    }
+
     /// Sets fragment positions in the inline direction based on alignment for one line. This
     /// performs text justification if mandated by the style.
     fn set_inline_fragment_positions(
@@ -1642,6 +1794,17 @@ impl Flow for InlineFlow {
             self.first_line_indentation
         };
 
+        // Undo the `::first-line` styling of the previous reflow, since the first line may not
+        // contain the same fragments anymore.
+        let first_line_style = self.first_line_style.take();
+        if let Some(ref first_line_style) = first_line_style {
+            first_line_style.restyle_fragments(
+                &mut self.fragments.fragments,
+                /* first_line = */ false,
+                layout_context,
+            );
+        }
+
         // Perform line breaking.
         let mut scanner = LineBreaker::new(
             self.base.floats.clone(),
@@ -1650,6 +1813,41 @@ impl Flow for InlineFlow {
         );
         scanner.scan_for_lines(self, layout_context);
 
+        // Style the text on the first line with the `::first-line` of our block parent. If that
+        // changes how the text is shaped, the lines have to be broken again. The text that doesn't
+        // fit on the first line anymore then goes back to the style of the other lines, which
+        // doesn't move the end of the first line, but may move the other line breaks.
+        if let Some(ref first_line_style) = first_line_style {
+            let end = self.first_line_end();
+            if first_line_style.restyle_fragments(
+                &mut self.fragments.fragments[..end],
+                /* first_line = */ true,
+                layout_context,
+            ) {
+                scanner = LineBreaker::new(
+                    self.base.floats.clone(),
+                    indentation,
+                    &self.minimum_line_metrics,
+                );
+                scanner.scan_for_lines(self, layout_context);
+
+                let end = self.first_line_end();
+                if first_line_style.restyle_fragments(
+                    &mut self.fragments.fragments[end..],
+                    /* first_line = */ false,
+                    layout_context,
+                ) {
+                    scanner = LineBreaker::new(
+                        self.base.floats.clone(),
+                        indentation,
+                        &self.minimum_line_metrics,
+                    );
+                    scanner.scan_for_lines(self, layout_context);
+                }
+            }
+        }
+        self.first_line_style = first_line_style;
+
         // Now, go through each line and lay out the fragments inside.
         let line_count = self.lines.len();
         for (line_index, line) in self.lines.iter_mut().enumerate() {
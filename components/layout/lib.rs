@@ -40,6 +40,7 @@ mod list_item;
 mod model;
 mod multicol;
 pub mod opaque_node;
+pub mod pagination;
 pub mod parallel;
 mod persistent_list;
 pub mod query;
@@ -0,0 +1,52 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Fragmentation of a laid out flow tree into pages, for printing.
+//!
+//! The page geometry and the slicing into pages are shared with the other layout engine,
+//! in `gfx::print`; this collects where the fragments allow the document to be broken.
+
+use crate::flow::Flow;
+use crate::fragment::{Fragment, FragmentBorderBoxIterator};
+use crate::sequential;
+use app_units::Au;
+use euclid::default::Rect;
+use gfx::print::PageBreaks;
+use std::ops::Range;
+use style::computed_values::break_inside::T as BreakInside;
+
+/// Collects the page breaks of the fragments of a flow tree.
+struct PageBreakCollector(PageBreaks);
+
+impl FragmentBorderBoxIterator for PageBreakCollector {
+    fn process(&mut self, fragment: &Fragment, _: i32, border_box: &Rect<Au>) {
+        let extent = border_box.origin.y..border_box.max_y();
+
+        // Lines of text and replaced elements are monolithic.
+        if fragment.is_scanned_text_fragment() || fragment.is_replaced() {
+            self.0.add_monolithic(extent);
+            return;
+        }
+
+        let box_style = fragment.style.get_box();
+        self.0.add_box(
+            extent,
+            box_style.break_before,
+            box_style.break_after,
+            box_style.break_inside == BreakInside::Avoid,
+        );
+    }
+
+    fn should_process(&mut self, _: &Fragment) -> bool {
+        true
+    }
+}
+
+/// Slice the laid out document into pages with a page area of the given height, returning
+/// the vertical extent of the document that goes on each page.
+pub fn paginate(root: &mut dyn Flow, page_height: Au) -> Vec<Range<Au>> {
+    let mut collector = PageBreakCollector(PageBreaks::new());
+    sequential::iterate_through_flow_tree_fragment_border_boxes(root, &mut collector);
+    collector.0.pages(page_height)
+}
@@ -17,30 +17,41 @@ use crate::wrapper::LayoutNodeLayoutData;
 use app_units::Au;
 use euclid::default::{Point2D, Rect, Size2D, Vector2D};
 use euclid::Size2D as TypedSize2D;
+use fxhash::FxHashMap;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
-use script_layout_interface::rpc::TextIndexResponse;
+use script_layout_interface::rpc::{BoxSizes, BoxSizesResponse, ClippedBox, ClippedBoxesResponse};
+use script_layout_interface::rpc::{CaretPositionResponse, TextIndexResponse};
 use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
 use script_layout_interface::rpc::{NodeGeometryResponse, NodeScrollIdResponse};
 use script_layout_interface::rpc::{OffsetParentResponse, ResolvedStyleResponse, StyleResponse};
+use script_layout_interface::rpc::{
+    RunningAnimation, RunningAnimationKind, RunningAnimationsResponse,
+};
 use script_layout_interface::wrapper_traits::{
-    LayoutNode, ThreadSafeLayoutElement, ThreadSafeLayoutNode,
+    LayoutNode, PseudoElementType, ThreadSafeLayoutElement, ThreadSafeLayoutNode,
 };
 use script_layout_interface::StyleData;
 use script_layout_interface::{LayoutElementType, LayoutNodeType};
 use script_traits::LayoutMsg as ConstellationMsg;
 use script_traits::UntrustedNodeAddress;
 use std::cmp::{max, min};
+use std::collections::{HashMap, HashSet};
+use std::mem;
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
+use style::animation::{Animation, KeyframesIterationState, KeyframesRunningState};
 use style::computed_values::display::T as Display;
+use style::computed_values::overflow_x::T as StyleOverflow;
 use style::computed_values::position::T as Position;
 use style::computed_values::visibility::T as Visibility;
 use style::context::{StyleContext, ThreadLocalStyleContext};
 use style::dom::TElement;
 use style::logical_geometry::{BlockFlowDirection, InlineBaseDirection, WritingMode};
+use style::properties::longhands::animation_direction::computed_value::single_value::T as AnimationDirection;
 use style::properties::{style_structs, LonghandId, PropertyDeclarationId, PropertyId};
 use style::selector_parser::PseudoElement;
+use style::timer::Timer;
 use style_traits::{CSSPixel, ToCss};
 use webrender_api::ExternalScrollId;
 
@@ -62,6 +73,16 @@ pub struct LayoutThreadData {
     /// A queued response for the content boxes of a node.
     pub content_boxes_response: Vec<Rect<Au>>,
 
+    /// A queued response for the boxes of a batch of nodes and their clipping scroll
+    /// containers.
+    pub clipped_boxes_response: Vec<Option<ClippedBox>>,
+
+    /// A queued response for the box sizes of a batch of nodes.
+    pub box_sizes_response: Vec<Option<BoxSizes>>,
+
+    /// A queued response for the CSS animations and transitions that are running.
+    pub running_animations_response: Vec<RunningAnimation>,
+
     /// A queued response for the client {top, left, width, height} of a node in pixels.
     pub client_rect_response: Rect<i32>,
 
@@ -86,6 +107,9 @@ pub struct LayoutThreadData {
     /// Index in a text fragment. We need this do determine the insertion point.
     pub text_index_response: TextIndexResponse,
 
+    /// A queued response for the caret position closest to a point.
+    pub caret_position_response: CaretPositionResponse,
+
     /// A queued response for the list of nodes at a given point.
     pub nodes_from_point_response: Vec<UntrustedNodeAddress>,
 
@@ -94,6 +118,9 @@ pub struct LayoutThreadData {
 
     /// A queued response for the viewport dimensions for a given browsing context.
     pub inner_window_dimensions_response: Option<TypedSize2D<f32, CSSPixel>>,
+
+    /// A queued response for the PDF document painted by a print query.
+    pub printed_pdf_response: Option<Vec<u8>>,
 }
 
 pub struct LayoutRPCImpl(pub Arc<Mutex<LayoutThreadData>>);
@@ -137,6 +164,27 @@ impl LayoutRPC for LayoutRPCImpl {
         ContentBoxesResponse(rw_data.content_boxes_response.clone())
     }
 
+    fn clipped_boxes(&self) -> ClippedBoxesResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let mut rw_data = rw_data.lock().unwrap();
+        ClippedBoxesResponse(mem::replace(&mut rw_data.clipped_boxes_response, vec![]))
+    }
+
+    fn box_sizes(&self) -> BoxSizesResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let mut rw_data = rw_data.lock().unwrap();
+        BoxSizesResponse(mem::replace(&mut rw_data.box_sizes_response, vec![]))
+    }
+
+    fn running_animations(&self) -> RunningAnimationsResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let mut rw_data = rw_data.lock().unwrap();
+        RunningAnimationsResponse(mem::replace(
+            &mut rw_data.running_animations_response,
+            vec![],
+        ))
+    }
+
     fn nodes_from_point_response(&self) -> Vec<UntrustedNodeAddress> {
         let &LayoutRPCImpl(ref rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -192,6 +240,12 @@ impl LayoutRPC for LayoutRPCImpl {
         rw_data.text_index_response.clone()
     }
 
+    fn caret_position(&self) -> CaretPositionResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.caret_position_response.clone()
+    }
+
     fn element_inner_text(&self) -> String {
         let &LayoutRPCImpl(ref rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -203,6 +257,12 @@ impl LayoutRPC for LayoutRPCImpl {
         let rw_data = rw_data.lock().unwrap();
         rw_data.inner_window_dimensions_response.clone()
     }
+
+    fn printed_pdf(&self) -> Option<Vec<u8>> {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let mut rw_data = rw_data.lock().unwrap();
+        rw_data.printed_pdf_response.take()
+    }
 }
 
 struct UnioningFragmentBorderBoxIterator {
@@ -396,6 +456,150 @@ pub fn process_content_boxes_request(
     iterator.rects
 }
 
+struct ClippedBoxesIterator {
+    /// The index in the response of each of the requested nodes.
+    requested_nodes: HashMap<OpaqueNode, usize>,
+    boxes: Vec<Option<ClippedBox>>,
+    /// The scroll containers around the fragment being processed, with the level of their
+    /// flows in the flow tree.
+    clips: Vec<(i32, OpaqueNode, Rect<Au>)>,
+}
+
+impl ClippedBoxesIterator {
+    fn add_fragment_box(
+        &mut self,
+        node: OpaqueNode,
+        border_box: &Rect<Au>,
+        padding_box: &Rect<Au>,
+        content_box: &Rect<Au>,
+    ) {
+        let index = match self.requested_nodes.get(&node) {
+            Some(index) => *index,
+            None => return,
+        };
+        match self.boxes[index] {
+            Some(ref mut clipped_box) => {
+                clipped_box.border_box = clipped_box.border_box.union(border_box);
+                clipped_box.padding_box = clipped_box.padding_box.union(padding_box);
+                clipped_box.content_box = clipped_box.content_box.union(content_box);
+            },
+            None => {
+                self.boxes[index] = Some(ClippedBox {
+                    border_box: *border_box,
+                    padding_box: *padding_box,
+                    content_box: *content_box,
+                    clips_overflow: false,
+                    clips: self
+                        .clips
+                        .iter()
+                        .rev()
+                        .map(|&(_, node, clip)| (node, clip))
+                        .collect(),
+                });
+            },
+        }
+    }
+}
+
+impl FragmentBorderBoxIterator for ClippedBoxesIterator {
+    fn process(&mut self, fragment: &Fragment, level: i32, border_box: &Rect<Au>) {
+        // Leave the scroll containers of flows that aren't ancestors of this one.
+        while self.clips.last().map_or(false, |clip| clip.0 >= level) {
+            self.clips.pop();
+        }
+
+        let writing_mode = fragment.style.writing_mode;
+        let padding_box = border_box.inner_rect(fragment.border_width().to_physical(writing_mode));
+        let content_box = border_box.inner_rect(fragment.border_padding.to_physical(writing_mode));
+        self.add_fragment_box(fragment.node, border_box, &padding_box, &content_box);
+        if let Some(ref context) = fragment.inline_context {
+            for node in &context.nodes {
+                self.add_fragment_box(node.address, border_box, &padding_box, &content_box);
+            }
+        }
+
+        // Only block containers clip their overflow, and the overflow of the root element
+        // applies to the viewport.
+        let box_style = fragment.style.get_box();
+        let clips_overflow = level > 0 &&
+            fragment.inline_context.is_none() &&
+            match fragment.specific {
+                SpecificFragmentInfo::Generic => true,
+                _ => false,
+            } &&
+            (box_style.overflow_x != StyleOverflow::Visible ||
+                box_style.overflow_y != StyleOverflow::Visible);
+        if clips_overflow {
+            if let Some(index) = self.requested_nodes.get(&fragment.node) {
+                if let Some(ref mut clipped_box) = self.boxes[*index] {
+                    clipped_box.clips_overflow = true;
+                }
+            }
+            self.clips.push((level, fragment.node, padding_box));
+        }
+    }
+
+    fn should_process(&mut self, _: &Fragment) -> bool {
+        true
+    }
+}
+
+struct BoxSizesIterator {
+    requested_nodes: HashSet<OpaqueNode>,
+    sizes: HashMap<OpaqueNode, BoxSizes>,
+}
+
+impl FragmentBorderBoxIterator for BoxSizesIterator {
+    fn process(&mut self, fragment: &Fragment, _: i32, border_box: &Rect<Au>) {
+        // The boxes of a node are those of its first fragment.
+        if self.sizes.contains_key(&fragment.node) {
+            return;
+        }
+        let writing_mode = fragment.style.writing_mode;
+        let padding_box = border_box.inner_rect(fragment.border_width().to_physical(writing_mode));
+        let content_box = border_box.inner_rect(fragment.border_padding.to_physical(writing_mode));
+        self.sizes.insert(
+            fragment.node,
+            BoxSizes {
+                content_rect: content_box.translate(-padding_box.origin.to_vector()),
+                border_box_size: border_box.size,
+                is_vertical: writing_mode.is_vertical(),
+            },
+        );
+    }
+
+    fn should_process(&mut self, fragment: &Fragment) -> bool {
+        fragment.pseudo == PseudoElementType::Normal &&
+            self.requested_nodes.contains(&fragment.node)
+    }
+}
+
+struct QueryContainerSizesIterator {
+    sizes: HashMap<OpaqueNode, Size2D<Au>>,
+}
+
+impl FragmentBorderBoxIterator for QueryContainerSizesIterator {
+    fn process(&mut self, fragment: &Fragment, _: i32, border_box: &Rect<Au>) {
+        // Like for the box sizes, the size of a query container is that of
+        // its first fragment.
+        if self.sizes.contains_key(&fragment.node) {
+            return;
+        }
+        let writing_mode = fragment.style.writing_mode;
+        let content_box = border_box.inner_rect(fragment.border_padding.to_physical(writing_mode));
+        self.sizes.insert(fragment.node, content_box.size);
+    }
+
+    fn should_process(&mut self, fragment: &Fragment) -> bool {
+        fragment.pseudo == PseudoElementType::Normal &&
+            fragment
+                .style
+                .get_box()
+                .clone_container_type()
+                .is_query_container()
+    }
+}
+
 struct FragmentLocatingFragmentIterator {
     node_address: OpaqueNode,
     client_rect: Rect<i32>,
@@ -679,6 +883,106 @@ impl FragmentBorderBoxIterator for ParentOffsetBorderBoxIterator {
     }
 }
 
+/// Find the border and padding boxes of each of the requested nodes, and the scroll
+/// containers clipping them, in a single traversal of the flow tree.
+pub fn process_clipped_boxes_request(
+    requested_nodes: &[OpaqueNode],
+    layout_root: &mut dyn Flow,
+) -> Vec<Option<ClippedBox>> {
+    let mut iterator = ClippedBoxesIterator {
+        requested_nodes: requested_nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (*node, index))
+            .collect(),
+        boxes: vec![None; requested_nodes.len()],
+        clips: vec![],
+    };
+    sequential::iterate_through_flow_tree_fragment_border_boxes(layout_root, &mut iterator);
+    iterator.boxes
+}
+
+/// Find the box sizes of each of the requested nodes in a single traversal of the flow tree.
+pub fn process_box_sizes_request(
+    requested_nodes: &[OpaqueNode],
+    layout_root: &mut dyn Flow,
+) -> Vec<Option<BoxSizes>> {
+    let mut iterator = BoxSizesIterator {
+        requested_nodes: requested_nodes.iter().cloned().collect(),
+        sizes: HashMap::new(),
+    };
+    sequential::iterate_through_flow_tree_fragment_border_boxes(layout_root, &mut iterator);
+    requested_nodes
+        .iter()
+        .map(|node| iterator.sizes.get(node).cloned())
+        .collect()
+}
+
+/// Find the content box sizes of all the query containers in a single
+/// traversal of the flow tree.
+pub fn process_query_container_sizes_request(
+    layout_root: &mut dyn Flow,
+) -> HashMap<OpaqueNode, Size2D<Au>> {
+    let mut iterator = QueryContainerSizesIterator {
+        sizes: HashMap::new(),
+    };
+    sequential::iterate_through_flow_tree_fragment_border_boxes(layout_root, &mut iterator);
+    iterator.sizes
+}
+
+/// Describes the CSS animations and transitions that are running for script.
+pub fn process_running_animations_request(
+    running_animations: &FxHashMap<OpaqueNode, Vec<Animation>>,
+    timer: &Timer,
+) -> Vec<RunningAnimation> {
+    let now = timer.seconds();
+    running_animations
+        .values()
+        .flat_map(|animations| animations.iter())
+        .filter(|animation| !animation.is_expired())
+        .map(|animation| match *animation {
+            // The start time of transitions already accounts for their delay.
+            Animation::Transition(node, started_at, ref frame) => RunningAnimation {
+                node: node.to_untrusted_node_address(),
+                kind: RunningAnimationKind::Transition(
+                    frame.property_animation.property_name().into(),
+                ),
+                current_time: now - started_at,
+                delay: 0.,
+                duration: frame.duration,
+                iterations: 1.,
+                direction: AnimationDirection::Normal,
+                paused: false,
+            },
+            // The start time of keyframes animations is the start time of their
+            // current iteration, after the delay.
+            //
+            // FIXME: We don't keep track of the iterations of infinite animations,
+            // so the current time of those is always within their first iteration.
+            Animation::Keyframes(node, _, ref name, ref state) => {
+                let (current_iteration, iterations) = match state.iteration_state {
+                    KeyframesIterationState::Finite(current, max) => (current as f64, max as f64),
+                    KeyframesIterationState::Infinite => (0., f64::INFINITY),
+                };
+                let (iteration_time, paused) = match state.running_state {
+                    KeyframesRunningState::Paused(progress) => (progress * state.duration, true),
+                    KeyframesRunningState::Running => (now - state.started_at, false),
+                };
+                RunningAnimation {
+                    node: node.to_untrusted_node_address(),
+                    kind: RunningAnimationKind::Animation(name.to_string()),
+                    current_time: state.delay + current_iteration * state.duration + iteration_time,
+                    delay: state.delay,
+                    duration: state.duration,
+                    iterations,
+                    direction: state.direction,
+                    paused,
+                }
+            },
+        })
+        .collect()
+}
+
 pub fn process_node_geometry_request(
     requested_node: OpaqueNode,
     layout_root: &mut dyn Flow,
@@ -810,6 +1114,7 @@ where
     let layout_el = match *pseudo {
         Some(PseudoElement::Before) => layout_el.get_before_pseudo(),
         Some(PseudoElement::After) => layout_el.get_after_pseudo(),
+        Some(PseudoElement::Backdrop) => layout_el.get_backdrop_pseudo(),
         Some(PseudoElement::DetailsSummary) |
         Some(PseudoElement::DetailsContent) |
         Some(PseudoElement::Selection) => None,
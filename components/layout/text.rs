@@ -4,13 +4,13 @@
 
 //! Text layout.
 
-use crate::context::LayoutFontContext;
+use crate::context::{LayoutContext, LayoutFontContext};
 use crate::fragment::{Fragment, ScannedTextFlags};
 use crate::fragment::{ScannedTextFragmentInfo, SpecificFragmentInfo, UnscannedTextFragmentInfo};
 use crate::inline::{InlineFragmentNodeFlags, InlineFragments};
 use crate::linked_list::split_off_head;
 use app_units::Au;
-use gfx::font::{FontMetrics, FontRef, RunMetrics, ShapingFlags, ShapingOptions};
+use gfx::font::{FontFeature, FontMetrics, FontRef, RunMetrics, ShapingFlags, ShapingOptions};
 use gfx::text::glyph::ByteIndex;
 use gfx::text::text_run::TextRun;
 use gfx::text::util::{self, CompressionMode};
@@ -66,6 +66,7 @@ impl TextRunScanner {
 
     pub fn scan_for_runs(
         &mut self,
+        layout_context: &LayoutContext,
         font_context: &mut LayoutFontContext,
         mut fragments: LinkedList<Fragment>,
     ) -> InlineFragments {
@@ -123,6 +124,7 @@ impl TextRunScanner {
 
             // Flush that clump to the list of fragments we're building up.
             last_whitespace = self.flush_clump_to_list(
+                layout_context,
                 font_context,
                 &mut new_fragments,
                 &mut paragraph_bytes_processed,
@@ -146,6 +148,7 @@ impl TextRunScanner {
     /// be adjusted.
     fn flush_clump_to_list(
         &mut self,
+        layout_context: &LayoutContext,
         mut font_context: &mut LayoutFontContext,
         out_fragments: &mut Vec<Fragment>,
         paragraph_bytes_processed: &mut usize,
@@ -181,10 +184,16 @@ impl TextRunScanner {
             let word_spacing;
             let text_rendering;
             let word_break;
+            let font_features;
             {
                 let in_fragment = self.clump.front().unwrap();
                 let font_style = in_fragment.style().clone_font();
                 let inherited_text_style = in_fragment.style().get_inherited_text();
+                font_features = FontFeature::list_from_style(
+                    &font_style,
+                    layout_context.style_context.stylist,
+                    &layout_context.style_context.guards,
+                );
                 font_group = font_context.font_group(font_style);
                 compression = match in_fragment.white_space() {
                     WhiteSpace::Normal | WhiteSpace::Nowrap => {
@@ -335,11 +344,12 @@ impl TextRunScanner {
                 word_spacing,
                 script: Script::Common,
                 flags: flags,
+                features: font_features,
             };
 
             let mut result = Vec::with_capacity(run_info_list.len());
             for run_info in run_info_list {
-                let mut options = options;
+                let mut options = options.clone();
                 options.script = run_info.script;
                 if run_info.bidi_level.is_rtl() {
                     options.flags.insert(ShapingFlags::RTL_FLAG);
@@ -159,6 +159,14 @@ impl BoxFragment {
         common: &CommonItemProperties,
         border_rect: units::LayoutRect,
     ) {
+        if let Some((widths, border)) = self.normal_border() {
+            let details = wr::BorderDetails::Normal(border);
+            builder.wr.push_border(common, border_rect, widths, details)
+        }
+    }
+
+    /// The widths and sides of the border of this box, if it has one.
+    pub(crate) fn normal_border(&self) -> Option<(units::LayoutSideOffsets, wr::NormalBorder)> {
         let b = self.style.get_border();
         let widths = SideOffsets2D::new(
             b.border_top_width.px(),
@@ -167,7 +175,7 @@ impl BoxFragment {
             b.border_left_width.px(),
         );
         if widths == SideOffsets2D::zero() {
-            return;
+            return None;
         }
         let side = |style, color| wr::BorderSide {
             color: rgba(self.style.resolve_color(color)),
@@ -184,19 +192,19 @@ impl BoxFragment {
                 BorderStyle::Outset => wr::BorderStyle::Outset,
             },
         };
-        let details = wr::BorderDetails::Normal(wr::NormalBorder {
+        let border = wr::NormalBorder {
             top: side(b.border_top_style, b.border_top_color),
             right: side(b.border_right_style, b.border_right_color),
             bottom: side(b.border_bottom_style, b.border_bottom_color),
             left: side(b.border_left_style, b.border_left_color),
             radius: wr::BorderRadius::zero(),
             do_aa: true,
-        });
-        builder.wr.push_border(common, border_rect, widths, details)
+        };
+        Some((widths, border))
     }
 }
 
-fn rgba(rgba: cssparser::RGBA) -> wr::ColorF {
+pub(crate) fn rgba(rgba: cssparser::RGBA) -> wr::ColorF {
     wr::ColorF::new(
         rgba.red_f32(),
         rgba.green_f32(),
@@ -205,7 +213,10 @@ fn rgba(rgba: cssparser::RGBA) -> wr::ColorF {
     )
 }
 
-fn glyphs(glyph_runs: &[Arc<GlyphStore>], mut origin: Vec2<Length>) -> Vec<wr::GlyphInstance> {
+pub(crate) fn glyphs(
+    glyph_runs: &[Arc<GlyphStore>],
+    mut origin: Vec2<Length>,
+) -> Vec<wr::GlyphInstance> {
     use gfx_traits::ByteIndex;
     use range::Range;
 
@@ -95,6 +95,10 @@ fn traverse_element<'dom, Node>(
 {
     let replaced = ReplacedContent::for_element(element);
     let style = element.style(context);
+    if style.in_top_layer() {
+        // Boxed as a child of the root, see `flow::root`.
+        return;
+    }
     match Display::from(style.get_box().display) {
         Display::None => element.unset_boxes_in_subtree(),
         Display::Contents => {
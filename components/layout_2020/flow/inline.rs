@@ -14,6 +14,7 @@ use crate::sizing::ContentSizes;
 use crate::style_ext::{ComputedValuesExt, Display, DisplayGeneratingBox, DisplayOutside};
 use crate::{relative_adjustement, ContainingBlock};
 use app_units::Au;
+use gfx::font_template::FontTemplateData;
 use gfx::text::text_run::GlyphRun;
 use servo_arc::Arc;
 use style::properties::ComputedValues;
@@ -491,13 +492,14 @@ struct BreakAndShapeResult {
     font_ascent: Au,
     font_line_gap: Au,
     font_key: FontInstanceKey,
+    font_template: std::sync::Arc<FontTemplateData>,
     runs: Vec<GlyphRun>,
     break_at_start: bool,
 }
 
 impl TextRun {
     fn break_and_shape(&self, layout_context: &LayoutContext) -> BreakAndShapeResult {
-        use gfx::font::ShapingFlags;
+        use gfx::font::{FontHandleMethods, ShapingFlags};
         use style::computed_values::text_rendering::T as TextRendering;
         use style::computed_values::word_break::T as WordBreak;
 
@@ -526,6 +528,11 @@ impl TextRun {
             word_spacing: inherited_text_style.word_spacing.to_hash_key(),
             script: unicode_script::Script::Common,
             flags,
+            features: gfx::font::FontFeature::list_from_style(
+                &font_style,
+                layout_context.style_context.stylist,
+                &layout_context.style_context.guards,
+            ),
         };
 
         crate::context::with_thread_local_font_context(layout_context, |font_context| {
@@ -547,6 +554,7 @@ impl TextRun {
                 font_ascent: font.metrics.ascent,
                 font_line_gap: font.metrics.line_gap,
                 font_key: font.font_key,
+                font_template: font.handle.template(),
                 runs,
                 break_at_start,
             }
@@ -560,6 +568,7 @@ impl TextRun {
             font_ascent,
             font_line_gap,
             font_key,
+            font_template,
             runs,
             break_at_start: _,
         } = self.break_and_shape(layout_context);
@@ -620,6 +629,7 @@ impl TextRun {
                     content_rect,
                     ascent: font_ascent.into(),
                     font_key,
+                    font_template: font_template.clone(),
                     glyphs,
                 }));
             if runs.is_empty() {
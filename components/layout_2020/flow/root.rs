@@ -5,6 +5,7 @@
 use crate::context::LayoutContext;
 use crate::display_list::IsContentful;
 use crate::dom_traversal::{Contents, NodeExt};
+use crate::element_data::LayoutBox;
 use crate::flow::construct::ContainsFloats;
 use crate::flow::float::FloatBox;
 use crate::flow::{BlockContainer, BlockFormattingContext, BlockLevelBox};
@@ -17,8 +18,9 @@ use crate::replaced::ReplacedContent;
 use crate::sizing::ContentSizesRequest;
 use crate::style_ext::{Direction, Display, DisplayGeneratingBox, DisplayInside, WritingMode};
 use crate::{ContainingBlock, DefiniteContainingBlock};
+use gfx::print::PageGeometry;
 use rayon::iter::{IntoParallelRefIterator, ParallelExtend, ParallelIterator};
-use script_layout_interface::wrapper_traits::LayoutNode;
+use script_layout_interface::wrapper_traits::{LayoutNode, ThreadSafeLayoutNode};
 use servo_arc::Arc;
 use style::values::computed::{Length, LengthOrAuto};
 use style::Zero;
@@ -32,7 +34,8 @@ impl BoxTreeRoot {
     where
         Node: 'dom + Copy + LayoutNode + Send + Sync,
     {
-        let (contains_floats, boxes) = construct_for_root_element(&context, root_element);
+        let (contains_floats, mut boxes) = construct_for_root_element(&context, root_element);
+        boxes.extend(construct_for_top_layer(&context, root_element));
         Self(BlockFormattingContext {
             contains_floats: contains_floats == ContainsFloats::Yes,
             contents: BlockContainer::BlockLevelBoxes(boxes),
@@ -91,6 +94,84 @@ fn construct_for_root_element<'dom>(
     }
 }
 
+/// Boxes elements in the top layer, and their `::backdrop`, as absolutely positioned
+/// children of the root so that they are laid out against the initial containing block
+/// and painted above everything else.
+///
+/// FIXME: the top layer is ordered by insertion rather than in tree order.
+/// <https://fullscreen.spec.whatwg.org/#top-layer>
+fn construct_for_top_layer<'dom, Node>(
+    context: &LayoutContext,
+    root_element: Node,
+) -> Vec<Arc<BlockLevelBox>>
+where
+    Node: NodeExt<'dom>,
+{
+    let mut elements = Vec::new();
+    collect_top_layer_elements(context, root_element, &mut elements);
+
+    let mut boxes = Vec::new();
+    for element in elements {
+        if let Some(backdrop_style) = element
+            .to_threadsafe()
+            .get_backdrop_pseudo()
+            .map(|backdrop| backdrop.style(context.shared_context()))
+        {
+            if let Display::GeneratingBox(DisplayGeneratingBox::OutsideInside { inside, .. }) =
+                Display::from(backdrop_style.get_box().display)
+            {
+                boxes.push(Arc::new(BlockLevelBox::OutOfFlowAbsolutelyPositionedBox(
+                    AbsolutelyPositionedBox::construct(
+                        context,
+                        backdrop_style,
+                        inside,
+                        Contents::<Node>::OfPseudoElement(Vec::new()),
+                    ),
+                )));
+            }
+        }
+
+        let style = element.style(context);
+        let replaced = ReplacedContent::for_element(element);
+        if let Display::GeneratingBox(DisplayGeneratingBox::OutsideInside { inside, .. }) =
+            Display::from(style.get_box().display)
+        {
+            let contents = replaced.map_or(Contents::OfElement(element), Contents::Replaced);
+            let box_ = Arc::new(BlockLevelBox::OutOfFlowAbsolutelyPositionedBox(
+                AbsolutelyPositionedBox::construct(context, style, inside, contents),
+            ));
+            element
+                .element_box_slot()
+                .set(LayoutBox::BlockLevel(box_.clone()));
+            boxes.push(box_);
+        }
+    }
+    boxes
+}
+
+/// Collects the rendered elements in the top layer, in tree order.
+fn collect_top_layer_elements<'dom, Node>(
+    context: &LayoutContext,
+    parent: Node,
+    elements: &mut Vec<Node>,
+) where
+    Node: NodeExt<'dom>,
+{
+    let mut next = parent.first_child();
+    while let Some(child) = next {
+        if child.is_element() {
+            let style = child.style(context);
+            if Display::from(style.get_box().display) != Display::None {
+                if style.in_top_layer() {
+                    elements.push(child);
+                }
+                collect_top_layer_elements(context, child, elements);
+            }
+        }
+        next = child.next_sibling();
+    }
+}
+
 impl BoxTreeRoot {
     pub fn layout(
         &self,
@@ -153,4 +234,21 @@ impl FragmentTreeRoot {
         }
         is_contentful
     }
+
+    /// Print this fragment tree, laid out in the page area of the given pages, into a PDF
+    /// document.
+    pub fn print_to_pdf(&self, geometry: &PageGeometry) -> Vec<u8> {
+        let page_area = geometry.content_size();
+        let containing_block = geom::physical::Rect {
+            top_left: geom::physical::Vec2 {
+                x: Length::zero(),
+                y: Length::zero(),
+            },
+            size: geom::physical::Vec2 {
+                x: Length::new(page_area.width),
+                y: Length::new(page_area.height),
+            },
+        };
+        crate::print::print_to_pdf(&self.0, &containing_block, geometry)
+    }
 }
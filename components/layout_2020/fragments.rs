@@ -4,7 +4,9 @@
 
 use crate::geom::flow_relative::{Rect, Sides};
 use crate::style_ext::{Direction, WritingMode};
+use gfx::font_template::FontTemplateData;
 use gfx::text::glyph::GlyphStore;
+use net_traits::image::base::Image;
 use servo_arc::Arc as ServoArc;
 use std::sync::Arc;
 use style::properties::ComputedValues;
@@ -59,6 +61,8 @@ pub(crate) struct TextFragment {
     pub content_rect: Rect<Length>,
     pub ascent: Length,
     pub font_key: FontInstanceKey,
+    /// The font the glyphs are from, for printing.
+    pub font_template: Arc<FontTemplateData>,
     pub glyphs: Vec<Arc<GlyphStore>>,
 }
 
@@ -66,6 +70,8 @@ pub(crate) struct ImageFragment {
     pub style: ServoArc<ComputedValues>,
     pub content_rect: Rect<Length>,
     pub image_key: ImageKey,
+    /// The decoded image, for printing.
+    pub image: Arc<Image>,
 }
 
 impl AnonymousFragment {
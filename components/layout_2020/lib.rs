@@ -17,6 +17,7 @@ mod fragments;
 mod geom;
 mod opaque_node;
 mod positioned;
+mod print;
 pub mod query;
 mod replaced;
 mod sizing;
@@ -0,0 +1,200 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Printing of a fragment tree into a paginated, vector PDF document.
+//!
+//! Backgrounds, borders, text and images are painted. Background images, gradients,
+//! shadows and rounded borders are not painted yet.
+
+use crate::display_list::{glyphs, rgba};
+use crate::fragments::{BoxFragment, Fragment};
+use crate::geom::physical::Rect;
+use crate::style_ext::ComputedValuesExt;
+use app_units::Au;
+use gfx::print::pdf::{self, PdfPage, PdfResources};
+use gfx::print::{PageBreaks, PageGeometry};
+use std::ops::Range;
+use style::computed_values::break_inside::T as BreakInside;
+use style::values::computed::Length;
+use webrender_api::units::LayoutRect;
+
+/// Paint the given fragments, laid out in a page area as wide as the pages, into a PDF
+/// document.
+pub(crate) fn print_to_pdf(
+    fragments: &[Fragment],
+    containing_block: &Rect<Length>,
+    geometry: &PageGeometry,
+) -> Vec<u8> {
+    let mut breaks = PageBreaks::new();
+    for fragment in fragments {
+        fragment.collect_page_breaks(&mut breaks, containing_block);
+    }
+
+    let page_height = Au::from_f32_px(geometry.content_size().height);
+    let mut resources = PdfResources::new();
+    let pages = breaks
+        .pages(page_height)
+        .iter()
+        .map(|extent| {
+            let mut page = PdfPage::new(geometry, extent);
+            for fragment in fragments {
+                fragment.print(&mut page, &mut resources, containing_block);
+            }
+            page
+        })
+        .collect();
+    pdf::write_document(&geometry.page_size, pages, &resources)
+}
+
+impl Fragment {
+    fn collect_page_breaks(&self, breaks: &mut PageBreaks, containing_block: &Rect<Length>) {
+        match self {
+            Fragment::Box(b) => {
+                let border_rect = b
+                    .border_rect()
+                    .to_physical(b.style.writing_mode(), containing_block)
+                    .translate(&containing_block.top_left);
+                let box_style = b.style.get_box();
+                breaks.add_box(
+                    vertical_extent(&border_rect),
+                    box_style.break_before,
+                    box_style.break_after,
+                    box_style.break_inside == BreakInside::Avoid,
+                );
+                let content_rect = b
+                    .content_rect
+                    .to_physical(b.style.writing_mode(), containing_block)
+                    .translate(&containing_block.top_left);
+                for child in &b.children {
+                    child.collect_page_breaks(breaks, &content_rect)
+                }
+            },
+            Fragment::Anonymous(a) => {
+                let rect = a
+                    .rect
+                    .to_physical(a.mode, containing_block)
+                    .translate(&containing_block.top_left);
+                for child in &a.children {
+                    child.collect_page_breaks(breaks, &rect)
+                }
+            },
+            // Lines of text and replaced elements are monolithic.
+            Fragment::Text(t) => {
+                let rect = t
+                    .content_rect
+                    .to_physical(t.parent_style.writing_mode(), containing_block)
+                    .translate(&containing_block.top_left);
+                breaks.add_monolithic(vertical_extent(&rect));
+            },
+            Fragment::Image(i) => {
+                let rect = i
+                    .content_rect
+                    .to_physical(i.style.writing_mode(), containing_block)
+                    .translate(&containing_block.top_left);
+                breaks.add_monolithic(vertical_extent(&rect));
+            },
+        }
+    }
+
+    fn print(
+        &self,
+        page: &mut PdfPage,
+        resources: &mut PdfResources,
+        containing_block: &Rect<Length>,
+    ) {
+        match self {
+            Fragment::Box(b) => b.print(page, resources, containing_block),
+            Fragment::Anonymous(a) => {
+                let rect = a
+                    .rect
+                    .to_physical(a.mode, containing_block)
+                    .translate(&containing_block.top_left);
+                for child in &a.children {
+                    child.print(page, resources, &rect)
+                }
+            },
+            Fragment::Text(t) => {
+                let rect = t
+                    .content_rect
+                    .to_physical(t.parent_style.writing_mode(), containing_block)
+                    .translate(&containing_block.top_left);
+                if !LayoutRect::from(rect.clone()).intersects(page.area()) {
+                    return;
+                }
+                let mut baseline_origin = rect.top_left.clone();
+                baseline_origin.y += t.ascent;
+                let glyphs = glyphs(&t.glyphs, baseline_origin);
+                if glyphs.is_empty() {
+                    return;
+                }
+                let color = rgba(t.parent_style.clone_color());
+                let size = t.parent_style.get_font().font_size.size.0.px();
+                page.save();
+                page.set_alpha(resources, color.a);
+                page.show_glyphs(resources, &t.font_template, size, &color, &glyphs);
+                page.restore();
+            },
+            Fragment::Image(i) => {
+                let rect: LayoutRect = i
+                    .content_rect
+                    .to_physical(i.style.writing_mode(), containing_block)
+                    .translate(&containing_block.top_left)
+                    .into();
+                if rect.intersects(page.area()) {
+                    page.draw_image(resources, i.image_key, &i.image, &rect);
+                }
+            },
+        }
+    }
+}
+
+impl BoxFragment {
+    fn print(
+        &self,
+        page: &mut PdfPage,
+        resources: &mut PdfResources,
+        containing_block: &Rect<Length>,
+    ) {
+        let border_rect: LayoutRect = self
+            .border_rect()
+            .to_physical(self.style.writing_mode(), containing_block)
+            .translate(&containing_block.top_left)
+            .into();
+        if border_rect.intersects(page.area()) {
+            let background_color = rgba(
+                self.style
+                    .resolve_color(self.style.clone_background_color()),
+            );
+            if background_color.a > 0. {
+                page.save();
+                page.set_alpha(resources, background_color.a);
+                page.fill_rect(&border_rect, &background_color);
+                page.restore();
+            }
+            if let Some((widths, border)) = self.normal_border() {
+                for (color, points) in pdf::border_sides(&border_rect, &widths, &border) {
+                    page.save();
+                    page.set_alpha(resources, color.a);
+                    page.fill_polygon(&points, &color);
+                    page.restore();
+                }
+            }
+        }
+
+        // Children may overflow this box, so they are printed even when it isn't.
+        let content_rect = self
+            .content_rect
+            .to_physical(self.style.writing_mode(), containing_block)
+            .translate(&containing_block.top_left);
+        for child in &self.children {
+            child.print(page, resources, &content_rect)
+        }
+    }
+}
+
+fn vertical_extent(rect: &Rect<Length>) -> Range<Au> {
+    let top = Au::from_f32_px(rect.top_left.y.px());
+    let bottom = Au::from_f32_px((rect.top_left.y + rect.size.y).px());
+    top..bottom
+}
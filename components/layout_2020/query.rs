@@ -11,14 +11,17 @@ use euclid::Size2D;
 use euclid::Vector2D;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
-use script_layout_interface::rpc::TextIndexResponse;
+use script_layout_interface::rpc::{BoxSizes, BoxSizesResponse, ClippedBox, ClippedBoxesResponse};
+use script_layout_interface::rpc::{CaretPositionResponse, TextIndexResponse};
 use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
 use script_layout_interface::rpc::{NodeGeometryResponse, NodeScrollIdResponse};
 use script_layout_interface::rpc::{OffsetParentResponse, ResolvedStyleResponse, StyleResponse};
+use script_layout_interface::rpc::{RunningAnimation, RunningAnimationsResponse};
 use script_layout_interface::wrapper_traits::{LayoutNode, ThreadSafeLayoutNode};
 use script_traits::LayoutMsg as ConstellationMsg;
 use script_traits::UntrustedNodeAddress;
 use std::collections::HashMap;
+use std::mem;
 use std::sync::{Arc, Mutex};
 use style::dom::OpaqueNode;
 use style::properties::PropertyId;
@@ -43,6 +46,16 @@ pub struct LayoutThreadData {
     /// A queued response for the content boxes of a node.
     pub content_boxes_response: Vec<Rect<Au>>,
 
+    /// A queued response for the boxes of a batch of nodes and their clipping scroll
+    /// containers.
+    pub clipped_boxes_response: Vec<Option<ClippedBox>>,
+
+    /// A queued response for the box sizes of a batch of nodes.
+    pub box_sizes_response: Vec<Option<BoxSizes>>,
+
+    /// A queued response for the CSS animations and transitions that are running.
+    pub running_animations_response: Vec<RunningAnimation>,
+
     /// A queued response for the client {top, left, width, height} of a node in pixels.
     pub client_rect_response: Rect<i32>,
 
@@ -67,6 +80,9 @@ pub struct LayoutThreadData {
     /// Index in a text fragment. We need this do determine the insertion point.
     pub text_index_response: TextIndexResponse,
 
+    /// A queued response for the caret position closest to a point.
+    pub caret_position_response: CaretPositionResponse,
+
     /// A queued response for the list of nodes at a given point.
     pub nodes_from_point_response: Vec<UntrustedNodeAddress>,
 
@@ -75,6 +91,9 @@ pub struct LayoutThreadData {
 
     /// A queued response for the viewport dimensions for a given browsing context.
     pub inner_window_dimensions_response: Option<Size2D<f32, CSSPixel>>,
+
+    /// A queued response for the PDF document painted by a print query.
+    pub printed_pdf_response: Option<Vec<u8>>,
 }
 
 pub struct LayoutRPCImpl(pub Arc<Mutex<LayoutThreadData>>);
@@ -95,6 +114,27 @@ impl LayoutRPC for LayoutRPCImpl {
         ContentBoxesResponse(rw_data.content_boxes_response.clone())
     }
 
+    fn clipped_boxes(&self) -> ClippedBoxesResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let mut rw_data = rw_data.lock().unwrap();
+        ClippedBoxesResponse(mem::replace(&mut rw_data.clipped_boxes_response, vec![]))
+    }
+
+    fn box_sizes(&self) -> BoxSizesResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let mut rw_data = rw_data.lock().unwrap();
+        BoxSizesResponse(mem::replace(&mut rw_data.box_sizes_response, vec![]))
+    }
+
+    fn running_animations(&self) -> RunningAnimationsResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let mut rw_data = rw_data.lock().unwrap();
+        RunningAnimationsResponse(mem::replace(
+            &mut rw_data.running_animations_response,
+            vec![],
+        ))
+    }
+
     fn nodes_from_point_response(&self) -> Vec<UntrustedNodeAddress> {
         let &LayoutRPCImpl(ref rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -150,6 +190,12 @@ impl LayoutRPC for LayoutRPCImpl {
         rw_data.text_index_response.clone()
     }
 
+    fn caret_position(&self) -> CaretPositionResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.caret_position_response.clone()
+    }
+
     fn element_inner_text(&self) -> String {
         let &LayoutRPCImpl(ref rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -161,12 +207,28 @@ impl LayoutRPC for LayoutRPCImpl {
         let rw_data = rw_data.lock().unwrap();
         rw_data.inner_window_dimensions_response.clone()
     }
+
+    fn printed_pdf(&self) -> Option<Vec<u8>> {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let mut rw_data = rw_data.lock().unwrap();
+        rw_data.printed_pdf_response.take()
+    }
 }
 
 pub fn process_content_box_request(_requested_node: OpaqueNode) -> Option<Rect<Au>> {
     None
 }
 
+pub fn process_clipped_boxes_request(requested_nodes: &[OpaqueNode]) -> Vec<Option<ClippedBox>> {
+    vec![None; requested_nodes.len()]
+}
+
+pub fn process_box_sizes_request(requested_nodes: &[OpaqueNode]) -> Vec<Option<BoxSizes>> {
+    // TODO: Fragments don't know which node they belong to yet, so resize observers see
+    // every target as having empty boxes.
+    vec![None; requested_nodes.len()]
+}
+
 pub fn process_content_boxes_request(_requested_node: OpaqueNode) -> Vec<Rect<Au>> {
     vec![]
 }
@@ -218,3 +280,7 @@ pub fn process_element_inner_text_query<N: LayoutNode>(_node: N) -> String {
 pub fn process_text_index_request(_node: OpaqueNode, _point: Point2D<Au>) -> TextIndexResponse {
     TextIndexResponse(None)
 }
+
+pub fn process_caret_position_request(_point: Point2D<Au>) -> CaretPositionResponse {
+    CaretPositionResponse(None)
+}
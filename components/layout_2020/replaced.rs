@@ -41,8 +41,8 @@ impl ReplacedContent {
         match &self.kind {
             ReplacedContentKind::Image(image) => image
                 .as_ref()
-                .and_then(|image| image.id)
-                .map(|image_key| {
+                .and_then(|image| image.id.map(|image_key| (image_key, image)))
+                .map(|(image_key, image)| {
                     Fragment::Image(ImageFragment {
                         style: style.clone(),
                         content_rect: flow_relative::Rect {
@@ -50,6 +50,7 @@ impl ReplacedContent {
                             size,
                         },
                         image_key,
+                        image: image.clone(),
                     })
                 })
                 .into_iter()
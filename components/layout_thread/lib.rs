@@ -34,6 +34,7 @@ use fxhash::{FxHashMap, FxHashSet};
 use gfx::font;
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context;
+use gfx::print::PageGeometry;
 use gfx_traits::{node_id_from_scroll_id, Epoch};
 use histogram::Histogram;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
@@ -45,12 +46,16 @@ use layout::context::LayoutContext;
 use layout::context::RegisteredPainter;
 use layout::context::RegisteredPainters;
 use layout::display_list::items::{OpaqueNode, WebRenderImageInfo};
-use layout::display_list::{IndexableText, ToLayout};
+use layout::display_list::{IndexableText, PrintResources, ToLayout};
 use layout::flow::{Flow, GetBaseFlow, ImmutableFlowUtils, MutableOwnedFlowUtils};
 use layout::flow_ref::FlowRef;
 use layout::incremental::{RelayoutMode, SpecialRestyleDamage};
 use layout::layout_debug;
+use layout::pagination;
 use layout::parallel;
+use layout::query::process_query_container_sizes_request;
+use layout::query::process_running_animations_request;
+use layout::query::{process_box_sizes_request, process_clipped_boxes_request};
 use layout::query::{
     process_content_box_request, process_content_boxes_request, LayoutRPCImpl, LayoutThreadData,
 };
@@ -63,7 +68,7 @@ use layout::sequential;
 use layout::traversal::{
     ComputeStackingRelativePositions, PreorderFlowTraversal, RecalcStyleAndConstructFlows,
 };
-use layout::wrapper::LayoutNodeLayoutData;
+use layout::wrapper::{GetRawData, LayoutNodeLayoutData};
 use layout_traits::LayoutThreadFactory;
 use libc::c_void;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
@@ -78,9 +83,10 @@ use parking_lot::RwLock;
 use profile_traits::mem::{self as profile_mem, Report, ReportKind, ReportsChan};
 use profile_traits::time::{self as profile_time, profile, TimerMetadata};
 use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
+use script_layout_interface::message::WebFontSource;
 use script_layout_interface::message::{LayoutThreadInit, Msg, NodesFromPointQueryType, Reflow};
 use script_layout_interface::message::{QueryMsg, ReflowComplete, ReflowGoal, ScriptReflow};
-use script_layout_interface::rpc::TextIndexResponse;
+use script_layout_interface::rpc::{CaretPositionResponse, TextIndexResponse};
 use script_layout_interface::rpc::{LayoutRPC, OffsetParentResponse, StyleResponse};
 use script_layout_interface::wrapper_traits::LayoutNode;
 use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg};
@@ -106,6 +112,7 @@ use std::time::Duration;
 use style::animation::Animation;
 use style::context::{QuirksMode, RegisteredSpeculativePainter, RegisteredSpeculativePainters};
 use style::context::{SharedStyleContext, ThreadLocalStyleContextCreationInfo};
+use style::dom::TShadowRoot;
 use style::dom::{ShowSubtree, ShowSubtreeDataAndPrimaryValues, TDocument, TElement, TNode};
 use style::driver;
 use style::error_reporting::RustLogReporter;
@@ -113,6 +120,7 @@ use style::global_style_data::{GLOBAL_STYLE_DATA, STYLE_THREAD_POOL};
 use style::invalidation::element::restyle_hints::RestyleHint;
 use style::logical_geometry::LogicalPoint;
 use style::media_queries::{Device, MediaList, MediaType};
+use style::properties::computed_value_flags::ComputedValueFlags;
 use style::properties::PropertyId;
 use style::selector_parser::SnapshotMap;
 use style::servo::restyle_damage::ServoRestyleDamage;
@@ -125,6 +133,7 @@ use style::thread_state::{self, ThreadState};
 use style::timer::Timer;
 use style::traversal::DomTraversal;
 use style::traversal_flags::TraversalFlags;
+use style::values::computed::font::FamilyName;
 use style_traits::CSSPixel;
 use style_traits::DevicePixel;
 use style_traits::SpeculativePainter;
@@ -153,10 +162,10 @@ pub struct LayoutThread {
     pipeline_port: Receiver<LayoutControlMsg>,
 
     /// The port on which we receive messages from the font cache thread.
-    font_cache_receiver: Receiver<()>,
+    font_cache_receiver: Receiver<bool>,
 
     /// The channel on which the font cache can send messages to us.
-    font_cache_sender: IpcSender<()>,
+    font_cache_sender: IpcSender<bool>,
 
     /// A means of communication with the background hang monitor.
     background_hang_monitor: Box<dyn BackgroundHangMonitor>,
@@ -469,7 +478,7 @@ fn add_font_face_rules(
     guard: &SharedRwLockReadGuard,
     device: &Device,
     font_cache_thread: &FontCacheThread,
-    font_cache_sender: &IpcSender<()>,
+    font_cache_sender: &IpcSender<bool>,
     outstanding_web_fonts_counter: &Arc<AtomicUsize>,
     load_webfonts_synchronously: bool,
 ) {
@@ -481,6 +490,7 @@ fn add_font_face_rules(
                 font_cache_thread.add_web_font(
                     font_face.family().clone(),
                     effective_sources,
+                    None,
                     sender.clone(),
                 );
                 receiver.recv().unwrap();
@@ -494,6 +504,7 @@ fn add_font_face_rules(
                 font_cache_thread.add_web_font(
                     font_face.family().clone(),
                     effective_sources,
+                    None,
                     (*font_cache_sender).clone(),
                 );
             }
@@ -535,11 +546,12 @@ impl LayoutThread {
         // Let webrender know about this pipeline by sending an empty display list.
         webrender_api.send_initial_transaction(webrender_document, id.to_webrender());
 
-        let device = Device::new(
+        let mut device = Device::new(
             MediaType::screen(),
             window_size.initial_viewport,
             window_size.device_pixel_ratio,
         );
+        device.set_media_environment(window_size.media_environment);
 
         // Create the channel on which new animations can be sent.
         let (new_animations_sender, new_animations_receiver) = unbounded();
@@ -591,6 +603,9 @@ impl LayoutThread {
                 indexable_text: IndexableText::default(),
                 content_box_response: None,
                 content_boxes_response: Vec::new(),
+                clipped_boxes_response: Vec::new(),
+                box_sizes_response: Vec::new(),
+                running_animations_response: Vec::new(),
                 client_rect_response: Rect::zero(),
                 scroll_id_response: None,
                 scroll_area_response: Rect::zero(),
@@ -599,9 +614,11 @@ impl LayoutThread {
                 style_response: StyleResponse(None),
                 scroll_offsets: HashMap::new(),
                 text_index_response: TextIndexResponse(None),
+                caret_position_response: CaretPositionResponse(None),
                 nodes_from_point_response: vec![],
                 element_inner_text_response: String::new(),
                 inner_window_dimensions_response: None,
+                printed_pdf_response: None,
             })),
             webrender_image_cache: Arc::new(RwLock::new(FnvHashMap::default())),
             timer: if pref!(layout.animations.test.enabled) {
@@ -705,6 +722,9 @@ impl LayoutThread {
             Msg::RegisterPaint(..) => LayoutHangAnnotation::RegisterPaint,
             Msg::SetNavigationStart(..) => LayoutHangAnnotation::SetNavigationStart,
             Msg::GetRunningAnimations(..) => LayoutHangAnnotation::GetRunningAnimations,
+            Msg::AddFontFace(..) => LayoutHangAnnotation::AddFontFace,
+            Msg::RemoveFontFace(..) => LayoutHangAnnotation::RemoveFontFace,
+            Msg::RegisterProperty(..) => LayoutHangAnnotation::RegisterProperty,
         };
         self.background_hang_monitor
             .notify_activity(HangAnnotation::Layout(hang_annotation));
@@ -881,11 +901,66 @@ impl LayoutThread {
             Msg::GetRunningAnimations(sender) => {
                 let _ = sender.send(self.running_animations.read().len());
             },
+            Msg::AddFontFace(family, font_face, source, sender) => {
+                self.handle_add_font_face(family, font_face, source, sender);
+            },
+            Msg::RemoveFontFace(family, font_face) => {
+                self.handle_remove_font_face(family, font_face);
+            },
+            Msg::RegisterProperty(registration) => {
+                self.stylist.register_custom_property(registration);
+            },
         }
 
         true
     }
 
+    /// Registers a font face created by script with the font cache thread.
+    ///
+    /// The outcome is reported to us as well as to script, so that font caches
+    /// get invalidated as when the fonts of `@font-face` rules are loaded.
+    fn handle_add_font_face(
+        &self,
+        family: FamilyName,
+        font_face: Atom,
+        source: WebFontSource,
+        sender: IpcSender<bool>,
+    ) {
+        let (font_sender, font_receiver) = ipc::channel().unwrap();
+        let font_cache_sender = self.font_cache_sender.clone();
+        ROUTER.add_route(
+            font_receiver.to_opaque(),
+            Box::new(move |message| {
+                let loaded: bool = message.to().unwrap();
+                let _ = font_cache_sender.send(loaded);
+                let _ = sender.send(loaded);
+            }),
+        );
+        self.outstanding_web_fonts.fetch_add(1, Ordering::SeqCst);
+        match source {
+            WebFontSource::Sources(sources) => {
+                self.font_cache_thread
+                    .add_web_font(family, sources, Some(font_face), font_sender)
+            },
+            WebFontSource::Data(bytes) => {
+                self.font_cache_thread
+                    .add_web_font_data(family, bytes, font_face, font_sender)
+            },
+        }
+    }
+
+    /// Removes a font face created by script from the font cache thread.
+    ///
+    /// This waits for the font cache thread, so that the next reflow doesn't
+    /// use the font face anymore.
+    fn handle_remove_font_face(&self, family: FamilyName, font_face: Atom) {
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.font_cache_thread
+            .remove_web_font(family, font_face, sender);
+        let _ = receiver.recv();
+        font_context::invalidate_font_caches();
+    }
+
     fn collect_reports<'a, 'b>(
         &self,
         reports_chan: ReportsChan,
@@ -1289,6 +1364,15 @@ impl LayoutThread {
                         &QueryMsg::ContentBoxesQuery(_) => {
                             rw_data.content_boxes_response = Vec::new();
                         },
+                        &QueryMsg::ClippedBoxesQuery(ref nodes) => {
+                            rw_data.clipped_boxes_response = vec![None; nodes.len()];
+                        },
+                        &QueryMsg::BoxSizesQuery(ref nodes) => {
+                            rw_data.box_sizes_response = vec![None; nodes.len()];
+                        },
+                        &QueryMsg::RunningAnimationsQuery => {
+                            rw_data.running_animations_response = Vec::new();
+                        },
                         &QueryMsg::NodesFromPointQuery(..) => {
                             rw_data.nodes_from_point_response = Vec::new();
                         },
@@ -1313,12 +1397,18 @@ impl LayoutThread {
                         &QueryMsg::TextIndexQuery(..) => {
                             rw_data.text_index_response = TextIndexResponse(None);
                         },
+                        &QueryMsg::CaretPositionQuery(_) => {
+                            rw_data.caret_position_response = CaretPositionResponse(None);
+                        },
                         &QueryMsg::ElementInnerTextQuery(_) => {
                             rw_data.element_inner_text_response = String::new();
                         },
                         &QueryMsg::InnerWindowDimensionsQuery(_) => {
                             rw_data.inner_window_dimensions_response = None;
                         },
+                        &QueryMsg::PrintQuery => {
+                            rw_data.printed_pdf_response = None;
+                        },
                     },
                     ReflowGoal::Full | ReflowGoal::TickAnimations => {},
                 }
@@ -1336,10 +1426,6 @@ impl LayoutThread {
         let initial_viewport = data.window_size.initial_viewport;
         let device_pixel_ratio = data.window_size.device_pixel_ratio;
         let old_viewport_size = self.viewport_size;
-        let current_screen_size = Size2D::new(
-            Au::from_f32_px(initial_viewport.width),
-            Au::from_f32_px(initial_viewport.height),
-        );
 
         // Calculate the actual viewport as per DEVICE-ADAPT § 6
         // If the entire flow tree is invalid, then it will be reflowed anyhow.
@@ -1354,8 +1440,26 @@ impl LayoutThread {
             ua_or_user: &ua_or_user_guard,
         };
 
+        // When printing, the document is laid out for print media in the page area of the
+        // pages described by the `@page` rules.
+        let page_geometry = match data.reflow_goal {
+            ReflowGoal::LayoutQuery(QueryMsg::PrintQuery, _) => {
+                Some(PageGeometry::from_stylist(&self.stylist, &guards))
+            },
+            _ => None,
+        };
+        let (media_type, initial_viewport) = match page_geometry {
+            Some(ref page_geometry) => (MediaType::print(), page_geometry.content_size()),
+            None => (MediaType::screen(), initial_viewport),
+        };
+        let current_screen_size = Size2D::new(
+            Au::from_f32_px(initial_viewport.width),
+            Au::from_f32_px(initial_viewport.height),
+        );
+
         let had_used_viewport_units = self.stylist.device().used_viewport_units();
-        let device = Device::new(MediaType::screen(), initial_viewport, device_pixel_ratio);
+        let mut device = Device::new(media_type, initial_viewport, device_pixel_ratio);
+        device.set_media_environment(data.window_size.media_environment);
         let sheet_origins_affected_by_device_change = self.stylist.set_device(device, &guards);
 
         self.stylist
@@ -1556,11 +1660,25 @@ impl LayoutThread {
             layout_context.style_context.stylist.rule_tree().maybe_gc();
         }
 
+        // The whole document is painted when printing, so nothing is clipped away, and the
+        // display list built for the screen can't be reused.
+        let reflow_info = match page_geometry {
+            Some(_) => {
+                rw_data.display_list = None;
+                Reflow {
+                    page_clip_rect: Rect::max_rect(),
+                }
+            },
+            None => Reflow {
+                page_clip_rect: data.reflow_info.page_clip_rect,
+            },
+        };
+
         // Perform post-style recalculation layout passes.
         if let Some(mut root_flow) = self.root_flow.borrow().clone() {
             self.perform_post_style_recalc_layout_passes(
                 &mut root_flow,
-                &data.reflow_info,
+                &reflow_info,
                 &data.reflow_goal,
                 Some(&document),
                 &mut rw_data,
@@ -1569,18 +1687,169 @@ impl LayoutThread {
             );
         }
 
+        // Container queries and container-relative units are evaluated against
+        // the size of query containers, which is only known after layout. If
+        // any of those sizes changed, restyle the elements that depend on them
+        // and lay them out again.
+        //
+        // We do this at most once per reflow: we don't implement size
+        // containment, so the styles of the contents of a query container can
+        // affect its size, and iterating to a fixed point might never end.
+        let uses_query_container_sizes = self.stylist.has_container_rules() ||
+            self.stylist.device().used_container_units() ||
+            document.shadow_roots().iter().any(|shadow_root| {
+                shadow_root
+                    .style_data()
+                    .map_or(false, |data| data.has_container_rules())
+            });
+        let query_container_sizes_changed = match self.root_flow.borrow().clone() {
+            Some(mut root_flow) if uses_query_container_sizes => {
+                let resized_containers = self.store_query_container_sizes(element, &mut root_flow);
+                self.restyle_query_container_dependents(&resized_containers)
+            },
+            _ => false,
+        };
+        if query_container_sizes_changed {
+            let traversal = RecalcStyleAndConstructFlows::new(layout_context);
+            let token = {
+                let shared = <RecalcStyleAndConstructFlows as DomTraversal<
+                    ServoLayoutElement,
+                >>::shared_context(&traversal);
+                RecalcStyleAndConstructFlows::pre_traverse(element, shared)
+            };
+
+            if token.should_traverse() {
+                profile(
+                    profile_time::ProfilerCategory::LayoutStyleRecalc,
+                    self.profiler_metadata(),
+                    self.time_profiler_chan.clone(),
+                    || {
+                        driver::traverse_dom::<ServoLayoutElement, RecalcStyleAndConstructFlows>(
+                            &traversal,
+                            token,
+                            thread_pool,
+                        );
+                    },
+                );
+                *self.root_flow.borrow_mut() = self.try_get_layout_root(element.as_node());
+            }
+
+            layout_context = traversal.destroy();
+
+            if let Some(mut root_flow) = self.root_flow.borrow().clone() {
+                self.perform_post_style_recalc_layout_passes(
+                    &mut root_flow,
+                    &reflow_info,
+                    &data.reflow_goal,
+                    Some(&document),
+                    &mut rw_data,
+                    &mut layout_context,
+                    FxHashSet::default(),
+                );
+
+                // Store the sizes the second layout pass ended up with, so
+                // that the next reflow only restyles the dependents of the
+                // query containers that get resized after this one.
+                self.store_query_container_sizes(element, &mut root_flow);
+            }
+        }
+
         self.first_reflow.set(false);
         self.respond_to_query_if_necessary(
             &data.reflow_goal,
+            page_geometry.as_ref(),
             &mut *rw_data,
             &mut layout_context,
             data.result.borrow_mut().as_mut().unwrap(),
         );
     }
 
+    /// Stores the size of every query container in the document as of the
+    /// last layout, and returns the elements whose size changed, including
+    /// the ones that stopped being query containers.
+    fn store_query_container_sizes<'a>(
+        &self,
+        root: ServoLayoutElement<'a>,
+        root_flow: &mut FlowRef,
+    ) -> Vec<ServoLayoutElement<'a>> {
+        let sizes = process_query_container_sizes_request(FlowRef::deref_mut(root_flow));
+        let mut resized_containers = vec![];
+        let root = root.as_node();
+        for node in Some(root).into_iter().chain(root.dom_descendants()) {
+            let element = match node.as_element() {
+                Some(element) => element,
+                None => continue,
+            };
+            let raw_data = match element.get_raw_data() {
+                Some(raw_data) => raw_data,
+                None => continue,
+            };
+
+            let new_size = sizes.get(&node.opaque()).cloned();
+            let mut size = raw_data.query_container_size.borrow_mut();
+            if *size != new_size {
+                *size = new_size;
+                resized_containers.push(element);
+            }
+        }
+        resized_containers
+    }
+
+    /// Marks the elements whose style depends on the size of the given query
+    /// containers for restyling, that is, the containers themselves if their
+    /// pseudo-elements do, and their descendants that do. Returns whether any
+    /// element was marked.
+    fn restyle_query_container_dependents(
+        &self,
+        resized_containers: &[ServoLayoutElement],
+    ) -> bool {
+        let mut restyled = false;
+        for container in resized_containers {
+            let container = container.as_node();
+            for node in Some(container)
+                .into_iter()
+                .chain(container.dom_descendants())
+            {
+                let element = match node.as_element() {
+                    Some(element) => element,
+                    None => continue,
+                };
+                let depends_on_query_container_size =
+                    element.get_raw_data().map_or(false, |raw_data| {
+                        raw_data
+                            .depends_on_query_container_size
+                            .load(Ordering::Relaxed)
+                    });
+                let mut data = match element.mutate_data() {
+                    Some(data) => data,
+                    None => continue,
+                };
+                // Styles shared with a sibling through the style sharing
+                // cache carry the dependency on container-relative units
+                // without the element having been noted itself.
+                let uses_container_units = data.styles.get_primary().map_or(false, |style| {
+                    style
+                        .flags
+                        .contains(ComputedValueFlags::DEPENDS_ON_QUERY_CONTAINER_SIZE)
+                });
+                if !depends_on_query_container_size && !uses_container_units {
+                    continue;
+                }
+
+                data.hint.insert(RestyleHint::RESTYLE_SELF);
+                if let Some(parent) = element.parent_element() {
+                    unsafe { parent.note_dirty_descendant() };
+                }
+                restyled = true;
+            }
+        }
+        restyled
+    }
+
     fn respond_to_query_if_necessary(
         &self,
         reflow_goal: &ReflowGoal,
+        page_geometry: Option<&PageGeometry>,
         rw_data: &mut LayoutThreadData,
         context: &mut LayoutContext,
         reflow_result: &mut ReflowComplete,
@@ -1596,6 +1865,7 @@ impl LayoutThread {
             None => vec![],
         };
         reflow_result.newly_transitioning_nodes = newly_transitioning_nodes;
+        reflow_result.pending_web_fonts = self.outstanding_web_fonts.load(Ordering::SeqCst) != 0;
 
         let mut root_flow = match self.root_flow.borrow().clone() {
             Some(root_flow) => root_flow,
@@ -1610,6 +1880,19 @@ impl LayoutThread {
                 &QueryMsg::ContentBoxesQuery(node) => {
                     rw_data.content_boxes_response = process_content_boxes_request(node, root_flow);
                 },
+                &QueryMsg::ClippedBoxesQuery(ref nodes) => {
+                    rw_data.clipped_boxes_response =
+                        process_clipped_boxes_request(nodes, root_flow);
+                },
+                &QueryMsg::BoxSizesQuery(ref nodes) => {
+                    rw_data.box_sizes_response = process_box_sizes_request(nodes, root_flow);
+                },
+                &QueryMsg::RunningAnimationsQuery => {
+                    rw_data.running_animations_response = process_running_animations_request(
+                        &*self.running_animations.read(),
+                        &self.timer,
+                    );
+                },
                 &QueryMsg::TextIndexQuery(node, point_in_node) => {
                     let point_in_node = Point2D::new(
                         Au::from_f32_px(point_in_node.x),
@@ -1618,6 +1901,13 @@ impl LayoutThread {
                     rw_data.text_index_response =
                         TextIndexResponse(rw_data.indexable_text.text_index(node, point_in_node));
                 },
+                &QueryMsg::CaretPositionQuery(point) => {
+                    let point = Point2D::new(Au::from_f32_px(point.x), Au::from_f32_px(point.y));
+                    rw_data.caret_position_response =
+                        CaretPositionResponse(rw_data.indexable_text.caret_position(point).map(
+                            |(node, index)| (UntrustedNodeAddress(node.0 as *const c_void), index),
+                        ));
+                },
                 &QueryMsg::NodeGeometryQuery(node) => {
                     rw_data.client_rect_response = process_node_geometry_request(node, root_flow);
                 },
@@ -1678,6 +1968,20 @@ impl LayoutThread {
                         .get(&browsing_context_id)
                         .cloned();
                 },
+                &QueryMsg::PrintQuery => {
+                    let page_geometry =
+                        page_geometry.expect("print queries are laid out for pages");
+                    let page_height = Au::from_f32_px(page_geometry.content_size().height);
+                    let pages = pagination::paginate(root_flow, page_height);
+                    let resources = PrintResources::collect(root_flow);
+
+                    // The display list painted for print media is thrown away, so that the
+                    // next reflow builds one for the screen again.
+                    rw_data.printed_pdf_response =
+                        rw_data.display_list.take().map(|display_list| {
+                            display_list.convert_to_pdf(page_geometry, &pages, &resources)
+                        });
+                },
             },
             ReflowGoal::Full | ReflowGoal::TickAnimations => {},
         }
@@ -75,8 +75,10 @@ use std::marker::PhantomData;
 use std::ptr::NonNull;
 use std::sync::atomic::Ordering;
 use std::sync::Arc as StdArc;
+use style::animation::KeyframeEffectSample;
 use style::applicable_declarations::ApplicableDeclarationBlock;
 use style::attr::AttrValue;
+use style::bloom::each_relevant_element_hash;
 use style::context::SharedStyleContext;
 use style::data::ElementData;
 use style::dom::{DomChildren, LayoutIterator, NodeInfo, OpaqueNode};
@@ -533,6 +535,10 @@ impl<'le> TElement for ServoLayoutElement<'le> {
         }
     }
 
+    fn keyframe_effect_samples(&self) -> &[KeyframeEffectSample] {
+        unsafe { &*self.element.keyframe_effect_samples() }
+    }
+
     fn state(&self) -> ElementState {
         self.element.get_state_for_layout()
     }
@@ -645,9 +651,9 @@ impl<'le> TElement for ServoLayoutElement<'le> {
 
     fn has_animations(&self) -> bool {
         // We use this function not only for Gecko but also for Servo to know if this element has
-        // animations, so we maybe try to get the important rules of this element. This is used for
-        // off-main thread animations, but we don't support it on Servo, so return false directly.
-        false
+        // animations, so that we don't share styles with it. The effects of the animations driven
+        // by script are applied while cascading, so those are the ones that matter here.
+        !self.keyframe_effect_samples().is_empty()
     }
 
     fn has_css_animations(&self) -> bool {
@@ -861,6 +867,17 @@ impl<'le> ::selectors::Element for ServoLayoutElement<'le> {
         None
     }
 
+    fn first_element_child(&self) -> Option<ServoLayoutElement<'le>> {
+        let mut child = self.as_node().first_child();
+        while let Some(node) = child {
+            if let Some(element) = node.as_element() {
+                return Some(element);
+            }
+            child = node.next_sibling();
+        }
+        None
+    }
+
     fn attr_matches(
         &self,
         ns: &NamespaceConstraint<&Namespace>,
@@ -917,6 +934,14 @@ impl<'le> ::selectors::Element for ServoLayoutElement<'le> {
             self.element.namespace() == other.element.namespace()
     }
 
+    fn each_bloom_hash<F>(&self, f: F) -> bool
+    where
+        F: FnMut(u32),
+    {
+        each_relevant_element_hash(*self, f);
+        true
+    }
+
     fn is_pseudo_element(&self) -> bool {
         false
     }
@@ -968,7 +993,17 @@ impl<'le> ::selectors::Element for ServoLayoutElement<'le> {
             NonTSPseudoClass::Indeterminate |
             NonTSPseudoClass::ReadWrite |
             NonTSPseudoClass::PlaceholderShown |
-            NonTSPseudoClass::Target => self
+            NonTSPseudoClass::Target |
+            NonTSPseudoClass::FocusVisible |
+            NonTSPseudoClass::FocusWithin |
+            NonTSPseudoClass::Valid |
+            NonTSPseudoClass::Invalid |
+            NonTSPseudoClass::Required |
+            NonTSPseudoClass::Optional |
+            NonTSPseudoClass::InRange |
+            NonTSPseudoClass::OutOfRange |
+            NonTSPseudoClass::Default |
+            NonTSPseudoClass::Defined => self
                 .element
                 .get_state_for_layout()
                 .contains(pseudo_class.state_flag()),
@@ -1129,6 +1164,17 @@ impl<'ln> ThreadSafeLayoutNode for ServoThreadSafeLayoutNode<'ln> {
         parent_data.styles.primary().clone()
     }
 
+    fn parent_selected_style(&self) -> Arc<ComputedValues> {
+        let parent = self.node.parent_node().unwrap().as_element().unwrap();
+        let parent_data = parent.get_data().unwrap().borrow();
+        parent_data
+            .styles
+            .pseudos
+            .get(&PseudoElement::Selection)
+            .unwrap_or(parent_data.styles.primary())
+            .clone()
+    }
+
     fn debug_id(self) -> usize {
         self.node.debug_id()
     }
@@ -1275,7 +1321,7 @@ where
             PseudoElementType::Normal => parent
                 .get_before_pseudo()
                 .or_else(|| parent.get_details_summary_pseudo())
-                .or_else(|| unsafe { parent.dangerous_first_child() }),
+                .or_else(|| unsafe { parent.dangerous_first_child() }.map(with_backdrop)),
             PseudoElementType::DetailsContent | PseudoElementType::DetailsSummary => unsafe {
                 parent.dangerous_first_child()
             },
@@ -1296,7 +1342,9 @@ where
     fn next(&mut self) -> Option<ConcreteNode> {
         use selectors::Element;
         match self.parent_node.get_pseudo_element_type() {
-            PseudoElementType::Before | PseudoElementType::After => None,
+            PseudoElementType::Before | PseudoElementType::After | PseudoElementType::Backdrop => {
+                None
+            },
 
             PseudoElementType::DetailsSummary => {
                 let mut current_node = self.current_node.clone();
@@ -1344,10 +1392,19 @@ where
                         PseudoElementType::Before => self
                             .parent_node
                             .get_details_summary_pseudo()
-                            .or_else(|| unsafe { self.parent_node.dangerous_first_child() })
+                            .or_else(|| {
+                                unsafe { self.parent_node.dangerous_first_child() }
+                                    .map(with_backdrop)
+                            })
                             .or_else(|| self.parent_node.get_after_pseudo()),
                         PseudoElementType::Normal => unsafe { node.dangerous_next_sibling() }
+                            .map(with_backdrop)
                             .or_else(|| self.parent_node.get_after_pseudo()),
+                        // The `::backdrop` of an element is immediately followed by the
+                        // element itself.
+                        PseudoElementType::Backdrop => node
+                            .as_element()
+                            .map(|el| el.with_pseudo(PseudoElementType::Normal).as_node()),
                         PseudoElementType::DetailsSummary => {
                             self.parent_node.get_details_content_pseudo()
                         },
@@ -1361,6 +1418,12 @@ where
     }
 }
 
+/// Returns the `::backdrop` of the given node if it has one, so that it is visited right
+/// before the node itself, or the node otherwise.
+fn with_backdrop<ConcreteNode: ThreadSafeLayoutNode>(node: ConcreteNode) -> ConcreteNode {
+    node.get_backdrop_pseudo().unwrap_or(node)
+}
+
 /// A wrapper around elements that ensures layout can only
 /// ever access safe properties and cannot race on elements.
 #[derive(Clone, Copy, Debug)]
@@ -1470,6 +1533,11 @@ impl<'le> ::selectors::Element for ServoThreadSafeLayoutElement<'le> {
         None
     }
 
+    fn first_element_child(&self) -> Option<Self> {
+        warn!("ServoThreadSafeLayoutElement::first_element_child called");
+        None
+    }
+
     fn is_html_slot_element(&self) -> bool {
         self.element.is_html_slot_element()
     }
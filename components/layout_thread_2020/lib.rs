@@ -31,20 +31,22 @@ use fnv::FnvHashMap;
 use fxhash::FxHashMap;
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context;
+use gfx::print::PageGeometry;
 use gfx_traits::{node_id_from_scroll_id, Epoch};
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use layout::context::LayoutContext;
 use layout::display_list::DisplayListBuilder;
+use layout::query::{process_box_sizes_request, process_clipped_boxes_request};
+use layout::query::{
+    process_caret_position_request, process_offset_parent_query, process_resolved_style_request,
+    process_style_query, process_text_index_request,
+};
 use layout::query::{
     process_content_box_request, process_content_boxes_request, LayoutRPCImpl, LayoutThreadData,
 };
 use layout::query::{process_element_inner_text_query, process_node_geometry_request};
 use layout::query::{process_node_scroll_area_request, process_node_scroll_id_request};
-use layout::query::{
-    process_offset_parent_query, process_resolved_style_request, process_style_query,
-    process_text_index_request,
-};
 use layout::traversal::RecalcStyle;
 use layout::BoxTreeRoot;
 use layout_traits::LayoutThreadFactory;
@@ -60,9 +62,10 @@ use net_traits::image_cache::ImageCache;
 use profile_traits::mem::{self as profile_mem, Report, ReportKind, ReportsChan};
 use profile_traits::time::{self as profile_time, profile, TimerMetadata};
 use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
+use script_layout_interface::message::WebFontSource;
 use script_layout_interface::message::{LayoutThreadInit, Msg, NodesFromPointQueryType};
 use script_layout_interface::message::{QueryMsg, ReflowComplete, ReflowGoal, ScriptReflow};
-use script_layout_interface::rpc::TextIndexResponse;
+use script_layout_interface::rpc::{CaretPositionResponse, TextIndexResponse};
 use script_layout_interface::rpc::{LayoutRPC, OffsetParentResponse, StyleResponse};
 use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg};
 use script_traits::{DrawAPaintImageResult, PaintWorkletError};
@@ -102,6 +105,7 @@ use style::thread_state::{self, ThreadState};
 use style::timer::Timer;
 use style::traversal::DomTraversal;
 use style::traversal_flags::TraversalFlags;
+use style::values::computed::font::FamilyName;
 use style_traits::CSSPixel;
 use style_traits::DevicePixel;
 use style_traits::SpeculativePainter;
@@ -130,10 +134,10 @@ pub struct LayoutThread {
     pipeline_port: Receiver<LayoutControlMsg>,
 
     /// The port on which we receive messages from the font cache thread.
-    font_cache_receiver: Receiver<()>,
+    font_cache_receiver: Receiver<bool>,
 
     /// The channel on which the font cache can send messages to us.
-    font_cache_sender: IpcSender<()>,
+    font_cache_sender: IpcSender<bool>,
 
     /// A means of communication with the background hang monitor.
     background_hang_monitor: Box<dyn BackgroundHangMonitor>,
@@ -395,7 +399,7 @@ fn add_font_face_rules(
     guard: &SharedRwLockReadGuard,
     device: &Device,
     font_cache_thread: &FontCacheThread,
-    font_cache_sender: &IpcSender<()>,
+    font_cache_sender: &IpcSender<bool>,
     outstanding_web_fonts_counter: &Arc<AtomicUsize>,
     load_webfonts_synchronously: bool,
 ) {
@@ -407,6 +411,7 @@ fn add_font_face_rules(
                 font_cache_thread.add_web_font(
                     font_face.family().clone(),
                     effective_sources,
+                    None,
                     sender.clone(),
                 );
                 receiver.recv().unwrap();
@@ -420,6 +425,7 @@ fn add_font_face_rules(
                 font_cache_thread.add_web_font(
                     font_face.family().clone(),
                     effective_sources,
+                    None,
                     (*font_cache_sender).clone(),
                 );
             }
@@ -455,11 +461,12 @@ impl LayoutThread {
 
         // The device pixel ratio is incorrect (it does not have the hidpi value),
         // but it will be set correctly when the initial reflow takes place.
-        let device = Device::new(
+        let mut device = Device::new(
             MediaType::screen(),
             window_size.initial_viewport,
             window_size.device_pixel_ratio,
         );
+        device.set_media_environment(window_size.media_environment);
 
         // Create the channel on which new animations can be sent.
         let (new_animations_sender, new_animations_receiver) = unbounded();
@@ -506,6 +513,9 @@ impl LayoutThread {
                 display_list: None,
                 content_box_response: None,
                 content_boxes_response: Vec::new(),
+                clipped_boxes_response: Vec::new(),
+                box_sizes_response: Vec::new(),
+                running_animations_response: Vec::new(),
                 client_rect_response: Rect::zero(),
                 scroll_id_response: None,
                 scroll_area_response: Rect::zero(),
@@ -514,9 +524,11 @@ impl LayoutThread {
                 style_response: StyleResponse(None),
                 scroll_offsets: HashMap::new(),
                 text_index_response: TextIndexResponse(None),
+                caret_position_response: CaretPositionResponse(None),
                 nodes_from_point_response: vec![],
                 element_inner_text_response: String::new(),
                 inner_window_dimensions_response: None,
+                printed_pdf_response: None,
             })),
             timer: if pref!(layout.animations.test.enabled) {
                 Timer::test_mode()
@@ -595,6 +607,9 @@ impl LayoutThread {
             Msg::RegisterPaint(..) => LayoutHangAnnotation::RegisterPaint,
             Msg::SetNavigationStart(..) => LayoutHangAnnotation::SetNavigationStart,
             Msg::GetRunningAnimations(..) => LayoutHangAnnotation::GetRunningAnimations,
+            Msg::AddFontFace(..) => LayoutHangAnnotation::AddFontFace,
+            Msg::RemoveFontFace(..) => LayoutHangAnnotation::RemoveFontFace,
+            Msg::RegisterProperty(..) => LayoutHangAnnotation::RegisterProperty,
         };
         self.background_hang_monitor
             .notify_activity(HangAnnotation::Layout(hang_annotation));
@@ -755,11 +770,66 @@ impl LayoutThread {
             Msg::GetRunningAnimations(sender) => {
                 let _ = sender.send(0);
             },
+            Msg::AddFontFace(family, font_face, source, sender) => {
+                self.handle_add_font_face(family, font_face, source, sender);
+            },
+            Msg::RemoveFontFace(family, font_face) => {
+                self.handle_remove_font_face(family, font_face);
+            },
+            Msg::RegisterProperty(registration) => {
+                self.stylist.register_custom_property(registration);
+            },
         }
 
         true
     }
 
+    /// Registers a font face created by script with the font cache thread.
+    ///
+    /// The outcome is reported to us as well as to script, so that font caches
+    /// get invalidated as when the fonts of `@font-face` rules are loaded.
+    fn handle_add_font_face(
+        &self,
+        family: FamilyName,
+        font_face: Atom,
+        source: WebFontSource,
+        sender: IpcSender<bool>,
+    ) {
+        let (font_sender, font_receiver) = ipc::channel().unwrap();
+        let font_cache_sender = self.font_cache_sender.clone();
+        ROUTER.add_route(
+            font_receiver.to_opaque(),
+            Box::new(move |message| {
+                let loaded: bool = message.to().unwrap();
+                let _ = font_cache_sender.send(loaded);
+                let _ = sender.send(loaded);
+            }),
+        );
+        self.outstanding_web_fonts.fetch_add(1, Ordering::SeqCst);
+        match source {
+            WebFontSource::Sources(sources) => {
+                self.font_cache_thread
+                    .add_web_font(family, sources, Some(font_face), font_sender)
+            },
+            WebFontSource::Data(bytes) => {
+                self.font_cache_thread
+                    .add_web_font_data(family, bytes, font_face, font_sender)
+            },
+        }
+    }
+
+    /// Removes a font face created by script from the font cache thread.
+    ///
+    /// This waits for the font cache thread, so that the next reflow doesn't
+    /// use the font face anymore.
+    fn handle_remove_font_face(&self, family: FamilyName, font_face: Atom) {
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.font_cache_thread
+            .remove_web_font(family, font_face, sender);
+        let _ = receiver.recv();
+        font_context::invalidate_font_caches();
+    }
+
     fn collect_reports<'a, 'b>(
         &self,
         reports_chan: ReportsChan,
@@ -899,6 +969,15 @@ impl LayoutThread {
                         &QueryMsg::ContentBoxesQuery(_) => {
                             rw_data.content_boxes_response = Vec::new();
                         },
+                        &QueryMsg::ClippedBoxesQuery(ref nodes) => {
+                            rw_data.clipped_boxes_response = vec![None; nodes.len()];
+                        },
+                        &QueryMsg::BoxSizesQuery(ref nodes) => {
+                            rw_data.box_sizes_response = vec![None; nodes.len()];
+                        },
+                        &QueryMsg::RunningAnimationsQuery => {
+                            rw_data.running_animations_response = Vec::new();
+                        },
                         &QueryMsg::NodesFromPointQuery(..) => {
                             rw_data.nodes_from_point_response = Vec::new();
                         },
@@ -923,12 +1002,18 @@ impl LayoutThread {
                         &QueryMsg::TextIndexQuery(..) => {
                             rw_data.text_index_response = TextIndexResponse(None);
                         },
+                        &QueryMsg::CaretPositionQuery(_) => {
+                            rw_data.caret_position_response = CaretPositionResponse(None);
+                        },
                         &QueryMsg::ElementInnerTextQuery(_) => {
                             rw_data.element_inner_text_response = String::new();
                         },
                         &QueryMsg::InnerWindowDimensionsQuery(_) => {
                             rw_data.inner_window_dimensions_response = None;
                         },
+                        &QueryMsg::PrintQuery => {
+                            rw_data.printed_pdf_response = None;
+                        },
                     },
                     ReflowGoal::Full | ReflowGoal::TickAnimations => {},
                 }
@@ -958,8 +1043,22 @@ impl LayoutThread {
             ua_or_user: &ua_or_user_guard,
         };
 
+        // When printing, the document is styled for print media in the page area of the
+        // pages described by the `@page` rules.
+        let page_geometry = match data.reflow_goal {
+            ReflowGoal::LayoutQuery(QueryMsg::PrintQuery, _) => {
+                Some(PageGeometry::from_stylist(&self.stylist, &guards))
+            },
+            _ => None,
+        };
+        let (media_type, device_viewport) = match page_geometry {
+            Some(ref page_geometry) => (MediaType::print(), page_geometry.content_size()),
+            None => (MediaType::screen(), initial_viewport),
+        };
+
         let had_used_viewport_units = self.stylist.device().used_viewport_units();
-        let device = Device::new(MediaType::screen(), initial_viewport, device_pixel_ratio);
+        let mut device = Device::new(media_type, device_viewport, device_pixel_ratio);
+        device.set_media_environment(data.window_size.media_environment);
         let sheet_origins_affected_by_device_change = self.stylist.set_device(device, &guards);
 
         self.stylist
@@ -1116,7 +1215,20 @@ impl LayoutThread {
             self.perform_post_style_recalc_layout_passes(root, &data.reflow_goal, Some(&document));
         }
 
+        // The printed document is laid out again in the page area, separately from the
+        // layout shown on the screen.
+        if let Some(ref page_geometry) = page_geometry {
+            rw_data.printed_pdf_response = self.box_tree_root.borrow().as_ref().map(|box_tree| {
+                let page_area = page_geometry.content_size();
+                let fragment_tree =
+                    rayon_pool.install(|| box_tree.layout(&layout_context, page_area));
+                fragment_tree.print_to_pdf(page_geometry)
+            });
+        }
+
         self.first_reflow.set(false);
+        data.result.borrow_mut().as_mut().unwrap().pending_web_fonts =
+            self.outstanding_web_fonts.load(Ordering::SeqCst) != 0;
         self.respond_to_query_if_necessary(&data.reflow_goal, &mut *rw_data, &mut layout_context);
     }
 
@@ -1134,6 +1246,16 @@ impl LayoutThread {
                 &QueryMsg::ContentBoxesQuery(node) => {
                     rw_data.content_boxes_response = process_content_boxes_request(node);
                 },
+                &QueryMsg::ClippedBoxesQuery(ref nodes) => {
+                    rw_data.clipped_boxes_response = process_clipped_boxes_request(nodes);
+                },
+                &QueryMsg::BoxSizesQuery(ref nodes) => {
+                    rw_data.box_sizes_response = process_box_sizes_request(nodes);
+                },
+                // This layout doesn't run CSS animations and transitions yet.
+                &QueryMsg::RunningAnimationsQuery => {
+                    rw_data.running_animations_response = Vec::new();
+                },
                 &QueryMsg::TextIndexQuery(node, point_in_node) => {
                     let point_in_node = Point2D::new(
                         Au::from_f32_px(point_in_node.x),
@@ -1141,6 +1263,10 @@ impl LayoutThread {
                     );
                     rw_data.text_index_response = process_text_index_request(node, point_in_node);
                 },
+                &QueryMsg::CaretPositionQuery(point) => {
+                    let point = Point2D::new(Au::from_f32_px(point.x), Au::from_f32_px(point.y));
+                    rw_data.caret_position_response = process_caret_position_request(point);
+                },
                 &QueryMsg::NodeGeometryQuery(node) => {
                     rw_data.client_rect_response = process_node_geometry_request(node);
                 },
@@ -1197,6 +1323,9 @@ impl LayoutThread {
                     //            builder in order to support query iframe sizing.
                     rw_data.inner_window_dimensions_response = None;
                 },
+                // The printed document is produced by `handle_reflow`, which lays it out for
+                // the pages.
+                &QueryMsg::PrintQuery => {},
             },
             ReflowGoal::Full | ReflowGoal::TickAnimations => {},
         }
@@ -742,6 +742,8 @@ where
             Component::Slotted(ref selector) | Component::Host(Some(ref selector)) => {
                 selector.size_of(ops)
             },
+            Component::Is(ref list) | Component::Where(ref list) => list.size_of(ops),
+            Component::Has(ref list) => list.size_of(ops),
             Component::PseudoElement(ref pseudo) => (*pseudo).size_of(ops),
             Component::Combinator(..) |
             Component::ExplicitAnyNamespace |
@@ -768,11 +770,24 @@ where
             Component::FirstOfType |
             Component::LastOfType |
             Component::OnlyOfType |
+            Component::RelativeSelectorAnchor |
+            Component::ParentSelector |
             Component::Host(None) => 0,
         }
     }
 }
 
+impl<Impl: selectors::parser::SelectorImpl> MallocSizeOf
+    for selectors::parser::RelativeSelector<Impl>
+where
+    Impl::NonTSPseudoClass: MallocSizeOf,
+    Impl::PseudoElement: MallocSizeOf,
+{
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        self.selector.size_of(ops)
+    }
+}
+
 impl<Impl: selectors::parser::SelectorImpl> MallocSizeOf
     for selectors::attr::AttrSelectorWithOptionalNamespace<Impl>
 {
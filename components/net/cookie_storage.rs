@@ -91,6 +91,16 @@ impl CookieStorage {
         }
     }
 
+    pub fn delete_cookie(&mut self, name: &str, domain: &str, path: &str) {
+        if let Some(cookies) = self.cookies_map.get_mut(&reg_host(domain)) {
+            cookies.retain(|c| {
+                c.cookie.name() != name ||
+                    c.cookie.domain() != Some(domain) ||
+                    c.cookie.path() != Some(path)
+            });
+        }
+    }
+
     // http://tools.ietf.org/html/rfc6265#section-5.3
     pub fn push(&mut self, mut cookie: Cookie, url: &ServoUrl, source: CookieSource) {
         // https://www.ietf.org/id/draft-ietf-httpbis-cookie-alone-01.txt Step 1
@@ -13,6 +13,7 @@ use crate::fetch::methods::{
 use crate::fetch::methods::{Data, DoneChannel, FetchContext, Target};
 use crate::hsts::HstsList;
 use crate::http_cache::{CacheKey, HttpCache};
+use crate::network_conditions::{self, NetworkConditions};
 use crate::resource_thread::AuthCache;
 use crossbeam_channel::{unbounded, Sender};
 use devtools_traits::{
@@ -56,10 +57,12 @@ use std::mem;
 use std::ops::Deref;
 use std::str::FromStr;
 use std::sync::{Condvar, Mutex, RwLock};
-use std::time::{Duration, SystemTime};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 use time::{self, Tm};
 use tokio::prelude::{future, Future, Stream};
 use tokio::runtime::Runtime;
+use tokio::timer::Delay;
 
 lazy_static! {
     pub static ref HANDLE: Mutex<Runtime> = { Mutex::new(Runtime::new().unwrap()) };
@@ -86,6 +89,8 @@ pub struct HttpState {
     pub http_cache_state: Mutex<HashMap<CacheKey, Arc<(Mutex<HttpCacheEntryState>, Condvar)>>>,
     pub auth_cache: RwLock<AuthCache>,
     pub history_states: RwLock<HashMap<HistoryStateId, Vec<u8>>>,
+    /// Network conditions emulated for the devtools server.
+    pub network_conditions: RwLock<NetworkConditions>,
     pub client: Client<Connector, Body>,
 }
 
@@ -98,6 +103,7 @@ impl HttpState {
             history_states: RwLock::new(HashMap::new()),
             http_cache: RwLock::new(HttpCache::new()),
             http_cache_state: Mutex::new(HashMap::new()),
+            network_conditions: RwLock::new(NetworkConditions::new()),
             client: create_http_client(ssl_connector_builder, HANDLE.lock().unwrap().executor()),
         }
     }
@@ -1399,6 +1405,26 @@ fn http_network_fetch(
     // Step 5
     let url = request.current_url();
 
+    // Let the devtools server block, replace or slow down this request.
+    let (delay, throttle) = {
+        let conditions = context.state.network_conditions.read().unwrap();
+        if conditions.is_blocked(&url) {
+            return Response::network_error(NetworkError::Internal(
+                "Request blocked by devtools".into(),
+            ));
+        }
+        if let Some(body) = conditions.response_override(&url) {
+            return overridden_response(url, body, context);
+        }
+        let body_length = request.body.as_ref().map_or(0, Vec::len);
+        (conditions.request_delay(body_length), conditions.throttle())
+    };
+    // This holds the fetch thread for the emulated latency and upload time, just like
+    // waiting for the response below does.
+    if let Some(delay) = delay {
+        thread::sleep(delay);
+    }
+
     let request_id = context
         .devtools_chan
         .as_ref()
@@ -1538,6 +1564,20 @@ fn http_network_fetch(
     HANDLE.lock().unwrap().spawn(
         res.into_body()
             .map_err(|_| ())
+            .and_then(move |chunk| {
+                // Hold each chunk back for as long as the emulated network takes to download it.
+                let download_time = throttle.and_then(|throttle| {
+                    network_conditions::transfer_time(chunk.len(), throttle.download_throughput)
+                });
+                match download_time {
+                    Some(time) => future::Either::A(
+                        Delay::new(Instant::now() + time)
+                            .map(move |_| chunk)
+                            .map_err(|_| ()),
+                    ),
+                    None => future::Either::B(future::ok(chunk)),
+                }
+            })
             .fold(res_body, move |res_body, chunk| {
                 if cancellation_listener.lock().unwrap().cancelled() {
                     *res_body.lock().unwrap() = ResponseBody::Done(vec![]);
@@ -1635,6 +1675,19 @@ fn http_network_fetch(
     response
 }
 
+/// A response serving a body provided through the devtools server instead of the network.
+fn overridden_response(url: ServoUrl, body: Vec<u8>, context: &FetchContext) -> Response {
+    let timing = context.timing.lock().unwrap().clone();
+    let mut response = Response::new(url, timing);
+    response.status = Some((StatusCode::OK, "OK".into()));
+    response.raw_status = Some((StatusCode::OK.as_u16(), b"OK".to_vec()));
+    response
+        .headers
+        .typed_insert(ContentLength(body.len() as u64));
+    *response.body.lock().unwrap() = ResponseBody::Done(body);
+    response
+}
+
 /// [CORS preflight fetch](https://fetch.spec.whatwg.org#cors-preflight-fetch)
 fn cors_preflight_fetch(
     request: &Request,
@@ -31,6 +31,7 @@ pub mod http_cache;
 pub mod http_loader;
 pub mod image_cache;
 pub mod mime_classifier;
+pub mod network_conditions;
 pub mod resource_thread;
 mod storage_thread;
 pub mod subresource_integrity;
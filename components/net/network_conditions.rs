@@ -0,0 +1,86 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Network conditions emulated on behalf of the devtools server, which can slow down,
+//! block or replace the responses of HTTP fetches.
+
+use devtools_traits::{NetworkControlMsg, NetworkThrottle};
+use servo_url::ServoUrl;
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[derive(Default)]
+pub struct NetworkConditions {
+    throttle: Option<NetworkThrottle>,
+    blocked_urls: Vec<String>,
+    overrides: HashMap<ServoUrl, Vec<u8>>,
+}
+
+impl NetworkConditions {
+    pub fn new() -> NetworkConditions {
+        NetworkConditions::default()
+    }
+
+    pub fn handle_msg(&mut self, msg: NetworkControlMsg) {
+        match msg {
+            NetworkControlMsg::SetThrottle(throttle) => self.throttle = throttle,
+            NetworkControlMsg::SetBlockedUrls(patterns) => self.blocked_urls = patterns,
+            NetworkControlMsg::SetResponseOverride(url, Some(body)) => {
+                self.overrides.insert(url, body);
+            },
+            NetworkControlMsg::SetResponseOverride(url, None) => {
+                self.overrides.remove(&url);
+            },
+        }
+    }
+
+    pub fn throttle(&self) -> Option<NetworkThrottle> {
+        self.throttle
+    }
+
+    pub fn is_blocked(&self, url: &ServoUrl) -> bool {
+        self.blocked_urls
+            .iter()
+            .any(|pattern| matches_pattern(url.as_str(), pattern))
+    }
+
+    pub fn response_override(&self, url: &ServoUrl) -> Option<Vec<u8>> {
+        self.overrides.get(url).cloned()
+    }
+
+    /// The time to wait before sending a request with a body of the given length.
+    pub fn request_delay(&self, body_length: usize) -> Option<Duration> {
+        let throttle = self.throttle?;
+        let latency = Duration::from_millis(throttle.latency);
+        let upload = transfer_time(body_length, throttle.upload_throughput);
+        Some(latency + upload.unwrap_or_default())
+    }
+}
+
+/// The time it takes to transfer the given number of bytes with the given throughput,
+/// in bytes per second.
+pub fn transfer_time(length: usize, throughput: u64) -> Option<Duration> {
+    if throughput == 0 || length == 0 {
+        return None;
+    }
+    Some(Duration::from_micros(
+        length as u64 * 1_000_000 / throughput,
+    ))
+}
+
+/// Whether the URL contains the pattern, where each `*` in the pattern matches any
+/// sequence of characters. Empty patterns match nothing.
+fn matches_pattern(url: &str, pattern: &str) -> bool {
+    if pattern.is_empty() {
+        return false;
+    }
+    let mut rest = url;
+    for part in pattern.split('*') {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
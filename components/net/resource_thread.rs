@@ -13,10 +13,11 @@ use crate::filemanager_thread::FileManager;
 use crate::hsts::HstsList;
 use crate::http_cache::HttpCache;
 use crate::http_loader::{http_redirect_fetch, HttpState, HANDLE};
+use crate::network_conditions::NetworkConditions;
 use crate::storage_thread::StorageThreadFactory;
 use crate::websocket_loader;
 use crossbeam_channel::Sender;
-use devtools_traits::DevtoolsControlMsg;
+use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg, NetworkControlMsg};
 use embedder_traits::resources::{self, Resource};
 use embedder_traits::EmbedderProxy;
 use hyper_serde::Serde;
@@ -148,6 +149,7 @@ fn create_http_states(
         history_states: RwLock::new(HashMap::new()),
         http_cache: RwLock::new(http_cache),
         http_cache_state: Mutex::new(HashMap::new()),
+        network_conditions: RwLock::new(NetworkConditions::new()),
         client: create_http_client(
             create_ssl_connector_builder(&certs),
             HANDLE.lock().unwrap().executor(),
@@ -161,6 +163,7 @@ fn create_http_states(
         history_states: RwLock::new(HashMap::new()),
         http_cache: RwLock::new(HttpCache::new()),
         http_cache_state: Mutex::new(HashMap::new()),
+        network_conditions: RwLock::new(NetworkConditions::new()),
         client: create_http_client(
             create_ssl_connector_builder(&certs),
             HANDLE.lock().unwrap().executor(),
@@ -188,6 +191,15 @@ impl ResourceChannelManager {
         let public_id = rx_set.add(public_receiver).unwrap();
         let reporter_id = rx_set.add(memory_reporter).unwrap();
 
+        // Let the devtools server emulate network conditions for both groups.
+        let (network_control_chan, network_control_port) =
+            ipc::channel::<NetworkControlMsg>().unwrap();
+        let network_control_id = rx_set.add(network_control_port).unwrap();
+        if let Some(ref devtools_chan) = self.resource_manager.devtools_chan {
+            let msg = ChromeToDevtoolsControlMsg::NetworkControlChannel(network_control_chan);
+            let _ = devtools_chan.send(DevtoolsControlMsg::FromChrome(msg));
+        }
+
         loop {
             for receiver in rx_set.select().unwrap().into_iter() {
                 // Handles case where profiler thread shuts down before resource thread.
@@ -202,6 +214,19 @@ impl ResourceChannelManager {
                         self.process_report(msg, &private_http_state, &public_http_state);
                         continue;
                     }
+                } else if id == network_control_id {
+                    if let Ok(msg) = data.to::<NetworkControlMsg>() {
+                        private_http_state
+                            .network_conditions
+                            .write()
+                            .unwrap()
+                            .handle_msg(msg.clone());
+                        public_http_state
+                            .network_conditions
+                            .write()
+                            .unwrap()
+                            .handle_msg(msg);
+                    }
                 } else {
                     let group = if id == private_id {
                         &private_http_state
@@ -274,6 +299,13 @@ impl ResourceChannelManager {
                     .clear_storage(&request);
                 return true;
             },
+            CoreResourceMsg::DeleteCookie(name, domain, path) => {
+                http_state
+                    .cookie_jar
+                    .write()
+                    .unwrap()
+                    .delete_cookie(&name, &domain, &path);
+            },
             CoreResourceMsg::FetchRedirect(req_init, res_init, sender, cancel_chan) => self
                 .resource_manager
                 .fetch(req_init, Some(res_init), sender, http_state, cancel_chan),
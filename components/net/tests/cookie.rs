@@ -475,3 +475,52 @@ fn test_cookie_eviction_all_nonsecure_new_nonsecure() {
         "extra2=bar; extra3=bar; extra4=bar; extra5=bar; foo=bar"
     );
 }
+
+#[test]
+fn test_delete_cookie() {
+    let url = ServoUrl::parse("http://example.com/foo/").unwrap();
+    let mut storage = CookieStorage::new(150);
+    add_cookie_to_storage(&mut storage, &url, "foo=bar");
+    add_cookie_to_storage(&mut storage, &url, "baz=qux");
+
+    storage.delete_cookie("foo", "example.com", "/foo");
+    assert_eq!(
+        storage.cookies_for_url(&url, CookieSource::HTTP).unwrap(),
+        "baz=qux"
+    );
+
+    storage.delete_cookie("baz", "example.com", "/foo");
+    assert_eq!(storage.cookies_for_url(&url, CookieSource::HTTP), None);
+}
+
+#[test]
+fn test_delete_cookie_on_other_path() {
+    let url = ServoUrl::parse("http://example.com/foo/").unwrap();
+    let other_url = ServoUrl::parse("http://example.com/bar/").unwrap();
+    let mut storage = CookieStorage::new(150);
+    add_cookie_to_storage(&mut storage, &url, "foo=bar; Path=/bar");
+    add_cookie_to_storage(&mut storage, &url, "foo=baz");
+
+    storage.delete_cookie("foo", "example.com", "/bar");
+    assert_eq!(
+        storage.cookies_for_url(&other_url, CookieSource::HTTP),
+        None
+    );
+    assert_eq!(
+        storage.cookies_for_url(&url, CookieSource::HTTP).unwrap(),
+        "foo=baz"
+    );
+}
+
+#[test]
+fn test_delete_cookie_for_unknown_domain() {
+    let url = ServoUrl::parse("http://example.com/").unwrap();
+    let mut storage = CookieStorage::new(150);
+    add_cookie_to_storage(&mut storage, &url, "foo=bar");
+
+    storage.delete_cookie("foo", "example.org", "/");
+    assert_eq!(
+        storage.cookies_for_url(&url, CookieSource::HTTP).unwrap(),
+        "foo=bar"
+    );
+}
@@ -11,6 +11,7 @@ use crossbeam_channel::{unbounded, Receiver};
 use devtools_traits::HttpRequest as DevtoolsHttpRequest;
 use devtools_traits::HttpResponse as DevtoolsHttpResponse;
 use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg, NetworkEvent};
+use devtools_traits::{NetworkControlMsg, NetworkThrottle};
 use flate2::write::{DeflateEncoder, GzEncoder};
 use flate2::Compression;
 use futures::{self, Future, Stream};
@@ -39,7 +40,7 @@ use std::io::Write;
 use std::str;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 fn mock_origin() -> ImmutableOrigin {
     ServoUrl::parse("http://servo.org").unwrap().origin()
@@ -1046,6 +1047,145 @@ fn test_load_errors_when_there_a_redirect_loop() {
     );
 }
 
+#[test]
+fn test_load_fails_for_url_blocked_by_devtools() {
+    let handled = Arc::new(AtomicBool::new(false));
+    let handled_clone = handled.clone();
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        handled_clone.store(true, Ordering::SeqCst);
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut request = RequestBuilder::new(url.join("/blocked/resource").unwrap())
+        .method(Method::GET)
+        .destination(Destination::Document)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+
+    let mut context = new_fetch_context(None, None);
+    context
+        .state
+        .network_conditions
+        .write()
+        .unwrap()
+        .handle_msg(NetworkControlMsg::SetBlockedUrls(vec![
+            "/blocked/*".to_owned()
+        ]));
+    let response = fetch_with_context(&mut request, &mut context);
+
+    let _ = server.close();
+
+    assert!(response.is_network_error());
+    assert!(!handled.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_load_is_not_blocked_by_empty_devtools_pattern() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut request = RequestBuilder::new(url.clone())
+        .method(Method::GET)
+        .destination(Destination::Document)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+
+    let mut context = new_fetch_context(None, None);
+    context
+        .state
+        .network_conditions
+        .write()
+        .unwrap()
+        .handle_msg(NetworkControlMsg::SetBlockedUrls(vec!["".to_owned()]));
+    let response = fetch_with_context(&mut request, &mut context);
+
+    let _ = server.close();
+
+    assert!(!response.is_network_error());
+}
+
+#[test]
+fn test_load_is_delayed_by_devtools_throttling() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut request = RequestBuilder::new(url.clone())
+        .method(Method::GET)
+        .destination(Destination::Document)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+
+    let mut context = new_fetch_context(None, None);
+    context
+        .state
+        .network_conditions
+        .write()
+        .unwrap()
+        .handle_msg(NetworkControlMsg::SetThrottle(Some(NetworkThrottle {
+            latency: 200,
+            download_throughput: 0,
+            upload_throughput: 0,
+        })));
+    let start = Instant::now();
+    let response = fetch_with_context(&mut request, &mut context);
+
+    let _ = server.close();
+
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    let response = response.to_actual();
+    assert_eq!(
+        *response.body.lock().unwrap(),
+        ResponseBody::Done(b"Yay!".to_vec())
+    );
+}
+
+#[test]
+fn test_load_serves_response_overridden_by_devtools() {
+    let handled = Arc::new(AtomicBool::new(false));
+    let handled_clone = handled.clone();
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        handled_clone.store(true, Ordering::SeqCst);
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut request = RequestBuilder::new(url.clone())
+        .method(Method::GET)
+        .destination(Destination::Document)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+
+    let mut context = new_fetch_context(None, None);
+    context
+        .state
+        .network_conditions
+        .write()
+        .unwrap()
+        .handle_msg(NetworkControlMsg::SetResponseOverride(
+            url.clone(),
+            Some(b"Overridden".to_vec()),
+        ));
+    let response = fetch_with_context(&mut request, &mut context);
+
+    let _ = server.close();
+
+    let response = response.to_actual();
+    assert!(!handled.load(Ordering::SeqCst));
+    assert_eq!(
+        *response.body.lock().unwrap(),
+        ResponseBody::Done(b"Overridden".to_vec())
+    );
+}
+
 #[test]
 fn test_load_succeeds_with_a_redirect_loop() {
     let url_b_for_a = Arc::new(Mutex::new(None::<ServoUrl>));
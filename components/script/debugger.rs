@@ -0,0 +1,459 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A JavaScript debugger for the globals of a script thread, built on SpiderMonkey's
+//! `Debugger` API.
+//!
+//! A `Debugger` object must live in a different compartment than the globals it
+//! debugs, so the debugger runs `resources/debugger.js` in a global of its own.
+//! Requests from the devtools server are forwarded to that script as JSON, and the
+//! script calls back into `pause` whenever a breakpoint, step, interrupt or exception
+//! should suspend execution; the script thread then only processes devtools messages
+//! until it is told to resume.
+
+use crate::dom::bindings::conversions::jsstring_to_str;
+use crate::dom::bindings::error::report_pending_exception;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::JSContext as SafeJSContext;
+use crate::script_thread::ScriptThread;
+use devtools_traits::{DebuggerPause, EnvironmentInfo, EvaluateJSReply, FrameInfo};
+use devtools_traits::{ObjectProperties, PauseReason, PropertyInfo, ResumeLimit};
+use devtools_traits::{SourceInfo, SourceLocation};
+use embedder_traits::resources::{self, Resource};
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{CallArgs, HandleValueArray, Heap, JSAutoRealm, JSContext, JSObject};
+use js::jsapi::{JS_DefineDebuggerObject, JS_DefineFunction, JS_NewGlobalObject};
+use js::jsapi::{JS_WrapObject, OnNewGlobalHookOption, Value};
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::rust::wrappers::{EvaluateUtf8, JS_CallFunctionName};
+use js::rust::{CompileOptionsWrapper, HandleValue, RealmOptions, SIMPLE_GLOBAL_CLASS};
+use msg::constellation_msg::PipelineId;
+use serde::de::DeserializeOwned;
+use std::ffi::CString;
+use std::ptr;
+
+/// The owner of the debugger global of a script thread.
+#[derive(JSTraceable)]
+pub struct ScriptDebugger {
+    global: Box<Heap<*mut JSObject>>,
+}
+
+/// A request handled by `handleRequest` in `debugger.js`.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum DebuggerRequest<'a> {
+    RemoveDebuggee {
+        pipeline: String,
+    },
+    GetSources {
+        pipeline: String,
+    },
+    GetSourceText {
+        id: &'a str,
+    },
+    SetBreakpoint {
+        url: &'a str,
+        line: u32,
+        column: u32,
+        condition: Option<&'a str>,
+    },
+    RemoveBreakpoint {
+        url: &'a str,
+        line: u32,
+        column: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    PauseOnExceptions {
+        pause: bool,
+        ignore_caught: bool,
+    },
+    Interrupt {
+        pipeline: String,
+    },
+    GetFrames {
+        pipeline: String,
+        start: u32,
+        count: u32,
+    },
+    GetFrameEnvironment {
+        pipeline: String,
+        depth: u32,
+    },
+    GetObjectProperties {
+        pipeline: String,
+        id: &'a str,
+    },
+}
+
+/// A debuggee value as described by `grip` in `debugger.js`. JSON cannot express
+/// non-finite numbers or negative zero, so those are carried as strings.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum JsGrip {
+    Undefined,
+    Null,
+    Boolean { value: bool },
+    Number { value: f64 },
+    SpecialNumber { value: String },
+    String { value: String },
+    Object { class: String, id: String },
+}
+
+impl From<JsGrip> for EvaluateJSReply {
+    fn from(grip: JsGrip) -> EvaluateJSReply {
+        match grip {
+            JsGrip::Undefined => EvaluateJSReply::VoidValue,
+            JsGrip::Null => EvaluateJSReply::NullValue,
+            JsGrip::Boolean { value } => EvaluateJSReply::BooleanValue(value),
+            JsGrip::Number { value } => EvaluateJSReply::NumberValue(value),
+            JsGrip::SpecialNumber { value } => {
+                EvaluateJSReply::NumberValue(value.parse().unwrap_or(std::f64::NAN))
+            },
+            JsGrip::String { value } => EvaluateJSReply::StringValue(value),
+            JsGrip::Object { class, id } => EvaluateJSReply::ActorValue { class, uuid: id },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsFrame {
+    depth: u32,
+    kind: String,
+    display_name: Option<String>,
+    location: SourceLocation,
+    this: JsGrip,
+}
+
+impl From<JsFrame> for FrameInfo {
+    fn from(frame: JsFrame) -> FrameInfo {
+        FrameInfo {
+            depth: frame.depth,
+            kind: frame.kind,
+            display_name: frame.display_name,
+            location: frame.location,
+            this: frame.this.into(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsEnvironment {
+    kind: String,
+    function_name: Option<String>,
+    object: Option<JsGrip>,
+    bindings: Vec<(String, JsGrip)>,
+    parent: Option<Box<JsEnvironment>>,
+}
+
+impl From<JsEnvironment> for EnvironmentInfo {
+    fn from(environment: JsEnvironment) -> EnvironmentInfo {
+        EnvironmentInfo {
+            kind: environment.kind,
+            function_name: environment.function_name,
+            object: environment.object.map(Into::into),
+            bindings: environment
+                .bindings
+                .into_iter()
+                .map(|(name, value)| (name, value.into()))
+                .collect(),
+            parent: environment.parent.map(|parent| Box::new((*parent).into())),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsProperty {
+    name: String,
+    value: Option<JsGrip>,
+    enumerable: bool,
+    writable: bool,
+    configurable: bool,
+}
+
+#[derive(Deserialize)]
+struct JsObjectProperties {
+    prototype: JsGrip,
+    properties: Vec<JsProperty>,
+}
+
+impl From<JsObjectProperties> for ObjectProperties {
+    fn from(object: JsObjectProperties) -> ObjectProperties {
+        ObjectProperties {
+            prototype: object.prototype.into(),
+            properties: object
+                .properties
+                .into_iter()
+                .map(|property| PropertyInfo {
+                    name: property.name,
+                    value: property.value.map(Into::into),
+                    enumerable: property.enumerable,
+                    writable: property.writable,
+                    configurable: property.configurable,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The argument of `servoDebuggerPause`.
+#[derive(Deserialize)]
+struct JsPause {
+    pipeline: String,
+    reason: String,
+    exception: Option<JsGrip>,
+    frame: Option<JsFrame>,
+}
+
+fn pipeline_key(pipeline: PipelineId) -> String {
+    serde_json::to_string(&pipeline).unwrap()
+}
+
+impl ScriptDebugger {
+    /// Create the debugger global and run `debugger.js` in it.
+    #[allow(unsafe_code)]
+    pub fn new(cx: SafeJSContext) -> ScriptDebugger {
+        let debugger = ScriptDebugger {
+            global: Box::new(Heap::default()),
+        };
+        unsafe {
+            let options = RealmOptions::default();
+            rooted!(in(*cx) let global = JS_NewGlobalObject(
+                *cx,
+                &SIMPLE_GLOBAL_CLASS,
+                ptr::null_mut(),
+                OnNewGlobalHookOption::FireOnNewGlobalHook,
+                &*options,
+            ));
+            assert!(!global.is_null());
+            debugger.global.set(global.get());
+
+            let _ac = JSAutoRealm::new(*cx, global.get());
+            assert!(JS_DefineDebuggerObject(*cx, global.handle().into()));
+            assert!(!JS_DefineFunction(
+                *cx,
+                global.handle().into(),
+                b"servoDebuggerPause\0".as_ptr() as *const _,
+                Some(pause),
+                1,
+                0,
+            )
+            .is_null());
+
+            let source = resources::read_string(Resource::DebuggerJS);
+            let filename = CString::new("debugger.js").unwrap();
+            let options = CompileOptionsWrapper::new(*cx, filename.as_ptr(), 0);
+            rooted!(in(*cx) let mut rval = UndefinedValue());
+            if !EvaluateUtf8(
+                *cx,
+                options.ptr,
+                source.as_ptr() as *const _,
+                source.len() as libc::size_t,
+                rval.handle_mut(),
+            ) {
+                report_pending_exception(*cx, false);
+                warn!("Failed to initialize the script debugger");
+            }
+        }
+        debugger
+    }
+
+    /// Call the given function of `debugger.js` with the given arguments, returning
+    /// the JSON string it produces.
+    #[allow(unsafe_code)]
+    unsafe fn call(&self, cx: *mut JSContext, function: &[u8], args: &[Value]) -> Option<String> {
+        rooted!(in(cx) let global = self.global.get());
+        let args = HandleValueArray::from_rooted_slice(args);
+        rooted!(in(cx) let mut rval = UndefinedValue());
+        if !JS_CallFunctionName(
+            cx,
+            global.handle(),
+            function.as_ptr() as *const _,
+            &args,
+            rval.handle_mut(),
+        ) {
+            report_pending_exception(cx, false);
+            return None;
+        }
+        if !rval.is_string() {
+            return None;
+        }
+        Some(String::from(jsstring_to_str(cx, rval.to_string())))
+    }
+
+    #[allow(unsafe_code)]
+    fn request<T: DeserializeOwned>(
+        &self,
+        cx: SafeJSContext,
+        request: DebuggerRequest,
+    ) -> Option<T> {
+        let request = serde_json::to_string(&request).unwrap();
+        let reply = unsafe {
+            let _ac = JSAutoRealm::new(*cx, self.global.get());
+            rooted!(in(*cx) let mut arg = UndefinedValue());
+            request.to_jsval(*cx, arg.handle_mut());
+            self.call(*cx, b"handleRequest\0", &[arg.get()])?
+        };
+        match serde_json::from_str::<Option<T>>(&reply) {
+            Ok(value) => value,
+            Err(error) => {
+                warn!("Unexpected reply from the script debugger: {}", error);
+                None
+            },
+        }
+    }
+
+    /// Start debugging the scripts of the given global.
+    #[allow(unsafe_code)]
+    pub fn add_debuggee(&self, cx: SafeJSContext, global: &GlobalScope, pipeline: PipelineId) {
+        unsafe {
+            let _ac = JSAutoRealm::new(*cx, self.global.get());
+            rooted!(in(*cx) let mut debuggee = global.reflector().get_jsobject().get());
+            if !JS_WrapObject(*cx, debuggee.handle_mut().into()) {
+                return report_pending_exception(*cx, false);
+            }
+            rooted!(in(*cx) let mut key = UndefinedValue());
+            pipeline_key(pipeline).to_jsval(*cx, key.handle_mut());
+            self.call(
+                *cx,
+                b"addDebuggee\0",
+                &[ObjectValue(debuggee.get()), key.get()],
+            );
+        }
+    }
+
+    pub fn remove_debuggee(&self, cx: SafeJSContext, pipeline: PipelineId) {
+        let pipeline = pipeline_key(pipeline);
+        let _: Option<()> = self.request(cx, DebuggerRequest::RemoveDebuggee { pipeline });
+    }
+
+    pub fn sources(&self, cx: SafeJSContext, pipeline: PipelineId) -> Vec<SourceInfo> {
+        let pipeline = pipeline_key(pipeline);
+        self.request(cx, DebuggerRequest::GetSources { pipeline })
+            .unwrap_or_default()
+    }
+
+    pub fn source_text(&self, cx: SafeJSContext, id: &str) -> Option<String> {
+        self.request(cx, DebuggerRequest::GetSourceText { id })
+    }
+
+    pub fn set_breakpoint(
+        &self,
+        cx: SafeJSContext,
+        url: &str,
+        line: u32,
+        column: u32,
+        condition: Option<&str>,
+    ) {
+        let request = DebuggerRequest::SetBreakpoint {
+            url,
+            line,
+            column,
+            condition,
+        };
+        let _: Option<()> = self.request(cx, request);
+    }
+
+    pub fn remove_breakpoint(&self, cx: SafeJSContext, url: &str, line: u32, column: u32) {
+        let request = DebuggerRequest::RemoveBreakpoint { url, line, column };
+        let _: Option<()> = self.request(cx, request);
+    }
+
+    pub fn pause_on_exceptions(&self, cx: SafeJSContext, pause: bool, ignore_caught: bool) {
+        let request = DebuggerRequest::PauseOnExceptions {
+            pause,
+            ignore_caught,
+        };
+        let _: Option<()> = self.request(cx, request);
+    }
+
+    pub fn interrupt(&self, cx: SafeJSContext, pipeline: PipelineId) {
+        let pipeline = pipeline_key(pipeline);
+        let _: Option<()> = self.request(cx, DebuggerRequest::Interrupt { pipeline });
+    }
+
+    pub fn frames(
+        &self,
+        cx: SafeJSContext,
+        pipeline: PipelineId,
+        start: u32,
+        count: u32,
+    ) -> Vec<FrameInfo> {
+        let pipeline = pipeline_key(pipeline);
+        let request = DebuggerRequest::GetFrames {
+            pipeline,
+            start,
+            count,
+        };
+        let frames: Vec<JsFrame> = self.request(cx, request).unwrap_or_default();
+        frames.into_iter().map(Into::into).collect()
+    }
+
+    pub fn frame_environment(
+        &self,
+        cx: SafeJSContext,
+        pipeline: PipelineId,
+        depth: u32,
+    ) -> Option<EnvironmentInfo> {
+        let pipeline = pipeline_key(pipeline);
+        let request = DebuggerRequest::GetFrameEnvironment { pipeline, depth };
+        self.request::<JsEnvironment>(cx, request).map(Into::into)
+    }
+
+    pub fn object_properties(
+        &self,
+        cx: SafeJSContext,
+        pipeline: PipelineId,
+        id: &str,
+    ) -> Option<ObjectProperties> {
+        let pipeline = pipeline_key(pipeline);
+        let request = DebuggerRequest::GetObjectProperties { pipeline, id };
+        self.request::<JsObjectProperties>(cx, request)
+            .map(Into::into)
+    }
+}
+
+fn pause_from_js(pause: JsPause) -> Option<(PipelineId, DebuggerPause)> {
+    let pipeline = serde_json::from_str(&pause.pipeline).ok()?;
+    let reason = match &*pause.reason {
+        "Interrupted" => PauseReason::Interrupted,
+        "Breakpoint" => PauseReason::Breakpoint,
+        "DebuggerStatement" => PauseReason::DebuggerStatement,
+        "ResumeLimit" => PauseReason::ResumeLimit,
+        "Exception" => PauseReason::Exception(
+            pause
+                .exception
+                .map(Into::into)
+                .unwrap_or(EvaluateJSReply::VoidValue),
+        ),
+        _ => return None,
+    };
+    let pause = DebuggerPause {
+        reason,
+        frame: pause.frame.map(Into::into),
+    };
+    Some((pipeline, pause))
+}
+
+/// The native implementation of `servoDebuggerPause` in `debugger.js`. Blocks until the
+/// devtools client resumes the paused pipeline, and returns the resume limit as JSON.
+#[allow(unsafe_code)]
+unsafe extern "C" fn pause(cx: *mut JSContext, argc: u32, vp: *mut Value) -> bool {
+    let args = CallArgs::from_vp(vp, argc);
+    let arg = HandleValue::from_raw(args.get(0));
+    let mut limit: Option<ResumeLimit> = None;
+    if arg.is_string() {
+        let json = String::from(jsstring_to_str(cx, arg.to_string()));
+        match serde_json::from_str(&json).ok().and_then(pause_from_js) {
+            Some((pipeline, pause)) => limit = ScriptThread::pause_for_debugger(pipeline, pause),
+            None => warn!("Ignoring malformed debugger pause {}", json),
+        }
+    }
+    rooted!(in(cx) let mut rval = UndefinedValue());
+    serde_json::to_string(&limit)
+        .unwrap()
+        .to_jsval(cx, rval.handle_mut());
+    *args.rval() = rval.get();
+    true
+}
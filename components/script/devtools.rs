@@ -3,29 +3,62 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::compartments::enter_realm;
+use crate::debugger::ScriptDebugger;
+use crate::dom::bindings::codegen::Bindings::CSSGroupingRuleBinding::CSSGroupingRuleMethods;
+use crate::dom::bindings::codegen::Bindings::CSSRuleListBinding::CSSRuleListMethods;
 use crate::dom::bindings::codegen::Bindings::CSSStyleDeclarationBinding::CSSStyleDeclarationMethods;
+use crate::dom::bindings::codegen::Bindings::CSSStyleRuleBinding::CSSStyleRuleMethods;
+use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding::CSSStyleSheetMethods;
 use crate::dom::bindings::codegen::Bindings::DOMRectBinding::DOMRectMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
+use crate::dom::bindings::codegen::Bindings::StyleSheetBinding::StyleSheetMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::conversions::{jsstring_to_str, ConversionResult, FromJSValConvertible};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::document::AnimationFrameCallback;
+use crate::dom::cssgroupingrule::CSSGroupingRule;
+use crate::dom::cssrulelist::{CSSRuleList, RulesSource};
+use crate::dom::cssstyledeclaration::CSSStyleDeclaration;
+use crate::dom::cssstylerule::CSSStyleRule;
+use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::document::{AnimationFrameCallback, Document};
 use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlelement::HTMLElement;
 use crate::dom::node::{window_from_node, Node, ShadowIncluding};
+use crate::dom::stylesheet::StyleSheet;
 use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
 use crate::script_thread::Documents;
-use devtools_traits::{AutoMargins, ComputedNodeLayout, TimelineMarkerType};
-use devtools_traits::{EvaluateJSReply, Modification, NodeInfo, TimelineMarker};
-use ipc_channel::ipc::IpcSender;
+use cookie::Cookie;
+use cssparser::ToCss;
+use devtools_traits::{AppliedRuleInfo, AutoMargins, ComputedNodeLayout, CookieAttributes};
+use devtools_traits::{CookieScope, EnvironmentInfo, EvaluateJSReply, FrameInfo, Modification};
+use devtools_traits::{NodeInfo, ObjectProperties, RuleModification, SourceInfo, StorageArea};
+use devtools_traits::{StorageItem, StyleSheetInfo, TimelineMarker, TimelineMarkerType};
+use hyper_serde::Serde;
+use ipc_channel::ipc::{self, IpcSender};
 use js::jsval::UndefinedValue;
 use js::rust::wrappers::ObjectClassName;
 use msg::constellation_msg::PipelineId;
+use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use net_traits::CookieSource::HTTP;
+use net_traits::CoreResourceMsg::{DeleteCookie, DeleteCookies};
+use net_traits::CoreResourceMsg::{GetCookiesDataForUrl, SetCookieForUrl};
+use net_traits::IpcSend;
+use servo_arc::Arc;
+use servo_url::ServoUrl;
 use std::ffi::CStr;
+use std::ptr;
+use std::rc::Rc;
 use std::str;
+use style::shared_lock::{Locked, SharedRwLockReadGuard, ToCssWithGuard};
+use style::stylesheets::{CssRule, StyleRule, Stylesheet};
+use time::{self, Timespec};
 use uuid::Uuid;
 
 #[allow(unsafe_code)]
@@ -250,3 +283,677 @@ pub fn handle_reload(documents: &Documents, id: PipelineId) {
         win.Location().reload_without_origin_check();
     }
 }
+
+pub fn handle_get_sources(
+    debugger: Option<Rc<ScriptDebugger>>,
+    cx: JSContext,
+    pipeline: PipelineId,
+    reply: IpcSender<Vec<SourceInfo>>,
+) {
+    let sources = debugger
+        .map(|debugger| debugger.sources(cx, pipeline))
+        .unwrap_or_default();
+    reply.send(sources).unwrap();
+}
+
+pub fn handle_get_source_text(
+    debugger: Option<Rc<ScriptDebugger>>,
+    cx: JSContext,
+    source_id: String,
+    reply: IpcSender<Option<String>>,
+) {
+    let text = debugger.and_then(|debugger| debugger.source_text(cx, &source_id));
+    reply.send(text).unwrap();
+}
+
+pub fn handle_get_frames(
+    debugger: Option<Rc<ScriptDebugger>>,
+    cx: JSContext,
+    pipeline: PipelineId,
+    start: u32,
+    count: u32,
+    reply: IpcSender<Vec<FrameInfo>>,
+) {
+    let frames = debugger
+        .map(|debugger| debugger.frames(cx, pipeline, start, count))
+        .unwrap_or_default();
+    reply.send(frames).unwrap();
+}
+
+pub fn handle_get_frame_environment(
+    debugger: Option<Rc<ScriptDebugger>>,
+    cx: JSContext,
+    pipeline: PipelineId,
+    depth: u32,
+    reply: IpcSender<Option<EnvironmentInfo>>,
+) {
+    let environment = debugger.and_then(|debugger| debugger.frame_environment(cx, pipeline, depth));
+    reply.send(environment).unwrap();
+}
+
+pub fn handle_get_object_properties(
+    debugger: Option<Rc<ScriptDebugger>>,
+    cx: JSContext,
+    pipeline: PipelineId,
+    object_id: String,
+    reply: IpcSender<Option<ObjectProperties>>,
+) {
+    let properties =
+        debugger.and_then(|debugger| debugger.object_properties(cx, pipeline, &object_id));
+    reply.send(properties).unwrap();
+}
+
+pub fn handle_get_storage_origin(
+    documents: &Documents,
+    pipeline: PipelineId,
+    reply: IpcSender<Option<String>>,
+) {
+    let origin = documents.find_document(pipeline).map(|document| {
+        document
+            .window()
+            .upcast::<GlobalScope>()
+            .set_devtools_wants_storage_updates(true);
+        document.origin().immutable().ascii_serialization()
+    });
+    reply.send(origin).unwrap();
+}
+
+fn storage_type(area: StorageArea) -> Option<StorageType> {
+    match area {
+        StorageArea::Cookies => None,
+        StorageArea::LocalStorage => Some(StorageType::Local),
+        StorageArea::SessionStorage => Some(StorageType::Session),
+    }
+}
+
+fn cookie_to_storage_item(cookie: Cookie<'static>) -> StorageItem {
+    let expires = match (cookie.expires(), cookie.max_age()) {
+        (_, Some(max_age)) => Some(time::get_time() + max_age),
+        (Some(expires), None) => Some(expires.to_timespec()),
+        (None, None) => None,
+    };
+    StorageItem {
+        name: cookie.name().to_owned(),
+        value: cookie.value().to_owned(),
+        cookie: Some(CookieAttributes {
+            domain: cookie.domain().unwrap_or("").to_owned(),
+            path: cookie.path().unwrap_or("/").to_owned(),
+            expires: expires.map(|t| t.sec as u64 * 1000 + t.nsec as u64 / 1_000_000),
+            http_only: cookie.http_only().unwrap_or(false),
+            secure: cookie.secure().unwrap_or(false),
+        }),
+    }
+}
+
+fn storage_item_to_cookie(item: StorageItem) -> Cookie<'static> {
+    let mut cookie = Cookie::new(item.name, item.value);
+    match item.cookie {
+        Some(attributes) => {
+            if !attributes.domain.is_empty() {
+                cookie.set_domain(attributes.domain);
+            }
+            cookie.set_path(attributes.path);
+            if let Some(expires) = attributes.expires {
+                let expires = Timespec::new(
+                    (expires / 1000) as i64,
+                    ((expires % 1000) * 1_000_000) as i32,
+                );
+                cookie.set_expires(time::at_utc(expires));
+            }
+            cookie.set_http_only(attributes.http_only);
+            cookie.set_secure(attributes.secure);
+        },
+        None => cookie.set_path("/"),
+    }
+    cookie
+}
+
+fn storage_url(documents: &Documents, pipeline: PipelineId) -> Option<(ServoUrl, DomRoot<Window>)> {
+    documents
+        .find_document(pipeline)
+        .map(|document| (document.url(), DomRoot::from_ref(document.window())))
+}
+
+pub fn handle_get_storage_items(
+    documents: &Documents,
+    pipeline: PipelineId,
+    area: StorageArea,
+    reply: IpcSender<Vec<StorageItem>>,
+) {
+    let (url, window) = match storage_url(documents, pipeline) {
+        Some(found) => found,
+        None => return reply.send(vec![]).unwrap(),
+    };
+    let resource_threads = window.upcast::<GlobalScope>().resource_threads();
+    let items = match storage_type(area) {
+        None => {
+            let (sender, receiver) = ipc::channel().unwrap();
+            let _ = resource_threads.send(GetCookiesDataForUrl(url, sender, HTTP));
+            receiver
+                .recv()
+                .unwrap()
+                .into_iter()
+                .map(|cookie| cookie_to_storage_item(cookie.into_inner()))
+                .collect()
+        },
+        Some(storage_type) => {
+            let (sender, receiver) = ipc::channel().unwrap();
+            let _ =
+                resource_threads.send(StorageThreadMsg::Keys(sender, url.clone(), storage_type));
+            let mut items = vec![];
+            for name in receiver.recv().unwrap() {
+                let (sender, receiver) = ipc::channel().unwrap();
+                let _ = resource_threads.send(StorageThreadMsg::GetItem(
+                    sender,
+                    url.clone(),
+                    storage_type,
+                    name.clone(),
+                ));
+                if let Some(value) = receiver.recv().unwrap() {
+                    items.push(StorageItem {
+                        name: name,
+                        value: value,
+                        cookie: None,
+                    });
+                }
+            }
+            items
+        },
+    };
+    reply.send(items).unwrap();
+}
+
+pub fn handle_set_storage_item(
+    documents: &Documents,
+    pipeline: PipelineId,
+    area: StorageArea,
+    item: StorageItem,
+) {
+    let (url, window) = match storage_url(documents, pipeline) {
+        Some(found) => found,
+        None => {
+            return warn!(
+                "Storage of closed pipeline {} cannot be modified.",
+                pipeline
+            )
+        },
+    };
+    let resource_threads = window.upcast::<GlobalScope>().resource_threads();
+    match storage_type(area) {
+        None => {
+            let cookie = storage_item_to_cookie(item);
+            let _ = resource_threads.send(SetCookieForUrl(url, Serde(cookie), HTTP));
+        },
+        Some(storage_type) => {
+            let (sender, receiver) = ipc::channel().unwrap();
+            let _ = resource_threads.send(StorageThreadMsg::SetItem(
+                sender,
+                url,
+                storage_type,
+                item.name,
+                item.value,
+            ));
+            if receiver.recv().unwrap().is_err() {
+                warn!("Storage quota exceeded while setting an item from devtools.");
+            }
+        },
+    }
+}
+
+pub fn handle_remove_storage_item(
+    documents: &Documents,
+    pipeline: PipelineId,
+    area: StorageArea,
+    name: String,
+    scope: Option<CookieScope>,
+) {
+    let (url, window) = match storage_url(documents, pipeline) {
+        Some(found) => found,
+        None => {
+            return warn!(
+                "Storage of closed pipeline {} cannot be modified.",
+                pipeline
+            )
+        },
+    };
+    let resource_threads = window.upcast::<GlobalScope>().resource_threads();
+    match storage_type(area) {
+        None => {
+            if let Some(scope) = scope {
+                let _ = resource_threads.send(DeleteCookie(name, scope.domain, scope.path));
+            }
+        },
+        Some(storage_type) => {
+            let (sender, receiver) = ipc::channel().unwrap();
+            let _ = resource_threads.send(StorageThreadMsg::RemoveItem(
+                sender,
+                url,
+                storage_type,
+                name,
+            ));
+            let _ = receiver.recv();
+        },
+    }
+}
+
+pub fn handle_clear_storage(documents: &Documents, pipeline: PipelineId, area: StorageArea) {
+    let (url, window) = match storage_url(documents, pipeline) {
+        Some(found) => found,
+        None => {
+            return warn!(
+                "Storage of closed pipeline {} cannot be modified.",
+                pipeline
+            )
+        },
+    };
+    let resource_threads = window.upcast::<GlobalScope>().resource_threads();
+    match storage_type(area) {
+        None => {
+            let _ = resource_threads.send(DeleteCookies(url));
+        },
+        Some(storage_type) => {
+            let (sender, receiver) = ipc::channel().unwrap();
+            let _ = resource_threads.send(StorageThreadMsg::Clear(sender, url, storage_type));
+            let _ = receiver.recv();
+        },
+    }
+}
+
+/// A style sheet found by its id: either a style sheet of the document, or a sheet
+/// imported by one.
+struct FoundStyleSheet {
+    owner: DomRoot<CSSStyleSheet>,
+    import: Option<Arc<Stylesheet>>,
+}
+
+impl FoundStyleSheet {
+    fn sheet(&self) -> &Arc<Stylesheet> {
+        match self.import {
+            Some(ref sheet) => sheet,
+            None => self.owner.style_stylesheet_arc(),
+        }
+    }
+
+    fn rules(&self) -> Option<DomRoot<CSSRuleList>> {
+        match self.import {
+            Some(ref sheet) => Some(CSSRuleList::new(
+                self.owner.global().as_window(),
+                &self.owner,
+                RulesSource::Rules(sheet.contents.rules.clone()),
+            )),
+            None => self.owner.GetCssRules().ok(),
+        }
+    }
+}
+
+/// Find a style sheet by its id, which is its index in the style sheets of the document
+/// followed by the indices of the `@import` rules leading to it, separated by dots.
+fn find_stylesheet(document: &Document, id: &str) -> Option<FoundStyleSheet> {
+    let mut path = id.split('.');
+    let owner = document.stylesheet_at(path.next()?.parse().ok()?)?;
+    let mut import: Option<Arc<Stylesheet>> = None;
+    for index in path {
+        let index: usize = index.parse().ok()?;
+        let next = {
+            let guard = owner.shared_lock().read();
+            let sheet = import
+                .as_ref()
+                .map_or(owner.style_stylesheet(), |sheet| &**sheet);
+            match *sheet.contents.rules(&guard).get(index)? {
+                CssRule::Import(ref rule) => rule.read_with(&guard).stylesheet.0.clone(),
+                _ => return None,
+            }
+        };
+        import = Some(next);
+    }
+    Some(FoundStyleSheet { owner, import })
+}
+
+fn describe_stylesheet(
+    found: FoundStyleSheet,
+    id: String,
+    href: Option<String>,
+    index: Option<usize>,
+    sheets: &mut Vec<StyleSheetInfo>,
+) {
+    let title = match found.import {
+        Some(_) => None,
+        None => found
+            .owner
+            .upcast::<StyleSheet>()
+            .GetTitle()
+            .map(String::from),
+    };
+    let imports: Vec<_> = {
+        let guard = found.owner.shared_lock().read();
+        let rules = found.sheet().contents.rules(&guard);
+        sheets.push(StyleSheetInfo {
+            id: id.clone(),
+            href: href,
+            title: title,
+            disabled: found.sheet().disabled(),
+            index: index,
+            rule_count: rules.len(),
+        });
+        rules
+            .iter()
+            .enumerate()
+            .filter_map(|(index, rule)| match *rule {
+                CssRule::Import(ref rule) => {
+                    let rule = rule.read_with(&guard);
+                    let href = rule.url.url().map(|url| url.as_str().to_owned());
+                    Some((index, rule.stylesheet.0.clone(), href))
+                },
+                _ => None,
+            })
+            .collect()
+    };
+    for (index, sheet, href) in imports {
+        let found = FoundStyleSheet {
+            owner: DomRoot::from_ref(&*found.owner),
+            import: Some(sheet),
+        };
+        describe_stylesheet(found, format!("{}.{}", id, index), href, None, sheets);
+    }
+}
+
+fn stylesheets_of(document: &Document) -> Vec<StyleSheetInfo> {
+    let mut sheets = vec![];
+    for index in 0..document.stylesheet_count() {
+        if let Some(owner) = document.stylesheet_at(index) {
+            let href = owner.upcast::<StyleSheet>().GetHref().map(String::from);
+            let found = FoundStyleSheet {
+                owner: owner,
+                import: None,
+            };
+            describe_stylesheet(found, index.to_string(), href, Some(index), &mut sheets);
+        }
+    }
+    sheets
+}
+
+pub fn handle_get_stylesheets(
+    documents: &Documents,
+    pipeline: PipelineId,
+    reply: IpcSender<Vec<StyleSheetInfo>>,
+) {
+    let sheets = documents
+        .find_document(pipeline)
+        .map_or(vec![], |document| stylesheets_of(&document));
+    reply.send(sheets).unwrap();
+}
+
+pub fn handle_get_stylesheet_text(
+    documents: &Documents,
+    pipeline: PipelineId,
+    id: String,
+    reply: IpcSender<Option<String>>,
+) {
+    let document = match documents.find_document(pipeline) {
+        Some(document) => document,
+        None => return reply.send(None).unwrap(),
+    };
+    let text = find_stylesheet(&document, &id).map(|found| {
+        // Sheets whose source wasn't recorded, like the ones loaded before the
+        // devtools were enabled, can only be shown as their serialization.
+        document
+            .stylesheet_source(found.sheet())
+            .unwrap_or_else(|| {
+                let guard = found.owner.shared_lock().read();
+                found
+                    .sheet()
+                    .contents
+                    .rules(&guard)
+                    .iter()
+                    .map(|rule| rule.to_css_string(&guard))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+    });
+    reply.send(text).unwrap();
+}
+
+pub fn handle_update_stylesheet(
+    documents: &Documents,
+    pipeline: PipelineId,
+    id: String,
+    text: String,
+) {
+    let document = documents.find_document(pipeline);
+    let found = document
+        .as_ref()
+        .and_then(|document| find_stylesheet(document, &id));
+    match (document, found) {
+        (Some(document), Some(found)) => {
+            found.owner.update_from_str(found.sheet(), &text);
+            document.set_stylesheet_source(found.sheet(), &text);
+        },
+        _ => warn!(
+            "style sheet {} for pipeline id {} is not found",
+            id, pipeline
+        ),
+    }
+}
+
+pub fn handle_toggle_stylesheet(
+    documents: &Documents,
+    pipeline: PipelineId,
+    id: String,
+    reply: IpcSender<Option<bool>>,
+) {
+    let document = match documents.find_document(pipeline) {
+        Some(document) => document,
+        None => return reply.send(None).unwrap(),
+    };
+    let disabled = find_stylesheet(&document, &id).map(|found| {
+        let disabled = !found.sheet().disabled();
+        if found.sheet().set_disabled(disabled) {
+            document.invalidate_stylesheets();
+        }
+        disabled
+    });
+    reply.send(disabled).unwrap();
+}
+
+pub fn handle_get_applied_rules(
+    documents: &Documents,
+    pipeline: PipelineId,
+    node_id: String,
+    reply: IpcSender<Option<Vec<AppliedRuleInfo>>>,
+) {
+    let document = match documents.find_document(pipeline) {
+        Some(document) => document,
+        None => return reply.send(None).unwrap(),
+    };
+    let node = match find_node_by_unique_id(documents, pipeline, &node_id) {
+        Some(node) => node,
+        None => return reply.send(None).unwrap(),
+    };
+    let element = match node.downcast::<Element>() {
+        Some(element) => element,
+        None => return reply.send(None).unwrap(),
+    };
+
+    let mut sheet_rules = vec![];
+    for sheet in stylesheets_of(&document) {
+        if sheet.disabled {
+            continue;
+        }
+        let found = match find_stylesheet(&document, &sheet.id) {
+            Some(found) => found,
+            None => continue,
+        };
+        let guard = found.owner.shared_lock().read();
+        let mut rules = vec![];
+        collect_style_rules(
+            found.sheet().contents.rules(&guard),
+            &format!("{}:", sheet.id),
+            &guard,
+            &mut rules,
+        );
+        sheet_rules.extend(
+            rules
+                .into_iter()
+                .map(|(id, rule)| (sheet.clone(), id, rule)),
+        );
+    }
+
+    // The rule nodes of the style of the element are the rules it matched, from the
+    // one that wins the cascade to the one that loses it. Elements without a style,
+    // like the ones in a `display: none` subtree, have no applied rules.
+    let style = match element.style() {
+        Some(style) => style,
+        None => return reply.send(Some(vec![])).unwrap(),
+    };
+    let style_attribute = element.style_attribute().borrow().clone();
+    let guard = document.style_shared_lock().read();
+    let mut rules: Vec<AppliedRuleInfo> = vec![];
+    for rule_node in style.rules().self_and_ancestors() {
+        let source = match rule_node.style_source() {
+            Some(source) => source,
+            None => continue,
+        };
+        let rule = if let Some(rule) = source.as_rule() {
+            let matched = sheet_rules
+                .iter()
+                .find(|&&(_, _, ref candidate)| ptr::eq(&**candidate, rule.get()));
+            let (sheet, id) = match matched {
+                Some(&(ref sheet, ref id, _)) => (sheet, id),
+                // Rules of the user agent and of shadow trees aren't shown.
+                None => continue,
+            };
+            let rule = rule.get().read_with(&guard);
+            let mut css_text = String::new();
+            let _ = rule.block.read_with(&guard).to_css(&mut css_text);
+            AppliedRuleInfo {
+                id: id.clone(),
+                selector: Some(rule.selectors.to_css_string()),
+                css_text: css_text,
+                line: rule.source_location.line,
+                column: rule.source_location.column,
+                stylesheet: Some(sheet.clone()),
+            }
+        } else {
+            let declarations = source.as_declarations();
+            let is_style_attribute = match (declarations, style_attribute.as_ref()) {
+                (Some(declarations), Some(attribute)) => ptr::eq(declarations.get(), &**attribute),
+                _ => false,
+            };
+            let html_element = match element.downcast::<HTMLElement>() {
+                Some(html_element) if is_style_attribute => html_element,
+                _ => continue,
+            };
+            AppliedRuleInfo {
+                id: format!("element:{}", node_id),
+                selector: None,
+                css_text: html_element.Style().CssText().into(),
+                line: 0,
+                column: 0,
+                stylesheet: None,
+            }
+        };
+        // Rules with important declarations have a rule node for each importance.
+        if !rules.iter().any(|existing| existing.id == rule.id) {
+            rules.push(rule);
+        }
+    }
+    reply.send(Some(rules)).unwrap();
+}
+
+/// Collect the style rules of `rules` along with their ids, recursing into the rules
+/// nested in grouping rules and in style rules. The id of a rule is `prefix` followed
+/// by its index, and the ids of the rules nested in it are that id followed by a dot.
+pub fn collect_style_rules(
+    rules: &[CssRule],
+    prefix: &str,
+    guard: &SharedRwLockReadGuard,
+    style_rules: &mut Vec<(String, Arc<Locked<StyleRule>>)>,
+) {
+    for (index, rule) in rules.iter().enumerate() {
+        let id = format!("{}{}", prefix, index);
+        let nested = match *rule {
+            CssRule::Style(ref lock) => {
+                style_rules.push((id.clone(), lock.clone()));
+                lock.read_with(guard).rules.clone()
+            },
+            CssRule::Media(ref lock) => Some(lock.read_with(guard).rules.clone()),
+            CssRule::Supports(ref lock) => Some(lock.read_with(guard).rules.clone()),
+            CssRule::Document(ref lock) => Some(lock.read_with(guard).rules.clone()),
+            CssRule::LayerBlock(ref lock) => Some(lock.read_with(guard).rules.clone()),
+            CssRule::Container(ref lock) => Some(lock.read_with(guard).rules.clone()),
+            _ => None,
+        };
+        if let Some(nested) = nested {
+            let prefix = format!("{}.", id);
+            collect_style_rules(&nested.read_with(guard).0, &prefix, guard, style_rules);
+        }
+    }
+}
+
+/// Split the id of a rule of a style sheet into the id of the sheet and the indices
+/// leading to the rule, from the rule list of the sheet through the nested ones.
+pub fn parse_rule_id(rule_id: &str) -> Option<(&str, Vec<usize>)> {
+    let mut parts = rule_id.rsplitn(2, ':');
+    let path = parts.next()?;
+    let sheet_id = parts.next()?;
+    let indices = path
+        .split('.')
+        .map(|index| index.parse().ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some((sheet_id, indices))
+}
+
+/// Find the declarations of a rule by its id, which is either `element:` followed by the
+/// id of a node for its style attribute, or the id of a style sheet followed by a colon
+/// and the indices of the rule in that sheet and in the rules it is nested in,
+/// separated by dots.
+fn find_rule_declarations(
+    documents: &Documents,
+    pipeline: PipelineId,
+    rule_id: &str,
+) -> Option<DomRoot<CSSStyleDeclaration>> {
+    if rule_id.starts_with("element:") {
+        let node = find_node_by_unique_id(documents, pipeline, &rule_id["element:".len()..])?;
+        return node
+            .downcast::<HTMLElement>()
+            .map(|element| element.Style());
+    }
+    let (sheet_id, indices) = parse_rule_id(rule_id)?;
+    let document = documents.find_document(pipeline)?;
+    let mut list = find_stylesheet(&document, sheet_id)?.rules()?;
+    let (&last, parents) = indices.split_last()?;
+    for &index in parents {
+        let rule = list.Item(index as u32)?;
+        list = match rule.downcast::<CSSGroupingRule>() {
+            Some(rule) => rule.CssRules(),
+            None => rule.downcast::<CSSStyleRule>()?.CssRules(),
+        };
+    }
+    let rule = list.Item(last as u32)?;
+    rule.downcast::<CSSStyleRule>().map(|rule| rule.Style())
+}
+
+pub fn handle_modify_rule(
+    documents: &Documents,
+    pipeline: PipelineId,
+    rule_id: String,
+    modifications: Vec<RuleModification>,
+    reply: IpcSender<Option<String>>,
+) {
+    let declarations = match find_rule_declarations(documents, pipeline, &rule_id) {
+        Some(declarations) => declarations,
+        None => return reply.send(None).unwrap(),
+    };
+    for modification in modifications {
+        let name = DOMString::from(modification.name);
+        let _ = match modification.value {
+            Some(value) => declarations.SetProperty(
+                name,
+                DOMString::from(value),
+                DOMString::from(modification.priority),
+            ),
+            None => declarations.RemoveProperty(name).map(|_| ()),
+        };
+    }
+    reply.send(Some(declarations.CssText().into())).unwrap();
+}
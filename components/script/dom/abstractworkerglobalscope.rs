@@ -149,4 +149,7 @@ pub fn run_worker_event_loop<T, WorkerMsg, Event>(
     worker_scope
         .upcast::<GlobalScope>()
         .perform_a_message_port_garbage_collection_checkpoint();
+    worker_scope
+        .upcast::<GlobalScope>()
+        .perform_a_broadcast_channel_garbage_collection_checkpoint();
 }
@@ -0,0 +1,882 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animationeffect::{AnimationEffect, Timing};
+use crate::dom::animationplaybackevent::AnimationPlaybackEvent;
+use crate::dom::animationtimeline::AnimationTimeline;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding::{
+    AnimationMethods, AnimationPlayState,
+};
+use crate::dom::bindings::codegen::Bindings::AnimationEffectBinding::{
+    FillMode, PlaybackDirection,
+};
+use crate::dom::bindings::codegen::Bindings::AnimationPlaybackEventBinding::AnimationPlaybackEventInit;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::keyframeeffect::KeyframeEffect;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use script_layout_interface::rpc::RunningAnimation;
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::rc::Rc;
+use style::animation::KeyframeEffectSample;
+use style::properties::longhands::animation_direction::computed_value::single_value::T as AnimationDirection;
+
+/// <https://drafts.csswg.org/web-animations/#pending-play-task>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+enum PendingTask {
+    Play,
+    Pause,
+}
+
+/// An animation playback event waiting for the next animation frame to be
+/// dispatched.
+#[derive(JSTraceable)]
+pub struct QueuedEvent {
+    type_: Atom,
+    current_time: Option<f64>,
+    /// The time of the timeline when the event was queued, which orders the
+    /// events of all the animations of a document.
+    pub timeline_time: Option<f64>,
+}
+
+// https://drafts.csswg.org/web-animations/#the-animation-interface
+#[dom_struct]
+pub struct Animation {
+    eventtarget: EventTarget,
+    id: DomRefCell<DOMString>,
+    effect: MutNullableDom<AnimationEffect>,
+    timeline: MutNullableDom<AnimationTimeline>,
+    start_time: Cell<Option<f64>>,
+    hold_time: Cell<Option<f64>>,
+    /// <https://drafts.csswg.org/web-animations/#previous-current-time>
+    previous_current_time: Cell<Option<f64>>,
+    playback_rate: Cell<f64>,
+    /// <https://drafts.csswg.org/web-animations/#pending-playback-rate>
+    pending_playback_rate: Cell<Option<f64>>,
+    pending_task: Cell<Option<PendingTask>>,
+    #[ignore_malloc_size_of = "Rc"]
+    ready_promise: DomRefCell<Rc<Promise>>,
+    #[ignore_malloc_size_of = "Rc"]
+    finished_promise: DomRefCell<Rc<Promise>>,
+    /// Whether the finish notification steps are waiting for the next
+    /// animation frame to run.
+    pending_finish_notification: Cell<bool>,
+    #[ignore_malloc_size_of = "Atom"]
+    queued_events: DomRefCell<Vec<QueuedEvent>>,
+}
+
+impl Animation {
+    pub fn new_inherited(window: &Window, timeline: Option<&AnimationTimeline>) -> Animation {
+        Animation {
+            eventtarget: EventTarget::new_inherited(),
+            id: DomRefCell::new(DOMString::new()),
+            effect: Default::default(),
+            timeline: MutNullableDom::new(timeline),
+            start_time: Cell::new(None),
+            hold_time: Cell::new(None),
+            previous_current_time: Cell::new(None),
+            playback_rate: Cell::new(1.),
+            pending_playback_rate: Cell::new(None),
+            pending_task: Cell::new(None),
+            ready_promise: DomRefCell::new(Promise::new(window.upcast())),
+            finished_promise: DomRefCell::new(Promise::new(window.upcast())),
+            pending_finish_notification: Cell::new(false),
+            queued_events: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        effect: Option<&AnimationEffect>,
+        timeline: Option<&AnimationTimeline>,
+    ) -> DomRoot<Animation> {
+        let animation = reflect_dom_object(
+            Box::new(Animation::new_inherited(window, timeline)),
+            window,
+            AnimationBinding::Wrap,
+        );
+        animation.set_up(effect);
+        animation
+    }
+
+    /// Finishes setting up a newly reflected animation.
+    pub fn set_up(&self, effect: Option<&AnimationEffect>) {
+        // The ready promise is initially resolved.
+        let promise = self.ready_promise.borrow().clone();
+        promise.resolve_native(&DomRoot::from_ref(self));
+        self.set_effect(effect);
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-animation
+    pub fn Constructor(
+        window: &Window,
+        effect: Option<&AnimationEffect>,
+        timeline: Option<Option<&AnimationTimeline>>,
+    ) -> DomRoot<Animation> {
+        let timeline = match timeline {
+            Some(timeline) => timeline.map(DomRoot::from_ref),
+            None => Some(DomRoot::upcast(window.Document().Timeline())),
+        };
+        Animation::new(window, effect, timeline.as_deref())
+    }
+
+    fn document(&self) -> DomRoot<Document> {
+        self.global().as_window().Document()
+    }
+
+    pub fn effect(&self) -> Option<DomRoot<AnimationEffect>> {
+        self.effect.get()
+    }
+
+    /// The target of the keyframe effect of this animation.
+    pub fn target(&self) -> Option<DomRoot<Element>> {
+        let effect = DomRoot::downcast::<KeyframeEffect>(self.effect.get()?)?;
+        effect.target()
+    }
+
+    pub fn playback_rate(&self) -> f64 {
+        self.playback_rate.get()
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#effective-playback-rate>
+    fn effective_playback_rate(&self) -> f64 {
+        self.pending_playback_rate
+            .get()
+            .unwrap_or(self.playback_rate.get())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#apply-any-pending-playback-rate>
+    fn apply_pending_playback_rate(&self) {
+        if let Some(rate) = self.pending_playback_rate.take() {
+            self.playback_rate.set(rate);
+        }
+    }
+
+    /// The current time of the timeline of this animation, which is `None`
+    /// if there is no timeline or it is inactive.
+    fn timeline_time(&self) -> Option<f64> {
+        self.timeline
+            .get()
+            .and_then(|timeline| timeline.current_time())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#associated-effect-end>
+    fn effect_end(&self) -> f64 {
+        self.effect.get().map_or(0., |effect| effect.end_time())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#animation-current-time>
+    pub fn current_time(&self) -> Option<f64> {
+        if let Some(hold_time) = self.hold_time.get() {
+            return Some(hold_time);
+        }
+        self.current_time_ignoring_hold_time()
+    }
+
+    fn current_time_ignoring_hold_time(&self) -> Option<f64> {
+        let timeline_time = self.timeline_time()?;
+        let start_time = self.start_time.get()?;
+        Some((timeline_time - start_time) * self.playback_rate.get())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#play-states>
+    pub fn play_state(&self) -> AnimationPlayState {
+        let current_time = self.current_time();
+        let pending_task = self.pending_task.get();
+        if current_time.is_none() && self.start_time.get().is_none() && pending_task.is_none() {
+            return AnimationPlayState::Idle;
+        }
+        if pending_task == Some(PendingTask::Pause) ||
+            (self.start_time.get().is_none() && pending_task != Some(PendingTask::Play))
+        {
+            return AnimationPlayState::Paused;
+        }
+        if let Some(current_time) = current_time {
+            let rate = self.effective_playback_rate();
+            if (rate > 0. && current_time >= self.effect_end()) || (rate < 0. && current_time <= 0.)
+            {
+                return AnimationPlayState::Finished;
+            }
+        }
+        AnimationPlayState::Running
+    }
+
+    /// Whether this animation needs animation frames to make progress.
+    pub fn is_active(&self) -> bool {
+        self.pending_task.get().is_some() ||
+            self.pending_finish_notification.get() ||
+            !self.queued_events.borrow().is_empty() ||
+            self.play_state() == AnimationPlayState::Running
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#relevant-animations>
+    pub fn is_relevant(&self) -> bool {
+        self.effect
+            .get()
+            .map_or(false, |effect| effect.is_current() || effect.is_in_effect())
+    }
+
+    /// Lets the document know that the state of this animation changed, so
+    /// that the style of its target is updated and animation frames keep
+    /// coming while it is active.
+    fn invalidate(&self) {
+        let document = self.document();
+        document.register_animation(self);
+        document.update_animation_samples();
+        if self.is_active() {
+            document.request_animation_tick();
+        }
+    }
+
+    fn resolve_ready_promise(&self) {
+        let promise = self.ready_promise.borrow().clone();
+        promise.resolve_native(&DomRoot::from_ref(self));
+    }
+
+    fn new_ready_promise(&self) {
+        *self.ready_promise.borrow_mut() = Promise::new(&self.global());
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#reset-an-animations-pending-tasks>
+    fn reset_pending_tasks(&self) {
+        if self.pending_task.get().is_none() {
+            return;
+        }
+        self.pending_task.set(None);
+        self.apply_pending_playback_rate();
+        let promise = self.ready_promise.borrow().clone();
+        promise.reject_error(Error::Abort);
+        self.new_ready_promise();
+        self.resolve_ready_promise();
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#setting-the-associated-effect>
+    fn set_effect(&self, effect: Option<&AnimationEffect>) {
+        let old_effect = self.effect.get();
+        if old_effect.as_deref().map(|old| old as *const _) == effect.map(|new| new as *const _) {
+            return;
+        }
+        if effect.is_none() {
+            self.reset_pending_tasks();
+        }
+        if let Some(effect) = effect {
+            if let Some(animation) = effect.animation() {
+                animation.set_effect(None);
+            }
+            effect.set_animation(Some(self));
+        }
+        if let Some(old_effect) = old_effect {
+            old_effect.set_animation(None);
+        }
+        self.effect.set(effect);
+        self.update_finished_state(false, false);
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#setting-the-timeline>
+    fn set_timeline(&self, timeline: Option<&AnimationTimeline>) {
+        let old_timeline = self.timeline.get();
+        if old_timeline.as_deref().map(|old| old as *const _) == timeline.map(|new| new as *const _)
+        {
+            return;
+        }
+        self.timeline.set(timeline);
+        if self.start_time.get().is_some() {
+            self.hold_time.set(None);
+        }
+        self.update_finished_state(false, false);
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#silently-set-the-current-time>
+    fn silently_set_current_time(&self, seek_time: Option<f64>) -> ErrorResult {
+        let seek_time = match seek_time {
+            Some(seek_time) => seek_time,
+            None => {
+                if self.current_time().is_some() {
+                    return Err(Error::Type(
+                        "The current time can't be made unresolved".to_owned(),
+                    ));
+                }
+                return Ok(());
+            },
+        };
+        let timeline_time = self.timeline_time();
+        let rate = self.playback_rate.get();
+        match timeline_time {
+            Some(timeline_time)
+                if self.hold_time.get().is_none() &&
+                    self.start_time.get().is_some() &&
+                    rate != 0. =>
+            {
+                self.start_time.set(Some(timeline_time - seek_time / rate));
+            }
+            _ => self.hold_time.set(Some(seek_time)),
+        }
+        if timeline_time.is_none() {
+            self.start_time.set(None);
+        }
+        self.previous_current_time.set(None);
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#set-the-current-time>
+    fn set_current_time(&self, seek_time: Option<f64>) -> ErrorResult {
+        self.silently_set_current_time(seek_time)?;
+        if self.pending_task.get() == Some(PendingTask::Pause) {
+            self.hold_time.set(seek_time);
+            self.apply_pending_playback_rate();
+            self.start_time.set(None);
+            self.pending_task.set(None);
+            self.resolve_ready_promise();
+        }
+        self.update_finished_state(true, false);
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#set-the-start-time>
+    fn set_start_time(&self, new_start_time: Option<f64>) {
+        if self.timeline_time().is_none() && new_start_time.is_some() {
+            self.hold_time.set(None);
+        }
+        let previous_current_time = self.current_time();
+        self.apply_pending_playback_rate();
+        self.start_time.set(new_start_time);
+        if new_start_time.is_some() {
+            if self.playback_rate.get() != 0. {
+                self.hold_time.set(None);
+            }
+        } else {
+            self.hold_time.set(previous_current_time);
+        }
+        if self.pending_task.get().is_some() {
+            self.pending_task.set(None);
+            self.resolve_ready_promise();
+        }
+        self.update_finished_state(true, false);
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#play-an-animation>
+    fn play(&self, auto_rewind: bool) -> ErrorResult {
+        // Step 1.
+        let aborted_pause = self.pending_task.get() == Some(PendingTask::Pause);
+        // Steps 2-4.
+        let mut has_pending_ready_promise = false;
+        let mut seek_time = None;
+        let current_time = self.current_time();
+        let end = self.effect_end();
+        if auto_rewind {
+            let rate = self.effective_playback_rate();
+            if rate >= 0. && current_time.map_or(true, |time| time < 0. || time >= end) {
+                seek_time = Some(0.);
+            } else if rate < 0. && current_time.map_or(true, |time| time <= 0. || time > end) {
+                if end.is_infinite() {
+                    return Err(Error::InvalidState);
+                }
+                seek_time = Some(end);
+            }
+        }
+        // Step 5.
+        if seek_time.is_none() && self.start_time.get().is_none() && self.hold_time.get().is_none()
+        {
+            seek_time = Some(0.);
+        }
+        // Steps 6-7.
+        if seek_time.is_some() {
+            self.hold_time.set(seek_time);
+        }
+        if self.hold_time.get().is_some() {
+            self.start_time.set(None);
+        }
+        // Step 8.
+        if self.pending_task.get().is_some() {
+            self.pending_task.set(None);
+            has_pending_ready_promise = true;
+        }
+        // Step 9.
+        if self.hold_time.get().is_none() &&
+            seek_time.is_none() &&
+            !aborted_pause &&
+            self.pending_playback_rate.get().is_none()
+        {
+            return Ok(());
+        }
+        // Step 10.
+        if !has_pending_ready_promise {
+            self.new_ready_promise();
+        }
+        // Steps 11-12.
+        self.pending_task.set(Some(PendingTask::Play));
+        self.update_finished_state(false, false);
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#pause-an-animation>
+    fn pause(&self) -> ErrorResult {
+        // Steps 1-2.
+        if self.pending_task.get() == Some(PendingTask::Pause) ||
+            self.play_state() == AnimationPlayState::Paused
+        {
+            return Ok(());
+        }
+        // Steps 3-5.
+        if self.current_time().is_none() {
+            let seek_time = if self.playback_rate.get() >= 0. {
+                0.
+            } else {
+                let end = self.effect_end();
+                if end.is_infinite() {
+                    return Err(Error::InvalidState);
+                }
+                end
+            };
+            self.hold_time.set(Some(seek_time));
+        }
+        // Steps 6-7.
+        if self.pending_task.get() != Some(PendingTask::Play) {
+            self.new_ready_promise();
+        }
+        // Steps 8-9.
+        self.pending_task.set(Some(PendingTask::Pause));
+        self.update_finished_state(false, false);
+        Ok(())
+    }
+
+    /// Runs the pending play or pause task of this animation, if any, now
+    /// that the next animation frame is there.
+    fn run_pending_task(&self) {
+        let ready_time = match self.timeline_time() {
+            Some(time) => time,
+            None => return,
+        };
+        match self.pending_task.take() {
+            // https://drafts.csswg.org/web-animations/#pending-play-task
+            Some(PendingTask::Play) => {
+                if let Some(hold_time) = self.hold_time.get() {
+                    self.apply_pending_playback_rate();
+                    let rate = self.playback_rate.get();
+                    if rate == 0. {
+                        self.start_time.set(Some(ready_time));
+                    } else {
+                        self.start_time.set(Some(ready_time - hold_time / rate));
+                        self.hold_time.set(None);
+                    }
+                } else if let (Some(start_time), Some(_)) =
+                    (self.start_time.get(), self.pending_playback_rate.get())
+                {
+                    let current_time_to_match =
+                        (ready_time - start_time) * self.playback_rate.get();
+                    self.apply_pending_playback_rate();
+                    let rate = self.playback_rate.get();
+                    if rate == 0. {
+                        self.hold_time.set(Some(current_time_to_match));
+                        self.start_time.set(Some(ready_time));
+                    } else {
+                        self.start_time
+                            .set(Some(ready_time - current_time_to_match / rate));
+                    }
+                }
+            },
+            // https://drafts.csswg.org/web-animations/#pending-pause-task
+            Some(PendingTask::Pause) => {
+                if let (Some(start_time), None) = (self.start_time.get(), self.hold_time.get()) {
+                    self.hold_time
+                        .set(Some((ready_time - start_time) * self.playback_rate.get()));
+                }
+                self.apply_pending_playback_rate();
+                self.start_time.set(None);
+            },
+            None => return,
+        }
+        self.resolve_ready_promise();
+        self.update_finished_state(false, false);
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#update-an-animations-finished-state>
+    fn update_finished_state(&self, did_seek: bool, synchronously_notify: bool) {
+        // Steps 1-2.
+        let unconstrained_current_time = if did_seek {
+            self.current_time()
+        } else {
+            self.current_time_ignoring_hold_time()
+        };
+        if let (Some(unconstrained_current_time), Some(_), None) = (
+            unconstrained_current_time,
+            self.start_time.get(),
+            self.pending_task.get(),
+        ) {
+            let rate = self.playback_rate.get();
+            let end = self.effect_end();
+            if rate > 0. && unconstrained_current_time >= end {
+                let hold_time = if did_seek {
+                    unconstrained_current_time
+                } else {
+                    self.previous_current_time
+                        .get()
+                        .map_or(end, |previous| previous.max(end))
+                };
+                self.hold_time.set(Some(hold_time));
+            } else if rate < 0. && unconstrained_current_time <= 0. {
+                let hold_time = if did_seek {
+                    unconstrained_current_time
+                } else {
+                    self.previous_current_time
+                        .get()
+                        .map_or(0., |previous| previous.min(0.))
+                };
+                self.hold_time.set(Some(hold_time));
+            } else if rate != 0. {
+                if let Some(timeline_time) = self.timeline_time() {
+                    if let (true, Some(hold_time)) = (did_seek, self.hold_time.get()) {
+                        self.start_time.set(Some(timeline_time - hold_time / rate));
+                    }
+                    self.hold_time.set(None);
+                }
+            }
+        }
+
+        // Step 3.
+        self.previous_current_time.set(self.current_time());
+
+        // Steps 4-6.
+        let finished = self.play_state() == AnimationPlayState::Finished;
+        let finished_promise_resolved = self.finished_promise.borrow().is_fulfilled();
+        if finished && !finished_promise_resolved {
+            if synchronously_notify {
+                self.pending_finish_notification.set(false);
+                self.run_finish_notification_steps();
+            } else {
+                self.pending_finish_notification.set(true);
+            }
+        }
+        if !finished && finished_promise_resolved {
+            *self.finished_promise.borrow_mut() = Promise::new(&self.global());
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#finish-notification-steps>
+    fn run_finish_notification_steps(&self) {
+        if self.play_state() != AnimationPlayState::Finished {
+            return;
+        }
+        let promise = self.finished_promise.borrow().clone();
+        promise.resolve_native(&DomRoot::from_ref(self));
+        self.queue_event(atom!("finish"), self.current_time());
+    }
+
+    fn queue_event(&self, type_: Atom, current_time: Option<f64>) {
+        self.queued_events.borrow_mut().push(QueuedEvent {
+            type_: type_,
+            current_time: current_time,
+            timeline_time: self.timeline_time(),
+        });
+    }
+
+    pub fn take_queued_events(&self) -> Vec<QueuedEvent> {
+        self.queued_events.borrow_mut().drain(..).collect()
+    }
+
+    pub fn dispatch_queued_event(&self, event: QueuedEvent) {
+        let init = AnimationPlaybackEventInit {
+            parent: Default::default(),
+            currentTime: event.current_time.map(Finite::wrap),
+            timelineTime: event.timeline_time.map(Finite::wrap),
+        };
+        let window = self.global();
+        let event = AnimationPlaybackEvent::new(window.as_window(), event.type_, &init);
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    /// Updates this animation at an animation frame, before the events of
+    /// the animations of the document are dispatched.
+    ///
+    /// <https://drafts.csswg.org/web-animations/#update-animations-and-send-events>
+    pub fn tick(&self) {
+        self.run_pending_task();
+        self.update_finished_state(false, false);
+        if self.pending_finish_notification.get() {
+            self.pending_finish_notification.set(false);
+            self.run_finish_notification_steps();
+        }
+    }
+
+    /// Samples the keyframe effect of this animation, for the style system
+    /// to apply it to its target.
+    pub fn sample(&self) -> Option<(DomRoot<Element>, KeyframeEffectSample)> {
+        let effect = self.effect.get()?;
+        let progress = effect.transformed_progress()?;
+        let iteration_duration = effect.iteration_duration() / 1000.;
+        let effect = DomRoot::downcast::<KeyframeEffect>(effect)?;
+        let keyframes = effect.effect_keyframes();
+        if keyframes.is_empty() {
+            return None;
+        }
+        Some((
+            effect.target()?,
+            KeyframeEffectSample {
+                keyframes: keyframes,
+                progress: progress,
+                iteration_duration: iteration_duration,
+            },
+        ))
+    }
+
+    /// Mirrors the state of a CSS animation or transition that layout runs.
+    ///
+    /// FIXME: Layout owns the timing of these animations, so controlling
+    /// their playback from script only changes what script reports about
+    /// them until they are queried again.
+    pub fn update_from_running_animation(&self, running: &RunningAnimation) {
+        if let Some(effect) = self.effect.get() {
+            effect.set_timing(Timing {
+                delay: running.delay * 1000.,
+                fill: FillMode::None,
+                iterations: running.iterations,
+                duration: Some(running.duration * 1000.),
+                direction: match running.direction {
+                    AnimationDirection::Normal => PlaybackDirection::Normal,
+                    AnimationDirection::Reverse => PlaybackDirection::Reverse,
+                    AnimationDirection::Alternate => PlaybackDirection::Alternate,
+                    AnimationDirection::AlternateReverse => PlaybackDirection::Alternate_reverse,
+                },
+                ..Timing::default()
+            });
+        }
+        let current_time = running.current_time * 1000.;
+        self.pending_task.set(None);
+        self.pending_playback_rate.set(None);
+        self.playback_rate.set(1.);
+        if running.paused {
+            self.start_time.set(None);
+            self.hold_time.set(Some(current_time));
+        } else {
+            self.hold_time.set(None);
+            self.start_time
+                .set(self.timeline_time().map(|time| time - current_time));
+        }
+        self.previous_current_time.set(self.current_time());
+    }
+
+    /// Lets this animation account for a change of the timing of its effect.
+    pub fn effect_timing_changed(&self) {
+        self.update_finished_state(false, false);
+        self.invalidate();
+    }
+}
+
+impl AnimationMethods for Animation {
+    // https://drafts.csswg.org/web-animations/#dom-animation-id
+    fn Id(&self) -> DOMString {
+        self.id.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-id
+    fn SetId(&self, id: DOMString) {
+        *self.id.borrow_mut() = id;
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-effect
+    fn GetEffect(&self) -> Option<DomRoot<AnimationEffect>> {
+        self.effect.get()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-effect
+    fn SetEffect(&self, effect: Option<&AnimationEffect>) {
+        self.set_effect(effect);
+        self.invalidate();
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-timeline
+    fn GetTimeline(&self) -> Option<DomRoot<AnimationTimeline>> {
+        self.timeline.get()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-timeline
+    fn SetTimeline(&self, timeline: Option<&AnimationTimeline>) {
+        self.set_timeline(timeline);
+        self.invalidate();
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-starttime
+    fn GetStartTime(&self) -> Option<Finite<f64>> {
+        self.start_time.get().map(Finite::wrap)
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-starttime
+    fn SetStartTime(&self, start_time: Option<Finite<f64>>) {
+        self.set_start_time(start_time.map(|time| *time));
+        self.invalidate();
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-currenttime
+    fn GetCurrentTime(&self) -> Option<Finite<f64>> {
+        self.current_time().map(Finite::wrap)
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-currenttime
+    fn SetCurrentTime(&self, current_time: Option<Finite<f64>>) -> ErrorResult {
+        self.set_current_time(current_time.map(|time| *time))?;
+        self.invalidate();
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-playbackrate
+    fn PlaybackRate(&self) -> Finite<f64> {
+        Finite::wrap(self.playback_rate.get())
+    }
+
+    // https://drafts.csswg.org/web-animations/#set-the-playback-rate
+    fn SetPlaybackRate(&self, rate: Finite<f64>) {
+        self.pending_playback_rate.set(None);
+        let previous_time = self.current_time();
+        self.playback_rate.set(*rate);
+        if previous_time.is_some() {
+            // This can't fail with a resolved time.
+            let _ = self.set_current_time(previous_time);
+        }
+        self.invalidate();
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-playstate
+    fn PlayState(&self) -> AnimationPlayState {
+        self.play_state()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-pending
+    fn Pending(&self) -> bool {
+        self.pending_task.get().is_some()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-ready
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready_promise.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-finished
+    fn Finished(&self) -> Rc<Promise> {
+        self.finished_promise.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-onfinish
+    event_handler!(finish, GetOnfinish, SetOnfinish);
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-oncancel
+    event_handler!(cancel, GetOncancel, SetOncancel);
+
+    // https://drafts.csswg.org/web-animations/#cancel-an-animation
+    fn Cancel(&self) {
+        if self.play_state() != AnimationPlayState::Idle {
+            self.reset_pending_tasks();
+            let promise = self.finished_promise.borrow().clone();
+            promise.reject_error(Error::Abort);
+            *self.finished_promise.borrow_mut() = Promise::new(&self.global());
+            self.queue_event(atom!("cancel"), None);
+        }
+        self.hold_time.set(None);
+        self.start_time.set(None);
+        self.invalidate();
+    }
+
+    // https://drafts.csswg.org/web-animations/#finish-an-animation
+    fn Finish(&self) -> ErrorResult {
+        let rate = self.effective_playback_rate();
+        let end = self.effect_end();
+        if rate == 0. || (rate > 0. && end.is_infinite()) {
+            return Err(Error::InvalidState);
+        }
+        self.apply_pending_playback_rate();
+        let limit = if rate > 0. { end } else { 0. };
+        self.silently_set_current_time(Some(limit))?;
+        if self.start_time.get().is_none() {
+            if let Some(timeline_time) = self.timeline_time() {
+                self.start_time.set(Some(timeline_time - limit / rate));
+            }
+        }
+        if self.pending_task.get().is_some() && self.start_time.get().is_some() {
+            if self.pending_task.get() == Some(PendingTask::Pause) {
+                self.hold_time.set(None);
+            }
+            self.pending_task.set(None);
+            self.resolve_ready_promise();
+        }
+        self.update_finished_state(true, true);
+        self.invalidate();
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-play
+    fn Play(&self) -> ErrorResult {
+        self.play(true)?;
+        self.invalidate();
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-pause
+    fn Pause(&self) -> ErrorResult {
+        self.pause()?;
+        self.invalidate();
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/web-animations/#seamlessly-update-the-playback-rate
+    fn UpdatePlaybackRate(&self, rate: Finite<f64>) {
+        let previous_play_state = self.play_state();
+        self.pending_playback_rate.set(Some(*rate));
+        if self.pending_task.get().is_none() {
+            match previous_play_state {
+                AnimationPlayState::Idle | AnimationPlayState::Paused => {
+                    self.apply_pending_playback_rate();
+                },
+                AnimationPlayState::Finished => {
+                    let unconstrained_current_time = self.current_time_ignoring_hold_time();
+                    let start_time = match (self.timeline_time(), unconstrained_current_time) {
+                        (Some(timeline_time), _) if *rate == 0. => Some(timeline_time),
+                        (Some(timeline_time), Some(current_time)) => {
+                            Some(timeline_time - current_time / *rate)
+                        },
+                        _ => None,
+                    };
+                    self.start_time.set(start_time);
+                    self.apply_pending_playback_rate();
+                    self.update_finished_state(false, false);
+                },
+                AnimationPlayState::Running => {
+                    // Playing without rewinding can't fail.
+                    let _ = self.play(false);
+                },
+            }
+        }
+        self.invalidate();
+    }
+
+    // https://drafts.csswg.org/web-animations/#reverse-an-animation
+    fn Reverse(&self) -> ErrorResult {
+        if self.timeline_time().is_none() {
+            return Err(Error::InvalidState);
+        }
+        let original_pending_playback_rate = self.pending_playback_rate.get();
+        self.pending_playback_rate
+            .set(Some(-self.effective_playback_rate()));
+        if let Err(error) = self.play(true) {
+            self.pending_playback_rate
+                .set(original_pending_playback_rate);
+            return Err(error);
+        }
+        self.invalidate();
+        Ok(())
+    }
+}
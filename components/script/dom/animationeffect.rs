@@ -0,0 +1,450 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animation::Animation;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationEffectBinding::{
+    AnimationEffectMethods, ComputedEffectTiming, EffectTiming, FillMode, OptionalEffectTiming,
+    PlaybackDirection,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrString;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::window::Window;
+use cssparser::{Parser, ParserInput};
+use dom_struct::dom_struct;
+use std::cell::Ref;
+use style::context::QuirksMode;
+use style::parser::{Parse, ParserContext};
+use style::stylesheets::CssRuleType;
+use style::values::computed::TimingFunction as ComputedTimingFunction;
+use style::values::specified::TimingFunction;
+use style_traits::{ParsingMode, ToCss};
+
+/// The timing properties of an animation effect, with times in milliseconds.
+///
+/// <https://drafts.csswg.org/web-animations/#the-effecttiming-dictionaries>
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct Timing {
+    pub delay: f64,
+    pub end_delay: f64,
+    pub fill: FillMode,
+    pub iteration_start: f64,
+    pub iterations: f64,
+    /// The iteration duration, or `None` for `auto`.
+    pub duration: Option<f64>,
+    pub direction: PlaybackDirection,
+    pub easing: ComputedTimingFunction,
+}
+
+impl Timing {
+    /// The timing of an effect that only specifies its iteration duration.
+    pub fn from_duration(duration: f64) -> Fallible<Timing> {
+        let mut timing = Timing::default();
+        timing.duration = Some(validate_duration(duration)?);
+        Ok(timing)
+    }
+
+    pub fn from_dictionary(window: &Window, dictionary: &EffectTiming) -> Fallible<Timing> {
+        let mut timing = Timing::default();
+        timing.update(
+            window,
+            &OptionalEffectTiming {
+                delay: Some(dictionary.delay),
+                direction: Some(dictionary.direction),
+                duration: Some(match dictionary.duration {
+                    UnrestrictedDoubleOrString::UnrestrictedDouble(duration) => {
+                        UnrestrictedDoubleOrString::UnrestrictedDouble(duration)
+                    },
+                    UnrestrictedDoubleOrString::String(ref duration) => {
+                        UnrestrictedDoubleOrString::String(duration.clone())
+                    },
+                }),
+                easing: Some(dictionary.easing.clone()),
+                endDelay: Some(dictionary.endDelay),
+                fill: Some(dictionary.fill),
+                iterationStart: Some(dictionary.iterationStart),
+                iterations: Some(dictionary.iterations),
+            },
+        )?;
+        Ok(timing)
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#update-the-timing-properties-of-an-animation-effect>
+    ///
+    /// Nothing is updated if any of the new properties is invalid.
+    fn update(&mut self, window: &Window, input: &OptionalEffectTiming) -> Fallible<()> {
+        // Step 1.
+        if let Some(iteration_start) = input.iterationStart {
+            if *iteration_start < 0. {
+                return Err(Error::Type(
+                    "iterationStart must not be negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(iterations) = input.iterations {
+            if iterations.is_nan() || iterations < 0. {
+                return Err(Error::Type(
+                    "iterations must be a non-negative number".to_owned(),
+                ));
+            }
+        }
+        let duration = match input.duration {
+            Some(UnrestrictedDoubleOrString::UnrestrictedDouble(duration)) => {
+                Some(Some(validate_duration(duration)?))
+            },
+            Some(UnrestrictedDoubleOrString::String(ref duration)) => {
+                if &**duration != "auto" {
+                    return Err(Error::Type("duration must be a number or auto".to_owned()));
+                }
+                Some(None)
+            },
+            None => None,
+        };
+        let easing = match input.easing {
+            Some(ref easing) => Some(parse_easing(window, easing)?),
+            None => None,
+        };
+
+        // Step 2.
+        if let Some(delay) = input.delay {
+            self.delay = *delay;
+        }
+        if let Some(end_delay) = input.endDelay {
+            self.end_delay = *end_delay;
+        }
+        if let Some(fill) = input.fill {
+            self.fill = fill;
+        }
+        if let Some(iteration_start) = input.iterationStart {
+            self.iteration_start = *iteration_start;
+        }
+        if let Some(iterations) = input.iterations {
+            self.iterations = iterations;
+        }
+        if let Some(duration) = duration {
+            self.duration = duration;
+        }
+        if let Some(direction) = input.direction {
+            self.direction = direction;
+        }
+        if let Some(easing) = easing {
+            self.easing = easing;
+        }
+        Ok(())
+    }
+
+    fn to_dictionary(&self) -> EffectTiming {
+        EffectTiming {
+            delay: Finite::wrap(self.delay),
+            direction: self.direction,
+            duration: match self.duration {
+                Some(duration) => UnrestrictedDoubleOrString::UnrestrictedDouble(duration),
+                None => UnrestrictedDoubleOrString::String(DOMString::from("auto")),
+            },
+            easing: DOMString::from(self.easing.to_css_string()),
+            endDelay: Finite::wrap(self.end_delay),
+            fill: self.fill,
+            iterationStart: Finite::wrap(self.iteration_start),
+            iterations: self.iterations,
+        }
+    }
+}
+
+impl Default for Timing {
+    fn default() -> Timing {
+        Timing {
+            delay: 0.,
+            end_delay: 0.,
+            fill: FillMode::Auto,
+            iteration_start: 0.,
+            iterations: 1.,
+            duration: None,
+            direction: PlaybackDirection::Normal,
+            easing: ComputedTimingFunction::linear(),
+        }
+    }
+}
+
+fn validate_duration(duration: f64) -> Fallible<f64> {
+    if duration.is_nan() || duration < 0. {
+        return Err(Error::Type(
+            "duration must be a non-negative number".to_owned(),
+        ));
+    }
+    Ok(duration)
+}
+
+/// Parses an `easing` member as a CSS `<easing-function>`.
+pub fn parse_easing(window: &Window, easing: &str) -> Fallible<ComputedTimingFunction> {
+    let url = window.Document().url();
+    let context = ParserContext::new_for_cssom(
+        &url,
+        Some(CssRuleType::Style),
+        ParsingMode::DEFAULT,
+        QuirksMode::NoQuirks,
+        None,
+        None,
+    );
+    let mut input = ParserInput::new(easing);
+    let mut parser = Parser::new(&mut input);
+    parser
+        .parse_entirely(|input| TimingFunction::parse(&context, input))
+        .map(|easing| easing.to_computed_value_without_context())
+        .map_err(|_| Error::Type(format!("'{}' is not a valid easing function", easing)))
+}
+
+/// <https://drafts.csswg.org/web-animations/#animation-effect-phases-and-states>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    Before,
+    Active,
+    After,
+    Idle,
+}
+
+// https://drafts.csswg.org/web-animations/#the-animationeffect-interface
+#[dom_struct]
+pub struct AnimationEffect {
+    reflector_: Reflector,
+    timing: DomRefCell<Timing>,
+    /// The animation this effect is associated with.
+    animation: MutNullableDom<Animation>,
+}
+
+impl AnimationEffect {
+    pub fn new_inherited(timing: Timing) -> AnimationEffect {
+        AnimationEffect {
+            reflector_: Reflector::new(),
+            timing: DomRefCell::new(timing),
+            animation: Default::default(),
+        }
+    }
+
+    pub fn timing(&self) -> Ref<Timing> {
+        self.timing.borrow()
+    }
+
+    pub fn set_timing(&self, timing: Timing) {
+        *self.timing.borrow_mut() = timing;
+    }
+
+    pub fn animation(&self) -> Option<DomRoot<Animation>> {
+        self.animation.get()
+    }
+
+    pub fn set_animation(&self, animation: Option<&Animation>) {
+        self.animation.set(animation);
+    }
+
+    /// The iteration duration, where `auto` is zero for keyframe effects.
+    pub fn iteration_duration(&self) -> f64 {
+        self.timing.borrow().duration.unwrap_or(0.)
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#active-duration>
+    pub fn active_duration(&self) -> f64 {
+        let duration = self.iteration_duration();
+        let iterations = self.timing.borrow().iterations;
+        if duration == 0. || iterations == 0. {
+            return 0.;
+        }
+        duration * iterations
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#end-time>
+    pub fn end_time(&self) -> f64 {
+        let timing = self.timing.borrow();
+        (timing.delay + self.active_duration() + timing.end_delay).max(0.)
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#local-time>
+    pub fn local_time(&self) -> Option<f64> {
+        self.animation
+            .get()
+            .and_then(|animation| animation.current_time())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#animation-effect-phases-and-states>
+    pub fn phase(&self) -> Phase {
+        let local_time = match self.local_time() {
+            Some(local_time) => local_time,
+            None => return Phase::Idle,
+        };
+        let backwards = self
+            .animation
+            .get()
+            .map_or(false, |animation| animation.playback_rate() < 0.);
+        let delay = self.timing.borrow().delay;
+        let end_time = self.end_time();
+        let before_active_boundary = delay.min(end_time).max(0.);
+        let active_after_boundary = (delay + self.active_duration()).min(end_time).max(0.);
+        if local_time < before_active_boundary ||
+            (backwards && local_time == before_active_boundary)
+        {
+            return Phase::Before;
+        }
+        if local_time > active_after_boundary || (!backwards && local_time == active_after_boundary)
+        {
+            return Phase::After;
+        }
+        Phase::Active
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#current>
+    pub fn is_current(&self) -> bool {
+        let rate = self
+            .animation
+            .get()
+            .map_or(0., |animation| animation.playback_rate());
+        match self.phase() {
+            Phase::Before => rate > 0.,
+            Phase::Active => true,
+            Phase::After => rate < 0.,
+            Phase::Idle => false,
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#in-effect>
+    pub fn is_in_effect(&self) -> bool {
+        self.active_time().is_some()
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#calculating-the-active-time>
+    pub fn active_time(&self) -> Option<f64> {
+        let local_time = self.local_time()?;
+        let timing = self.timing.borrow();
+        match self.phase() {
+            Phase::Before => match timing.fill {
+                FillMode::Backwards | FillMode::Both => Some((local_time - timing.delay).max(0.)),
+                _ => None,
+            },
+            Phase::Active => Some(local_time - timing.delay),
+            Phase::After => match timing.fill {
+                FillMode::Forwards | FillMode::Both => Some(
+                    (local_time - timing.delay)
+                        .min(self.active_duration())
+                        .max(0.),
+                ),
+                _ => None,
+            },
+            Phase::Idle => None,
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#overall-progress>
+    fn overall_progress(&self) -> Option<f64> {
+        let active_time = self.active_time()?;
+        let timing = self.timing.borrow();
+        let duration = self.iteration_duration();
+        if duration == 0. {
+            if self.phase() == Phase::Before {
+                return Some(timing.iteration_start);
+            }
+            return Some(timing.iteration_start + timing.iterations);
+        }
+        Some(active_time / duration + timing.iteration_start)
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#simple-iteration-progress>
+    fn simple_iteration_progress(&self) -> Option<f64> {
+        let overall_progress = self.overall_progress()?;
+        let timing = self.timing.borrow();
+        let progress = if overall_progress.is_infinite() {
+            timing.iteration_start % 1.
+        } else {
+            overall_progress % 1.
+        };
+        let phase = self.phase();
+        if progress == 0. &&
+            (phase == Phase::Active || phase == Phase::After) &&
+            self.active_time() == Some(self.active_duration()) &&
+            timing.iterations != 0.
+        {
+            return Some(1.);
+        }
+        Some(progress)
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#current-iteration>
+    pub fn current_iteration(&self) -> Option<f64> {
+        self.active_time()?;
+        if self.phase() == Phase::After && self.timing.borrow().iterations.is_infinite() {
+            return Some(f64::INFINITY);
+        }
+        let overall_progress = self.overall_progress()?;
+        if self.simple_iteration_progress() == Some(1.) {
+            return Some(overall_progress.floor() - 1.);
+        }
+        Some(overall_progress.floor())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#directed-progress>
+    fn directed_progress(&self) -> Option<f64> {
+        let progress = self.simple_iteration_progress()?;
+        let current_iteration = self.current_iteration()?;
+        let forwards = match self.timing.borrow().direction {
+            PlaybackDirection::Normal => true,
+            PlaybackDirection::Reverse => false,
+            PlaybackDirection::Alternate => current_iteration % 2. == 0.,
+            PlaybackDirection::Alternate_reverse => current_iteration % 2. != 0.,
+        };
+        Some(if forwards { progress } else { 1. - progress })
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#transformed-progress>
+    pub fn transformed_progress(&self) -> Option<f64> {
+        let progress = self.directed_progress()?;
+        let duration = self.iteration_duration().max(1.) / 1000.;
+        let epsilon = 1. / (200. * duration);
+        Some(
+            self.timing
+                .borrow()
+                .easing
+                .calculate_output(progress, epsilon),
+        )
+    }
+}
+
+impl AnimationEffectMethods for AnimationEffect {
+    // https://drafts.csswg.org/web-animations/#dom-animationeffect-gettiming
+    fn GetTiming(&self) -> EffectTiming {
+        self.timing.borrow().to_dictionary()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animationeffect-getcomputedtiming
+    fn GetComputedTiming(&self) -> ComputedEffectTiming {
+        let mut parent = self.timing.borrow().to_dictionary();
+        // Keyframe effects fill as if `auto` was `none`, and have no
+        // intrinsic iteration duration.
+        if parent.fill == FillMode::Auto {
+            parent.fill = FillMode::None;
+        }
+        parent.duration = UnrestrictedDoubleOrString::UnrestrictedDouble(self.iteration_duration());
+        ComputedEffectTiming {
+            parent: parent,
+            activeDuration: Some(self.active_duration()),
+            currentIteration: Some(self.current_iteration()),
+            endTime: Some(self.end_time()),
+            localTime: Some(self.local_time().map(Finite::wrap)),
+            progress: Some(self.transformed_progress().map(Finite::wrap)),
+        }
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animationeffect-updatetiming
+    fn UpdateTiming(&self, timing: &OptionalEffectTiming) -> Fallible<()> {
+        let window = self.global();
+        self.timing
+            .borrow_mut()
+            .update(window.as_window(), timing)?;
+        if let Some(animation) = self.animation.get() {
+            animation.effect_timing_changed();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::AnimationTimelineBinding::AnimationTimelineMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::root::Dom;
+use crate::dom::document::Document;
+use dom_struct::dom_struct;
+
+// https://drafts.csswg.org/web-animations/#the-animationtimeline-interface
+#[dom_struct]
+pub struct AnimationTimeline {
+    reflector_: Reflector,
+    document: Dom<Document>,
+    /// The zero time of this timeline, in milliseconds relative to the time
+    /// origin of its document.
+    origin_time: f64,
+}
+
+impl AnimationTimeline {
+    pub fn new_inherited(document: &Document, origin_time: f64) -> AnimationTimeline {
+        AnimationTimeline {
+            reflector_: Reflector::new(),
+            document: Dom::from_ref(document),
+            origin_time: origin_time,
+        }
+    }
+
+    pub fn document(&self) -> &Document {
+        &*self.document
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#timeline-current-time>
+    ///
+    /// Document timelines are inactive when their document isn't fully
+    /// active, in which case this is `None`.
+    pub fn current_time(&self) -> Option<f64> {
+        self.document
+            .animation_timeline_time()
+            .map(|time| time - self.origin_time)
+    }
+}
+
+impl AnimationTimelineMethods for AnimationTimeline {
+    // https://drafts.csswg.org/web-animations/#dom-animationtimeline-currenttime
+    fn GetCurrentTime(&self) -> Option<Finite<f64>> {
+        self.current_time().map(Finite::wrap)
+    }
+}
@@ -736,6 +736,15 @@ def getJSToNativeConversionInfo(type, descriptorProvider, failureCode=None,
                 default = '%s::USVString(USVString("%s".to_owned()))' % (
                     union_native_type(type),
                     defaultValue.value)
+            elif tag is IDLType.Tags.domstring:
+                default = '%s::String(DOMString::from("%s"))' % (
+                    union_native_type(type),
+                    defaultValue.value)
+            elif tag in [IDLType.Tags.float, IDLType.Tags.double]:
+                default = "%s::%s(Finite::wrap(%s))" % (
+                    union_native_type(type),
+                    defaultValue.type.name,
+                    defaultValue.value)
             elif defaultValue.type.isEnum():
                 enum = defaultValue.type.inner.identifier.name
                 default = "%s::%s(%s::%s)" % (
@@ -744,7 +753,8 @@ def getJSToNativeConversionInfo(type, descriptorProvider, failureCode=None,
                     enum,
                     getEnumValueName(defaultValue.value))
             else:
-                raise("We don't currently support default values that aren't null, boolean or default dictionary")
+                raise("We don't currently support default values that aren't null, boolean, "
+                      "restricted floats or default dictionary")
         elif dictionaries:
             if defaultValue:
                 assert isinstance(defaultValue, IDLDefaultDictionaryValue)
@@ -888,7 +898,8 @@ def getJSToNativeConversionInfo(type, descriptorProvider, failureCode=None,
 
         typeName = type.unroll().name  # unroll because it may be nullable
 
-        if isMember == "Union":
+        # Members of unions and dictionaries are stored in traceable heap locations.
+        if isMember in ("Union", "Dictionary"):
             typeName = "Heap" + typeName
 
         templateBody = fill(
@@ -4573,7 +4584,10 @@ class CGUnionConversionStruct(CGThing):
             conversions.append(CGIfWrapper("value.get().is_object()", templateBody))
 
         if dictionaryObject:
-            assert not hasObjectTypes
+            # Other object types are tried first, so that a dictionary only
+            # handles the objects that weren't converted by them.
+            # https://heycam.github.io/webidl/#es-union
+            assert not object and not mozMapObject
             conversions.append(dictionaryObject)
 
         stringTypes = [t for t in memberTypes if t.isString() or t.isEnum()]
@@ -6495,6 +6509,14 @@ class CGDictionary(CGThing):
         initParent = ("parent: %s,\n" % initParent) if initParent else ""
         memberInits = CGList([memberInit(m, True) for m in self.memberInfo])
 
+        if d.parent:
+            # Start from the object holding the members of the parent dictionary.
+            createObject = ("rooted!(in(cx) let mut parent_js = UndefinedValue());\n"
+                            "self.parent.to_jsval(cx, parent_js.handle_mut());\n"
+                            "rooted!(in(cx) let obj = parent_js.to_object());\n")
+        else:
+            createObject = "rooted!(in(cx) let obj = JS_NewObject(cx, ptr::null()));\n"
+
         return string.Template(
             "impl ${selfName} {\n"
             "${empty}\n"
@@ -6528,7 +6550,7 @@ class CGDictionary(CGThing):
             "\n"
             "impl ToJSValConvertible for ${selfName} {\n"
             "    unsafe fn to_jsval(&self, cx: *mut JSContext, mut rval: MutableHandleValue) {\n"
-            "        rooted!(in(cx) let obj = JS_NewObject(cx, ptr::null()));\n"
+            "${createObject}"
             "${insertMembers}"
             "        rval.set(ObjectOrNullValue(obj.get()))\n"
             "    }\n"
@@ -6538,6 +6560,7 @@ class CGDictionary(CGThing):
                 "empty": CGIndenter(CGGeneric(self.makeEmpty()), indentLevel=4).define(),
                 "initParent": CGIndenter(CGGeneric(initParent), indentLevel=16).define(),
                 "initMembers": CGIndenter(memberInits, indentLevel=16).define(),
+                "createObject": CGIndenter(CGGeneric(createObject), indentLevel=8).define(),
                 "insertMembers": CGIndenter(memberInserts, indentLevel=8).define(),
                 "preInitial": CGIndenter(CGGeneric(preInitial), indentLevel=8).define(),
                 "postInitial": CGIndenter(CGGeneric(postInitial), indentLevel=8).define(),
@@ -0,0 +1,21 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Trait representing the concept of [serializable objects]
+//! (https://html.spec.whatwg.org/multipage/#serializable-objects).
+
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
+use crate::dom::globalscope::GlobalScope;
+
+pub(crate) trait Serializable: DomObject {
+    /// <https://html.spec.whatwg.org/multipage/#serialization-steps>
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()>;
+    /// <https://html.spec.whatwg.org/multipage/#deserialization-steps>
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()>;
+}
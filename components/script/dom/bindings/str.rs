@@ -344,6 +344,15 @@ impl DOMString {
         RE.is_match(&self.0) && parse_floating_point_number(&self.0).is_ok()
     }
 
+    /// Returns the number this string represents, if it is a valid floating-point number.
+    /// https://html.spec.whatwg.org/multipage/#valid-floating-point-number
+    pub fn parse_floating_point_number(&self) -> Option<f64> {
+        if !self.is_valid_floating_point_number_string() {
+            return None;
+        }
+        self.0.parse().ok()
+    }
+
     /// https://html.spec.whatwg.org/multipage/#best-representation-of-the-number-as-a-floating-point-number
     pub fn set_best_representation_of_the_floating_point_number(&mut self) {
         if let Ok(val) = parse_floating_point_number(&self.0) {
@@ -10,10 +10,22 @@ use crate::dom::bindings::conversions::{root_from_object, ToJSValConvertible};
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::serializable::Serializable;
 use crate::dom::bindings::transferable::Transferable;
-use crate::dom::blob::{Blob, BlobImpl};
+use crate::dom::blob::Blob;
+use crate::dom::dommatrix::DOMMatrix;
+use crate::dom::dommatrixreadonly::DOMMatrixReadOnly;
+use crate::dom::dompoint::DOMPoint;
+use crate::dom::dompointreadonly::DOMPointReadOnly;
+use crate::dom::domquad::DOMQuad;
+use crate::dom::domrect::DOMRect;
+use crate::dom::domrectreadonly::DOMRectReadOnly;
+use crate::dom::file::File;
+use crate::dom::filelist::FileList;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::imagedata::ImageData;
 use crate::dom::messageport::MessagePort;
+use crate::dom::offscreencanvas::OffscreenCanvas;
 use crate::script_runtime::JSContext as SafeJSContext;
 use js::glue::CopyJSStructuredCloneData;
 use js::glue::DeleteJSAutoStructuredCloneBuffer;
@@ -37,6 +49,7 @@ use js::rust::{CustomAutoRooterGuard, HandleValue, MutableHandleValue};
 use msg::constellation_msg::MessagePortId;
 use script_traits::transferable::MessagePortImpl;
 use script_traits::StructuredSerializedData;
+use std::any::Any;
 use std::collections::HashMap;
 use std::os::raw;
 use std::ptr;
@@ -51,6 +64,17 @@ enum StructuredCloneTags {
     Min = 0xFFFF8000,
     DomBlob = 0xFFFF8001,
     MessagePort = 0xFFFF8002,
+    File = 0xFFFF8003,
+    FileList = 0xFFFF8004,
+    ImageData = 0xFFFF8005,
+    DomPointReadOnly = 0xFFFF8006,
+    DomPoint = 0xFFFF8007,
+    DomRectReadOnly = 0xFFFF8008,
+    DomRect = 0xFFFF8009,
+    DomQuad = 0xFFFF800A,
+    DomMatrixReadOnly = 0xFFFF800B,
+    DomMatrix = 0xFFFF800C,
+    OffscreenCanvas = 0xFFFF800D,
     Max = 0xFFFFFFFF,
 }
 
@@ -90,81 +114,114 @@ unsafe fn read_length(r: *mut JSStructuredCloneReader) -> usize {
     return length as usize;
 }
 
-struct StructuredCloneWriter {
+/// Writes the serialized state of platform objects into a structured clone buffer.
+pub(crate) struct StructuredCloneWriter {
     w: *mut JSStructuredCloneWriter,
 }
 
 impl StructuredCloneWriter {
-    unsafe fn write_slice(&self, v: &[u8]) {
+    pub fn write_u32_pair(&self, first: u32, second: u32) {
+        unsafe {
+            assert!(JS_WriteUint32Pair(self.w, first, second));
+        }
+    }
+    pub fn write_u64(&self, v: u64) {
+        self.write_u32_pair((v >> 32) as u32, v as u32);
+    }
+    pub fn write_f64(&self, v: f64) {
+        self.write_u64(v.to_bits());
+    }
+    pub fn write_slice(&self, v: &[u8]) {
         let type_length = v.len();
-        write_length(self.w, type_length);
-        assert!(JS_WriteBytes(
-            self.w,
-            v.as_ptr() as *const raw::c_void,
-            type_length
-        ));
-    }
-    unsafe fn write_str(&self, s: &str) {
+        unsafe {
+            write_length(self.w, type_length);
+            assert!(JS_WriteBytes(
+                self.w,
+                v.as_ptr() as *const raw::c_void,
+                type_length
+            ));
+        }
+    }
+    pub fn write_str(&self, s: &str) {
         self.write_slice(s.as_bytes());
     }
 }
 
-struct StructuredCloneReader {
+/// Reads back the serialized state of platform objects from a structured clone buffer.
+pub(crate) struct StructuredCloneReader {
     r: *mut JSStructuredCloneReader,
 }
 
 impl StructuredCloneReader {
-    unsafe fn read_bytes(&self) -> Vec<u8> {
-        let mut bytes = vec![0u8; read_length(self.r)];
-        let blob_length = bytes.len();
-        assert!(JS_ReadBytes(
-            self.r,
-            bytes.as_mut_ptr() as *mut raw::c_void,
-            blob_length
-        ));
-        return bytes;
-    }
-    unsafe fn read_str(&self) -> String {
+    pub fn read_u32_pair(&self) -> (u32, u32) {
+        let mut first: u32 = 0;
+        let mut second: u32 = 0;
+        unsafe {
+            assert!(JS_ReadUint32Pair(
+                self.r,
+                &mut first as *mut u32,
+                &mut second as *mut u32
+            ));
+        }
+        (first, second)
+    }
+    pub fn read_u64(&self) -> u64 {
+        let (high, low) = self.read_u32_pair();
+        ((high as u64) << 32) | low as u64
+    }
+    pub fn read_f64(&self) -> f64 {
+        f64::from_bits(self.read_u64())
+    }
+    pub fn read_bytes(&self) -> Vec<u8> {
+        unsafe {
+            let mut bytes = vec![0u8; read_length(self.r)];
+            let blob_length = bytes.len();
+            assert!(JS_ReadBytes(
+                self.r,
+                bytes.as_mut_ptr() as *mut raw::c_void,
+                blob_length
+            ));
+            return bytes;
+        }
+    }
+    pub fn read_str(&self) -> String {
         let str_buffer = self.read_bytes();
-        return String::from_utf8_unchecked(str_buffer);
+        unsafe { String::from_utf8_unchecked(str_buffer) }
     }
 }
 
-unsafe fn read_blob(
+/// Runs the deserialization steps of a platform object, and returns its reflector.
+/// The object is kept rooted in `sc_holder` until the whole read is done.
+unsafe fn read_object<T: Serializable>(
     cx: *mut JSContext,
     r: *mut JSStructuredCloneReader,
     sc_holder: &mut StructuredDataHolder,
 ) -> *mut JSObject {
     let structured_reader = StructuredCloneReader { r: r };
-    let blob_buffer = structured_reader.read_bytes();
-    let type_str = structured_reader.read_str();
     let target_global = GlobalScope::from_context(cx);
-    let read_blob = Blob::new(
-        &target_global,
-        BlobImpl::new_from_bytes(blob_buffer),
-        type_str,
-    );
-    let js_object = read_blob.reflector().get_jsobject().get();
+    let object = match T::deserialize(&target_global, &structured_reader) {
+        Ok(object) => object,
+        Err(()) => return ptr::null_mut(),
+    };
+    let js_object = object.reflector().get_jsobject().get();
     match sc_holder {
-        StructuredDataHolder::Read { blob, .. } => {
-            *blob = Some(read_blob);
+        StructuredDataHolder::Read { deserialized, .. } => {
+            deserialized.push(Box::new(object));
         },
         _ => panic!("Unexpected variant of StructuredDataHolder"),
     }
     js_object
 }
 
-unsafe fn write_blob(blob: DomRoot<Blob>, w: *mut JSStructuredCloneWriter) -> Result<(), ()> {
+/// Writes the tag of a platform object, then runs its serialization steps.
+unsafe fn write_object<T: Serializable>(
+    object: &T,
+    tag: StructuredCloneTags,
+    w: *mut JSStructuredCloneWriter,
+) -> bool {
     let structured_writer = StructuredCloneWriter { w: w };
-    let blob_vec = blob.get_bytes()?;
-    assert!(JS_WriteUint32Pair(
-        w,
-        StructuredCloneTags::DomBlob as u32,
-        0
-    ));
-    structured_writer.write_slice(&blob_vec);
-    structured_writer.write_str(&blob.type_string());
-    return Ok(());
+    structured_writer.write_u32_pair(tag as u32, 0);
+    object.serialize(&structured_writer).is_ok()
 }
 
 unsafe extern "C" fn read_callback(
@@ -182,8 +239,39 @@ unsafe extern "C" fn read_callback(
         tag > StructuredCloneTags::Min as u32,
         "tag should be higher than StructuredCloneTags::Min"
     );
+    let sc_holder = &mut *(closure as *mut StructuredDataHolder);
     if tag == StructuredCloneTags::DomBlob as u32 {
-        return read_blob(cx, r, &mut *(closure as *mut StructuredDataHolder));
+        return read_object::<Blob>(cx, r, sc_holder);
+    }
+    if tag == StructuredCloneTags::File as u32 {
+        return read_object::<File>(cx, r, sc_holder);
+    }
+    if tag == StructuredCloneTags::FileList as u32 {
+        return read_object::<FileList>(cx, r, sc_holder);
+    }
+    if tag == StructuredCloneTags::ImageData as u32 {
+        return read_object::<ImageData>(cx, r, sc_holder);
+    }
+    if tag == StructuredCloneTags::DomPointReadOnly as u32 {
+        return read_object::<DOMPointReadOnly>(cx, r, sc_holder);
+    }
+    if tag == StructuredCloneTags::DomPoint as u32 {
+        return read_object::<DOMPoint>(cx, r, sc_holder);
+    }
+    if tag == StructuredCloneTags::DomRectReadOnly as u32 {
+        return read_object::<DOMRectReadOnly>(cx, r, sc_holder);
+    }
+    if tag == StructuredCloneTags::DomRect as u32 {
+        return read_object::<DOMRect>(cx, r, sc_holder);
+    }
+    if tag == StructuredCloneTags::DomQuad as u32 {
+        return read_object::<DOMQuad>(cx, r, sc_holder);
+    }
+    if tag == StructuredCloneTags::DomMatrixReadOnly as u32 {
+        return read_object::<DOMMatrixReadOnly>(cx, r, sc_holder);
+    }
+    if tag == StructuredCloneTags::DomMatrix as u32 {
+        return read_object::<DOMMatrix>(cx, r, sc_holder);
     }
     return ptr::null_mut();
 }
@@ -194,8 +282,39 @@ unsafe extern "C" fn write_callback(
     obj: RawHandleObject,
     _closure: *mut raw::c_void,
 ) -> bool {
+    // Objects that inherit from another serializable interface have to be checked first.
+    if let Ok(file) = root_from_object::<File>(*obj, cx) {
+        return write_object(&*file, StructuredCloneTags::File, w);
+    }
     if let Ok(blob) = root_from_object::<Blob>(*obj, cx) {
-        return write_blob(blob, w).is_ok();
+        return write_object(&*blob, StructuredCloneTags::DomBlob, w);
+    }
+    if let Ok(file_list) = root_from_object::<FileList>(*obj, cx) {
+        return write_object(&*file_list, StructuredCloneTags::FileList, w);
+    }
+    if let Ok(image_data) = root_from_object::<ImageData>(*obj, cx) {
+        return write_object(&*image_data, StructuredCloneTags::ImageData, w);
+    }
+    if let Ok(point) = root_from_object::<DOMPoint>(*obj, cx) {
+        return write_object(&*point, StructuredCloneTags::DomPoint, w);
+    }
+    if let Ok(point) = root_from_object::<DOMPointReadOnly>(*obj, cx) {
+        return write_object(&*point, StructuredCloneTags::DomPointReadOnly, w);
+    }
+    if let Ok(rect) = root_from_object::<DOMRect>(*obj, cx) {
+        return write_object(&*rect, StructuredCloneTags::DomRect, w);
+    }
+    if let Ok(rect) = root_from_object::<DOMRectReadOnly>(*obj, cx) {
+        return write_object(&*rect, StructuredCloneTags::DomRectReadOnly, w);
+    }
+    if let Ok(quad) = root_from_object::<DOMQuad>(*obj, cx) {
+        return write_object(&*quad, StructuredCloneTags::DomQuad, w);
+    }
+    if let Ok(matrix) = root_from_object::<DOMMatrix>(*obj, cx) {
+        return write_object(&*matrix, StructuredCloneTags::DomMatrix, w);
+    }
+    if let Ok(matrix) = root_from_object::<DOMMatrixReadOnly>(*obj, cx) {
+        return write_object(&*matrix, StructuredCloneTags::DomMatrixReadOnly, w);
     }
     return false;
 }
@@ -221,6 +340,18 @@ unsafe extern "C" fn read_transfer_callback(
             return true;
         }
     }
+    if tag == StructuredCloneTags::OffscreenCanvas as u32 {
+        let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
+        let owner = GlobalScope::from_context(cx);
+        if let Ok(_) = <OffscreenCanvas as Transferable>::transfer_receive(
+            &owner,
+            &mut sc_holder,
+            extra_data,
+            return_object,
+        ) {
+            return true;
+        }
+    }
     false
 }
 
@@ -243,6 +374,15 @@ unsafe extern "C" fn write_transfer_callback(
             return true;
         }
     }
+    if let Ok(canvas) = root_from_object::<OffscreenCanvas>(*obj, cx) {
+        *tag = StructuredCloneTags::OffscreenCanvas as u32;
+        *ownership = TransferableOwnership::SCTAG_TMO_CUSTOM;
+        let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
+        if let Ok(data) = canvas.transfer(&mut sc_holder) {
+            *extra_data = data;
+            return true;
+        }
+    }
     false
 }
 
@@ -260,9 +400,13 @@ unsafe extern "C" fn can_transfer_callback(
     obj: RawHandleObject,
     _closure: *mut raw::c_void,
 ) -> bool {
+    // ArrayBuffers are transferred by SpiderMonkey itself, without asking us.
     if let Ok(_port) = root_from_object::<MessagePort>(*obj, cx) {
         return true;
     }
+    if let Ok(_canvas) = root_from_object::<OffscreenCanvas>(*obj, cx) {
+        return true;
+    }
     false
 }
 
@@ -282,8 +426,8 @@ static STRUCTURED_CLONE_CALLBACKS: JSStructuredCloneCallbacks = JSStructuredClon
 /// https://html.spec.whatwg.org/multipage/#safe-passing-of-structured-data
 pub enum StructuredDataHolder {
     Read {
-        /// A deserialized blob, stored temporarily here to keep it rooted.
-        blob: Option<DomRoot<Blob>>,
+        /// The platform objects deserialized so far, kept rooted until the read is done.
+        deserialized: Vec<Box<dyn Any>>,
         /// A vec of transfer-received DOM ports,
         /// to be made available to script through a message event.
         message_ports: Option<Vec<DomRoot<MessagePort>>>,
@@ -367,7 +511,7 @@ pub fn read(
     let cx = global.get_cx();
     let _ac = enter_realm(&*global);
     let mut sc_holder = StructuredDataHolder::Read {
-        blob: None,
+        deserialized: vec![],
         message_ports: None,
         port_impls: data.ports.take(),
     };
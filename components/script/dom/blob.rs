@@ -9,7 +9,9 @@ use crate::dom::bindings::codegen::UnionTypes::ArrayBufferOrArrayBufferViewOrBlo
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::serializable::Serializable;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 use net_traits::blob_url_store::{get_blob_origin, BlobBuf};
@@ -369,6 +371,29 @@ pub fn blob_parts_to_bytes(
     Ok(ret)
 }
 
+impl Serializable for Blob {
+    /// <https://w3c.github.io/FileAPI/#blob-section>
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()> {
+        writer.write_slice(&self.get_bytes()?);
+        writer.write_str(&self.type_string);
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/FileAPI/#blob-section>
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()> {
+        let bytes = reader.read_bytes();
+        let type_string = reader.read_str();
+        Ok(Blob::new(
+            owner,
+            BlobImpl::new_from_bytes(bytes),
+            type_string,
+        ))
+    }
+}
+
 impl BlobMethods for Blob {
     // https://w3c.github.io/FileAPI/#dfn-size
     fn Size(&self) -> u64 {
@@ -0,0 +1,247 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding;
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{
+    CryptoKeyMethods, KeyType, KeyUsage,
+};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    AesKeyAlgorithm, EcKeyAlgorithm, HmacKeyAlgorithm, KeyAlgorithm, RsaHashedKeyAlgorithm,
+    RsaKeyAlgorithm,
+};
+use crate::dom::bindings::conversions::ToJSValConvertible;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::subtlecrypto::{AlgorithmName, NamedCurve};
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use js::jsval::UndefinedValue;
+use js::rust::MutableHandleValue;
+use js::typedarray::{CreateWith, HeapUint8Array, Uint8Array};
+use openssl::pkey::{PKey, Private, Public};
+use std::ptr;
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+/// The key material held by a `CryptoKey`, shared with the threads that operate on it.
+#[derive(Clone)]
+pub enum Handle {
+    Secret(Arc<Vec<u8>>),
+    Private(Arc<PKey<Private>>),
+    Public(Arc<PKey<Public>>),
+}
+
+unsafe_no_jsmanaged_fields!(Handle);
+
+impl Handle {
+    pub fn key_type(&self) -> KeyType {
+        match *self {
+            Handle::Secret(_) => KeyType::Secret,
+            Handle::Private(_) => KeyType::Private,
+            Handle::Public(_) => KeyType::Public,
+        }
+    }
+}
+
+/// The contents of the [[algorithm]] internal slot of a `CryptoKey`.
+#[derive(Clone, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum KeyAlgorithmData {
+    /// https://w3c.github.io/webcrypto/#AesKeyAlgorithm-dictionary
+    Aes { name: AlgorithmName, length: u16 },
+    /// https://w3c.github.io/webcrypto/#EcKeyAlgorithm-dictionary
+    Ec {
+        name: AlgorithmName,
+        named_curve: NamedCurve,
+    },
+    /// https://w3c.github.io/webcrypto/#HmacKeyAlgorithm-dictionary
+    Hmac { hash: AlgorithmName, length: u32 },
+    /// A plain `KeyAlgorithm`, used by HKDF and PBKDF2 keys.
+    Kdf(AlgorithmName),
+    /// https://w3c.github.io/webcrypto/#RsaHashedKeyAlgorithm-dictionary
+    RsaHashed {
+        name: AlgorithmName,
+        modulus_length: u32,
+        public_exponent: Vec<u8>,
+        hash: AlgorithmName,
+    },
+}
+
+impl KeyAlgorithmData {
+    pub fn name(&self) -> AlgorithmName {
+        match *self {
+            KeyAlgorithmData::Aes { name, .. } |
+            KeyAlgorithmData::Ec { name, .. } |
+            KeyAlgorithmData::Kdf(name) |
+            KeyAlgorithmData::RsaHashed { name, .. } => name,
+            KeyAlgorithmData::Hmac { .. } => AlgorithmName::Hmac,
+        }
+    }
+
+    pub fn hash(&self) -> Option<AlgorithmName> {
+        match *self {
+            KeyAlgorithmData::Hmac { hash, .. } | KeyAlgorithmData::RsaHashed { hash, .. } => {
+                Some(hash)
+            },
+            _ => None,
+        }
+    }
+}
+
+fn key_algorithm(name: AlgorithmName) -> KeyAlgorithm {
+    KeyAlgorithm {
+        name: DOMString::from(name.as_str()),
+    }
+}
+
+impl ToJSValConvertible for KeyAlgorithmData {
+    #[allow(unsafe_code)]
+    unsafe fn to_jsval(&self, cx: *mut js::jsapi::JSContext, rval: MutableHandleValue) {
+        match *self {
+            KeyAlgorithmData::Aes { name, length } => AesKeyAlgorithm {
+                parent: key_algorithm(name),
+                length: length,
+            }
+            .to_jsval(cx, rval),
+            KeyAlgorithmData::Ec { name, named_curve } => EcKeyAlgorithm {
+                parent: key_algorithm(name),
+                namedCurve: DOMString::from(named_curve.as_str()),
+            }
+            .to_jsval(cx, rval),
+            KeyAlgorithmData::Hmac { hash, length } => HmacKeyAlgorithm {
+                parent: key_algorithm(AlgorithmName::Hmac),
+                hash: key_algorithm(hash),
+                length: length,
+            }
+            .to_jsval(cx, rval),
+            KeyAlgorithmData::Kdf(name) => key_algorithm(name).to_jsval(cx, rval),
+            KeyAlgorithmData::RsaHashed {
+                name,
+                modulus_length,
+                ref public_exponent,
+                hash,
+            } => {
+                rooted!(in(cx) let mut array = ptr::null_mut::<JSObject>());
+                let _ =
+                    Uint8Array::create(cx, CreateWith::Slice(public_exponent), array.handle_mut());
+                let algorithm = RootedTraceableBox::new(RsaHashedKeyAlgorithm {
+                    parent: RsaKeyAlgorithm {
+                        parent: key_algorithm(name),
+                        modulusLength: modulus_length,
+                        publicExponent: HeapUint8Array::from(array.get())
+                            .expect("Uint8Array::create returned a non-typed array"),
+                    },
+                    hash: key_algorithm(hash),
+                });
+                algorithm.to_jsval(cx, rval)
+            },
+        }
+    }
+}
+
+// https://w3c.github.io/webcrypto/#cryptokey-interface
+#[dom_struct]
+pub struct CryptoKey {
+    reflector_: Reflector,
+    extractable: bool,
+    algorithm: KeyAlgorithmData,
+    usages: Vec<KeyUsage>,
+    #[ignore_malloc_size_of = "Defined in openssl"]
+    handle: Handle,
+    #[ignore_malloc_size_of = "mozjs"]
+    algorithm_object: Heap<*mut JSObject>,
+    #[ignore_malloc_size_of = "mozjs"]
+    usages_object: Heap<*mut JSObject>,
+}
+
+impl CryptoKey {
+    fn new_inherited(
+        extractable: bool,
+        algorithm: KeyAlgorithmData,
+        usages: Vec<KeyUsage>,
+        handle: Handle,
+    ) -> CryptoKey {
+        CryptoKey {
+            reflector_: Reflector::new(),
+            extractable: extractable,
+            algorithm: algorithm,
+            usages: usages,
+            handle: handle,
+            algorithm_object: Heap::default(),
+            usages_object: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        extractable: bool,
+        algorithm: KeyAlgorithmData,
+        usages: Vec<KeyUsage>,
+        handle: Handle,
+    ) -> DomRoot<CryptoKey> {
+        reflect_dom_object(
+            Box::new(CryptoKey::new_inherited(
+                extractable,
+                algorithm,
+                usages,
+                handle,
+            )),
+            global,
+            CryptoKeyBinding::Wrap,
+        )
+    }
+
+    pub fn extractable(&self) -> bool {
+        self.extractable
+    }
+
+    pub fn algorithm(&self) -> &KeyAlgorithmData {
+        &self.algorithm
+    }
+
+    pub fn usages(&self) -> &[KeyUsage] {
+        &self.usages
+    }
+
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+}
+
+impl CryptoKeyMethods for CryptoKey {
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-type
+    fn Type(&self) -> KeyType {
+        self.handle.key_type()
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-extractable
+    fn Extractable(&self) -> bool {
+        self.extractable
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-algorithm
+    fn Algorithm(&self, cx: JSContext) -> NonNull<JSObject> {
+        if self.algorithm_object.get().is_null() {
+            rooted!(in(*cx) let mut algorithm = UndefinedValue());
+            unsafe { self.algorithm.to_jsval(*cx, algorithm.handle_mut()) };
+            self.algorithm_object.set(algorithm.to_object());
+        }
+        unsafe { NonNull::new_unchecked(self.algorithm_object.get()) }
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-usages
+    fn Usages(&self, cx: JSContext) -> NonNull<JSObject> {
+        if self.usages_object.get().is_null() {
+            rooted!(in(*cx) let mut usages = UndefinedValue());
+            unsafe { self.usages.to_jsval(*cx, usages.handle_mut()) };
+            self.usages_object.set(usages.to_object());
+        }
+        unsafe { NonNull::new_unchecked(self.usages_object.get()) }
+    }
+}
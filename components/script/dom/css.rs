@@ -2,8 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::CSSBinding::PropertyDefinition;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
-use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::reflector::Reflector;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
@@ -12,7 +13,10 @@ use crate::dom::worklet::Worklet;
 use cssparser::{serialize_identifier, Parser, ParserInput};
 use dom_struct::dom_struct;
 use style::context::QuirksMode;
+use style::custom_properties::{self, Name, VariableValue};
 use style::parser::ParserContext;
+use style::properties_and_values::syntax::Descriptor;
+use style::properties_and_values::PropertyRegistration;
 use style::stylesheets::supports_rule::{parse_condition_or_declaration, Declaration};
 use style::stylesheets::CssRuleType;
 use style_traits::ParsingMode;
@@ -74,4 +78,35 @@ impl CSS {
     pub fn PaintWorklet(win: &Window) -> DomRoot<Worklet> {
         win.paint_worklet()
     }
+
+    /// <https://drafts.css-houdini.org/css-properties-values-api/#dom-css-registerproperty>
+    pub fn RegisterProperty(win: &Window, definition: &PropertyDefinition) -> ErrorResult {
+        let syntax = Descriptor::from_str(&definition.syntax).map_err(|()| Error::Syntax)?;
+        let name = custom_properties::parse_name(&definition.name).map_err(|()| Error::Syntax)?;
+
+        let url = win.Document().url();
+        let initial_value = match definition.initialValue {
+            Some(ref initial_value) => {
+                let mut input = ParserInput::new(initial_value);
+                let value = Parser::new(&mut input)
+                    .parse_entirely(VariableValue::parse)
+                    .map_err(|_| Error::Syntax)?;
+                if !PropertyRegistration::is_valid_initial_value(&syntax, &value, &url) {
+                    return Err(Error::Syntax);
+                }
+                Some(value)
+            },
+            None if syntax.is_universal() => None,
+            None => return Err(Error::Syntax),
+        };
+
+        win.Document()
+            .register_custom_property(PropertyRegistration {
+                name: Name::from(name),
+                syntax,
+                inherits: definition.inherits,
+                initial_value,
+                url_data: url,
+            })
+    }
 }
@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animation::Animation;
+use crate::dom::animationeffect::{AnimationEffect, Timing};
+use crate::dom::animationtimeline::AnimationTimeline;
+use crate::dom::bindings::codegen::Bindings::CSSAnimationBinding;
+use crate::dom::bindings::codegen::Bindings::CSSAnimationBinding::CSSAnimationMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::keyframeeffect::KeyframeEffect;
+use crate::dom::node::document_from_node;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+// https://drafts.csswg.org/css-animations-2/#the-CSSAnimation-interface
+#[dom_struct]
+pub struct CSSAnimation {
+    animation: Animation,
+    /// The name of the `@keyframes` rule this animation runs.
+    animation_name: DOMString,
+}
+
+impl CSSAnimation {
+    fn new_inherited(
+        window: &Window,
+        timeline: &AnimationTimeline,
+        animation_name: DOMString,
+    ) -> CSSAnimation {
+        CSSAnimation {
+            animation: Animation::new_inherited(window, Some(timeline)),
+            animation_name: animation_name,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        target: &Element,
+        animation_name: DOMString,
+    ) -> DomRoot<CSSAnimation> {
+        let timeline = document_from_node(target).Timeline();
+        let animation = reflect_dom_object(
+            Box::new(CSSAnimation::new_inherited(
+                window,
+                timeline.upcast(),
+                animation_name,
+            )),
+            window,
+            CSSAnimationBinding::Wrap,
+        );
+        let effect = KeyframeEffect::new(window, Some(target), Timing::default());
+        animation
+            .upcast::<Animation>()
+            .set_up(Some(effect.upcast::<AnimationEffect>()));
+        animation
+    }
+
+    pub fn animation_name(&self) -> &str {
+        &self.animation_name
+    }
+}
+
+impl CSSAnimationMethods for CSSAnimation {
+    // https://drafts.csswg.org/css-animations-2/#dom-cssanimation-animationname
+    fn AnimationName(&self) -> DOMString {
+        self.animation_name.clone()
+    }
+}
@@ -5,6 +5,7 @@
 use crate::dom::bindings::codegen::Bindings::CSSConditionRuleBinding::CSSConditionRuleMethods;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::csscontainerrule::CSSContainerRule;
 use crate::dom::cssgroupingrule::CSSGroupingRule;
 use crate::dom::cssmediarule::CSSMediaRule;
 use crate::dom::cssstylesheet::CSSStyleSheet;
@@ -45,6 +46,8 @@ impl CSSConditionRuleMethods for CSSConditionRule {
             rule.get_condition_text()
         } else if let Some(rule) = self.downcast::<CSSSupportsRule>() {
             rule.get_condition_text()
+        } else if let Some(rule) = self.downcast::<CSSContainerRule>() {
+            rule.get_condition_text()
         } else {
             unreachable!()
         }
@@ -56,6 +59,8 @@ impl CSSConditionRuleMethods for CSSConditionRule {
             rule.set_condition_text(text)
         } else if let Some(rule) = self.downcast::<CSSSupportsRule>() {
             rule.set_condition_text(text)
+        } else if let Some(rule) = self.downcast::<CSSContainerRule>() {
+            rule.set_condition_text(text)
         } else {
             unreachable!()
         }
@@ -0,0 +1,105 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSContainerRuleBinding;
+use crate::dom::bindings::codegen::Bindings::CSSContainerRuleBinding::CSSContainerRuleMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::cssconditionrule::CSSConditionRule;
+use crate::dom::cssrule::SpecificCSSRule;
+use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_arc::Arc;
+use style::shared_lock::{Locked, ToCssWithGuard};
+use style::stylesheets::ContainerRule;
+use style_traits::ToCss;
+
+#[dom_struct]
+pub struct CSSContainerRule {
+    cssconditionrule: CSSConditionRule,
+    #[ignore_malloc_size_of = "Arc"]
+    containerrule: Arc<Locked<ContainerRule>>,
+}
+
+impl CSSContainerRule {
+    fn new_inherited(
+        parent_stylesheet: &CSSStyleSheet,
+        containerrule: Arc<Locked<ContainerRule>>,
+    ) -> CSSContainerRule {
+        let guard = parent_stylesheet.shared_lock().read();
+        let list = containerrule.read_with(&guard).rules.clone();
+        CSSContainerRule {
+            cssconditionrule: CSSConditionRule::new_inherited(parent_stylesheet, list),
+            containerrule: containerrule,
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        window: &Window,
+        parent_stylesheet: &CSSStyleSheet,
+        containerrule: Arc<Locked<ContainerRule>>,
+    ) -> DomRoot<CSSContainerRule> {
+        reflect_dom_object(
+            Box::new(CSSContainerRule::new_inherited(
+                parent_stylesheet,
+                containerrule,
+            )),
+            window,
+            CSSContainerRuleBinding::Wrap,
+        )
+    }
+
+    /// <https://drafts.csswg.org/css-contain-3/#dom-csscontainerrule-conditiontext>
+    pub fn get_condition_text(&self) -> DOMString {
+        let guard = self.cssconditionrule.shared_lock().read();
+        let rule = self.containerrule.read_with(&guard);
+        rule.condition.to_css_string().into()
+    }
+
+    /// The condition of a `@container` rule can't be changed.
+    ///
+    /// <https://drafts.csswg.org/css-contain-3/#dom-csscontainerrule-conditiontext>
+    pub fn set_condition_text(&self, _text: DOMString) {}
+}
+
+impl SpecificCSSRule for CSSContainerRule {
+    fn ty(&self) -> u16 {
+        // https://drafts.csswg.org/cssom/#dom-cssrule-type
+        // New rule types return 0.
+        0
+    }
+
+    fn get_css(&self) -> DOMString {
+        let guard = self.cssconditionrule.shared_lock().read();
+        self.containerrule
+            .read_with(&guard)
+            .to_css_string(&guard)
+            .into()
+    }
+}
+
+impl CSSContainerRuleMethods for CSSContainerRule {
+    /// <https://drafts.csswg.org/css-contain-3/#dom-csscontainerrule-containername>
+    fn ContainerName(&self) -> DOMString {
+        let guard = self.cssconditionrule.shared_lock().read();
+        match self.containerrule.read_with(&guard).condition.name {
+            Some(ref name) => name.to_css_string().into(),
+            None => DOMString::new(),
+        }
+    }
+
+    /// <https://drafts.csswg.org/css-contain-3/#dom-csscontainerrule-containerquery>
+    fn ContainerQuery(&self) -> DOMString {
+        let guard = self.cssconditionrule.shared_lock().read();
+        self.containerrule
+            .read_with(&guard)
+            .condition
+            .condition
+            .to_css_string()
+            .into()
+    }
+}
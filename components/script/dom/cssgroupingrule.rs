@@ -65,7 +65,9 @@ impl CSSGroupingRuleMethods for CSSGroupingRule {
 
     // https://drafts.csswg.org/cssom/#dom-cssgroupingrule-insertrule
     fn InsertRule(&self, rule: DOMString, index: u32) -> Fallible<u32> {
-        self.rulelist().insert_rule(&rule, index, /* nested */ true)
+        self.rulelist().insert_rule(
+            &rule, index, /* nested */ true, /* in_style_rule */ false,
+        )
     }
 
     // https://drafts.csswg.org/cssom/#dom-cssgroupingrule-deleterule
@@ -0,0 +1,87 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSLayerStatementRuleBinding;
+use crate::dom::bindings::codegen::Bindings::CSSLayerStatementRuleBinding::CSSLayerStatementRuleMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::cssrule::{CSSRule, SpecificCSSRule};
+use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsval::JSVal;
+use servo_arc::Arc;
+use style::shared_lock::{Locked, ToCssWithGuard};
+use style::stylesheets::LayerStatementRule;
+use style_traits::ToCss;
+
+#[dom_struct]
+pub struct CSSLayerStatementRule {
+    cssrule: CSSRule,
+    #[ignore_malloc_size_of = "Arc"]
+    layerstatementrule: Arc<Locked<LayerStatementRule>>,
+}
+
+impl CSSLayerStatementRule {
+    fn new_inherited(
+        parent_stylesheet: &CSSStyleSheet,
+        layerstatementrule: Arc<Locked<LayerStatementRule>>,
+    ) -> CSSLayerStatementRule {
+        CSSLayerStatementRule {
+            cssrule: CSSRule::new_inherited(parent_stylesheet),
+            layerstatementrule: layerstatementrule,
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        window: &Window,
+        parent_stylesheet: &CSSStyleSheet,
+        layerstatementrule: Arc<Locked<LayerStatementRule>>,
+    ) -> DomRoot<CSSLayerStatementRule> {
+        reflect_dom_object(
+            Box::new(CSSLayerStatementRule::new_inherited(
+                parent_stylesheet,
+                layerstatementrule,
+            )),
+            window,
+            CSSLayerStatementRuleBinding::Wrap,
+        )
+    }
+}
+
+impl SpecificCSSRule for CSSLayerStatementRule {
+    fn ty(&self) -> u16 {
+        // https://drafts.csswg.org/cssom/#dom-cssrule-type
+        // New rule types return 0.
+        0
+    }
+
+    fn get_css(&self) -> DOMString {
+        let guard = self.cssrule.shared_lock().read();
+        self.layerstatementrule
+            .read_with(&guard)
+            .to_css_string(&guard)
+            .into()
+    }
+}
+
+impl CSSLayerStatementRuleMethods for CSSLayerStatementRule {
+    // https://drafts.csswg.org/css-cascade-5/#dom-csslayerstatementrule-namelist
+    fn NameList(&self, cx: JSContext) -> JSVal {
+        let names: Vec<DOMString> = {
+            let guard = self.cssrule.shared_lock().read();
+            self.layerstatementrule
+                .read_with(&guard)
+                .names
+                .iter()
+                .map(|name| name.to_css_string().into())
+                .collect()
+        };
+        to_frozen_array(&names, cx)
+    }
+}
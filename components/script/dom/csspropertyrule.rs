@@ -0,0 +1,100 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSPropertyRuleBinding;
+use crate::dom::bindings::codegen::Bindings::CSSPropertyRuleBinding::CSSPropertyRuleMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::cssrule::{CSSRule, SpecificCSSRule};
+use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_arc::Arc;
+use style::shared_lock::{Locked, ToCssWithGuard};
+use style::stylesheets::PropertyRule;
+use style_traits::ToCss;
+
+#[dom_struct]
+pub struct CSSPropertyRule {
+    cssrule: CSSRule,
+    #[ignore_malloc_size_of = "Arc"]
+    propertyrule: Arc<Locked<PropertyRule>>,
+}
+
+impl CSSPropertyRule {
+    fn new_inherited(
+        parent_stylesheet: &CSSStyleSheet,
+        propertyrule: Arc<Locked<PropertyRule>>,
+    ) -> CSSPropertyRule {
+        CSSPropertyRule {
+            cssrule: CSSRule::new_inherited(parent_stylesheet),
+            propertyrule: propertyrule,
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        window: &Window,
+        parent_stylesheet: &CSSStyleSheet,
+        propertyrule: Arc<Locked<PropertyRule>>,
+    ) -> DomRoot<CSSPropertyRule> {
+        reflect_dom_object(
+            Box::new(CSSPropertyRule::new_inherited(
+                parent_stylesheet,
+                propertyrule,
+            )),
+            window,
+            CSSPropertyRuleBinding::Wrap,
+        )
+    }
+}
+
+impl SpecificCSSRule for CSSPropertyRule {
+    fn ty(&self) -> u16 {
+        // https://drafts.csswg.org/cssom/#dom-cssrule-type
+        // New rule types return 0.
+        0
+    }
+
+    fn get_css(&self) -> DOMString {
+        let guard = self.cssrule.shared_lock().read();
+        self.propertyrule
+            .read_with(&guard)
+            .to_css_string(&guard)
+            .into()
+    }
+}
+
+impl CSSPropertyRuleMethods for CSSPropertyRule {
+    // https://drafts.css-houdini.org/css-properties-values-api/#dom-csspropertyrule-name
+    fn Name(&self) -> DOMString {
+        let guard = self.cssrule.shared_lock().read();
+        let rule = self.propertyrule.read_with(&guard);
+        format!("--{}", rule.registration.name).into()
+    }
+
+    // https://drafts.css-houdini.org/css-properties-values-api/#dom-csspropertyrule-syntax
+    fn Syntax(&self) -> DOMString {
+        let guard = self.cssrule.shared_lock().read();
+        let rule = self.propertyrule.read_with(&guard);
+        rule.registration.syntax.to_css_string().into()
+    }
+
+    // https://drafts.css-houdini.org/css-properties-values-api/#dom-csspropertyrule-inherits
+    fn Inherits(&self) -> bool {
+        let guard = self.cssrule.shared_lock().read();
+        self.propertyrule.read_with(&guard).registration.inherits
+    }
+
+    // https://drafts.css-houdini.org/css-properties-values-api/#dom-csspropertyrule-initialvalue
+    fn GetInitialValue(&self) -> Option<DOMString> {
+        let guard = self.cssrule.shared_lock().read();
+        let rule = self.propertyrule.read_with(&guard);
+        rule.registration
+            .initial_value
+            .as_ref()
+            .map(|value| value.to_css_string().into())
+    }
+}
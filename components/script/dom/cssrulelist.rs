@@ -6,18 +6,16 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::CSSRuleListBinding;
 use crate::dom::bindings::codegen::Bindings::CSSRuleListBinding::CSSRuleListMethods;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
-use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::csskeyframerule::CSSKeyframeRule;
 use crate::dom::cssrule::CSSRule;
 use crate::dom::cssstylesheet::CSSStyleSheet;
-use crate::dom::htmlelement::HTMLElement;
 use crate::dom::window::Window;
-use crate::stylesheet_loader::StylesheetLoader;
 use dom_struct::dom_struct;
 use servo_arc::Arc;
 use style::shared_lock::Locked;
+use style::stylesheets::StylesheetLoader as StyleStylesheetLoader;
 use style::stylesheets::{CssRules, CssRulesHelpers, KeyframesRule, RulesMutateError};
 
 #[allow(unsafe_code)]
@@ -92,7 +90,13 @@ impl CSSRuleList {
 
     /// Should only be called for CssRules-backed rules. Use append_lazy_rule
     /// for keyframes-backed rules.
-    pub fn insert_rule(&self, rule: &str, idx: u32, nested: bool) -> Fallible<u32> {
+    pub fn insert_rule(
+        &self,
+        rule: &str,
+        idx: u32,
+        nested: bool,
+        in_style_rule: bool,
+    ) -> Fallible<u32> {
         let css_rules = if let RulesSource::Rules(ref rules) = self.rules {
             rules
         } else {
@@ -104,12 +108,7 @@ impl CSSRuleList {
         let index = idx as usize;
 
         let parent_stylesheet = self.parent_stylesheet.style_stylesheet();
-        let owner = self
-            .parent_stylesheet
-            .get_owner()
-            .downcast::<HTMLElement>()
-            .unwrap();
-        let loader = StylesheetLoader::for_element(owner);
+        let loader = self.parent_stylesheet.loader();
         let new_rule = css_rules.with_raw_offset_arc(|arc| {
             arc.insert_rule(
                 &parent_stylesheet.shared_lock,
@@ -117,7 +116,10 @@ impl CSSRuleList {
                 &parent_stylesheet.contents,
                 index,
                 nested,
-                Some(&loader),
+                in_style_rule,
+                loader
+                    .as_ref()
+                    .map(|loader| loader as &dyn StyleStylesheetLoader),
             )
         })?;
 
@@ -3,24 +3,25 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::CSSStyleRuleBinding::{self, CSSStyleRuleMethods};
+use crate::dom::bindings::error::{ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::cssrule::{CSSRule, SpecificCSSRule};
+use crate::dom::cssrulelist::{CSSRuleList, RulesSource};
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::cssstylesheet::CSSStyleSheet;
-use crate::dom::node::{stylesheets_owner_from_node, Node};
 use crate::dom::window::Window;
-use cssparser::ToCss;
 use cssparser::{Parser as CssParser, ParserInput as CssParserInput};
+use cssparser::{SourceLocation, ToCss};
 use dom_struct::dom_struct;
 use selectors::parser::SelectorList;
 use servo_arc::Arc;
 use std::mem;
 use style::selector_parser::SelectorParser;
 use style::shared_lock::{Locked, ToCssWithGuard};
-use style::stylesheets::{Origin, StyleRule};
+use style::stylesheets::{CssRules, Origin, StyleRule};
 
 #[dom_struct]
 pub struct CSSStyleRule {
@@ -28,6 +29,7 @@ pub struct CSSStyleRule {
     #[ignore_malloc_size_of = "Arc"]
     stylerule: Arc<Locked<StyleRule>>,
     style_decl: MutNullableDom<CSSStyleDeclaration>,
+    rulelist: MutNullableDom<CSSRuleList>,
 }
 
 impl CSSStyleRule {
@@ -39,6 +41,7 @@ impl CSSStyleRule {
             cssrule: CSSRule::new_inherited(parent_stylesheet),
             stylerule: stylerule,
             style_decl: Default::default(),
+            rulelist: Default::default(),
         }
     }
 
@@ -54,6 +57,34 @@ impl CSSStyleRule {
             CSSStyleRuleBinding::Wrap,
         )
     }
+
+    fn rulelist(&self) -> DomRoot<CSSRuleList> {
+        let parent_stylesheet = self.cssrule.parent_stylesheet();
+        self.rulelist.or_init(|| {
+            // Rules without nested rules don't have a rule list, so create an
+            // empty one to be able to insert rules in it.
+            let rules = {
+                let shared_lock = self.cssrule.shared_lock();
+                let mut guard = shared_lock.write();
+                let stylerule = self.stylerule.write_with(&mut guard);
+                stylerule
+                    .rules
+                    .get_or_insert_with(|| CssRules::new(vec![], shared_lock))
+                    .clone()
+            };
+            CSSRuleList::new(
+                self.global().as_window(),
+                parent_stylesheet,
+                RulesSource::Rules(rules),
+            )
+        })
+    }
+
+    /// The position of this rule in the source of its style sheet.
+    pub fn source_location(&self) -> SourceLocation {
+        let guard = self.cssrule.shared_lock().read();
+        self.stylerule.read_with(&guard).source_location
+    }
 }
 
 impl SpecificCSSRule for CSSStyleRule {
@@ -118,8 +149,24 @@ impl CSSStyleRuleMethods for CSSStyleRule {
             let mut guard = self.cssrule.shared_lock().write();
             let stylerule = self.stylerule.write_with(&mut guard);
             mem::swap(&mut stylerule.selectors, &mut s);
-            stylesheets_owner_from_node(self.cssrule.parent_stylesheet().owner().upcast::<Node>())
-                .invalidate_stylesheets();
+            self.cssrule.parent_stylesheet().invalidate_stylesheets();
         }
     }
+
+    // https://drafts.csswg.org/css-nesting/#dom-cssstylerule-cssrules
+    fn CssRules(&self) -> DomRoot<CSSRuleList> {
+        self.rulelist()
+    }
+
+    // https://drafts.csswg.org/css-nesting/#dom-cssstylerule-insertrule
+    fn InsertRule(&self, rule: DOMString, index: u32) -> Fallible<u32> {
+        self.rulelist().insert_rule(
+            &rule, index, /* nested */ true, /* in_style_rule */ true,
+        )
+    }
+
+    // https://drafts.csswg.org/css-nesting/#dom-cssstylerule-deleterule
+    fn DeleteRule(&self, index: u32) -> ErrorResult {
+        self.rulelist().remove_rule(index)
+    }
 }
@@ -2,37 +2,70 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding;
-use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding::CSSStyleSheetMethods;
+use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding::{
+    CSSStyleSheetInit, CSSStyleSheetMethods,
+};
+use crate::dom::bindings::codegen::Bindings::MediaListBinding::MediaListMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::MediaListOrString;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::console::Console;
 use crate::dom::cssrulelist::{CSSRuleList, RulesSource};
+use crate::dom::document::Document;
 use crate::dom::element::Element;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlelement::HTMLElement;
 use crate::dom::node::{stylesheets_owner_from_node, Node};
+use crate::dom::promise::Promise;
 use crate::dom::stylesheet::StyleSheet;
+use crate::dom::stylesheetlist::StyleSheetListOwner;
 use crate::dom::window::Window;
+use crate::stylesheet_loader::StylesheetLoader;
+use crate::task_source::TaskSource;
+use cssparser::{ParseError, ParseErrorKind, Parser as CssParser, ParserInput, SourceLocation};
 use dom_struct::dom_struct;
 use servo_arc::Arc;
+use servo_url::ServoUrl;
 use std::cell::Cell;
+use std::rc::Rc;
+use style::error_reporting::{ContextualParseError, ParseErrorReporter};
+use style::media_queries::MediaList;
+use style::parser::ParserContext as CssParserContext;
 use style::shared_lock::SharedRwLock;
 use style::stylesheets::Stylesheet as StyleStyleSheet;
+use style::stylesheets::StylesheetLoader as StyleStylesheetLoader;
+use style::stylesheets::{CssRuleType, Origin};
+use style_traits::{ParsingMode, StyleParseErrorKind};
 
 #[dom_struct]
 pub struct CSSStyleSheet {
     stylesheet: StyleSheet,
-    owner: Dom<Element>,
+    /// The element owning this sheet, or `None` for a constructed sheet.
+    owner: Option<Dom<Element>>,
     rulelist: MutNullableDom<CSSRuleList>,
     #[ignore_malloc_size_of = "Arc"]
     style_stylesheet: Arc<StyleStyleSheet>,
     origin_clean: Cell<bool>,
+    /// <https://drafts.csswg.org/cssom/#concept-css-style-sheet-constructor-document>
+    constructor_document: Option<Dom<Document>>,
+    /// The documents and shadow roots whose `adoptedStyleSheets` contain this
+    /// constructed sheet.
+    adopters: DomRefCell<Vec<StyleSheetListOwner>>,
+    /// <https://drafts.csswg.org/cssom/#concept-css-style-sheet-disallow-modification-flag>
+    disallow_modification: Cell<bool>,
 }
 
 impl CSSStyleSheet {
     fn new_inherited(
-        owner: &Element,
+        owner: Option<&Element>,
+        constructor_document: Option<&Document>,
         type_: DOMString,
         href: Option<DOMString>,
         title: Option<DOMString>,
@@ -40,10 +73,13 @@ impl CSSStyleSheet {
     ) -> CSSStyleSheet {
         CSSStyleSheet {
             stylesheet: StyleSheet::new_inherited(type_, href, title),
-            owner: Dom::from_ref(owner),
+            owner: owner.map(Dom::from_ref),
             rulelist: MutNullableDom::new(None),
             style_stylesheet: stylesheet,
             origin_clean: Cell::new(true),
+            constructor_document: constructor_document.map(Dom::from_ref),
+            adopters: DomRefCell::new(vec![]),
+            disallow_modification: Cell::new(false),
         }
     }
 
@@ -58,15 +94,86 @@ impl CSSStyleSheet {
     ) -> DomRoot<CSSStyleSheet> {
         reflect_dom_object(
             Box::new(CSSStyleSheet::new_inherited(
-                owner, type_, href, title, stylesheet,
+                Some(owner),
+                None,
+                type_,
+                href,
+                title,
+                stylesheet,
             )),
             window,
             CSSStyleSheetBinding::Wrap,
         )
     }
 
-    pub fn owner(&self) -> DomRoot<Element> {
-        DomRoot::from_ref(&*self.owner)
+    // https://drafts.csswg.org/cssom/#dom-cssstylesheet-cssstylesheet
+    pub fn Constructor(
+        window: &Window,
+        options: &CSSStyleSheetInit,
+    ) -> Fallible<DomRoot<CSSStyleSheet>> {
+        let document = window.Document();
+
+        // Steps 2 and 3.
+        let url = match options.baseURL {
+            Some(ref base_url) => document
+                .base_url()
+                .join(base_url)
+                .map_err(|_| Error::NotAllowed)?,
+            None => document.base_url(),
+        };
+
+        // Step 6.
+        let media = match options.media {
+            MediaListOrString::MediaList(ref media) => media.MediaText(),
+            MediaListOrString::String(ref media) => media.clone(),
+        };
+        let css_error_reporter = window.css_error_reporter();
+        let context = CssParserContext::new_for_cssom(
+            &url,
+            Some(CssRuleType::Media),
+            ParsingMode::DEFAULT,
+            document.quirks_mode(),
+            css_error_reporter,
+            None,
+        );
+        let shared_lock = document.style_shared_lock().clone();
+        let mut input = ParserInput::new(&media);
+        let media =
+            Arc::new(shared_lock.wrap(MediaList::parse(&context, &mut CssParser::new(&mut input))));
+
+        let href = DOMString::from(url.as_str());
+        let sheet = StyleStyleSheet::from_str(
+            "",
+            url,
+            Origin::Author,
+            media,
+            shared_lock,
+            None,
+            css_error_reporter,
+            document.quirks_mode(),
+            0,
+        );
+
+        // Step 7.
+        sheet.set_disabled(options.disabled);
+
+        // Steps 1, 4 and 5.
+        Ok(reflect_dom_object(
+            Box::new(CSSStyleSheet::new_inherited(
+                None,
+                Some(&document),
+                DOMString::from("text/css"),
+                Some(href),
+                None,
+                Arc::new(sheet),
+            )),
+            window,
+            CSSStyleSheetBinding::Wrap,
+        ))
+    }
+
+    pub fn owner(&self) -> Option<DomRoot<Element>> {
+        self.owner.as_ref().map(|owner| DomRoot::from_ref(&**owner))
     }
 
     fn rulelist(&self) -> DomRoot<CSSRuleList> {
@@ -80,13 +187,17 @@ impl CSSStyleSheet {
         self.style_stylesheet.disabled()
     }
 
-    pub fn get_owner(&self) -> &Element {
-        &*self.owner
+    /// Returns the loader for the `@import` rules of this sheet, or `None` for a
+    /// constructed sheet, which can't have any.
+    pub fn loader(&self) -> Option<StylesheetLoader> {
+        self.owner
+            .as_ref()
+            .map(|owner| StylesheetLoader::for_element(owner.downcast::<HTMLElement>().unwrap()))
     }
 
     pub fn set_disabled(&self, disabled: bool) {
         if self.style_stylesheet.set_disabled(disabled) {
-            stylesheets_owner_from_node(self.owner().upcast::<Node>()).invalidate_stylesheets();
+            self.invalidate_stylesheets();
         }
     }
 
@@ -98,9 +209,108 @@ impl CSSStyleSheet {
         &self.style_stylesheet
     }
 
+    pub fn style_stylesheet_arc(&self) -> &Arc<StyleStyleSheet> {
+        &self.style_stylesheet
+    }
+
     pub fn set_origin_clean(&self, origin_clean: bool) {
         self.origin_clean.set(origin_clean);
     }
+
+    /// Returns the sheet's constructor document if this is a constructed sheet.
+    pub fn constructor_document(&self) -> Option<&Document> {
+        self.constructor_document.as_deref()
+    }
+
+    /// Records that the `adoptedStyleSheets` of `adopter` now contain this sheet.
+    pub fn add_adopter(&self, adopter: StyleSheetListOwner) {
+        self.adopters.borrow_mut().push(adopter);
+    }
+
+    /// Records that the `adoptedStyleSheets` of `adopter` no longer contain this
+    /// sheet.
+    pub fn remove_adopter(&self, adopter: &StyleSheetListOwner) {
+        let mut adopters = self.adopters.borrow_mut();
+        if let Some(index) = adopters.iter().position(|a| a == adopter) {
+            adopters.remove(index);
+        }
+    }
+
+    /// Invalidates the styles of the document or shadow root the owner of this
+    /// sheet is in, or of the ones that adopted it.
+    pub fn invalidate_stylesheets(&self) {
+        if let Some(ref owner) = self.owner {
+            stylesheets_owner_from_node(owner.upcast::<Node>()).invalidate_stylesheets();
+        }
+        for adopter in self.adopters.borrow().iter() {
+            adopter.invalidate_stylesheets();
+        }
+    }
+
+    /// Replace the rules of `sheet`, which is either this sheet or one it imports, by
+    /// parsing `css`.
+    pub fn update_from_str(&self, sheet: &StyleStyleSheet, css: &str) {
+        let url_data = sheet.contents.url_data.read().clone();
+        let loader = self.loader();
+        // Constructed sheets have no loader, so their @import rules are dropped.
+        let global = self.global();
+        let error_reporter = match loader {
+            Some(_) => None,
+            None => Some(ImportRuleReporter { global: &global }),
+        };
+        StyleStyleSheet::update_from_str(
+            sheet,
+            css,
+            url_data,
+            loader
+                .as_ref()
+                .map(|loader| loader as &dyn StyleStylesheetLoader),
+            error_reporter
+                .as_ref()
+                .map(|reporter| reporter as &dyn ParseErrorReporter),
+            0,
+        );
+        // The rules of the CSSOM rule list no longer exist.
+        self.rulelist.set(None);
+        self.invalidate_stylesheets();
+    }
+
+    /// Whether the rules of this sheet can't be changed right now.
+    fn check_modification_allowed(&self) -> ErrorResult {
+        if self.disallow_modification.get() {
+            return Err(Error::NotAllowed);
+        }
+        Ok(())
+    }
+}
+
+/// Warns on the console about the `@import` rules dropped from the text of a
+/// constructed sheet.
+///
+/// <https://drafts.csswg.org/cssom/#dom-cssstylesheet-replace>
+struct ImportRuleReporter<'a> {
+    global: &'a GlobalScope,
+}
+
+impl<'a> ParseErrorReporter for ImportRuleReporter<'a> {
+    fn report_error(&self, url: &ServoUrl, location: SourceLocation, error: ContextualParseError) {
+        if let ContextualParseError::InvalidRule(
+            _,
+            ParseError {
+                kind: ParseErrorKind::Custom(StyleParseErrorKind::UnexpectedImportRule),
+                ..
+            },
+        ) = error
+        {
+            Console::Warn(
+                self.global,
+                vec![DOMString::from(format!(
+                    "{}:{}:{} @import rules are not allowed in constructed stylesheets",
+                    url, location.line, location.column,
+                ))],
+            );
+        }
+    }
 }
 
 impl CSSStyleSheetMethods for CSSStyleSheet {
@@ -117,8 +327,10 @@ impl CSSStyleSheetMethods for CSSStyleSheet {
         if !self.origin_clean.get() {
             return Err(Error::Security);
         }
-        self.rulelist()
-            .insert_rule(&rule, index, /* nested */ false)
+        self.check_modification_allowed()?;
+        self.rulelist().insert_rule(
+            &rule, index, /* nested */ false, /* in_style_rule */ false,
+        )
     }
 
     // https://drafts.csswg.org/cssom/#dom-cssstylesheet-deleterule
@@ -126,6 +338,57 @@ impl CSSStyleSheetMethods for CSSStyleSheet {
         if !self.origin_clean.get() {
             return Err(Error::Security);
         }
+        self.check_modification_allowed()?;
         self.rulelist().remove_rule(index)
     }
+
+    // https://drafts.csswg.org/cssom/#dom-cssstylesheet-replace
+    fn Replace(&self, text: USVString) -> Rc<Promise> {
+        // Step 1.
+        let promise = Promise::new(&self.global());
+
+        // Step 2.
+        if self.constructor_document.is_none() || self.disallow_modification.get() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+
+        // Step 3.
+        self.disallow_modification.set(true);
+
+        // Step 4. @import rules are dropped while parsing, with a warning,
+        // since constructed sheets don't have a loader.
+        let sheet = Trusted::new(self);
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        let global = self.global();
+        let _ = global
+            .as_window()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(
+                task!(replace_stylesheet_rules: move || {
+                    let sheet = sheet.root();
+                    sheet.update_from_str(sheet.style_stylesheet(), &text.0);
+                    sheet.disallow_modification.set(false);
+                    trusted_promise.root().resolve_native(&sheet);
+                }),
+                &global,
+            );
+
+        // Step 5.
+        promise
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-cssstylesheet-replacesync
+    fn ReplaceSync(&self, text: USVString) -> ErrorResult {
+        // Step 1.
+        if self.constructor_document.is_none() {
+            return Err(Error::NotAllowed);
+        }
+        self.check_modification_allowed()?;
+
+        // Steps 2 to 4.
+        self.update_from_str(self.style_stylesheet(), &text.0);
+        Ok(())
+    }
 }
@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animation::Animation;
+use crate::dom::animationeffect::{AnimationEffect, Timing};
+use crate::dom::animationtimeline::AnimationTimeline;
+use crate::dom::bindings::codegen::Bindings::CSSTransitionBinding;
+use crate::dom::bindings::codegen::Bindings::CSSTransitionBinding::CSSTransitionMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::keyframeeffect::KeyframeEffect;
+use crate::dom::node::document_from_node;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+// https://drafts.csswg.org/css-transitions-2/#the-CSSTransition-interface
+#[dom_struct]
+pub struct CSSTransition {
+    animation: Animation,
+    /// The property this transition animates.
+    transition_property: DOMString,
+}
+
+impl CSSTransition {
+    fn new_inherited(
+        window: &Window,
+        timeline: &AnimationTimeline,
+        transition_property: DOMString,
+    ) -> CSSTransition {
+        CSSTransition {
+            animation: Animation::new_inherited(window, Some(timeline)),
+            transition_property: transition_property,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        target: &Element,
+        transition_property: DOMString,
+    ) -> DomRoot<CSSTransition> {
+        let timeline = document_from_node(target).Timeline();
+        let transition = reflect_dom_object(
+            Box::new(CSSTransition::new_inherited(
+                window,
+                timeline.upcast(),
+                transition_property,
+            )),
+            window,
+            CSSTransitionBinding::Wrap,
+        );
+        let effect = KeyframeEffect::new(window, Some(target), Timing::default());
+        transition
+            .upcast::<Animation>()
+            .set_up(Some(effect.upcast::<AnimationEffect>()));
+        transition
+    }
+
+    pub fn transition_property(&self) -> &str {
+        &self.transition_property
+    }
+}
+
+impl CSSTransitionMethods for CSSTransition {
+    // https://drafts.csswg.org/css-transitions-2/#dom-csstransition-transitionproperty
+    fn TransitionProperty(&self) -> DOMString {
+        self.transition_property.clone()
+    }
+}
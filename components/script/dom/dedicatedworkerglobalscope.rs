@@ -419,6 +419,12 @@ impl DedicatedWorkerGlobalScope {
                         parent_sender,
                         CommonScriptMsg::CollectReports,
                     );
+
+                // The worker's event loop is destroyed,
+                // tell the constellation to drop the sender to our broadcast router.
+                scope
+                    .upcast::<GlobalScope>()
+                    .remove_broadcast_channel_router();
             })
             .expect("Thread spawning failed");
     }
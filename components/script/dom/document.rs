@@ -5,10 +5,12 @@
 use crate::compartments::{AlreadyInCompartment, InCompartment};
 use crate::document_loader::{DocumentLoader, LoadType};
 use crate::dom::activation::{synthetic_click_activation, ActivationSource};
+use crate::dom::animation::Animation;
 use crate::dom::attr::Attr;
 use crate::dom::beforeunloadevent::BeforeUnloadEvent;
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding::AnimationPlayState;
 use crate::dom::bindings::codegen::Bindings::BeforeUnloadEventBinding::BeforeUnloadEventBinding::BeforeUnloadEventMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::ElementCreationOptions;
@@ -17,9 +19,11 @@ use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
 };
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElementBinding::HTMLIFrameElementMethods;
+use crate::dom::bindings::codegen::Bindings::IntersectionObserverBinding::IntersectionObserverMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::NodeFilterBinding::NodeFilter;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMethods;
 use crate::dom::bindings::codegen::Bindings::TouchBinding::TouchMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
@@ -41,11 +45,14 @@ use crate::dom::cdatasection::CDATASection;
 use crate::dom::closeevent::CloseEvent;
 use crate::dom::comment::Comment;
 use crate::dom::compositionevent::CompositionEvent;
+use crate::dom::cssanimation::CSSAnimation;
 use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::csstransition::CSSTransition;
 use crate::dom::customelementregistry::CustomElementDefinition;
 use crate::dom::customevent::CustomEvent;
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::documentorshadowroot::{DocumentOrShadowRoot, StyleSheetInDocument};
+use crate::dom::documenttimeline::DocumentTimeline;
 use crate::dom::documenttype::DocumentType;
 use crate::dom::domimplementation::DOMImplementation;
 use crate::dom::element::CustomElementCreationMode;
@@ -56,6 +63,7 @@ use crate::dom::errorevent::ErrorEvent;
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventDefault, EventStatus};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::focusevent::FocusEvent;
+use crate::dom::fontfaceset::FontFaceSet;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
@@ -63,6 +71,7 @@ use crate::dom::htmlareaelement::HTMLAreaElement;
 use crate::dom::htmlbaseelement::HTMLBaseElement;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
+use crate::dom::htmldialogelement::HTMLDialogElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlembedelement::HTMLEmbedElement;
 use crate::dom::htmlformelement::{FormControl, FormControlElementHelpers, HTMLFormElement};
@@ -70,8 +79,11 @@ use crate::dom::htmlheadelement::HTMLHeadElement;
 use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
 use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::htmlinputelement::HTMLInputElement;
 use crate::dom::htmlscriptelement::{HTMLScriptElement, ScriptResult};
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
 use crate::dom::htmltitleelement::HTMLTitleElement;
+use crate::dom::intersectionobserver::IntersectionObserver;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::location::Location;
 use crate::dom::messageevent::MessageEvent;
@@ -86,6 +98,8 @@ use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::progressevent::ProgressEvent;
 use crate::dom::promise::Promise;
 use crate::dom::range::Range;
+use crate::dom::resizeobserver::ResizeObserver;
+use crate::dom::selection::Selection;
 use crate::dom::servoparser::ServoParser;
 use crate::dom::shadowroot::ShadowRoot;
 use crate::dom::storageevent::StorageEvent;
@@ -102,6 +116,7 @@ use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::wheelevent::WheelEvent;
 use crate::dom::window::{ReflowReason, Window};
 use crate::dom::windowproxy::WindowProxy;
+use crate::editing::{self, Command, EditHistory};
 use crate::fetch::FetchCanceller;
 use crate::script_runtime::JSContext;
 use crate::script_runtime::{CommonScriptMsg, ScriptThreadEventCategory};
@@ -109,11 +124,12 @@ use crate::script_thread::{MainThreadScriptMsg, ScriptThread};
 use crate::stylesheet_set::StylesheetSetRef;
 use crate::task::TaskBox;
 use crate::task_source::{TaskSource, TaskSourceName};
+use crate::textinput::CMD_OR_CONTROL;
 use crate::timers::OneshotTimerCallback;
 use canvas_traits::webgl::{self, SwapChainId, WebGLContextId, WebGLMsg};
 use content_security_policy::{self as csp, CspList};
 use cookie::Cookie;
-use devtools_traits::ScriptToDevtoolsControlMsg;
+use devtools_traits::{ScriptToDevtoolsControlMsg, StorageArea, StorageChange};
 use dom_struct::dom_struct;
 use embedder_traits::EmbedderMsg;
 use encoding_rs::{Encoding, UTF_8};
@@ -122,7 +138,9 @@ use html5ever::{LocalName, Namespace, QualName};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcSender};
 use js::jsapi::{JSObject, JSRuntime};
-use keyboard_types::{Code, Key, KeyState};
+use js::jsval::JSVal;
+use js::rust::HandleValue;
+use keyboard_types::{Code, Key, KeyState, ShortcutMatcher};
 use metrics::{
     InteractiveFlag, InteractiveMetrics, InteractiveWindow, ProfilerMetadataFactory,
     ProgressiveWebMetric,
@@ -133,7 +151,7 @@ use net_traits::pub_domains::is_pub_domain;
 use net_traits::request::RequestBuilder;
 use net_traits::response::HttpsState;
 use net_traits::CookieSource::NonHTTP;
-use net_traits::CoreResourceMsg::{GetCookiesForUrl, SetCookiesForUrl};
+use net_traits::CoreResourceMsg::{GetCookiesDataForUrl, GetCookiesForUrl, SetCookiesForUrl};
 use net_traits::{FetchResponseMsg, IpcSend, ReferrerPolicy};
 use num_traits::ToPrimitive;
 use percent_encoding::percent_decode;
@@ -142,6 +160,7 @@ use profile_traits::time::{TimerMetadata, TimerMetadataFrameType, TimerMetadataR
 use ref_filter_map::ref_filter_map;
 use ref_slice::ref_slice;
 use script_layout_interface::message::{Msg, ReflowGoal};
+use script_layout_interface::rpc::RunningAnimationKind;
 use script_traits::{AnimationState, DocumentActivity, MouseButton, MouseEventType};
 use script_traits::{
     MsDuration, ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta,
@@ -153,17 +172,22 @@ use servo_media::{ClientContextId, ServoMedia};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use std::borrow::Cow;
 use std::cell::{Cell, Ref, RefMut};
+use std::cmp::Ordering;
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::default::Default;
 use std::mem;
-use std::ptr::NonNull;
+use std::ptr::{self, NonNull};
 use std::rc::Rc;
 use std::time::{Duration, Instant};
+use style::animation::KeyframeEffectSample;
 use style::attr::AttrValue;
 use style::context::QuirksMode;
+use style::element_state::ElementState;
+use style::invalidation::element::relative_selector;
 use style::invalidation::element::restyle_hints::RestyleHint;
 use style::media_queries::{Device, MediaType};
+use style::properties_and_values::PropertyRegistration;
 use style::selector_parser::{RestyleDamage, Snapshot};
 use style::shared_lock::SharedRwLock as StyleSharedRwLock;
 use style::str::{split_html_space_chars, str_join};
@@ -266,6 +290,10 @@ pub struct Document {
     /// List of stylesheets associated with nodes in this document. |None| if the list needs to be refreshed.
     stylesheets: DomRefCell<DocumentStylesheetSet<StyleSheetInDocument>>,
     stylesheet_list: MutNullableDom<StyleSheetList>,
+    /// The source text of the style sheets of this document, kept for the devtools
+    /// when they are enabled, since the parsed rules don't preserve it.
+    #[ignore_malloc_size_of = "Arc"]
+    stylesheet_sources: DomRefCell<Vec<(Arc<Stylesheet>, String)>>,
     ready_state: Cell<DocumentReadyState>,
     /// Whether the DOMContentLoaded event has already been dispatched.
     domcontentloaded_dispatched: Cell<bool>,
@@ -299,6 +327,23 @@ pub struct Document {
     /// Tracking this is not necessary for correctness. Instead, it is an optimization to avoid
     /// sending needless `ChangeRunningAnimationsState` messages to the compositor.
     running_animation_callbacks: Cell<bool>,
+    /// <https://drafts.csswg.org/web-animations/#the-documents-default-timeline>
+    timeline: MutNullableDom<DocumentTimeline>,
+    /// The current time of the timelines of this document, in milliseconds.
+    /// It only advances with animation frames.
+    animation_timeline_time: Cell<Option<f64>>,
+    /// The animations created by script which may still affect this document.
+    animations: DomRefCell<Vec<Dom<Animation>>>,
+    /// The elements which keyframe effects were last applied to.
+    animated_elements: DomRefCell<Vec<Dom<Element>>>,
+    /// The objects exposing the CSS animations run by layout to script.
+    css_animations: DomRefCell<Vec<Dom<CSSAnimation>>>,
+    /// The objects exposing the CSS transitions run by layout to script.
+    css_transitions: DomRefCell<Vec<Dom<CSSTransition>>>,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontfacesource-fonts>
+    fonts: MutNullableDom<FontFaceSet>,
+    /// The names of the custom properties registered with `CSS.registerProperty()`.
+    registered_custom_properties: DomRefCell<HashSet<Atom>>,
     /// Tracks all outstanding loads related to this document.
     loader: DomRefCell<DocumentLoader>,
     /// The current active HTML parser, to allow resuming after interruptions.
@@ -389,6 +434,10 @@ pub struct Document {
     delayed_tasks: DomRefCell<Vec<Box<dyn TaskBox>>>,
     /// https://html.spec.whatwg.org/multipage/#completely-loaded
     completely_loaded: Cell<bool>,
+    /// https://html.spec.whatwg.org/multipage/#ready-for-post-load-tasks
+    ready_for_post_load_tasks: Cell<bool>,
+    /// https://html.spec.whatwg.org/multipage/#print-when-loaded
+    print_when_loaded: Cell<bool>,
     /// Set of shadow roots connected to the document tree.
     shadow_roots: DomRefCell<HashSet<Dom<ShadowRoot>>>,
     /// Whether any of the shadow roots need the stylesheets flushed.
@@ -404,6 +453,24 @@ pub struct Document {
     /// https://html.spec.whatwg.org/multipage/#concept-document-csp-list
     #[ignore_malloc_size_of = "Defined in rust-content-security-policy"]
     csp_list: DomRefCell<Option<CspList>>,
+    /// The intersection observers with targets to compute the intersections of.
+    /// https://w3c.github.io/IntersectionObserver/#document-intersectionobservers
+    intersection_observers: DomRefCell<Vec<Dom<IntersectionObserver>>>,
+    /// https://w3c.github.io/IntersectionObserver/#document-intersectionobservertaskqueued
+    intersection_observer_task_queued: Cell<bool>,
+    /// https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer
+    lazy_load_intersection_observer: MutNullableDom<IntersectionObserver>,
+    /// The resize observers with targets to observe.
+    /// https://drafts.csswg.org/resize-observer/#document-resizeobservers-slot
+    resize_observers: DomRefCell<Vec<Dom<ResizeObserver>>>,
+    /// https://w3c.github.io/selection-api/#dfn-selection
+    selection: MutNullableDom<Selection>,
+    /// Whether the user is selecting text by dragging the mouse.
+    selecting_with_mouse: Cell<bool>,
+    /// The changes that editing commands made to the editable content of this document.
+    edit_history: DomRefCell<EditHistory>,
+    /// https://fullscreen.spec.whatwg.org/#top-layer
+    top_layer: DomRefCell<Vec<Dom<Element>>>,
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -856,6 +923,39 @@ impl Document {
 
         self.upcast::<EventTarget>()
             .fire_event(atom!("readystatechange"));
+
+        if state == DocumentReadyState::Complete {
+            self.fonts_environment_changed();
+        }
+    }
+
+    /// Lets the font face set of this document know that it may no longer be
+    /// waiting for the document or layout to load fonts.
+    pub fn fonts_environment_changed(&self) {
+        if let Some(fonts) = self.fonts.get() {
+            fonts.environment_changed();
+        }
+    }
+
+    /// <https://drafts.css-houdini.org/css-properties-values-api/#the-registerproperty-function>
+    ///
+    /// Adds `registration` to the registered property set of this document and
+    /// tells layout about it, failing if a property with the same name has
+    /// already been registered.
+    pub fn register_custom_property(&self, registration: PropertyRegistration) -> ErrorResult {
+        if !self
+            .registered_custom_properties
+            .borrow_mut()
+            .insert(registration.name.clone())
+        {
+            return Err(Error::InvalidModification);
+        }
+        let _ = self
+            .window
+            .layout_chan()
+            .send(Msg::RegisterProperty(registration));
+        self.invalidate_stylesheets();
+        Ok(())
     }
 
     /// Return whether scripting is enabled or not
@@ -884,7 +984,7 @@ impl Document {
 
     /// Reassign the focus context to the element that last requested focus during this
     /// transaction, or none if no elements requested it.
-    pub fn commit_focus_transaction(&self, focus_type: FocusType) {
+    pub fn commit_focus_transaction(&self, focus_type: FocusType, initiator: FocusInitiator) {
         if self.focused == self.possibly_focused.get().as_deref() {
             return;
         }
@@ -904,6 +1004,13 @@ impl Document {
 
         if let Some(ref elem) = self.focused.get() {
             elem.set_focus_state(true);
+            // https://drafts.csswg.org/selectors-4/#the-focus-visible-pseudo
+            //
+            // Clicking an element only indicates focus if it takes keyboard
+            // input, any other way of focusing always does.
+            elem.set_focus_visible_state(
+                initiator != FocusInitiator::Pointer || elem.input_method_type().is_some(),
+            );
             let node = elem.upcast::<Node>();
             // FIXME: pass appropriate relatedTarget
             self.fire_focus_event(FocusEventType::Focus, node, None);
@@ -917,6 +1024,36 @@ impl Document {
             if let Some(kind) = elem.input_method_type() {
                 self.send_to_embedder(EmbedderMsg::ShowIME(kind));
             }
+
+            if let Some(host) = elem.downcast::<HTMLElement>() {
+                if host.is_editing_host() {
+                    editing::place_caret(host);
+                }
+            }
+        }
+    }
+
+    /// Drops the focus of the focused element, which is being removed from the
+    /// children of `parent`, including the focus-within state of its former
+    /// ancestors. No blur event is fired.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#focus-fixup-rule>
+    pub fn unfocus_removed_element(&self, parent: &Node) {
+        let elem = match self.focused.take() {
+            Some(elem) => elem,
+            None => return,
+        };
+        elem.set_focus_state(false);
+        for ancestor in parent
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .filter_map(DomRoot::downcast::<Element>)
+        {
+            ancestor.set_state(ElementState::IN_FOCUS_WITHIN_STATE, false);
+        }
+
+        // Notify the embedder to hide the input method.
+        if elem.input_method_type().is_some() {
+            self.send_to_embedder(EmbedderMsg::HideIME);
         }
     }
 
@@ -952,7 +1089,7 @@ impl Document {
     pub fn handle_mouse_event(
         &self,
         js_runtime: *mut JSRuntime,
-        _button: MouseButton,
+        button: MouseButton,
         client_point: Point2D<f32>,
         mouse_event_type: MouseEventType,
         node_address: Option<UntrustedNodeAddress>,
@@ -979,6 +1116,11 @@ impl Document {
 
         let node = el.upcast::<Node>();
         debug!("{} on {:?}", mouse_event_type_string, node.debug_str());
+        // Inert nodes can't be interacted with.
+        // https://html.spec.whatwg.org/multipage/#inert
+        if self.is_inert(node) {
+            return;
+        }
         // Prevent click event if form control element is disabled.
         if let MouseEventType::Click = mouse_event_type {
             if el.click_event_filter_by_disabled_state() {
@@ -1026,7 +1168,10 @@ impl Document {
                 }
 
                 let target = node.upcast();
-                event.fire(target);
+                let status = event.fire(target);
+                if let (MouseButton::Left, EventStatus::NotCanceled) = (button, status) {
+                    self.begin_mouse_selection(&el, client_point);
+                }
             },
             MouseEventType::MouseUp => {
                 if let Some(a) = activatable {
@@ -1035,11 +1180,14 @@ impl Document {
 
                 let target = node.upcast();
                 event.fire(target);
+                if let MouseButton::Left = button {
+                    self.selecting_with_mouse.set(false);
+                }
             },
         }
 
         if let MouseEventType::Click = mouse_event_type {
-            self.commit_focus_transaction(FocusType::Element);
+            self.commit_focus_transaction(FocusType::Element, FocusInitiator::Pointer);
             self.maybe_fire_dblclick(client_point, node, pressed_mouse_buttons);
         }
 
@@ -1047,6 +1195,68 @@ impl Document {
             .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
     }
 
+    /// Returns the text node and offset of the caret position closest to `client_point`.
+    fn caret_position_from_point(
+        &self,
+        client_point: Point2D<f32>,
+    ) -> Option<(DomRoot<Text>, u32)> {
+        let viewport_origin = self.window.current_viewport().origin;
+        let point = Point2D::new(
+            client_point.x + viewport_origin.x.to_f32_px(),
+            client_point.y + viewport_origin.y.to_f32_px(),
+        );
+        self.window.caret_position_query(point)
+    }
+
+    /// Starts selecting text with the mouse at the caret position closest to `client_point`,
+    /// unless the `selectstart` event gets canceled.
+    fn begin_mouse_selection(&self, target: &Element, client_point: Point2D<f32>) {
+        // Text controls manage the selection of their own value.
+        if target.is::<HTMLInputElement>() || target.is::<HTMLTextAreaElement>() {
+            return;
+        }
+        let selection = match self.GetSelection() {
+            Some(selection) => selection,
+            None => return,
+        };
+        let (text, offset) = match self.caret_position_from_point(client_point) {
+            Some(caret_position) => caret_position,
+            None => {
+                selection.RemoveAllRanges();
+                return;
+            },
+        };
+
+        // https://w3c.github.io/selection-api/#selectstart-event
+        let event = Event::new(
+            self.window.upcast(),
+            atom!("selectstart"),
+            EventBubbles::Bubbles,
+            EventCancelable::Cancelable,
+        );
+        event.set_trusted(true);
+        if event.fire(text.upcast()) == EventStatus::Canceled {
+            return;
+        }
+
+        let _ = selection.Collapse(Some(text.upcast()), offset);
+        self.selecting_with_mouse.set(true);
+    }
+
+    /// Extends the selection being made with the mouse to the caret position closest to
+    /// `client_point`.
+    fn extend_mouse_selection(&self, client_point: Point2D<f32>) {
+        let selection = match self.GetSelection() {
+            Some(selection) => selection,
+            None => return,
+        };
+        if let Some((text, offset)) = self.caret_position_from_point(client_point) {
+            let _ = selection.Extend(text.upcast(), offset);
+            self.window
+                .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+        }
+    }
+
     fn maybe_fire_dblclick(
         &self,
         click_pos: Point2D<f32>,
@@ -1181,6 +1391,10 @@ impl Document {
             pressed_mouse_buttons,
         );
 
+        if self.selecting_with_mouse.get() {
+            self.extend_mouse_selection(client_point);
+        }
+
         // Nothing more to do here, mousemove is sent,
         // and the element under the mouse hasn't changed.
         if maybe_new_target == prev_mouse_over_target.get() {
@@ -1473,6 +1687,33 @@ impl Document {
             let msg = EmbedderMsg::Keyboard(keyboard_event.clone());
             self.send_to_embedder(msg);
 
+            // Copy the selected text of the page, unless a text control handles the shortcut
+            // for its own selection.
+            let in_text_control =
+                target.is::<HTMLInputElement>() || target.is::<HTMLTextAreaElement>();
+            if keyboard_event.state == KeyState::Down && !in_text_control {
+                ShortcutMatcher::new(
+                    KeyState::Down,
+                    keyboard_event.key.clone(),
+                    keyboard_event.modifiers,
+                )
+                .shortcut(CMD_OR_CONTROL, 'C', || {
+                    let selection = self.GetSelection().filter(|s| !s.IsCollapsed());
+                    if let Some(selection) = selection {
+                        let contents = String::from(selection.Stringifier());
+                        self.send_to_embedder(EmbedderMsg::SetClipboardContents(contents));
+                    }
+                })
+                .otherwise(|| ());
+            }
+
+            // https://html.spec.whatwg.org/multipage/#canceling-dialogs
+            if keyboard_event.key == Key::Escape && keyboard_event.state == KeyState::Down {
+                if let Some(dialog) = self.topmost_modal_dialog() {
+                    dialog.cancel();
+                }
+            }
+
             // This behavior is unspecced
             // We are supposed to dispatch synthetic click activation for Space and/or Return,
             // however *when* we do it is up to us.
@@ -1619,7 +1860,14 @@ impl Document {
             .push((ident, Some(callback)));
 
         // TODO: Should tick animation only when document is visible
+        self.request_animation_tick();
 
+        ident
+    }
+
+    /// Makes sure that `run_the_animation_frame_callbacks` runs at the next
+    /// animation frame.
+    pub fn request_animation_tick(&self) {
         // If we are running 'fake' animation frames, we unconditionally
         // set up a one-shot timer for script to execute the rAF callbacks.
         if self.is_faking_animation_frames() {
@@ -1641,8 +1889,6 @@ impl Document {
                 ScriptMsg::ChangeRunningAnimationsState(AnimationState::AnimationCallbacksPresent);
             self.window().send_to_constellation(event);
         }
-
-        ident
     }
 
     /// <https://html.spec.whatwg.org/multipage/#dom-window-cancelanimationframe>
@@ -1653,6 +1899,174 @@ impl Document {
         }
     }
 
+    /// The current time of the timelines of this document, or `None` if it
+    /// is not fully active.
+    pub fn animation_timeline_time(&self) -> Option<f64> {
+        if !self.is_fully_active() {
+            return None;
+        }
+        if self.animation_timeline_time.get().is_none() {
+            self.animation_timeline_time
+                .set(Some(*self.global().performance().Now()));
+        }
+        self.animation_timeline_time.get()
+    }
+
+    /// Starts updating `animation` at each animation frame, until it is idle.
+    pub fn register_animation(&self, animation: &Animation) {
+        let mut animations = self.animations.borrow_mut();
+        if !animations.iter().any(|a| ptr::eq(&**a, animation)) {
+            animations.push(Dom::from_ref(animation));
+        }
+    }
+
+    fn has_active_animations(&self) -> bool {
+        self.animations.borrow().iter().any(|a| a.is_active())
+    }
+
+    /// Hands the current samples of the keyframe effects of the animations of
+    /// this document to their targets, for the next restyle.
+    pub fn update_animation_samples(&self) {
+        let animations: Vec<_> = self
+            .animations
+            .borrow()
+            .iter()
+            .map(|a| DomRoot::from_ref(&**a))
+            .collect();
+
+        let mut samples: Vec<(DomRoot<Element>, Vec<KeyframeEffectSample>)> = vec![];
+        for (target, sample) in animations.iter().filter_map(|a| a.sample()) {
+            match samples.iter_mut().find(|(t, _)| *t == target) {
+                Some((_, target_samples)) => target_samples.push(sample),
+                None => samples.push((target, vec![sample])),
+            }
+        }
+
+        let previous = mem::replace(
+            &mut *self.animated_elements.borrow_mut(),
+            samples.iter().map(|(t, _)| Dom::from_ref(&**t)).collect(),
+        );
+        for element in previous {
+            if !samples.iter().any(|(t, _)| ptr::eq(&**t, &*element)) {
+                element.set_keyframe_effect_samples(vec![]);
+            }
+        }
+        for (target, target_samples) in samples {
+            target.set_keyframe_effect_samples(target_samples);
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#update-animations-and-send-events>
+    fn update_animations(&self) {
+        let animations: Vec<_> = self
+            .animations
+            .borrow()
+            .iter()
+            .map(|a| DomRoot::from_ref(&**a))
+            .collect();
+        for animation in &animations {
+            animation.tick();
+        }
+        self.update_animation_samples();
+
+        let mut events = vec![];
+        for animation in &animations {
+            events.extend(
+                animation
+                    .take_queued_events()
+                    .into_iter()
+                    .map(|event| (animation, event)),
+            );
+        }
+        events.sort_by(|a, b| {
+            a.1.timeline_time
+                .partial_cmp(&b.1.timeline_time)
+                .unwrap_or(Ordering::Equal)
+        });
+        for (animation, event) in events {
+            animation.dispatch_queued_event(event);
+        }
+
+        self.animations.borrow_mut().retain(|animation| {
+            animation.play_state() != AnimationPlayState::Idle || animation.is_active()
+        });
+    }
+
+    /// The animations of this document, in composite order: CSS transitions,
+    /// then CSS animations, then the animations created by script.
+    pub fn get_animations(&self) -> Vec<DomRoot<Animation>> {
+        let mut transitions: Vec<DomRoot<CSSTransition>> = vec![];
+        let mut css_animations: Vec<DomRoot<CSSAnimation>> = vec![];
+        for (element, running) in self.window.running_animations_query() {
+            match running.kind {
+                RunningAnimationKind::Transition(ref property) => {
+                    let existing = self
+                        .css_transitions
+                        .borrow()
+                        .iter()
+                        .find(|t| {
+                            t.transition_property() == &**property &&
+                                t.upcast::<Animation>().target().as_ref() == Some(&element)
+                        })
+                        .map(|t| DomRoot::from_ref(&**t));
+                    let transition = existing.unwrap_or_else(|| {
+                        CSSTransition::new(&self.window, &element, DOMString::from(&**property))
+                    });
+                    transition
+                        .upcast::<Animation>()
+                        .update_from_running_animation(&running);
+                    transitions.push(transition);
+                },
+                RunningAnimationKind::Animation(ref name) => {
+                    let existing = self
+                        .css_animations
+                        .borrow()
+                        .iter()
+                        .find(|a| {
+                            a.animation_name() == &**name &&
+                                a.upcast::<Animation>().target().as_ref() == Some(&element)
+                        })
+                        .map(|a| DomRoot::from_ref(&**a));
+                    let animation = existing.unwrap_or_else(|| {
+                        CSSAnimation::new(&self.window, &element, DOMString::from(&**name))
+                    });
+                    animation
+                        .upcast::<Animation>()
+                        .update_from_running_animation(&running);
+                    css_animations.push(animation);
+                },
+            }
+        }
+        *self.css_transitions.borrow_mut() =
+            transitions.iter().map(|t| Dom::from_ref(&**t)).collect();
+        *self.css_animations.borrow_mut() =
+            css_animations.iter().map(|a| Dom::from_ref(&**a)).collect();
+
+        transitions
+            .iter()
+            .map(|t| DomRoot::from_ref(t.upcast::<Animation>()))
+            .chain(
+                css_animations
+                    .iter()
+                    .map(|a| DomRoot::from_ref(a.upcast::<Animation>())),
+            )
+            .chain(
+                self.animations
+                    .borrow()
+                    .iter()
+                    .filter(|a| {
+                        // CSS animations and transitions were listed above.
+                        !a.is::<CSSAnimation>() &&
+                            !a.is::<CSSTransition>() &&
+                            a.is_relevant() &&
+                            a.target()
+                                .map_or(false, |target| target.upcast::<Node>().is_connected())
+                    })
+                    .map(|a| DomRoot::from_ref(&**a)),
+            )
+            .collect()
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#run-the-animation-frame-callbacks>
     pub fn run_the_animation_frame_callbacks(&self) {
         rooted_vec!(let mut animation_frame_list);
@@ -1665,6 +2079,9 @@ impl Document {
         let was_faking_animation_frames = self.is_faking_animation_frames();
         let timing = self.global().performance().Now();
 
+        self.animation_timeline_time.set(Some(*timing));
+        self.update_animations();
+
         for (_, callback) in animation_frame_list.drain(..) {
             if let Some(callback) = callback {
                 callback.call(self, *timing);
@@ -1702,7 +2119,10 @@ impl Document {
         // animation frame is one in which the callback did not mutate the DOM—that is, an
         // animation frame that wasn't actually used for animation.)
         let is_empty = self.animation_frame_list.borrow().is_empty();
-        if is_empty || (!was_faking_animation_frames && self.is_faking_animation_frames()) {
+        let has_active_animations = self.has_active_animations();
+        if (is_empty && !has_active_animations) ||
+            (!was_faking_animation_frames && self.is_faking_animation_frames())
+        {
             if is_empty {
                 // If the current animation frame list in the DOM instance is empty,
                 // we can reuse the original `Vec<T>` that we put on the stack to
@@ -1718,6 +2138,9 @@ impl Document {
             );
             self.window().send_to_constellation(event);
         }
+        if has_active_animations && self.is_faking_animation_frames() {
+            self.request_animation_tick();
+        }
 
         // Update the counter of spurious animation frames.
         if spurious {
@@ -2042,11 +2465,33 @@ impl Document {
         // Step 9.
         // TODO: pending application cache download process tasks.
 
-        // Step 10.
-        // TODO: printing steps.
+        // Step 10-11.
+        // Queued after the load and pageshow events, so that the document is printed once
+        // those have been handled.
+        let document = Trusted::new(self);
+        self.window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(
+                task!(ready_for_post_load_tasks: move || {
+                    let document = document.root();
+                    let window = document.window();
+                    if !window.is_alive() {
+                        return;
+                    }
+
+                    // Step 10.
+                    if document.print_when_loaded.get() {
+                        document.print_when_loaded.set(false);
+                        window.run_the_printing_steps();
+                    }
 
-        // Step 11.
-        // TODO: ready for post-load tasks.
+                    // Step 11.
+                    document.ready_for_post_load_tasks.set(true);
+                }),
+                self.window.upcast(),
+            )
+            .unwrap();
 
         // Step 12: completely loaded.
         // https://html.spec.whatwg.org/multipage/#completely-loaded
@@ -2418,6 +2863,23 @@ impl Document {
         !self.has_browsing_context || !url_has_network_scheme(&self.url())
     }
 
+    /// The cookies that script can see for this document, serialized with their attributes and
+    /// keyed by name. Used to tell the devtools how `document.cookie` changed them.
+    fn cookies_by_name(&self) -> HashMap<String, String> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let _ = self
+            .window
+            .upcast::<GlobalScope>()
+            .resource_threads()
+            .send(GetCookiesDataForUrl(self.url(), sender, NonHTTP));
+        receiver
+            .recv()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|cookie| (cookie.name().to_owned(), cookie.to_string()))
+            .collect()
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#look-up-a-custom-element-definition>
     pub fn lookup_custom_element_definition(
         &self,
@@ -2495,6 +2957,110 @@ impl Document {
         }
     }
 
+    pub fn add_intersection_observer(&self, observer: &IntersectionObserver) {
+        register_observer(&self.intersection_observers, observer);
+    }
+
+    pub fn remove_intersection_observer(&self, observer: &IntersectionObserver) {
+        unregister_observer(&self.intersection_observers, observer);
+    }
+
+    pub fn intersection_observers(&self) -> Vec<DomRoot<IntersectionObserver>> {
+        self.intersection_observers
+            .borrow()
+            .iter()
+            .map(|observer| DomRoot::from_ref(&**observer))
+            .collect()
+    }
+
+    pub fn intersection_observer_task_queued(&self) -> bool {
+        self.intersection_observer_task_queued.get()
+    }
+
+    pub fn set_intersection_observer_task_queued(&self, queued: bool) {
+        self.intersection_observer_task_queued.set(queued);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#start-intersection-observing-a-lazy-loading-element>
+    pub fn start_intersection_observing_lazy_loading_element(&self, element: &Element) {
+        self.lazy_load_intersection_observer
+            .or_init(|| IntersectionObserver::new_lazy_load_observer(&self.window))
+            .Observe(element);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#stop-intersection-observing-a-lazy-loading-element>
+    pub fn stop_intersection_observing_lazy_loading_element(&self, element: &Element) {
+        if let Some(observer) = self.lazy_load_intersection_observer.get() {
+            observer.Unobserve(element);
+        }
+    }
+
+    pub fn add_resize_observer(&self, observer: &ResizeObserver) {
+        register_observer(&self.resize_observers, observer);
+    }
+
+    pub fn remove_resize_observer(&self, observer: &ResizeObserver) {
+        unregister_observer(&self.resize_observers, observer);
+    }
+
+    pub fn resize_observers(&self) -> Vec<DomRoot<ResizeObserver>> {
+        self.resize_observers
+            .borrow()
+            .iter()
+            .map(|observer| DomRoot::from_ref(&**observer))
+            .collect()
+    }
+
+    pub fn edit_history(&self) -> &DomRefCell<EditHistory> {
+        &self.edit_history
+    }
+
+    /// Adds the element to the top layer, moving it to the top if it already is in it.
+    /// https://fullscreen.spec.whatwg.org/#top-layer-add
+    pub fn add_to_top_layer(&self, element: &Element) {
+        self.remove_from_top_layer(element);
+        self.top_layer.borrow_mut().push(Dom::from_ref(element));
+    }
+
+    pub fn remove_from_top_layer(&self, element: &Element) {
+        self.top_layer
+            .borrow_mut()
+            .retain(|in_top_layer| **in_top_layer != *element);
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#topmost-dialog-node
+    pub fn topmost_modal_dialog(&self) -> Option<DomRoot<HTMLDialogElement>> {
+        self.top_layer
+            .borrow()
+            .iter()
+            .rev()
+            .filter(|element| {
+                element
+                    .state()
+                    .contains(ElementState::IN_MODAL_DIALOG_STATE)
+            })
+            .filter_map(|element| {
+                DomRoot::downcast::<HTMLDialogElement>(DomRoot::from_ref(&**element))
+            })
+            .next()
+    }
+
+    /// Returns whether the node is inert because a modal dialog is blocking the document.
+    /// https://html.spec.whatwg.org/multipage/#blocked-by-a-modal-dialog
+    pub fn is_inert(&self, node: &Node) -> bool {
+        self.topmost_modal_dialog().map_or(false, |dialog| {
+            !dialog.upcast::<Node>().is_inclusive_ancestor_of(node)
+        })
+    }
+
+    /// Tells layout which text to highlight as selected, after the selection or the document
+    /// changed.
+    pub fn update_selection_highlight(&self) {
+        if let Some(selection) = self.selection.get() {
+            selection.update_highlight();
+        }
+    }
+
     pub fn add_dirty_canvas(&self, context: &WebGLRenderingContext) {
         self.dirty_webgl_contexts
             .borrow_mut()
@@ -2674,6 +3240,25 @@ fn url_has_network_scheme(url: &ServoUrl) -> bool {
     }
 }
 
+/// Keeps `observer` alive and updated by the document, once.
+fn register_observer<T: DomObject + PartialEq>(observers: &DomRefCell<Vec<Dom<T>>>, observer: &T) {
+    let mut observers = observers.borrow_mut();
+    if !observers.iter().any(|registered| **registered == *observer) {
+        observers.push(Dom::from_ref(observer));
+    }
+}
+
+/// Stops updating `observer`. Observers unregister themselves once they have no targets
+/// left: they have nothing to report then, and shouldn't be kept alive by the document.
+fn unregister_observer<T: DomObject + PartialEq>(
+    observers: &DomRefCell<Vec<Dom<T>>>,
+    observer: &T,
+) {
+    observers
+        .borrow_mut()
+        .retain(|registered| **registered != *observer);
+}
+
 #[derive(Clone, Copy, Eq, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum HasBrowsingContext {
     No,
@@ -2765,6 +3350,7 @@ impl Document {
             },
             stylesheets: DomRefCell::new(DocumentStylesheetSet::new()),
             stylesheet_list: MutNullableDom::new(None),
+            stylesheet_sources: DomRefCell::new(vec![]),
             ready_state: Cell::new(ready_state),
             domcontentloaded_dispatched: Cell::new(domcontentloaded_dispatched),
             possibly_focused: Default::default(),
@@ -2779,6 +3365,14 @@ impl Document {
             animation_frame_ident: Cell::new(0),
             animation_frame_list: DomRefCell::new(vec![]),
             running_animation_callbacks: Cell::new(false),
+            timeline: Default::default(),
+            animation_timeline_time: Cell::new(None),
+            animations: DomRefCell::new(vec![]),
+            animated_elements: DomRefCell::new(vec![]),
+            css_animations: DomRefCell::new(vec![]),
+            css_transitions: DomRefCell::new(vec![]),
+            fonts: Default::default(),
+            registered_custom_properties: Default::default(),
             loader: DomRefCell::new(doc_loader),
             current_parser: Default::default(),
             reflow_timeout: Cell::new(None),
@@ -2819,6 +3413,8 @@ impl Document {
             responsive_images: Default::default(),
             redirect_count: Cell::new(0),
             completely_loaded: Cell::new(false),
+            ready_for_post_load_tasks: Cell::new(false),
+            print_when_loaded: Cell::new(false),
             script_and_layout_blockers: Cell::new(0),
             delayed_tasks: Default::default(),
             shadow_roots: DomRefCell::new(HashSet::new()),
@@ -2826,6 +3422,14 @@ impl Document {
             media_controls: DomRefCell::new(HashMap::new()),
             dirty_webgl_contexts: DomRefCell::new(HashMap::new()),
             csp_list: DomRefCell::new(None),
+            intersection_observers: DomRefCell::new(vec![]),
+            intersection_observer_task_queued: Cell::new(false),
+            lazy_load_intersection_observer: Default::default(),
+            resize_observers: DomRefCell::new(vec![]),
+            selection: Default::default(),
+            selecting_with_mouse: Cell::new(false),
+            edit_history: Default::default(),
+            top_layer: DomRefCell::new(vec![]),
         }
     }
 
@@ -3010,7 +3614,9 @@ impl Document {
         let window_size = self.window().window_size();
         let viewport_size = window_size.initial_viewport;
         let device_pixel_ratio = window_size.device_pixel_ratio;
-        Device::new(MediaType::screen(), viewport_size, device_pixel_ratio)
+        let mut device = Device::new(MediaType::screen(), viewport_size, device_pixel_ratio);
+        device.set_media_environment(window_size.media_environment);
+        device
     }
 
     pub fn salvageable(&self) -> bool {
@@ -3065,6 +3671,7 @@ impl Document {
     }
 
     pub fn element_state_will_change(&self, el: &Element) {
+        self.relative_selectors_will_change(el);
         let mut entry = self.ensure_pending_restyle(el);
         if entry.snapshot.is_none() {
             entry.snapshot = Some(Snapshot::new(el.html_element_in_html_document()));
@@ -3081,6 +3688,7 @@ impl Document {
         // I'm getting rid of the whole hashtable soon anyway, since all it does
         // right now is populate the element restyle data in layout, and we
         // could in theory do it in the DOM I think.
+        self.relative_selectors_will_change(el);
         let mut entry = self.ensure_pending_restyle(el);
         if entry.snapshot.is_none() {
             entry.snapshot = Some(Snapshot::new(el.html_element_in_html_document()));
@@ -3111,6 +3719,27 @@ impl Document {
         }
     }
 
+    /// Invalidates the `:has()` selectors that may have looked at `el`, before
+    /// a state or attribute change on it.
+    fn relative_selectors_will_change(&self, el: &Element) {
+        relative_selector::invalidate_for_element_change(
+            &DomRoot::from_ref(el),
+            |e| e.selector_flags(),
+            |anchor| self.invalidate_relative_selectors(anchor),
+        );
+    }
+
+    /// Marks the relative selectors anchored at `el` as invalidated, so that
+    /// the next restyle looks at the `:has()` selectors that may match it.
+    pub fn invalidate_relative_selectors(&self, el: &Element) {
+        let mut entry = self.ensure_pending_restyle(el);
+        if entry.snapshot.is_none() {
+            entry.snapshot = Some(Snapshot::new(el.html_element_in_html_document()));
+        }
+        let snapshot = entry.snapshot.as_mut().unwrap();
+        snapshot.relative_selectors_invalidated = true;
+    }
+
     pub fn set_referrer_policy(&self, policy: Option<ReferrerPolicy>) {
         self.referrer_policy.set(policy);
     }
@@ -3149,6 +3778,16 @@ impl Document {
         self.ignore_opens_during_unload_counter.get() > 0
     }
 
+    /// https://html.spec.whatwg.org/multipage/#ready-for-post-load-tasks
+    pub fn is_ready_for_post_load_tasks(&self) -> bool {
+        self.ready_for_post_load_tasks.get()
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#print-when-loaded
+    pub fn set_print_when_loaded(&self) {
+        self.print_when_loaded.set(true);
+    }
+
     fn incr_ignore_opens_during_unload_counter(&self) {
         self.ignore_opens_during_unload_counter
             .set(self.ignore_opens_during_unload_counter.get() + 1);
@@ -3341,8 +3980,39 @@ impl Document {
         self.shadow_roots_styles_changed.set(false);
     }
 
+    /// Returns the number of sheets owned by elements of this document, which
+    /// come before the adopted ones.
     pub fn stylesheet_count(&self) -> usize {
-        self.stylesheets.borrow().len()
+        self.stylesheets
+            .borrow()
+            .iter()
+            .filter(|(sheet, _origin)| sheet.owner.is_some())
+            .count()
+    }
+
+    /// Records the source text `text` of `sheet` for the devtools, if they are
+    /// enabled. Sources of sheets that are no longer used are dropped.
+    pub fn set_stylesheet_source(&self, sheet: &Arc<Stylesheet>, text: &str) {
+        if self
+            .window
+            .upcast::<GlobalScope>()
+            .devtools_chan()
+            .is_none()
+        {
+            return;
+        }
+        let mut sources = self.stylesheet_sources.borrow_mut();
+        sources.retain(|&(ref source, _)| !source.is_unique() && !Arc::ptr_eq(source, sheet));
+        sources.push((sheet.clone(), text.to_owned()));
+    }
+
+    /// Returns the source text recorded for `sheet`, if any.
+    pub fn stylesheet_source(&self, sheet: &Arc<Stylesheet>) -> Option<String> {
+        self.stylesheet_sources
+            .borrow()
+            .iter()
+            .find(|&&(ref source, _)| Arc::ptr_eq(source, sheet))
+            .map(|&(_, ref text)| text.clone())
     }
 
     pub fn stylesheet_at(&self, index: usize) -> Option<DomRoot<CSSStyleSheet>> {
@@ -3350,7 +4020,8 @@ impl Document {
 
         stylesheets
             .get(Origin::Author, index)
-            .and_then(|s| s.owner.upcast::<Node>().get_cssom_stylesheet())
+            .and_then(|s| s.owner.as_ref())
+            .and_then(|owner| owner.upcast::<Node>().get_cssom_stylesheet())
     }
 
     /// Add a stylesheet owned by `owner` to the list of document sheets, in the
@@ -3358,15 +4029,10 @@ impl Document {
     #[allow(unrooted_must_root)] // Owner needs to be rooted already necessarily.
     pub fn add_stylesheet(&self, owner: &Element, sheet: Arc<Stylesheet>) {
         let stylesheets = &mut *self.stylesheets.borrow_mut();
-        let insertion_point = stylesheets
-            .iter()
-            .map(|(sheet, _origin)| sheet)
-            .find(|sheet_in_doc| {
-                owner
-                    .upcast::<Node>()
-                    .is_before(sheet_in_doc.owner.upcast())
-            })
-            .cloned();
+        let insertion_point = DocumentOrShadowRoot::stylesheet_insertion_point(
+            owner,
+            stylesheets.iter().map(|(sheet, _origin)| sheet),
+        );
 
         self.window
             .layout_chan()
@@ -3399,6 +4065,28 @@ impl Document {
             StylesheetSetRef::Document(&mut *self.stylesheets.borrow_mut()),
         )
     }
+
+    /// Replace the adopted sheets of this document, `old`, by `new`.
+    pub fn replace_adopted_stylesheets(&self, old: &[Arc<Stylesheet>], new: &[Arc<Stylesheet>]) {
+        let layout_chan = self.window.layout_chan();
+        for sheet in old {
+            layout_chan
+                .send(Msg::RemoveStylesheet(sheet.clone()))
+                .unwrap();
+        }
+        for sheet in new {
+            layout_chan
+                .send(Msg::AddStylesheet(sheet.clone(), None))
+                .unwrap();
+        }
+
+        DocumentOrShadowRoot::replace_adopted_stylesheets(
+            StylesheetSetRef::Document(&mut *self.stylesheets.borrow_mut()),
+            old,
+            new,
+            self.style_shared_lock(),
+        );
+    }
 }
 
 impl Element {
@@ -3438,6 +4126,21 @@ impl DocumentMethods for Document {
         })
     }
 
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    fn AdoptedStyleSheets(&self, cx: JSContext) -> JSVal {
+        self.document_or_shadow_root.adopted_stylesheets(cx)
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    fn SetAdoptedStyleSheets(&self, cx: JSContext, value: HandleValue) -> ErrorResult {
+        self.document_or_shadow_root.set_adopted_stylesheets(
+            cx,
+            value,
+            StyleSheetListOwner::Document(Dom::from_ref(self)),
+            self,
+        )
+    }
+
     // https://dom.spec.whatwg.org/#dom-document-implementation
     fn Implementation(&self) -> DomRoot<DOMImplementation> {
         self.implementation.or_init(|| DOMImplementation::new(self))
@@ -3467,6 +4170,47 @@ impl DocumentMethods for Document {
         false
     }
 
+    // https://w3c.github.io/editing/docs/execCommand/#execcommand()
+    fn ExecCommand(&self, command_id: DOMString, _show_ui: bool, value: DOMString) -> bool {
+        match Command::from_name(&command_id) {
+            Some(command) => editing::exec_command(self, command, value),
+            None => false,
+        }
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandenabled()
+    fn QueryCommandEnabled(&self, command_id: DOMString) -> bool {
+        match Command::from_name(&command_id) {
+            Some(command) => editing::query_command_enabled(self, command),
+            None => false,
+        }
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandindeterm()
+    fn QueryCommandIndeterm(&self, _command_id: DOMString) -> bool {
+        // None of the supported commands have an indeterminacy.
+        false
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandstate()
+    fn QueryCommandState(&self, command_id: DOMString) -> bool {
+        match Command::from_name(&command_id) {
+            Some(command) => editing::query_command_state(self, command),
+            None => false,
+        }
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandsupported()
+    fn QueryCommandSupported(&self, command_id: DOMString) -> bool {
+        Command::from_name(&command_id).is_some()
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandvalue()
+    fn QueryCommandValue(&self, _command_id: DOMString) -> DOMString {
+        // None of the supported commands have a value.
+        DOMString::new()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-domain
     fn Domain(&self) -> DOMString {
         // Step 1.
@@ -4267,11 +5011,38 @@ impl DocumentMethods for Document {
             vec![]
         };
 
-        let _ = self
-            .window
-            .upcast::<GlobalScope>()
+        let global = self.window.upcast::<GlobalScope>();
+        let names: Vec<String> = cookies.iter().map(|c| c.name().to_owned()).collect();
+        // Only ask for the cookies when the devtools storage actor listens for changes.
+        let devtools_chan = global
+            .devtools_chan()
+            .filter(|_| global.devtools_wants_storage_updates());
+        let old_cookies = devtools_chan.map(|_| self.cookies_by_name());
+
+        let _ = global
             .resource_threads()
             .send(SetCookiesForUrl(self.url(), cookies, NonHTTP));
+
+        if let (Some(chan), Some(old_cookies)) = (devtools_chan, old_cookies) {
+            // The resource thread handles our messages in order, so this sees whether the cookie
+            // was added, changed, removed by an expiry date in the past, or rejected.
+            let new_cookies = self.cookies_by_name();
+            for name in names {
+                let change = match (old_cookies.get(&name), new_cookies.get(&name)) {
+                    (None, Some(_)) => StorageChange::Added(name),
+                    (Some(old), Some(new)) if old != new => StorageChange::Changed(name),
+                    (Some(_), None) => StorageChange::Removed(name),
+                    // The cookie was rejected, or nothing changed.
+                    _ => continue,
+                };
+                let _ = chan.send(ScriptToDevtoolsControlMsg::StorageChanged(
+                    global.pipeline_id(),
+                    self.origin.immutable().ascii_serialization(),
+                    StorageArea::Cookies,
+                    change,
+                ));
+            }
+        }
         Ok(())
     }
 
@@ -4424,6 +5195,15 @@ impl DocumentMethods for Document {
         )
     }
 
+    // https://w3c.github.io/selection-api/#dom-document-getselection
+    fn GetSelection(&self) -> Option<DomRoot<Selection>> {
+        if self.has_browsing_context {
+            Some(self.selection.or_init(|| Selection::new(self)))
+        } else {
+            None
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-open
     fn Open(
         &self,
@@ -4655,6 +5435,22 @@ impl DocumentMethods for Document {
         self.exit_fullscreen()
     }
 
+    // https://drafts.csswg.org/web-animations/#dom-document-timeline
+    fn Timeline(&self) -> DomRoot<DocumentTimeline> {
+        self.timeline.or_init(|| DocumentTimeline::new(self, 0.))
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-document-getanimations
+    fn GetAnimations(&self) -> Vec<DomRoot<Animation>> {
+        self.get_animations()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfacesource-fonts
+    fn Fonts(&self) -> DomRoot<FontFaceSet> {
+        self.fonts
+            .or_init(|| FontFaceSet::new(&self.window, Some(self)))
+    }
+
     // check-tidy: no specs after this line
     // Servo only API to get an instance of the controls of a specific
     // media element matching the given id.
@@ -4697,6 +5493,13 @@ pub enum FocusType {
     Parent,  // Focusing a parent element (an iframe)
 }
 
+/// What caused the focus to change.
+#[derive(Clone, Copy, PartialEq)]
+pub enum FocusInitiator {
+    Pointer, // The user clicked or tapped on an element.
+    Other,   // Focus was moved by script or by a nested browsing context.
+}
+
 /// Focus events
 pub enum FocusEventType {
     Focus, // Element gained focus. Doesn't bubble.
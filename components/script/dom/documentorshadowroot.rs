@@ -4,17 +4,26 @@
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeBinding::NodeMethods;
+use crate::dom::bindings::conversions::{ConversionResult, FromJSValConvertible};
+use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::document::Document;
 use crate::dom::element::Element;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlmetaelement::HTMLMetaElement;
 use crate::dom::node::{self, Node, VecPreOrderInsertionHelper};
+use crate::dom::stylesheetlist::StyleSheetListOwner;
 use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
 use crate::stylesheet_set::StylesheetSetRef;
 use euclid::default::Point2D;
-use js::jsapi::JS_GetRuntime;
+use js::jsapi::{Heap, JS_GetRuntime};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::HandleValue;
 use script_layout_interface::message::{NodesFromPointQueryType, QueryMsg};
 use script_traits::UntrustedNodeAddress;
 use servo_arc::Arc;
@@ -32,7 +41,8 @@ use style::stylesheets::{CssRule, Origin, Stylesheet};
 pub struct StyleSheetInDocument {
     #[ignore_malloc_size_of = "Arc"]
     pub sheet: Arc<Stylesheet>,
-    pub owner: Dom<Element>,
+    /// The element owning the sheet, or `None` for a sheet in `adoptedStyleSheets`.
+    pub owner: Option<Dom<Element>>,
 }
 
 impl fmt::Debug for StyleSheetInDocument {
@@ -80,12 +90,20 @@ impl ::style::stylesheets::StylesheetInDocument for StyleSheetInDocument {
 #[derive(JSTraceable, MallocSizeOf)]
 pub struct DocumentOrShadowRoot {
     window: Dom<Window>,
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    adopted_stylesheets: DomRefCell<Vec<Dom<CSSStyleSheet>>>,
+    /// The frozen array returned for `adoptedStyleSheets` until it is set again,
+    /// or undefined if it wasn't created yet.
+    #[ignore_malloc_size_of = "mozjs"]
+    adopted_stylesheets_array: Heap<JSVal>,
 }
 
 impl DocumentOrShadowRoot {
     pub fn new(window: &Window) -> Self {
         Self {
             window: Dom::from_ref(window),
+            adopted_stylesheets: DomRefCell::new(vec![]),
+            adopted_stylesheets_array: Heap::default(),
         }
     }
 
@@ -225,7 +243,7 @@ impl DocumentOrShadowRoot {
             None,
             StyleSheetInDocument {
                 sheet: s.clone(),
-                owner: Dom::from_ref(owner),
+                owner: Some(Dom::from_ref(owner)),
             },
             &guard,
         );
@@ -251,7 +269,7 @@ impl DocumentOrShadowRoot {
 
         let sheet = StyleSheetInDocument {
             sheet,
-            owner: Dom::from_ref(owner),
+            owner: Some(Dom::from_ref(owner)),
         };
 
         let guard = style_shared_lock.read();
@@ -266,6 +284,119 @@ impl DocumentOrShadowRoot {
         }
     }
 
+    /// Returns the first sheet of `stylesheets` that should come after a sheet
+    /// owned by `owner`, which is the first sheet with a later owner or the
+    /// first adopted sheet, since adopted sheets come after all the others.
+    pub fn stylesheet_insertion_point<'a>(
+        owner: &Element,
+        mut stylesheets: impl Iterator<Item = &'a StyleSheetInDocument>,
+    ) -> Option<StyleSheetInDocument> {
+        stylesheets
+            .find(|sheet_in_doc| match sheet_in_doc.owner {
+                Some(ref sheet_owner) => owner.upcast::<Node>().is_before(sheet_owner.upcast()),
+                None => true,
+            })
+            .cloned()
+    }
+
+    /// Replace the adopted sheets at the end of `stylesheets`, `old`, by `new`.
+    pub fn replace_adopted_stylesheets(
+        mut stylesheets: StylesheetSetRef<StyleSheetInDocument>,
+        old: &[Arc<Stylesheet>],
+        new: &[Arc<Stylesheet>],
+        style_shared_lock: &StyleSharedRwLock,
+    ) {
+        let guard = style_shared_lock.read();
+        for sheet in old {
+            stylesheets.remove_stylesheet(
+                None,
+                StyleSheetInDocument {
+                    sheet: sheet.clone(),
+                    owner: None,
+                },
+                &guard,
+            );
+        }
+        for sheet in new {
+            stylesheets.append_stylesheet(
+                None,
+                StyleSheetInDocument {
+                    sheet: sheet.clone(),
+                    owner: None,
+                },
+                &guard,
+            );
+        }
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    //
+    // The attribute is a FrozenArray, so the same array is returned until it is
+    // set again, and the array can't be modified in place.
+    pub fn adopted_stylesheets(&self, cx: JSContext) -> JSVal {
+        if self.adopted_stylesheets_array.get().is_undefined() {
+            let sheets: Vec<DomRoot<CSSStyleSheet>> = self
+                .adopted_stylesheets
+                .borrow()
+                .iter()
+                .map(|sheet| DomRoot::from_ref(&**sheet))
+                .collect();
+            self.adopted_stylesheets_array
+                .set(to_frozen_array(&sheets, cx));
+        }
+        self.adopted_stylesheets_array.get()
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    #[allow(unrooted_must_root, unsafe_code)]
+    pub fn set_adopted_stylesheets(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        owner: StyleSheetListOwner,
+        document: &Document,
+    ) -> ErrorResult {
+        let sheets = match unsafe { Vec::<DomRoot<CSSStyleSheet>>::from_jsval(*cx, value, ()) } {
+            Ok(ConversionResult::Success(sheets)) => sheets,
+            Ok(ConversionResult::Failure(message)) => {
+                return Err(Error::Type(message.into_owned()));
+            },
+            Err(()) => return Err(Error::JSFailed),
+        };
+
+        // Only sheets constructed in this document can be adopted in its tree.
+        if sheets
+            .iter()
+            .any(|sheet| sheet.constructor_document() != Some(document))
+        {
+            return Err(Error::NotAllowed);
+        }
+
+        let old_sheets: Vec<DomRoot<CSSStyleSheet>> = self
+            .adopted_stylesheets
+            .borrow()
+            .iter()
+            .map(|sheet| DomRoot::from_ref(&**sheet))
+            .collect();
+        *self.adopted_stylesheets.borrow_mut() =
+            sheets.iter().map(|sheet| Dom::from_ref(&**sheet)).collect();
+        self.adopted_stylesheets_array.set(UndefinedValue());
+
+        for sheet in &old_sheets {
+            sheet.remove_adopter(&owner);
+        }
+        for sheet in &sheets {
+            sheet.add_adopter(owner.clone());
+        }
+
+        owner.replace_adopted_stylesheets(
+            &style_stylesheets(&old_sheets),
+            &style_stylesheets(&sheets),
+        );
+        owner.invalidate_stylesheets();
+        Ok(())
+    }
+
     /// Remove any existing association between the provided id and any elements in this document.
     pub fn unregister_named_element(
         &self,
@@ -310,3 +441,18 @@ impl DocumentOrShadowRoot {
         elements.insert_pre_order(element, &root);
     }
 }
+
+/// Returns the style sheets of `sheets`, keeping only the last occurrence of
+/// a sheet adopted more than once, which is the one that determines its
+/// position in the cascade.
+fn style_stylesheets(sheets: &[DomRoot<CSSStyleSheet>]) -> Vec<Arc<Stylesheet>> {
+    let mut result: Vec<Arc<Stylesheet>> = vec![];
+    for sheet in sheets.iter().rev() {
+        let sheet = sheet.style_stylesheet_arc();
+        if !result.iter().any(|s| Arc::ptr_eq(s, sheet)) {
+            result.push(sheet.clone());
+        }
+    }
+    result.reverse();
+    result
+}
@@ -0,0 +1,43 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animationtimeline::AnimationTimeline;
+use crate::dom::bindings::codegen::Bindings::DocumentTimelineBinding;
+use crate::dom::bindings::codegen::Bindings::DocumentTimelineBinding::DocumentTimelineOptions;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::document::Document;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+// https://drafts.csswg.org/web-animations/#the-documenttimeline-interface
+#[dom_struct]
+pub struct DocumentTimeline {
+    timeline: AnimationTimeline,
+}
+
+impl DocumentTimeline {
+    fn new_inherited(document: &Document, origin_time: f64) -> DocumentTimeline {
+        DocumentTimeline {
+            timeline: AnimationTimeline::new_inherited(document, origin_time),
+        }
+    }
+
+    pub fn new(document: &Document, origin_time: f64) -> DomRoot<DocumentTimeline> {
+        reflect_dom_object(
+            Box::new(DocumentTimeline::new_inherited(document, origin_time)),
+            document.window(),
+            DocumentTimelineBinding::Wrap,
+        )
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-documenttimeline-documenttimeline
+    pub fn Constructor(
+        window: &Window,
+        options: &DocumentTimelineOptions,
+    ) -> DomRoot<DocumentTimeline> {
+        DocumentTimeline::new(&window.Document(), *options.originTime)
+    }
+}
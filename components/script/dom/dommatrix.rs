@@ -12,8 +12,11 @@ use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::serializable::Serializable;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
 use crate::dom::dommatrixreadonly::{
-    dommatrixinit_to_matrix, entries_to_matrix, transform_to_matrix, DOMMatrixReadOnly,
+    deserialize_matrix, dommatrixinit_to_matrix, entries_to_matrix, transform_to_matrix,
+    DOMMatrixReadOnly,
 };
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::window::Window;
@@ -102,6 +105,22 @@ impl DOMMatrix {
     }
 }
 
+impl Serializable for DOMMatrix {
+    // https://drafts.fxtf.org/geometry-1/#structured-serialization
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()> {
+        self.parent.serialize(writer)
+    }
+
+    // https://drafts.fxtf.org/geometry-1/#structured-serialization
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()> {
+        let (is_2d, matrix) = deserialize_matrix(reader)?;
+        Ok(Self::new(owner, is_2d, matrix))
+    }
+}
+
 impl DOMMatrixMethods for DOMMatrix {
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m11
     fn M11(&self) -> f64 {
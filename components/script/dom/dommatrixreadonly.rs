@@ -14,6 +14,8 @@ use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::serializable::Serializable;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
 use crate::dom::dommatrix::DOMMatrix;
 use crate::dom::dompoint::DOMPoint;
 use crate::dom::globalscope::GlobalScope;
@@ -421,6 +423,34 @@ impl DOMMatrixReadOnly {
     }
 }
 
+impl Serializable for DOMMatrixReadOnly {
+    // https://drafts.fxtf.org/geometry-1/#structured-serialization
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()> {
+        writer.write_u32_pair(self.is2D.get() as u32, 0);
+        for entry in self.matrix.borrow().to_row_major_array().iter() {
+            writer.write_f64(*entry);
+        }
+        Ok(())
+    }
+
+    // https://drafts.fxtf.org/geometry-1/#structured-serialization
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()> {
+        let (is_2d, matrix) = deserialize_matrix(reader)?;
+        Ok(Self::new(owner, is_2d, matrix))
+    }
+}
+
+/// Reads back the state written by the serialization steps of `DOMMatrixReadOnly`.
+pub fn deserialize_matrix(reader: &StructuredCloneReader) -> Result<(bool, Transform3D<f64>), ()> {
+    let (is_2d, _) = reader.read_u32_pair();
+    let entries: Vec<f64> = (0..16).map(|_| reader.read_f64()).collect();
+    let (_, matrix) = entries_to_matrix(&entries).map_err(|_| ())?;
+    Ok((is_2d != 0, matrix))
+}
+
 impl DOMMatrixReadOnlyMethods for DOMMatrixReadOnly {
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m11
     fn M11(&self) -> f64 {
@@ -9,7 +9,9 @@ use crate::dom::bindings::codegen::Bindings::DOMPointReadOnlyBinding::DOMPointRe
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::dompointreadonly::{DOMPointReadOnly, DOMPointWriteMethods};
+use crate::dom::bindings::serializable::Serializable;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
+use crate::dom::dompointreadonly::{deserialize_point, DOMPointReadOnly, DOMPointWriteMethods};
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 
@@ -50,6 +52,22 @@ impl DOMPoint {
     }
 }
 
+impl Serializable for DOMPoint {
+    // https://drafts.fxtf.org/geometry/#structured-serialization
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()> {
+        self.point.serialize(writer)
+    }
+
+    // https://drafts.fxtf.org/geometry/#structured-serialization
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()> {
+        let (x, y, z, w) = deserialize_point(reader);
+        Ok(Self::new(owner, x, y, z, w))
+    }
+}
+
 impl DOMPointMethods for DOMPoint {
     // https://dev.w3.org/fxtf/geometry/Overview.html#dom-dompointreadonly-x
     fn X(&self) -> f64 {
@@ -9,6 +9,8 @@ use crate::dom::bindings::codegen::Bindings::DOMPointReadOnlyBinding::{
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::serializable::Serializable;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 use std::cell::Cell;
@@ -58,6 +60,36 @@ impl DOMPointReadOnly {
     }
 }
 
+impl Serializable for DOMPointReadOnly {
+    // https://drafts.fxtf.org/geometry/#structured-serialization
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()> {
+        writer.write_f64(self.x.get());
+        writer.write_f64(self.y.get());
+        writer.write_f64(self.z.get());
+        writer.write_f64(self.w.get());
+        Ok(())
+    }
+
+    // https://drafts.fxtf.org/geometry/#structured-serialization
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()> {
+        let (x, y, z, w) = deserialize_point(reader);
+        Ok(Self::new(owner, x, y, z, w))
+    }
+}
+
+/// Reads back the coordinates written by the serialization steps of `DOMPointReadOnly`.
+pub fn deserialize_point(reader: &StructuredCloneReader) -> (f64, f64, f64, f64) {
+    (
+        reader.read_f64(),
+        reader.read_f64(),
+        reader.read_f64(),
+        reader.read_f64(),
+    )
+}
+
 impl DOMPointReadOnlyMethods for DOMPointReadOnly {
     // https://dev.w3.org/fxtf/geometry/Overview.html#dom-dompointreadonly-x
     fn X(&self) -> f64 {
@@ -8,6 +8,8 @@ use crate::dom::bindings::codegen::Bindings::DOMRectReadOnlyBinding::DOMRectInit
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::serializable::Serializable;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
 use crate::dom::dompoint::DOMPoint;
 use crate::dom::domrect::DOMRect;
 use crate::dom::globalscope::GlobalScope;
@@ -93,6 +95,30 @@ impl DOMQuad {
     }
 }
 
+impl Serializable for DOMQuad {
+    // https://drafts.fxtf.org/geometry/#structured-serialization
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()> {
+        self.p1.serialize(writer)?;
+        self.p2.serialize(writer)?;
+        self.p3.serialize(writer)?;
+        self.p4.serialize(writer)
+    }
+
+    // https://drafts.fxtf.org/geometry/#structured-serialization
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()> {
+        Ok(DOMQuad::new(
+            owner,
+            &*DOMPoint::deserialize(owner, reader)?,
+            &*DOMPoint::deserialize(owner, reader)?,
+            &*DOMPoint::deserialize(owner, reader)?,
+            &*DOMPoint::deserialize(owner, reader)?,
+        ))
+    }
+}
+
 impl DOMQuadMethods for DOMQuad {
     // https://drafts.fxtf.org/geometry/#dom-domquad-p1
     fn P1(&self) -> DomRoot<DOMPoint> {
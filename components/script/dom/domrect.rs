@@ -8,7 +8,9 @@ use crate::dom::bindings::codegen::Bindings::DOMRectReadOnlyBinding::DOMRectRead
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::domrectreadonly::DOMRectReadOnly;
+use crate::dom::bindings::serializable::Serializable;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
+use crate::dom::domrectreadonly::{deserialize_rect, DOMRectReadOnly};
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 
@@ -43,6 +45,22 @@ impl DOMRect {
     }
 }
 
+impl Serializable for DOMRect {
+    // https://drafts.fxtf.org/geometry/#structured-serialization
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()> {
+        self.rect.serialize(writer)
+    }
+
+    // https://drafts.fxtf.org/geometry/#structured-serialization
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()> {
+        let (x, y, width, height) = deserialize_rect(reader);
+        Ok(Self::new(owner, x, y, width, height))
+    }
+}
+
 impl DOMRectMethods for DOMRect {
     // https://drafts.fxtf.org/geometry/#dom-domrect-x
     fn X(&self) -> f64 {
@@ -8,6 +8,8 @@ use crate::dom::bindings::codegen::Bindings::DOMRectReadOnlyBinding::{
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::serializable::Serializable;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 use std::cell::Cell;
@@ -73,6 +75,36 @@ impl DOMRectReadOnly {
     }
 }
 
+impl Serializable for DOMRectReadOnly {
+    // https://drafts.fxtf.org/geometry/#structured-serialization
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()> {
+        writer.write_f64(self.x.get());
+        writer.write_f64(self.y.get());
+        writer.write_f64(self.width.get());
+        writer.write_f64(self.height.get());
+        Ok(())
+    }
+
+    // https://drafts.fxtf.org/geometry/#structured-serialization
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()> {
+        let (x, y, width, height) = deserialize_rect(reader);
+        Ok(Self::new(owner, x, y, width, height))
+    }
+}
+
+/// Reads back the dimensions written by the serialization steps of `DOMRectReadOnly`.
+pub fn deserialize_rect(reader: &StructuredCloneReader) -> (f64, f64, f64, f64) {
+    (
+        reader.read_f64(),
+        reader.read_f64(),
+        reader.read_f64(),
+        reader.read_f64(),
+    )
+}
+
 impl DOMRectReadOnlyMethods for DOMRectReadOnly {
     // https://drafts.fxtf.org/geometry/#dom-domrectreadonly-x
     fn X(&self) -> f64 {
@@ -5,8 +5,11 @@
 //! Element nodes.
 
 use crate::dom::activation::Activatable;
+use crate::dom::animation::Animation;
 use crate::dom::attr::{Attr, AttrHelpersForLayout};
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimatableBinding::GetAnimationsOptions;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding::AnimationMethods;
 use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ElementBinding;
@@ -19,6 +22,9 @@ use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootBindin
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
 use crate::dom::bindings::codegen::UnionTypes::NodeOrString;
+use crate::dom::bindings::codegen::UnionTypes::{
+    UnrestrictedDoubleOrKeyframeAnimationOptions, UnrestrictedDoubleOrKeyframeEffectOptions,
+};
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
@@ -69,6 +75,7 @@ use crate::dom::htmltablesectionelement::{
 };
 use crate::dom::htmltemplateelement::HTMLTemplateElement;
 use crate::dom::htmltextareaelement::{HTMLTextAreaElement, LayoutHTMLTextAreaElementHelpers};
+use crate::dom::keyframeeffect::KeyframeEffect;
 use crate::dom::mutationobserver::{Mutation, MutationObserver};
 use crate::dom::namednodemap::NamedNodeMap;
 use crate::dom::node::{document_from_node, window_from_node};
@@ -81,8 +88,10 @@ use crate::dom::servoparser::ServoParser;
 use crate::dom::shadowroot::{IsUserAgentWidget, ShadowRoot};
 use crate::dom::text::Text;
 use crate::dom::validation::Validatable;
+use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::{vtable_for, VirtualMethods};
 use crate::dom::window::ReflowReason;
+use crate::script_runtime::JSContext;
 use crate::script_thread::ScriptThread;
 use crate::stylesheet_loader::StylesheetOwner;
 use crate::task::TaskOnce;
@@ -93,7 +102,7 @@ use html5ever::serialize::SerializeOpts;
 use html5ever::serialize::TraversalScope;
 use html5ever::serialize::TraversalScope::{ChildrenOnly, IncludeNode};
 use html5ever::{LocalName, Namespace, Prefix, QualName};
-use js::jsapi::Heap;
+use js::jsapi::{Heap, JSObject};
 use js::jsval::JSVal;
 use msg::constellation_msg::InputMethodType;
 use net_traits::request::CorsSettings;
@@ -111,13 +120,16 @@ use std::cell::{Cell, Ref, RefMut};
 use std::default::Default;
 use std::fmt;
 use std::mem;
+use std::ptr;
 use std::rc::Rc;
 use std::str::FromStr;
+use style::animation::KeyframeEffectSample;
 use style::applicable_declarations::ApplicableDeclarationBlock;
 use style::attr::{AttrValue, LengthOrPercentageOrAuto};
 use style::context::QuirksMode;
 use style::dom_apis;
 use style::element_state::ElementState;
+use style::invalidation::element::relative_selector;
 use style::invalidation::element::restyle_hints::RestyleHint;
 use style::properties::longhands::{
     self, background_image, border_spacing, font_family, font_size,
@@ -131,6 +143,7 @@ use style::selector_parser::{
     NonTSPseudoClass, PseudoElement, RestyleDamage, SelectorImpl, SelectorParser,
 };
 use style::shared_lock::{Locked, SharedRwLock};
+use style::stylesheets::layer_rule::LayerOrder;
 use style::thread_state;
 use style::values::generics::NonNegative;
 use style::values::{computed, specified, CSSFloat};
@@ -167,6 +180,10 @@ pub struct Element {
     #[ignore_malloc_size_of = "bitflags defined in rust-selectors"]
     selector_flags: Cell<ElementSelectorFlags>,
     rare_data: DomRefCell<Option<Box<ElementRareData>>>,
+    /// The samples of the script-created keyframe effects targeting this
+    /// element, computed by the document at each animation frame.
+    #[ignore_malloc_size_of = "Arc"]
+    keyframe_effect_samples: DomRefCell<Vec<KeyframeEffectSample>>,
 }
 
 impl fmt::Debug for Element {
@@ -280,9 +297,12 @@ impl Element {
             style_attribute: DomRefCell::new(None),
             attr_list: Default::default(),
             class_list: Default::default(),
-            state: Cell::new(state),
+            // Elements are defined unless they turn out to be custom elements
+            // which haven't been upgraded yet.
+            state: Cell::new(state | ElementState::IN_DEFINED_STATE),
             selector_flags: Cell::new(ElementSelectorFlags::empty()),
             rare_data: Default::default(),
+            keyframe_effect_samples: Default::default(),
         }
     }
 
@@ -326,6 +346,13 @@ impl Element {
 
     pub fn set_custom_element_state(&self, state: CustomElementState) {
         self.ensure_rare_data().custom_element_state = state;
+
+        // https://dom.spec.whatwg.org/#concept-element-defined
+        let defined = match state {
+            CustomElementState::Uncustomized | CustomElementState::Custom => true,
+            CustomElementState::Undefined | CustomElementState::Failed => false,
+        };
+        self.set_state(ElementState::IN_DEFINED_STATE, defined);
     }
 
     pub fn get_custom_element_state(&self) -> CustomElementState {
@@ -601,6 +628,7 @@ pub trait LayoutElementHelpers {
     unsafe fn is_html_element(&self) -> bool;
     fn id_attribute(&self) -> *const Option<Atom>;
     fn style_attribute(&self) -> *const Option<Arc<Locked<PropertyDeclarationBlock>>>;
+    fn keyframe_effect_samples(&self) -> *const Vec<KeyframeEffectSample>;
     fn local_name(&self) -> &LocalName;
     fn namespace(&self) -> &Namespace;
     fn get_lang_for_layout(&self) -> String;
@@ -651,6 +679,7 @@ impl LayoutElementHelpers for LayoutDom<Element> {
                     Importance::Normal,
                 ))),
                 CascadeLevel::PresHints,
+                LayerOrder::root(),
             )
         }
 
@@ -996,6 +1025,15 @@ impl LayoutElementHelpers for LayoutDom<Element> {
         unsafe { (*self.unsafe_get()).style_attribute.borrow_for_layout() }
     }
 
+    #[allow(unsafe_code)]
+    fn keyframe_effect_samples(&self) -> *const Vec<KeyframeEffectSample> {
+        unsafe {
+            (*self.unsafe_get())
+                .keyframe_effect_samples
+                .borrow_for_layout()
+        }
+    }
+
     #[allow(unsafe_code)]
     fn local_name(&self) -> &LocalName {
         unsafe { &(*self.unsafe_get()).local_name }
@@ -1150,6 +1188,25 @@ impl Element {
         &self.style_attribute
     }
 
+    /// Replaces the samples of the keyframe effects targeting this element,
+    /// restyling it if they changed.
+    pub fn set_keyframe_effect_samples(&self, samples: Vec<KeyframeEffectSample>) {
+        let unchanged = {
+            let old = self.keyframe_effect_samples.borrow();
+            old.len() == samples.len() &&
+                old.iter().zip(samples.iter()).all(|(old, new)| {
+                    Arc::ptr_eq(&old.keyframes, &new.keyframes) &&
+                        old.progress == new.progress &&
+                        old.iteration_duration == new.iteration_duration
+                })
+        };
+        if unchanged {
+            return;
+        }
+        *self.keyframe_effect_samples.borrow_mut() = samples;
+        self.upcast::<Node>().dirty(NodeDamage::NodeStyleDamaged);
+    }
+
     pub fn summarize(&self) -> Vec<AttrInfo> {
         self.attrs
             .borrow()
@@ -1269,6 +1326,11 @@ impl Element {
             input.input_type().as_ime_type()
         } else if self.is::<HTMLTextAreaElement>() {
             Some(InputMethodType::Text)
+        } else if self
+            .downcast::<HTMLElement>()
+            .map_or(false, |element| element.is_editing_host())
+        {
+            Some(InputMethodType::Text)
         } else {
             // Other focusable elements that are not input fields.
             None
@@ -1281,9 +1343,19 @@ impl Element {
         }
         // TODO: Check whether the element is being rendered (i.e. not hidden).
         let node = self.upcast::<Node>();
+        // https://html.spec.whatwg.org/multipage/#inert
+        if node.owner_doc().is_inert(node) {
+            return false;
+        }
         if node.get_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE) {
             return true;
         }
+        if self
+            .downcast::<HTMLElement>()
+            .map_or(false, |element| element.is_editing_host())
+        {
+            return true;
+        }
         // https://html.spec.whatwg.org/multipage/#specially-focusable
         match node.type_id() {
             NodeTypeId::Element(ElementTypeId::HTMLElement(
@@ -2633,6 +2705,53 @@ impl ElementMethods for Element {
             .map(|_| ())
     }
 
+    // https://drafts.csswg.org/web-animations/#dom-animatable-animate
+    fn Animate(
+        &self,
+        cx: JSContext,
+        keyframes: *mut JSObject,
+        options: UnrestrictedDoubleOrKeyframeAnimationOptions,
+    ) -> Fallible<DomRoot<Animation>> {
+        let (options, id) = match options {
+            UnrestrictedDoubleOrKeyframeAnimationOptions::UnrestrictedDouble(duration) => (
+                UnrestrictedDoubleOrKeyframeEffectOptions::UnrestrictedDouble(duration),
+                DOMString::new(),
+            ),
+            UnrestrictedDoubleOrKeyframeAnimationOptions::KeyframeAnimationOptions(options) => (
+                UnrestrictedDoubleOrKeyframeEffectOptions::KeyframeEffectOptions(options.parent),
+                options.id,
+            ),
+        };
+
+        // Step 1.
+        let window = window_from_node(self);
+        let effect = KeyframeEffect::Constructor(cx, &window, Some(self), keyframes, options)?;
+
+        // Steps 2-4.
+        let timeline = document_from_node(self).Timeline();
+        let animation = Animation::new(&window, Some(effect.upcast()), Some(timeline.upcast()));
+        animation.SetId(id);
+
+        // Step 5.
+        animation.Play()?;
+        Ok(animation)
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animatable-getanimations
+    fn GetAnimations(&self, options: &GetAnimationsOptions) -> Vec<DomRoot<Animation>> {
+        document_from_node(self)
+            .get_animations()
+            .into_iter()
+            .filter(|animation| match animation.target() {
+                Some(ref target) if options.subtree => self
+                    .upcast::<Node>()
+                    .is_inclusive_ancestor_of(target.upcast()),
+                Some(ref target) => ptr::eq(&**target, self),
+                None => false,
+            })
+            .collect()
+    }
+
     // check-tidy: no specs after this line
     fn EnterFormalActivationState(&self) -> ErrorResult {
         match self.as_maybe_activatable() {
@@ -2860,6 +2979,9 @@ impl VirtualMethods for Element {
         if fullscreen.as_deref() == Some(self) {
             doc.exit_fullscreen();
         }
+        if doc.get_focused_element().as_deref() == Some(self) {
+            doc.unfocus_removed_element(context.parent);
+        }
         if let Some(ref value) = *self.id_attribute.borrow() {
             doc.unregister_named_element(self, value.clone());
         }
@@ -2873,6 +2995,31 @@ impl VirtualMethods for Element {
         }
 
         let flags = self.selector_flags.get();
+        let prev = match *mutation {
+            ChildrenMutation::Append { prev, .. } | ChildrenMutation::Insert { prev, .. } => {
+                Some(prev)
+            },
+            ChildrenMutation::Replace { prev, .. } => prev,
+            _ => None,
+        };
+        let prev_element = prev.and_then(|prev| {
+            prev.inclusively_preceding_siblings()
+                .filter_map(DomRoot::downcast::<Element>)
+                .next()
+        });
+        let prev_element_flags = prev_element
+            .as_ref()
+            .map_or(ElementSelectorFlags::empty(), |e| e.selector_flags());
+        if (flags | prev_element_flags).intersects(ElementSelectorFlags::for_relative_selectors()) {
+            let doc = document_from_node(self);
+            relative_selector::invalidate_for_children_change(
+                &DomRoot::from_ref(self),
+                prev_element.as_ref(),
+                |e| e.selector_flags(),
+                |anchor| doc.invalidate_relative_selectors(anchor),
+            );
+        }
+
         if flags.intersects(ElementSelectorFlags::HAS_SLOW_SELECTOR) {
             // All children of this node need to be restyled when any child changes.
             self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
@@ -2956,6 +3103,10 @@ impl<'a> SelectorsElement for DomRoot<Element> {
             .next()
     }
 
+    fn first_element_child(&self) -> Option<DomRoot<Element>> {
+        self.node.children().filter_map(DomRoot::downcast).next()
+    }
+
     fn attr_matches(
         &self,
         ns: &NamespaceConstraint<&Namespace>,
@@ -3042,7 +3193,17 @@ impl<'a> SelectorsElement for DomRoot<Element> {
             NonTSPseudoClass::Indeterminate |
             NonTSPseudoClass::ReadWrite |
             NonTSPseudoClass::PlaceholderShown |
-            NonTSPseudoClass::Target => Element::state(self).contains(pseudo_class.state_flag()),
+            NonTSPseudoClass::Target |
+            NonTSPseudoClass::FocusVisible |
+            NonTSPseudoClass::FocusWithin |
+            NonTSPseudoClass::Valid |
+            NonTSPseudoClass::Invalid |
+            NonTSPseudoClass::Required |
+            NonTSPseudoClass::Optional |
+            NonTSPseudoClass::InRange |
+            NonTSPseudoClass::OutOfRange |
+            NonTSPseudoClass::Default |
+            NonTSPseudoClass::Defined => Element::state(self).contains(pseudo_class.state_flag()),
         }
     }
 
@@ -3182,6 +3343,20 @@ impl Element {
         element
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#selector-valid>
+    /// <https://html.spec.whatwg.org/multipage/#selector-invalid>
+    pub fn update_validity_state(&self) {
+        let (valid, invalid) = match self.as_maybe_validatable() {
+            Some(validatable) if validatable.is_instance_validatable() => {
+                let valid = validatable.validate(ValidationFlags::all());
+                (valid, !valid)
+            },
+            _ => (false, false),
+        };
+        self.set_state(ElementState::IN_VALID_STATE, valid);
+        self.set_state(ElementState::IN_INVALID_STATE, invalid);
+    }
+
     pub fn click_in_progress(&self) -> bool {
         self.upcast::<Node>().get_flag(NodeFlags::CLICK_IN_PROGRESS)
     }
@@ -3272,6 +3447,10 @@ impl Element {
             .unwrap_or(String::new())
     }
 
+    pub fn selector_flags(&self) -> ElementSelectorFlags {
+        self.selector_flags.get()
+    }
+
     pub fn state(&self) -> ElementState {
         self.state.get()
     }
@@ -3306,9 +3485,26 @@ impl Element {
 
     pub fn set_focus_state(&self, value: bool) {
         self.set_state(ElementState::IN_FOCUS_STATE, value);
+        if !value {
+            self.set_focus_visible_state(false);
+        }
+
+        // https://drafts.csswg.org/selectors-4/#the-focus-within-pseudo
+        for ancestor in self
+            .upcast::<Node>()
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .filter_map(DomRoot::downcast::<Element>)
+        {
+            ancestor.set_state(ElementState::IN_FOCUS_WITHIN_STATE, value);
+        }
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
+    /// <https://drafts.csswg.org/selectors-4/#the-focus-visible-pseudo>
+    pub fn set_focus_visible_state(&self, value: bool) {
+        self.set_state(ElementState::IN_FOCUSRING_STATE, value)
+    }
+
     pub fn hover_state(&self) -> bool {
         self.state.get().contains(ElementState::IN_HOVER_STATE)
     }
@@ -3330,7 +3526,13 @@ impl Element {
     }
 
     pub fn set_disabled_state(&self, value: bool) {
-        self.set_state(ElementState::IN_DISABLED_STATE, value)
+        if self.disabled_state() == value {
+            return;
+        }
+        self.set_state(ElementState::IN_DISABLED_STATE, value);
+        // Disabled form controls are barred from constraint validation, so
+        // this only validates the element when it gets enabled.
+        self.update_validity_state();
     }
 
     pub fn read_write_state(&self) -> bool {
@@ -3362,6 +3564,18 @@ impl Element {
         self.set_state(ElementState::IN_FULLSCREEN_STATE, value)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#selector-required>
+    /// <https://html.spec.whatwg.org/multipage/#selector-optional>
+    pub fn set_required_state(&self, value: bool) {
+        self.set_state(ElementState::IN_REQUIRED_STATE, value);
+        self.set_state(ElementState::IN_OPTIONAL_STATE, !value);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#selector-default>
+    pub fn set_default_state(&self, value: bool) {
+        self.set_state(ElementState::IN_DEFAULT_STATE, value)
+    }
+
     /// <https://dom.spec.whatwg.org/#connected>
     pub fn is_connected(&self) -> bool {
         self.upcast::<Node>().is_connected()
@@ -9,7 +9,9 @@ use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::serializable::Serializable;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
 use crate::dom::blob::{blob_parts_to_bytes, Blob, BlobImpl};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::window::Window;
@@ -111,6 +113,34 @@ impl File {
     }
 }
 
+impl Serializable for File {
+    /// <https://w3c.github.io/FileAPI/#file-section>
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()> {
+        self.blob.serialize(writer)?;
+        writer.write_str(&self.name);
+        writer.write_u64(self.modified as u64);
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/FileAPI/#file-section>
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()> {
+        let bytes = reader.read_bytes();
+        let type_string = reader.read_str();
+        let name = reader.read_str();
+        let modified = reader.read_u64() as i64;
+        Ok(File::new(
+            owner,
+            BlobImpl::new_from_bytes(bytes),
+            DOMString::from(name),
+            Some(modified),
+            &type_string,
+        ))
+    }
+}
+
 impl FileMethods for File {
     // https://w3c.github.io/FileAPI/#dfn-name
     fn Name(&self) -> DOMString {
@@ -6,8 +6,10 @@ use crate::dom::bindings::codegen::Bindings::FileListBinding;
 use crate::dom::bindings::codegen::Bindings::FileListBinding::FileListMethods;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::serializable::Serializable;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
 use crate::dom::file::File;
-use crate::dom::window::Window;
+use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 use std::slice::Iter;
 
@@ -28,12 +30,12 @@ impl FileList {
     }
 
     #[allow(unrooted_must_root)]
-    pub fn new(window: &Window, files: Vec<DomRoot<File>>) -> DomRoot<FileList> {
+    pub fn new(global: &GlobalScope, files: Vec<DomRoot<File>>) -> DomRoot<FileList> {
         reflect_dom_object(
             Box::new(FileList::new_inherited(
                 files.iter().map(|r| Dom::from_ref(&**r)).collect(),
             )),
-            window,
+            global,
             FileListBinding::Wrap,
         )
     }
@@ -43,6 +45,29 @@ impl FileList {
     }
 }
 
+impl Serializable for FileList {
+    /// <https://w3c.github.io/FileAPI/#filelist-section>
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()> {
+        writer.write_u64(self.list.len() as u64);
+        for file in &self.list {
+            file.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/FileAPI/#filelist-section>
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()> {
+        let length = reader.read_u64();
+        let files = (0..length)
+            .map(|_| File::deserialize(owner, reader))
+            .collect::<Result<Vec<_>, ()>>()?;
+        Ok(FileList::new(owner, files))
+    }
+}
+
 impl FileListMethods for FileList {
     // https://w3c.github.io/FileAPI/#dfn-length
     fn Length(&self) -> u32 {
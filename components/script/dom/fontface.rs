@@ -0,0 +1,483 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::FontFaceBinding;
+use crate::dom::bindings::codegen::Bindings::FontFaceBinding::{
+    FontFaceDescriptors, FontFaceLoadStatus, FontFaceMethods,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::StringOrBinaryData;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::fontfaceset::FontFaceSet;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use cssparser::{Parser, ParserInput, UnicodeRange};
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use script_layout_interface::message::{Msg, WebFontSource};
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::ptr;
+use std::rc::Rc;
+use style::font_face::{ComputedFontStretchRange, ComputedFontStyleDescriptor};
+use style::font_face::{ComputedFontWeightRange, EffectiveSources, FontDisplay};
+use style::font_face::{FontStretchRange, FontStyle, FontWeightRange, Source};
+use style::parser::{Parse, ParserContext};
+use style::stylesheets::CssRuleType;
+use style::values::computed::font::FamilyName;
+use style_traits::{ParseError, ParsingMode, ToCss};
+use uuid::Uuid;
+
+/// Where the data of a font face comes from.
+#[derive(JSTraceable, MallocSizeOf)]
+enum FontFaceSource {
+    /// The sources of a `src` descriptor, only fetched when the font face is
+    /// loaded.
+    Sources(#[ignore_malloc_size_of = "Defined in style"] EffectiveSources),
+    /// Font data, loaded as soon as the font face is created, and kept to
+    /// register the font face again.
+    Data(Vec<u8>),
+}
+
+/// The descriptors of a font face that font matching uses.
+pub struct MatchingDescriptors {
+    pub style: ComputedFontStyleDescriptor,
+    pub weight: ComputedFontWeightRange,
+    pub stretch: ComputedFontStretchRange,
+    pub unicode_range: Vec<UnicodeRange>,
+}
+
+impl MatchingDescriptors {
+    /// Whether this font face has glyphs for `character`, as far as its
+    /// `unicode-range` descriptor tells.
+    pub fn covers(&self, character: char) -> bool {
+        let code_point = character as u32;
+        self.unicode_range
+            .iter()
+            .any(|range| range.start <= code_point && code_point <= range.end)
+    }
+}
+
+// https://drafts.csswg.org/css-font-loading/#fontface-interface
+#[dom_struct]
+pub struct FontFace {
+    reflector_: Reflector,
+    family: DomRefCell<DOMString>,
+    style: DomRefCell<DOMString>,
+    weight: DomRefCell<DOMString>,
+    stretch: DomRefCell<DOMString>,
+    unicode_range: DomRefCell<DOMString>,
+    display: DomRefCell<DOMString>,
+    /// The source of this font face, or `None` if it failed to parse.
+    source: DomRefCell<Option<FontFaceSource>>,
+    /// The id that the fonts of this font face are registered with in the font
+    /// cache thread.
+    id: Atom,
+    /// The family that this font face is registered in the font cache thread
+    /// with, if it is.
+    #[ignore_malloc_size_of = "Defined in style"]
+    registered_family: DomRefCell<Option<FamilyName>>,
+    status: Cell<FontFaceLoadStatus>,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontface-fontstatuspromise-slot>
+    #[ignore_malloc_size_of = "Rc"]
+    font_status_promise: Rc<Promise>,
+    /// The font face sets this font face belongs to.
+    font_face_sets: DomRefCell<Vec<Dom<FontFaceSet>>>,
+}
+
+impl FontFace {
+    fn new_inherited(window: &Window) -> FontFace {
+        FontFace {
+            reflector_: Reflector::new(),
+            family: DomRefCell::new(DOMString::new()),
+            style: DomRefCell::new(DOMString::from("normal")),
+            weight: DomRefCell::new(DOMString::from("normal")),
+            stretch: DomRefCell::new(DOMString::from("normal")),
+            unicode_range: DomRefCell::new(DOMString::from("U+0-10FFFF")),
+            display: DomRefCell::new(DOMString::from("auto")),
+            source: DomRefCell::new(None),
+            id: Atom::from(Uuid::new_v4().to_string()),
+            registered_family: DomRefCell::new(None),
+            status: Cell::new(FontFaceLoadStatus::Unloaded),
+            font_status_promise: Promise::new(window.upcast()),
+            font_face_sets: DomRefCell::new(vec![]),
+        }
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-fontface
+    pub fn Constructor(
+        window: &Window,
+        family: DOMString,
+        source: StringOrBinaryData,
+        descriptors: &FontFaceDescriptors,
+    ) -> DomRoot<FontFace> {
+        // Step 1.
+        let font_face = reflect_dom_object(
+            Box::new(FontFace::new_inherited(window)),
+            window,
+            FontFaceBinding::Wrap,
+        );
+
+        // Step 2.
+        let parsed = font_face.parse_descriptors(window, family, descriptors, &source);
+
+        // Step 3.
+        if parsed.is_err() {
+            for value in &[
+                &font_face.family,
+                &font_face.style,
+                &font_face.weight,
+                &font_face.stretch,
+                &font_face.unicode_range,
+                &font_face.display,
+            ] {
+                *value.borrow_mut() = DOMString::new();
+            }
+            *font_face.source.borrow_mut() = None;
+            font_face.status.set(FontFaceLoadStatus::Error);
+            font_face.font_status_promise.reject_error(Error::Syntax);
+            return font_face;
+        }
+
+        // Steps 4-6.
+        let data = match source {
+            StringOrBinaryData::String(_) => return font_face,
+            StringOrBinaryData::ArrayBuffer(buffer) => buffer.to_vec(),
+            StringOrBinaryData::ArrayBufferView(view) => view.to_vec(),
+        };
+        *font_face.source.borrow_mut() = Some(FontFaceSource::Data(data.clone()));
+        font_face.load(WebFontSource::Data(data));
+        font_face
+    }
+
+    fn parse_descriptors(
+        &self,
+        window: &Window,
+        family: DOMString,
+        descriptors: &FontFaceDescriptors,
+        source: &StringOrBinaryData,
+    ) -> ErrorResult {
+        *self.family.borrow_mut() = parse_descriptor::<FamilyName>(window, &family)?;
+        *self.style.borrow_mut() = parse_descriptor::<FontStyle>(window, &descriptors.style)?;
+        *self.weight.borrow_mut() =
+            parse_descriptor::<FontWeightRange>(window, &descriptors.weight)?;
+        *self.stretch.borrow_mut() =
+            parse_descriptor::<FontStretchRange>(window, &descriptors.stretch)?;
+        *self.unicode_range.borrow_mut() =
+            parse_descriptor::<Vec<UnicodeRange>>(window, &descriptors.unicodeRange)?;
+        *self.display.borrow_mut() = parse_descriptor::<FontDisplay>(window, &descriptors.display)?;
+        if let StringOrBinaryData::String(ref source) = *source {
+            let sources = parse(window, source, |context, input| {
+                input.parse_comma_separated(|input| Source::parse(context, input))
+            })?;
+            *self.source.borrow_mut() = Some(FontFaceSource::Sources(
+                EffectiveSources::from_sources(&sources),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn status(&self) -> FontFaceLoadStatus {
+        self.status.get()
+    }
+
+    /// The name of the family of this font face, if it parsed.
+    pub fn family_name(&self) -> Option<FamilyName> {
+        let window = self.global();
+        parse(window.as_window(), &self.family.borrow(), FamilyName::parse).ok()
+    }
+
+    /// The descriptors of this font face that font matching uses, if they
+    /// parsed.
+    pub fn matching_descriptors(&self) -> Option<MatchingDescriptors> {
+        let window = self.global();
+        let window = window.as_window();
+        Some(MatchingDescriptors {
+            style: parse(window, &self.style.borrow(), FontStyle::parse)
+                .ok()?
+                .compute(),
+            weight: parse(window, &self.weight.borrow(), FontWeightRange::parse)
+                .ok()?
+                .compute(),
+            stretch: parse(window, &self.stretch.borrow(), FontStretchRange::parse)
+                .ok()?
+                .compute(),
+            unicode_range: parse(
+                window,
+                &self.unicode_range.borrow(),
+                Vec::<UnicodeRange>::parse,
+            )
+            .ok()?,
+        })
+    }
+
+    pub fn add_to_font_face_set(&self, font_face_set: &FontFaceSet) {
+        self.font_face_sets
+            .borrow_mut()
+            .push(Dom::from_ref(font_face_set));
+        self.update_registration();
+    }
+
+    pub fn remove_from_font_face_set(&self, font_face_set: &FontFaceSet) {
+        self.font_face_sets
+            .borrow_mut()
+            .retain(|set| !ptr::eq(&**set, font_face_set));
+        self.update_registration();
+    }
+
+    /// Registers this font face with the font cache thread, or removes it from
+    /// there, so that it is used for rendering exactly while it is loaded and
+    /// in the font face set of a document.
+    fn update_registration(&self) {
+        // Loads register the font face themselves.
+        if self.status.get() == FontFaceLoadStatus::Loading {
+            return;
+        }
+        let used = self.status.get() == FontFaceLoadStatus::Loaded &&
+            self.font_face_sets
+                .borrow()
+                .iter()
+                .any(|set| set.is_document_set());
+        let registered = self.registered_family.borrow().is_some();
+        if used == registered {
+            return;
+        }
+
+        let window = self.global();
+        let window = window.as_window();
+        if registered {
+            let family = self.registered_family.borrow_mut().take().unwrap();
+            let _ = window
+                .layout_chan()
+                .send(Msg::RemoveFontFace(family, self.id.clone()));
+            window.Document().dirty_all_nodes();
+            return;
+        }
+
+        let family = match self.family_name() {
+            Some(family) => family,
+            None => return,
+        };
+        let source = match *self.source.borrow() {
+            Some(FontFaceSource::Sources(ref sources)) => WebFontSource::Sources(sources.clone()),
+            Some(FontFaceSource::Data(ref data)) => WebFontSource::Data(data.clone()),
+            None => return,
+        };
+        // Layout reflows once the font face is registered again, and the font
+        // face is already known to load.
+        let (sender, _) = ipc::channel().unwrap();
+        *self.registered_family.borrow_mut() = Some(family.clone());
+        let _ =
+            window
+                .layout_chan()
+                .send(Msg::AddFontFace(family, self.id.clone(), source, sender));
+    }
+
+    fn set_status(&self, status: FontFaceLoadStatus) {
+        self.status.set(status);
+        let font_face_sets: Vec<_> = self
+            .font_face_sets
+            .borrow()
+            .iter()
+            .map(|set| DomRoot::from_ref(&**set))
+            .collect();
+        for font_face_set in font_face_sets {
+            font_face_set.font_face_status_changed(self);
+        }
+    }
+
+    /// Registers this font face with the font cache thread, through layout.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontface-load>
+    fn load(&self, source: WebFontSource) {
+        let family = match self.family_name() {
+            Some(family) => family,
+            None => return,
+        };
+        self.set_status(FontFaceLoadStatus::Loading);
+
+        let window = self.global();
+        let window = window.as_window();
+        let (sender, receiver) = ipc::channel().unwrap();
+        let (task_source, canceller) = window
+            .task_manager()
+            .dom_manipulation_task_source_with_canceller();
+        let this = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let this = this.clone();
+                let loaded: bool = message.to().unwrap();
+                let _ = task_source.queue_with_canceller(
+                    task!(font_face_loaded: move || {
+                        this.root().finish_load(loaded);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        *self.registered_family.borrow_mut() = Some(family.clone());
+        let _ =
+            window
+                .layout_chan()
+                .send(Msg::AddFontFace(family, self.id.clone(), source, sender));
+    }
+
+    fn finish_load(&self, loaded: bool) {
+        if loaded {
+            self.set_status(FontFaceLoadStatus::Loaded);
+            self.update_registration();
+            self.font_status_promise
+                .resolve_native(&DomRoot::from_ref(self));
+            return;
+        }
+        *self.registered_family.borrow_mut() = None;
+        self.set_status(FontFaceLoadStatus::Error);
+        let error = match *self.source.borrow() {
+            Some(FontFaceSource::Data(_)) => Error::Syntax,
+            _ => Error::Network,
+        };
+        self.font_status_promise.reject_error(error);
+    }
+}
+
+impl FontFaceMethods for FontFace {
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-family
+    fn Family(&self) -> DOMString {
+        self.family.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-family
+    fn SetFamily(&self, value: DOMString) -> ErrorResult {
+        let window = self.global();
+        *self.family.borrow_mut() = parse_descriptor::<FamilyName>(window.as_window(), &value)?;
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-style
+    fn Style(&self) -> DOMString {
+        self.style.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-style
+    fn SetStyle(&self, value: DOMString) -> ErrorResult {
+        let window = self.global();
+        *self.style.borrow_mut() = parse_descriptor::<FontStyle>(window.as_window(), &value)?;
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-weight
+    fn Weight(&self) -> DOMString {
+        self.weight.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-weight
+    fn SetWeight(&self, value: DOMString) -> ErrorResult {
+        let window = self.global();
+        *self.weight.borrow_mut() =
+            parse_descriptor::<FontWeightRange>(window.as_window(), &value)?;
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-stretch
+    fn Stretch(&self) -> DOMString {
+        self.stretch.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-stretch
+    fn SetStretch(&self, value: DOMString) -> ErrorResult {
+        let window = self.global();
+        *self.stretch.borrow_mut() =
+            parse_descriptor::<FontStretchRange>(window.as_window(), &value)?;
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-unicoderange
+    fn UnicodeRange(&self) -> DOMString {
+        self.unicode_range.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-unicoderange
+    fn SetUnicodeRange(&self, value: DOMString) -> ErrorResult {
+        let window = self.global();
+        *self.unicode_range.borrow_mut() =
+            parse_descriptor::<Vec<UnicodeRange>>(window.as_window(), &value)?;
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-display
+    fn Display(&self) -> DOMString {
+        self.display.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-display
+    fn SetDisplay(&self, value: DOMString) -> ErrorResult {
+        let window = self.global();
+        *self.display.borrow_mut() = parse_descriptor::<FontDisplay>(window.as_window(), &value)?;
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-status
+    fn Status(&self) -> FontFaceLoadStatus {
+        self.status.get()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-load
+    fn Load(&self) -> Rc<Promise> {
+        // Step 2.
+        if self.status.get() == FontFaceLoadStatus::Unloaded {
+            let sources = match *self.source.borrow() {
+                Some(FontFaceSource::Sources(ref sources)) => Some(sources.clone()),
+                _ => None,
+            };
+            // Steps 3-5.
+            if let Some(sources) = sources {
+                self.load(WebFontSource::Sources(sources));
+            }
+        }
+
+        // Step 6.
+        self.font_status_promise.clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-loaded
+    fn Loaded(&self) -> Rc<Promise> {
+        self.font_status_promise.clone()
+    }
+}
+
+fn parse<T, F>(window: &Window, value: &str, parse: F) -> Fallible<T>
+where
+    F: for<'i, 't> FnOnce(&ParserContext, &mut Parser<'i, 't>) -> Result<T, ParseError<'i>>,
+{
+    let document = window.Document();
+    let url = document.url();
+    let context = ParserContext::new_for_cssom(
+        &url,
+        Some(CssRuleType::FontFace),
+        ParsingMode::DEFAULT,
+        document.quirks_mode(),
+        None,
+        None,
+    );
+    let mut input = ParserInput::new(value);
+    let mut parser = Parser::new(&mut input);
+    parser
+        .parse_entirely(|input| parse(&context, input))
+        .map_err(|_| Error::Syntax)
+}
+
+/// Parses the value of a descriptor of a font face, and returns its
+/// serialization.
+fn parse_descriptor<T: Parse + ToCss>(window: &Window, value: &str) -> Fallible<DOMString> {
+    parse(window, value, T::parse).map(|value| DOMString::from(value.to_css_string()))
+}
@@ -0,0 +1,601 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
+    DocumentMethods, DocumentReadyState,
+};
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventInit;
+use crate::dom::bindings::codegen::Bindings::FontFaceBinding::{
+    FontFaceLoadStatus, FontFaceMethods,
+};
+use crate::dom::bindings::codegen::Bindings::FontFaceSetBinding;
+use crate::dom::bindings::codegen::Bindings::FontFaceSetBinding::{
+    FontFaceSetLoadStatus, FontFaceSetMethods,
+};
+use crate::dom::bindings::codegen::Bindings::FontFaceSetLoadEventBinding::FontFaceSetLoadEventInit;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::fontface::{FontFace, MatchingDescriptors};
+use crate::dom::fontfacesetloadevent::FontFaceSetLoadEvent;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::f32;
+use std::mem;
+use std::ptr;
+use std::rc::Rc;
+use style::font_face::ComputedFontStyleDescriptor;
+use style::properties::{parse_one_declaration_into, SourcePropertyDeclaration};
+use style::properties::{PropertyDeclaration, PropertyId, ShorthandId};
+use style::values::computed::font::{FamilyName, SingleFontFamily};
+use style::values::computed::FontWeight as ComputedFontWeight;
+use style::values::generics::font::FontStyle as GenericFontStyle;
+use style::values::specified::font::SpecifiedFontStyle;
+use style::values::specified::{FontFamily, FontStretch, FontStyle, FontWeight};
+use style_traits::ParsingMode;
+
+/// A promise returned by `FontFaceSet.load()`, and the font faces it waits
+/// for.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct PendingLoad {
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    font_faces: Vec<Dom<FontFace>>,
+}
+
+// https://drafts.csswg.org/css-font-loading/#fontfaceset
+#[dom_struct]
+pub struct FontFaceSet {
+    eventtarget: EventTarget,
+    /// The document whose fonts this set holds, if any.
+    document: Option<Dom<Document>>,
+    font_faces: DomRefCell<Vec<Dom<FontFace>>>,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-loadingfonts-slot>
+    loading_fonts: DomRefCell<Vec<Dom<FontFace>>>,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-loadedfonts-slot>
+    loaded_fonts: DomRefCell<Vec<Dom<FontFace>>>,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-failedfonts-slot>
+    failed_fonts: DomRefCell<Vec<Dom<FontFace>>>,
+    status: Cell<FontFaceSetLoadStatus>,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-readypromise-slot>
+    #[ignore_malloc_size_of = "Rc"]
+    ready_promise: DomRefCell<Rc<Promise>>,
+    pending_loads: DomRefCell<Vec<PendingLoad>>,
+}
+
+impl FontFaceSet {
+    fn new_inherited(global: &GlobalScope, document: Option<&Document>) -> FontFaceSet {
+        FontFaceSet {
+            eventtarget: EventTarget::new_inherited(),
+            document: document.map(Dom::from_ref),
+            font_faces: DomRefCell::new(vec![]),
+            loading_fonts: DomRefCell::new(vec![]),
+            loaded_fonts: DomRefCell::new(vec![]),
+            failed_fonts: DomRefCell::new(vec![]),
+            status: Cell::new(FontFaceSetLoadStatus::Loaded),
+            ready_promise: DomRefCell::new(Promise::new(global)),
+            pending_loads: DomRefCell::new(vec![]),
+        }
+    }
+
+    /// Creates the set of fonts of `document`, or a standalone set if `None`.
+    pub fn new(window: &Window, document: Option<&Document>) -> DomRoot<FontFaceSet> {
+        let font_face_set = reflect_dom_object(
+            Box::new(FontFaceSet::new_inherited(window.upcast(), document)),
+            window,
+            FontFaceSetBinding::Wrap,
+        );
+        font_face_set.resolve_ready_promise_if_loaded();
+        font_face_set
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-fontfaceset
+    pub fn Constructor(
+        window: &Window,
+        initial_faces: Vec<DomRoot<FontFace>>,
+    ) -> DomRoot<FontFaceSet> {
+        let font_face_set = FontFaceSet::new(window, None);
+        for font_face in initial_faces {
+            font_face_set.Add(&font_face);
+        }
+        font_face_set
+    }
+
+    /// Whether this is the set of fonts of a document, whose font faces are
+    /// used to render it.
+    pub fn is_document_set(&self) -> bool {
+        self.document.is_some()
+    }
+
+    /// Whether font faces of this set are loading, or layout is loading fonts
+    /// of `@font-face` rules for the document of this set.
+    fn is_loading(&self) -> bool {
+        !self.loading_fonts.borrow().is_empty() ||
+            self.document
+                .as_ref()
+                .map_or(false, |document| document.window().has_pending_web_fonts())
+    }
+
+    /// Whether the document of this set is still loading.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#fontfaceset-pending-on-the-environment>
+    fn is_pending_on_the_environment(&self) -> bool {
+        self.document.as_ref().map_or(false, |document| {
+            document.ReadyState() != DocumentReadyState::Complete
+        })
+    }
+
+    /// Lets this set know that its document finished loading, or that layout
+    /// started or finished loading fonts for it.
+    pub fn environment_changed(&self) {
+        let loading = self.is_loading();
+        match self.status.get() {
+            FontFaceSetLoadStatus::Loaded if loading => self.switch_to_loading(),
+            FontFaceSetLoadStatus::Loading if !loading => self.switch_to_loaded(),
+            _ => self.resolve_ready_promise_if_loaded(),
+        }
+    }
+
+    fn resolve_ready_promise_if_loaded(&self) {
+        if self.is_loading() || self.is_pending_on_the_environment() {
+            return;
+        }
+        let promise = self.ready_promise.borrow().clone();
+        if !promise.is_fulfilled() {
+            promise.resolve_native(&DomRoot::from_ref(self));
+        }
+    }
+
+    /// <https://drafts.csswg.org/css-font-loading/#switch-the-fontfaceset-to-loading>
+    fn switch_to_loading(&self) {
+        // Step 1.
+        self.status.set(FontFaceSetLoadStatus::Loading);
+
+        // Step 2.
+        if self.ready_promise.borrow().is_fulfilled() {
+            *self.ready_promise.borrow_mut() = Promise::new(&self.global());
+        }
+
+        // Step 3.
+        self.queue_load_event(atom!("loading"), vec![]);
+    }
+
+    /// <https://drafts.csswg.org/css-font-loading/#switch-the-fontfaceset-to-loaded>
+    ///
+    /// The fonts of `@font-face` rules have no `FontFace` objects yet, so the
+    /// events only list the font faces created by script.
+    fn switch_to_loaded(&self) {
+        self.status.set(FontFaceSetLoadStatus::Loaded);
+
+        let loaded_fonts = mem::replace(&mut *self.loaded_fonts.borrow_mut(), vec![]);
+        let failed_fonts = mem::replace(&mut *self.failed_fonts.borrow_mut(), vec![]);
+        self.queue_load_event(atom!("loadingdone"), loaded_fonts);
+        if !failed_fonts.is_empty() {
+            self.queue_load_event(atom!("loadingerror"), failed_fonts);
+        }
+        self.resolve_ready_promise_if_loaded();
+    }
+
+    fn queue_load_event(&self, type_: Atom, font_faces: Vec<Dom<FontFace>>) {
+        let font_face_set = Trusted::new(self);
+        let font_faces: Vec<_> = font_faces
+            .iter()
+            .map(|font_face| Trusted::new(&**font_face))
+            .collect();
+        let global = self.global();
+        let _ = global
+            .as_window()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(
+                task!(fire_font_face_set_load_event: move || {
+                    let font_face_set = font_face_set.root();
+                    let init = FontFaceSetLoadEventInit {
+                        parent: EventInit::default(),
+                        fontfaces: font_faces.iter().map(|font_face| font_face.root()).collect(),
+                    };
+                    let global = font_face_set.global();
+                    let event = FontFaceSetLoadEvent::new(global.as_window(), type_, &init);
+                    event.upcast::<Event>().fire(font_face_set.upcast());
+                }),
+                &global,
+            );
+    }
+
+    /// Keeps track of the loading state of the font faces of this set.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#fontface-interface>
+    pub fn font_face_status_changed(&self, font_face: &FontFace) {
+        match font_face.status() {
+            FontFaceLoadStatus::Loading => {
+                self.loading_fonts
+                    .borrow_mut()
+                    .push(Dom::from_ref(font_face));
+                if self.status.get() == FontFaceSetLoadStatus::Loaded {
+                    self.switch_to_loading();
+                }
+            },
+            status @ FontFaceLoadStatus::Loaded | status @ FontFaceLoadStatus::Error => {
+                if !remove(&self.loading_fonts, font_face) {
+                    return;
+                }
+                if status == FontFaceLoadStatus::Loaded {
+                    self.loaded_fonts
+                        .borrow_mut()
+                        .push(Dom::from_ref(font_face));
+                } else {
+                    self.failed_fonts
+                        .borrow_mut()
+                        .push(Dom::from_ref(font_face));
+                }
+                if !self.is_loading() {
+                    self.switch_to_loaded();
+                }
+            },
+            FontFaceLoadStatus::Unloaded => {},
+        }
+        self.settle_pending_loads();
+    }
+
+    /// Settles the promises returned by `load()` whose font faces are done
+    /// loading.
+    fn settle_pending_loads(&self) {
+        let mut settled = vec![];
+        self.pending_loads.borrow_mut().retain(|pending_load| {
+            let faces = &pending_load.font_faces;
+            let failed = faces
+                .iter()
+                .any(|font_face| font_face.status() == FontFaceLoadStatus::Error);
+            let loaded = faces
+                .iter()
+                .all(|font_face| font_face.status() == FontFaceLoadStatus::Loaded);
+            if failed || loaded {
+                let font_faces: Vec<_> = faces
+                    .iter()
+                    .map(|font_face| DomRoot::from_ref(&**font_face))
+                    .collect();
+                settled.push((pending_load.promise.clone(), font_faces, failed));
+            }
+            !(failed || loaded)
+        });
+        for (promise, font_faces, failed) in settled {
+            if failed {
+                promise.reject_error(Error::Network);
+            } else {
+                promise.resolve_native(&font_faces);
+            }
+        }
+    }
+
+    /// <https://drafts.csswg.org/css-font-loading/#find-the-matching-font-faces>
+    fn find_matching_font_faces(&self, font: &str, text: &str) -> Fallible<Vec<DomRoot<FontFace>>> {
+        // Steps 1-3.
+        let global = self.global();
+        let font = parse_font(global.as_window(), font)?;
+
+        // Step 4.
+        let font_faces = self.font_faces.borrow();
+        let candidates: Vec<_> = font_faces
+            .iter()
+            .filter_map(|font_face| {
+                let family = font_face.family_name()?;
+                let descriptors = font_face.matching_descriptors()?;
+                Some((font_face, family, descriptors))
+            })
+            .collect();
+        let mut matching = vec![];
+        for family in &font.families {
+            let family_faces: Vec<_> = candidates
+                .iter()
+                .filter(|&&(_, ref name, _)| family.name.eq_ignore_ascii_case(&name.name))
+                .map(|&(font_face, _, ref descriptors)| (font_face, descriptors))
+                .collect();
+            let mut characters: Vec<char> = text.chars().collect();
+            characters.sort();
+            characters.dedup();
+            for character in characters {
+                let covering = family_faces
+                    .iter()
+                    .filter(|&&(_, descriptors)| descriptors.covers(character))
+                    .cloned()
+                    .collect();
+                matching.extend(font.match_style(covering));
+            }
+        }
+
+        // Step 5.
+        Ok(font_faces
+            .iter()
+            .filter(|font_face| matching.contains(font_face))
+            .map(|font_face| DomRoot::from_ref(&**font_face))
+            .collect())
+    }
+}
+
+impl FontFaceSetMethods for FontFaceSet {
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-add
+    fn Add(&self, font: &FontFace) -> DomRoot<FontFaceSet> {
+        // Step 1.
+        if self.Has(font) {
+            return DomRoot::from_ref(self);
+        }
+
+        // Step 3.
+        self.font_faces.borrow_mut().push(Dom::from_ref(font));
+        font.add_to_font_face_set(self);
+
+        // Step 4.
+        if font.status() == FontFaceLoadStatus::Loading {
+            self.font_face_status_changed(font);
+        }
+
+        // Step 5.
+        DomRoot::from_ref(self)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-delete
+    fn Delete(&self, font: &FontFace) -> bool {
+        // Step 2.
+        if !remove(&self.font_faces, font) {
+            return false;
+        }
+        font.remove_from_font_face_set(self);
+
+        // Step 3.
+        remove(&self.loaded_fonts, font);
+        remove(&self.failed_fonts, font);
+        if remove(&self.loading_fonts, font) && !self.is_loading() {
+            self.switch_to_loaded();
+        }
+        true
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-clear
+    fn Clear(&self) {
+        let font_faces: Vec<_> = self
+            .font_faces
+            .borrow()
+            .iter()
+            .map(|font_face| DomRoot::from_ref(&**font_face))
+            .collect();
+        for font_face in font_faces {
+            self.Delete(&font_face);
+        }
+    }
+
+    fn Has(&self, font: &FontFace) -> bool {
+        self.font_faces
+            .borrow()
+            .iter()
+            .any(|font_face| ptr::eq(&**font_face, font))
+    }
+
+    fn Size(&self) -> u32 {
+        self.font_faces.borrow().len() as u32
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-onloading
+    event_handler!(loading, GetOnloading, SetOnloading);
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-onloadingdone
+    event_handler!(loadingdone, GetOnloadingdone, SetOnloadingdone);
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-onloadingerror
+    event_handler!(loadingerror, GetOnloadingerror, SetOnloadingerror);
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-load
+    fn Load(&self, font: DOMString, text: DOMString) -> Rc<Promise> {
+        // Step 1.
+        let promise = Promise::new(&self.global());
+
+        // Step 2.
+        let font_faces = match self.find_matching_font_faces(&font, &text) {
+            Ok(font_faces) => font_faces,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Step 3.
+        for font_face in &font_faces {
+            font_face.Load();
+        }
+        self.pending_loads.borrow_mut().push(PendingLoad {
+            promise: promise.clone(),
+            font_faces: font_faces
+                .iter()
+                .map(|font_face| Dom::from_ref(&**font_face))
+                .collect(),
+        });
+        self.settle_pending_loads();
+        promise
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-check
+    fn Check(&self, font: DOMString, text: DOMString) -> Fallible<bool> {
+        let font_faces = self.find_matching_font_faces(&font, &text)?;
+        Ok(font_faces
+            .iter()
+            .all(|font_face| font_face.status() == FontFaceLoadStatus::Loaded))
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-ready
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready_promise.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-status
+    fn Status(&self) -> FontFaceSetLoadStatus {
+        self.status.get()
+    }
+}
+
+/// Removes `font_face` from `list`, returning whether it was there.
+fn remove(list: &DomRefCell<Vec<Dom<FontFace>>>, font_face: &FontFace) -> bool {
+    let mut list = list.borrow_mut();
+    let len = list.len();
+    list.retain(|item| !ptr::eq(&**item, font_face));
+    list.len() != len
+}
+
+/// The font that a `font` argument of `load()` or `check()` asks for.
+struct Font {
+    families: Vec<FamilyName>,
+    style: ComputedFontStyleDescriptor,
+    weight: f32,
+    stretch: f32,
+}
+
+impl Font {
+    /// Narrows `font_faces` down to the ones whose style descriptors match
+    /// this font best, by stretch, then style, then weight.
+    ///
+    /// The angles of oblique font faces aren't compared.
+    ///
+    /// <https://drafts.csswg.org/css-fonts/#font-style-matching>
+    fn match_style<T: Copy>(&self, mut font_faces: Vec<(T, &MatchingDescriptors)>) -> Vec<T> {
+        retain_closest(&mut font_faces, |descriptors| {
+            let (min, max) = (descriptors.stretch.0, descriptors.stretch.1);
+            match self.stretch {
+                stretch if min <= stretch && stretch <= max => 0.,
+                // Narrower font faces are preferred for condensed fonts.
+                stretch if stretch <= 1. && max < stretch => stretch - max,
+                stretch if stretch <= 1. => 10. + min - stretch,
+                stretch if min > stretch => min - stretch,
+                stretch => 10. + stretch - max,
+            }
+        });
+        retain_closest(&mut font_faces, |descriptors| {
+            use self::ComputedFontStyleDescriptor::*;
+            match (&self.style, &descriptors.style) {
+                (&Normal, &Normal) | (&Italic, &Italic) | (&Oblique(..), &Oblique(..)) => 0.,
+                (&Normal, &Oblique(..)) | (&Italic, &Oblique(..)) | (&Oblique(..), &Italic) => 1.,
+                _ => 2.,
+            }
+        });
+        retain_closest(&mut font_faces, |descriptors| {
+            let (min, max) = (descriptors.weight.0, descriptors.weight.1);
+            match self.weight {
+                weight if min <= weight && weight <= max => 0.,
+                // Between 400 and 500, heavier font faces up to 500 are
+                // preferred, then lighter ones, then heavier ones.
+                weight if weight >= 400. && weight <= 500. => {
+                    if min > weight && min <= 500. {
+                        min - weight
+                    } else if max < weight {
+                        1000. + weight - max
+                    } else {
+                        2000. + min - weight
+                    }
+                },
+                weight if weight < 400. && max < weight => weight - max,
+                weight if weight < 400. => 1000. + min - weight,
+                weight if min > weight => min - weight,
+                weight => 1000. + weight - max,
+            }
+        });
+        font_faces
+            .into_iter()
+            .map(|(font_face, _)| font_face)
+            .collect()
+    }
+}
+
+/// Keeps the font faces of `font_faces` with the smallest `distance`.
+fn retain_closest<T, F>(font_faces: &mut Vec<(T, &MatchingDescriptors)>, distance: F)
+where
+    F: Fn(&MatchingDescriptors) -> f32,
+{
+    let closest = font_faces
+        .iter()
+        .map(|&(_, descriptors)| distance(descriptors))
+        .fold(f32::INFINITY, f32::min);
+    font_faces.retain(|&(_, descriptors)| distance(descriptors) == closest);
+}
+
+/// Parses the value of a `font` shorthand.
+fn parse_font(window: &Window, font: &str) -> Fallible<Font> {
+    let document = window.Document();
+    let mut declarations = SourcePropertyDeclaration::new();
+    parse_one_declaration_into(
+        &mut declarations,
+        PropertyId::Shorthand(ShorthandId::Font),
+        font,
+        &document.url(),
+        window.css_error_reporter(),
+        ParsingMode::DEFAULT,
+        document.quirks_mode(),
+    )
+    .map_err(|_| Error::Syntax)?;
+
+    // CSS-wide keywords and system fonts are not allowed.
+    let mut families = None;
+    let mut style = ComputedFontStyleDescriptor::Normal;
+    let mut weight = ComputedFontWeight::normal();
+    let mut stretch = 1.;
+    for declaration in declarations.drain() {
+        match declaration {
+            PropertyDeclaration::FontFamily(FontFamily::Values(ref list)) => {
+                families = Some(
+                    list.iter()
+                        .filter_map(|family| match *family {
+                            SingleFontFamily::FamilyName(ref name) => Some(name.clone()),
+                            SingleFontFamily::Generic(_) => None,
+                        })
+                        .collect(),
+                );
+            },
+            PropertyDeclaration::FontStyle(FontStyle::Specified(ref specified)) => {
+                style = match *specified {
+                    GenericFontStyle::Normal => ComputedFontStyleDescriptor::Normal,
+                    GenericFontStyle::Italic => ComputedFontStyleDescriptor::Italic,
+                    GenericFontStyle::Oblique(ref angle) => {
+                        let degrees = SpecifiedFontStyle::compute_angle_degrees(angle);
+                        ComputedFontStyleDescriptor::Oblique(degrees, degrees)
+                    },
+                };
+            },
+            PropertyDeclaration::FontWeight(ref specified) => {
+                weight = match *specified {
+                    FontWeight::Absolute(ref absolute) => absolute.compute(),
+                    FontWeight::Bolder => weight.bolder(),
+                    FontWeight::Lighter => weight.lighter(),
+                    FontWeight::System(_) => return Err(Error::Syntax),
+                };
+            },
+            PropertyDeclaration::FontStretch(ref specified) => {
+                stretch = match *specified {
+                    FontStretch::Stretch(ref percentage) => percentage.get(),
+                    FontStretch::Keyword(ref keyword) => keyword.compute().0,
+                    FontStretch::System(_) => return Err(Error::Syntax),
+                };
+            },
+            PropertyDeclaration::FontStyle(_) | PropertyDeclaration::FontFamily(_) => {
+                return Err(Error::Syntax);
+            },
+            _ => {},
+        }
+    }
+
+    Ok(Font {
+        families: families.ok_or(Error::Syntax)?,
+        style,
+        weight: weight.0,
+        stretch,
+    })
+}
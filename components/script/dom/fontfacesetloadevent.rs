@@ -0,0 +1,84 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::FontFaceSetLoadEventBinding;
+use crate::dom::bindings::codegen::Bindings::FontFaceSetLoadEventBinding::{
+    FontFaceSetLoadEventInit, FontFaceSetLoadEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::event::Event;
+use crate::dom::fontface::FontFace;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsval::JSVal;
+use servo_atoms::Atom;
+
+// https://drafts.csswg.org/css-font-loading/#fontfacesetloadevent
+#[dom_struct]
+pub struct FontFaceSetLoadEvent {
+    event: Event,
+    fontfaces: Vec<Dom<FontFace>>,
+}
+
+impl FontFaceSetLoadEvent {
+    fn new_inherited(init: &FontFaceSetLoadEventInit) -> FontFaceSetLoadEvent {
+        FontFaceSetLoadEvent {
+            event: Event::new_inherited(),
+            fontfaces: init
+                .fontfaces
+                .iter()
+                .map(|font_face| Dom::from_ref(&**font_face))
+                .collect(),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: Atom,
+        init: &FontFaceSetLoadEventInit,
+    ) -> DomRoot<FontFaceSetLoadEvent> {
+        let ev = reflect_dom_object(
+            Box::new(FontFaceSetLoadEvent::new_inherited(init)),
+            window,
+            FontFaceSetLoadEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, init.parent.bubbles, init.parent.cancelable);
+        }
+        ev
+    }
+
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &FontFaceSetLoadEventInit,
+    ) -> Fallible<DomRoot<FontFaceSetLoadEvent>> {
+        Ok(FontFaceSetLoadEvent::new(window, Atom::from(type_), init))
+    }
+}
+
+impl FontFaceSetLoadEventMethods for FontFaceSetLoadEvent {
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfacesetloadevent-fontfaces
+    fn Fontfaces(&self, cx: JSContext) -> JSVal {
+        let fontfaces: Vec<_> = self
+            .fontfaces
+            .iter()
+            .map(|font_face| DomRoot::from_ref(&**font_face))
+            .collect();
+        to_frozen_array(&fontfaces, cx)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.upcast::<Event>().IsTrusted()
+    }
+}
@@ -3,19 +3,23 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::BroadcastChannelBinding::BroadcastChannelMethods;
 use crate::dom::bindings::codegen::Bindings::EventSourceBinding::EventSourceBinding::EventSourceMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::WindowOrWorkerGlobalScopeBinding::StructuredSerializeOptions;
 use crate::dom::bindings::codegen::Bindings::WorkerGlobalScopeBinding::WorkerGlobalScopeMethods;
 use crate::dom::bindings::conversions::{root_from_object, root_from_object_static};
-use crate::dom::bindings::error::{report_pending_exception, ErrorInfo};
+use crate::dom::bindings::error::{report_pending_exception, Error, ErrorInfo, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::settings_stack::{entry_global, incumbent_global, AutoEntryScript};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::structuredclone;
+use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::bindings::weakref::{DOMTracker, WeakRef};
+use crate::dom::broadcastchannel::BroadcastChannel;
 use crate::dom::crypto::Crypto;
 use crate::dom::dedicatedworkerglobalscope::DedicatedWorkerGlobalScope;
 use crate::dom::errorevent::ErrorEvent;
@@ -55,19 +59,22 @@ use js::jsapi::JSObject;
 use js::jsapi::{CurrentGlobalOrNull, GetNonCCWObjectGlobal};
 use js::jsapi::{HandleObject, Heap};
 use js::jsapi::{JSAutoRealm, JSContext};
-use js::jsval::UndefinedValue;
+use js::jsval::{JSVal, UndefinedValue};
 use js::panic::maybe_resume_unwind;
 use js::rust::wrappers::EvaluateUtf8;
 use js::rust::{get_object_class, CompileOptionsWrapper, ParentRuntime, Runtime};
-use js::rust::{HandleValue, MutableHandleValue};
+use js::rust::{CustomAutoRooter, CustomAutoRooterGuard, HandleValue, MutableHandleValue};
 use js::{JSCLASS_IS_DOMJSCLASS, JSCLASS_IS_GLOBAL};
-use msg::constellation_msg::{MessagePortId, MessagePortRouterId, PipelineId};
+use msg::constellation_msg::{
+    BroadcastChannelRouterId, MessagePortId, MessagePortRouterId, PipelineId,
+};
 use net_traits::image_cache::ImageCache;
 use net_traits::{CoreResourceThread, IpcSend, ResourceThreads};
 use profile_traits::{mem as profile_mem, time as profile_time};
 use script_traits::transferable::MessagePortImpl;
 use script_traits::{
-    MessagePortMsg, MsDuration, PortMessageTask, ScriptMsg, ScriptToConstellationChan, TimerEvent,
+    BroadcastMsg, MessagePortMsg, MsDuration, PortMessageTask, ScriptMsg,
+    ScriptToConstellationChan, TimerEvent,
 };
 use script_traits::{TimerEventId, TimerSchedulerMsg, TimerSource};
 use servo_url::{MutableOrigin, ServoUrl};
@@ -80,6 +87,7 @@ use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use time::{get_time, Timespec};
+use uuid::Uuid;
 
 #[derive(JSTraceable)]
 pub struct AutoCloseWorker(Arc<AtomicBool>);
@@ -99,6 +107,9 @@ pub struct GlobalScope {
     /// The message-port router id for this global, if it is managing ports.
     message_port_state: DomRefCell<MessagePortState>,
 
+    /// The broadcast channels state this global, if it is managing any.
+    broadcast_channel_state: DomRefCell<BroadcastChannelState>,
+
     /// Pipeline id associated with this global.
     pipeline_id: PipelineId,
 
@@ -106,6 +117,10 @@ pub struct GlobalScope {
     /// live updates from the worker.
     devtools_wants_updates: Cell<bool>,
 
+    /// Whether the storage actor of the developer tools listens for the
+    /// changes script makes to cookies and web storage.
+    devtools_wants_storage_updates: Cell<bool>,
+
     /// Timers used by the Console API.
     console_timers: DomRefCell<HashMap<DOMString, u64>>,
 
@@ -194,6 +209,13 @@ struct MessageListener {
     context: Trusted<GlobalScope>,
 }
 
+/// A wrapper for broadcasts coming in over IPC, and the event-loop.
+struct BroadcastListener {
+    canceller: TaskCanceller,
+    task_source: DOMManipulationTaskSource,
+    context: Trusted<GlobalScope>,
+}
+
 /// A wrapper between timer events coming in over IPC, and the event-loop.
 struct TimerListener {
     canceller: TaskCanceller,
@@ -225,6 +247,90 @@ pub enum MessagePortState {
     UnManaged,
 }
 
+/// A broadcast channel managed by a global, which is kept alive while it can still receive
+/// messages.
+/// <https://html.spec.whatwg.org/multipage/#broadcastchannel:garbage-collection>
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub enum ManagedBroadcastChannel {
+    /// A channel that isn't closed and has `message` or `messageerror` event listeners,
+    /// or that was created since the last garbage collection checkpoint.
+    Strong(Dom<BroadcastChannel>),
+    /// Any other channel, which can be collected.
+    Weak(WeakRef<BroadcastChannel>),
+}
+
+/// State representing whether this global is currently managing broadcast channels.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub enum BroadcastChannelState {
+    /// The broadcast-channel router id for this global, and a map of channel-name
+    /// to the channels with that name, in order of creation.
+    /// The order is required by the "sort destinations" step of
+    /// <https://html.spec.whatwg.org/multipage/#dom-broadcastchannel-postmessage>
+    Managed(
+        BroadcastChannelRouterId,
+        HashMap<DOMString, VecDeque<ManagedBroadcastChannel>>,
+    ),
+    /// This global is not managing any broadcast channels at this time.
+    UnManaged,
+}
+
+impl ManagedBroadcastChannel {
+    fn root(&self) -> Option<DomRoot<BroadcastChannel>> {
+        match *self {
+            ManagedBroadcastChannel::Strong(ref channel) => Some(DomRoot::from_ref(&**channel)),
+            ManagedBroadcastChannel::Weak(ref channel) => channel.root(),
+        }
+    }
+
+    /// Holds the channel strongly while it can still receive messages, and weakly otherwise.
+    /// Returns whether the channel should still be managed, which isn't the case once it has
+    /// been closed or collected.
+    fn update(&mut self) -> bool {
+        let channel = match self.root() {
+            Some(channel) if !channel.closed() => channel,
+            _ => return false,
+        };
+        let target = channel.upcast::<EventTarget>();
+        let has_listeners = target.has_listeners_for(&atom!("message")) ||
+            target.has_listeners_for(&atom!("messageerror"));
+        match (has_listeners, &*self) {
+            (true, ManagedBroadcastChannel::Weak(_)) => {
+                *self = ManagedBroadcastChannel::Strong(Dom::from_ref(&*channel));
+            },
+            (false, ManagedBroadcastChannel::Strong(_)) => {
+                *self = ManagedBroadcastChannel::Weak(WeakRef::new(&*channel));
+            },
+            _ => {},
+        }
+        true
+    }
+}
+
+impl BroadcastListener {
+    /// Handle a broadcast coming in over IPC,
+    /// by queueing the appropriate task on the relevant event-loop.
+    fn handle(&self, event: BroadcastMsg) {
+        let context = self.context.clone();
+
+        // Note: strictly speaking we should just queue the message event tasks,
+        // not queue a task that then queues more tasks.
+        // This however seems to be hard to avoid in the light of the IPC.
+        // One can imagine queueing tasks directly,
+        // for channels that would be in the same script-thread.
+        let _ = self.task_source.queue_with_canceller(
+            task!(broadcast_message_event: move || {
+                let global = context.root();
+                // Step 10 of https://html.spec.whatwg.org/multipage/#dom-broadcastchannel-postmessage,
+                // For each BroadcastChannel object destination in destinations, queue a task.
+                global.broadcast_message_event(event, None);
+            }),
+            &self.canceller,
+        );
+    }
+}
+
 impl TimerListener {
     /// Handle a timer-event coming-in over IPC,
     /// by queuing the appropriate task on the relevant event-loop.
@@ -345,11 +451,13 @@ impl GlobalScope {
     ) -> Self {
         Self {
             message_port_state: DomRefCell::new(MessagePortState::UnManaged),
+            broadcast_channel_state: DomRefCell::new(BroadcastChannelState::UnManaged),
             eventtarget: EventTarget::new_inherited(),
             crypto: Default::default(),
             next_worker_id: Cell::new(WorkerId(0)),
             pipeline_id,
             devtools_wants_updates: Default::default(),
+            devtools_wants_storage_updates: Default::default(),
             console_timers: DomRefCell::new(Default::default()),
             devtools_chan,
             mem_profiler_chan,
@@ -458,6 +566,22 @@ impl GlobalScope {
         *self.message_port_state.borrow_mut() = MessagePortState::UnManaged;
     }
 
+    /// Update our state to un-managed,
+    /// and tell the constellation to drop the sender to our broadcast router.
+    pub fn remove_broadcast_channel_router(&self) {
+        if let BroadcastChannelState::Managed(router_id, _channels) =
+            &*self.broadcast_channel_state.borrow()
+        {
+            let _ =
+                self.script_to_constellation_chan()
+                    .send(ScriptMsg::RemoveBroadcastChannelRouter(
+                        router_id.clone(),
+                        self.origin().immutable().clone(),
+                    ));
+        }
+        *self.broadcast_channel_state.borrow_mut() = BroadcastChannelState::UnManaged;
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#entangle>
     pub fn entangle_ports(&self, port1: MessagePortId, port2: MessagePortId) {
         if let MessagePortState::Managed(_id, message_ports) =
@@ -667,6 +791,126 @@ impl GlobalScope {
         }
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-broadcastchannel-postmessage>
+    /// Step 7 and following steps.
+    pub fn schedule_broadcast(&self, msg: BroadcastMsg, channel_id: &Uuid) {
+        // First, broadcast locally.
+        self.broadcast_message_event(msg.clone(), Some(channel_id));
+
+        let router_id = match &*self.broadcast_channel_state.borrow() {
+            BroadcastChannelState::Managed(router_id, _) => router_id.clone(),
+            BroadcastChannelState::UnManaged => {
+                warn!("Attempt to broadcast a message via a global not managing any channels.");
+                return;
+            },
+        };
+
+        // Second, broadcast to other globals via the constellation.
+        //
+        // Note: for globals in the same script-thread,
+        // we could skip the hop to the constellation.
+        let _ = self
+            .script_to_constellation_chan()
+            .send(ScriptMsg::ScheduleBroadcast(router_id, msg));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-broadcastchannel-postmessage>
+    /// Step 7 and following steps.
+    pub fn broadcast_message_event(&self, event: BroadcastMsg, channel_id: Option<&Uuid>) {
+        let destinations: Vec<DomRoot<BroadcastChannel>> =
+            if let BroadcastChannelState::Managed(_, channels) =
+                &*self.broadcast_channel_state.borrow()
+            {
+                let BroadcastMsg {
+                    ref origin,
+                    ref channel_name,
+                    ..
+                } = event;
+
+                // Step 7, a few preliminary steps.
+
+                // - Check the worker is not closing.
+                if let Some(worker) = self.downcast::<WorkerGlobalScope>() {
+                    if worker.is_closing() {
+                        return;
+                    }
+                }
+
+                // - Check the associated document is fully-active.
+                if let Some(window) = self.downcast::<Window>() {
+                    if !window.Document().is_fully_active() {
+                        return;
+                    }
+                }
+
+                // - Check for a case-sensitive match for the name of the channel.
+                let channel_name = DOMString::from_string(channel_name.clone());
+
+                if let Some(channels) = channels.get(&channel_name) {
+                    channels
+                        .iter()
+                        .filter_map(|channel| channel.root())
+                        .filter(|channel| {
+                            // Step 8.
+                            // Filter out the sender.
+                            if let Some(id) = channel_id {
+                                channel.id() != id
+                            } else {
+                                true
+                            }
+                        })
+                        .filter(|channel| {
+                            // Step 7, the channel and the message must be same-origin.
+                            !channel.closed() && channel.global().origin().immutable() == origin
+                        })
+                        // Step 9, sort by creation order,
+                        // done by using a queue to store channels in creation order.
+                        .collect()
+                } else {
+                    return;
+                }
+            } else {
+                return;
+            };
+
+        // Step 10, for each destination, queue a task.
+        for channel in destinations {
+            let event = event.clone();
+            let channel = Trusted::new(&*channel);
+            let global = Trusted::new(self);
+            let _ = self.dom_manipulation_task_source().queue(
+                task!(process_pending_broadcast_message: move || {
+                    let destination = channel.root();
+                    let global = global.root();
+
+                    // 10.1 and 10.2, the channel might have been closed in the meantime.
+                    if destination.closed() {
+                        return;
+                    }
+
+                    // 10.3 and 10.4
+                    let BroadcastMsg { data, origin, .. } = event;
+                    rooted!(in(*global.get_cx()) let mut message = UndefinedValue());
+                    if let Ok(ports) = structuredclone::read(&global, data, message.handle_mut()) {
+                        // 10.6 and 10.7
+                        MessageEvent::dispatch_jsval(
+                            destination.upcast(),
+                            &global,
+                            message.handle(),
+                            Some(&origin.ascii_serialization()),
+                            None,
+                            ports,
+                        );
+                    } else {
+                        // 10.5, fire messageerror.
+                        MessageEvent::dispatch_error(destination.upcast(), &global);
+                    }
+                }),
+                self,
+            );
+        }
+    }
+
     /// Check all ports that have been transfer-received in the previous task,
     /// and complete their transfer if they haven't been re-transferred.
     pub fn maybe_add_pending_ports(&self) {
@@ -737,6 +981,102 @@ impl GlobalScope {
         }
     }
 
+    /// Remove closed and collected broadcast channels, since they can never receive messages
+    /// again, and only keep alive the channels that have listeners for the messages.
+    /// <https://html.spec.whatwg.org/multipage/#broadcastchannel:garbage-collection>
+    pub fn perform_a_broadcast_channel_garbage_collection_checkpoint(&self) {
+        let is_empty = if let BroadcastChannelState::Managed(router_id, channels) =
+            &mut *self.broadcast_channel_state.borrow_mut()
+        {
+            channels.retain(|name, queue| {
+                let mut index = 0;
+                while index < queue.len() {
+                    if queue[index].update() {
+                        index += 1;
+                    } else {
+                        queue.remove(index);
+                    }
+                }
+                if queue.is_empty() {
+                    let _ = self.script_to_constellation_chan().send(
+                        ScriptMsg::RemoveBroadcastChannelNameInRouter(
+                            router_id.clone(),
+                            name.to_string(),
+                            self.origin().immutable().clone(),
+                        ),
+                    );
+                    false
+                } else {
+                    true
+                }
+            });
+            channels.is_empty()
+        } else {
+            false
+        };
+        if is_empty {
+            self.remove_broadcast_channel_router();
+        }
+    }
+
+    /// Start tracking a broadcast-channel.
+    pub fn track_broadcast_channel(&self, dom_channel: &BroadcastChannel) {
+        let mut current_state = self.broadcast_channel_state.borrow_mut();
+
+        if let BroadcastChannelState::UnManaged = &*current_state {
+            // Setup a route for IPC, for broadcasts from the constellation to our channels.
+            let (broadcast_control_sender, broadcast_control_receiver) =
+                ipc::channel().expect("ipc channel failure");
+            let context = Trusted::new(self);
+            let (task_source, canceller) = (
+                self.dom_manipulation_task_source(),
+                self.task_canceller(TaskSourceName::DOMManipulation),
+            );
+            let listener = BroadcastListener {
+                canceller,
+                task_source,
+                context,
+            };
+            ROUTER.add_route(
+                broadcast_control_receiver.to_opaque(),
+                Box::new(move |message| {
+                    let msg = message.to();
+                    match msg {
+                        Ok(msg) => listener.handle(msg),
+                        Err(err) => warn!("Error receiving a BroadcastMsg: {:?}", err),
+                    }
+                }),
+            );
+            let router_id = BroadcastChannelRouterId::new();
+            *current_state = BroadcastChannelState::Managed(router_id.clone(), HashMap::new());
+            let _ = self
+                .script_to_constellation_chan()
+                .send(ScriptMsg::NewBroadcastChannelRouter(
+                    router_id,
+                    broadcast_control_sender,
+                    self.origin().immutable().clone(),
+                ));
+        }
+
+        let (router_id, channels) = match &mut *current_state {
+            BroadcastChannelState::Managed(router_id, channels) => (router_id, channels),
+            BroadcastChannelState::UnManaged => return,
+        };
+        let entry = channels.entry(dom_channel.Name()).or_insert_with(|| {
+            let _ = self.script_to_constellation_chan().send(
+                ScriptMsg::NewBroadcastChannelNameInRouter(
+                    router_id.clone(),
+                    dom_channel.Name().to_string(),
+                    self.origin().immutable().clone(),
+                ),
+            );
+            VecDeque::new()
+        });
+        // The channel is kept alive until the next checkpoint, which gives the script that
+        // created it a chance to add its listeners.
+        entry.push_back(ManagedBroadcastChannel::Strong(Dom::from_ref(dom_channel)));
+    }
+
     /// Start tracking a message-port
     pub fn track_message_port(&self, dom_port: &MessagePort, port_impl: Option<MessagePortImpl>) {
         let mut current_state = self.message_port_state.borrow_mut();
@@ -917,6 +1257,33 @@ impl GlobalScope {
         &self.consumed_rejections
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-structuredclone>
+    pub fn structured_clone(
+        &self,
+        cx: SafeJSContext,
+        value: HandleValue,
+        options: RootedTraceableBox<StructuredSerializeOptions>,
+    ) -> Fallible<JSVal> {
+        let mut rooted = CustomAutoRooter::new(
+            options
+                .transfer
+                .iter()
+                .map(|js: &RootedTraceableBox<Heap<*mut JSObject>>| js.get())
+                .collect(),
+        );
+        let guard = CustomAutoRooterGuard::new(*cx, &mut rooted);
+
+        // Step 1
+        let data = structuredclone::write(cx, value, Some(guard))?;
+
+        // Step 2-3
+        rooted!(in(*cx) let mut clone = UndefinedValue());
+        structuredclone::read(self, data, clone.handle_mut()).map_err(|_| Error::DataClone)?;
+
+        // Step 4
+        Ok(clone.get())
+    }
+
     #[allow(unsafe_code)]
     pub fn get_cx(&self) -> SafeJSContext {
         unsafe { SafeJSContext::from_ptr(Runtime::get()) }
@@ -942,6 +1309,14 @@ impl GlobalScope {
         self.devtools_wants_updates.set(value);
     }
 
+    pub fn devtools_wants_storage_updates(&self) -> bool {
+        self.devtools_wants_storage_updates.get()
+    }
+
+    pub fn set_devtools_wants_storage_updates(&self, value: bool) {
+        self.devtools_wants_storage_updates.set(value);
+    }
+
     pub fn time(&self, label: DOMString) -> Result<(), ()> {
         let mut timers = self.console_timers.borrow_mut();
         if timers.len() >= 10000 {
@@ -21,7 +21,7 @@ use crate::dom::htmlformelement::{FormSubmitter, ResetFrom, SubmittedFrom};
 use crate::dom::node::{window_from_node, BindContext, Node, UnbindContext};
 use crate::dom::nodelist::NodeList;
 use crate::dom::validation::Validatable;
-use crate::dom::validitystate::{ValidationFlags, ValidityState};
+use crate::dom::validitystate::ValidityState;
 use crate::dom::virtualmethods::VirtualMethods;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
@@ -52,7 +52,7 @@ impl HTMLButtonElement {
     ) -> HTMLButtonElement {
         HTMLButtonElement {
             htmlelement: HTMLElement::new_inherited_with_state(
-                ElementState::IN_ENABLED_STATE,
+                ElementState::IN_ENABLED_STATE | ElementState::IN_VALID_STATE,
                 local_name,
                 prefix,
                 document,
@@ -230,6 +230,8 @@ impl VirtualMethods for HTMLButtonElement {
             },
             _ => {},
         }
+
+        self.upcast::<Element>().update_validity_state();
     }
 
     fn bind_to_tree(&self, context: &BindContext) {
@@ -237,8 +239,9 @@ impl VirtualMethods for HTMLButtonElement {
             s.bind_to_tree(context);
         }
 
-        self.upcast::<Element>()
-            .check_ancestors_disabled_state_for_form_control();
+        let el = self.upcast::<Element>();
+        el.check_ancestors_disabled_state_for_form_control();
+        el.update_validity_state();
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext) {
@@ -273,12 +276,8 @@ impl FormControl for HTMLButtonElement {
 
 impl Validatable for HTMLButtonElement {
     fn is_instance_validatable(&self) -> bool {
-        true
-    }
-    fn validate(&self, validate_flags: ValidationFlags) -> bool {
-        if validate_flags.is_empty() {}
-        // Need more flag check for different validation types later
-        true
+        // https://html.spec.whatwg.org/multipage/#the-button-element:barred-from-constraint-validation
+        self.button_type.get() == ButtonType::Submit && !self.upcast::<Element>().disabled_state()
     }
 }
 
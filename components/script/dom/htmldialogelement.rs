@@ -5,16 +5,23 @@
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HTMLDialogElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLDialogElementBinding::HTMLDialogElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
 use crate::dom::element::Element;
+use crate::dom::event::EventStatus;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::{window_from_node, Node};
+use crate::dom::node::{
+    document_from_node, window_from_node, Node, ShadowIncluding, UnbindContext,
+};
+use crate::dom::virtualmethods::VirtualMethods;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
+use style::element_state::ElementState;
 
 #[dom_struct]
 pub struct HTMLDialogElement {
@@ -48,6 +55,55 @@ impl HTMLDialogElement {
             HTMLDialogElementBinding::Wrap,
         )
     }
+
+    fn is_modal(&self) -> bool {
+        self.upcast::<Element>()
+            .state()
+            .contains(ElementState::IN_MODAL_DIALOG_STATE)
+    }
+
+    /// Stops this dialog from being modal, and removes it from the top layer.
+    fn remove_from_top_layer(&self) {
+        let element = self.upcast::<Element>();
+        element.set_state(ElementState::IN_MODAL_DIALOG_STATE, false);
+        document_from_node(self).remove_from_top_layer(element);
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dialog-focusing-steps
+    fn run_focusing_steps(&self) {
+        let node = self.upcast::<Node>();
+        let descendants = || {
+            node.traverse_preorder(ShadowIncluding::No)
+                .skip(1)
+                .filter_map(DomRoot::downcast::<HTMLElement>)
+        };
+
+        // Step 1 & 2
+        let control = descendants()
+            .find(|element| {
+                element
+                    .upcast::<Element>()
+                    .has_attribute(&local_name!("autofocus")) &&
+                    element.upcast::<Element>().is_focusable_area()
+            })
+            .or_else(|| {
+                descendants().find(|element| element.upcast::<Element>().is_focusable_area())
+            })
+            .unwrap_or_else(|| DomRoot::from_ref(self.upcast()));
+
+        // Step 3
+        control.Focus();
+    }
+
+    // https://html.spec.whatwg.org/multipage/#cancel-dialog
+    pub fn cancel(&self) {
+        let event = self
+            .upcast::<EventTarget>()
+            .fire_cancelable_event(atom!("cancel"));
+        if event.status() == EventStatus::NotCanceled {
+            self.Close(None);
+        }
+    }
 }
 
 impl HTMLDialogElementMethods for HTMLDialogElement {
@@ -68,6 +124,51 @@ impl HTMLDialogElementMethods for HTMLDialogElement {
         *self.return_value.borrow_mut() = return_value;
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-dialog-show
+    fn Show(&self) {
+        let element = self.upcast::<Element>();
+
+        // Step 1
+        if element.has_attribute(&local_name!("open")) {
+            return;
+        }
+
+        // Step 2
+        element.set_bool_attribute(&local_name!("open"), true);
+
+        // Step 4
+        self.run_focusing_steps();
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-dialog-showmodal
+    fn ShowModal(&self) -> ErrorResult {
+        let element = self.upcast::<Element>();
+
+        // Step 1
+        if element.has_attribute(&local_name!("open")) {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2
+        if !self.upcast::<Node>().is_connected() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3
+        element.set_bool_attribute(&local_name!("open"), true);
+
+        // Step 4 & 5
+        element.set_state(ElementState::IN_MODAL_DIALOG_STATE, true);
+
+        // Step 6
+        document_from_node(self).add_to_top_layer(element);
+
+        // Step 8
+        self.run_focusing_steps();
+
+        Ok(())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-dialog-close
     fn Close(&self, return_value: Option<DOMString>) {
         let element = self.upcast::<Element>();
@@ -87,7 +188,8 @@ impl HTMLDialogElementMethods for HTMLDialogElement {
             *self.return_value.borrow_mut() = new_value;
         }
 
-        // TODO: Step 4 implement pending dialog stack removal
+        // Step 4
+        self.remove_from_top_layer();
 
         // Step 5
         win.task_manager()
@@ -95,3 +197,20 @@ impl HTMLDialogElementMethods for HTMLDialogElement {
             .queue_simple_event(target, atom!("close"), &win);
     }
 }
+
+impl VirtualMethods for HTMLDialogElement {
+    fn super_type(&self) -> Option<&dyn VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#the-dialog-element:html-element-removing-steps
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        if let Some(ref s) = self.super_type() {
+            s.unbind_from_tree(context);
+        }
+
+        if self.is_modal() {
+            self.remove_from_top_layer();
+        }
+    }
+}
@@ -5,6 +5,7 @@
 use crate::dom::activation::{synthetic_click_activation, ActivationSource};
 use crate::dom::attr::Attr;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use crate::dom::bindings::codegen::Bindings::HTMLElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
@@ -15,11 +16,13 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::inheritance::{ElementTypeId, HTMLElementTypeId, NodeTypeId};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
-use crate::dom::document::{Document, FocusType};
+use crate::dom::document::{Document, FocusInitiator, FocusType};
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::domstringmap::DOMStringMap;
 use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlbrelement::HTMLBRElement;
@@ -27,11 +30,13 @@ use crate::dom::htmlframesetelement::HTMLFrameSetElement;
 use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
 use crate::dom::htmllabelelement::HTMLLabelElement;
+use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::node::{document_from_node, window_from_node};
 use crate::dom::node::{BindContext, Node, NodeFlags, ShadowIncluding};
 use crate::dom::nodelist::NodeList;
 use crate::dom::text::Text;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::editing;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
 use script_layout_interface::message::QueryMsg;
@@ -41,6 +46,14 @@ use std::rc::Rc;
 use style::attr::AttrValue;
 use style::element_state::*;
 
+/// The states of the `contenteditable` attribute.
+/// <https://html.spec.whatwg.org/multipage/#attr-contenteditable>
+pub enum ContentEditableState {
+    True,
+    False,
+    Inherit,
+}
+
 #[dom_struct]
 pub struct HTMLElement {
     element: Element,
@@ -89,6 +102,36 @@ impl HTMLElement {
         )
     }
 
+    /// The state of the `contenteditable` attribute.
+    /// <https://html.spec.whatwg.org/multipage/#attr-contenteditable>
+    pub fn content_editable_state(&self) -> ContentEditableState {
+        let element = self.upcast::<Element>();
+        match element.get_attribute(&ns!(), &local_name!("contenteditable")) {
+            Some(attr) => {
+                let value = attr.value();
+                if value.is_empty() || value.eq_ignore_ascii_case("true") {
+                    ContentEditableState::True
+                } else if value.eq_ignore_ascii_case("false") {
+                    ContentEditableState::False
+                } else {
+                    ContentEditableState::Inherit
+                }
+            },
+            None => ContentEditableState::Inherit,
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#editing-host>
+    pub fn is_editing_host(&self) -> bool {
+        if let ContentEditableState::True = self.content_editable_state() {
+            return !self
+                .upcast::<Node>()
+                .ancestors()
+                .any(|ancestor| editing::is_content_editable(&ancestor));
+        }
+        false
+    }
+
     fn is_body_or_frameset(&self) -> bool {
         let eventtarget = self.upcast::<EventTarget>();
         eventtarget.is::<HTMLBodyElement>() || eventtarget.is::<HTMLFrameSetElement>()
@@ -134,7 +177,9 @@ impl HTMLElement {
                     } else {
                         node.set_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE, false);
                     }
-                    //TODO set SEQUENTIALLY_FOCUSABLE flag if editing host
+                    if self.is_editing_host() {
+                        node.set_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE, true);
+                    }
                     //TODO set SEQUENTIALLY_FOCUSABLE flag if "sorting interface th elements"
                 },
             }
@@ -171,6 +216,35 @@ impl HTMLElementMethods for HTMLElement {
     // https://html.spec.whatwg.org/multipage/#dom-hidden
     make_bool_setter!(SetHidden, "hidden");
 
+    // https://html.spec.whatwg.org/multipage/#dom-contenteditable
+    fn ContentEditable(&self) -> DOMString {
+        match self.content_editable_state() {
+            ContentEditableState::True => DOMString::from("true"),
+            ContentEditableState::False => DOMString::from("false"),
+            ContentEditableState::Inherit => DOMString::from("inherit"),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-contenteditable
+    fn SetContentEditable(&self, value: DOMString) -> ErrorResult {
+        let element = self.upcast::<Element>();
+        if value.eq_ignore_ascii_case("inherit") {
+            element.remove_attribute(&ns!(), &local_name!("contenteditable"));
+        } else if value.eq_ignore_ascii_case("true") {
+            element.set_string_attribute(&local_name!("contenteditable"), "true".into());
+        } else if value.eq_ignore_ascii_case("false") {
+            element.set_string_attribute(&local_name!("contenteditable"), "false".into());
+        } else {
+            return Err(Error::Syntax);
+        }
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-iscontenteditable
+    fn IsContentEditable(&self) -> bool {
+        editing::is_content_editable(self.upcast())
+    }
+
     // https://html.spec.whatwg.org/multipage/#globaleventhandlers
     global_event_handlers!(NoOnload);
 
@@ -379,7 +453,7 @@ impl HTMLElementMethods for HTMLElement {
         let document = document_from_node(self);
         document.begin_focus_transaction();
         document.request_focus(self.upcast());
-        document.commit_focus_transaction(FocusType::Element);
+        document.commit_focus_transaction(FocusType::Element, FocusInitiator::Other);
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-blur
@@ -392,7 +466,7 @@ impl HTMLElementMethods for HTMLElement {
         let document = document_from_node(self);
         document.begin_focus_transaction();
         // If `request_focus` is not called, focus will be set to None.
-        document.commit_focus_transaction(FocusType::Element);
+        document.commit_focus_transaction(FocusType::Element, FocusInitiator::Other);
     }
 
     // https://drafts.csswg.org/cssom-view/#dom-htmlelement-offsetparent
@@ -736,6 +810,7 @@ impl VirtualMethods for HTMLElement {
                     DOMString::from(&**attr.value()),
                 );
             },
+            (&local_name!("contenteditable"), _) => self.update_sequentially_focusable_status(),
             _ => {},
         }
     }
@@ -747,6 +822,44 @@ impl VirtualMethods for HTMLElement {
         self.update_sequentially_focusable_status();
     }
 
+    fn handle_event(&self, event: &Event) {
+        if let Some(s) = self.super_type() {
+            s.handle_event(event);
+        }
+        if event.DefaultPrevented() {
+            return;
+        }
+        if event.type_() == atom!("click") {
+            // Clicking in editable content focuses its editing host.
+            if let Some(host) = editing::editing_host(self.upcast()) {
+                document_from_node(self).request_focus(host.upcast());
+            }
+            return;
+        }
+        if !self.is_editing_host() {
+            return;
+        }
+        let handled = if let Some(event) = event.downcast::<KeyboardEvent>() {
+            match event.upcast::<Event>().type_() {
+                atom!("keydown") => editing::handle_keydown(self, event),
+                atom!("keypress") => editing::handle_keypress(self, event),
+                _ => false,
+            }
+        } else if let Some(event) = event.downcast::<CompositionEvent>() {
+            if event.upcast::<Event>().type_() == atom!("compositionend") {
+                editing::handle_compositionend(self, event);
+                true
+            } else {
+                false
+            }
+        } else {
+            false
+        };
+        if handled {
+            event.mark_as_handled();
+        }
+    }
+
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
         match name {
             &local_name!("itemprop") => AttrValue::from_serialized_tokenlist(value.into()),
@@ -8,6 +8,7 @@ use crate::dom::bindings::codegen::Bindings::BlobBinding::BlobMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLButtonElementBinding::HTMLButtonElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLDialogElementBinding::HTMLDialogElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLFormControlsCollectionBinding::HTMLFormControlsCollectionMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding::HTMLFormElementMethods;
@@ -31,6 +32,7 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlbuttonelement::HTMLButtonElement;
 use crate::dom::htmlcollection::CollectionFilter;
 use crate::dom::htmldatalistelement::HTMLDataListElement;
+use crate::dom::htmldialogelement::HTMLDialogElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
 use crate::dom::htmlformcontrolscollection::HTMLFormControlsCollection;
@@ -367,6 +369,11 @@ impl HTMLFormElement {
             return;
         }
 
+        if let FormMethod::FormDialog = submitter.method() {
+            self.submit_dialog(submitter);
+            return;
+        }
+
         // Step 11
         let mut action = submitter.action();
 
@@ -407,10 +414,6 @@ impl HTMLFormElement {
 
         // Step 22
         match (&*scheme, method) {
-            (_, FormMethod::FormDialog) => {
-                // TODO: Submit dialog
-                // https://html.spec.whatwg.org/multipage/#submit-dialog
-            },
             // https://html.spec.whatwg.org/multipage/#submit-mutate-action
             ("http", FormMethod::FormGet) |
             ("https", FormMethod::FormGet) |
@@ -451,6 +454,30 @@ impl HTMLFormElement {
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#submit-dialog
+    fn submit_dialog(&self, submitter: FormSubmitter) {
+        // Step 1 & 2
+        let dialog = match self
+            .upcast::<Node>()
+            .ancestors()
+            .filter_map(DomRoot::downcast::<HTMLDialogElement>)
+            .next()
+        {
+            Some(dialog) => dialog,
+            None => return,
+        };
+
+        // Step 3
+        let result = match submitter {
+            FormSubmitter::InputElement(input_element) => Some(input_element.Value()),
+            FormSubmitter::ButtonElement(button_element) => Some(button_element.Value()),
+            FormSubmitter::FormElement(_) => None,
+        };
+
+        // Step 4
+        dialog.Close(result);
+    }
+
     // https://html.spec.whatwg.org/multipage/#submit-mutate-action
     fn mutate_action_url(
         &self,
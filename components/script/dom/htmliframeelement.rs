@@ -180,6 +180,7 @@ impl HTMLIFrameElement {
                 .inner_window_dimensions_query(browsing_context_id)
                 .unwrap_or_default(),
             device_pixel_ratio: window.device_pixel_ratio(),
+            media_environment: window.window_size().media_environment,
         };
 
         match nav_type {
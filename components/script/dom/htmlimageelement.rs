@@ -156,6 +156,8 @@ pub struct HTMLImageElement {
     #[ignore_malloc_size_of = "SourceSet"]
     source_set: DomRefCell<SourceSet>,
     last_selected_source: DomRefCell<Option<USVString>>,
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps>
+    lazy_load_resumption_steps: DomRefCell<Option<LazyLoadResumptionSteps>>,
 }
 
 impl HTMLImageElement {
@@ -164,6 +166,15 @@ impl HTMLImageElement {
     }
 }
 
+/// The rest of the update the image data algorithm for an image that is lazy loaded, to run
+/// once the image is about to come into view.
+#[derive(JSTraceable, MallocSizeOf)]
+struct LazyLoadResumptionSteps {
+    url: ServoUrl,
+    src: USVString,
+    pixel_density: f64,
+}
+
 /// The context required for asynchronously loading an external image.
 struct ImageContext {
     /// Reference to the script thread image cache.
@@ -815,6 +826,7 @@ impl HTMLImageElement {
 
     /// Step 8-12 of html.spec.whatwg.org/multipage/#update-the-image-data
     fn update_the_image_data_sync_steps(&self) {
+        self.lazy_load_resumption_steps.borrow_mut().take();
         let document = document_from_node(self);
         let window = document.window();
         let task_source = window.task_manager().dom_manipulation_task_source();
@@ -857,6 +869,15 @@ impl HTMLImageElement {
         let parsed_url = base_url.join(&src.0);
         match parsed_url {
             Ok(url) => {
+                if self.will_lazy_load() {
+                    *self.lazy_load_resumption_steps.borrow_mut() = Some(LazyLoadResumptionSteps {
+                        url,
+                        src,
+                        pixel_density,
+                    });
+                    document.start_intersection_observing_lazy_loading_element(self.upcast());
+                    return;
+                }
                 // Step 13-17
                 self.prepare_image_request(&url, &src, pixel_density);
             },
@@ -984,6 +1005,36 @@ impl HTMLImageElement {
         ScriptThread::await_stable_state(Microtask::ImageElement(task));
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#will-lazy-load-element-steps>
+    fn will_lazy_load(&self) -> bool {
+        // Step 1
+        if !document_from_node(self).is_scripting_enabled() {
+            return false;
+        }
+        // Step 2-3
+        self.upcast::<Element>()
+            .get_string_attribute(&LocalName::from("loading"))
+            .eq_ignore_ascii_case("lazy")
+    }
+
+    /// Runs the lazy load resumption steps, if the image is waiting to come into view.
+    pub fn resume_lazy_load(&self) {
+        let steps = self.lazy_load_resumption_steps.borrow_mut().take();
+        if let Some(steps) = steps {
+            self.prepare_image_request(&steps.url, &steps.src, steps.pixel_density);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#attr-img-loading>
+    fn loading_attribute_changed(&self) {
+        // Only images that are no longer lazy loaded start loading right away.
+        if self.will_lazy_load() || self.lazy_load_resumption_steps.borrow().is_none() {
+            return;
+        }
+        document_from_node(self).stop_intersection_observing_lazy_loading_element(self.upcast());
+        self.resume_lazy_load();
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#img-environment-changes>
     pub fn react_to_environment_changes(&self) {
         // Step 1
@@ -1233,6 +1284,7 @@ impl HTMLImageElement {
             generation: Default::default(),
             source_set: DomRefCell::new(SourceSet::new()),
             last_selected_source: DomRefCell::new(None),
+            lazy_load_resumption_steps: DomRefCell::new(None),
         }
     }
 
@@ -1469,6 +1521,24 @@ impl HTMLImageElementMethods for HTMLImageElement {
         set_cross_origin_attribute(self.upcast::<Element>(), value);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-img-loading
+    fn Loading(&self) -> DOMString {
+        let loading = self
+            .upcast::<Element>()
+            .get_string_attribute(&LocalName::from("loading"));
+        if loading.eq_ignore_ascii_case("lazy") {
+            DOMString::from("lazy")
+        } else {
+            DOMString::from("eager")
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-loading
+    fn SetLoading(&self, value: DOMString) {
+        self.upcast::<Element>()
+            .set_string_attribute(&LocalName::from("loading"), value);
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-img-usemap
     make_getter!(UseMap, "usemap");
     // https://html.spec.whatwg.org/multipage/#dom-img-usemap
@@ -1619,6 +1689,7 @@ impl VirtualMethods for HTMLImageElement {
             &local_name!("width") |
             &local_name!("crossorigin") |
             &local_name!("sizes") => self.update_the_image_data(),
+            name if &**name == "loading" => self.loading_attribute_changed(),
             _ => {},
         }
     }
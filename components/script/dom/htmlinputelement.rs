@@ -26,6 +26,7 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::file::File;
 use crate::dom::filelist::FileList;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmldatalistelement::HTMLDataListElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
 use crate::dom::htmlformelement::{
@@ -280,7 +281,10 @@ impl HTMLInputElement {
             .clone();
         HTMLInputElement {
             htmlelement: HTMLElement::new_inherited_with_state(
-                ElementState::IN_ENABLED_STATE | ElementState::IN_READ_WRITE_STATE,
+                ElementState::IN_ENABLED_STATE |
+                    ElementState::IN_READ_WRITE_STATE |
+                    ElementState::IN_OPTIONAL_STATE |
+                    ElementState::IN_VALID_STATE,
                 local_name,
                 prefix,
                 document,
@@ -658,7 +662,7 @@ impl HTMLInputElementMethods for HTMLInputElement {
             ValueMode::Filename => {
                 if value.is_empty() {
                     let window = window_from_node(self);
-                    let fl = FileList::new(&window, vec![]);
+                    let fl = FileList::new(window.upcast(), vec![]);
                     self.filelist.set(Some(&fl));
                 } else {
                     return Err(Error::InvalidState);
@@ -666,6 +670,7 @@ impl HTMLInputElementMethods for HTMLInputElement {
             },
         }
 
+        self.update_constraint_states();
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
         Ok(())
     }
@@ -900,6 +905,8 @@ fn broadcast_radio_checked(broadcaster: &HTMLInputElement, group: Option<&Atom>)
             if r.Checked() {
                 r.SetChecked(false);
             }
+            // The group now has a checked member.
+            r.upcast::<Element>().update_validity_state();
         }
     }
 
@@ -1024,6 +1031,7 @@ impl HTMLInputElement {
             broadcast_radio_checked(self, self.radio_group_name().as_ref());
         }
 
+        self.update_constraint_states();
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
         //TODO: dispatch change event
     }
@@ -1047,6 +1055,7 @@ impl HTMLInputElement {
         }
         self.textinput.borrow_mut().set_content(self.DefaultValue());
         self.value_dirty.set(false);
+        self.update_constraint_states();
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
@@ -1062,6 +1071,97 @@ impl HTMLInputElement {
         el.set_placeholder_shown_state(has_placeholder && !has_value);
     }
 
+    // https://html.spec.whatwg.org/multipage/#attr-input-required
+    fn is_required(&self) -> bool {
+        match self.input_type() {
+            InputType::Hidden |
+            InputType::Range |
+            InputType::Color |
+            InputType::Submit |
+            InputType::Image |
+            InputType::Reset |
+            InputType::Button => false,
+            _ => self.Required(),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#radio-button-group
+    fn radio_group_has_checked_member(&self) -> bool {
+        if self.Checked() {
+            return true;
+        }
+        let group = self.radio_group_name();
+        match group {
+            None | Some(atom!("")) => return false,
+            _ => {},
+        }
+
+        //TODO: if not in document, use root ancestor instead of document
+        let owner = self.form_owner();
+        let doc = document_from_node(self);
+        let has_checked_member = doc
+            .upcast::<Node>()
+            .query_selector_iter(DOMString::from("input[type=radio]"))
+            .unwrap()
+            .filter_map(DomRoot::downcast::<HTMLInputElement>)
+            .any(|r| r.Checked() && in_same_group(&r, owner.as_deref(), group.as_ref()));
+        has_checked_member
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-input-min
+    // https://html.spec.whatwg.org/multipage/#concept-input-max
+    fn range_limits(&self) -> Option<(Option<f64>, Option<f64>)> {
+        // TODO: The date and time input types have range limitations too.
+        let (default_minimum, default_maximum) = match self.input_type() {
+            InputType::Number => (None, None),
+            InputType::Range => (Some(0.0), Some(100.0)),
+            _ => return None,
+        };
+        let limit = |name: &LocalName| {
+            self.upcast::<Element>()
+                .get_attribute(&ns!(), name)
+                .and_then(|attr| DOMString::from(&**attr.value()).parse_floating_point_number())
+        };
+        match (
+            limit(&local_name!("min")).or(default_minimum),
+            limit(&local_name!("max")).or(default_maximum),
+        ) {
+            (None, None) => None,
+            limits => Some(limits),
+        }
+    }
+
+    /// Updates the states backing the `:required`, `:optional`, `:default`,
+    /// `:in-range`, `:out-of-range`, `:valid` and `:invalid` pseudo-classes.
+    fn update_constraint_states(&self) {
+        let el = self.upcast::<Element>();
+        el.set_required_state(self.is_required());
+
+        // https://html.spec.whatwg.org/multipage/#selector-default
+        let is_default = match self.input_type() {
+            InputType::Checkbox | InputType::Radio => self.DefaultChecked(),
+            // TODO: Submit buttons match when they are the default button of their form.
+            _ => false,
+        };
+        el.set_default_state(is_default);
+
+        // https://html.spec.whatwg.org/multipage/#selector-in-range
+        // https://html.spec.whatwg.org/multipage/#selector-out-of-range
+        let (in_range, out_of_range) =
+            if self.range_limits().is_some() && self.is_instance_validatable() {
+                let out_of_range = self
+                    .validity_flags()
+                    .intersects(ValidationFlags::RANGE_UNDERFLOW | ValidationFlags::RANGE_OVERFLOW);
+                (!out_of_range, out_of_range)
+            } else {
+                (false, false)
+            };
+        el.set_state(ElementState::IN_INRANGE_STATE, in_range);
+        el.set_state(ElementState::IN_OUTOFRANGE_STATE, out_of_range);
+
+        el.update_validity_state();
+    }
+
     // https://html.spec.whatwg.org/multipage/#file-upload-state-(type=file)
     // Select files by invoking UI or by passed in argument
     fn select_files(&self, opt_test_paths: Option<Vec<DOMString>>) {
@@ -1124,8 +1224,9 @@ impl HTMLInputElement {
         if let Some(err) = error {
             debug!("Input file select error: {:?}", err);
         } else {
-            let filelist = FileList::new(&window, files);
+            let filelist = FileList::new(window.upcast(), files);
             self.filelist.set(Some(&filelist));
+            self.update_constraint_states();
 
             target.fire_bubbling_event(atom!("input"));
             target.fire_bubbling_event(atom!("change"));
@@ -1364,7 +1465,7 @@ impl VirtualMethods for HTMLInputElement {
 
                         if new_type == InputType::File {
                             let window = window_from_node(self);
-                            let filelist = FileList::new(&window, vec![]);
+                            let filelist = FileList::new(window.upcast(), vec![]);
                             self.filelist.set(Some(&filelist));
                         }
 
@@ -1497,6 +1598,8 @@ impl VirtualMethods for HTMLInputElement {
             },
             _ => {},
         }
+
+        self.update_constraint_states();
     }
 
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
@@ -1524,6 +1627,7 @@ impl VirtualMethods for HTMLInputElement {
         }
         self.upcast::<Element>()
             .check_ancestors_disabled_state_for_form_control();
+        self.update_constraint_states();
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext) {
@@ -1539,6 +1643,7 @@ impl VirtualMethods for HTMLInputElement {
         } else {
             el.check_disabled_attribute();
         }
+        self.update_constraint_states();
     }
 
     fn handle_event(&self, event: &Event) {
@@ -1594,6 +1699,7 @@ impl VirtualMethods for HTMLInputElement {
                     DispatchInput => {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
+                        self.update_constraint_states();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                         event.mark_as_handled();
                     },
@@ -1634,6 +1740,7 @@ impl VirtualMethods for HTMLInputElement {
                         .textinput
                         .borrow_mut()
                         .handle_compositionend(compositionevent);
+                    self.update_constraint_states();
                     self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                 }
                 event.mark_as_handled();
@@ -1659,11 +1766,62 @@ impl FormControl for HTMLInputElement {
 impl Validatable for HTMLInputElement {
     fn is_instance_validatable(&self) -> bool {
         // https://html.spec.whatwg.org/multipage/#candidate-for-constraint-validation
-        true
+        match self.input_type() {
+            InputType::Hidden | InputType::Reset | InputType::Button => return false,
+            _ => {},
+        }
+        let el = self.upcast::<Element>();
+        if el.disabled_state() || (self.ReadOnly() && self.input_type().is_textual_or_password()) {
+            return false;
+        }
+        // https://html.spec.whatwg.org/multipage/#the-datalist-element:barred-from-constraint-validation
+        !self
+            .upcast::<Node>()
+            .ancestors()
+            .any(|ancestor| ancestor.is::<HTMLDataListElement>())
     }
-    fn validate(&self, _validate_flags: ValidationFlags) -> bool {
-        // call stub methods defined in validityState.rs file here according to the flags set in validate_flags
-        true
+
+    fn validity_flags(&self) -> ValidationFlags {
+        let mut flags = ValidationFlags::empty();
+
+        // https://html.spec.whatwg.org/multipage/#suffering-from-being-missing
+        if self.is_required() {
+            let value_missing = match self.input_type() {
+                InputType::Checkbox => !self.Checked(),
+                InputType::Radio => !self.radio_group_has_checked_member(),
+                InputType::File => self.filelist.get().map_or(true, |fl| fl.Length() == 0),
+                _ => self.textinput.borrow().is_empty(),
+            };
+            if value_missing {
+                flags.insert(ValidationFlags::VALUE_MISSING);
+            }
+        }
+
+        // https://html.spec.whatwg.org/multipage/#suffering-from-an-underflow
+        // https://html.spec.whatwg.org/multipage/#suffering-from-an-overflow
+        // Range inputs never suffer from either, since their value sanitization
+        // algorithm clamps the value to the range.
+        let range_limits = match self.input_type() {
+            InputType::Range => None,
+            _ => self.range_limits(),
+        };
+        if let Some((minimum, maximum)) = range_limits {
+            if let Some(value) = self
+                .textinput
+                .borrow()
+                .get_content()
+                .parse_floating_point_number()
+            {
+                if minimum.map_or(false, |minimum| value < minimum) {
+                    flags.insert(ValidationFlags::RANGE_UNDERFLOW);
+                }
+                if maximum.map_or(false, |maximum| value > maximum) {
+                    flags.insert(ValidationFlags::RANGE_OVERFLOW);
+                }
+            }
+        }
+
+        flags
     }
 }
 
@@ -15,7 +15,7 @@ use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlformelement::{FormControl, HTMLFormElement};
 use crate::dom::node::{window_from_node, Node};
 use crate::dom::validation::Validatable;
-use crate::dom::validitystate::{ValidationFlags, ValidityState};
+use crate::dom::validitystate::ValidityState;
 use crate::dom::virtualmethods::VirtualMethods;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
@@ -104,12 +104,8 @@ impl HTMLObjectElementMethods for HTMLObjectElement {
 
 impl Validatable for HTMLObjectElement {
     fn is_instance_validatable(&self) -> bool {
-        true
-    }
-    fn validate(&self, validate_flags: ValidationFlags) -> bool {
-        if validate_flags.is_empty() {}
-        // Need more flag check for different validation types later
-        true
+        // https://html.spec.whatwg.org/multipage/#the-object-element:barred-from-constraint-validation
+        false
     }
 }
 
@@ -91,6 +91,7 @@ impl HTMLOptionElement {
                 select.pick_option(self);
             }
             select.ask_for_reset();
+            select.update_constraint_states();
         }
     }
 }
@@ -216,6 +217,9 @@ impl VirtualMethods for HTMLOptionElement {
                 }
             },
             &local_name!("selected") => {
+                // https://html.spec.whatwg.org/multipage/#selector-default
+                self.upcast::<Element>()
+                    .set_default_state(mutation.new_value(attr).is_some());
                 match mutation {
                     AttributeMutation::Set(_) => {
                         // https://html.spec.whatwg.org/multipage/#concept-option-selectedness
@@ -256,6 +260,7 @@ impl VirtualMethods for HTMLOptionElement {
             .next()
         {
             select.ask_for_reset();
+            select.update_constraint_states();
         }
 
         let node = self.upcast::<Node>();
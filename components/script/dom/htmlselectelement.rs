@@ -73,7 +73,9 @@ impl HTMLSelectElement {
     ) -> HTMLSelectElement {
         HTMLSelectElement {
             htmlelement: HTMLElement::new_inherited_with_state(
-                ElementState::IN_ENABLED_STATE,
+                ElementState::IN_ENABLED_STATE |
+                    ElementState::IN_OPTIONAL_STATE |
+                    ElementState::IN_VALID_STATE,
                 local_name,
                 prefix,
                 document,
@@ -119,6 +121,7 @@ impl HTMLSelectElement {
             opt.set_dirtiness(false);
         }
         self.ask_for_reset();
+        self.update_constraint_states();
     }
 
     // https://html.spec.whatwg.org/multipage/#ask-for-a-reset
@@ -180,6 +183,25 @@ impl HTMLSelectElement {
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#placeholder-label-option
+    fn placeholder_label_option(&self) -> Option<DomRoot<HTMLOptionElement>> {
+        if self.Multiple() || self.display_size() != 1 {
+            return None;
+        }
+        self.list_of_options().next().filter(|option| {
+            option.Value().is_empty() &&
+                option.upcast::<Node>().GetParentNode().as_deref() == Some(self.upcast::<Node>())
+        })
+    }
+
+    /// Updates the states backing the `:required`, `:optional`, `:valid` and
+    /// `:invalid` pseudo-classes.
+    pub fn update_constraint_states(&self) {
+        let el = self.upcast::<Element>();
+        el.set_required_state(el.has_attribute(&local_name!("required")));
+        el.update_validity_state();
+    }
+
     // https://html.spec.whatwg.org/multipage/#concept-select-size
     fn display_size(&self) -> u32 {
         if self.Size() == 0 {
@@ -323,6 +345,7 @@ impl HTMLSelectElementMethods for HTMLSelectElement {
         for opt in opt_iter {
             opt.set_selectedness(false);
         }
+        self.update_constraint_states();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-select-selectedindex
@@ -349,6 +372,7 @@ impl HTMLSelectElementMethods for HTMLSelectElement {
                 opt.set_selectedness(false);
             }
         }
+        self.update_constraint_states();
     }
 }
 
@@ -379,6 +403,8 @@ impl VirtualMethods for HTMLSelectElement {
             },
             _ => {},
         }
+
+        self.update_constraint_states();
     }
 
     fn bind_to_tree(&self, context: &BindContext) {
@@ -388,6 +414,7 @@ impl VirtualMethods for HTMLSelectElement {
 
         self.upcast::<Element>()
             .check_ancestors_disabled_state_for_form_control();
+        self.update_constraint_states();
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext) {
@@ -432,12 +459,27 @@ impl FormControl for HTMLSelectElement {
 
 impl Validatable for HTMLSelectElement {
     fn is_instance_validatable(&self) -> bool {
-        true
+        // https://html.spec.whatwg.org/multipage/#enabling-and-disabling-form-controls:-the-disabled-attribute:barred-from-constraint-validation
+        !self.upcast::<Element>().disabled_state()
     }
-    fn validate(&self, validate_flags: ValidationFlags) -> bool {
-        if validate_flags.is_empty() {}
-        // Need more flag check for different validation types later
-        true
+
+    fn validity_flags(&self) -> ValidationFlags {
+        // https://html.spec.whatwg.org/multipage/#the-select-element:suffering-from-being-missing
+        if !self
+            .upcast::<Element>()
+            .has_attribute(&local_name!("required"))
+        {
+            return ValidationFlags::empty();
+        }
+        let value_missing = match self.list_of_options().find(|option| option.Selected()) {
+            None => true,
+            Some(selected) => self.placeholder_label_option().as_ref() == Some(&selected),
+        };
+        if value_missing {
+            ValidationFlags::VALUE_MISSING
+        } else {
+            ValidationFlags::empty()
+        }
     }
 }
 
@@ -124,6 +124,7 @@ impl HTMLStyleElement {
         );
 
         let sheet = Arc::new(sheet);
+        doc.set_stylesheet_source(&sheet, &data);
 
         // No subresource loads were triggered, queue load event
         if self.pending_loads.get() == 0 {
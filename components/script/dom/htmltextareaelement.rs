@@ -30,6 +30,7 @@ use crate::dom::node::{
 use crate::dom::nodelist::NodeList;
 use crate::dom::textcontrol::{TextControlElement, TextControlSelection};
 use crate::dom::validation::Validatable;
+use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::textinput::{
     Direction, KeyReaction, Lines, SelectionDirection, TextInput, UTF16CodeUnits, UTF8Bytes,
@@ -137,7 +138,10 @@ impl HTMLTextAreaElement {
             .clone();
         HTMLTextAreaElement {
             htmlelement: HTMLElement::new_inherited_with_state(
-                ElementState::IN_ENABLED_STATE | ElementState::IN_READ_WRITE_STATE,
+                ElementState::IN_ENABLED_STATE |
+                    ElementState::IN_READ_WRITE_STATE |
+                    ElementState::IN_OPTIONAL_STATE |
+                    ElementState::IN_VALID_STATE,
                 local_name,
                 prefix,
                 document,
@@ -177,6 +181,14 @@ impl HTMLTextAreaElement {
         let el = self.upcast::<Element>();
         el.set_placeholder_shown_state(has_placeholder && !has_value);
     }
+
+    /// Updates the states backing the `:required`, `:optional`, `:valid` and
+    /// `:invalid` pseudo-classes.
+    fn update_constraint_states(&self) {
+        let el = self.upcast::<Element>();
+        el.set_required_state(self.Required());
+        el.update_validity_state();
+    }
 }
 
 impl TextControlElement for HTMLTextAreaElement {
@@ -305,7 +317,9 @@ impl HTMLTextAreaElementMethods for HTMLTextAreaElement {
             // Step 4
             textinput.clear_selection_to_limit(Direction::Forward);
         }
+        drop(textinput);
 
+        self.update_constraint_states();
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
@@ -382,9 +396,9 @@ impl HTMLTextAreaElementMethods for HTMLTextAreaElement {
 impl HTMLTextAreaElement {
     pub fn reset(&self) {
         // https://html.spec.whatwg.org/multipage/#the-textarea-element:concept-form-reset-control
-        let mut textinput = self.textinput.borrow_mut();
-        textinput.set_content(self.DefaultValue());
+        self.textinput.borrow_mut().set_content(self.DefaultValue());
         self.value_dirty.set(false);
+        self.update_constraint_states();
     }
 
     #[allow(unrooted_must_root)]
@@ -471,6 +485,8 @@ impl VirtualMethods for HTMLTextAreaElement {
             },
             _ => {},
         }
+
+        self.update_constraint_states();
     }
 
     fn bind_to_tree(&self, context: &BindContext) {
@@ -480,6 +496,7 @@ impl VirtualMethods for HTMLTextAreaElement {
 
         self.upcast::<Element>()
             .check_ancestors_disabled_state_for_form_control();
+        self.update_constraint_states();
     }
 
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
@@ -512,6 +529,7 @@ impl VirtualMethods for HTMLTextAreaElement {
         } else {
             el.check_disabled_attribute();
         }
+        self.update_constraint_states();
     }
 
     // The cloning steps for textarea elements must propagate the raw value
@@ -527,8 +545,10 @@ impl VirtualMethods for HTMLTextAreaElement {
         }
         let el = copy.downcast::<HTMLTextAreaElement>().unwrap();
         el.value_dirty.set(self.value_dirty.get());
-        let mut textinput = el.textinput.borrow_mut();
-        textinput.set_content(self.textinput.borrow().get_content());
+        el.textinput
+            .borrow_mut()
+            .set_content(self.textinput.borrow().get_content());
+        el.update_constraint_states();
     }
 
     fn children_changed(&self, mutation: &ChildrenMutation) {
@@ -560,6 +580,7 @@ impl VirtualMethods for HTMLTextAreaElement {
                     KeyReaction::DispatchInput => {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
+                        self.update_constraint_states();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                         event.mark_as_handled();
                     },
@@ -596,6 +617,7 @@ impl VirtualMethods for HTMLTextAreaElement {
                         .textinput
                         .borrow_mut()
                         .handle_compositionend(compositionevent);
+                    self.update_constraint_states();
                     self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                 }
                 event.mark_as_handled();
@@ -625,4 +647,18 @@ impl FormControl for HTMLTextAreaElement {
     }
 }
 
-impl Validatable for HTMLTextAreaElement {}
+impl Validatable for HTMLTextAreaElement {
+    fn is_instance_validatable(&self) -> bool {
+        // https://html.spec.whatwg.org/multipage/#the-textarea-element:barred-from-constraint-validation
+        !(self.upcast::<Element>().disabled_state() || self.ReadOnly())
+    }
+
+    fn validity_flags(&self) -> ValidationFlags {
+        // https://html.spec.whatwg.org/multipage/#the-textarea-element:suffering-from-being-missing
+        if self.Required() && self.textinput.borrow().is_empty() {
+            ValidationFlags::VALUE_MISSING
+        } else {
+            ValidationFlags::empty()
+        }
+    }
+}
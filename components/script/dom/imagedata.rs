@@ -7,6 +7,8 @@ use crate::dom::bindings::codegen::Bindings::ImageDataBinding::ImageDataMethods;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::serializable::Serializable;
+use crate::dom::bindings::structuredclone::{StructuredCloneReader, StructuredCloneWriter};
 use crate::dom::globalscope::GlobalScope;
 use crate::script_runtime::JSContext;
 use dom_struct::dom_struct;
@@ -178,6 +180,26 @@ impl ImageData {
     }
 }
 
+impl Serializable for ImageData {
+    /// <https://html.spec.whatwg.org/multipage/#the-imagedata-interface:serialization-steps>
+    #[allow(unsafe_code)]
+    fn serialize(&self, writer: &StructuredCloneWriter) -> Result<(), ()> {
+        writer.write_u32_pair(self.width, self.height);
+        writer.write_slice(unsafe { self.as_slice() });
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#the-imagedata-interface:deserialization-steps>
+    fn deserialize(
+        owner: &GlobalScope,
+        reader: &StructuredCloneReader,
+    ) -> Result<DomRoot<Self>, ()> {
+        let (width, height) = reader.read_u32_pair();
+        let data = reader.read_bytes();
+        ImageData::new(owner, width, height, Some(data)).map_err(|_| ())
+    }
+}
+
 impl ImageDataMethods for ImageData {
     // https://html.spec.whatwg.org/multipage/#dom-imagedata-width
     fn Width(&self) -> u32 {
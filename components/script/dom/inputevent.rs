@@ -17,6 +17,7 @@ pub struct InputEvent {
     uievent: UIEvent,
     data: Option<DOMString>,
     is_composing: bool,
+    input_type: DOMString,
 }
 
 impl InputEvent {
@@ -29,12 +30,14 @@ impl InputEvent {
         detail: i32,
         data: Option<DOMString>,
         is_composing: bool,
+        input_type: DOMString,
     ) -> DomRoot<InputEvent> {
         let ev = reflect_dom_object(
             Box::new(InputEvent {
                 uievent: UIEvent::new_inherited(),
                 data: data,
                 is_composing: is_composing,
+                input_type: input_type,
             }),
             window,
             InputEventBinding::Wrap,
@@ -58,6 +61,7 @@ impl InputEvent {
             init.parent.detail,
             init.data.clone(),
             init.isComposing,
+            init.inputType.clone(),
         );
         Ok(event)
     }
@@ -74,6 +78,11 @@ impl InputEventMethods for InputEvent {
         self.is_composing
     }
 
+    // https://w3c.github.io/input-events/#dom-inputevent-inputtype
+    fn InputType(&self) -> DOMString {
+        self.input_type.clone()
+    }
+
     // https://dom.spec.whatwg.org/#dom-event-istrusted
     fn IsTrusted(&self) -> bool {
         self.uievent.IsTrusted()
@@ -0,0 +1,764 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IntersectionObserverBinding::{
+    IntersectionObserverCallback, IntersectionObserverInit, IntersectionObserverMethods, Wrap,
+};
+use crate::dom::bindings::codegen::Bindings::IntersectionObserverEntryBinding::IntersectionObserverEntryMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::{
+    DOMHighResTimeStamp, PerformanceMethods,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::{DoubleOrDoubleSequence, ElementOrDocument};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::intersectionobserverentry::IntersectionObserverEntry;
+use crate::dom::node::{window_from_node, Node, ShadowIncluding};
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use crate::task_source::TaskSource;
+use app_units::Au;
+use cssparser::{Parser, ParserInput, Token};
+use dom_struct::dom_struct;
+use euclid::default::{Point2D, Rect, Size2D, Vector2D};
+use js::jsval::JSVal;
+use script_layout_interface::rpc::ClippedBox;
+use std::cell::Cell;
+use std::cmp::{max, min};
+use std::fmt;
+use std::rc::Rc;
+use style::context::QuirksMode;
+use style::dom::OpaqueNode;
+use style::parser::{Parse, ParserContext};
+use style::stylesheets::CssRuleType;
+use style::values::computed::{CSSPixelLength, LengthPercentage, Percentage};
+use style::values::generics::rect::Rect as SideValues;
+use style_traits::values::SequenceWriter;
+use style_traits::{CssWriter, ParseError, ParsingMode, StyleParseErrorKind, ToCss};
+
+/// How far outside of the viewport images that are lazy loaded start loading, so that they
+/// are usually ready by the time they are scrolled into view.
+const LAZY_LOAD_ROOT_MARGIN: &'static str = "1250px";
+
+/// <https://w3c.github.io/IntersectionObserver/#intersection-observer-interface>
+#[dom_struct]
+pub struct IntersectionObserver {
+    reflector_: Reflector,
+    /// The callback invoked with the queued entries, or `None` for the lazy load
+    /// intersection observer of a document, which handles its entries itself.
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    callback: Option<Rc<IntersectionObserverCallback>>,
+    /// The intersection root, or `None` for the implicit root.
+    root: Option<Dom<Node>>,
+    root_margin: RootMargin,
+    /// The thresholds, sorted in increasing order.
+    thresholds: Vec<f64>,
+    /// <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-observationtargets-slot>
+    observation_targets: DomRefCell<Vec<IntersectionObservation>>,
+    /// <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-queuedentries-slot>
+    queued_entries: DomRefCell<Vec<Dom<IntersectionObserverEntry>>>,
+}
+
+/// What an observer remembers about one of its targets between two runs of the update
+/// intersection observations steps.
+///
+/// <https://w3c.github.io/IntersectionObserver/#intersectionobserverregistration>
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct IntersectionObservation {
+    target: Dom<Element>,
+    /// The index of the threshold last crossed, or -1 before the first update.
+    previous_threshold_index: Cell<i32>,
+    previous_is_intersecting: Cell<bool>,
+}
+
+/// Where a target is, relative to the intersection root.
+struct IntersectionGeometry {
+    /// The root intersection rectangle, relative to the viewport of the root's document, or
+    /// `None` if it must not be exposed to the target's document.
+    root_bounds: Option<Rect<Au>>,
+    /// The border box of the target, relative to the viewport of its document.
+    bounding_client_rect: Rect<Au>,
+    /// The part of the target that isn't clipped by the root or by any scroll container in
+    /// between, relative to the viewport of the target's document, or `None` if the target
+    /// isn't intersecting the root.
+    intersection_rect: Option<Rect<Au>>,
+}
+
+impl IntersectionObserver {
+    fn new_inherited(
+        callback: Option<Rc<IntersectionObserverCallback>>,
+        root: Option<&Node>,
+        root_margin: RootMargin,
+        thresholds: Vec<f64>,
+    ) -> IntersectionObserver {
+        IntersectionObserver {
+            reflector_: Reflector::new(),
+            callback,
+            root: root.map(Dom::from_ref),
+            root_margin,
+            thresholds,
+            observation_targets: DomRefCell::new(vec![]),
+            queued_entries: DomRefCell::new(vec![]),
+        }
+    }
+
+    fn new(
+        window: &Window,
+        callback: Option<Rc<IntersectionObserverCallback>>,
+        root: Option<&Node>,
+        root_margin: RootMargin,
+        thresholds: Vec<f64>,
+    ) -> DomRoot<IntersectionObserver> {
+        let observer = IntersectionObserver::new_inherited(callback, root, root_margin, thresholds);
+        reflect_dom_object(Box::new(observer), window, Wrap)
+    }
+
+    /// Creates the lazy load intersection observer of the document of `window`.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer>
+    pub fn new_lazy_load_observer(window: &Window) -> DomRoot<IntersectionObserver> {
+        let root_margin = parse_root_margin(window, LAZY_LOAD_ROOT_MARGIN)
+            .expect("The lazy load root margin should be valid");
+        IntersectionObserver::new(window, None, None, root_margin, vec![0.])
+    }
+
+    // https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-intersectionobserver
+    pub fn Constructor(
+        window: &Window,
+        callback: Rc<IntersectionObserverCallback>,
+        options: &IntersectionObserverInit,
+    ) -> Fallible<DomRoot<IntersectionObserver>> {
+        // Step 2-3
+        let root_margin = parse_root_margin(window, &options.rootMargin).ok_or(Error::Syntax)?;
+
+        // Step 4-7
+        let mut thresholds = match options.threshold {
+            DoubleOrDoubleSequence::Double(threshold) => vec![*threshold],
+            DoubleOrDoubleSequence::DoubleSequence(ref thresholds) => {
+                thresholds.iter().map(|threshold| **threshold).collect()
+            },
+        };
+        if thresholds
+            .iter()
+            .any(|threshold| *threshold < 0. || *threshold > 1.)
+        {
+            return Err(Error::Range(
+                "Thresholds must be between 0 and 1".to_owned(),
+            ));
+        }
+        thresholds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        if thresholds.is_empty() {
+            thresholds.push(0.);
+        }
+
+        // Step 1, 8-10
+        let root = options.root.as_ref().map(|root| match *root {
+            ElementOrDocument::Element(ref element) => DomRoot::from_ref(element.upcast::<Node>()),
+            ElementOrDocument::Document(ref document) => {
+                DomRoot::from_ref(document.upcast::<Node>())
+            },
+        });
+        Ok(IntersectionObserver::new(
+            window,
+            Some(callback),
+            root.as_deref(),
+            root_margin,
+            thresholds,
+        ))
+    }
+
+    fn document(&self) -> DomRoot<Document> {
+        self.global().as_window().Document()
+    }
+
+    /// <https://w3c.github.io/IntersectionObserver/#run-the-update-intersection-observations-steps>
+    pub fn update_intersection_observations(document: &Document) {
+        let observers = document.intersection_observers();
+        if observers.is_empty() {
+            return;
+        }
+        let time = document.window().Performance().Now();
+        // Step 1-2
+        for observer in observers {
+            observer.update_observations(document, time);
+        }
+    }
+
+    /// Step 2 of
+    /// <https://w3c.github.io/IntersectionObserver/#run-the-update-intersection-observations-steps>
+    fn update_observations(&self, document: &Document, time: DOMHighResTimeStamp) {
+        let targets: Vec<DomRoot<Element>> = self
+            .observation_targets
+            .borrow()
+            .iter()
+            .map(|observation| DomRoot::from_ref(&*observation.target))
+            .collect();
+        let geometries = self.compute_geometries(&targets);
+
+        // Step 2.3
+        for (index, (target, geometry)) in targets.iter().zip(geometries).enumerate() {
+            // Step 2.3.1-2.3.11
+            let is_intersecting = geometry.intersection_rect.is_some();
+            let intersection_rect = geometry.intersection_rect.unwrap_or_else(Rect::zero);
+
+            // Step 2.3.12
+            let target_area = area(&geometry.bounding_client_rect);
+            let intersection_ratio = if target_area > 0. {
+                area(&intersection_rect) / target_area
+            } else if is_intersecting {
+                1.
+            } else {
+                0.
+            };
+
+            // Step 2.3.13
+            let threshold_index = self
+                .thresholds
+                .iter()
+                .position(|threshold| *threshold > intersection_ratio)
+                .unwrap_or(self.thresholds.len()) as i32;
+
+            // Step 2.3.14-2.3.16, 2.3.18
+            let changed = {
+                let observations = self.observation_targets.borrow();
+                let observation = &observations[index];
+                let changed = observation.previous_threshold_index.get() != threshold_index ||
+                    observation.previous_is_intersecting.get() != is_intersecting;
+                observation.previous_threshold_index.set(threshold_index);
+                observation.previous_is_intersecting.set(is_intersecting);
+                changed
+            };
+
+            // Step 2.3.17
+            if changed {
+                let entry = IntersectionObserverEntry::new(
+                    document.window(),
+                    time,
+                    geometry.root_bounds.as_ref().map(to_px_rect),
+                    to_px_rect(&geometry.bounding_client_rect),
+                    to_px_rect(&intersection_rect),
+                    is_intersecting,
+                    intersection_ratio,
+                    target,
+                );
+                self.queue_entry(&entry);
+            }
+        }
+    }
+
+    /// <https://w3c.github.io/IntersectionObserver/#queue-an-intersectionobserverentry>
+    fn queue_entry(&self, entry: &IntersectionObserverEntry) {
+        self.queued_entries.borrow_mut().push(Dom::from_ref(entry));
+        IntersectionObserver::queue_intersection_observer_task(&self.document());
+    }
+
+    /// <https://w3c.github.io/IntersectionObserver/#queue-intersection-observer-task>
+    fn queue_intersection_observer_task(document: &Document) {
+        // Step 1-2
+        if document.intersection_observer_task_queued() {
+            return;
+        }
+        // Step 3
+        document.set_intersection_observer_task_queued(true);
+        // Step 4
+        let window = document.window();
+        let document = Trusted::new(document);
+        let _ = window.task_manager().dom_manipulation_task_source().queue(
+            task!(notify_intersection_observers: move || {
+                IntersectionObserver::notify_intersection_observers(&document.root());
+            }),
+            window.upcast(),
+        );
+    }
+
+    /// <https://w3c.github.io/IntersectionObserver/#notify-intersection-observers-algo>
+    fn notify_intersection_observers(document: &Document) {
+        // Step 1
+        document.set_intersection_observer_task_queued(false);
+        // Step 2-3
+        for observer in document.intersection_observers() {
+            // Step 3.1-3.4
+            let queue: Vec<DomRoot<IntersectionObserverEntry>> = observer
+                .queued_entries
+                .borrow_mut()
+                .drain(..)
+                .map(|entry| DomRoot::from_ref(&*entry))
+                .collect();
+            if queue.is_empty() {
+                continue;
+            }
+            // Step 3.5
+            match observer.callback {
+                Some(ref callback) => {
+                    let _ =
+                        callback.Call_(&*observer, queue, &*observer, ExceptionHandling::Report);
+                },
+                None => observer.resume_lazy_loads(queue),
+            }
+        }
+    }
+
+    /// The callback of the lazy load intersection observer, which starts loading the images
+    /// that are about to come into view.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer>
+    fn resume_lazy_loads(&self, entries: Vec<DomRoot<IntersectionObserverEntry>>) {
+        for entry in entries {
+            if !entry.IsIntersecting() {
+                continue;
+            }
+            let target = entry.Target();
+            self.Unobserve(&target);
+            if let Some(image) = target.downcast::<HTMLImageElement>() {
+                image.resume_lazy_load();
+            }
+        }
+    }
+
+    /// Computes where each of the targets is relative to the intersection root, querying
+    /// layout once per document the targets are in.
+    fn compute_geometries(&self, targets: &[DomRoot<Element>]) -> Vec<IntersectionGeometry> {
+        let mut geometries: Vec<Option<IntersectionGeometry>> =
+            targets.iter().map(|_| None).collect();
+        for index in 0..targets.len() {
+            if geometries[index].is_some() {
+                continue;
+            }
+            let document = targets[index].upcast::<Node>().owner_doc();
+            let indices: Vec<usize> = (index..targets.len())
+                .filter(|i| targets[*i].upcast::<Node>().owner_doc() == document)
+                .collect();
+
+            // The boxes of an element root are only needed for the targets in its document.
+            let root_element = self
+                .root
+                .as_ref()
+                .and_then(|root| root.downcast::<Element>())
+                .filter(|root| root.upcast::<Node>().owner_doc() == document);
+
+            let mut nodes: Vec<&Node> = indices
+                .iter()
+                .map(|i| targets[*i].upcast::<Node>())
+                .collect();
+            if let Some(root) = root_element {
+                nodes.push(root.upcast());
+            }
+            let window = document.window();
+            let mut boxes = window.clipped_boxes_query(&nodes);
+            let root_box = match root_element {
+                Some(_) => boxes.pop().unwrap(),
+                None => None,
+            };
+
+            for (i, target_box) in indices.into_iter().zip(boxes) {
+                geometries[i] = Some(
+                    self.compute_geometry(
+                        window,
+                        &targets[i],
+                        target_box.as_ref(),
+                        root_element
+                            .and_then(|root| root_box.as_ref().map(|root_box| (root, root_box))),
+                    ),
+                );
+            }
+        }
+        geometries.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// Computes where `target` is relative to the intersection root, given the boxes of
+    /// the target and of the root, if the root is an element in the same document.
+    ///
+    /// <https://w3c.github.io/IntersectionObserver/#calculate-intersection-rect-algo>
+    fn compute_geometry(
+        &self,
+        window: &Window,
+        target: &Element,
+        target_box: Option<&ClippedBox>,
+        root_element: Option<(&Element, &ClippedBox)>,
+    ) -> IntersectionGeometry {
+        let bounding_client_rect = target_box.map_or(Rect::zero(), |target_box| {
+            target_box
+                .border_box
+                .translate(client_offset(window, &target_box.clips))
+        });
+        let (root_bounds, intersection_rect) = match self.root {
+            None => self.intersect_with_viewport(window, target_box, true),
+            Some(ref root) => match root.downcast::<Document>() {
+                Some(root) if root == &*window.Document() => {
+                    self.intersect_with_viewport(window, target_box, false)
+                },
+                // The targets of a document root that aren't in that document never
+                // intersect it.
+                Some(_) => (None, None),
+                None => match root_element {
+                    Some((root, root_box)) => {
+                        self.intersect_with_element(window, target, target_box, root, root_box)
+                    },
+                    // The root isn't rendered, or isn't in the same document as the target.
+                    None => (None, None),
+                },
+            },
+        };
+        IntersectionGeometry {
+            root_bounds,
+            bounding_client_rect,
+            intersection_rect,
+        }
+    }
+
+    /// Intersects `target_box` with the viewport of its document, and with the viewports of
+    /// its ancestor documents if `through_ancestor_documents` is set, for observers with a
+    /// document root or the implicit root. Returns the root bounds, and the intersection
+    /// relative to the viewport of `window`.
+    fn intersect_with_viewport(
+        &self,
+        window: &Window,
+        target_box: Option<&ClippedBox>,
+        through_ancestor_documents: bool,
+    ) -> (Option<Rect<Au>>, Option<Rect<Au>>) {
+        // The part of the target that is visible so far, and the offset from the viewport of
+        // `window` to the viewport of `current_window` that it is relative to.
+        let mut rect = target_box.and_then(|target_box| {
+            clip_to_scroll_containers(window, target_box.border_box, &target_box.clips, &[])
+                .map(|rect| rect.translate(-window.current_viewport().origin.to_vector()))
+        });
+        let mut offset = Vector2D::zero();
+        let mut current_window = DomRoot::from_ref(window);
+
+        while through_ancestor_documents {
+            let window_proxy = current_window.window_proxy();
+            // The container of a document in another script thread can't be queried, so
+            // the viewport of that document stands in for the top-level one.
+            let frame_element = match window_proxy.frame_element() {
+                Some(frame_element) => frame_element,
+                None => break,
+            };
+            rect = rect.and_then(|rect| {
+                edge_inclusive_intersection(&rect, &viewport_rect(&current_window))
+            });
+
+            let parent_window = window_from_node(frame_element);
+            let frame_box = parent_window
+                .clipped_boxes_query(&[frame_element.upcast::<Node>()])
+                .pop()
+                .unwrap();
+            match frame_box {
+                Some(frame_box) => {
+                    let content_origin = frame_box.content_box.origin.to_vector();
+                    rect = rect.and_then(|rect| {
+                        clip_to_scroll_containers(
+                            &parent_window,
+                            rect.translate(content_origin),
+                            &frame_box.clips,
+                            &[],
+                        )
+                        .map(|rect| {
+                            rect.translate(-parent_window.current_viewport().origin.to_vector())
+                        })
+                    });
+                    offset =
+                        offset + content_origin + client_offset(&parent_window, &frame_box.clips);
+                },
+                // A document whose container isn't rendered isn't rendered either.
+                None => rect = None,
+            }
+            current_window = parent_window;
+        }
+
+        // The root margin only applies to targets that are same origin-domain with the root.
+        let root_document = current_window.Document();
+        let exposes_root = *current_window == *window ||
+            (current_window.is_top_level() &&
+                window
+                    .Document()
+                    .origin()
+                    .same_origin_domain(root_document.origin()));
+        let mut root_rect = viewport_rect(&current_window);
+        if exposes_root {
+            root_rect = self.expand_by_root_margin(&root_rect);
+        }
+        let intersection_rect = rect
+            .and_then(|rect| edge_inclusive_intersection(&rect, &root_rect))
+            .map(|rect| rect.translate(-offset));
+        let root_bounds = if exposes_root { Some(root_rect) } else { None };
+        (root_bounds, intersection_rect)
+    }
+
+    /// Intersects `target_box` with the box of `root`, an element in the same document.
+    /// Returns the root bounds, and the intersection relative to the viewport of `window`.
+    fn intersect_with_element(
+        &self,
+        window: &Window,
+        target: &Element,
+        target_box: Option<&ClippedBox>,
+        root: &Element,
+        root_box: &ClippedBox,
+    ) -> (Option<Rect<Au>>, Option<Rect<Au>>) {
+        let root_node = root.upcast::<Node>();
+        let (root_scroll_offset, root_rect) = if root_box.clips_overflow {
+            (
+                scroll_offset(window, root_node.to_opaque()),
+                root_box.padding_box,
+            )
+        } else {
+            (Vector2D::zero(), root_box.border_box)
+        };
+        let root_rect = self.expand_by_root_margin(&root_rect);
+        let root_offset = client_offset(window, &root_box.clips);
+
+        // Only the descendants of the root in the containing block chain can intersect it.
+        let is_descendant = target
+            .upcast::<Node>()
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .skip(1)
+            .any(|ancestor| &*ancestor == root_node);
+        let target_box = match target_box {
+            Some(target_box) if is_descendant => target_box,
+            _ => return (Some(root_rect.translate(root_offset)), None),
+        };
+
+        // The scroll containers from the root out clip the root as much as the target,
+        // and don't clip the root intersection rectangle.
+        let root_and_ancestors: Vec<OpaqueNode> = root_node
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .map(|node| node.to_opaque())
+            .collect();
+        let intersection_rect = clip_to_scroll_containers(
+            window,
+            target_box.border_box,
+            &target_box.clips,
+            &root_and_ancestors,
+        )
+        .and_then(|rect| {
+            edge_inclusive_intersection(&rect.translate(-root_scroll_offset), &root_rect)
+        })
+        .map(|rect| rect.translate(root_offset));
+        (Some(root_rect.translate(root_offset)), intersection_rect)
+    }
+
+    /// Expands the root intersection rectangle by the root margin.
+    fn expand_by_root_margin(&self, rect: &Rect<Au>) -> Rect<Au> {
+        let margin = &self.root_margin.0;
+        let top = margin.0.to_used_value(rect.size.height);
+        let right = margin.1.to_used_value(rect.size.width);
+        let bottom = margin.2.to_used_value(rect.size.height);
+        let left = margin.3.to_used_value(rect.size.width);
+        Rect::new(
+            Point2D::new(rect.origin.x - left, rect.origin.y - top),
+            Size2D::new(
+                rect.size.width + left + right,
+                rect.size.height + top + bottom,
+            ),
+        )
+    }
+}
+
+impl IntersectionObserverMethods for IntersectionObserver {
+    // https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-root
+    fn GetRoot(&self) -> Option<ElementOrDocument> {
+        self.root
+            .as_ref()
+            .map(|root| match root.downcast::<Document>() {
+                Some(document) => ElementOrDocument::Document(DomRoot::from_ref(document)),
+                None => ElementOrDocument::Element(DomRoot::from_ref(
+                    root.downcast::<Element>().unwrap(),
+                )),
+            })
+    }
+
+    // https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-rootmargin
+    fn RootMargin(&self) -> DOMString {
+        DOMString::from(self.root_margin.to_css_string())
+    }
+
+    // https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-thresholds
+    fn Thresholds(&self, cx: JSContext) -> JSVal {
+        to_frozen_array(&self.thresholds, cx)
+    }
+
+    // https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-observe
+    fn Observe(&self, target: &Element) {
+        // Step 1
+        if self
+            .observation_targets
+            .borrow()
+            .iter()
+            .any(|observation| &*observation.target == target)
+        {
+            return;
+        }
+        // Step 2-4
+        self.observation_targets
+            .borrow_mut()
+            .push(IntersectionObservation {
+                target: Dom::from_ref(target),
+                previous_threshold_index: Cell::new(-1),
+                previous_is_intersecting: Cell::new(false),
+            });
+        self.document().add_intersection_observer(self);
+    }
+
+    // https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-unobserve
+    fn Unobserve(&self, target: &Element) {
+        let mut observation_targets = self.observation_targets.borrow_mut();
+        observation_targets.retain(|observation| &*observation.target != target);
+
+        if observation_targets.is_empty() {
+            self.document().remove_intersection_observer(self);
+        }
+    }
+
+    // https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-disconnect
+    fn Disconnect(&self) {
+        self.observation_targets.borrow_mut().clear();
+        self.document().remove_intersection_observer(self);
+    }
+
+    // https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-takerecords
+    fn TakeRecords(&self) -> Vec<DomRoot<IntersectionObserverEntry>> {
+        self.queued_entries
+            .borrow_mut()
+            .drain(..)
+            .map(|entry| DomRoot::from_ref(&*entry))
+            .collect()
+    }
+}
+
+/// The margins that grow or shrink the root intersection rectangle, as px lengths or
+/// percentages of the size of the root.
+#[derive(Clone, MallocSizeOf)]
+pub struct RootMargin(SideValues<LengthPercentage>);
+
+unsafe_no_jsmanaged_fields!(RootMargin);
+
+impl Parse for RootMargin {
+    /// Only bare px or percentage values are allowed, not other length units or `calc()`.
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let sides = SideValues::parse_with(context, input, |_, input| {
+            let location = input.current_source_location();
+            let value = match *input.next()? {
+                Token::Dimension {
+                    value, ref unit, ..
+                } if unit.eq_ignore_ascii_case("px") => {
+                    Ok(LengthPercentage::new(CSSPixelLength::new(value), None))
+                },
+                Token::Percentage { unit_value, .. } => {
+                    Ok(LengthPercentage::new_percent(Percentage(unit_value)))
+                },
+                _ => Err(()),
+            };
+            value.map_err(|()| location.new_custom_error(StyleParseErrorKind::UnspecifiedError))
+        })?;
+        Ok(RootMargin(sides))
+    }
+}
+
+// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-rootmargin
+impl ToCss for RootMargin {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        // All four sides are listed, even when they are equal.
+        let mut writer = SequenceWriter::new(dest, " ");
+        let sides = &self.0;
+        writer.item(&sides.0)?;
+        writer.item(&sides.1)?;
+        writer.item(&sides.2)?;
+        writer.item(&sides.3)
+    }
+}
+
+/// <https://w3c.github.io/IntersectionObserver/#parse-a-root-margin>
+fn parse_root_margin(window: &Window, margin: &str) -> Option<RootMargin> {
+    let mut input = ParserInput::new(margin);
+    let mut parser = Parser::new(&mut input);
+    let url = window.get_url();
+    let context = ParserContext::new_for_cssom(
+        &url,
+        Some(CssRuleType::Style),
+        ParsingMode::DEFAULT,
+        QuirksMode::NoQuirks,
+        None,
+        None,
+    );
+    parser
+        .parse_entirely(|input| RootMargin::parse(&context, input))
+        .ok()
+}
+
+/// Intersects two rectangles, counting rectangles that only share an edge as intersecting.
+fn edge_inclusive_intersection(a: &Rect<Au>, b: &Rect<Au>) -> Option<Rect<Au>> {
+    let origin = Point2D::new(max(a.min_x(), b.min_x()), max(a.min_y(), b.min_y()));
+    let corner = Point2D::new(min(a.max_x(), b.max_x()), min(a.max_y(), b.max_y()));
+    if corner.x < origin.x || corner.y < origin.y {
+        return None;
+    }
+    Some(Rect::new(
+        origin,
+        Size2D::new(corner.x - origin.x, corner.y - origin.y),
+    ))
+}
+
+/// Clips `rect` by each of the scroll containers of `clips`, from the innermost one out,
+/// moving it along with the scrolled contents of each of them. Stops at the first scroll
+/// container established by one of the `stop_at` nodes.
+fn clip_to_scroll_containers(
+    window: &Window,
+    rect: Rect<Au>,
+    clips: &[(OpaqueNode, Rect<Au>)],
+    stop_at: &[OpaqueNode],
+) -> Option<Rect<Au>> {
+    let mut rect = rect;
+    for &(node, ref clip) in clips {
+        if stop_at.contains(&node) {
+            break;
+        }
+        rect = edge_inclusive_intersection(&rect.translate(-scroll_offset(window, node)), clip)?;
+    }
+    Some(rect)
+}
+
+/// The offset from the coordinate space of the document of `window` to its viewport, for a
+/// box in the given scroll containers.
+fn client_offset(window: &Window, clips: &[(OpaqueNode, Rect<Au>)]) -> Vector2D<Au> {
+    clips.iter().fold(
+        -window.current_viewport().origin.to_vector(),
+        |offset, &(node, _)| offset - scroll_offset(window, node),
+    )
+}
+
+fn scroll_offset(window: &Window, node: OpaqueNode) -> Vector2D<Au> {
+    let offset = window.scroll_offset_of_opaque_node(node);
+    Vector2D::new(Au::from_f32_px(offset.x), Au::from_f32_px(offset.y))
+}
+
+fn viewport_rect(window: &Window) -> Rect<Au> {
+    Rect::new(Point2D::zero(), window.current_viewport().size)
+}
+
+fn area(rect: &Rect<Au>) -> f64 {
+    rect.size.width.to_f64_px() * rect.size.height.to_f64_px()
+}
+
+fn to_px_rect(rect: &Rect<Au>) -> Rect<f64> {
+    Rect::new(
+        Point2D::new(rect.origin.x.to_f64_px(), rect.origin.y.to_f64_px()),
+        Size2D::new(rect.size.width.to_f64_px(), rect.size.height.to_f64_px()),
+    )
+}
@@ -0,0 +1,647 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animationeffect::{parse_easing, AnimationEffect, Timing};
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::KeyframeEffectBinding;
+use crate::dom::bindings::codegen::Bindings::KeyframeEffectBinding::KeyframeEffectMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrKeyframeEffectOptions;
+use crate::dom::bindings::conversions::jsid_to_string;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::bindings::utils::{get_dictionary_property, set_dictionary_property};
+use crate::dom::element::Element;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::conversions::StringificationBehavior;
+use js::conversions::{ConversionResult, FromJSValConvertible, ToJSValConvertible};
+use js::jsapi::{Heap, JSObject, JS_NewPlainObject, PropertyDescriptor};
+use js::jsapi::{JSITER_OWNONLY, JSPROP_ENUMERATE};
+use js::jsval::{NullValue, ObjectValue, UndefinedValue};
+use js::rust::wrappers::{GetPropertyKeys, JS_GetOwnPropertyDescriptorById, JS_IsArrayObject};
+use js::rust::{HandleObject, HandleValue, IdVector, MutableHandleValue};
+use servo_arc::Arc;
+use std::ptr::NonNull;
+use style::animation::EffectKeyframe;
+use style::properties::{parse_one_declaration_into, SourcePropertyDeclaration};
+use style::properties::{Importance, PropertyDeclarationBlock, PropertyId};
+use style::shared_lock::Locked;
+use style::values::computed::TimingFunction as ComputedTimingFunction;
+use style_traits::{ParsingMode, ToCss};
+
+/// A keyframe of a keyframe effect, once its offset has been computed.
+///
+/// <https://drafts.csswg.org/web-animations/#keyframe>
+#[derive(JSTraceable, MallocSizeOf)]
+struct Keyframe {
+    /// The offset specified by script, if any.
+    offset: Option<f64>,
+    computed_offset: f64,
+    easing: ComputedTimingFunction,
+    /// The IDL attribute names of the properties of this keyframe, which
+    /// getKeyframes() uses as the keys of their values.
+    properties: Vec<DOMString>,
+    #[ignore_malloc_size_of = "Arc"]
+    block: Arc<Locked<PropertyDeclarationBlock>>,
+}
+
+/// A keyframe as read from script, before its offset is computed.
+struct KeyframeInput {
+    offset: Option<f64>,
+    easing: DOMString,
+    values: Vec<(DOMString, PropertyId, DOMString)>,
+}
+
+impl KeyframeInput {
+    fn new() -> KeyframeInput {
+        KeyframeInput {
+            offset: None,
+            easing: DOMString::from("linear"),
+            values: vec![],
+        }
+    }
+}
+
+// https://drafts.csswg.org/web-animations/#the-keyframeeffect-interface
+#[dom_struct]
+pub struct KeyframeEffect {
+    effect: AnimationEffect,
+    target: MutNullableDom<Element>,
+    keyframes: DomRefCell<Vec<Keyframe>>,
+    /// The keyframes in the form the style system interpolates them, which
+    /// are shared with layout through the samples of the target.
+    #[ignore_malloc_size_of = "Arc"]
+    effect_keyframes: DomRefCell<Arc<Vec<EffectKeyframe>>>,
+}
+
+impl KeyframeEffect {
+    fn new_inherited(target: Option<&Element>, timing: Timing) -> KeyframeEffect {
+        KeyframeEffect {
+            effect: AnimationEffect::new_inherited(timing),
+            target: MutNullableDom::new(target),
+            keyframes: DomRefCell::new(vec![]),
+            effect_keyframes: DomRefCell::new(Arc::new(vec![])),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        target: Option<&Element>,
+        timing: Timing,
+    ) -> DomRoot<KeyframeEffect> {
+        reflect_dom_object(
+            Box::new(KeyframeEffect::new_inherited(target, timing)),
+            window,
+            KeyframeEffectBinding::Wrap,
+        )
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-keyframeeffect
+    #[allow(unsafe_code)]
+    pub fn Constructor(
+        cx: JSContext,
+        window: &Window,
+        target: Option<&Element>,
+        keyframes: *mut JSObject,
+        options: UnrestrictedDoubleOrKeyframeEffectOptions,
+    ) -> Fallible<DomRoot<KeyframeEffect>> {
+        let timing = match options {
+            UnrestrictedDoubleOrKeyframeEffectOptions::UnrestrictedDouble(duration) => {
+                Timing::from_duration(duration)?
+            },
+            UnrestrictedDoubleOrKeyframeEffectOptions::KeyframeEffectOptions(ref options) => {
+                Timing::from_dictionary(window, &options.parent)?
+            },
+        };
+        let effect = KeyframeEffect::new(window, target, timing);
+        rooted!(in(*cx) let keyframes = keyframes);
+        effect.set_keyframes(cx, keyframes.handle())?;
+        Ok(effect)
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-keyframeeffect-source
+    pub fn Constructor_(
+        window: &Window,
+        source: &KeyframeEffect,
+    ) -> Fallible<DomRoot<KeyframeEffect>> {
+        let effect = KeyframeEffect::new(
+            window,
+            source.target.get().as_deref(),
+            source.effect.timing().clone(),
+        );
+        *effect.keyframes.borrow_mut() = source
+            .keyframes
+            .borrow()
+            .iter()
+            .map(|keyframe| Keyframe {
+                offset: keyframe.offset,
+                computed_offset: keyframe.computed_offset,
+                easing: keyframe.easing,
+                properties: keyframe.properties.clone(),
+                block: keyframe.block.clone(),
+            })
+            .collect();
+        *effect.effect_keyframes.borrow_mut() = source.effect_keyframes.borrow().clone();
+        Ok(effect)
+    }
+
+    pub fn target(&self) -> Option<DomRoot<Element>> {
+        self.target.get()
+    }
+
+    /// The keyframes of this effect, in the form the style system
+    /// interpolates them.
+    pub fn effect_keyframes(&self) -> Arc<Vec<EffectKeyframe>> {
+        self.effect_keyframes.borrow().clone()
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#process-a-keyframes-argument>
+    #[allow(unsafe_code)]
+    fn set_keyframes(&self, cx: JSContext, object: HandleObject) -> Fallible<()> {
+        let window = self.global();
+        let window = window.as_window();
+        let inputs = if object.get().is_null() {
+            vec![]
+        } else {
+            unsafe {
+                rooted!(in(*cx) let value = ObjectValue(object.get()));
+                if is_array(*cx, value.handle())? {
+                    read_keyframe_list(*cx, value.handle())?
+                } else {
+                    read_property_indexed_keyframes(*cx, window, object)?
+                }
+            }
+        };
+
+        // Step 8, checking the offsets.
+        let mut previous_offset = None;
+        for input in &inputs {
+            if let Some(offset) = input.offset {
+                if offset < 0. || offset > 1. {
+                    return Err(Error::Type(
+                        "Keyframe offsets must be between 0 and 1".to_owned(),
+                    ));
+                }
+                if previous_offset.map_or(false, |previous| offset < previous) {
+                    return Err(Error::Type(
+                        "Keyframe offsets must be loosely sorted".to_owned(),
+                    ));
+                }
+                previous_offset = Some(offset);
+            }
+        }
+
+        let easings = inputs
+            .iter()
+            .map(|input| parse_easing(window, &input.easing))
+            .collect::<Fallible<Vec<_>>>()?;
+        let offsets = compute_missing_offsets(&inputs);
+
+        let document = window.Document();
+        let url = document.url();
+        let quirks_mode = document.quirks_mode();
+        let lock = document.style_shared_lock();
+        let mut keyframes = Vec::with_capacity(inputs.len());
+        for ((mut input, easing), computed_offset) in inputs.into_iter().zip(easings).zip(offsets) {
+            // Shorthands are applied first, so that the longhands they
+            // expand to can be overridden by the keyframe.
+            input
+                .values
+                .sort_by_key(|&(_, ref id, _)| !id.is_shorthand());
+            let mut block = PropertyDeclarationBlock::new();
+            let mut properties = vec![];
+            for (name, id, value) in input.values {
+                let mut declarations = SourcePropertyDeclaration::new();
+                let result = parse_one_declaration_into(
+                    &mut declarations,
+                    id,
+                    &value,
+                    &url,
+                    window.css_error_reporter(),
+                    ParsingMode::DEFAULT,
+                    quirks_mode,
+                );
+                // Invalid values are ignored.
+                if result.is_ok() {
+                    block.extend(declarations.drain(), Importance::Normal);
+                    properties.push(name);
+                }
+            }
+            keyframes.push(Keyframe {
+                offset: input.offset,
+                computed_offset: computed_offset,
+                easing: easing,
+                properties: properties,
+                block: Arc::new(lock.wrap(block)),
+            });
+        }
+
+        let effect_keyframes = {
+            let guard = lock.read();
+            keyframes
+                .iter()
+                .map(|keyframe| {
+                    EffectKeyframe::new(
+                        keyframe.computed_offset,
+                        keyframe.easing,
+                        keyframe.block.clone(),
+                        &guard,
+                    )
+                })
+                .collect()
+        };
+        *self.keyframes.borrow_mut() = keyframes;
+        *self.effect_keyframes.borrow_mut() = Arc::new(effect_keyframes);
+        self.keyframes_changed();
+        Ok(())
+    }
+
+    fn keyframes_changed(&self) {
+        if let Some(animation) = self.upcast::<AnimationEffect>().animation() {
+            animation.effect_timing_changed();
+        }
+    }
+}
+
+impl KeyframeEffectMethods for KeyframeEffect {
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-target
+    fn GetTarget(&self) -> Option<DomRoot<Element>> {
+        self.target.get()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-target
+    fn SetTarget(&self, target: Option<&Element>) {
+        self.target.set(target);
+        self.keyframes_changed();
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-getkeyframes
+    #[allow(unsafe_code)]
+    fn GetKeyframes(&self, cx: JSContext) -> Fallible<Vec<NonNull<JSObject>>> {
+        let keyframes = self.keyframes.borrow();
+        let window = self.global();
+        let document = window.as_window().Document();
+        let guard = document.style_shared_lock().read();
+        // The objects are kept in place until all of them are created, so
+        // that they stay rooted.
+        let mut objects: RootedTraceableBox<Vec<Heap<*mut JSObject>>> =
+            RootedTraceableBox::new(Vec::with_capacity(keyframes.len()));
+        for keyframe in keyframes.iter() {
+            unsafe {
+                rooted!(in(*cx) let object = JS_NewPlainObject(*cx));
+                rooted!(in(*cx) let mut value = UndefinedValue());
+                match keyframe.offset {
+                    Some(offset) => offset.to_jsval(*cx, value.handle_mut()),
+                    None => value.set(NullValue()),
+                }
+                set_property(*cx, object.handle(), "offset", value.handle())?;
+                keyframe.computed_offset.to_jsval(*cx, value.handle_mut());
+                set_property(*cx, object.handle(), "computedOffset", value.handle())?;
+                DOMString::from(keyframe.easing.to_css_string()).to_jsval(*cx, value.handle_mut());
+                set_property(*cx, object.handle(), "easing", value.handle())?;
+                DOMString::from("auto").to_jsval(*cx, value.handle_mut());
+                set_property(*cx, object.handle(), "composite", value.handle())?;
+                let block = keyframe.block.read_with(&guard);
+                for name in &keyframe.properties {
+                    let id = match property_from_idl_attribute_name(name) {
+                        Some(id) => id,
+                        None => continue,
+                    };
+                    let mut serialization = String::new();
+                    block
+                        .property_value_to_css(&id, &mut serialization)
+                        .unwrap();
+                    DOMString::from(serialization).to_jsval(*cx, value.handle_mut());
+                    set_property(*cx, object.handle(), name, value.handle())?;
+                }
+                objects.push(Heap::default());
+                objects.last().unwrap().set(object.get());
+            }
+        }
+        Ok(objects
+            .iter()
+            .map(|object| NonNull::new(object.get()).unwrap())
+            .collect())
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-setkeyframes
+    fn SetKeyframes(&self, cx: JSContext, keyframes: *mut JSObject) -> Fallible<()> {
+        rooted!(in(*cx) let keyframes = keyframes);
+        self.set_keyframes(cx, keyframes.handle())
+    }
+}
+
+/// Returns the CSS property an IDL attribute of `CSSStyleDeclaration` stands
+/// for, if it is one that keyframes can specify.
+///
+/// <https://drafts.csswg.org/web-animations/#idl-attribute-name-to-animation-property-name>
+fn property_from_idl_attribute_name(name: &str) -> Option<PropertyId> {
+    let property = match name {
+        "cssFloat" => "float".to_owned(),
+        "cssOffset" => "offset".to_owned(),
+        // These are keyframe members, or would clash with them.
+        "float" | "offset" | "easing" | "composite" => return None,
+        // Only the camel-cased names of properties are recognized.
+        _ if name.contains('-') => return None,
+        _ => {
+            let mut property = String::with_capacity(name.len());
+            for c in name.chars() {
+                if c.is_ascii_uppercase() {
+                    property.push('-');
+                    property.push(c.to_ascii_lowercase());
+                } else {
+                    property.push(c);
+                }
+            }
+            property
+        },
+    };
+    PropertyId::parse_enabled_for_all_content(&property).ok()
+}
+
+/// <https://drafts.csswg.org/web-animations/#compute-missing-keyframe-offsets>
+fn compute_missing_offsets(inputs: &[KeyframeInput]) -> Vec<f64> {
+    let mut offsets = inputs.iter().map(|input| input.offset).collect::<Vec<_>>();
+    let len = offsets.len();
+    if len == 0 {
+        return vec![];
+    }
+    if len > 1 && offsets[0].is_none() {
+        offsets[0] = Some(0.);
+    }
+    if offsets[len - 1].is_none() {
+        offsets[len - 1] = Some(1.);
+    }
+
+    // Space the keyframes without an offset evenly between the ones
+    // surrounding them.
+    let mut start = 0;
+    for end in 1..len {
+        let end_offset = match offsets[end] {
+            Some(offset) => offset,
+            None => continue,
+        };
+        let start_offset = offsets[start].unwrap();
+        for i in (start + 1)..end {
+            let fraction = (i - start) as f64 / (end - start) as f64;
+            offsets[i] = Some(start_offset + (end_offset - start_offset) * fraction);
+        }
+        start = end;
+    }
+    offsets.into_iter().map(Option::unwrap).collect()
+}
+
+#[allow(unsafe_code)]
+unsafe fn is_array(cx: *mut js::jsapi::JSContext, value: HandleValue) -> Fallible<bool> {
+    let mut is_array = false;
+    if !JS_IsArrayObject(cx, value, &mut is_array) {
+        return Err(Error::JSFailed);
+    }
+    Ok(is_array)
+}
+
+#[allow(unsafe_code)]
+unsafe fn get_property(
+    cx: *mut js::jsapi::JSContext,
+    object: HandleObject,
+    name: &str,
+    rval: MutableHandleValue,
+) -> Fallible<bool> {
+    get_dictionary_property(cx, object, name, rval).map_err(|()| Error::JSFailed)
+}
+
+#[allow(unsafe_code)]
+unsafe fn set_property(
+    cx: *mut js::jsapi::JSContext,
+    object: HandleObject,
+    name: &str,
+    value: HandleValue,
+) -> Fallible<()> {
+    set_dictionary_property(cx, object, name, value).map_err(|()| Error::JSFailed)
+}
+
+#[allow(unsafe_code)]
+unsafe fn convert<T: FromJSValConvertible>(
+    cx: *mut js::jsapi::JSContext,
+    value: HandleValue,
+    config: T::Config,
+) -> Fallible<T> {
+    match T::from_jsval(cx, value, config) {
+        Ok(ConversionResult::Success(value)) => Ok(value),
+        Ok(ConversionResult::Failure(message)) => Err(Error::Type(message.into_owned())),
+        Err(()) => Err(Error::JSFailed),
+    }
+}
+
+#[allow(unsafe_code)]
+unsafe fn to_string(cx: *mut js::jsapi::JSContext, value: HandleValue) -> Fallible<DOMString> {
+    convert(cx, value, StringificationBehavior::Default)
+}
+
+#[allow(unsafe_code)]
+unsafe fn to_offset(cx: *mut js::jsapi::JSContext, value: HandleValue) -> Fallible<Option<f64>> {
+    if value.is_null_or_undefined() {
+        return Ok(None);
+    }
+    let offset: f64 = convert(cx, value, ())?;
+    if !offset.is_finite() {
+        return Err(Error::Type("Keyframe offsets must be finite".to_owned()));
+    }
+    Ok(Some(offset))
+}
+
+/// Calls `f` with each element of an array.
+///
+/// FIXME: Other iterables should be accepted too.
+#[allow(unsafe_code)]
+unsafe fn for_each_element<F>(
+    cx: *mut js::jsapi::JSContext,
+    array: HandleValue,
+    mut f: F,
+) -> Fallible<()>
+where
+    F: FnMut(HandleValue) -> Fallible<()>,
+{
+    rooted!(in(cx) let array = array.to_object());
+    rooted!(in(cx) let mut value = UndefinedValue());
+    get_property(cx, array.handle(), "length", value.handle_mut())?;
+    let length: f64 = convert(cx, value.handle(), ())?;
+    for index in 0..(length as u32) {
+        value.set(UndefinedValue());
+        get_property(cx, array.handle(), &index.to_string(), value.handle_mut())?;
+        f(value.handle())?;
+    }
+    Ok(())
+}
+
+/// The properties of `object` that keyframes can specify, sorted by name.
+#[allow(unsafe_code)]
+unsafe fn animation_properties(
+    cx: *mut js::jsapi::JSContext,
+    object: HandleObject,
+) -> Fallible<Vec<(DOMString, PropertyId)>> {
+    let ids = IdVector::new(cx);
+    if !GetPropertyKeys(cx, object, JSITER_OWNONLY, ids.get()) {
+        return Err(Error::JSFailed);
+    }
+    let mut properties = vec![];
+    for id in &*ids {
+        rooted!(in(cx) let id = *id);
+        rooted!(in(cx) let mut desc = PropertyDescriptor::default());
+        if !JS_GetOwnPropertyDescriptorById(cx, object, id.handle(), desc.handle_mut()) {
+            return Err(Error::JSFailed);
+        }
+        if (JSPROP_ENUMERATE as u32) & desc.attrs == 0 {
+            continue;
+        }
+        let name = match jsid_to_string(cx, id.handle()) {
+            Some(name) => name,
+            None => continue,
+        };
+        if let Some(id) = property_from_idl_attribute_name(&name) {
+            properties.push((name, id));
+        }
+    }
+    properties.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(properties)
+}
+
+/// Reads the keyframes of the array form of a keyframes argument.
+#[allow(unsafe_code)]
+unsafe fn read_keyframe_list(
+    cx: *mut js::jsapi::JSContext,
+    array: HandleValue,
+) -> Fallible<Vec<KeyframeInput>> {
+    let mut inputs = vec![];
+    for_each_element(cx, array, |keyframe| {
+        let mut input = KeyframeInput::new();
+        if keyframe.is_null_or_undefined() {
+            inputs.push(input);
+            return Ok(());
+        }
+        if !keyframe.is_object() {
+            return Err(Error::Type("Keyframes must be objects".to_owned()));
+        }
+        rooted!(in(cx) let keyframe = keyframe.to_object());
+        rooted!(in(cx) let mut value = UndefinedValue());
+        if get_property(cx, keyframe.handle(), "offset", value.handle_mut())? {
+            input.offset = to_offset(cx, value.handle())?;
+        }
+        if get_property(cx, keyframe.handle(), "easing", value.handle_mut())? &&
+            !value.is_undefined()
+        {
+            input.easing = to_string(cx, value.handle())?;
+        }
+        for (name, id) in animation_properties(cx, keyframe.handle())? {
+            value.set(UndefinedValue());
+            get_property(cx, keyframe.handle(), &name, value.handle_mut())?;
+            let property_value = to_string(cx, value.handle())?;
+            input.values.push((name, id, property_value));
+        }
+        inputs.push(input);
+        Ok(())
+    })?;
+    Ok(inputs)
+}
+
+/// Reads the keyframes of the property-indexed form of a keyframes argument.
+#[allow(unsafe_code)]
+unsafe fn read_property_indexed_keyframes(
+    cx: *mut js::jsapi::JSContext,
+    window: &Window,
+    object: HandleObject,
+) -> Fallible<Vec<KeyframeInput>> {
+    rooted!(in(cx) let mut value = UndefinedValue());
+
+    let mut offsets = vec![];
+    if get_property(cx, object, "offset", value.handle_mut())? {
+        if is_array(cx, value.handle())? {
+            for_each_element(cx, value.handle(), |offset| {
+                offsets.push(to_offset(cx, offset)?);
+                Ok(())
+            })?;
+        } else {
+            offsets.push(to_offset(cx, value.handle())?);
+        }
+    }
+
+    let mut easings = vec![];
+    if get_property(cx, object, "easing", value.handle_mut())? && !value.is_undefined() {
+        if is_array(cx, value.handle())? {
+            for_each_element(cx, value.handle(), |easing| {
+                easings.push(to_string(cx, easing)?);
+                Ok(())
+            })?;
+        } else {
+            easings.push(to_string(cx, value.handle())?);
+        }
+    }
+
+    // Each value of each property makes a keyframe, and the keyframes of the
+    // values of a property are evenly spaced.
+    let mut keyframes: Vec<(f64, KeyframeInput)> = vec![];
+    for (name, id) in animation_properties(cx, object)? {
+        value.set(UndefinedValue());
+        get_property(cx, object, &name, value.handle_mut())?;
+        let mut values = vec![];
+        if is_array(cx, value.handle())? {
+            for_each_element(cx, value.handle(), |value| {
+                values.push(to_string(cx, value)?);
+                Ok(())
+            })?;
+        } else {
+            values.push(to_string(cx, value.handle())?);
+        }
+        let count = values.len();
+        for (index, property_value) in values.into_iter().enumerate() {
+            let offset = if count == 1 {
+                1.
+            } else {
+                index as f64 / (count - 1) as f64
+            };
+            let position = keyframes.iter().position(|&(o, _)| o >= offset);
+            match position {
+                Some(position) if keyframes[position].0 == offset => {
+                    keyframes[position]
+                        .1
+                        .values
+                        .push((name.clone(), id.clone(), property_value));
+                },
+                _ => {
+                    let mut input = KeyframeInput::new();
+                    input
+                        .values
+                        .push((name.clone(), id.clone(), property_value));
+                    let position = position.unwrap_or(keyframes.len());
+                    keyframes.insert(position, (offset, input));
+                },
+            }
+        }
+    }
+
+    let mut inputs = keyframes
+        .into_iter()
+        .map(|(_, input)| input)
+        .collect::<Vec<_>>();
+    for (input, offset) in inputs.iter_mut().zip(offsets) {
+        input.offset = offset;
+    }
+    // Easings are repeated if there are fewer of them than keyframes, and
+    // the extra ones are ignored, but all of them must be valid.
+    for easing in easings.iter().skip(inputs.len()) {
+        parse_easing(window, easing)?;
+    }
+    if !easings.is_empty() {
+        for (index, input) in inputs.iter_mut().enumerate() {
+            input.easing = easings[index % easings.len()].clone();
+        }
+    }
+    Ok(inputs)
+}
@@ -213,6 +213,10 @@ pub mod abstractworker;
 pub mod abstractworkerglobalscope;
 pub mod activation;
 pub mod analysernode;
+pub mod animation;
+pub mod animationeffect;
+pub mod animationplaybackevent;
+pub mod animationtimeline;
 pub mod attr;
 pub mod audiobuffer;
 pub mod audiobuffersourcenode;
@@ -239,6 +243,7 @@ pub mod bluetoothremotegattdescriptor;
 pub mod bluetoothremotegattserver;
 pub mod bluetoothremotegattservice;
 pub mod bluetoothuuid;
+pub mod broadcastchannel;
 pub mod canvasgradient;
 pub mod canvaspattern;
 pub mod canvasrenderingcontext2d;
@@ -254,15 +259,21 @@ pub mod console;
 pub mod constantsourcenode;
 mod create;
 pub mod crypto;
+pub mod cryptokey;
 pub mod css;
+pub mod cssanimation;
 pub mod cssconditionrule;
+pub mod csscontainerrule;
 pub mod cssfontfacerule;
 pub mod cssgroupingrule;
 pub mod cssimportrule;
 pub mod csskeyframerule;
 pub mod csskeyframesrule;
+pub mod csslayerblockrule;
+pub mod csslayerstatementrule;
 pub mod cssmediarule;
 pub mod cssnamespacerule;
+pub mod csspropertyrule;
 pub mod cssrule;
 pub mod cssrulelist;
 pub mod cssstyledeclaration;
@@ -270,6 +281,7 @@ pub mod cssstylerule;
 pub mod cssstylesheet;
 pub mod cssstylevalue;
 pub mod csssupportsrule;
+pub mod csstransition;
 pub mod cssviewportrule;
 pub mod customelementregistry;
 pub mod customevent;
@@ -279,6 +291,7 @@ pub mod dissimilaroriginwindow;
 pub mod document;
 pub mod documentfragment;
 pub mod documentorshadowroot;
+pub mod documenttimeline;
 pub mod documenttype;
 pub mod domexception;
 pub mod domimplementation;
@@ -306,6 +319,9 @@ pub mod filelist;
 pub mod filereader;
 pub mod filereadersync;
 pub mod focusevent;
+pub mod fontface;
+pub mod fontfaceset;
+pub mod fontfacesetloadevent;
 pub mod formdata;
 pub mod formdataevent;
 pub mod gainnode;
@@ -395,7 +411,10 @@ pub mod htmlvideoelement;
 pub mod identityhub;
 pub mod imagedata;
 pub mod inputevent;
+pub mod intersectionobserver;
+pub mod intersectionobserverentry;
 pub mod keyboardevent;
+pub mod keyframeeffect;
 pub mod location;
 pub mod mediadevices;
 pub mod mediaelementaudiosourcenode;
@@ -457,6 +476,9 @@ pub mod radionodelist;
 pub mod range;
 pub mod raredata;
 pub mod request;
+pub mod resizeobserver;
+pub mod resizeobserverentry;
+pub mod resizeobserversize;
 pub mod response;
 pub mod rtcicecandidate;
 pub mod rtcpeerconnection;
@@ -464,6 +486,7 @@ pub mod rtcpeerconnectioniceevent;
 pub mod rtcsessiondescription;
 pub mod rtctrackevent;
 pub mod screen;
+pub mod selection;
 pub mod serviceworker;
 pub mod serviceworkercontainer;
 pub mod serviceworkerglobalscope;
@@ -476,6 +499,7 @@ pub mod storageevent;
 pub mod stylepropertymapreadonly;
 pub mod stylesheet;
 pub mod stylesheetlist;
+pub mod subtlecrypto;
 pub mod svgelement;
 pub mod svggraphicselement;
 pub mod svgsvgelement;
@@ -57,7 +57,7 @@ use crate::dom::raredata::NodeRareData;
 use crate::dom::shadowroot::{LayoutShadowRootHelpers, ShadowRoot};
 use crate::dom::stylesheetlist::StyleSheetListOwner;
 use crate::dom::svgsvgelement::{LayoutSVGSVGElementHelpers, SVGSVGElement};
-use crate::dom::text::Text;
+use crate::dom::text::{LayoutTextHelpers, Text};
 use crate::dom::virtualmethods::{vtable_for, VirtualMethods};
 use crate::dom::window::Window;
 use crate::script_runtime::JSContext;
@@ -1383,6 +1383,10 @@ impl LayoutNodeHelpers for LayoutDom<Node> {
             return unsafe { input.selection_for_layout() };
         }
 
+        if let Some(text) = self.downcast::<Text>() {
+            return unsafe { text.selection_for_layout() };
+        }
+
         None
     }
 
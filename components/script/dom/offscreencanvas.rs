@@ -11,6 +11,8 @@ use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::structuredclone::StructuredDataHolder;
+use crate::dom::bindings::transferable::Transferable;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::HTMLCanvasElement;
@@ -20,6 +22,7 @@ use canvas_traits::canvas::{CanvasMsg, FromScriptMsg};
 use dom_struct::dom_struct;
 use euclid::default::Size2D;
 use ipc_channel::ipc::IpcSharedMemory;
+use js::jsapi::MutableHandleObject;
 use js::rust::HandleValue;
 use profile_traits::ipc;
 use ref_filter_map;
@@ -41,6 +44,8 @@ pub struct OffscreenCanvas {
     height: Cell<u64>,
     context: DomRefCell<Option<OffscreenCanvasContext>>,
     placeholder: Option<Dom<HTMLCanvasElement>>,
+    /// Whether this canvas was transferred to another global.
+    detached: Cell<bool>,
 }
 
 impl OffscreenCanvas {
@@ -55,6 +60,7 @@ impl OffscreenCanvas {
             height: Cell::new(height),
             context: DomRefCell::new(None),
             placeholder: placeholder.map(Dom::from_ref),
+            detached: Cell::new(false),
         }
     }
 
@@ -124,6 +130,9 @@ impl OffscreenCanvas {
 
     #[allow(unsafe_code)]
     fn get_or_init_2d_context(&self) -> Option<DomRoot<OffscreenCanvasRenderingContext2D>> {
+        if self.detached.get() {
+            return None;
+        }
         if let Some(ctx) = self.context() {
             return match *ctx {
                 OffscreenCanvasContext::OffscreenContext2d(ref ctx) => Some(DomRoot::from_ref(ctx)),
@@ -141,6 +150,41 @@ impl OffscreenCanvas {
     }
 }
 
+impl Transferable for OffscreenCanvas {
+    /// <https://html.spec.whatwg.org/multipage/#the-offscreencanvas-interface:transfer-steps>
+    fn transfer(&self, _sc_holder: &mut StructuredDataHolder) -> Result<u64, ()> {
+        // Step 1
+        if self.detached.get() || self.context.borrow().is_some() {
+            return Err(());
+        }
+
+        // The size is carried over in the transferred data itself.
+        let (width, height) = (self.width.get(), self.height.get());
+        if width > u32::max_value() as u64 || height > u32::max_value() as u64 {
+            return Err(());
+        }
+
+        // Step 2-5
+        self.detached.set(true);
+        self.width.set(0);
+        self.height.set(0);
+
+        Ok(width << 32 | height)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#the-offscreencanvas-interface:transfer-receiving-steps>
+    fn transfer_receive(
+        owner: &DomRoot<GlobalScope>,
+        _sc_holder: &mut StructuredDataHolder,
+        extra_data: u64,
+        return_object: MutableHandleObject,
+    ) -> Result<(), ()> {
+        let canvas = OffscreenCanvas::new(owner, extra_data >> 32, extra_data & 0xFFFF_FFFF, None);
+        return_object.set(canvas.reflector().rootable().get());
+        Ok(())
+    }
+}
+
 impl OffscreenCanvasMethods for OffscreenCanvas {
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-getcontext
     fn GetContext(
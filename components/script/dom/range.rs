@@ -14,7 +14,7 @@ use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::inheritance::{CharacterDataTypeId, NodeTypeId};
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
-use crate::dom::bindings::root::{Dom, DomRoot, MutDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutDom, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::trace::JSTraceable;
 use crate::dom::bindings::weakref::{WeakRef, WeakRefVec};
@@ -24,6 +24,7 @@ use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::element::Element;
 use crate::dom::htmlscriptelement::HTMLScriptElement;
 use crate::dom::node::{Node, ShadowIncluding, UnbindContext};
+use crate::dom::selection::Selection;
 use crate::dom::text::Text;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
@@ -37,6 +38,9 @@ pub struct Range {
     reflector_: Reflector,
     start: BoundaryPoint,
     end: BoundaryPoint,
+    /// The selection this range is associated with, if any.
+    /// https://w3c.github.io/selection-api/#dfn-associated
+    selection: MutNullableDom<Selection>,
 }
 
 impl Range {
@@ -50,6 +54,7 @@ impl Range {
             reflector_: Reflector::new(),
             start: BoundaryPoint::new(start_container, start_offset),
             end: BoundaryPoint::new(end_container, end_offset),
+            selection: Default::default(),
         }
     }
 
@@ -88,8 +93,13 @@ impl Range {
         Ok(Range::new_with_doc(&document))
     }
 
+    /// Associates this range with `selection`, or with no selection.
+    pub fn set_selection(&self, selection: Option<&Selection>) {
+        self.selection.set(selection);
+    }
+
     // https://dom.spec.whatwg.org/#contained
-    fn contains(&self, node: &Node) -> bool {
+    pub fn contains(&self, node: &Node) -> bool {
         match (
             bp_position(node, 0, &self.StartContainer(), self.StartOffset()),
             bp_position(node, node.len(), &self.EndContainer(), self.EndOffset()),
@@ -173,6 +183,7 @@ impl Range {
             }
         }
         self.start.set(node, offset);
+        self.boundary_points_changed();
     }
 
     // https://dom.spec.whatwg.org/#concept-range-bp-set
@@ -188,6 +199,14 @@ impl Range {
             }
         }
         self.end.set(node, offset);
+        self.boundary_points_changed();
+    }
+
+    fn boundary_points_changed(&self) {
+        // https://w3c.github.io/selection-api/#selectionchange-event
+        if let Some(selection) = self.selection.get() {
+            selection.queue_selectionchange_task();
+        }
     }
 
     // https://dom.spec.whatwg.org/#dom-range-comparepointnode-offset
@@ -1057,7 +1076,7 @@ impl PartialEq for BoundaryPoint {
 }
 
 // https://dom.spec.whatwg.org/#concept-range-bp-position
-fn bp_position(a_node: &Node, a_offset: u32, b_node: &Node, b_offset: u32) -> Option<Ordering> {
+pub fn bp_position(a_node: &Node, a_offset: u32, b_node: &Node, b_offset: u32) -> Option<Ordering> {
     if a_node as *const Node == b_node as *const Node {
         // Step 1.
         return Some(a_offset.cmp(&b_offset));
@@ -0,0 +1,366 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ResizeObserverBinding::{
+    ResizeObserverBoxOptions, ResizeObserverCallback, ResizeObserverMethods, ResizeObserverOptions,
+    Wrap,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{ErrorInfo, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::resizeobserverentry::ResizeObserverEntry;
+use crate::dom::resizeobserversize::ResizeObserverSizeImpl;
+use crate::dom::window::Window;
+use app_units::Au;
+use dom_struct::dom_struct;
+use euclid::default::{Point2D, Rect, Size2D};
+use js::rust::HandleValue;
+use script_layout_interface::rpc::BoxSizes;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// <https://drafts.csswg.org/resize-observer/#resize-observer-interface>
+#[dom_struct]
+pub struct ResizeObserver {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    callback: Rc<ResizeObserverCallback>,
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-observationtargets-slot>
+    observation_targets: DomRefCell<Vec<ResizeObservation>>,
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-activetargets-slot>
+    active_targets: DomRefCell<Vec<Dom<Element>>>,
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-skippedtargets-slot>
+    skipped_targets: DomRefCell<Vec<Dom<Element>>>,
+}
+
+/// <https://drafts.csswg.org/resize-observer/#resizeobservation>
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct ResizeObservation {
+    target: Dom<Element>,
+    observed_box: ResizeObserverBoxOptions,
+    /// The size of the observed box when it was last reported, which starts out as 0×0.
+    last_reported_size: Cell<ResizeObserverSizeImpl>,
+}
+
+/// The sizes of the boxes of a target, as they are reported in its entries.
+struct TargetSizes {
+    /// The content box, relative to the padding box, in CSS pixels.
+    content_rect: Rect<f64>,
+    border_box: ResizeObserverSizeImpl,
+    content_box: ResizeObserverSizeImpl,
+    device_pixel_content_box: ResizeObserverSizeImpl,
+}
+
+impl ResizeObserver {
+    fn new_inherited(callback: Rc<ResizeObserverCallback>) -> ResizeObserver {
+        ResizeObserver {
+            reflector_: Reflector::new(),
+            callback,
+            observation_targets: DomRefCell::new(vec![]),
+            active_targets: DomRefCell::new(vec![]),
+            skipped_targets: DomRefCell::new(vec![]),
+        }
+    }
+
+    // https://drafts.csswg.org/resize-observer/#dom-resizeobserver-resizeobserver
+    pub fn Constructor(
+        window: &Window,
+        callback: Rc<ResizeObserverCallback>,
+    ) -> Fallible<DomRoot<ResizeObserver>> {
+        let observer = ResizeObserver::new_inherited(callback);
+        Ok(reflect_dom_object(Box::new(observer), window, Wrap))
+    }
+
+    fn document(&self) -> DomRoot<Document> {
+        self.global().as_window().Document()
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#gather-active-observations-h>
+    pub fn gather_active_observations_at_depth(document: &Document, depth: usize) {
+        let observers = document.resize_observers();
+        if observers.is_empty() {
+            return;
+        }
+
+        // Ask layout about the targets of all the observers at once.
+        let targets: Vec<Vec<DomRoot<Element>>> = observers
+            .iter()
+            .map(|observer| observer.observed_targets())
+            .collect();
+        let mut all_sizes = {
+            let nodes: Vec<&Node> = targets
+                .iter()
+                .flat_map(|targets| targets.iter().map(|target| target.upcast::<Node>()))
+                .collect();
+            target_sizes(document.window(), &nodes).into_iter()
+        };
+
+        // Step 1-2
+        for (observer, targets) in observers.iter().zip(targets) {
+            // Step 2.1-2.2
+            observer.active_targets.borrow_mut().clear();
+            observer.skipped_targets.borrow_mut().clear();
+
+            // Step 2.3
+            for (index, target) in targets.iter().enumerate() {
+                let sizes = all_sizes.next().unwrap();
+                // Step 2.3.1
+                let is_active = {
+                    let observations = observer.observation_targets.borrow();
+                    let observation = &observations[index];
+                    sizes.size_of(observation.observed_box) != observation.last_reported_size.get()
+                };
+                if !is_active {
+                    continue;
+                }
+                // Step 2.3.1.1-2.3.1.3
+                if depth_of(target) > depth {
+                    observer
+                        .active_targets
+                        .borrow_mut()
+                        .push(Dom::from_ref(&**target));
+                } else {
+                    observer
+                        .skipped_targets
+                        .borrow_mut()
+                        .push(Dom::from_ref(&**target));
+                }
+            }
+        }
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#has-active-observations-h>
+    pub fn has_active_observations(document: &Document) -> bool {
+        document
+            .resize_observers()
+            .iter()
+            .any(|observer| !observer.active_targets.borrow().is_empty())
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#has-skipped-observations-h>
+    pub fn has_skipped_observations(document: &Document) -> bool {
+        document
+            .resize_observers()
+            .iter()
+            .any(|observer| !observer.skipped_targets.borrow().is_empty())
+    }
+
+    /// Returns the depth of the shallowest target that was reported, which the next round
+    /// only reports targets deeper than.
+    ///
+    /// <https://drafts.csswg.org/resize-observer/#broadcast-active-observations>
+    pub fn broadcast_active_observations(document: &Document) -> usize {
+        // Step 1
+        let mut shallowest_target_depth = usize::max_value();
+
+        // Step 2
+        for observer in document.resize_observers() {
+            // Step 2.1
+            let targets: Vec<DomRoot<Element>> = observer
+                .active_targets
+                .borrow()
+                .iter()
+                .map(|target| DomRoot::from_ref(&**target))
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+
+            // Step 2.2-2.3
+            let nodes: Vec<&Node> = targets.iter().map(|target| target.upcast()).collect();
+            let entries: Vec<DomRoot<ResizeObserverEntry>> = targets
+                .iter()
+                .zip(target_sizes(document.window(), &nodes))
+                .map(|(target, sizes)| {
+                    // Step 2.3.1
+                    let entry = ResizeObserverEntry::new(
+                        document.window(),
+                        target,
+                        sizes.content_rect,
+                        sizes.border_box,
+                        sizes.content_box,
+                        sizes.device_pixel_content_box,
+                    );
+                    // Step 2.3.2-2.3.4
+                    if let Some(observation) = observer
+                        .observation_targets
+                        .borrow()
+                        .iter()
+                        .find(|observation| &*observation.target == &**target)
+                    {
+                        observation
+                            .last_reported_size
+                            .set(sizes.size_of(observation.observed_box));
+                    }
+                    // Step 2.3.5-2.3.6
+                    shallowest_target_depth = shallowest_target_depth.min(depth_of(target));
+                    entry
+                })
+                .collect();
+
+            // Step 2.4
+            let _ =
+                observer
+                    .callback
+                    .Call_(&*observer, entries, &*observer, ExceptionHandling::Report);
+
+            // Step 2.5-2.6
+            observer.active_targets.borrow_mut().clear();
+            observer.skipped_targets.borrow_mut().clear();
+        }
+
+        // Step 3
+        shallowest_target_depth
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#deliver-resize-error>
+    pub fn deliver_resize_loop_error_notification(document: &Document) {
+        let error_info = ErrorInfo {
+            message: "ResizeObserver loop completed with undelivered notifications.".to_owned(),
+            filename: document.url().as_str().to_owned(),
+            lineno: 0,
+            column: 0,
+        };
+        document
+            .window()
+            .upcast::<GlobalScope>()
+            .report_an_error(error_info, HandleValue::null());
+    }
+
+    fn observed_targets(&self) -> Vec<DomRoot<Element>> {
+        self.observation_targets
+            .borrow()
+            .iter()
+            .map(|observation| DomRoot::from_ref(&*observation.target))
+            .collect()
+    }
+}
+
+impl ResizeObserverMethods for ResizeObserver {
+    // https://drafts.csswg.org/resize-observer/#dom-resizeobserver-observe
+    fn Observe(&self, target: &Element, options: &ResizeObserverOptions) {
+        // Step 1-2
+        self.observation_targets
+            .borrow_mut()
+            .retain(|observation| &*observation.target != target);
+        // Step 3-4
+        self.observation_targets
+            .borrow_mut()
+            .push(ResizeObservation {
+                target: Dom::from_ref(target),
+                observed_box: options.box_,
+                last_reported_size: Cell::new(ResizeObserverSizeImpl::default()),
+            });
+        self.document().add_resize_observer(self);
+    }
+
+    // https://drafts.csswg.org/resize-observer/#dom-resizeobserver-unobserve
+    fn Unobserve(&self, target: &Element) {
+        let mut observation_targets = self.observation_targets.borrow_mut();
+        observation_targets.retain(|observation| &*observation.target != target);
+
+        if observation_targets.is_empty() {
+            self.document().remove_resize_observer(self);
+        }
+    }
+
+    // https://drafts.csswg.org/resize-observer/#dom-resizeobserver-disconnect
+    fn Disconnect(&self) {
+        self.observation_targets.borrow_mut().clear();
+        self.active_targets.borrow_mut().clear();
+        self.skipped_targets.borrow_mut().clear();
+        self.document().remove_resize_observer(self);
+    }
+}
+
+impl TargetSizes {
+    /// Computes the reported sizes from the answer of layout, where targets without a box
+    /// have empty boxes.
+    ///
+    /// <https://drafts.csswg.org/resize-observer/#calculate-box-size>
+    fn new(box_sizes: Option<BoxSizes>, device_pixel_ratio: f64) -> TargetSizes {
+        let box_sizes = match box_sizes {
+            Some(box_sizes) => box_sizes,
+            None => {
+                return TargetSizes {
+                    content_rect: Rect::zero(),
+                    border_box: ResizeObserverSizeImpl::default(),
+                    content_box: ResizeObserverSizeImpl::default(),
+                    device_pixel_content_box: ResizeObserverSizeImpl::default(),
+                };
+            },
+        };
+        let logical_size = |width: f64, height: f64| {
+            if box_sizes.is_vertical {
+                ResizeObserverSizeImpl {
+                    inline_size: height,
+                    block_size: width,
+                }
+            } else {
+                ResizeObserverSizeImpl {
+                    inline_size: width,
+                    block_size: height,
+                }
+            }
+        };
+        let content_rect = to_px_rect(&box_sizes.content_rect);
+        let content_size = content_rect.size;
+        let border_box_size = box_sizes.border_box_size;
+        TargetSizes {
+            content_rect,
+            border_box: logical_size(
+                border_box_size.width.to_f64_px(),
+                border_box_size.height.to_f64_px(),
+            ),
+            content_box: logical_size(content_size.width, content_size.height),
+            device_pixel_content_box: logical_size(
+                (content_size.width * device_pixel_ratio).round(),
+                (content_size.height * device_pixel_ratio).round(),
+            ),
+        }
+    }
+
+    fn size_of(&self, observed_box: ResizeObserverBoxOptions) -> ResizeObserverSizeImpl {
+        match observed_box {
+            ResizeObserverBoxOptions::Border_box => self.border_box,
+            ResizeObserverBoxOptions::Content_box => self.content_box,
+            ResizeObserverBoxOptions::Device_pixel_content_box => self.device_pixel_content_box,
+        }
+    }
+}
+
+/// Computes the sizes of the boxes of `nodes` with a single layout query.
+fn target_sizes(window: &Window, nodes: &[&Node]) -> Vec<TargetSizes> {
+    let device_pixel_ratio = window.device_pixel_ratio().get() as f64;
+    window
+        .box_sizes_query(nodes)
+        .into_iter()
+        .map(|box_sizes| TargetSizes::new(box_sizes, device_pixel_ratio))
+        .collect()
+}
+
+/// <https://drafts.csswg.org/resize-observer/#calculate-depth-for-node>
+fn depth_of(target: &Element) -> usize {
+    target
+        .upcast::<Node>()
+        .inclusive_ancestors(ShadowIncluding::Yes)
+        .count() -
+        1
+}
+
+fn to_px_rect(rect: &Rect<Au>) -> Rect<f64> {
+    Rect::new(
+        Point2D::new(rect.origin.x.to_f64_px(), rect.origin.y.to_f64_px()),
+        Size2D::new(rect.size.width.to_f64_px(), rect.size.height.to_f64_px()),
+    )
+}
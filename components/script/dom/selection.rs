@@ -0,0 +1,677 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::{self, SelectionMethods};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::range::{bp_position, Range};
+use crate::dom::text::Text;
+use crate::editing;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+/// <https://w3c.github.io/selection-api/#dfn-direction>
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
+enum Direction {
+    Forwards,
+    Backwards,
+    Directionless,
+}
+
+/// The units `Selection.modify()` can move a boundary point by.
+#[derive(Clone, Copy, PartialEq)]
+enum Granularity {
+    Character,
+    Word,
+    DocumentBoundary,
+}
+
+/// <https://w3c.github.io/selection-api/#selection-interface>
+#[dom_struct]
+pub struct Selection {
+    reflector_: Reflector,
+    document: Dom<Document>,
+    range: MutNullableDom<Range>,
+    direction: Cell<Direction>,
+    /// <https://w3c.github.io/selection-api/#dfn-has-scheduled-selectionchange-event>
+    has_scheduled_selectionchange_event: Cell<bool>,
+    /// The text nodes that layout was last told to highlight parts of.
+    highlighted_nodes: DomRefCell<Vec<Dom<Text>>>,
+}
+
+impl Selection {
+    fn new_inherited(document: &Document) -> Selection {
+        Selection {
+            reflector_: Reflector::new(),
+            document: Dom::from_ref(document),
+            range: Default::default(),
+            direction: Cell::new(Direction::Directionless),
+            has_scheduled_selectionchange_event: Cell::new(false),
+            highlighted_nodes: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(document: &Document) -> DomRoot<Selection> {
+        reflect_dom_object(
+            Box::new(Selection::new_inherited(document)),
+            document.window(),
+            SelectionBinding::Wrap,
+        )
+    }
+
+    /// Replaces the range of this selection, associating the new range with it.
+    fn set_range(&self, range: Option<&Range>, direction: Direction) {
+        if let Some(old_range) = self.range.get() {
+            old_range.set_selection(None);
+        }
+        if let Some(range) = range {
+            range.set_selection(Some(self));
+        }
+        self.range.set(range);
+        self.direction.set(direction);
+        self.queue_selectionchange_task();
+    }
+
+    /// Whether the root of `node` is the document associated with this selection.
+    fn is_in_document(&self, node: &Node) -> bool {
+        node.is_in_doc() && *node.owner_doc() == *self.document
+    }
+
+    // https://w3c.github.io/selection-api/#dfn-anchor
+    fn anchor(&self, range: &Range) -> (DomRoot<Node>, u32) {
+        if self.direction.get() == Direction::Backwards {
+            (range.EndContainer(), range.EndOffset())
+        } else {
+            (range.StartContainer(), range.StartOffset())
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dfn-focus
+    fn focus(&self, range: &Range) -> (DomRoot<Node>, u32) {
+        if self.direction.get() == Direction::Backwards {
+            (range.StartContainer(), range.StartOffset())
+        } else {
+            (range.EndContainer(), range.EndOffset())
+        }
+    }
+
+    /// Replaces the range of this selection with one spanning from the anchor to the focus,
+    /// setting the direction depending on their relative position.
+    fn set_anchor_and_focus(&self, anchor: (&Node, u32), focus: (&Node, u32)) {
+        let (start, end, direction) = match bp_position(anchor.0, anchor.1, focus.0, focus.1) {
+            None => (focus, focus, Direction::Forwards),
+            Some(Ordering::Greater) => (focus, anchor, Direction::Backwards),
+            Some(_) => (anchor, focus, Direction::Forwards),
+        };
+        let range = Range::new(&self.document, start.0, start.1, end.0, end.1);
+        self.set_range(Some(&range), direction);
+    }
+
+    /// Replaces the range of this selection with a collapsed range at the given boundary point.
+    fn collapse_to(&self, node: &Node, offset: u32) {
+        let range = Range::new(&self.document, node, offset, node, offset);
+        self.set_range(Some(&range), Direction::Directionless);
+    }
+
+    /// <https://w3c.github.io/selection-api/#scheduling-selectionchange-event>
+    pub fn queue_selectionchange_task(&self) {
+        if self.has_scheduled_selectionchange_event.get() {
+            return;
+        }
+        self.has_scheduled_selectionchange_event.set(true);
+        let window = self.document.window();
+        let this = Trusted::new(self);
+        let _ = window.task_manager().user_interaction_task_source().queue(
+            task!(fire_selectionchange_event: move || {
+                let this = this.root();
+                this.has_scheduled_selectionchange_event.set(false);
+                this.document
+                    .upcast::<EventTarget>()
+                    .fire_event(atom!("selectionchange"));
+            }),
+            window.upcast(),
+        );
+    }
+
+    /// Tells layout which parts of which text nodes to highlight, to match the range of this
+    /// selection after it or the document changed.
+    pub fn update_highlight(&self) {
+        let selected = self.selected_text();
+        let previously_highlighted: Vec<DomRoot<Text>> = self
+            .highlighted_nodes
+            .borrow()
+            .iter()
+            .map(|text| DomRoot::from_ref(&**text))
+            .collect();
+        for text in previously_highlighted {
+            if !selected.iter().any(|&(ref selected, _)| *selected == text) {
+                text.set_selected_range(None);
+            }
+        }
+        for &(ref text, ref bytes) in &selected {
+            text.set_selected_range(Some(bytes.clone()));
+        }
+        *self.highlighted_nodes.borrow_mut() = selected
+            .iter()
+            .map(|&(ref text, _)| Dom::from_ref(&**text))
+            .collect();
+    }
+
+    /// The text nodes with data in the range of this selection, along with which bytes of their
+    /// data are in it.
+    /// A collapsed selection in the focused editing host selects no bytes of its text, which
+    /// layout draws as the caret.
+    fn selected_text(&self) -> Vec<(DomRoot<Text>, std::ops::Range<usize>)> {
+        let range = match self.range.get() {
+            Some(range) => range,
+            None => return vec![],
+        };
+        if range.Collapsed() {
+            return self.caret(&range).into_iter().collect();
+        }
+        let start_container = range.StartContainer();
+        let end_container = range.EndContainer();
+        if !self.is_in_document(&start_container) {
+            return vec![];
+        }
+        range
+            .CommonAncestorContainer()
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Text>)
+            .filter_map(|text| {
+                let bytes = {
+                    let node = text.upcast::<Node>();
+                    let start = if *node == *start_container {
+                        range.StartOffset()
+                    } else if range.IntersectsNode(node) {
+                        0
+                    } else {
+                        return None;
+                    };
+                    let end = if *node == *end_container {
+                        range.EndOffset()
+                    } else {
+                        node.len()
+                    };
+                    if start >= end {
+                        return None;
+                    }
+                    let data = text.upcast::<CharacterData>().data();
+                    byte_offset(&data, start)..byte_offset(&data, end)
+                };
+                Some((text, bytes))
+            })
+            .collect()
+    }
+
+    /// The text node and byte offset where the caret is, if `range` is collapsed in the text of
+    /// the focused editing host.
+    fn caret(&self, range: &Range) -> Option<(DomRoot<Text>, std::ops::Range<usize>)> {
+        let node = range.StartContainer();
+        let text = DomRoot::downcast::<Text>(node)?;
+        let host = editing::editing_host(text.upcast())?;
+        let focused = self.document.get_focused_element()?;
+        if *focused != *host.upcast::<Element>() || !self.is_in_document(text.upcast()) {
+            return None;
+        }
+        let offset = byte_offset(&text.upcast::<CharacterData>().data(), range.StartOffset());
+        Some((text, offset..offset))
+    }
+
+    /// Returns the boundary point that moving `(node, offset)` by one unit of `granularity`
+    /// leads to, staying within the text of the document.
+    fn moved_point(
+        &self,
+        node: &Node,
+        offset: u32,
+        forwards: bool,
+        granularity: Granularity,
+    ) -> Option<(DomRoot<Text>, u32)> {
+        let root = self.document.upcast::<Node>();
+        let mut text_nodes = root
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Text>);
+        if granularity == Granularity::DocumentBoundary {
+            return if forwards {
+                text_nodes.last().map(|text| {
+                    let length = text.upcast::<Node>().len();
+                    (text, length)
+                })
+            } else {
+                text_nodes.next().map(|text| (text, 0))
+            };
+        }
+
+        let mut position = match DomRoot::downcast::<Text>(DomRoot::from_ref(node)) {
+            Some(text) => TextPosition::new(text, offset),
+            None if forwards => {
+                let text = text_nodes.find(|text| {
+                    bp_position(text.upcast(), 0, node, offset) != Some(Ordering::Less)
+                })?;
+                TextPosition::new(text, 0)
+            },
+            None => {
+                let text = text_nodes
+                    .filter(|text| {
+                        let text = text.upcast::<Node>();
+                        bp_position(text, text.len(), node, offset) != Some(Ordering::Greater)
+                    })
+                    .last()?;
+                let length = text.upcast::<Node>().len();
+                TextPosition::new(text, length)
+            },
+        };
+
+        match granularity {
+            Granularity::Character => {
+                position.step(root, forwards);
+            },
+            Granularity::Word => {
+                // Skip the characters separating words, then the word itself.
+                while position
+                    .peek(root, forwards)
+                    .map_or(false, |c| !c.is_alphanumeric())
+                {
+                    position.step(root, forwards);
+                }
+                while position
+                    .peek(root, forwards)
+                    .map_or(false, char::is_alphanumeric)
+                {
+                    position.step(root, forwards);
+                }
+            },
+            Granularity::DocumentBoundary => unreachable!(),
+        }
+        Some(position.into_boundary_point())
+    }
+}
+
+/// Converts an offset in UTF-16 code units into `data` to an offset in bytes.
+fn byte_offset(data: &str, utf16_offset: u32) -> usize {
+    let mut utf16_count = 0;
+    for (byte_index, c) in data.char_indices() {
+        if utf16_count >= utf16_offset as usize {
+            return byte_index;
+        }
+        utf16_count += c.len_utf16();
+    }
+    data.len()
+}
+
+/// A position between two characters of a text node, which can move across the text nodes of
+/// a document.
+struct TextPosition {
+    text: DomRoot<Text>,
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl TextPosition {
+    fn new(text: DomRoot<Text>, utf16_offset: u32) -> TextPosition {
+        let chars: Vec<char> = text.upcast::<CharacterData>().data().chars().collect();
+        let mut utf16_count = 0;
+        let index = chars
+            .iter()
+            .take_while(|c| {
+                utf16_count += c.len_utf16();
+                utf16_count <= utf16_offset as usize
+            })
+            .count();
+        TextPosition { text, chars, index }
+    }
+
+    /// The next text node in the given direction with any data in it.
+    fn adjacent_text(&self, root: &Node, forwards: bool) -> Option<DomRoot<Text>> {
+        let node = self.text.upcast::<Node>();
+        let is_nonempty_text = |node: &DomRoot<Node>| node.is::<Text>() && node.len() > 0;
+        let adjacent = if forwards {
+            node.following_nodes(root).find(is_nonempty_text)
+        } else {
+            node.preceding_nodes(root).find(is_nonempty_text)
+        };
+        adjacent.and_then(DomRoot::downcast::<Text>)
+    }
+
+    /// The character that moving in the given direction would cross.
+    fn peek(&self, root: &Node, forwards: bool) -> Option<char> {
+        if forwards && self.index < self.chars.len() {
+            return Some(self.chars[self.index]);
+        }
+        if !forwards && self.index > 0 {
+            return Some(self.chars[self.index - 1]);
+        }
+        let text = self.adjacent_text(root, forwards)?;
+        let data = text.upcast::<CharacterData>().data();
+        if forwards {
+            data.chars().next()
+        } else {
+            data.chars().next_back()
+        }
+    }
+
+    /// Moves over one character in the given direction, moving to the adjacent text node if
+    /// this is at the edge of its own.
+    fn step(&mut self, root: &Node, forwards: bool) {
+        if forwards && self.index == self.chars.len() {
+            match self.adjacent_text(root, true) {
+                Some(text) => *self = TextPosition::new(text, 0),
+                None => return,
+            }
+        } else if !forwards && self.index == 0 {
+            match self.adjacent_text(root, false) {
+                Some(text) => {
+                    let length = text.upcast::<Node>().len();
+                    *self = TextPosition::new(text, length);
+                },
+                None => return,
+            }
+        }
+        if forwards {
+            self.index += 1;
+        } else {
+            self.index -= 1;
+        }
+    }
+
+    fn into_boundary_point(self) -> (DomRoot<Text>, u32) {
+        let utf16_offset = self.chars[..self.index]
+            .iter()
+            .map(|c| c.len_utf16())
+            .sum::<usize>();
+        (self.text, utf16_offset as u32)
+    }
+}
+
+impl SelectionMethods for Selection {
+    // https://w3c.github.io/selection-api/#dom-selection-anchornode
+    fn GetAnchorNode(&self) -> Option<DomRoot<Node>> {
+        self.range.get().map(|range| self.anchor(&range).0)
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-anchoroffset
+    fn AnchorOffset(&self) -> u32 {
+        self.range.get().map_or(0, |range| self.anchor(&range).1)
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-focusnode
+    fn GetFocusNode(&self) -> Option<DomRoot<Node>> {
+        self.range.get().map(|range| self.focus(&range).0)
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-focusoffset
+    fn FocusOffset(&self) -> u32 {
+        self.range.get().map_or(0, |range| self.focus(&range).1)
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-iscollapsed
+    fn IsCollapsed(&self) -> bool {
+        self.range.get().map_or(true, |range| range.Collapsed())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-rangecount
+    fn RangeCount(&self) -> u32 {
+        if self.range.get().is_some() {
+            1
+        } else {
+            0
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-type
+    fn Type(&self) -> DOMString {
+        match self.range.get() {
+            None => DOMString::from("None"),
+            Some(ref range) if range.Collapsed() => DOMString::from("Caret"),
+            Some(_) => DOMString::from("Range"),
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-getrangeat
+    fn GetRangeAt(&self, index: u32) -> Fallible<DomRoot<Range>> {
+        match self.range.get() {
+            Some(range) if index == 0 => Ok(range),
+            _ => Err(Error::IndexSize),
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-addrange
+    fn AddRange(&self, range: &Range) {
+        // Step 1.
+        if !self.is_in_document(&range.StartContainer()) {
+            return;
+        }
+        // Step 2.
+        if self.range.get().is_some() {
+            return;
+        }
+        // Step 3.
+        self.set_range(Some(range), Direction::Directionless);
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-removerange
+    fn RemoveRange(&self, range: &Range) -> ErrorResult {
+        match self.range.get() {
+            Some(ref current) if &**current == range => {
+                self.set_range(None, Direction::Directionless);
+                Ok(())
+            },
+            _ => Err(Error::NotFound),
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-removeallranges
+    fn RemoveAllRanges(&self) {
+        if self.range.get().is_some() {
+            self.set_range(None, Direction::Directionless);
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-empty
+    fn Empty(&self) {
+        self.RemoveAllRanges();
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-collapse
+    fn Collapse(&self, node: Option<&Node>, offset: u32) -> ErrorResult {
+        // Step 1.
+        let node = match node {
+            Some(node) => node,
+            None => {
+                self.RemoveAllRanges();
+                return Ok(());
+            },
+        };
+        // Step 2.
+        if node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+        // Step 3.
+        if offset > node.len() {
+            return Err(Error::IndexSize);
+        }
+        // Step 4.
+        if !self.is_in_document(node) {
+            return Ok(());
+        }
+        // Steps 5-6.
+        self.collapse_to(node, offset);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-setposition
+    fn SetPosition(&self, node: Option<&Node>, offset: u32) -> ErrorResult {
+        self.Collapse(node, offset)
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-collapsetostart
+    fn CollapseToStart(&self) -> ErrorResult {
+        let range = self.range.get().ok_or(Error::InvalidState)?;
+        self.collapse_to(&range.StartContainer(), range.StartOffset());
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-collapsetoend
+    fn CollapseToEnd(&self) -> ErrorResult {
+        let range = self.range.get().ok_or(Error::InvalidState)?;
+        self.collapse_to(&range.EndContainer(), range.EndOffset());
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-extend
+    fn Extend(&self, node: &Node, offset: u32) -> ErrorResult {
+        // Step 1.
+        if !self.is_in_document(node) {
+            return Ok(());
+        }
+        // Step 2.
+        let range = self.range.get().ok_or(Error::InvalidState)?;
+        if node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+        if offset > node.len() {
+            return Err(Error::IndexSize);
+        }
+        // Steps 3-10.
+        let (anchor_node, anchor_offset) = self.anchor(&range);
+        self.set_anchor_and_focus((&anchor_node, anchor_offset), (node, offset));
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-setbaseandextent
+    fn SetBaseAndExtent(
+        &self,
+        anchor_node: &Node,
+        anchor_offset: u32,
+        focus_node: &Node,
+        focus_offset: u32,
+    ) -> ErrorResult {
+        // Step 1.
+        if anchor_offset > anchor_node.len() || focus_offset > focus_node.len() {
+            return Err(Error::IndexSize);
+        }
+        if anchor_node.is_doctype() || focus_node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+        // Step 2.
+        if !self.is_in_document(anchor_node) || !self.is_in_document(focus_node) {
+            return Ok(());
+        }
+        // Steps 3-7.
+        self.set_anchor_and_focus((anchor_node, anchor_offset), (focus_node, focus_offset));
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-selectallchildren
+    fn SelectAllChildren(&self, node: &Node) -> ErrorResult {
+        // Step 1.
+        if node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+        // Step 2.
+        if !self.is_in_document(node) {
+            return Ok(());
+        }
+        // Steps 3-7.
+        let range = Range::new(&self.document, node, 0, node, node.children_count());
+        self.set_range(Some(&range), Direction::Forwards);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-modify
+    fn Modify(&self, alter: DOMString, direction: DOMString, granularity: DOMString) {
+        // Step 1.
+        let extend = match alter.to_ascii_lowercase().as_str() {
+            "extend" => true,
+            "move" => false,
+            _ => return,
+        };
+        // Step 2.
+        let forwards = match direction.to_ascii_lowercase().as_str() {
+            // TODO: "left" and "right" should depend on the direction of the text.
+            "forward" | "right" => true,
+            "backward" | "left" => false,
+            _ => return,
+        };
+        // Step 3.
+        let granularity = match granularity.to_ascii_lowercase().as_str() {
+            "character" => Granularity::Character,
+            "word" => Granularity::Word,
+            "documentboundary" => Granularity::DocumentBoundary,
+            // TODO: moving by sentences, lines and paragraphs needs layout information.
+            _ => return,
+        };
+        // Step 4.
+        let range = match self.range.get() {
+            Some(range) => range,
+            None => return,
+        };
+        let (focus_node, focus_offset) = self.focus(&range);
+        let (node, offset) =
+            match self.moved_point(&focus_node, focus_offset, forwards, granularity) {
+                Some(point) => point,
+                None => return,
+            };
+        if extend {
+            let (anchor_node, anchor_offset) = self.anchor(&range);
+            self.set_anchor_and_focus((&anchor_node, anchor_offset), (node.upcast(), offset));
+        } else {
+            self.collapse_to(node.upcast(), offset);
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-deletefromdocument
+    fn DeleteFromDocument(&self) -> ErrorResult {
+        match self.range.get() {
+            Some(range) => range.DeleteContents(),
+            None => Ok(()),
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-containsnode
+    fn ContainsNode(&self, node: &Node, allow_partial_containment: bool) -> bool {
+        // Step 1.
+        if !self.is_in_document(node) {
+            return false;
+        }
+        let range = match self.range.get() {
+            Some(range) => range,
+            None => return false,
+        };
+        let (start_node, start_offset) = (range.StartContainer(), range.StartOffset());
+        let (end_node, end_offset) = (range.EndContainer(), range.EndOffset());
+        let not_after = |a: (&Node, u32), b: (&Node, u32)| match bp_position(a.0, a.1, b.0, b.1) {
+            Some(Ordering::Less) | Some(Ordering::Equal) => true,
+            _ => false,
+        };
+        // Steps 2-3.
+        if allow_partial_containment {
+            not_after((&start_node, start_offset), (node, node.len())) &&
+                not_after((node, 0), (&end_node, end_offset))
+        } else {
+            not_after((&start_node, start_offset), (node, 0)) &&
+                not_after((node, node.len()), (&end_node, end_offset))
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-stringifier
+    fn Stringifier(&self) -> DOMString {
+        self.range
+            .get()
+            .map_or_else(DOMString::new, |range| range.Stringifier())
+    }
+}
@@ -360,6 +360,12 @@ impl ServiceWorkerGlobalScope {
                         scope.script_chan(),
                         CommonScriptMsg::CollectReports,
                     );
+
+                // The worker's event loop is destroyed,
+                // tell the constellation to drop the sender to our broadcast router.
+                scope
+                    .upcast::<GlobalScope>()
+                    .remove_broadcast_channel_router();
             })
             .expect("Thread spawning failed");
     }
@@ -3,8 +3,10 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootBinding::ShadowRootMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::{self, ShadowRootMode};
+use crate::dom::bindings::error::ErrorResult;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::reflect_dom_object;
@@ -15,10 +17,14 @@ use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::documentorshadowroot::{DocumentOrShadowRoot, StyleSheetInDocument};
 use crate::dom::element::Element;
 use crate::dom::node::{Node, NodeDamage, NodeFlags, ShadowIncluding, UnbindContext};
+use crate::dom::selection::Selection;
 use crate::dom::stylesheetlist::{StyleSheetList, StyleSheetListOwner};
 use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
 use crate::stylesheet_set::StylesheetSetRef;
 use dom_struct::dom_struct;
+use js::jsval::JSVal;
+use js::rust::HandleValue;
 use selectors::context::QuirksMode;
 use servo_arc::Arc;
 use servo_atoms::Atom;
@@ -90,8 +96,15 @@ impl ShadowRoot {
         None
     }
 
+    /// Returns the number of sheets owned by elements of this shadow root,
+    /// which come before the adopted ones.
     pub fn stylesheet_count(&self) -> usize {
-        self.author_styles.borrow().stylesheets.len()
+        self.author_styles
+            .borrow()
+            .stylesheets
+            .iter()
+            .filter(|sheet| sheet.owner.is_some())
+            .count()
     }
 
     pub fn stylesheet_at(&self, index: usize) -> Option<DomRoot<CSSStyleSheet>> {
@@ -99,7 +112,8 @@ impl ShadowRoot {
 
         stylesheets
             .get(index)
-            .and_then(|s| s.owner.upcast::<Node>().get_cssom_stylesheet())
+            .and_then(|s| s.owner.as_ref())
+            .and_then(|owner| owner.upcast::<Node>().get_cssom_stylesheet())
     }
 
     /// Add a stylesheet owned by `owner` to the list of shadow root sheets, in the
@@ -107,14 +121,8 @@ impl ShadowRoot {
     #[allow(unrooted_must_root)] // Owner needs to be rooted already necessarily.
     pub fn add_stylesheet(&self, owner: &Element, sheet: Arc<Stylesheet>) {
         let stylesheets = &mut self.author_styles.borrow_mut().stylesheets;
-        let insertion_point = stylesheets
-            .iter()
-            .find(|sheet_in_shadow| {
-                owner
-                    .upcast::<Node>()
-                    .is_before(sheet_in_shadow.owner.upcast())
-            })
-            .cloned();
+        let insertion_point =
+            DocumentOrShadowRoot::stylesheet_insertion_point(owner, stylesheets.iter());
         DocumentOrShadowRoot::add_stylesheet(
             owner,
             StylesheetSetRef::Author(stylesheets),
@@ -134,6 +142,16 @@ impl ShadowRoot {
         )
     }
 
+    /// Replace the adopted sheets of this shadow root, `old`, by `new`.
+    pub fn replace_adopted_stylesheets(&self, old: &[Arc<Stylesheet>], new: &[Arc<Stylesheet>]) {
+        DocumentOrShadowRoot::replace_adopted_stylesheets(
+            StylesheetSetRef::Author(&mut self.author_styles.borrow_mut().stylesheets),
+            old,
+            new,
+            self.document.style_shared_lock(),
+        );
+    }
+
     pub fn invalidate_stylesheets(&self) {
         self.document.invalidate_shadow_roots_stylesheets();
         self.author_styles.borrow_mut().stylesheets.force_dirty();
@@ -217,6 +235,11 @@ impl ShadowRootMethods for ShadowRoot {
         elements
     }
 
+    // https://w3c.github.io/selection-api/#dom-documentorshadowroot-getselection
+    fn GetSelection(&self) -> Option<DomRoot<Selection>> {
+        self.document.GetSelection()
+    }
+
     /// https://dom.spec.whatwg.org/#dom-shadowroot-mode
     fn Mode(&self) -> ShadowRootMode {
         ShadowRootMode::Closed
@@ -237,6 +260,21 @@ impl ShadowRootMethods for ShadowRoot {
             )
         })
     }
+
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    fn AdoptedStyleSheets(&self, cx: JSContext) -> JSVal {
+        self.document_or_shadow_root.adopted_stylesheets(cx)
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    fn SetAdoptedStyleSheets(&self, cx: JSContext, value: HandleValue) -> ErrorResult {
+        self.document_or_shadow_root.set_adopted_stylesheets(
+            cx,
+            value,
+            StyleSheetListOwner::ShadowRoot(Dom::from_ref(self)),
+            &self.document,
+        )
+    }
 }
 
 #[allow(unsafe_code)]
@@ -14,6 +14,7 @@ use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::storageevent::StorageEvent;
 use crate::dom::window::Window;
 use crate::task_source::TaskSource;
+use devtools_traits::{ScriptToDevtoolsControlMsg, StorageArea, StorageChange};
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
@@ -190,11 +191,31 @@ impl Storage {
     ) {
         let storage = self.storage_type;
         let url = self.get_url();
+        let global = self.global();
+        let devtools_chan = global
+            .devtools_chan()
+            .filter(|_| global.devtools_wants_storage_updates());
+        if let Some(chan) = devtools_chan {
+            let area = match storage {
+                StorageType::Local => StorageArea::LocalStorage,
+                StorageType::Session => StorageArea::SessionStorage,
+            };
+            let change = match (&key, &old_value, &new_value) {
+                (None, _, _) => StorageChange::Cleared,
+                (Some(key), None, _) => StorageChange::Added(key.clone()),
+                (Some(key), _, None) => StorageChange::Removed(key.clone()),
+                (Some(key), _, _) => StorageChange::Changed(key.clone()),
+            };
+            let origin = global.origin().immutable().ascii_serialization();
+            let _ = chan.send(ScriptToDevtoolsControlMsg::StorageChanged(
+                global.pipeline_id(),
+                origin,
+                area,
+                change,
+            ));
+        }
         let msg = ScriptMsg::BroadcastStorageEvent(storage, url, key, old_value, new_value);
-        self.global()
-            .script_to_constellation_chan()
-            .send(msg)
-            .unwrap();
+        global.script_to_constellation_chan().send(msg).unwrap();
     }
 
     /// <https://html.spec.whatwg.org/multipage/#send-a-storage-notification>
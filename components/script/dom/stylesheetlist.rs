@@ -17,7 +17,7 @@ use servo_arc::Arc;
 use style::stylesheets::Stylesheet;
 
 #[unrooted_must_root_lint::must_root]
-#[derive(JSTraceable, MallocSizeOf)]
+#[derive(Clone, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum StyleSheetListOwner {
     Document(Dom<Document>),
     ShadowRoot(Dom<ShadowRoot>),
@@ -56,6 +56,15 @@ impl StyleSheetListOwner {
         }
     }
 
+    pub fn replace_adopted_stylesheets(&self, old: &[Arc<Stylesheet>], new: &[Arc<Stylesheet>]) {
+        match *self {
+            StyleSheetListOwner::Document(ref doc) => doc.replace_adopted_stylesheets(old, new),
+            StyleSheetListOwner::ShadowRoot(ref shadow_root) => {
+                shadow_root.replace_adopted_stylesheets(old, new)
+            },
+        }
+    }
+
     pub fn invalidate_stylesheets(&self) {
         match *self {
             StyleSheetListOwner::Document(ref doc) => doc.invalidate_stylesheets(),
@@ -0,0 +1,2167 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::enter_realm;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{KeyType, KeyUsage};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding;
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    AesCbcParams, AesCtrParams, AesDerivedKeyParams, AesGcmParams, AesKeyGenParams, Algorithm,
+    CryptoKeyPair, EcKeyGenParams, EcKeyImportParams, EcdsaParams, HkdfParams, HmacImportParams,
+    HmacKeyGenParams, JsonWebKey, KeyFormat, Pbkdf2Params, RsaHashedImportParams,
+    RsaHashedKeyGenParams, RsaOaepParams, RsaPssParams, SubtleCryptoMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::{
+    ArrayBufferViewOrArrayBuffer, BufferSourceOrJsonWebKey, ObjectOrString,
+};
+use crate::dom::bindings::conversions::{
+    ConversionResult, FromJSValConvertible, ToJSValConvertible,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::refcounted::TrustedPromise;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::cryptokey::{CryptoKey, Handle, KeyAlgorithmData};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::script_runtime::JSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
+use crossbeam_channel::{unbounded, Sender};
+use dom_struct::dom_struct;
+use js::jsapi::JSObject;
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::rust::HandleValue;
+use js::typedarray::{ArrayBuffer, CreateWith};
+use openssl::bn::{BigNum, BigNumContext, BigNumRef};
+use openssl::ec::{EcGroup, EcKey, EcPoint, PointConversionForm};
+use openssl::ecdsa::EcdsaSig;
+use openssl::error::ErrorStack;
+use openssl::hash::{hash, MessageDigest};
+use openssl::memcmp;
+use openssl::nid::Nid;
+use openssl::pkcs5::pbkdf2_hmac;
+use openssl::pkey::{HasPublic, PKey, PKeyRef, Private, Public};
+use openssl::rand::rand_bytes;
+use openssl::rsa::{Padding, Rsa};
+use openssl::sign::{RsaPssSaltlen, Signer, Verifier};
+use openssl::symm::{self, Cipher, Crypter, Mode};
+use std::ptr;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::thread;
+
+/// The names of the algorithms registered with the `SubtleCrypto` interface.
+/// https://w3c.github.io/webcrypto/#algorithm-overview
+#[derive(Clone, Copy, Debug, Eq, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum AlgorithmName {
+    AesCbc,
+    AesCtr,
+    AesGcm,
+    Ecdsa,
+    Hkdf,
+    Hmac,
+    Pbkdf2,
+    RsaOaep,
+    RsaPss,
+    RsassaPkcs1V15,
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl AlgorithmName {
+    /// Algorithm names are matched ASCII case-insensitively.
+    /// https://w3c.github.io/webcrypto/#algorithm-normalization-normalize-an-algorithm
+    fn from_name(name: &str) -> Option<AlgorithmName> {
+        Some(match &*name.to_ascii_uppercase() {
+            "AES-CBC" => AlgorithmName::AesCbc,
+            "AES-CTR" => AlgorithmName::AesCtr,
+            "AES-GCM" => AlgorithmName::AesGcm,
+            "ECDSA" => AlgorithmName::Ecdsa,
+            "HKDF" => AlgorithmName::Hkdf,
+            "HMAC" => AlgorithmName::Hmac,
+            "PBKDF2" => AlgorithmName::Pbkdf2,
+            "RSA-OAEP" => AlgorithmName::RsaOaep,
+            "RSA-PSS" => AlgorithmName::RsaPss,
+            "RSASSA-PKCS1-V1_5" => AlgorithmName::RsassaPkcs1V15,
+            "SHA-1" => AlgorithmName::Sha1,
+            "SHA-256" => AlgorithmName::Sha256,
+            "SHA-384" => AlgorithmName::Sha384,
+            "SHA-512" => AlgorithmName::Sha512,
+            _ => return None,
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            AlgorithmName::AesCbc => "AES-CBC",
+            AlgorithmName::AesCtr => "AES-CTR",
+            AlgorithmName::AesGcm => "AES-GCM",
+            AlgorithmName::Ecdsa => "ECDSA",
+            AlgorithmName::Hkdf => "HKDF",
+            AlgorithmName::Hmac => "HMAC",
+            AlgorithmName::Pbkdf2 => "PBKDF2",
+            AlgorithmName::RsaOaep => "RSA-OAEP",
+            AlgorithmName::RsaPss => "RSA-PSS",
+            AlgorithmName::RsassaPkcs1V15 => "RSASSA-PKCS1-v1_5",
+            AlgorithmName::Sha1 => "SHA-1",
+            AlgorithmName::Sha256 => "SHA-256",
+            AlgorithmName::Sha384 => "SHA-384",
+            AlgorithmName::Sha512 => "SHA-512",
+        }
+    }
+
+    fn message_digest(&self) -> Fallible<MessageDigest> {
+        match *self {
+            AlgorithmName::Sha1 => Ok(MessageDigest::sha1()),
+            AlgorithmName::Sha256 => Ok(MessageDigest::sha256()),
+            AlgorithmName::Sha384 => Ok(MessageDigest::sha384()),
+            AlgorithmName::Sha512 => Ok(MessageDigest::sha512()),
+            _ => Err(Error::NotSupported),
+        }
+    }
+
+    /// The digest length of a hash algorithm, in bytes.
+    fn digest_length(&self) -> usize {
+        match *self {
+            AlgorithmName::Sha1 => 20,
+            AlgorithmName::Sha256 => 32,
+            AlgorithmName::Sha384 => 48,
+            _ => 64,
+        }
+    }
+
+    /// The block size of a hash algorithm, in bits.
+    fn block_size(&self) -> u32 {
+        match *self {
+            AlgorithmName::Sha1 | AlgorithmName::Sha256 => 512,
+            _ => 1024,
+        }
+    }
+
+    /// The suffix used for this hash in JSON Web Algorithms identifiers.
+    fn jwa_suffix(&self) -> &'static str {
+        match *self {
+            AlgorithmName::Sha1 => "1",
+            AlgorithmName::Sha256 => "256",
+            AlgorithmName::Sha384 => "384",
+            _ => "512",
+        }
+    }
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-NamedCurve
+#[derive(Clone, Copy, Debug, Eq, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum NamedCurve {
+    P256,
+    P384,
+    P521,
+}
+
+impl NamedCurve {
+    fn from_name(name: &str) -> Option<NamedCurve> {
+        match name {
+            "P-256" => Some(NamedCurve::P256),
+            "P-384" => Some(NamedCurve::P384),
+            "P-521" => Some(NamedCurve::P521),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            NamedCurve::P256 => "P-256",
+            NamedCurve::P384 => "P-384",
+            NamedCurve::P521 => "P-521",
+        }
+    }
+
+    fn nid(&self) -> Nid {
+        match *self {
+            NamedCurve::P256 => Nid::X9_62_PRIME256V1,
+            NamedCurve::P384 => Nid::SECP384R1,
+            NamedCurve::P521 => Nid::SECP521R1,
+        }
+    }
+
+    /// The length of a field element of this curve, in bytes.
+    fn coordinate_length(&self) -> usize {
+        match *self {
+            NamedCurve::P256 => 32,
+            NamedCurve::P384 => 48,
+            NamedCurve::P521 => 66,
+        }
+    }
+
+    fn jwa_name(&self) -> &'static str {
+        match *self {
+            NamedCurve::P256 => "ES256",
+            NamedCurve::P384 => "ES384",
+            NamedCurve::P521 => "ES512",
+        }
+    }
+
+    fn group(&self) -> Fallible<EcGroup> {
+        EcGroup::from_curve_name(self.nid()).map_err(operation_error)
+    }
+}
+
+/// The operations an algorithm can be registered for.
+/// https://w3c.github.io/webcrypto/#supported-operation
+#[derive(Clone, Copy, PartialEq)]
+enum Operation {
+    Encrypt,
+    Decrypt,
+    Sign,
+    Verify,
+    Digest,
+    GenerateKey,
+    DeriveBits,
+    ImportKey,
+    GetKeyLength,
+}
+
+/// The result of normalizing an algorithm, holding copies of the members of the
+/// desired dictionary type so that it can be sent to another thread.
+/// https://w3c.github.io/webcrypto/#algorithm-normalization-normalize-an-algorithm
+enum NormalizedAlgorithm {
+    /// An algorithm whose parameters are a plain `Algorithm` dictionary.
+    Algorithm(AlgorithmName),
+    AesCbcParams {
+        iv: Vec<u8>,
+    },
+    AesCtrParams {
+        counter: Vec<u8>,
+        length: u8,
+    },
+    AesGcmParams {
+        iv: Vec<u8>,
+        additional_data: Option<Vec<u8>>,
+        tag_length: Option<u8>,
+    },
+    /// `AesKeyGenParams` or `AesDerivedKeyParams`.
+    AesKeyGenParams(AlgorithmName, u16),
+    EcdsaParams {
+        hash: AlgorithmName,
+    },
+    /// `EcKeyGenParams` or `EcKeyImportParams`.
+    EcKeyGenParams(NamedCurve),
+    HkdfParams {
+        hash: AlgorithmName,
+        salt: Vec<u8>,
+        info: Vec<u8>,
+    },
+    /// `HmacImportParams` or `HmacKeyGenParams`.
+    HmacImportParams {
+        hash: AlgorithmName,
+        length: Option<u32>,
+    },
+    Pbkdf2Params {
+        hash: AlgorithmName,
+        salt: Vec<u8>,
+        iterations: u32,
+    },
+    RsaHashedImportParams(AlgorithmName, AlgorithmName),
+    RsaHashedKeyGenParams {
+        name: AlgorithmName,
+        modulus_length: u32,
+        public_exponent: Vec<u8>,
+        hash: AlgorithmName,
+    },
+    RsaOaepParams {
+        label: Option<Vec<u8>>,
+    },
+    RsaPssParams {
+        salt_length: u32,
+    },
+}
+
+impl NormalizedAlgorithm {
+    fn name(&self) -> AlgorithmName {
+        match *self {
+            NormalizedAlgorithm::Algorithm(name) |
+            NormalizedAlgorithm::AesKeyGenParams(name, _) |
+            NormalizedAlgorithm::RsaHashedImportParams(name, _) |
+            NormalizedAlgorithm::RsaHashedKeyGenParams { name, .. } => name,
+            NormalizedAlgorithm::AesCbcParams { .. } => AlgorithmName::AesCbc,
+            NormalizedAlgorithm::AesCtrParams { .. } => AlgorithmName::AesCtr,
+            NormalizedAlgorithm::AesGcmParams { .. } => AlgorithmName::AesGcm,
+            NormalizedAlgorithm::EcdsaParams { .. } | NormalizedAlgorithm::EcKeyGenParams(_) => {
+                AlgorithmName::Ecdsa
+            },
+            NormalizedAlgorithm::HkdfParams { .. } => AlgorithmName::Hkdf,
+            NormalizedAlgorithm::HmacImportParams { .. } => AlgorithmName::Hmac,
+            NormalizedAlgorithm::Pbkdf2Params { .. } => AlgorithmName::Pbkdf2,
+            NormalizedAlgorithm::RsaOaepParams { .. } => AlgorithmName::RsaOaep,
+            NormalizedAlgorithm::RsaPssParams { .. } => AlgorithmName::RsaPss,
+        }
+    }
+}
+
+/// A `JsonWebKey` dictionary whose members have been copied out of their
+/// `DOMString`s, so that it can be sent to another thread.
+#[derive(Default)]
+struct Jwk {
+    kty: Option<String>,
+    use_: Option<String>,
+    key_ops: Option<Vec<String>>,
+    alg: Option<String>,
+    ext: Option<bool>,
+    crv: Option<String>,
+    x: Option<String>,
+    y: Option<String>,
+    d: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+    p: Option<String>,
+    q: Option<String>,
+    dp: Option<String>,
+    dq: Option<String>,
+    qi: Option<String>,
+    k: Option<String>,
+    has_other_primes: bool,
+}
+
+impl Jwk {
+    fn from_dictionary(jwk: &JsonWebKey) -> Jwk {
+        let string = |member: &Option<DOMString>| member.as_ref().map(|value| value.to_string());
+        Jwk {
+            kty: string(&jwk.kty),
+            use_: string(&jwk.use_),
+            key_ops: jwk
+                .key_ops
+                .as_ref()
+                .map(|ops| ops.iter().map(|op| op.to_string()).collect()),
+            alg: string(&jwk.alg),
+            ext: jwk.ext,
+            crv: string(&jwk.crv),
+            x: string(&jwk.x),
+            y: string(&jwk.y),
+            d: string(&jwk.d),
+            n: string(&jwk.n),
+            e: string(&jwk.e),
+            p: string(&jwk.p),
+            q: string(&jwk.q),
+            dp: string(&jwk.dp),
+            dq: string(&jwk.dq),
+            qi: string(&jwk.qi),
+            k: string(&jwk.k),
+            has_other_primes: jwk.oth.is_some(),
+        }
+    }
+
+    fn to_dictionary(&self) -> JsonWebKey {
+        let string =
+            |member: &Option<String>| member.as_ref().map(|value| DOMString::from(&**value));
+        let mut jwk = JsonWebKey::empty();
+        jwk.kty = string(&self.kty);
+        jwk.key_ops = self
+            .key_ops
+            .as_ref()
+            .map(|ops| ops.iter().map(|op| DOMString::from(&**op)).collect());
+        jwk.alg = string(&self.alg);
+        jwk.ext = self.ext;
+        jwk.crv = string(&self.crv);
+        jwk.x = string(&self.x);
+        jwk.y = string(&self.y);
+        jwk.d = string(&self.d);
+        jwk.n = string(&self.n);
+        jwk.e = string(&self.e);
+        jwk.p = string(&self.p);
+        jwk.q = string(&self.q);
+        jwk.dp = string(&self.dp);
+        jwk.dq = string(&self.dq);
+        jwk.qi = string(&self.qi);
+        jwk.k = string(&self.k);
+        jwk
+    }
+}
+
+/// The keyData argument of importKey.
+enum KeyData {
+    Bytes(Vec<u8>),
+    Jwk(Jwk),
+}
+
+/// A key created off the script thread, reflected as a `CryptoKey` once the
+/// operation completes.
+struct NewKey {
+    extractable: bool,
+    algorithm: KeyAlgorithmData,
+    usages: Vec<KeyUsage>,
+    handle: Handle,
+}
+
+impl NewKey {
+    fn into_crypto_key(self, global: &GlobalScope) -> DomRoot<CryptoKey> {
+        CryptoKey::new(
+            global,
+            self.extractable,
+            self.algorithm,
+            self.usages,
+            self.handle,
+        )
+    }
+}
+
+/// The value a `SubtleCrypto` promise is resolved with.
+enum CryptoResult {
+    Bytes(Vec<u8>),
+    Bool(bool),
+    Key(NewKey),
+    KeyPair { public: NewKey, private: NewKey },
+    Jwk(Jwk),
+}
+
+// https://w3c.github.io/webcrypto/#subtlecrypto-interface
+#[dom_struct]
+pub struct SubtleCrypto {
+    reflector_: Reflector,
+    /// The channel to the thread that runs the operations of this object, one after the other.
+    /// The thread is started by the first operation, and exits once this object is collected.
+    #[ignore_malloc_size_of = "Defined in std"]
+    crypto_thread: DomRefCell<Option<Sender<CryptoJob>>>,
+}
+
+impl SubtleCrypto {
+    fn new_inherited() -> SubtleCrypto {
+        SubtleCrypto {
+            reflector_: Reflector::new(),
+            crypto_thread: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<SubtleCrypto> {
+        reflect_dom_object(
+            Box::new(SubtleCrypto::new_inherited()),
+            global,
+            SubtleCryptoBinding::Wrap,
+        )
+    }
+
+    /// Runs `operation` in parallel, and settles `promise` with its result
+    /// in a task queued on the DOM manipulation task source.
+    fn perform<F>(&self, promise: &Rc<Promise>, operation: F)
+    where
+        F: FnOnce() -> Fallible<CryptoResult> + Send + 'static,
+    {
+        let global = self.global();
+        let promise = TrustedPromise::new(promise.clone());
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+
+        let job = Box::new(move || {
+            let result = operation();
+            let _ = task_source.queue_with_canceller(
+                task!(settle_subtle_crypto_promise: move || {
+                    settle_promise(&promise.root(), result);
+                }),
+                &canceller,
+            );
+        });
+        let mut crypto_thread = self.crypto_thread.borrow_mut();
+        let sender = crypto_thread.get_or_insert_with(|| {
+            let (sender, receiver) = unbounded::<CryptoJob>();
+            thread::Builder::new()
+                .name("SubtleCrypto".to_owned())
+                .spawn(move || {
+                    for job in receiver {
+                        job();
+                    }
+                })
+                .expect("Thread spawning failed");
+            sender
+        });
+        if sender.send(job).is_err() {
+            warn!("The SubtleCrypto thread is gone.");
+        }
+    }
+}
+
+/// An operation that runs on the thread of a `SubtleCrypto` object.
+type CryptoJob = Box<dyn FnOnce() + Send>;
+
+impl SubtleCryptoMethods for SubtleCrypto {
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-encrypt
+    fn Encrypt(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+    ) -> Rc<Promise> {
+        // Steps 1-2.
+        let data = buffer_source_to_vec(&data);
+        let promise = Promise::new(&self.global());
+
+        // Steps 3-6.
+        let normalized = match normalize_algorithm(cx, &algorithm, Operation::Encrypt) {
+            Ok(normalized) => normalized,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Steps 8-9.
+        if let Err(error) = check_key(key, normalized.name(), KeyUsage::Encrypt) {
+            promise.reject_error(error);
+            return promise;
+        }
+
+        // Steps 7 and 10.
+        let key_algorithm = key.algorithm().clone();
+        let handle = key.handle().clone();
+        self.perform(&promise, move || {
+            encrypt(&normalized, &key_algorithm, &handle, &data).map(CryptoResult::Bytes)
+        });
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-decrypt
+    fn Decrypt(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+    ) -> Rc<Promise> {
+        // Steps 1-2.
+        let data = buffer_source_to_vec(&data);
+        let promise = Promise::new(&self.global());
+
+        // Steps 3-6.
+        let normalized = match normalize_algorithm(cx, &algorithm, Operation::Decrypt) {
+            Ok(normalized) => normalized,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Steps 8-9.
+        if let Err(error) = check_key(key, normalized.name(), KeyUsage::Decrypt) {
+            promise.reject_error(error);
+            return promise;
+        }
+
+        // Steps 7 and 10.
+        let key_algorithm = key.algorithm().clone();
+        let handle = key.handle().clone();
+        self.perform(&promise, move || {
+            decrypt(&normalized, &key_algorithm, &handle, &data).map(CryptoResult::Bytes)
+        });
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-sign
+    fn Sign(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+    ) -> Rc<Promise> {
+        // Steps 1-2.
+        let data = buffer_source_to_vec(&data);
+        let promise = Promise::new(&self.global());
+
+        // Steps 3-6.
+        let normalized = match normalize_algorithm(cx, &algorithm, Operation::Sign) {
+            Ok(normalized) => normalized,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Steps 8-9.
+        if let Err(error) = check_key(key, normalized.name(), KeyUsage::Sign) {
+            promise.reject_error(error);
+            return promise;
+        }
+
+        // Steps 7 and 10.
+        let key_algorithm = key.algorithm().clone();
+        let handle = key.handle().clone();
+        self.perform(&promise, move || {
+            sign(&normalized, &key_algorithm, &handle, &data).map(CryptoResult::Bytes)
+        });
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-verify
+    fn Verify(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        signature: ArrayBufferViewOrArrayBuffer,
+        data: ArrayBufferViewOrArrayBuffer,
+    ) -> Rc<Promise> {
+        // Steps 1-4.
+        let signature = buffer_source_to_vec(&signature);
+        let data = buffer_source_to_vec(&data);
+        let promise = Promise::new(&self.global());
+
+        // Steps 5-8.
+        let normalized = match normalize_algorithm(cx, &algorithm, Operation::Verify) {
+            Ok(normalized) => normalized,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Steps 10-11.
+        if let Err(error) = check_key(key, normalized.name(), KeyUsage::Verify) {
+            promise.reject_error(error);
+            return promise;
+        }
+
+        // Steps 9 and 12.
+        let key_algorithm = key.algorithm().clone();
+        let handle = key.handle().clone();
+        self.perform(&promise, move || {
+            verify(&normalized, &key_algorithm, &handle, &signature, &data).map(CryptoResult::Bool)
+        });
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-digest
+    fn Digest(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        data: ArrayBufferViewOrArrayBuffer,
+    ) -> Rc<Promise> {
+        // Steps 1-2.
+        let data = buffer_source_to_vec(&data);
+        let promise = Promise::new(&self.global());
+
+        // Steps 3-4.
+        let normalized = match normalize_algorithm(cx, &algorithm, Operation::Digest) {
+            Ok(normalized) => normalized,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Steps 5-8.
+        self.perform(&promise, move || {
+            let digest = normalized.name().message_digest()?;
+            hash(digest, &data)
+                .map(|bytes| CryptoResult::Bytes(bytes.to_vec()))
+                .map_err(operation_error)
+        });
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-generateKey
+    fn GenerateKey(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+    ) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+
+        // Steps 1-3.
+        let normalized = match normalize_algorithm(cx, &algorithm, Operation::GenerateKey) {
+            Ok(normalized) => normalized,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Steps 4-6.
+        self.perform(&promise, move || {
+            generate_key(normalized, extractable, key_usages)
+        });
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-deriveKey
+    fn DeriveKey(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        base_key: &CryptoKey,
+        derived_key_type: ObjectOrString,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+    ) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+
+        // Steps 1-10.
+        let normalized =
+            normalize_algorithm(cx, &algorithm, Operation::DeriveBits).and_then(|normalized| {
+                let import = normalize_algorithm(cx, &derived_key_type, Operation::ImportKey)?;
+                let key_length =
+                    normalize_algorithm(cx, &derived_key_type, Operation::GetKeyLength)?;
+                check_key(base_key, normalized.name(), KeyUsage::DeriveKey)?;
+                Ok((normalized, import, key_length))
+            });
+        let (normalized, import, key_length) = match normalized {
+            Ok(normalized) => normalized,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Steps 11-17.
+        let handle = base_key.handle().clone();
+        self.perform(&promise, move || {
+            let length = get_key_length(&key_length)?;
+            let secret = derive_bits(&normalized, &handle, length)?;
+            import_key(
+                KeyFormat::Raw,
+                KeyData::Bytes(secret),
+                import,
+                extractable,
+                key_usages,
+            )
+            .map(CryptoResult::Key)
+        });
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-deriveBits
+    fn DeriveBits(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        base_key: &CryptoKey,
+        length: u32,
+    ) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+
+        // Steps 1-4 and 7-8.
+        let normalized =
+            normalize_algorithm(cx, &algorithm, Operation::DeriveBits).and_then(|normalized| {
+                check_key(base_key, normalized.name(), KeyUsage::DeriveBits)?;
+                Ok(normalized)
+            });
+        let normalized = match normalized {
+            Ok(normalized) => normalized,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Steps 5-6 and 9-10.
+        let handle = base_key.handle().clone();
+        self.perform(&promise, move || {
+            derive_bits(&normalized, &handle, length).map(CryptoResult::Bytes)
+        });
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-importKey
+    fn ImportKey(
+        &self,
+        cx: JSContext,
+        format: KeyFormat,
+        key_data: BufferSourceOrJsonWebKey,
+        algorithm: ObjectOrString,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+    ) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+
+        // Steps 1-3.
+        let normalized = match normalize_algorithm(cx, &algorithm, Operation::ImportKey) {
+            Ok(normalized) => normalized,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Step 4.
+        let key_data = match (format, key_data) {
+            (KeyFormat::Jwk, BufferSourceOrJsonWebKey::JsonWebKey(jwk)) => {
+                KeyData::Jwk(Jwk::from_dictionary(&jwk))
+            },
+            (KeyFormat::Jwk, _) | (_, BufferSourceOrJsonWebKey::JsonWebKey(_)) => {
+                promise.reject_error(Error::Type(
+                    "keyData does not match the key format.".to_owned(),
+                ));
+                return promise;
+            },
+            (_, BufferSourceOrJsonWebKey::ArrayBufferView(view)) => KeyData::Bytes(view.to_vec()),
+            (_, BufferSourceOrJsonWebKey::ArrayBuffer(buffer)) => KeyData::Bytes(buffer.to_vec()),
+        };
+
+        // Steps 5-8.
+        self.perform(&promise, move || {
+            import_key(format, key_data, normalized, extractable, key_usages).map(CryptoResult::Key)
+        });
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-exportKey
+    fn ExportKey(&self, format: KeyFormat, key: &CryptoKey) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+
+        // Step 6.
+        if !key.extractable() {
+            promise.reject_error(Error::InvalidAccess);
+            return promise;
+        }
+
+        // Steps 1-5 and 7-8.
+        let algorithm = key.algorithm().clone();
+        let handle = key.handle().clone();
+        let usages = key.usages().to_vec();
+        self.perform(&promise, move || {
+            export_key(format, &algorithm, &handle, &usages)
+        });
+        promise
+    }
+}
+
+#[allow(unsafe_code)]
+fn settle_promise(promise: &Promise, result: Fallible<CryptoResult>) {
+    let global = promise.global();
+    match result {
+        Ok(CryptoResult::Bytes(bytes)) => {
+            let cx = global.get_cx();
+            let _ac = enter_realm(&*global);
+            rooted!(in(*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+            let created = unsafe {
+                ArrayBuffer::create(*cx, CreateWith::Slice(&bytes), array_buffer.handle_mut())
+            };
+            if created.is_err() {
+                return promise.reject_error(Error::JSFailed);
+            }
+            rooted!(in(*cx) let value = ObjectValue(array_buffer.get()));
+            promise.resolve(cx, value.handle());
+        },
+        Ok(CryptoResult::Bool(value)) => promise.resolve_native(&value),
+        Ok(CryptoResult::Key(key)) => promise.resolve_native(&key.into_crypto_key(&global)),
+        Ok(CryptoResult::KeyPair { public, private }) => {
+            let pair = CryptoKeyPair {
+                publicKey: Some(public.into_crypto_key(&global)),
+                privateKey: Some(private.into_crypto_key(&global)),
+            };
+            promise.resolve_native(&pair);
+        },
+        Ok(CryptoResult::Jwk(jwk)) => promise.resolve_native(&jwk.to_dictionary()),
+        Err(error) => promise.reject_error(error),
+    }
+}
+
+fn buffer_source_to_vec(source: &ArrayBufferViewOrArrayBuffer) -> Vec<u8> {
+    match *source {
+        ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref view) => view.to_vec(),
+        ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref buffer) => buffer.to_vec(),
+    }
+}
+
+fn operation_error(_: ErrorStack) -> Error {
+    Error::Operation
+}
+
+fn data_error(_: ErrorStack) -> Error {
+    Error::Data
+}
+
+#[allow(unsafe_code)]
+fn convert_dictionary<T>(cx: JSContext, value: HandleValue) -> Fallible<T>
+where
+    T: FromJSValConvertible<Config = ()>,
+{
+    match unsafe { T::from_jsval(*cx, value, ()) } {
+        Ok(ConversionResult::Success(dictionary)) => Ok(dictionary),
+        Ok(ConversionResult::Failure(error)) => Err(Error::Type(error.into_owned())),
+        Err(()) => Err(Error::JSFailed),
+    }
+}
+
+// https://w3c.github.io/webcrypto/#algorithm-normalization-normalize-an-algorithm
+#[allow(unsafe_code)]
+fn normalize_algorithm(
+    cx: JSContext,
+    algorithm: &ObjectOrString,
+    operation: Operation,
+) -> Fallible<NormalizedAlgorithm> {
+    rooted!(in(*cx) let mut value = UndefinedValue());
+    match *algorithm {
+        // Step 1.
+        ObjectOrString::String(ref name) => unsafe {
+            let dictionary = Algorithm { name: name.clone() };
+            dictionary.to_jsval(*cx, value.handle_mut());
+        },
+        ObjectOrString::Object(ref object) => value.set(ObjectValue(object.get())),
+    }
+
+    // Steps 2-7.
+    let initial = convert_dictionary::<Algorithm>(cx, value.handle())?;
+    let name = AlgorithmName::from_name(&initial.name).ok_or(Error::NotSupported)?;
+
+    // Steps 8-10.
+    let hash = |hash: &ObjectOrString| -> Fallible<AlgorithmName> {
+        Ok(normalize_algorithm(cx, hash, Operation::Digest)?.name())
+    };
+    let named_curve = |curve: &DOMString| NamedCurve::from_name(curve).ok_or(Error::NotSupported);
+    Ok(match (operation, name) {
+        (Operation::Digest, AlgorithmName::Sha1) |
+        (Operation::Digest, AlgorithmName::Sha256) |
+        (Operation::Digest, AlgorithmName::Sha384) |
+        (Operation::Digest, AlgorithmName::Sha512) |
+        (Operation::Sign, AlgorithmName::RsassaPkcs1V15) |
+        (Operation::Verify, AlgorithmName::RsassaPkcs1V15) |
+        (Operation::Sign, AlgorithmName::Hmac) |
+        (Operation::Verify, AlgorithmName::Hmac) |
+        (Operation::ImportKey, AlgorithmName::AesCbc) |
+        (Operation::ImportKey, AlgorithmName::AesCtr) |
+        (Operation::ImportKey, AlgorithmName::AesGcm) |
+        (Operation::ImportKey, AlgorithmName::Hkdf) |
+        (Operation::ImportKey, AlgorithmName::Pbkdf2) |
+        (Operation::GetKeyLength, AlgorithmName::Hkdf) |
+        (Operation::GetKeyLength, AlgorithmName::Pbkdf2) => NormalizedAlgorithm::Algorithm(name),
+        (Operation::Encrypt, AlgorithmName::RsaOaep) |
+        (Operation::Decrypt, AlgorithmName::RsaOaep) => {
+            let params =
+                convert_dictionary::<RootedTraceableBox<RsaOaepParams>>(cx, value.handle())?;
+            NormalizedAlgorithm::RsaOaepParams {
+                label: params.label.as_ref().map(buffer_source_to_vec),
+            }
+        },
+        (Operation::Encrypt, AlgorithmName::AesCbc) |
+        (Operation::Decrypt, AlgorithmName::AesCbc) => {
+            let params =
+                convert_dictionary::<RootedTraceableBox<AesCbcParams>>(cx, value.handle())?;
+            NormalizedAlgorithm::AesCbcParams {
+                iv: buffer_source_to_vec(&params.iv),
+            }
+        },
+        (Operation::Encrypt, AlgorithmName::AesCtr) |
+        (Operation::Decrypt, AlgorithmName::AesCtr) => {
+            let params =
+                convert_dictionary::<RootedTraceableBox<AesCtrParams>>(cx, value.handle())?;
+            NormalizedAlgorithm::AesCtrParams {
+                counter: buffer_source_to_vec(&params.counter),
+                length: params.length,
+            }
+        },
+        (Operation::Encrypt, AlgorithmName::AesGcm) |
+        (Operation::Decrypt, AlgorithmName::AesGcm) => {
+            let params =
+                convert_dictionary::<RootedTraceableBox<AesGcmParams>>(cx, value.handle())?;
+            NormalizedAlgorithm::AesGcmParams {
+                iv: buffer_source_to_vec(&params.iv),
+                additional_data: params.additionalData.as_ref().map(buffer_source_to_vec),
+                tag_length: params.tagLength,
+            }
+        },
+        (Operation::Sign, AlgorithmName::RsaPss) | (Operation::Verify, AlgorithmName::RsaPss) => {
+            let params = convert_dictionary::<RsaPssParams>(cx, value.handle())?;
+            NormalizedAlgorithm::RsaPssParams {
+                salt_length: params.saltLength,
+            }
+        },
+        (Operation::Sign, AlgorithmName::Ecdsa) | (Operation::Verify, AlgorithmName::Ecdsa) => {
+            let params = convert_dictionary::<RootedTraceableBox<EcdsaParams>>(cx, value.handle())?;
+            NormalizedAlgorithm::EcdsaParams {
+                hash: hash(&params.hash)?,
+            }
+        },
+        (Operation::GenerateKey, AlgorithmName::RsassaPkcs1V15) |
+        (Operation::GenerateKey, AlgorithmName::RsaPss) |
+        (Operation::GenerateKey, AlgorithmName::RsaOaep) => {
+            let params = convert_dictionary::<RootedTraceableBox<RsaHashedKeyGenParams>>(
+                cx,
+                value.handle(),
+            )?;
+            NormalizedAlgorithm::RsaHashedKeyGenParams {
+                name: name,
+                modulus_length: params.parent.modulusLength,
+                public_exponent: params.parent.publicExponent.to_vec(),
+                hash: hash(&params.hash)?,
+            }
+        },
+        (Operation::GenerateKey, AlgorithmName::Ecdsa) => {
+            let params = convert_dictionary::<EcKeyGenParams>(cx, value.handle())?;
+            NormalizedAlgorithm::EcKeyGenParams(named_curve(&params.namedCurve)?)
+        },
+        (Operation::GenerateKey, AlgorithmName::Hmac) => {
+            let params =
+                convert_dictionary::<RootedTraceableBox<HmacKeyGenParams>>(cx, value.handle())?;
+            NormalizedAlgorithm::HmacImportParams {
+                hash: hash(&params.hash)?,
+                length: params.length,
+            }
+        },
+        (Operation::GenerateKey, AlgorithmName::AesCbc) |
+        (Operation::GenerateKey, AlgorithmName::AesCtr) |
+        (Operation::GenerateKey, AlgorithmName::AesGcm) => {
+            let params = convert_dictionary::<AesKeyGenParams>(cx, value.handle())?;
+            NormalizedAlgorithm::AesKeyGenParams(name, params.length)
+        },
+        (Operation::DeriveBits, AlgorithmName::Hkdf) => {
+            let params = convert_dictionary::<RootedTraceableBox<HkdfParams>>(cx, value.handle())?;
+            NormalizedAlgorithm::HkdfParams {
+                hash: hash(&params.hash)?,
+                salt: buffer_source_to_vec(&params.salt),
+                info: buffer_source_to_vec(&params.info),
+            }
+        },
+        (Operation::DeriveBits, AlgorithmName::Pbkdf2) => {
+            let params =
+                convert_dictionary::<RootedTraceableBox<Pbkdf2Params>>(cx, value.handle())?;
+            NormalizedAlgorithm::Pbkdf2Params {
+                hash: hash(&params.hash)?,
+                salt: buffer_source_to_vec(&params.salt),
+                iterations: params.iterations,
+            }
+        },
+        (Operation::ImportKey, AlgorithmName::RsassaPkcs1V15) |
+        (Operation::ImportKey, AlgorithmName::RsaPss) |
+        (Operation::ImportKey, AlgorithmName::RsaOaep) => {
+            let params = convert_dictionary::<RootedTraceableBox<RsaHashedImportParams>>(
+                cx,
+                value.handle(),
+            )?;
+            NormalizedAlgorithm::RsaHashedImportParams(name, hash(&params.hash)?)
+        },
+        (Operation::ImportKey, AlgorithmName::Ecdsa) => {
+            let params = convert_dictionary::<EcKeyImportParams>(cx, value.handle())?;
+            NormalizedAlgorithm::EcKeyGenParams(named_curve(&params.namedCurve)?)
+        },
+        (Operation::ImportKey, AlgorithmName::Hmac) |
+        (Operation::GetKeyLength, AlgorithmName::Hmac) => {
+            let params =
+                convert_dictionary::<RootedTraceableBox<HmacImportParams>>(cx, value.handle())?;
+            NormalizedAlgorithm::HmacImportParams {
+                hash: hash(&params.hash)?,
+                length: params.length,
+            }
+        },
+        (Operation::GetKeyLength, AlgorithmName::AesCbc) |
+        (Operation::GetKeyLength, AlgorithmName::AesCtr) |
+        (Operation::GetKeyLength, AlgorithmName::AesGcm) => {
+            let params = convert_dictionary::<AesDerivedKeyParams>(cx, value.handle())?;
+            NormalizedAlgorithm::AesKeyGenParams(name, params.length)
+        },
+        _ => return Err(Error::NotSupported),
+    })
+}
+
+/// Checks that `key` belongs to the algorithm `name` and may be used for `usage`.
+fn check_key(key: &CryptoKey, name: AlgorithmName, usage: KeyUsage) -> ErrorResult {
+    if key.algorithm().name() != name || !key.usages().contains(&usage) {
+        return Err(Error::InvalidAccess);
+    }
+    Ok(())
+}
+
+const ENCRYPTION_USAGES: &[KeyUsage] = &[
+    KeyUsage::Encrypt,
+    KeyUsage::Decrypt,
+    KeyUsage::WrapKey,
+    KeyUsage::UnwrapKey,
+];
+const SIGNATURE_USAGES: &[KeyUsage] = &[KeyUsage::Sign, KeyUsage::Verify];
+const DERIVATION_USAGES: &[KeyUsage] = &[KeyUsage::DeriveKey, KeyUsage::DeriveBits];
+
+/// The usages allowed for the public and private keys of an asymmetric algorithm.
+fn key_pair_usages(name: AlgorithmName) -> (&'static [KeyUsage], &'static [KeyUsage]) {
+    match name {
+        AlgorithmName::RsaOaep => (
+            &[KeyUsage::Encrypt, KeyUsage::WrapKey],
+            &[KeyUsage::Decrypt, KeyUsage::UnwrapKey],
+        ),
+        _ => (&[KeyUsage::Verify], &[KeyUsage::Sign]),
+    }
+}
+
+fn check_usages(usages: &[KeyUsage], allowed: &[KeyUsage]) -> ErrorResult {
+    if usages.iter().any(|usage| !allowed.contains(usage)) {
+        return Err(Error::Syntax);
+    }
+    Ok(())
+}
+
+fn filter_usages(usages: &[KeyUsage], allowed: &[KeyUsage]) -> Vec<KeyUsage> {
+    usages
+        .iter()
+        .cloned()
+        .filter(|usage| allowed.contains(usage))
+        .collect()
+}
+
+fn secret_key(handle: &Handle) -> Fallible<&[u8]> {
+    match *handle {
+        Handle::Secret(ref bytes) => Ok(&bytes[..]),
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+fn private_key(handle: &Handle) -> Fallible<&PKey<Private>> {
+    match *handle {
+        Handle::Private(ref key) => Ok(&**key),
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+fn public_key(handle: &Handle) -> Fallible<&PKey<Public>> {
+    match *handle {
+        Handle::Public(ref key) => Ok(&**key),
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+fn key_hash(algorithm: &KeyAlgorithmData) -> Fallible<MessageDigest> {
+    algorithm
+        .hash()
+        .ok_or(Error::InvalidAccess)?
+        .message_digest()
+}
+
+/// The cipher for an AES key of 128, 192 or 256 bits.
+/// The `openssl` crate has no constructors for the 192-bit ciphers, so those come from `openssl-sys`.
+fn aes_cipher(name: AlgorithmName, key: &[u8]) -> Fallible<Cipher> {
+    Ok(match (name, key.len()) {
+        (AlgorithmName::AesCbc, 16) => Cipher::aes_128_cbc(),
+        (AlgorithmName::AesCbc, 24) => unsafe { Cipher::from_ptr(openssl_sys::EVP_aes_192_cbc()) },
+        (AlgorithmName::AesCbc, 32) => Cipher::aes_256_cbc(),
+        (AlgorithmName::AesCtr, 16) => Cipher::aes_128_ecb(),
+        (AlgorithmName::AesCtr, 24) => unsafe { Cipher::from_ptr(openssl_sys::EVP_aes_192_ecb()) },
+        (AlgorithmName::AesCtr, 32) => Cipher::aes_256_ecb(),
+        (AlgorithmName::AesGcm, 16) => Cipher::aes_128_gcm(),
+        (AlgorithmName::AesGcm, 24) => unsafe { Cipher::from_ptr(openssl_sys::EVP_aes_192_gcm()) },
+        (AlgorithmName::AesGcm, 32) => Cipher::aes_256_gcm(),
+        _ => return Err(Error::NotSupported),
+    })
+}
+
+/// AES-CTR, incrementing only the rightmost `length` bits of the counter block.
+/// https://w3c.github.io/webcrypto/#aes-ctr-operations
+fn aes_ctr(key: &[u8], counter: &[u8], length: u8, data: &[u8]) -> Fallible<Vec<u8>> {
+    if counter.len() != 16 || length == 0 || length > 128 {
+        return Err(Error::Operation);
+    }
+    let blocks = (data.len() + 15) / 16;
+    if length < 64 && blocks as u64 > 1 << length {
+        return Err(Error::Operation);
+    }
+
+    let mask = if length == 128 {
+        !0u128
+    } else {
+        (1u128 << length) - 1
+    };
+    let mut block = [0; 16];
+    block.copy_from_slice(counter);
+    let mut value = u128::from_be_bytes(block);
+    let mut counter_blocks = Vec::with_capacity(blocks * 16);
+    for _ in 0..blocks {
+        counter_blocks.extend_from_slice(&value.to_be_bytes());
+        value = (value & !mask) | (value.wrapping_add(1) & mask);
+    }
+
+    let cipher = aes_cipher(AlgorithmName::AesCtr, key)?;
+    let mut crypter = Crypter::new(cipher, Mode::Encrypt, key, None).map_err(operation_error)?;
+    crypter.pad(false);
+    let mut keystream = vec![0; counter_blocks.len() + cipher.block_size()];
+    let count = crypter
+        .update(&counter_blocks, &mut keystream)
+        .map_err(operation_error)?;
+    crypter
+        .finalize(&mut keystream[count..])
+        .map_err(operation_error)?;
+    Ok(data
+        .iter()
+        .zip(keystream.iter())
+        .map(|(byte, key)| byte ^ key)
+        .collect())
+}
+
+fn gcm_tag_length(tag_length: Option<u8>) -> Fallible<usize> {
+    match tag_length.unwrap_or(128) {
+        length @ 32 |
+        length @ 64 |
+        length @ 96 |
+        length @ 104 |
+        length @ 112 |
+        length @ 120 |
+        length @ 128 => Ok(length as usize / 8),
+        _ => Err(Error::Operation),
+    }
+}
+
+fn encrypt(
+    normalized: &NormalizedAlgorithm,
+    key_algorithm: &KeyAlgorithmData,
+    handle: &Handle,
+    data: &[u8],
+) -> Fallible<Vec<u8>> {
+    match *normalized {
+        // https://w3c.github.io/webcrypto/#aes-cbc-operations
+        NormalizedAlgorithm::AesCbcParams { ref iv } => {
+            if iv.len() != 16 {
+                return Err(Error::Operation);
+            }
+            let key = secret_key(handle)?;
+            let cipher = aes_cipher(AlgorithmName::AesCbc, key)?;
+            symm::encrypt(cipher, key, Some(iv), data).map_err(operation_error)
+        },
+        NormalizedAlgorithm::AesCtrParams {
+            ref counter,
+            length,
+        } => aes_ctr(secret_key(handle)?, counter, length, data),
+        // https://w3c.github.io/webcrypto/#aes-gcm-operations
+        NormalizedAlgorithm::AesGcmParams {
+            ref iv,
+            ref additional_data,
+            tag_length,
+        } => {
+            let key = secret_key(handle)?;
+            let cipher = aes_cipher(AlgorithmName::AesGcm, key)?;
+            let mut tag = vec![0; gcm_tag_length(tag_length)?];
+            let aad = additional_data.as_ref().map_or(&[][..], |aad| &aad[..]);
+            let mut ciphertext = symm::encrypt_aead(cipher, key, Some(iv), aad, data, &mut tag)
+                .map_err(operation_error)?;
+            ciphertext.extend_from_slice(&tag);
+            Ok(ciphertext)
+        },
+        // https://w3c.github.io/webcrypto/#rsa-oaep-operations
+        NormalizedAlgorithm::RsaOaepParams { ref label } => {
+            check_oaep_parameters(key_algorithm, label)?;
+            let rsa = public_key(handle)?.rsa().map_err(operation_error)?;
+            let mut ciphertext = vec![0; rsa.size() as usize];
+            let length = rsa
+                .public_encrypt(data, &mut ciphertext, Padding::PKCS1_OAEP)
+                .map_err(operation_error)?;
+            ciphertext.truncate(length);
+            Ok(ciphertext)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn decrypt(
+    normalized: &NormalizedAlgorithm,
+    key_algorithm: &KeyAlgorithmData,
+    handle: &Handle,
+    data: &[u8],
+) -> Fallible<Vec<u8>> {
+    match *normalized {
+        // https://w3c.github.io/webcrypto/#aes-cbc-operations
+        NormalizedAlgorithm::AesCbcParams { ref iv } => {
+            if iv.len() != 16 {
+                return Err(Error::Operation);
+            }
+            let key = secret_key(handle)?;
+            let cipher = aes_cipher(AlgorithmName::AesCbc, key)?;
+            symm::decrypt(cipher, key, Some(iv), data).map_err(operation_error)
+        },
+        NormalizedAlgorithm::AesCtrParams {
+            ref counter,
+            length,
+        } => aes_ctr(secret_key(handle)?, counter, length, data),
+        // https://w3c.github.io/webcrypto/#aes-gcm-operations
+        NormalizedAlgorithm::AesGcmParams {
+            ref iv,
+            ref additional_data,
+            tag_length,
+        } => {
+            let key = secret_key(handle)?;
+            let cipher = aes_cipher(AlgorithmName::AesGcm, key)?;
+            let tag_length = gcm_tag_length(tag_length)?;
+            if data.len() < tag_length {
+                return Err(Error::Operation);
+            }
+            let (ciphertext, tag) = data.split_at(data.len() - tag_length);
+            let aad = additional_data.as_ref().map_or(&[][..], |aad| &aad[..]);
+            symm::decrypt_aead(cipher, key, Some(iv), aad, ciphertext, tag).map_err(operation_error)
+        },
+        // https://w3c.github.io/webcrypto/#rsa-oaep-operations
+        NormalizedAlgorithm::RsaOaepParams { ref label } => {
+            check_oaep_parameters(key_algorithm, label)?;
+            let rsa = private_key(handle)?.rsa().map_err(operation_error)?;
+            let mut plaintext = vec![0; rsa.size() as usize];
+            let length = rsa
+                .private_decrypt(data, &mut plaintext, Padding::PKCS1_OAEP)
+                .map_err(operation_error)?;
+            plaintext.truncate(length);
+            Ok(plaintext)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// OpenSSL's RSA API only implements OAEP with SHA-1 and an empty label.
+fn check_oaep_parameters(key_algorithm: &KeyAlgorithmData, label: &Option<Vec<u8>>) -> ErrorResult {
+    if key_algorithm.hash() != Some(AlgorithmName::Sha1) ||
+        label.as_ref().map_or(false, |label| !label.is_empty())
+    {
+        return Err(Error::NotSupported);
+    }
+    Ok(())
+}
+
+fn hmac(hash: MessageDigest, key: &[u8], data: &[u8]) -> Fallible<Vec<u8>> {
+    let key = PKey::hmac(key).map_err(operation_error)?;
+    let mut signer = Signer::new(hash, &key).map_err(operation_error)?;
+    signer.update(data).map_err(operation_error)?;
+    signer.sign_to_vec().map_err(operation_error)
+}
+
+fn sign(
+    normalized: &NormalizedAlgorithm,
+    key_algorithm: &KeyAlgorithmData,
+    handle: &Handle,
+    data: &[u8],
+) -> Fallible<Vec<u8>> {
+    match *normalized {
+        // https://w3c.github.io/webcrypto/#hmac-operations
+        NormalizedAlgorithm::Algorithm(AlgorithmName::Hmac) => {
+            hmac(key_hash(key_algorithm)?, secret_key(handle)?, data)
+        },
+        // https://w3c.github.io/webcrypto/#rsassa-pkcs1-operations
+        // https://w3c.github.io/webcrypto/#rsa-pss-operations
+        NormalizedAlgorithm::Algorithm(AlgorithmName::RsassaPkcs1V15) |
+        NormalizedAlgorithm::RsaPssParams { .. } => {
+            let hash = key_hash(key_algorithm)?;
+            let mut signer = Signer::new(hash, private_key(handle)?).map_err(operation_error)?;
+            if let NormalizedAlgorithm::RsaPssParams { salt_length } = *normalized {
+                signer
+                    .set_rsa_padding(Padding::PKCS1_PSS)
+                    .map_err(operation_error)?;
+                signer
+                    .set_rsa_pss_saltlen(RsaPssSaltlen::custom(salt_length as i32))
+                    .map_err(operation_error)?;
+                signer.set_rsa_mgf1_md(hash).map_err(operation_error)?;
+            }
+            signer.update(data).map_err(operation_error)?;
+            signer.sign_to_vec().map_err(operation_error)
+        },
+        // https://w3c.github.io/webcrypto/#ecdsa-operations
+        NormalizedAlgorithm::EcdsaParams { hash } => {
+            let curve = key_curve(key_algorithm)?;
+            let mut signer = Signer::new(hash.message_digest()?, private_key(handle)?)
+                .map_err(operation_error)?;
+            signer.update(data).map_err(operation_error)?;
+            let der = signer.sign_to_vec().map_err(operation_error)?;
+
+            // WebCrypto signatures are the concatenation of r and s, rather than
+            // the DER encoding OpenSSL produces.
+            let signature = EcdsaSig::from_der(&der).map_err(operation_error)?;
+            let length = curve.coordinate_length();
+            let mut raw = pad_to(signature.r().to_vec(), length);
+            raw.extend(pad_to(signature.s().to_vec(), length));
+            Ok(raw)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn verify(
+    normalized: &NormalizedAlgorithm,
+    key_algorithm: &KeyAlgorithmData,
+    handle: &Handle,
+    signature: &[u8],
+    data: &[u8],
+) -> Fallible<bool> {
+    match *normalized {
+        // https://w3c.github.io/webcrypto/#hmac-operations
+        NormalizedAlgorithm::Algorithm(AlgorithmName::Hmac) => {
+            let expected = hmac(key_hash(key_algorithm)?, secret_key(handle)?, data)?;
+            Ok(expected.len() == signature.len() && memcmp::eq(&expected, signature))
+        },
+        // https://w3c.github.io/webcrypto/#rsassa-pkcs1-operations
+        // https://w3c.github.io/webcrypto/#rsa-pss-operations
+        NormalizedAlgorithm::Algorithm(AlgorithmName::RsassaPkcs1V15) |
+        NormalizedAlgorithm::RsaPssParams { .. } => {
+            let hash = key_hash(key_algorithm)?;
+            let mut verifier = Verifier::new(hash, public_key(handle)?).map_err(operation_error)?;
+            if let NormalizedAlgorithm::RsaPssParams { salt_length } = *normalized {
+                verifier
+                    .set_rsa_padding(Padding::PKCS1_PSS)
+                    .map_err(operation_error)?;
+                verifier
+                    .set_rsa_pss_saltlen(RsaPssSaltlen::custom(salt_length as i32))
+                    .map_err(operation_error)?;
+                verifier.set_rsa_mgf1_md(hash).map_err(operation_error)?;
+            }
+            verifier.update(data).map_err(operation_error)?;
+            Ok(verifier.verify(signature).unwrap_or(false))
+        },
+        // https://w3c.github.io/webcrypto/#ecdsa-operations
+        NormalizedAlgorithm::EcdsaParams { hash } => {
+            let length = key_curve(key_algorithm)?.coordinate_length();
+            if signature.len() != 2 * length {
+                return Ok(false);
+            }
+            let r = BigNum::from_slice(&signature[..length]).map_err(operation_error)?;
+            let s = BigNum::from_slice(&signature[length..]).map_err(operation_error)?;
+            let der = EcdsaSig::from_private_components(r, s)
+                .and_then(|signature| signature.to_der())
+                .map_err(operation_error)?;
+            let mut verifier = Verifier::new(hash.message_digest()?, public_key(handle)?)
+                .map_err(operation_error)?;
+            verifier.update(data).map_err(operation_error)?;
+            Ok(verifier.verify(&der).unwrap_or(false))
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn key_curve(algorithm: &KeyAlgorithmData) -> Fallible<NamedCurve> {
+    match *algorithm {
+        KeyAlgorithmData::Ec { named_curve, .. } => Ok(named_curve),
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+/// Left-pads a big-endian integer with zeroes up to `length` bytes.
+fn pad_to(bytes: Vec<u8>, length: usize) -> Vec<u8> {
+    if bytes.len() >= length {
+        return bytes;
+    }
+    let mut padded = vec![0; length - bytes.len()];
+    padded.extend(bytes);
+    padded
+}
+
+fn random_bytes(length: usize) -> Fallible<Vec<u8>> {
+    let mut bytes = vec![0; length];
+    rand_bytes(&mut bytes).map_err(operation_error)?;
+    Ok(bytes)
+}
+
+fn public_key_of(key: &PKeyRef<Private>) -> Fallible<PKey<Public>> {
+    let der = key.public_key_to_der().map_err(operation_error)?;
+    PKey::public_key_from_der(&der).map_err(operation_error)
+}
+
+fn rsa_parameters<T: HasPublic>(key: &PKeyRef<T>) -> Fallible<(u32, Vec<u8>)> {
+    let rsa = key.rsa().map_err(data_error)?;
+    Ok((rsa.size() * 8, rsa.e().to_vec()))
+}
+
+fn generate_key(
+    normalized: NormalizedAlgorithm,
+    extractable: bool,
+    usages: Vec<KeyUsage>,
+) -> Fallible<CryptoResult> {
+    match normalized {
+        // https://w3c.github.io/webcrypto/#aes-ctr-operations
+        NormalizedAlgorithm::AesKeyGenParams(name, length) => {
+            check_usages(&usages, ENCRYPTION_USAGES)?;
+            let bytes = match length {
+                128 | 192 | 256 => random_bytes(length as usize / 8)?,
+                _ => return Err(Error::Operation),
+            };
+            if usages.is_empty() {
+                return Err(Error::Syntax);
+            }
+            Ok(CryptoResult::Key(NewKey {
+                extractable: extractable,
+                algorithm: KeyAlgorithmData::Aes {
+                    name: name,
+                    length: length,
+                },
+                usages: usages,
+                handle: Handle::Secret(Arc::new(bytes)),
+            }))
+        },
+        // https://w3c.github.io/webcrypto/#hmac-operations
+        NormalizedAlgorithm::HmacImportParams { hash, length } => {
+            check_usages(&usages, SIGNATURE_USAGES)?;
+            let length = length.unwrap_or_else(|| hash.block_size());
+            if length == 0 {
+                return Err(Error::Operation);
+            }
+            let mut bytes = random_bytes((length as usize + 7) / 8)?;
+            if length % 8 != 0 {
+                let last = bytes.len() - 1;
+                bytes[last] &= 0xffu8 << (8 - length % 8);
+            }
+            if usages.is_empty() {
+                return Err(Error::Syntax);
+            }
+            Ok(CryptoResult::Key(NewKey {
+                extractable: extractable,
+                algorithm: KeyAlgorithmData::Hmac {
+                    hash: hash,
+                    length: length,
+                },
+                usages: usages,
+                handle: Handle::Secret(Arc::new(bytes)),
+            }))
+        },
+        // https://w3c.github.io/webcrypto/#ecdsa-operations
+        NormalizedAlgorithm::EcKeyGenParams(curve) => {
+            check_usages(&usages, SIGNATURE_USAGES)?;
+            let ec_key = EcKey::generate(&curve.group()?).map_err(operation_error)?;
+            let private = PKey::from_ec_key(ec_key).map_err(operation_error)?;
+            let public = public_key_of(&private)?;
+            let algorithm = KeyAlgorithmData::Ec {
+                name: AlgorithmName::Ecdsa,
+                named_curve: curve,
+            };
+            key_pair(algorithm, private, public, extractable, &usages)
+        },
+        // https://w3c.github.io/webcrypto/#rsassa-pkcs1-operations
+        NormalizedAlgorithm::RsaHashedKeyGenParams {
+            name,
+            modulus_length,
+            public_exponent,
+            hash,
+        } => {
+            let (public_usages, private_usages) = key_pair_usages(name);
+            if usages
+                .iter()
+                .any(|usage| !public_usages.contains(usage) && !private_usages.contains(usage))
+            {
+                return Err(Error::Syntax);
+            }
+            // OpenSSL only generates keys with the public exponent 65537.
+            let exponent: Vec<u8> = public_exponent
+                .into_iter()
+                .skip_while(|byte| *byte == 0)
+                .collect();
+            if exponent != [1, 0, 1] {
+                return Err(Error::NotSupported);
+            }
+            let rsa = Rsa::generate(modulus_length).map_err(operation_error)?;
+            let private = PKey::from_rsa(rsa).map_err(operation_error)?;
+            let public = public_key_of(&private)?;
+            let algorithm = KeyAlgorithmData::RsaHashed {
+                name: name,
+                modulus_length: modulus_length,
+                public_exponent: exponent,
+                hash: hash,
+            };
+            key_pair(algorithm, private, public, extractable, &usages)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn key_pair(
+    algorithm: KeyAlgorithmData,
+    private: PKey<Private>,
+    public: PKey<Public>,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<CryptoResult> {
+    let (public_usages, private_usages) = key_pair_usages(algorithm.name());
+    let private_usages = filter_usages(usages, private_usages);
+    if private_usages.is_empty() {
+        return Err(Error::Syntax);
+    }
+    Ok(CryptoResult::KeyPair {
+        public: NewKey {
+            extractable: true,
+            algorithm: algorithm.clone(),
+            usages: filter_usages(usages, public_usages),
+            handle: Handle::Public(Arc::new(public)),
+        },
+        private: NewKey {
+            extractable: extractable,
+            algorithm: algorithm,
+            usages: private_usages,
+            handle: Handle::Private(Arc::new(private)),
+        },
+    })
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-Algorithm-operation-get-key-length
+fn get_key_length(normalized: &NormalizedAlgorithm) -> Fallible<u32> {
+    match *normalized {
+        NormalizedAlgorithm::AesKeyGenParams(_, length) => match length {
+            128 | 192 | 256 => Ok(length as u32),
+            _ => Err(Error::Operation),
+        },
+        NormalizedAlgorithm::HmacImportParams { hash, length } => match length {
+            Some(0) => Err(Error::Type("HMAC key length must not be zero.".to_owned())),
+            Some(length) => Ok(length),
+            None => Ok(hash.block_size()),
+        },
+        // HKDF and PBKDF2 keys have a null length, which cannot be derived.
+        _ => Err(Error::Operation),
+    }
+}
+
+fn derive_bits(
+    normalized: &NormalizedAlgorithm,
+    handle: &Handle,
+    length: u32,
+) -> Fallible<Vec<u8>> {
+    if length == 0 || length % 8 != 0 {
+        return Err(Error::Operation);
+    }
+    let key = secret_key(handle)?;
+    let length = length as usize / 8;
+    match *normalized {
+        // https://w3c.github.io/webcrypto/#pbkdf2-operations
+        NormalizedAlgorithm::Pbkdf2Params {
+            hash,
+            ref salt,
+            iterations,
+        } => {
+            if iterations == 0 {
+                return Err(Error::Operation);
+            }
+            let mut bits = vec![0; length];
+            pbkdf2_hmac(
+                key,
+                salt,
+                iterations as usize,
+                hash.message_digest()?,
+                &mut bits,
+            )
+            .map_err(operation_error)?;
+            Ok(bits)
+        },
+        // https://w3c.github.io/webcrypto/#hkdf-operations
+        NormalizedAlgorithm::HkdfParams {
+            hash,
+            ref salt,
+            ref info,
+        } => hkdf(hash, key, salt, info, length),
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// HMAC-based Extract-and-Expand Key Derivation Function.
+/// https://tools.ietf.org/html/rfc5869
+fn hkdf(
+    hash: AlgorithmName,
+    key: &[u8],
+    salt: &[u8],
+    info: &[u8],
+    length: usize,
+) -> Fallible<Vec<u8>> {
+    let digest = hash.message_digest()?;
+    let hash_length = hash.digest_length();
+    if length > 255 * hash_length {
+        return Err(Error::Operation);
+    }
+
+    // Step 1. An absent salt is a string of HashLen zeroes.
+    let salt = if salt.is_empty() {
+        vec![0; hash_length]
+    } else {
+        salt.to_vec()
+    };
+    let prk = hmac(digest, &salt, key)?;
+
+    // Step 2.
+    let blocks = (length + hash_length - 1) / hash_length;
+    let mut okm = Vec::with_capacity(blocks * hash_length);
+    let mut previous = vec![];
+    for counter in 1..=blocks as u8 {
+        let mut input = previous;
+        input.extend_from_slice(info);
+        input.push(counter);
+        previous = hmac(digest, &prk, &input)?;
+        okm.extend_from_slice(&previous);
+    }
+    okm.truncate(length);
+    Ok(okm)
+}
+
+fn decode_jwk_member(member: &Option<String>) -> Fallible<Vec<u8>> {
+    let member = member.as_ref().ok_or(Error::Data)?;
+    base64::decode_config(member, base64::URL_SAFE_NO_PAD).map_err(|_| Error::Data)
+}
+
+fn decode_jwk_bignum(member: &Option<String>) -> Fallible<BigNum> {
+    BigNum::from_slice(&decode_jwk_member(member)?).map_err(data_error)
+}
+
+fn encode_jwk_member(bytes: &[u8]) -> Option<String> {
+    Some(base64::encode_config(bytes, base64::URL_SAFE_NO_PAD))
+}
+
+/// The "alg" member of a JSON Web Key holding a key of this algorithm.
+fn jwk_alg(algorithm: &KeyAlgorithmData) -> Option<String> {
+    match *algorithm {
+        KeyAlgorithmData::Aes { name, length } => {
+            let mode = match name {
+                AlgorithmName::AesCbc => "CBC",
+                AlgorithmName::AesCtr => "CTR",
+                _ => "GCM",
+            };
+            Some(format!("A{}{}", length, mode))
+        },
+        KeyAlgorithmData::Hmac { hash, .. } => Some(format!("HS{}", hash.jwa_suffix())),
+        KeyAlgorithmData::RsaHashed { name, hash, .. } => Some(match (name, hash) {
+            (AlgorithmName::RsaOaep, AlgorithmName::Sha1) => "RSA-OAEP".to_owned(),
+            (AlgorithmName::RsaOaep, hash) => format!("RSA-OAEP-{}", hash.jwa_suffix()),
+            (AlgorithmName::RsaPss, hash) => format!("PS{}", hash.jwa_suffix()),
+            (_, hash) => format!("RS{}", hash.jwa_suffix()),
+        }),
+        KeyAlgorithmData::Ec { .. } | KeyAlgorithmData::Kdf(_) => None,
+    }
+}
+
+/// The checks shared by the JWK import steps of every algorithm.
+fn check_jwk(
+    jwk: &Jwk,
+    kty: &str,
+    expected_use: &str,
+    usages: &[KeyUsage],
+    extractable: bool,
+) -> ErrorResult {
+    if jwk.kty.as_ref().map(|kty| &**kty) != Some(kty) {
+        return Err(Error::Data);
+    }
+    if !usages.is_empty() && jwk.use_.as_ref().map_or(false, |use_| use_ != expected_use) {
+        return Err(Error::Data);
+    }
+    if let Some(ref key_ops) = jwk.key_ops {
+        if usages
+            .iter()
+            .any(|usage| !key_ops.iter().any(|op| op == usage.as_str()))
+        {
+            return Err(Error::Data);
+        }
+    }
+    if jwk.ext == Some(false) && extractable {
+        return Err(Error::Data);
+    }
+    Ok(())
+}
+
+fn check_jwk_alg(alg: Option<String>, algorithm: &KeyAlgorithmData) -> ErrorResult {
+    match (alg, jwk_alg(algorithm)) {
+        (Some(ref alg), Some(ref expected)) if alg != expected => Err(Error::Data),
+        _ => Ok(()),
+    }
+}
+
+fn import_key(
+    format: KeyFormat,
+    key_data: KeyData,
+    normalized: NormalizedAlgorithm,
+    extractable: bool,
+    usages: Vec<KeyUsage>,
+) -> Fallible<NewKey> {
+    let key = match normalized {
+        NormalizedAlgorithm::Algorithm(name @ AlgorithmName::Hkdf) |
+        NormalizedAlgorithm::Algorithm(name @ AlgorithmName::Pbkdf2) => {
+            import_kdf_key(name, format, key_data, extractable, usages)?
+        },
+        NormalizedAlgorithm::Algorithm(name) => {
+            import_aes_key(name, format, key_data, extractable, usages)?
+        },
+        NormalizedAlgorithm::HmacImportParams { hash, length } => {
+            import_hmac_key(hash, length, format, key_data, extractable, usages)?
+        },
+        NormalizedAlgorithm::EcKeyGenParams(curve) => {
+            import_ec_key(curve, format, key_data, extractable, usages)?
+        },
+        NormalizedAlgorithm::RsaHashedImportParams(name, hash) => {
+            import_rsa_key(name, hash, format, key_data, extractable, usages)?
+        },
+        _ => return Err(Error::NotSupported),
+    };
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-importKey
+    // Step 9.
+    if key.handle.key_type() != KeyType::Public && key.usages.is_empty() {
+        return Err(Error::Syntax);
+    }
+    Ok(key)
+}
+
+// https://w3c.github.io/webcrypto/#aes-ctr-operations
+fn import_aes_key(
+    name: AlgorithmName,
+    format: KeyFormat,
+    key_data: KeyData,
+    extractable: bool,
+    usages: Vec<KeyUsage>,
+) -> Fallible<NewKey> {
+    check_usages(&usages, ENCRYPTION_USAGES)?;
+    let (bytes, alg) = match (format, key_data) {
+        (KeyFormat::Raw, KeyData::Bytes(bytes)) => (bytes, None),
+        (KeyFormat::Jwk, KeyData::Jwk(jwk)) => {
+            check_jwk(&jwk, "oct", "enc", &usages, extractable)?;
+            (decode_jwk_member(&jwk.k)?, jwk.alg)
+        },
+        _ => return Err(Error::NotSupported),
+    };
+    let length = match bytes.len() {
+        16 | 24 | 32 => bytes.len() as u16 * 8,
+        _ => return Err(Error::Data),
+    };
+    let algorithm = KeyAlgorithmData::Aes {
+        name: name,
+        length: length,
+    };
+    check_jwk_alg(alg, &algorithm)?;
+    Ok(NewKey {
+        extractable: extractable,
+        algorithm: algorithm,
+        usages: usages,
+        handle: Handle::Secret(Arc::new(bytes)),
+    })
+}
+
+// https://w3c.github.io/webcrypto/#hmac-operations
+fn import_hmac_key(
+    hash: AlgorithmName,
+    length: Option<u32>,
+    format: KeyFormat,
+    key_data: KeyData,
+    extractable: bool,
+    usages: Vec<KeyUsage>,
+) -> Fallible<NewKey> {
+    check_usages(&usages, SIGNATURE_USAGES)?;
+    let (bytes, alg) = match (format, key_data) {
+        (KeyFormat::Raw, KeyData::Bytes(bytes)) => (bytes, None),
+        (KeyFormat::Jwk, KeyData::Jwk(jwk)) => {
+            check_jwk(&jwk, "oct", "sig", &usages, extractable)?;
+            (decode_jwk_member(&jwk.k)?, jwk.alg)
+        },
+        _ => return Err(Error::NotSupported),
+    };
+    if bytes.is_empty() {
+        return Err(Error::Data);
+    }
+    let data_length = bytes.len() as u32 * 8;
+    let length = match length {
+        Some(length) if length > data_length || length <= data_length - 8 => {
+            return Err(Error::Data);
+        },
+        Some(length) => length,
+        None => data_length,
+    };
+    let algorithm = KeyAlgorithmData::Hmac {
+        hash: hash,
+        length: length,
+    };
+    check_jwk_alg(alg, &algorithm)?;
+    Ok(NewKey {
+        extractable: extractable,
+        algorithm: algorithm,
+        usages: usages,
+        handle: Handle::Secret(Arc::new(bytes)),
+    })
+}
+
+// https://w3c.github.io/webcrypto/#pbkdf2-operations
+// https://w3c.github.io/webcrypto/#hkdf-operations
+fn import_kdf_key(
+    name: AlgorithmName,
+    format: KeyFormat,
+    key_data: KeyData,
+    extractable: bool,
+    usages: Vec<KeyUsage>,
+) -> Fallible<NewKey> {
+    check_usages(&usages, DERIVATION_USAGES)?;
+    let bytes = match (format, key_data) {
+        (KeyFormat::Raw, KeyData::Bytes(bytes)) => bytes,
+        _ => return Err(Error::NotSupported),
+    };
+    if extractable {
+        return Err(Error::Syntax);
+    }
+    Ok(NewKey {
+        extractable: false,
+        algorithm: KeyAlgorithmData::Kdf(name),
+        usages: usages,
+        handle: Handle::Secret(Arc::new(bytes)),
+    })
+}
+
+fn check_curve<T>(ec_key: &EcKey<T>, curve: NamedCurve) -> ErrorResult {
+    if ec_key.group().curve_name() != Some(curve.nid()) {
+        return Err(Error::Data);
+    }
+    Ok(())
+}
+
+// https://w3c.github.io/webcrypto/#ecdsa-operations
+fn import_ec_key(
+    curve: NamedCurve,
+    format: KeyFormat,
+    key_data: KeyData,
+    extractable: bool,
+    usages: Vec<KeyUsage>,
+) -> Fallible<NewKey> {
+    let (public_usages, private_usages) = key_pair_usages(AlgorithmName::Ecdsa);
+    let group = curve.group()?;
+    let handle = match (format, key_data) {
+        (KeyFormat::Spki, KeyData::Bytes(der)) => {
+            check_usages(&usages, public_usages)?;
+            let key = PKey::public_key_from_der(&der).map_err(data_error)?;
+            check_curve(&key.ec_key().map_err(data_error)?, curve)?;
+            Handle::Public(Arc::new(key))
+        },
+        (KeyFormat::Pkcs8, KeyData::Bytes(der)) => {
+            check_usages(&usages, private_usages)?;
+            let key = PKey::private_key_from_der(&der).map_err(data_error)?;
+            check_curve(&key.ec_key().map_err(data_error)?, curve)?;
+            Handle::Private(Arc::new(key))
+        },
+        (KeyFormat::Raw, KeyData::Bytes(bytes)) => {
+            check_usages(&usages, public_usages)?;
+            let mut context = BigNumContext::new().map_err(operation_error)?;
+            let point = EcPoint::from_bytes(&group, &bytes, &mut context).map_err(data_error)?;
+            let ec_key = EcKey::from_public_key(&group, &point).map_err(data_error)?;
+            Handle::Public(Arc::new(PKey::from_ec_key(ec_key).map_err(data_error)?))
+        },
+        (KeyFormat::Jwk, KeyData::Jwk(jwk)) => {
+            let allowed = if jwk.d.is_some() {
+                private_usages
+            } else {
+                public_usages
+            };
+            check_usages(&usages, allowed)?;
+            check_jwk(&jwk, "EC", "sig", &usages, extractable)?;
+            if jwk.crv.as_ref().map(|crv| &**crv) != Some(curve.as_str()) {
+                return Err(Error::Data);
+            }
+            if jwk
+                .alg
+                .as_ref()
+                .map_or(false, |alg| alg != curve.jwa_name())
+            {
+                return Err(Error::Data);
+            }
+            let x = decode_jwk_bignum(&jwk.x)?;
+            let y = decode_jwk_bignum(&jwk.y)?;
+            let public =
+                EcKey::from_public_key_affine_coordinates(&group, &x, &y).map_err(data_error)?;
+            if jwk.d.is_some() {
+                let d = decode_jwk_bignum(&jwk.d)?;
+                let private = EcKey::from_private_components(&group, &d, public.public_key())
+                    .map_err(data_error)?;
+                Handle::Private(Arc::new(PKey::from_ec_key(private).map_err(data_error)?))
+            } else {
+                Handle::Public(Arc::new(PKey::from_ec_key(public).map_err(data_error)?))
+            }
+        },
+        _ => return Err(Error::NotSupported),
+    };
+    Ok(NewKey {
+        extractable: extractable,
+        algorithm: KeyAlgorithmData::Ec {
+            name: AlgorithmName::Ecdsa,
+            named_curve: curve,
+        },
+        usages: usages,
+        handle: handle,
+    })
+}
+
+// https://w3c.github.io/webcrypto/#rsassa-pkcs1-operations
+fn import_rsa_key(
+    name: AlgorithmName,
+    hash: AlgorithmName,
+    format: KeyFormat,
+    key_data: KeyData,
+    extractable: bool,
+    usages: Vec<KeyUsage>,
+) -> Fallible<NewKey> {
+    let (public_usages, private_usages) = key_pair_usages(name);
+    let (handle, alg) = match (format, key_data) {
+        (KeyFormat::Spki, KeyData::Bytes(der)) => {
+            check_usages(&usages, public_usages)?;
+            let key = PKey::public_key_from_der(&der).map_err(data_error)?;
+            (Handle::Public(Arc::new(key)), None)
+        },
+        (KeyFormat::Pkcs8, KeyData::Bytes(der)) => {
+            check_usages(&usages, private_usages)?;
+            let key = PKey::private_key_from_der(&der).map_err(data_error)?;
+            (Handle::Private(Arc::new(key)), None)
+        },
+        (KeyFormat::Jwk, KeyData::Jwk(jwk)) => {
+            let allowed = if jwk.d.is_some() {
+                private_usages
+            } else {
+                public_usages
+            };
+            check_usages(&usages, allowed)?;
+            let expected_use = if name == AlgorithmName::RsaOaep {
+                "enc"
+            } else {
+                "sig"
+            };
+            check_jwk(&jwk, "RSA", expected_use, &usages, extractable)?;
+            if jwk.has_other_primes {
+                return Err(Error::NotSupported);
+            }
+            let n = decode_jwk_bignum(&jwk.n)?;
+            let e = decode_jwk_bignum(&jwk.e)?;
+            let handle = if jwk.d.is_some() {
+                let rsa = Rsa::from_private_components(
+                    n,
+                    e,
+                    decode_jwk_bignum(&jwk.d)?,
+                    decode_jwk_bignum(&jwk.p)?,
+                    decode_jwk_bignum(&jwk.q)?,
+                    decode_jwk_bignum(&jwk.dp)?,
+                    decode_jwk_bignum(&jwk.dq)?,
+                    decode_jwk_bignum(&jwk.qi)?,
+                )
+                .map_err(data_error)?;
+                Handle::Private(Arc::new(PKey::from_rsa(rsa).map_err(data_error)?))
+            } else {
+                let rsa = Rsa::from_public_components(n, e).map_err(data_error)?;
+                Handle::Public(Arc::new(PKey::from_rsa(rsa).map_err(data_error)?))
+            };
+            (handle, jwk.alg)
+        },
+        _ => return Err(Error::NotSupported),
+    };
+    let (modulus_length, public_exponent) = match handle {
+        Handle::Private(ref key) => rsa_parameters(key)?,
+        Handle::Public(ref key) => rsa_parameters(key)?,
+        Handle::Secret(_) => unreachable!(),
+    };
+    let algorithm = KeyAlgorithmData::RsaHashed {
+        name: name,
+        modulus_length: modulus_length,
+        public_exponent: public_exponent,
+        hash: hash,
+    };
+    check_jwk_alg(alg, &algorithm)?;
+    Ok(NewKey {
+        extractable: extractable,
+        algorithm: algorithm,
+        usages: usages,
+        handle: handle,
+    })
+}
+
+/// Converts a private key to a DER-encoded PKCS #8 PrivateKeyInfo structure.
+fn pkcs8_der(key: &PKey<Private>) -> Fallible<Vec<u8>> {
+    let pem = key.private_key_to_pem_pkcs8().map_err(operation_error)?;
+    let pem = String::from_utf8(pem).map_err(|_| Error::Operation)?;
+    let base64: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    base64::decode(&base64).map_err(|_| Error::Operation)
+}
+
+fn export_key(
+    format: KeyFormat,
+    algorithm: &KeyAlgorithmData,
+    handle: &Handle,
+    usages: &[KeyUsage],
+) -> Fallible<CryptoResult> {
+    match format {
+        KeyFormat::Raw => match (algorithm, handle) {
+            (&KeyAlgorithmData::Aes { .. }, &Handle::Secret(ref bytes)) |
+            (&KeyAlgorithmData::Hmac { .. }, &Handle::Secret(ref bytes)) => {
+                Ok(CryptoResult::Bytes(bytes.to_vec()))
+            },
+            (&KeyAlgorithmData::Ec { .. }, &Handle::Public(ref key)) => {
+                let ec_key = key.ec_key().map_err(operation_error)?;
+                let mut context = BigNumContext::new().map_err(operation_error)?;
+                let bytes = ec_key
+                    .public_key()
+                    .to_bytes(
+                        ec_key.group(),
+                        PointConversionForm::UNCOMPRESSED,
+                        &mut context,
+                    )
+                    .map_err(operation_error)?;
+                Ok(CryptoResult::Bytes(bytes))
+            },
+            (&KeyAlgorithmData::Ec { .. }, _) => Err(Error::InvalidAccess),
+            _ => Err(Error::NotSupported),
+        },
+        KeyFormat::Spki => match *handle {
+            Handle::Public(ref key) => key
+                .public_key_to_der()
+                .map(CryptoResult::Bytes)
+                .map_err(operation_error),
+            Handle::Private(_) => Err(Error::InvalidAccess),
+            Handle::Secret(_) => Err(Error::NotSupported),
+        },
+        KeyFormat::Pkcs8 => match *handle {
+            Handle::Private(ref key) => pkcs8_der(key).map(CryptoResult::Bytes),
+            Handle::Public(_) => Err(Error::InvalidAccess),
+            Handle::Secret(_) => Err(Error::NotSupported),
+        },
+        KeyFormat::Jwk => export_jwk(algorithm, handle, usages).map(CryptoResult::Jwk),
+    }
+}
+
+fn export_jwk(algorithm: &KeyAlgorithmData, handle: &Handle, usages: &[KeyUsage]) -> Fallible<Jwk> {
+    let mut jwk = Jwk::default();
+    match (algorithm, handle) {
+        (&KeyAlgorithmData::Kdf(_), _) => return Err(Error::NotSupported),
+        (_, &Handle::Secret(ref bytes)) => {
+            jwk.kty = Some("oct".to_owned());
+            jwk.k = encode_jwk_member(bytes);
+        },
+        (&KeyAlgorithmData::Ec { named_curve, .. }, _) => {
+            let ec_key = match *handle {
+                Handle::Private(ref key) => {
+                    let ec_key = key.ec_key().map_err(operation_error)?;
+                    jwk.d = encode_jwk_member(&pad_to(
+                        ec_key.private_key().to_vec(),
+                        named_curve.coordinate_length(),
+                    ));
+                    EcKey::from_public_key(ec_key.group(), ec_key.public_key())
+                        .map_err(operation_error)?
+                },
+                Handle::Public(ref key) => key.ec_key().map_err(operation_error)?,
+                Handle::Secret(_) => unreachable!(),
+            };
+            let mut x = BigNum::new().map_err(operation_error)?;
+            let mut y = BigNum::new().map_err(operation_error)?;
+            let mut context = BigNumContext::new().map_err(operation_error)?;
+            ec_key
+                .public_key()
+                .affine_coordinates_gfp(ec_key.group(), &mut x, &mut y, &mut context)
+                .map_err(operation_error)?;
+            jwk.kty = Some("EC".to_owned());
+            jwk.crv = Some(named_curve.as_str().to_owned());
+            jwk.x = encode_jwk_member(&pad_to(x.to_vec(), named_curve.coordinate_length()));
+            jwk.y = encode_jwk_member(&pad_to(y.to_vec(), named_curve.coordinate_length()));
+        },
+        (_, &Handle::Private(ref key)) => {
+            let rsa = key.rsa().map_err(operation_error)?;
+            jwk.kty = Some("RSA".to_owned());
+            jwk.n = encode_jwk_member(&rsa.n().to_vec());
+            jwk.e = encode_jwk_member(&rsa.e().to_vec());
+            jwk.d = encode_jwk_member(&rsa.d().to_vec());
+            let component = |component: Option<&BigNumRef>| {
+                component.and_then(|value| encode_jwk_member(&value.to_vec()))
+            };
+            jwk.p = component(rsa.p());
+            jwk.q = component(rsa.q());
+            jwk.dp = component(rsa.dmp1());
+            jwk.dq = component(rsa.dmq1());
+            jwk.qi = component(rsa.iqmp());
+        },
+        (_, &Handle::Public(ref key)) => {
+            let rsa = key.rsa().map_err(operation_error)?;
+            jwk.kty = Some("RSA".to_owned());
+            jwk.n = encode_jwk_member(&rsa.n().to_vec());
+            jwk.e = encode_jwk_member(&rsa.e().to_vec());
+        },
+    }
+    jwk.alg = jwk_alg(algorithm);
+    jwk.key_ops = Some(
+        usages
+            .iter()
+            .map(|usage| usage.as_str().to_owned())
+            .collect(),
+    );
+    jwk.ext = Some(true);
+    Ok(jwk)
+}
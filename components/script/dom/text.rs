@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
@@ -9,24 +10,29 @@ use crate::dom::bindings::codegen::Bindings::TextBinding::{self, TextMethods};
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{DomRoot, LayoutDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::characterdata::CharacterData;
 use crate::dom::document::Document;
-use crate::dom::node::Node;
+use crate::dom::node::{Node, NodeDamage};
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
+use std::ops::Range;
 
 /// An HTML text node.
 #[dom_struct]
 pub struct Text {
     characterdata: CharacterData,
+    /// The bytes of the data that are in the selection of the document, which layout
+    /// highlights.
+    selected_range: DomRefCell<Option<Range<usize>>>,
 }
 
 impl Text {
     pub fn new_inherited(text: DOMString, document: &Document) -> Text {
         Text {
             characterdata: CharacterData::new_inherited(text, document),
+            selected_range: DomRefCell::new(None),
         }
     }
 
@@ -42,6 +48,32 @@ impl Text {
         let document = window.Document();
         Ok(Text::new(text, &document))
     }
+
+    /// Sets the bytes of the data that are in the selection of the document, or `None` if the
+    /// selection doesn't cover any of the text.
+    pub fn set_selected_range(&self, range: Option<Range<usize>>) {
+        if *self.selected_range.borrow() == range {
+            return;
+        }
+        *self.selected_range.borrow_mut() = range;
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+}
+
+#[allow(unsafe_code)]
+pub trait LayoutTextHelpers {
+    unsafe fn selection_for_layout(&self) -> Option<Range<usize>>;
+}
+
+#[allow(unsafe_code)]
+impl LayoutTextHelpers for LayoutDom<Text> {
+    #[inline]
+    unsafe fn selection_for_layout(&self) -> Option<Range<usize>> {
+        (*self.unsafe_get())
+            .selected_range
+            .borrow_for_layout()
+            .clone()
+    }
 }
 
 impl TextMethods for Text {
@@ -7,7 +7,12 @@ pub trait Validatable {
     fn is_instance_validatable(&self) -> bool {
         true
     }
-    fn validate(&self, _validate_flags: ValidationFlags) -> bool {
-        true
+    /// The constraints this element currently fails to satisfy.
+    /// <https://html.spec.whatwg.org/multipage/#validity-states>
+    fn validity_flags(&self) -> ValidationFlags {
+        ValidationFlags::empty()
+    }
+    fn validate(&self, validate_flags: ValidationFlags) -> bool {
+        !self.validity_flags().intersects(validate_flags)
     }
 }
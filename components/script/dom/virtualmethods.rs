@@ -21,6 +21,7 @@ use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlbuttonelement::HTMLButtonElement;
 use crate::dom::htmlcanvaselement::HTMLCanvasElement;
 use crate::dom::htmldetailselement::HTMLDetailsElement;
+use crate::dom::htmldialogelement::HTMLDialogElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
 use crate::dom::htmlfontelement::HTMLFontElement;
@@ -177,6 +178,9 @@ pub fn vtable_for(node: &Node) -> &dyn VirtualMethods {
         NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLDetailsElement)) => {
             node.downcast::<HTMLDetailsElement>().unwrap() as &dyn VirtualMethods
         },
+        NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLDialogElement)) => {
+            node.downcast::<HTMLDialogElement>().unwrap() as &dyn VirtualMethods
+        },
         NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLFieldSetElement)) => {
             node.downcast::<HTMLFieldSetElement>().unwrap() as &dyn VirtualMethods
         },
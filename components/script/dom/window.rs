@@ -15,6 +15,7 @@ use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     self, FrameRequestCallback, WindowMethods, WindowPostMessageOptions,
 };
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
+use crate::dom::bindings::codegen::Bindings::WindowOrWorkerGlobalScopeBinding::StructuredSerializeOptions;
 use crate::dom::bindings::codegen::UnionTypes::{RequestOrUSVString, StringOrFunction};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
@@ -28,6 +29,7 @@ use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::bindings::utils::{GlobalStaticData, WindowProxyHandler};
 use crate::dom::bindings::weakref::DOMTracker;
 use crate::dom::bluetooth::BluetoothExtraPermissionData;
+use crate::dom::characterdata::CharacterData;
 use crate::dom::crypto::Crypto;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::customelementregistry::CustomElementRegistry;
@@ -47,8 +49,10 @@ use crate::dom::node::{document_from_node, from_untrusted_node_address, Node, No
 use crate::dom::performance::Performance;
 use crate::dom::promise::Promise;
 use crate::dom::screen::Screen;
+use crate::dom::selection::Selection;
 use crate::dom::storage::Storage;
 use crate::dom::testrunner::TestRunner;
+use crate::dom::text::Text;
 use crate::dom::webglrenderingcontext::WebGLCommandSender;
 use crate::dom::windowproxy::WindowProxy;
 use crate::dom::worklet::Worklet;
@@ -98,10 +102,12 @@ use profile_traits::ipc as ProfiledIpc;
 use profile_traits::mem::ProfilerChan as MemProfilerChan;
 use profile_traits::time::{ProfilerChan as TimeProfilerChan, ProfilerMsg};
 use script_layout_interface::message::{Msg, QueryMsg, Reflow, ReflowGoal, ScriptReflow};
-use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
+use script_layout_interface::rpc::{BoxSizes, BoxSizesResponse, ClippedBox, ClippedBoxesResponse};
 use script_layout_interface::rpc::{
-    NodeScrollIdResponse, ResolvedStyleResponse, TextIndexResponse,
+    CaretPositionResponse, NodeScrollIdResponse, ResolvedStyleResponse, TextIndexResponse,
 };
+use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
+use script_layout_interface::rpc::{RunningAnimation, RunningAnimationsResponse};
 use script_layout_interface::{PendingImageState, TrustedNodeAddress};
 use script_traits::webdriver_msg::{WebDriverJSError, WebDriverJSResult};
 use script_traits::{ConstellationControlMsg, DocumentState, HistoryEntryReplacement, LoadData};
@@ -170,6 +176,7 @@ pub enum ReflowReason {
     IFrameLoadEvent,
     MissingExplicitReflow,
     ElementStateChanged,
+    Print,
 }
 
 #[dom_struct]
@@ -310,6 +317,14 @@ pub struct Window {
     /// It is used to avoid sending idle message more than once, which is unneccessary.
     has_sent_idle_message: Cell<bool>,
 
+    /// Whether layout was loading web fonts at the end of the last reflow, so
+    /// that the font face set of the document learns when this changes.
+    layout_has_pending_web_fonts: Cell<bool>,
+
+    /// Whether the printing steps are running, so that `print()` calls made by the
+    /// `beforeprint` and `afterprint` event handlers are ignored.
+    printing: Cell<bool>,
+
     /// Flag that indicates if the layout thread is busy handling a request.
     #[ignore_malloc_size_of = "Arc<T> is hard"]
     layout_is_busy: Arc<AtomicBool>,
@@ -633,6 +648,24 @@ impl WindowMethods for Window {
         receiver.recv().unwrap();
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-print
+    fn Print(&self) {
+        // Step 1.
+        let document = self.Document();
+
+        // Step 2-3.
+        if !document.is_fully_active() || document.is_prompting_or_unloading() {
+            return;
+        }
+
+        // Step 4-5.
+        if document.is_ready_for_post_load_tasks() {
+            self.run_the_printing_steps();
+        } else {
+            document.set_print_when_loaded();
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-window-stop
     fn Stop(&self) {
         // TODO: Cancel ongoing navigation.
@@ -758,6 +791,11 @@ impl WindowMethods for Window {
             .expect("Document accessed before initialization.")
     }
 
+    // https://w3c.github.io/selection-api/#dom-window-getselection
+    fn GetSelection(&self) -> Option<DomRoot<Selection>> {
+        self.Document().GetSelection()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-history
     fn History(&self) -> DomRoot<History> {
         self.history.or_init(|| History::new(self))
@@ -944,6 +982,17 @@ impl WindowMethods for Window {
         base64_atob(atob)
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-structuredclone
+    fn StructuredClone(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        options: RootedTraceableBox<StructuredSerializeOptions>,
+    ) -> Fallible<JSVal> {
+        self.upcast::<GlobalScope>()
+            .structured_clone(cx, value, options)
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-window-requestanimationframe>
     fn RequestAnimationFrame(&self, callback: Rc<FrameRequestCallback>) -> u32 {
         self.Document()
@@ -1375,6 +1424,10 @@ impl Window {
         // Tell the constellation to drop the sender to our message-port router, if there is any.
         self.upcast::<GlobalScope>().remove_message_ports_router();
 
+        // Tell the constellation to drop the sender to our broadcast router, if there is any.
+        self.upcast::<GlobalScope>()
+            .remove_broadcast_channel_router();
+
         // Clean up any active promises
         // https://github.com/servo/servo/issues/15318
         if let Some(custom_elements) = self.custom_element_registry.get() {
@@ -1660,6 +1713,14 @@ impl Window {
             ScriptThread::note_newly_transitioning_nodes(complete.newly_transitioning_nodes);
         }
 
+        // Stylesheets may have made layout load the fonts of their `@font-face`
+        // rules.
+        if self.layout_has_pending_web_fonts.get() != complete.pending_web_fonts {
+            self.layout_has_pending_web_fonts
+                .set(complete.pending_web_fonts);
+            self.Document().fonts_environment_changed();
+        }
+
         true
     }
 
@@ -1678,6 +1739,7 @@ impl Window {
     /// viewport size is not present). See #11223 for an example of that.
     pub fn reflow(&self, reflow_goal: ReflowGoal, reason: ReflowReason) -> bool {
         self.Document().ensure_safe_to_run_script_or_layout();
+        self.Document().update_selection_highlight();
         let for_display = reflow_goal == ReflowGoal::Full;
 
         let mut issued_reflow = false;
@@ -1764,6 +1826,47 @@ impl Window {
         rects
     }
 
+    /// Find the boxes of the given nodes and the scroll containers clipping them, or `None`
+    /// for the nodes that aren't rendered.
+    pub fn clipped_boxes_query(&self, nodes: &[&Node]) -> Vec<Option<ClippedBox>> {
+        let opaque_nodes = nodes.iter().map(|node| node.to_opaque()).collect();
+        if !self.layout_reflow(QueryMsg::ClippedBoxesQuery(opaque_nodes)) {
+            return vec![None; nodes.len()];
+        }
+        let ClippedBoxesResponse(boxes) = self.layout_rpc.clipped_boxes();
+        boxes
+    }
+
+    /// Find the sizes of the boxes of the given nodes, or `None` for the nodes that aren't
+    /// rendered.
+    pub fn box_sizes_query(&self, nodes: &[&Node]) -> Vec<Option<BoxSizes>> {
+        let opaque_nodes = nodes.iter().map(|node| node.to_opaque()).collect();
+        if !self.layout_reflow(QueryMsg::BoxSizesQuery(opaque_nodes)) {
+            return vec![None; nodes.len()];
+        }
+        let BoxSizesResponse(sizes) = self.layout_rpc.box_sizes();
+        sizes
+    }
+
+    /// Find the CSS animations and transitions that layout is running, along
+    /// with the elements they apply to.
+    #[allow(unsafe_code)]
+    pub fn running_animations_query(&self) -> Vec<(DomRoot<Element>, RunningAnimation)> {
+        if !self.layout_reflow(QueryMsg::RunningAnimationsQuery) {
+            return vec![];
+        }
+        let RunningAnimationsResponse(animations) = self.layout_rpc.running_animations();
+        let js_runtime = self.js_runtime.borrow();
+        let js_runtime = js_runtime.as_ref().unwrap();
+        animations
+            .into_iter()
+            .filter_map(|animation| {
+                let node = unsafe { from_untrusted_node_address(js_runtime.rt(), animation.node) };
+                Some((DomRoot::downcast(node)?, animation))
+            })
+            .collect()
+    }
+
     pub fn client_rect_query(&self, node: &Node) -> UntypedRect<i32> {
         if !self.layout_reflow(QueryMsg::NodeGeometryQuery(node.to_opaque())) {
             return Rect::zero();
@@ -1779,7 +1882,13 @@ impl Window {
     }
 
     pub fn scroll_offset_query(&self, node: &Node) -> Vector2D<f32, LayoutPixel> {
-        if let Some(scroll_offset) = self.scroll_offsets.borrow().get(&node.to_opaque()) {
+        self.scroll_offset_of_opaque_node(node.to_opaque())
+    }
+
+    /// The last known scroll offset of the scroll container established by `node`, for
+    /// when only the address of the node is at hand, e.g. in layout query responses.
+    pub fn scroll_offset_of_opaque_node(&self, node: OpaqueNode) -> Vector2D<f32, LayoutPixel> {
+        if let Some(scroll_offset) = self.scroll_offsets.borrow().get(&node) {
             return *scroll_offset;
         }
         Vector2D::new(0.0, 0.0)
@@ -1833,6 +1942,43 @@ impl Window {
         self.layout_rpc.inner_window_dimensions()
     }
 
+    /// Lay the document out for print media and send the printed PDF document to the
+    /// embedder.
+    /// https://html.spec.whatwg.org/multipage/#printing-steps
+    pub fn run_the_printing_steps(&self) {
+        // The event loop can't be paused while printing, so calls made from the print
+        // events are ignored rather than nested.
+        if self.printing.get() {
+            return;
+        }
+        self.printing.set(true);
+
+        // TODO: Step 2, return if the sandboxed modals flag is set.
+
+        // Step 3.
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("beforeprint"));
+
+        // Step 4.
+        self.print_to_pdf();
+
+        // Step 5.
+        self.upcast::<EventTarget>().fire_event(atom!("afterprint"));
+
+        self.printing.set(false);
+    }
+
+    pub fn print_to_pdf(&self) {
+        if self.layout_reflow(QueryMsg::PrintQuery) {
+            if let Some(pdf) = self.layout_rpc.printed_pdf() {
+                self.send_to_embedder(EmbedderMsg::PdfPrinted(pdf));
+            }
+        }
+
+        // Lay the document out for the screen again.
+        self.reflow(ReflowGoal::Full, ReflowReason::Print);
+    }
+
     #[allow(unsafe_code)]
     pub fn offset_parent_query(&self, node: &Node) -> (Option<DomRoot<Element>>, UntypedRect<Au>) {
         if !self.layout_reflow(QueryMsg::OffsetParentQuery(node.to_opaque())) {
@@ -1869,6 +2015,31 @@ impl Window {
         self.layout_rpc.text_index()
     }
 
+    /// Returns the text node and the offset within its data of the caret position closest to
+    /// `point`, which is relative to the initial containing block.
+    #[allow(unsafe_code)]
+    pub fn caret_position_query(&self, point: UntypedPoint2D<f32>) -> Option<(DomRoot<Text>, u32)> {
+        if !self.layout_reflow(QueryMsg::CaretPositionQuery(point)) {
+            return None;
+        }
+        let CaretPositionResponse(position) = self.layout_rpc.caret_position();
+        let (address, index) = position?;
+        let js_runtime = self.js_runtime.borrow();
+        let js_runtime = js_runtime.as_ref().unwrap();
+        let node = unsafe { from_untrusted_node_address(js_runtime.rt(), address) };
+        let text = DomRoot::downcast::<Text>(node)?;
+        // Layout counts the characters of the rendered text, which are mapped back onto the data
+        // of the node as is, without accounting for collapsed white space.
+        let offset: usize = text
+            .upcast::<CharacterData>()
+            .data()
+            .chars()
+            .take(index)
+            .map(char::len_utf16)
+            .sum();
+        Some((text, offset as u32))
+    }
+
     #[allow(unsafe_code)]
     pub fn init_window_proxy(&self, window_proxy: &WindowProxy) {
         assert!(self.window_proxy.get().is_none());
@@ -2004,6 +2175,12 @@ impl Window {
         &self.layout_chan
     }
 
+    /// Whether layout was still loading web fonts for this window after the
+    /// last reflow.
+    pub fn has_pending_web_fonts(&self) -> bool {
+        self.layout_has_pending_web_fonts.get()
+    }
+
     pub fn windowproxy_handler(&self) -> WindowProxyHandler {
         WindowProxyHandler(self.dom_static.windowproxy_handler.0)
     }
@@ -2304,6 +2481,8 @@ impl Window {
             exists_mut_observer: Cell::new(false),
             webrender_api_sender,
             has_sent_idle_message: Cell::new(false),
+            layout_has_pending_web_fonts: Cell::new(false),
+            printing: Cell::new(false),
             layout_is_busy,
             relayout_event,
             prepare_for_screenshot,
@@ -2354,6 +2533,9 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
         ReflowGoal::LayoutQuery(ref query_msg, _) => match query_msg {
             &QueryMsg::ContentBoxQuery(_n) => "\tContentBoxQuery",
             &QueryMsg::ContentBoxesQuery(_n) => "\tContentBoxesQuery",
+            &QueryMsg::ClippedBoxesQuery(_) => "\tClippedBoxesQuery",
+            &QueryMsg::BoxSizesQuery(_) => "\tBoxSizesQuery",
+            &QueryMsg::RunningAnimationsQuery => "\tRunningAnimationsQuery",
             &QueryMsg::NodesFromPointQuery(..) => "\tNodesFromPointQuery",
             &QueryMsg::NodeGeometryQuery(_n) => "\tNodeGeometryQuery",
             &QueryMsg::NodeScrollGeometryQuery(_n) => "\tNodeScrollGeometryQuery",
@@ -2362,8 +2544,10 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
             &QueryMsg::OffsetParentQuery(_n) => "\tOffsetParentQuery",
             &QueryMsg::StyleQuery(_n) => "\tStyleQuery",
             &QueryMsg::TextIndexQuery(..) => "\tTextIndexQuery",
+            &QueryMsg::CaretPositionQuery(_) => "\tCaretPositionQuery",
             &QueryMsg::ElementInnerTextQuery(_) => "\tElementInnerTextQuery",
             &QueryMsg::InnerWindowDimensionsQuery(_) => "\tInnerWindowDimensionsQuery",
+            &QueryMsg::PrintQuery => "\tPrintQuery",
         },
     });
 
@@ -2388,6 +2572,7 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
         ReflowReason::IFrameLoadEvent => "\tIFrameLoadEvent",
         ReflowReason::MissingExplicitReflow => "\tMissingExplicitReflow",
         ReflowReason::ElementStateChanged => "\tElementStateChanged",
+        ReflowReason::Print => "\tPrint",
     });
 
     println!("{}", debug_msg);
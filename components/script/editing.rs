@@ -0,0 +1,1285 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The editing of `contenteditable` elements, both by the user and through
+//! `document.execCommand()`.
+//!
+//! Editing commands change the DOM around the selection of the document. Every change they make
+//! is recorded in the document's `EditHistory`, so that the changes of a command can be undone
+//! and redone as a whole.
+
+use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::compositionevent::CompositionEvent;
+use crate::dom::document::Document;
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::event::{Event, EventStatus};
+use crate::dom::htmlbrelement::HTMLBRElement;
+use crate::dom::htmlelement::{ContentEditableState, HTMLElement};
+use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::inputevent::InputEvent;
+use crate::dom::keyboardevent::KeyboardEvent;
+use crate::dom::node::{document_from_node, window_from_node};
+use crate::dom::node::{CloneChildrenFlag, Node, ShadowIncluding};
+use crate::dom::range::{bp_position, Range};
+use crate::dom::selection::Selection;
+use crate::dom::text::Text;
+use crate::textinput::CMD_OR_CONTROL;
+use html5ever::{LocalName, QualName};
+use keyboard_types::{Key, KeyState, Modifiers, ShortcutMatcher};
+use std::cmp::Ordering;
+use std::iter;
+
+/// The commands of `document.execCommand()` that Servo supports.
+/// <https://w3c.github.io/editing/docs/execCommand/#commands>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    Bold,
+    CreateLink,
+    Delete,
+    ForwardDelete,
+    InsertParagraph,
+    InsertText,
+    Italic,
+    Redo,
+    Undo,
+}
+
+impl Command {
+    pub fn from_name(name: &str) -> Option<Command> {
+        match &*name.to_ascii_lowercase() {
+            "bold" => Some(Command::Bold),
+            "createlink" => Some(Command::CreateLink),
+            "delete" => Some(Command::Delete),
+            "forwarddelete" => Some(Command::ForwardDelete),
+            "insertparagraph" => Some(Command::InsertParagraph),
+            "inserttext" => Some(Command::InsertText),
+            "italic" => Some(Command::Italic),
+            "redo" => Some(Command::Redo),
+            "undo" => Some(Command::Undo),
+            _ => None,
+        }
+    }
+
+    /// The `inputType` of the input events fired around this command.
+    /// <https://w3c.github.io/input-events/#interface-InputEvent-Attributes>
+    fn input_type(self) -> &'static str {
+        match self {
+            Command::Bold => "formatBold",
+            Command::CreateLink => "insertLink",
+            Command::Delete => "deleteContentBackward",
+            Command::ForwardDelete => "deleteContentForward",
+            Command::InsertParagraph => "insertParagraph",
+            Command::InsertText => "insertText",
+            Command::Italic => "formatItalic",
+            Command::Redo => "historyRedo",
+            Command::Undo => "historyUndo",
+        }
+    }
+
+    /// Whether `element` applies the style that this command adds to the selected text.
+    fn is_applied_by(self, element: &Element) -> bool {
+        if *element.namespace() != ns!(html) {
+            return false;
+        }
+        match (self, element.local_name()) {
+            (Command::Bold, &local_name!("b")) |
+            (Command::Bold, &local_name!("strong")) |
+            (Command::Italic, &local_name!("i")) |
+            (Command::Italic, &local_name!("em")) |
+            (Command::CreateLink, &local_name!("a")) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A change that an editing command made to the DOM.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+enum Operation {
+    InsertData {
+        node: Dom<CharacterData>,
+        offset: u32,
+        data: DOMString,
+    },
+    DeleteData {
+        node: Dom<CharacterData>,
+        offset: u32,
+        data: DOMString,
+    },
+    /// Inserted `node` into `parent` before `child`.
+    InsertNode {
+        parent: Dom<Node>,
+        node: Dom<Node>,
+        child: Option<Dom<Node>>,
+    },
+    /// Removed `node` from `parent`, where it was before `child`.
+    RemoveNode {
+        parent: Dom<Node>,
+        node: Dom<Node>,
+        child: Option<Dom<Node>>,
+    },
+    SetHref {
+        element: Dom<Element>,
+        old_value: Option<DOMString>,
+        new_value: DOMString,
+    },
+}
+
+impl Operation {
+    fn apply(&self) {
+        match *self {
+            Operation::InsertData {
+                ref node,
+                offset,
+                ref data,
+            } => {
+                let _ = node.InsertData(offset, data.clone());
+            },
+            Operation::DeleteData {
+                ref node,
+                offset,
+                ref data,
+            } => {
+                let _ = node.DeleteData(offset, utf16_length(data));
+            },
+            Operation::InsertNode {
+                ref parent,
+                ref node,
+                ref child,
+            } => {
+                let _ = parent.InsertBefore(node, child.as_deref());
+            },
+            Operation::RemoveNode {
+                ref parent,
+                ref node,
+                ..
+            } => {
+                let _ = parent.RemoveChild(node);
+            },
+            Operation::SetHref {
+                ref element,
+                ref new_value,
+                ..
+            } => {
+                element.set_string_attribute(&local_name!("href"), new_value.clone());
+            },
+        }
+    }
+
+    fn revert(&self) {
+        match *self {
+            Operation::InsertData {
+                ref node,
+                offset,
+                ref data,
+            } => {
+                let _ = node.DeleteData(offset, utf16_length(data));
+            },
+            Operation::DeleteData {
+                ref node,
+                offset,
+                ref data,
+            } => {
+                let _ = node.InsertData(offset, data.clone());
+            },
+            Operation::InsertNode {
+                ref parent,
+                ref node,
+                ..
+            } => {
+                let _ = parent.RemoveChild(node);
+            },
+            Operation::RemoveNode {
+                ref parent,
+                ref node,
+                ref child,
+            } => {
+                let _ = parent.InsertBefore(node, child.as_deref());
+            },
+            Operation::SetHref {
+                ref element,
+                ref old_value,
+                ..
+            } => match *old_value {
+                Some(ref old_value) => {
+                    element.set_string_attribute(&local_name!("href"), old_value.clone())
+                },
+                None => {
+                    element.remove_attribute(&ns!(), &local_name!("href"));
+                },
+            },
+        }
+    }
+}
+
+/// The anchor and focus of a selection.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct SelectionState {
+    anchor_node: Dom<Node>,
+    anchor_offset: u32,
+    focus_node: Dom<Node>,
+    focus_offset: u32,
+}
+
+impl SelectionState {
+    #[allow(unrooted_must_root)]
+    fn of(selection: &Selection) -> Option<SelectionState> {
+        Some(SelectionState {
+            anchor_node: Dom::from_ref(&*selection.GetAnchorNode()?),
+            anchor_offset: selection.AnchorOffset(),
+            focus_node: Dom::from_ref(&*selection.GetFocusNode()?),
+            focus_offset: selection.FocusOffset(),
+        })
+    }
+
+    fn restore(&self, selection: &Selection) {
+        let _ = selection.SetBaseAndExtent(
+            &self.anchor_node,
+            self.anchor_offset,
+            &self.focus_node,
+            self.focus_offset,
+        );
+    }
+}
+
+/// The changes made by one editing command, which are undone and redone together.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct Transaction {
+    operations: Vec<Operation>,
+    selection_before: Option<SelectionState>,
+    selection_after: Option<SelectionState>,
+}
+
+/// The undo and redo stacks of a document.
+/// <https://w3c.github.io/editing/docs/execCommand/#the-undo-and-redo-commands>
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub struct EditHistory {
+    /// The transactions that undoing reverts, the most recent one last.
+    undo_stack: Vec<Transaction>,
+    /// The transactions that redoing applies again, the most recently undone one last.
+    redo_stack: Vec<Transaction>,
+}
+
+/// Whether `node` is editable or an editing host, as `isContentEditable` reflects.
+/// <https://html.spec.whatwg.org/multipage/#dom-iscontenteditable>
+pub fn is_content_editable(node: &Node) -> bool {
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLElement>)
+        .filter_map(|element| match element.content_editable_state() {
+            ContentEditableState::True => Some(true),
+            ContentEditableState::False => Some(false),
+            ContentEditableState::Inherit => None,
+        })
+        .next()
+        .unwrap_or(false)
+}
+
+/// Returns the editing host that `node` is editable in, or `node` itself if it is one.
+/// <https://html.spec.whatwg.org/multipage/#editing-host>
+pub fn editing_host(node: &Node) -> Option<DomRoot<HTMLElement>> {
+    if !is_content_editable(node) {
+        return None;
+    }
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLElement>)
+        .find(|element| element.is_editing_host())
+}
+
+/// Puts the caret at the start of `host` after it got focused, unless the selection already
+/// is in it.
+pub fn place_caret(host: &HTMLElement) {
+    let document = document_from_node(host);
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return,
+    };
+    let in_host = selection
+        .GetRangeAt(0)
+        .ok()
+        .and_then(|range| editing_host(&range.StartContainer()))
+        .map_or(false, |selection_host| &*selection_host == host);
+    if in_host {
+        return;
+    }
+    let node = host.upcast::<Node>();
+    let _ = match node
+        .traverse_preorder(ShadowIncluding::No)
+        .find(|node| node.is::<Text>())
+    {
+        Some(text) => selection.Collapse(Some(&text), 0),
+        None => selection.Collapse(Some(node), 0),
+    };
+}
+
+/// <https://w3c.github.io/editing/docs/execCommand/#execcommand()>
+pub fn exec_command(document: &Document, command: Command, value: DOMString) -> bool {
+    let editor = match Editor::new(document) {
+        Some(editor) => editor,
+        None => return false,
+    };
+    let data = match command {
+        Command::InsertText => Some(value.clone()),
+        _ => None,
+    };
+    if !editor.run(command, value) {
+        return false;
+    }
+    fire_input_event(&editor.host, "input", command.input_type(), data);
+    true
+}
+
+/// <https://w3c.github.io/editing/docs/execCommand/#querycommandenabled()>
+pub fn query_command_enabled(document: &Document, command: Command) -> bool {
+    if Editor::new(document).is_none() {
+        return false;
+    }
+    let history = document.edit_history().borrow();
+    match command {
+        Command::Undo => !history.undo_stack.is_empty(),
+        Command::Redo => !history.redo_stack.is_empty(),
+        _ => true,
+    }
+}
+
+/// <https://w3c.github.io/editing/docs/execCommand/#querycommandstate()>
+pub fn query_command_state(document: &Document, command: Command) -> bool {
+    match command {
+        Command::Bold | Command::Italic => {},
+        _ => return false,
+    }
+    let editor = match Editor::new(document) {
+        Some(editor) => editor,
+        None => return false,
+    };
+    editor.range().map_or(false, |range| {
+        editor
+            .style_ancestor(&range.StartContainer(), command)
+            .is_some()
+    })
+}
+
+/// Runs the editing command bound to a key pressed in the editing host `host`, returning
+/// whether there was one.
+pub fn handle_keydown(host: &HTMLElement, event: &KeyboardEvent) -> bool {
+    let document = document_from_node(host);
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return false,
+    };
+    let mut modifiers = event.modifiers();
+    let extend = modifiers.contains(Modifiers::SHIFT);
+    modifiers.remove(Modifiers::SHIFT);
+    let command = |command: Command| {
+        run_user_command(host, command, command.input_type(), DOMString::new());
+        true
+    };
+    let move_caret = |direction: &str| {
+        let alter = if extend { "extend" } else { "move" };
+        selection.Modify(
+            DOMString::from(alter),
+            DOMString::from(direction),
+            DOMString::from("character"),
+        );
+        true
+    };
+    ShortcutMatcher::new(KeyState::Down, event.key(), modifiers)
+        .shortcut(CMD_OR_CONTROL, 'Z', || {
+            command(if extend { Command::Redo } else { Command::Undo })
+        })
+        .shortcut(CMD_OR_CONTROL, 'Y', || command(Command::Redo))
+        .shortcut(CMD_OR_CONTROL, 'B', || command(Command::Bold))
+        .shortcut(CMD_OR_CONTROL, 'I', || command(Command::Italic))
+        .shortcut(CMD_OR_CONTROL, 'A', || {
+            let _ = selection.SelectAllChildren(host.upcast());
+            true
+        })
+        .shortcut(Modifiers::empty(), Key::Backspace, || {
+            command(Command::Delete)
+        })
+        .shortcut(Modifiers::empty(), Key::Delete, || {
+            command(Command::ForwardDelete)
+        })
+        .shortcut(Modifiers::empty(), Key::Enter, || {
+            command(Command::InsertParagraph)
+        })
+        .shortcut(Modifiers::empty(), Key::ArrowLeft, || {
+            move_caret("backward")
+        })
+        .shortcut(Modifiers::empty(), Key::ArrowRight, || {
+            move_caret("forward")
+        })
+        .otherwise(|| false)
+}
+
+/// Inserts the character of a key pressed in the editing host `host`, returning whether there
+/// was one.
+pub fn handle_keypress(host: &HTMLElement, event: &KeyboardEvent) -> bool {
+    if event
+        .modifiers()
+        .intersects(Modifiers::CONTROL | Modifiers::META)
+    {
+        return false;
+    }
+    match event.key() {
+        Key::Character(character) => {
+            let command = Command::InsertText;
+            run_user_command(host, command, command.input_type(), character.into());
+            true
+        },
+        _ => false,
+    }
+}
+
+/// Inserts the text that an input method composed in the editing host `host`.
+pub fn handle_compositionend(host: &HTMLElement, event: &CompositionEvent) {
+    run_user_command(
+        host,
+        Command::InsertText,
+        "insertFromComposition",
+        DOMString::from(event.data()),
+    );
+}
+
+/// Runs a command on behalf of the user, unless the `beforeinput` event gets canceled.
+/// <https://w3c.github.io/input-events/#events-inputevents>
+fn run_user_command(host: &HTMLElement, command: Command, input_type: &str, data: DOMString) {
+    let event_data = match command {
+        Command::InsertText => Some(data.clone()),
+        _ => None,
+    };
+    if !fire_input_event(host, "beforeinput", input_type, event_data.clone()) {
+        return;
+    }
+    let document = document_from_node(host);
+    let ran = Editor::new(&document).map_or(false, |editor| editor.run(command, data));
+    if ran {
+        fire_input_event(host, "input", input_type, event_data);
+    }
+}
+
+/// Fires a `beforeinput` or `input` event at `host`, returning whether it wasn't canceled.
+fn fire_input_event(
+    host: &HTMLElement,
+    type_: &str,
+    input_type: &str,
+    data: Option<DOMString>,
+) -> bool {
+    let window = window_from_node(host);
+    let event = InputEvent::new(
+        &window,
+        DOMString::from(type_),
+        true,
+        type_ == "beforeinput",
+        Some(&window),
+        0,
+        data,
+        false,
+        DOMString::from(input_type),
+    );
+    let event = event.upcast::<Event>();
+    event.set_trusted(true);
+    event.fire(host.upcast()) == EventStatus::NotCanceled
+}
+
+/// The length of `data` in UTF-16 code units, which offsets into character data count.
+fn utf16_length(data: &str) -> u32 {
+    data.encode_utf16().count() as u32
+}
+
+/// The length in UTF-16 code units of the character just after or just before the offset
+/// `offset` into `data`, or 0 if there is none.
+fn char_length_at(data: &str, offset: u32, forwards: bool) -> u32 {
+    let mut position = 0;
+    for character in data.chars() {
+        let length = character.len_utf16() as u32;
+        if (forwards && position == offset) || (!forwards && position + length == offset) {
+            return length;
+        }
+        position += length;
+    }
+    0
+}
+
+/// Whether `node` is content that deleting next to the caret deletes from or removes.
+fn is_leaf(node: &Node) -> bool {
+    (node.is::<Text>() && node.len() > 0) ||
+        node.is::<HTMLBRElement>() ||
+        node.is::<HTMLImageElement>()
+}
+
+/// The boundary points just before and just after the content of `leaf`.
+fn leaf_bounds(leaf: &Node) -> ((DomRoot<Node>, u32), (DomRoot<Node>, u32)) {
+    if leaf.is::<Text>() {
+        (
+            (DomRoot::from_ref(leaf), 0),
+            (DomRoot::from_ref(leaf), leaf.len()),
+        )
+    } else {
+        let parent = leaf
+            .GetParentNode()
+            .expect("Editable content without a parent");
+        let index = leaf.index();
+        ((parent.clone(), index), (parent, index + 1))
+    }
+}
+
+/// Whether `node` is an element that editing commands treat as a paragraph, which
+/// inserting a paragraph splits and deleting across joins.
+fn is_paragraph(node: &Node) -> bool {
+    let element = match node.downcast::<Element>() {
+        Some(element) => element,
+        None => return false,
+    };
+    if *element.namespace() != ns!(html) {
+        return false;
+    }
+    match *element.local_name() {
+        local_name!("address") |
+        local_name!("blockquote") |
+        local_name!("dd") |
+        local_name!("div") |
+        local_name!("dt") |
+        local_name!("h1") |
+        local_name!("h2") |
+        local_name!("h3") |
+        local_name!("h4") |
+        local_name!("h5") |
+        local_name!("h6") |
+        local_name!("li") |
+        local_name!("p") |
+        local_name!("pre") => true,
+        _ => false,
+    }
+}
+
+/// Whether `node` only contains the `<br>` that gives an empty paragraph its height.
+fn is_placeholder(node: &Node) -> bool {
+    node.children_count() == 1 &&
+        node.GetFirstChild()
+            .map_or(false, |child| child.is::<HTMLBRElement>())
+}
+
+/// Edits the contents of an editing host around the selection of its document.
+struct Editor<'a> {
+    document: &'a Document,
+    selection: DomRoot<Selection>,
+    host: DomRoot<HTMLElement>,
+}
+
+impl<'a> Editor<'a> {
+    /// Returns an editor for the editing host the selection of `document` is in, if it is
+    /// in one.
+    fn new(document: &'a Document) -> Option<Editor<'a>> {
+        let selection = document.GetSelection()?;
+        let range = selection.GetRangeAt(0).ok()?;
+        let host = editing_host(&range.StartContainer())?;
+        if editing_host(&range.EndContainer()).as_ref() != Some(&host) {
+            return None;
+        }
+        Some(Editor {
+            document,
+            selection,
+            host,
+        })
+    }
+
+    fn host_node(&self) -> &Node {
+        self.host.upcast()
+    }
+
+    fn range(&self) -> Option<DomRoot<Range>> {
+        self.selection.GetRangeAt(0).ok()
+    }
+
+    fn collapse(&self, node: &Node, offset: u32) {
+        let _ = self.selection.Collapse(Some(node), offset);
+    }
+
+    /// Runs `command`, recording its changes as one transaction, and returns whether it did
+    /// anything.
+    fn run(&self, command: Command, value: DOMString) -> bool {
+        match command {
+            Command::Undo => return self.undo(),
+            Command::Redo => return self.redo(),
+            _ => {},
+        }
+        self.begin_transaction();
+        let ran = match command {
+            Command::Bold | Command::Italic => self.toggle_style(command, None),
+            Command::CreateLink => !value.is_empty() && self.toggle_style(command, Some(value)),
+            Command::Delete => self.delete(false),
+            Command::ForwardDelete => self.delete(true),
+            Command::InsertParagraph => self.insert_paragraph(),
+            Command::InsertText => self.insert_text(value),
+            Command::Undo | Command::Redo => unreachable!(),
+        };
+        self.end_transaction();
+        ran
+    }
+
+    #[allow(unrooted_must_root)]
+    fn begin_transaction(&self) {
+        let transaction = Transaction {
+            operations: vec![],
+            selection_before: SelectionState::of(&self.selection),
+            selection_after: None,
+        };
+        self.document
+            .edit_history()
+            .borrow_mut()
+            .undo_stack
+            .push(transaction);
+    }
+
+    #[allow(unrooted_must_root)]
+    fn end_transaction(&self) {
+        let selection_after = SelectionState::of(&self.selection);
+        let mut history = self.document.edit_history().borrow_mut();
+        let changed = history
+            .undo_stack
+            .last()
+            .map_or(false, |transaction| !transaction.operations.is_empty());
+        if !changed {
+            history.undo_stack.pop();
+            return;
+        }
+        if let Some(transaction) = history.undo_stack.last_mut() {
+            transaction.selection_after = selection_after;
+        }
+        history.redo_stack.clear();
+    }
+
+    #[allow(unrooted_must_root)]
+    fn record(&self, operation: Operation) {
+        let mut history = self.document.edit_history().borrow_mut();
+        if let Some(transaction) = history.undo_stack.last_mut() {
+            transaction.operations.push(operation);
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    fn undo(&self) -> bool {
+        {
+            let mut history = self.document.edit_history().borrow_mut();
+            match history.undo_stack.pop() {
+                Some(transaction) => history.redo_stack.push(transaction),
+                None => return false,
+            }
+        }
+        let history = self.document.edit_history().borrow();
+        let transaction = history.redo_stack.last().expect("No transaction to undo");
+        for operation in transaction.operations.iter().rev() {
+            operation.revert();
+        }
+        if let Some(ref selection) = transaction.selection_before {
+            selection.restore(&self.selection);
+        }
+        true
+    }
+
+    #[allow(unrooted_must_root)]
+    fn redo(&self) -> bool {
+        {
+            let mut history = self.document.edit_history().borrow_mut();
+            match history.redo_stack.pop() {
+                Some(transaction) => history.undo_stack.push(transaction),
+                None => return false,
+            }
+        }
+        let history = self.document.edit_history().borrow();
+        let transaction = history.undo_stack.last().expect("No transaction to redo");
+        for operation in &transaction.operations {
+            operation.apply();
+        }
+        if let Some(ref selection) = transaction.selection_after {
+            selection.restore(&self.selection);
+        }
+        true
+    }
+
+    #[allow(unrooted_must_root)]
+    fn insert_data(&self, node: &CharacterData, offset: u32, data: DOMString) {
+        if node.InsertData(offset, data.clone()).is_ok() {
+            self.record(Operation::InsertData {
+                node: Dom::from_ref(node),
+                offset,
+                data,
+            });
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    fn delete_data(&self, node: &CharacterData, offset: u32, count: u32) {
+        let data = match node.SubstringData(offset, count) {
+            Ok(ref data) if data.is_empty() => return,
+            Ok(data) => data,
+            Err(_) => return,
+        };
+        if node.DeleteData(offset, count).is_ok() {
+            self.record(Operation::DeleteData {
+                node: Dom::from_ref(node),
+                offset,
+                data,
+            });
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    fn insert_node(&self, parent: &Node, node: &Node, child: Option<&Node>) {
+        if parent.InsertBefore(node, child).is_ok() {
+            self.record(Operation::InsertNode {
+                parent: Dom::from_ref(parent),
+                node: Dom::from_ref(node),
+                child: child.map(Dom::from_ref),
+            });
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    fn remove_node(&self, node: &Node) {
+        let parent = match node.GetParentNode() {
+            Some(parent) => parent,
+            None => return,
+        };
+        let child = node.GetNextSibling();
+        if parent.RemoveChild(node).is_ok() {
+            self.record(Operation::RemoveNode {
+                parent: Dom::from_ref(&*parent),
+                node: Dom::from_ref(node),
+                child: child.as_deref().map(Dom::from_ref),
+            });
+        }
+    }
+
+    fn move_node(&self, node: &Node, parent: &Node, child: Option<&Node>) {
+        self.remove_node(node);
+        self.insert_node(parent, node, child);
+    }
+
+    #[allow(unrooted_must_root)]
+    fn set_href(&self, element: &Element, value: DOMString) {
+        let old_value = element
+            .get_attribute(&ns!(), &local_name!("href"))
+            .map(|attr| DOMString::from(&**attr.value()));
+        element.set_string_attribute(&local_name!("href"), value.clone());
+        self.record(Operation::SetHref {
+            element: Dom::from_ref(element),
+            old_value,
+            new_value: value,
+        });
+    }
+
+    fn create_element(&self, name: LocalName) -> DomRoot<Element> {
+        Element::create(
+            QualName::new(None, ns!(html), name),
+            None,
+            self.document,
+            ElementCreator::ScriptCreated,
+            CustomElementCreationMode::Synchronous,
+        )
+    }
+
+    /// Moves the data of `text` after `offset` to a new text node following it, and returns
+    /// that node, or `None` if `text` has no parent or no data after `offset`.
+    fn split_text(&self, text: &Text, offset: u32) -> Option<DomRoot<Text>> {
+        let node = text.upcast::<Node>();
+        let parent = node.GetParentNode()?;
+        let data = text.upcast::<CharacterData>();
+        let count = node.len().checked_sub(offset)?;
+        let tail = Text::new(data.SubstringData(offset, count).ok()?, self.document);
+        self.delete_data(data, offset, count);
+        self.insert_node(&parent, tail.upcast(), node.GetNextSibling().as_deref());
+        Some(tail)
+    }
+
+    /// The innermost paragraph that contains `node` within the editing host.
+    fn paragraph_of(&self, node: &Node) -> Option<DomRoot<Node>> {
+        node.inclusive_ancestors(ShadowIncluding::No)
+            .take_while(|ancestor| **ancestor != *self.host_node())
+            .find(|ancestor| is_paragraph(ancestor))
+    }
+
+    /// The nearest content that `is_leaf` before or after the boundary point `(node, offset)`
+    /// in the editing host, not counting `node` itself.
+    fn adjacent_leaf(&self, node: &Node, offset: u32, forwards: bool) -> Option<DomRoot<Node>> {
+        let mut leaves = self
+            .host_node()
+            .traverse_preorder(ShadowIncluding::No)
+            .filter(|leaf| is_leaf(leaf) && **leaf != *node);
+        if forwards {
+            leaves.find(|leaf| {
+                let (start, _) = leaf_bounds(leaf);
+                bp_position(&start.0, start.1, node, offset) != Some(Ordering::Less)
+            })
+        } else {
+            leaves
+                .filter(|leaf| {
+                    let (_, end) = leaf_bounds(leaf);
+                    bp_position(&end.0, end.1, node, offset) != Some(Ordering::Greater)
+                })
+                .last()
+        }
+    }
+
+    /// Appends the content of the paragraph `second` to the paragraph `first`, or to the
+    /// content just before it if that isn't in a paragraph, and removes `second`. Returns the
+    /// boundary point where the two meet.
+    fn join(&self, first: Option<&Node>, second: &Node) -> (DomRoot<Node>, u32) {
+        let (parent, child, meeting_point) = match first {
+            Some(first) => {
+                if let Some(placeholder) = first.GetFirstChild().filter(|_| is_placeholder(first)) {
+                    self.remove_node(&placeholder);
+                }
+                let meeting_point = (DomRoot::from_ref(first), first.children_count());
+                (DomRoot::from_ref(first), None, meeting_point)
+            },
+            None => {
+                let parent = second.GetParentNode().expect("Paragraph without a parent");
+                let meeting_point = (parent.clone(), second.index());
+                (parent, Some(DomRoot::from_ref(second)), meeting_point)
+            },
+        };
+        if !is_placeholder(second) {
+            let children: Vec<_> = second.children().collect();
+            for moved in children {
+                self.move_node(&moved, &parent, child.as_deref());
+            }
+        }
+        self.remove_node(second);
+        meeting_point
+    }
+
+    /// Deletes the contents of `range`, joining the paragraphs it started and ended in, and
+    /// collapses the selection where they were.
+    fn delete_contents(&self, range: &Range) {
+        let (start_node, start_offset) = (range.StartContainer(), range.StartOffset());
+        let (end_node, end_offset) = (range.EndContainer(), range.EndOffset());
+
+        if start_node == end_node {
+            match start_node.downcast::<CharacterData>() {
+                Some(data) => self.delete_data(data, start_offset, end_offset - start_offset),
+                None => {
+                    let children: Vec<_> = start_node
+                        .children()
+                        .skip(start_offset as usize)
+                        .take((end_offset - start_offset) as usize)
+                        .collect();
+                    for child in children {
+                        self.remove_node(&child);
+                    }
+                },
+            }
+            self.collapse(&start_node, start_offset);
+            return;
+        }
+
+        let start_paragraph = self.paragraph_of(&start_node);
+        let end_paragraph = self.paragraph_of(&end_node);
+        let contained: Vec<_> = range
+            .CommonAncestorContainer()
+            .traverse_preorder(ShadowIncluding::No)
+            .filter(|node| {
+                range.contains(node) &&
+                    !node
+                        .GetParentNode()
+                        .map_or(false, |parent| range.contains(&parent))
+            })
+            .collect();
+        if let Some(data) = start_node.downcast::<CharacterData>() {
+            self.delete_data(data, start_offset, start_node.len() - start_offset);
+        }
+        for node in contained {
+            self.remove_node(&node);
+        }
+        if let Some(data) = end_node.downcast::<CharacterData>() {
+            self.delete_data(data, 0, end_offset);
+        }
+        if let (Some(first), Some(second)) = (start_paragraph, end_paragraph) {
+            let nested =
+                first.is_inclusive_ancestor_of(&second) || second.is_inclusive_ancestor_of(&first);
+            if !nested {
+                self.join(Some(&first), &second);
+            }
+        }
+        self.collapse(&start_node, start_offset);
+    }
+
+    /// <https://w3c.github.io/editing/docs/execCommand/#the-inserttext-command>
+    fn insert_text(&self, data: DOMString) -> bool {
+        let range = match self.range() {
+            Some(range) => range,
+            None => return false,
+        };
+        if !range.Collapsed() {
+            self.delete_contents(&range);
+        }
+        if data.is_empty() {
+            return true;
+        }
+        let (node, offset) = (range.StartContainer(), range.StartOffset());
+        if let Some(text) = node.downcast::<Text>() {
+            let length = utf16_length(&data);
+            self.insert_data(text.upcast(), offset, data);
+            self.collapse(&node, offset + length);
+            return true;
+        }
+        let placeholder = node.GetFirstChild().filter(|_| is_placeholder(&node));
+        let text = Text::new(data, self.document);
+        let child = node.children().nth(offset as usize);
+        self.insert_node(&node, text.upcast(), child.as_deref());
+        if let Some(placeholder) = placeholder {
+            self.remove_node(&placeholder);
+        }
+        let text = text.upcast::<Node>();
+        self.collapse(text, text.len());
+        true
+    }
+
+    /// <https://w3c.github.io/editing/docs/execCommand/#the-delete-command> and
+    /// <https://w3c.github.io/editing/docs/execCommand/#the-forwarddelete-command>
+    fn delete(&self, forwards: bool) -> bool {
+        let range = match self.range() {
+            Some(range) => range,
+            None => return false,
+        };
+        if !range.Collapsed() {
+            self.delete_contents(&range);
+            return true;
+        }
+        let (node, offset) = (range.StartContainer(), range.StartOffset());
+        if let Some(data) = node.downcast::<CharacterData>() {
+            let length = char_length_at(&data.data(), offset, forwards);
+            if length > 0 {
+                let start = if forwards { offset } else { offset - length };
+                self.delete_data(data, start, length);
+                self.collapse(&node, start);
+                return true;
+            }
+        }
+
+        // The caret is at the edge of its text, so delete from the adjacent content, or join
+        // the paragraphs the caret is between.
+        let leaf = match self.adjacent_leaf(&node, offset, forwards) {
+            Some(leaf) => leaf,
+            None => return false,
+        };
+        let paragraph = self.paragraph_of(&node);
+        let leaf_paragraph = self.paragraph_of(&leaf);
+        if paragraph != leaf_paragraph {
+            let (first, second) = if forwards {
+                (paragraph, leaf_paragraph)
+            } else {
+                (leaf_paragraph, paragraph)
+            };
+            let nested = match (&first, &second) {
+                (&Some(ref first), &Some(ref second)) => {
+                    first.is_inclusive_ancestor_of(second) || second.is_inclusive_ancestor_of(first)
+                },
+                _ => false,
+            };
+            if let (Some(second), false) = (second, nested) {
+                let (node, offset) = self.join(first.as_deref(), &second);
+                self.collapse(&node, offset);
+                return true;
+            }
+        }
+        match leaf.downcast::<CharacterData>() {
+            Some(data) => {
+                let (start, length) = if forwards {
+                    (0, char_length_at(&data.data(), 0, true))
+                } else {
+                    let end = leaf.len();
+                    let length = char_length_at(&data.data(), end, false);
+                    (end - length, length)
+                };
+                self.delete_data(data, start, length);
+                self.collapse(&leaf, start);
+            },
+            None => {
+                let ((parent, index), _) = leaf_bounds(&leaf);
+                self.remove_node(&leaf);
+                self.collapse(&parent, index);
+            },
+        }
+        true
+    }
+
+    /// <https://w3c.github.io/editing/docs/execCommand/#the-insertparagraph-command>
+    fn insert_paragraph(&self) -> bool {
+        let range = match self.range() {
+            Some(range) => range,
+            None => return false,
+        };
+        if !range.Collapsed() {
+            self.delete_contents(&range);
+        }
+        let (node, offset) = (range.StartContainer(), range.StartOffset());
+        let (paragraph, (node, offset)) = match self.paragraph_of(&node) {
+            Some(paragraph) => (paragraph, (node, offset)),
+            None => self.wrap_in_paragraph(&node, offset),
+        };
+        let new_paragraph = self.split(&paragraph, &node, offset);
+        for paragraph in &[&paragraph, &new_paragraph] {
+            let has_content = paragraph
+                .traverse_preorder(ShadowIncluding::No)
+                .any(|node| is_leaf(&node));
+            if !has_content {
+                let br = self.create_element(local_name!("br"));
+                self.insert_node(paragraph, br.upcast(), None);
+            }
+        }
+        match new_paragraph
+            .traverse_preorder(ShadowIncluding::No)
+            .find(|node| node.is::<Text>())
+        {
+            Some(text) => self.collapse(&text, 0),
+            None => self.collapse(&new_paragraph, 0),
+        }
+        true
+    }
+
+    /// Wraps the content around the boundary point `(node, offset)` that isn't in a paragraph
+    /// in a new `<div>`, and returns it along with where the boundary point is now.
+    fn wrap_in_paragraph(&self, node: &Node, offset: u32) -> (DomRoot<Node>, (DomRoot<Node>, u32)) {
+        let host = self.host_node();
+        let children: Vec<_> = host.children().collect();
+        let (mut start, mut end) = if node == host {
+            (offset as usize, offset as usize)
+        } else {
+            let index = node
+                .inclusive_ancestors(ShadowIncluding::No)
+                .find(|ancestor| ancestor.GetParentNode().as_deref() == Some(host))
+                .expect("Editable content outside of its editing host")
+                .index() as usize;
+            (index, index + 1)
+        };
+        while start > 0 && !is_paragraph(&children[start - 1]) {
+            start -= 1;
+        }
+        while end < children.len() && !is_paragraph(&children[end]) {
+            end += 1;
+        }
+
+        let paragraph = DomRoot::upcast::<Node>(self.create_element(local_name!("div")));
+        self.insert_node(host, &paragraph, children.get(end).map(|child| &**child));
+        for child in &children[start..end] {
+            self.move_node(child, &paragraph, None);
+        }
+        let point = if node == host {
+            (paragraph.clone(), offset - start as u32)
+        } else {
+            (DomRoot::from_ref(node), offset)
+        };
+        (paragraph, point)
+    }
+
+    /// Moves everything in `paragraph` after the boundary point `(node, offset)` to a copy of
+    /// `paragraph` inserted after it, copying the elements in between too, and returns the
+    /// copy.
+    fn split(&self, paragraph: &Node, node: &Node, offset: u32) -> DomRoot<Node> {
+        let (mut container, mut moved): (DomRoot<Node>, Vec<DomRoot<Node>>) =
+            match node.downcast::<Text>() {
+                Some(text) => {
+                    if offset > 0 && offset < node.len() {
+                        self.split_text(text, offset);
+                    }
+                    let mut moved: Vec<_> = node.following_siblings().collect();
+                    if offset == 0 {
+                        moved.insert(0, DomRoot::from_ref(node));
+                    }
+                    let parent = node
+                        .GetParentNode()
+                        .expect("Editable text without a parent");
+                    (parent, moved)
+                },
+                None => (
+                    DomRoot::from_ref(node),
+                    node.children().skip(offset as usize).collect(),
+                ),
+            };
+        loop {
+            let copy = Node::clone(&container, None, CloneChildrenFlag::DoNotCloneChildren);
+            for node in &moved {
+                self.move_node(node, &copy, None);
+            }
+            if *container == *paragraph {
+                let parent = container
+                    .GetParentNode()
+                    .expect("Paragraph without a parent");
+                self.insert_node(&parent, &copy, container.GetNextSibling().as_deref());
+                return copy;
+            }
+            moved = iter::once(copy)
+                .chain(container.following_siblings())
+                .collect();
+            container = container
+                .GetParentNode()
+                .expect("Editable content outside of its editing host");
+        }
+    }
+
+    /// The element between `node` and the editing host that applies the style `command` adds.
+    fn style_ancestor(&self, node: &Node, command: Command) -> Option<DomRoot<Element>> {
+        node.inclusive_ancestors(ShadowIncluding::No)
+            .take_while(|ancestor| **ancestor != *self.host_node())
+            .filter_map(DomRoot::downcast::<Element>)
+            .find(|element| command.is_applied_by(element))
+    }
+
+    /// Splits the text nodes at the boundaries of `range`, so the selected text is made of
+    /// whole text nodes, and returns them.
+    fn split_selected_text(&self, range: &Range) -> Vec<DomRoot<Text>> {
+        let (start_node, start_offset) = (range.StartContainer(), range.StartOffset());
+        let (end_node, end_offset) = (range.EndContainer(), range.EndOffset());
+
+        // Split at the end first, which keeps the start offset valid.
+        if let Some(text) = end_node.downcast::<Text>() {
+            if end_offset > 0 && end_offset < end_node.len() {
+                self.split_text(text, end_offset);
+            }
+        }
+        let tail = match start_node.downcast::<Text>() {
+            Some(text) if start_offset > 0 && start_offset < start_node.len() => {
+                self.split_text(text, start_offset)
+            },
+            _ => None,
+        };
+        let (start, end) = match tail {
+            Some(tail) => {
+                let tail = DomRoot::upcast::<Node>(tail);
+                let end = if start_node == end_node {
+                    (tail.clone(), end_offset - start_offset)
+                } else {
+                    (end_node, end_offset)
+                };
+                ((tail, 0), end)
+            },
+            None => ((start_node, start_offset), (end_node, end_offset)),
+        };
+
+        self.host_node()
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Text>)
+            .filter(|text| {
+                let node = text.upcast::<Node>();
+                node.len() > 0 &&
+                    bp_position(node, 0, &start.0, start.1) != Some(Ordering::Less) &&
+                    bp_position(node, node.len(), &end.0, end.1) != Some(Ordering::Greater)
+            })
+            .collect()
+    }
+
+    /// Removes the style `command` applies from the selected text if all of it has it, or
+    /// applies it to the rest otherwise.
+    /// <https://w3c.github.io/editing/docs/execCommand/#the-bold-command>
+    /// <https://w3c.github.io/editing/docs/execCommand/#the-italic-command>
+    /// <https://w3c.github.io/editing/docs/execCommand/#the-createlink-command>
+    fn toggle_style(&self, command: Command, href: Option<DOMString>) -> bool {
+        let range = match self.range() {
+            Some(ref range) if !range.Collapsed() => range.clone(),
+            _ => return false,
+        };
+        let texts = self.split_selected_text(&range);
+        let (first, last) = match (texts.first(), texts.last()) {
+            (Some(first), Some(last)) => (first.clone(), last.clone()),
+            _ => return false,
+        };
+
+        let styled: Vec<_> = texts
+            .iter()
+            .filter_map(|text| self.style_ancestor(text.upcast(), command))
+            .collect();
+        if let Some(ref href) = href {
+            // Point the links that already are around the selected text at the new target.
+            for link in &styled {
+                self.set_href(link, href.clone());
+            }
+        } else if styled.len() == texts.len() {
+            // Unwrap the styling elements that only contain selected text.
+            let mut unwrapped: Vec<DomRoot<Element>> = vec![];
+            for element in styled {
+                if unwrapped.contains(&element) {
+                    continue;
+                }
+                let only_selected_text = element
+                    .upcast::<Node>()
+                    .traverse_preorder(ShadowIncluding::No)
+                    .filter_map(DomRoot::downcast::<Text>)
+                    .all(|text| text.upcast::<Node>().len() == 0 || texts.contains(&text));
+                if only_selected_text {
+                    self.unwrap(element.upcast());
+                    unwrapped.push(element);
+                }
+            }
+            let _ = self.selection.SetBaseAndExtent(
+                first.upcast(),
+                0,
+                last.upcast(),
+                last.upcast::<Node>().len(),
+            );
+            return true;
+        }
+
+        let name = match command {
+            Command::Bold => local_name!("b"),
+            Command::Italic => local_name!("i"),
+            _ => local_name!("a"),
+        };
+        let mut wrapper: Option<DomRoot<Node>> = None;
+        for text in &texts {
+            let node = text.upcast::<Node>();
+            if self.style_ancestor(node, command).is_some() {
+                continue;
+            }
+            // Put consecutive text nodes into the same element.
+            if let Some(ref wrapper) = wrapper {
+                if wrapper.GetNextSibling().as_deref() == Some(node) {
+                    self.move_node(node, wrapper, None);
+                    continue;
+                }
+            }
+            let element = self.create_element(name.clone());
+            if let Some(ref href) = href {
+                element.set_string_attribute(&local_name!("href"), href.clone());
+            }
+            let parent = node
+                .GetParentNode()
+                .expect("Editable text without a parent");
+            self.insert_node(&parent, element.upcast(), Some(node));
+            self.move_node(node, element.upcast(), None);
+            wrapper = Some(DomRoot::upcast(element));
+        }
+        let _ = self.selection.SetBaseAndExtent(
+            first.upcast(),
+            0,
+            last.upcast(),
+            last.upcast::<Node>().len(),
+        );
+        true
+    }
+
+    /// Replaces `node` with its children.
+    fn unwrap(&self, node: &Node) {
+        let parent = match node.GetParentNode() {
+            Some(parent) => parent,
+            None => return,
+        };
+        let children: Vec<_> = node.children().collect();
+        for child in children {
+            self.move_node(&child, &parent, Some(node));
+        }
+        self.remove_node(node);
+    }
+}
@@ -57,6 +57,8 @@ mod body;
 #[warn(deprecated)]
 pub mod clipboard_provider;
 #[warn(deprecated)]
+mod debugger;
+#[warn(deprecated)]
 mod devtools;
 #[warn(deprecated)]
 pub mod document_loader;
@@ -67,6 +69,8 @@ mod dom;
 mod canvas_state;
 #[warn(deprecated)]
 mod compartments;
+#[warn(deprecated)]
+mod editing;
 mod euclidext;
 #[warn(deprecated)]
 pub mod fetch;
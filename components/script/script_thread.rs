@@ -18,6 +18,7 @@
 //! loop.
 
 use crate::compartments::enter_realm;
+use crate::debugger::ScriptDebugger;
 use crate::devtools;
 use crate::document_loader::DocumentLoader;
 use crate::dom::bindings::cell::DomRefCell;
@@ -44,19 +45,22 @@ use crate::dom::customelementregistry::{
     CallbackReaction, CustomElementDefinition, CustomElementReactionStack,
 };
 use crate::dom::document::{
-    Document, DocumentSource, FocusType, HasBrowsingContext, IsHTMLDocument, TouchEventResult,
+    Document, DocumentSource, FocusInitiator, FocusType, HasBrowsingContext, IsHTMLDocument,
+    TouchEventResult,
 };
 use crate::dom::element::Element;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
 use crate::dom::htmliframeelement::{HTMLIFrameElement, NavigationType};
+use crate::dom::intersectionobserver::IntersectionObserver;
 use crate::dom::mutationobserver::MutationObserver;
 use crate::dom::node::{
     from_untrusted_node_address, window_from_node, Node, NodeDamage, ShadowIncluding,
 };
 use crate::dom::performanceentry::PerformanceEntry;
 use crate::dom::performancepainttiming::PerformancePaintTiming;
+use crate::dom::resizeobserver::ResizeObserver;
 use crate::dom::serviceworker::TrustedServiceWorkerAddress;
 use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
 use crate::dom::servoparser::{ParserContext, ServoParser};
@@ -92,8 +96,8 @@ use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::CSSError;
-use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo};
-use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
+use devtools_traits::{DebuggerPause, DevtoolScriptControlMsg, DevtoolsPageInfo};
+use devtools_traits::{ResumeLimit, ScriptToDevtoolsControlMsg, WorkerId};
 use embedder_traits::{EmbedderMsg, EventLoopWaker};
 use euclid::default::{Point2D, Rect};
 use euclid::Vector2D;
@@ -604,6 +608,9 @@ pub struct ScriptThread {
     /// no such server exists.
     devtools_port: Receiver<DevtoolScriptControlMsg>,
     devtools_sender: IpcSender<DevtoolScriptControlMsg>,
+    /// The JavaScript debugger, created once a devtools client starts debugging a
+    /// pipeline of this thread.
+    debugger: DomRefCell<Option<Rc<ScriptDebugger>>>,
 
     /// The JavaScript runtime.
     js_runtime: Rc<Runtime>,
@@ -1321,6 +1328,7 @@ impl ScriptThread {
             devtools_chan: state.devtools_chan,
             devtools_port: devtools_port,
             devtools_sender: ipc_devtools_sender,
+            debugger: Default::default(),
 
             microtask_queue: runtime.microtask_queue.clone(),
 
@@ -1593,6 +1601,9 @@ impl ScriptThread {
             window
                 .upcast::<GlobalScope>()
                 .perform_a_message_port_garbage_collection_checkpoint();
+            window
+                .upcast::<GlobalScope>()
+                .perform_a_broadcast_channel_garbage_collection_checkpoint();
 
             let pending_reflows = window.get_pending_reflow_count();
             if pending_reflows > 0 {
@@ -1606,6 +1617,38 @@ impl ScriptThread {
             }
         }
 
+        // https://html.spec.whatwg.org/multipage/#update-the-rendering step 16
+        // The callbacks may add or remove documents, so don't hold on to the map while
+        // running them.
+        let documents: Vec<DomRoot<Document>> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, document)| document)
+            .filter(|document| document.is_fully_active())
+            .collect();
+        for document in &documents {
+            let mut depth = 0;
+            ResizeObserver::gather_active_observations_at_depth(&document, depth);
+            while ResizeObserver::has_active_observations(&document) {
+                depth = ResizeObserver::broadcast_active_observations(&document);
+                ResizeObserver::gather_active_observations_at_depth(&document, depth);
+            }
+            if ResizeObserver::has_skipped_observations(&document) {
+                ResizeObserver::deliver_resize_loop_error_notification(&document);
+            }
+        }
+
+        // https://w3c.github.io/IntersectionObserver/#run-the-update-intersection-observations-steps
+        // This runs once every document is laid out, as the intersections of targets in
+        // nested documents depend on the layout of the documents containing them. Like the
+        // resize observations, this doesn't hold on to the map of documents.
+        for document in &documents {
+            if document.is_fully_active() {
+                IntersectionObserver::update_intersection_observations(document);
+            }
+        }
+
         true
     }
 
@@ -1709,6 +1752,7 @@ impl ScriptThread {
                     DispatchStorageEvent(id, ..) => Some(id),
                     ReportCSSError(id, ..) => Some(id),
                     Reload(id, ..) => Some(id),
+                    PrintToPdf(id) => Some(id),
                     WebVREvents(id, ..) => Some(id),
                     PaintMetric(..) => None,
                     ExitFullScreen(id, ..) => Some(id),
@@ -1926,6 +1970,9 @@ impl ScriptThread {
                 self.handle_css_error_reporting(pipeline_id, filename, line, column, msg)
             },
             ConstellationControlMsg::Reload(pipeline_id) => self.handle_reload(pipeline_id),
+            ConstellationControlMsg::PrintToPdf(pipeline_id) => {
+                self.handle_print_to_pdf(pipeline_id)
+            },
             ConstellationControlMsg::ExitPipeline(pipeline_id, discard_browsing_context) => {
                 self.handle_exit_pipeline_msg(pipeline_id, discard_browsing_context)
             },
@@ -2010,6 +2057,151 @@ impl ScriptThread {
                 devtools::handle_request_animation_frame(&*documents, id, name)
             },
             DevtoolScriptControlMsg::Reload(id) => devtools::handle_reload(&*documents, id),
+            DevtoolScriptControlMsg::AttachDebugger(id, attach) => {
+                self.handle_attach_debugger(&*documents, id, attach)
+            },
+            DevtoolScriptControlMsg::GetSources(id, reply) => {
+                devtools::handle_get_sources(self.debugger(), self.get_cx(), id, reply)
+            },
+            DevtoolScriptControlMsg::GetSourceText(_, source_id, reply) => {
+                devtools::handle_get_source_text(self.debugger(), self.get_cx(), source_id, reply)
+            },
+            DevtoolScriptControlMsg::SetBreakpoint(_, url, line, column, condition) => {
+                if let Some(debugger) = self.debugger() {
+                    debugger.set_breakpoint(
+                        self.get_cx(),
+                        &url,
+                        line,
+                        column,
+                        condition.as_ref().map(|c| &**c),
+                    );
+                }
+            },
+            DevtoolScriptControlMsg::RemoveBreakpoint(_, url, line, column) => {
+                if let Some(debugger) = self.debugger() {
+                    debugger.remove_breakpoint(self.get_cx(), &url, line, column);
+                }
+            },
+            DevtoolScriptControlMsg::PauseOnExceptions(_, pause, ignore_caught) => {
+                if let Some(debugger) = self.debugger() {
+                    debugger.pause_on_exceptions(self.get_cx(), pause, ignore_caught);
+                }
+            },
+            DevtoolScriptControlMsg::Interrupt(id) => {
+                if let Some(debugger) = self.debugger() {
+                    debugger.interrupt(self.get_cx(), id);
+                }
+            },
+            DevtoolScriptControlMsg::Resume(id, _) => {
+                warn!("Asked to resume pipeline {}, which is not paused.", id)
+            },
+            DevtoolScriptControlMsg::GetFrames(id, start, count, reply) => {
+                devtools::handle_get_frames(self.debugger(), self.get_cx(), id, start, count, reply)
+            },
+            DevtoolScriptControlMsg::GetFrameEnvironment(id, depth, reply) => {
+                devtools::handle_get_frame_environment(
+                    self.debugger(),
+                    self.get_cx(),
+                    id,
+                    depth,
+                    reply,
+                )
+            },
+            DevtoolScriptControlMsg::GetObjectProperties(id, object_id, reply) => {
+                devtools::handle_get_object_properties(
+                    self.debugger(),
+                    self.get_cx(),
+                    id,
+                    object_id,
+                    reply,
+                )
+            },
+            DevtoolScriptControlMsg::GetStorageOrigin(id, reply) => {
+                devtools::handle_get_storage_origin(&*documents, id, reply)
+            },
+            DevtoolScriptControlMsg::GetStorageItems(id, area, reply) => {
+                devtools::handle_get_storage_items(&*documents, id, area, reply)
+            },
+            DevtoolScriptControlMsg::SetStorageItem(id, area, item) => {
+                devtools::handle_set_storage_item(&*documents, id, area, item)
+            },
+            DevtoolScriptControlMsg::RemoveStorageItem(id, area, name, scope) => {
+                devtools::handle_remove_storage_item(&*documents, id, area, name, scope)
+            },
+            DevtoolScriptControlMsg::ClearStorage(id, area) => {
+                devtools::handle_clear_storage(&*documents, id, area)
+            },
+            DevtoolScriptControlMsg::GetStyleSheets(id, reply) => {
+                devtools::handle_get_stylesheets(&*documents, id, reply)
+            },
+            DevtoolScriptControlMsg::GetStyleSheetText(id, sheet_id, reply) => {
+                devtools::handle_get_stylesheet_text(&*documents, id, sheet_id, reply)
+            },
+            DevtoolScriptControlMsg::UpdateStyleSheet(id, sheet_id, text) => {
+                devtools::handle_update_stylesheet(&*documents, id, sheet_id, text)
+            },
+            DevtoolScriptControlMsg::ToggleStyleSheet(id, sheet_id, reply) => {
+                devtools::handle_toggle_stylesheet(&*documents, id, sheet_id, reply)
+            },
+            DevtoolScriptControlMsg::GetAppliedRules(id, node_id, reply) => {
+                devtools::handle_get_applied_rules(&*documents, id, node_id, reply)
+            },
+            DevtoolScriptControlMsg::ModifyRule(id, rule_id, modifications, reply) => {
+                devtools::handle_modify_rule(&*documents, id, rule_id, modifications, reply)
+            },
+        }
+    }
+
+    fn debugger(&self) -> Option<Rc<ScriptDebugger>> {
+        self.debugger.borrow().clone()
+    }
+
+    fn handle_attach_debugger(&self, documents: &Documents, id: PipelineId, attach: bool) {
+        if !attach {
+            if let Some(debugger) = self.debugger() {
+                debugger.remove_debuggee(self.get_cx(), id);
+            }
+            return;
+        }
+        let window = match documents.find_window(id) {
+            Some(window) => window,
+            None => return warn!("Message sent to closed pipeline {}.", id),
+        };
+        let debugger = match self.debugger() {
+            Some(debugger) => debugger,
+            None => {
+                let debugger = Rc::new(ScriptDebugger::new(self.get_cx()));
+                *self.debugger.borrow_mut() = Some(debugger.clone());
+                debugger
+            },
+        };
+        debugger.add_debuggee(self.get_cx(), window.upcast(), id);
+    }
+
+    /// Suspend script execution in a pipeline that was paused by the debugger. Only
+    /// devtools messages are processed until the devtools client resumes the pipeline,
+    /// which may ask for execution to pause again after stepping.
+    pub fn pause_for_debugger(id: PipelineId, pause: DebuggerPause) -> Option<ResumeLimit> {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            let script_thread = unsafe { &*root.get()? };
+            script_thread.handle_debugger_pause(id, pause)
+        })
+    }
+
+    fn handle_debugger_pause(&self, id: PipelineId, pause: DebuggerPause) -> Option<ResumeLimit> {
+        let chan = self.devtools_chan.as_ref()?;
+        chan.send(ScriptToDevtoolsControlMsg::DebuggerPaused(id, pause))
+            .unwrap();
+        self.background_hang_monitor.notify_wait();
+        loop {
+            match self.devtools_port.recv() {
+                Ok(DevtoolScriptControlMsg::Resume(resumed_id, limit)) if resumed_id == id => {
+                    self.notify_activity_to_hang_monitor(&ScriptThreadEventCategory::DevtoolsMsg);
+                    return limit;
+                },
+                Ok(msg) => self.handle_msg_from_devtools(msg),
+                Err(_) => return None,
+            }
         }
     }
 
@@ -2488,7 +2680,7 @@ impl ScriptThread {
         if let Some(ref frame_element) = frame_element {
             doc.begin_focus_transaction();
             doc.request_focus(frame_element.upcast());
-            doc.commit_focus_transaction(FocusType::Parent);
+            doc.commit_focus_transaction(FocusType::Parent, FocusInitiator::Other);
         }
     }
 
@@ -2958,6 +3150,7 @@ impl ScriptThread {
         let document = self.documents.borrow().find_document(pipeline_id);
         if let Some(document) = document {
             self.rebuild_and_force_reflow(&document, ReflowReason::WebFontLoaded);
+            document.fonts_environment_changed();
         }
     }
 
@@ -3674,20 +3867,22 @@ impl ScriptThread {
         };
 
         let window = document.window();
-        if window.window_size() == new_size {
+        let old_size = window.window_size();
+        if old_size == new_size {
             return;
         }
         debug!(
             "resizing pipeline {:?} from {:?} to {:?}",
-            pipeline_id,
-            window.window_size(),
-            new_size
+            pipeline_id, old_size, new_size
         );
         window.set_window_size(new_size);
         window.force_reflow(ReflowGoal::Full, ReflowReason::WindowResize);
 
         // http://dev.w3.org/csswg/cssom-view/#resizing-viewports
-        if size_type == WindowSizeType::Resize {
+        // A change of the media environment alone doesn't resize the viewport.
+        let viewport_changed = old_size.initial_viewport != new_size.initial_viewport ||
+            old_size.device_pixel_ratio != new_size.device_pixel_ratio;
+        if size_type == WindowSizeType::Resize && viewport_changed {
             let uievent = UIEvent::new(
                 &window,
                 DOMString::from("resize"),
@@ -3868,6 +4063,13 @@ impl ScriptThread {
         }
     }
 
+    fn handle_print_to_pdf(&self, pipeline_id: PipelineId) {
+        let window = self.documents.borrow().find_window(pipeline_id);
+        if let Some(window) = window {
+            window.Print();
+        }
+    }
+
     fn handle_webvr_events(&self, pipeline_id: PipelineId, events: Vec<WebVREvent>) {
         let window = self.documents.borrow().find_window(pipeline_id);
         if let Some(window) = window {
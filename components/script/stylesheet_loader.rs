@@ -39,7 +39,7 @@ use std::sync::Mutex;
 use style::media_queries::MediaList;
 use style::parser::ParserContext;
 use style::shared_lock::{Locked, SharedRwLock};
-use style::stylesheets::import_rule::ImportSheet;
+use style::stylesheets::import_rule::{ImportLayer, ImportSheet};
 use style::stylesheets::StylesheetLoader as StyleStylesheetLoader;
 use style::stylesheets::{
     CssRules, ImportRule, Namespaces, Origin, Stylesheet, StylesheetContents,
@@ -144,6 +144,11 @@ impl FetchResponseListener for StylesheetContext {
             let protocol_encoding_label = metadata.charset.as_ref().map(|s| &**s);
             let final_url = metadata.final_url;
 
+            let source = Stylesheet::decode_bytes(
+                &data,
+                protocol_encoding_label,
+                Some(environment_encoding),
+            );
             let win = window_from_node(&*elem);
 
             let loader = StylesheetLoader::for_element(&elem);
@@ -157,18 +162,19 @@ impl FetchResponseListener for StylesheetContext {
                         .map_or(true, |gen| gen == link.get_request_generation_id());
                     if is_stylesheet_load_applicable {
                         let shared_lock = document.style_shared_lock().clone();
-                        let sheet = Arc::new(Stylesheet::from_bytes(
-                            &data,
+                        let media = Arc::new(shared_lock.wrap(media.take().unwrap()));
+                        let sheet = Arc::new(Stylesheet::from_str(
+                            &source,
                             final_url,
-                            protocol_encoding_label,
-                            Some(environment_encoding),
                             Origin::Author,
-                            media.take().unwrap(),
+                            media,
                             shared_lock,
                             Some(&loader),
                             win.css_error_reporter(),
                             document.quirks_mode(),
+                            0,
                         ));
+                        document.set_stylesheet_source(&sheet, &source);
 
                         if link.is_alternate() {
                             sheet.set_disabled(true);
@@ -178,15 +184,15 @@ impl FetchResponseListener for StylesheetContext {
                     }
                 },
                 StylesheetContextSource::Import(ref stylesheet) => {
-                    Stylesheet::update_from_bytes(
+                    Stylesheet::update_from_str(
                         &stylesheet,
-                        &data,
-                        protocol_encoding_label,
-                        Some(environment_encoding),
+                        &source,
                         final_url,
                         Some(&loader),
                         win.css_error_reporter(),
+                        0,
                     );
+                    document.set_stylesheet_source(stylesheet, &source);
                 },
             }
 
@@ -362,6 +368,7 @@ impl<'a> StyleStylesheetLoader for StylesheetLoader<'a> {
         context: &ParserContext,
         lock: &SharedRwLock,
         media: Arc<Locked<MediaList>>,
+        layer: Option<ImportLayer>,
     ) -> Arc<Locked<ImportRule>> {
         let sheet = Arc::new(Stylesheet {
             contents: StylesheetContents {
@@ -381,6 +388,7 @@ impl<'a> StyleStylesheetLoader for StylesheetLoader<'a> {
         let stylesheet = ImportSheet(sheet.clone());
         let import = ImportRule {
             url,
+            layer,
             source_location,
             stylesheet,
         };
@@ -66,3 +66,38 @@ pub mod srcset {
 pub mod timeranges {
     pub use crate::dom::timeranges::TimeRangesContainer;
 }
+
+pub mod devtools {
+    pub use crate::devtools::parse_rule_id;
+
+    use crate::devtools::collect_style_rules;
+    use servo_arc::Arc;
+    use servo_url::ServoUrl;
+    use style::context::QuirksMode;
+    use style::media_queries::MediaList;
+    use style::shared_lock::SharedRwLock;
+    use style::stylesheets::{Origin, Stylesheet};
+
+    /// Returns the ids that the devtools give to the style rules of a style sheet
+    /// with the id `0` and the source `css`.
+    pub fn style_rule_ids(css: &str) -> Vec<String> {
+        let lock = SharedRwLock::new();
+        let media = Arc::new(lock.wrap(MediaList::empty()));
+        let url = ServoUrl::parse("http://localhost").unwrap();
+        let sheet = Stylesheet::from_str(
+            css,
+            url,
+            Origin::Author,
+            media,
+            lock,
+            None,
+            None,
+            QuirksMode::NoQuirks,
+            0,
+        );
+        let guard = sheet.shared_lock.read();
+        let mut rules = vec![];
+        collect_style_rules(sheet.contents.rules(&guard), "0:", &guard, &mut rules);
+        rules.into_iter().map(|(id, _)| id).collect()
+    }
+}
@@ -23,9 +23,12 @@ use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use style::context::QuirksMode;
 use style::dom::OpaqueNode;
+use style::font_face::EffectiveSources;
 use style::properties::PropertyId;
+use style::properties_and_values::PropertyRegistration;
 use style::selector_parser::PseudoElement;
 use style::stylesheets::Stylesheet;
+use style::values::computed::font::FamilyName;
 
 /// Asynchronous messages that script can send to layout.
 pub enum Msg {
@@ -103,6 +106,24 @@ pub enum Msg {
 
     /// Request the current number of animations that are running.
     GetRunningAnimations(IpcSender<usize>),
+
+    /// Registers a font face created by script, identified by the given id, with the font
+    /// cache, replying with whether it could be loaded.
+    AddFontFace(FamilyName, Atom, WebFontSource, IpcSender<bool>),
+
+    /// Removes the font face created by script with the given id from the font cache.
+    RemoveFontFace(FamilyName, Atom),
+
+    /// Registers a custom property with `CSS.registerProperty()`.
+    RegisterProperty(PropertyRegistration),
+}
+
+/// Where the data of a font face created by script comes from.
+pub enum WebFontSource {
+    /// The sources of its `src` descriptor, tried in order.
+    Sources(EffectiveSources),
+    /// The font data itself.
+    Data(Vec<u8>),
 }
 
 #[derive(Debug, PartialEq)]
@@ -119,7 +140,11 @@ pub enum QueryMsg {
     NodeScrollGeometryQuery(OpaqueNode),
     OffsetParentQuery(OpaqueNode),
     TextIndexQuery(OpaqueNode, Point2D<f32>),
+    CaretPositionQuery(Point2D<f32>),
     NodesFromPointQuery(Point2D<f32>, NodesFromPointQueryType),
+    ClippedBoxesQuery(Vec<OpaqueNode>),
+    BoxSizesQuery(Vec<OpaqueNode>),
+    RunningAnimationsQuery,
 
     // FIXME(nox): The following queries use the TrustedNodeAddress to
     // access actual DOM nodes, but those values can be constructed from
@@ -129,6 +154,8 @@ pub enum QueryMsg {
     StyleQuery(TrustedNodeAddress),
     ElementInnerTextQuery(TrustedNodeAddress),
     InnerWindowDimensionsQuery(BrowsingContextId),
+    /// Lay the document out for print media and paint it into a PDF document.
+    PrintQuery,
 }
 
 /// Any query to perform with this reflow.
@@ -148,10 +175,15 @@ impl ReflowGoal {
             ReflowGoal::LayoutQuery(ref querymsg, _) => match *querymsg {
                 QueryMsg::NodesFromPointQuery(..) |
                 QueryMsg::TextIndexQuery(..) |
+                QueryMsg::CaretPositionQuery(_) |
                 QueryMsg::InnerWindowDimensionsQuery(_) |
+                QueryMsg::PrintQuery |
                 QueryMsg::ElementInnerTextQuery(_) => true,
                 QueryMsg::ContentBoxQuery(_) |
                 QueryMsg::ContentBoxesQuery(_) |
+                QueryMsg::ClippedBoxesQuery(_) |
+                QueryMsg::BoxSizesQuery(_) |
+                QueryMsg::RunningAnimationsQuery |
                 QueryMsg::NodeGeometryQuery(_) |
                 QueryMsg::NodeScrollGeometryQuery(_) |
                 QueryMsg::NodeScrollIdQuery(_) |
@@ -170,15 +202,20 @@ impl ReflowGoal {
             ReflowGoal::LayoutQuery(ref querymsg, _) => match *querymsg {
                 QueryMsg::NodesFromPointQuery(..) |
                 QueryMsg::TextIndexQuery(..) |
+                QueryMsg::CaretPositionQuery(_) |
                 QueryMsg::ElementInnerTextQuery(_) => true,
                 QueryMsg::ContentBoxQuery(_) |
                 QueryMsg::ContentBoxesQuery(_) |
+                QueryMsg::ClippedBoxesQuery(_) |
+                QueryMsg::BoxSizesQuery(_) |
+                QueryMsg::RunningAnimationsQuery |
                 QueryMsg::NodeGeometryQuery(_) |
                 QueryMsg::NodeScrollGeometryQuery(_) |
                 QueryMsg::NodeScrollIdQuery(_) |
                 QueryMsg::ResolvedStyleQuery(..) |
                 QueryMsg::OffsetParentQuery(_) |
                 QueryMsg::InnerWindowDimensionsQuery(_) |
+                QueryMsg::PrintQuery |
                 QueryMsg::StyleQuery(_) => false,
             },
         }
@@ -198,6 +235,8 @@ pub struct ReflowComplete {
     pub pending_images: Vec<PendingImage>,
     /// The list of nodes that initiated a CSS transition.
     pub newly_transitioning_nodes: Vec<UntrustedNodeAddress>,
+    /// Whether web fonts are still being loaded or removed.
+    pub pending_web_fonts: bool,
 }
 
 /// Information needed for a script-initiated reflow.
@@ -3,10 +3,12 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use app_units::Au;
-use euclid::default::Rect;
+use euclid::default::{Rect, Size2D as UntypedSize2D};
 use euclid::Size2D;
 use script_traits::UntrustedNodeAddress;
 use servo_arc::Arc;
+use style::dom::OpaqueNode;
+use style::properties::longhands::animation_direction::computed_value::single_value::T as AnimationDirection;
 use style::properties::ComputedValues;
 use style_traits::CSSPixel;
 use webrender_api::ExternalScrollId;
@@ -24,6 +26,14 @@ pub trait LayoutRPC {
     fn content_box(&self) -> ContentBoxResponse;
     /// Requests the dimensions of all the content boxes, as in the `getClientRects()` call.
     fn content_boxes(&self) -> ContentBoxesResponse;
+    /// Requests the border boxes of a batch of nodes, and the scroll containers clipping
+    /// them. Used by `IntersectionObserver`.
+    fn clipped_boxes(&self) -> ClippedBoxesResponse;
+    /// Requests the sizes of the boxes of a batch of nodes. Used by `ResizeObserver`.
+    fn box_sizes(&self) -> BoxSizesResponse;
+    /// Requests the CSS animations and transitions that are running. Used by
+    /// `getAnimations()`.
+    fn running_animations(&self) -> RunningAnimationsResponse;
     /// Requests the geometry of this node. Used by APIs such as `clientTop`.
     fn node_geometry(&self) -> NodeGeometryResponse;
     /// Requests the scroll geometry of this node. Used by APIs such as `scrollTop`.
@@ -37,18 +47,91 @@ pub trait LayoutRPC {
     /// none` subtree.
     fn style(&self) -> StyleResponse;
     fn text_index(&self) -> TextIndexResponse;
+    /// Requests the text node and the offset in its text of the caret position closest to the
+    /// given point.
+    fn caret_position(&self) -> CaretPositionResponse;
     /// Requests the list of nodes from the given point.
     fn nodes_from_point_response(&self) -> Vec<UntrustedNodeAddress>;
     /// Query layout to get the inner text for a given element.
     fn element_inner_text(&self) -> String;
     /// Get the dimensions of an iframe's inner window.
     fn inner_window_dimensions(&self) -> Option<Size2D<f32, CSSPixel>>;
+    /// Takes the PDF document painted by the last print query, if any.
+    fn printed_pdf(&self) -> Option<Vec<u8>>;
 }
 
 pub struct ContentBoxResponse(pub Option<Rect<Au>>);
 
 pub struct ContentBoxesResponse(pub Vec<Rect<Au>>);
 
+/// The boxes of a node, in the coordinate space of its document, before any scrolling.
+#[derive(Clone, Debug)]
+pub struct ClippedBox {
+    /// The union of the border boxes of the node's fragments.
+    pub border_box: Rect<Au>,
+    /// The union of the padding boxes of the node's fragments.
+    pub padding_box: Rect<Au>,
+    /// The union of the content boxes of the node's fragments.
+    pub content_box: Rect<Au>,
+    /// Whether the node clips its overflow to its padding box.
+    pub clips_overflow: bool,
+    /// The nodes establishing the scroll containers the node is in, from the innermost
+    /// one out, with the padding boxes their contents are clipped to.
+    pub clips: Vec<(OpaqueNode, Rect<Au>)>,
+}
+
+/// The boxes of each of the requested nodes, or `None` for nodes that aren't rendered.
+pub struct ClippedBoxesResponse(pub Vec<Option<ClippedBox>>);
+
+/// The sizes of the boxes of a node.
+#[derive(Clone, Copy, Debug)]
+pub struct BoxSizes {
+    /// The content box of the node, relative to its padding box.
+    pub content_rect: Rect<Au>,
+    /// The size of the border box of the node.
+    pub border_box_size: UntypedSize2D<Au>,
+    /// Whether the inline axis of the node is vertical, making its height its inline size.
+    pub is_vertical: bool,
+}
+
+/// The box sizes of each of the requested nodes, or `None` for nodes that aren't rendered.
+pub struct BoxSizesResponse(pub Vec<Option<BoxSizes>>);
+
+/// Whether a running animation is a CSS animation or a CSS transition.
+#[derive(Clone, Debug)]
+pub enum RunningAnimationKind {
+    /// A CSS animation, with its animation name.
+    Animation(String),
+    /// A CSS transition, with the property it transitions.
+    Transition(String),
+}
+
+/// A CSS animation or transition that layout is running.
+#[derive(Clone, Debug)]
+pub struct RunningAnimation {
+    /// The node the animation applies to.
+    pub node: UntrustedNodeAddress,
+    /// What kind of animation this is.
+    pub kind: RunningAnimationKind,
+    /// The time elapsed since the animation started, in seconds. This
+    /// includes the delay of the animation, and is negative for transitions
+    /// that are still delayed.
+    pub current_time: f64,
+    /// The delay of the animation, in seconds.
+    pub delay: f64,
+    /// The duration of a single iteration, in seconds.
+    pub duration: f64,
+    /// The number of iterations, which is infinite for `infinite` animations.
+    pub iterations: f64,
+    /// The direction the animation iterates in.
+    pub direction: AnimationDirection,
+    /// Whether the animation is paused.
+    pub paused: bool,
+}
+
+/// The CSS animations and transitions running in the document.
+pub struct RunningAnimationsResponse(pub Vec<RunningAnimation>);
+
 pub struct NodeGeometryResponse {
     pub client_rect: Rect<i32>,
 }
@@ -77,3 +160,7 @@ pub struct StyleResponse(pub Option<Arc<ComputedValues>>);
 
 #[derive(Clone)]
 pub struct TextIndexResponse(pub Option<usize>);
+
+/// A text node and an offset in characters within its rendered text.
+#[derive(Clone)]
+pub struct CaretPositionResponse(pub Option<(UntrustedNodeAddress, usize)>);
@@ -24,6 +24,7 @@ use style::context::SharedStyleContext;
 use style::data::ElementData;
 use style::dom::OpaqueNode;
 use style::dom::{LayoutIterator, NodeInfo, TElement, TNode};
+use style::element_state::ElementState;
 use style::font_metrics::ServoMetricsProvider;
 use style::properties::ComputedValues;
 use style::selector_parser::{PseudoElement, PseudoElementCascadeType, SelectorImpl};
@@ -35,6 +36,7 @@ pub enum PseudoElementType {
     Normal,
     Before,
     After,
+    Backdrop,
     DetailsSummary,
     DetailsContent,
 }
@@ -45,6 +47,7 @@ impl PseudoElementType {
             PseudoElementType::Normal => FragmentType::FragmentBody,
             PseudoElementType::Before => FragmentType::BeforePseudoContent,
             PseudoElementType::After => FragmentType::AfterPseudoContent,
+            PseudoElementType::Backdrop => FragmentType::BackdropPseudoContent,
             PseudoElementType::DetailsSummary => FragmentType::FragmentBody,
             PseudoElementType::DetailsContent => FragmentType::FragmentBody,
         }
@@ -71,6 +74,7 @@ impl PseudoElementType {
             },
             PseudoElementType::Before => PseudoElement::Before,
             PseudoElementType::After => PseudoElement::After,
+            PseudoElementType::Backdrop => PseudoElement::Backdrop,
             PseudoElementType::DetailsSummary => PseudoElement::DetailsSummary,
             PseudoElementType::DetailsContent => PseudoElement::DetailsContent,
         }
@@ -191,6 +195,10 @@ pub trait ThreadSafeLayoutNode:
     /// the parent until all the children have been processed.
     fn parent_style(&self) -> Arc<ComputedValues>;
 
+    /// Returns the style of the `::selection` pseudo-element of the parent of a text node,
+    /// which the selected parts of the text are painted with, falling back to `parent_style`.
+    fn parent_selected_style(&self) -> Arc<ComputedValues>;
+
     fn get_before_pseudo(&self) -> Option<Self> {
         self.as_element()
             .and_then(|el| el.get_before_pseudo())
@@ -203,6 +211,12 @@ pub trait ThreadSafeLayoutNode:
             .map(|el| el.as_node())
     }
 
+    fn get_backdrop_pseudo(&self) -> Option<Self> {
+        self.as_element()
+            .and_then(|el| el.get_backdrop_pseudo())
+            .map(|el| el.as_node())
+    }
+
     fn get_details_summary_pseudo(&self) -> Option<Self> {
         self.as_element()
             .and_then(|el| el.get_details_summary_pseudo())
@@ -247,8 +261,7 @@ pub trait ThreadSafeLayoutNode:
             el.selected_style()
         } else {
             debug_assert!(self.is_text_node());
-            // TODO(stshine): What should the selected style be for text?
-            self.parent_style()
+            self.parent_selected_style()
         }
     }
 
@@ -385,6 +398,19 @@ pub trait ThreadSafeLayoutElement:
         }
     }
 
+    /// Returns the `::backdrop` of this element, which is rendered just below it if it is in
+    /// the top layer.
+    #[inline]
+    fn get_backdrop_pseudo(&self) -> Option<Self> {
+        if self.get_pseudo_element_type() == PseudoElementType::Normal &&
+            self.style_data().styles.primary().in_top_layer()
+        {
+            Some(self.with_pseudo(PseudoElementType::Backdrop))
+        } else {
+            None
+        }
+    }
+
     #[inline]
     fn get_details_summary_pseudo(&self) -> Option<Self> {
         if self.has_local_name(&local_name!("details")) && self.has_namespace(&ns!(html)) {
@@ -436,26 +462,40 @@ pub trait ThreadSafeLayoutElement:
                             Some(data.styles.primary()),
                             &ServoMetricsProvider,
                         ),
-                    PseudoElementCascadeType::Lazy => {
-                        context
-                            .stylist
-                            .lazily_compute_pseudo_element_style(
-                                &context.guards,
-                                unsafe { self.unsafe_get() },
-                                &style_pseudo,
-                                RuleInclusion::All,
-                                data.styles.primary(),
-                                /* is_probe = */ false,
-                                &ServoMetricsProvider,
-                                /* matching_func = */ None,
-                            )
-                            .unwrap()
-                    },
+                    PseudoElementCascadeType::Lazy => self.lazy_pseudo_element_style(
+                        context,
+                        &style_pseudo,
+                        data.styles.primary(),
+                    ),
                 }
             },
         }
     }
 
+    /// Computes the style of the given lazily-cascaded pseudo-element of this
+    /// element, inheriting from `parent_style`.
+    #[inline]
+    fn lazy_pseudo_element_style(
+        &self,
+        context: &SharedStyleContext,
+        pseudo: &PseudoElement,
+        parent_style: &ComputedValues,
+    ) -> Arc<ComputedValues> {
+        context
+            .stylist
+            .lazily_compute_pseudo_element_style(
+                &context.guards,
+                unsafe { self.unsafe_get() },
+                pseudo,
+                RuleInclusion::All,
+                parent_style,
+                /* is_probe = */ false,
+                &ServoMetricsProvider,
+                /* matching_func = */ None,
+            )
+            .unwrap()
+    }
+
     #[inline]
     fn selected_style(&self) -> Arc<ComputedValues> {
         let data = self.style_data();
@@ -466,6 +506,50 @@ pub trait ThreadSafeLayoutElement:
             .clone()
     }
 
+    /// Returns the style of the `::first-line` of this element, if any rule
+    /// applies to it.
+    #[inline]
+    fn first_line_style(&self) -> Option<Arc<ComputedValues>> {
+        self.style_data()
+            .styles
+            .pseudos
+            .get(&PseudoElement::FirstLine)
+            .cloned()
+    }
+
+    /// Returns the style of the `::first-letter` of this element, if any rule
+    /// applies to it.
+    #[inline]
+    fn first_letter_style(&self) -> Option<Arc<ComputedValues>> {
+        self.style_data()
+            .styles
+            .pseudos
+            .get(&PseudoElement::FirstLetter)
+            .cloned()
+    }
+
+    /// Returns the style of the `::marker` of this element.
+    #[inline]
+    fn marker_style(&self, context: &SharedStyleContext) -> Arc<ComputedValues> {
+        let data = self.style_data();
+        self.lazy_pseudo_element_style(context, &PseudoElement::Marker, data.styles.primary())
+    }
+
+    /// Returns the style of the `::placeholder` of this element, inheriting
+    /// from `parent_style`, if its placeholder text is being shown.
+    #[inline]
+    fn placeholder_style(
+        &self,
+        context: &SharedStyleContext,
+        parent_style: &ComputedValues,
+    ) -> Option<Arc<ComputedValues>> {
+        let state = unsafe { self.unsafe_get() }.state();
+        if !state.contains(ElementState::IN_PLACEHOLDER_SHOWN_STATE) {
+            return None;
+        }
+        Some(self.lazy_pseudo_element_style(context, &PseudoElement::Placeholder, parent_style))
+    }
+
     /// Returns the already resolved style of the node.
     ///
     /// This differs from `style(ctx)` in that if the pseudo-element has not yet
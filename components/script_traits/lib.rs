@@ -59,6 +59,7 @@ use std::fmt;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Duration;
+use style_traits::media_queries::MediaEnvironment;
 use style_traits::CSSPixel;
 use style_traits::SpeculativePainter;
 use webgpu::WebGPU;
@@ -389,6 +390,8 @@ pub enum ConstellationControlMsg {
     ReportCSSError(PipelineId, String, u32, u32, String),
     /// Reload the given page.
     Reload(PipelineId),
+    /// Print the given page to a PDF document.
+    PrintToPdf(PipelineId),
     /// Notifies the script thread of WebVR events.
     WebVREvents(PipelineId, Vec<WebVREvent>),
     /// Notifies the script thread about a new recorded paint metric.
@@ -430,6 +433,7 @@ impl fmt::Debug for ConstellationControlMsg {
             DispatchStorageEvent(..) => "DispatchStorageEvent",
             ReportCSSError(..) => "ReportCSSError",
             Reload(..) => "Reload",
+            PrintToPdf(..) => "PrintToPdf",
             WebVREvents(..) => "WebVREvents",
             PaintMetric(..) => "PaintMetric",
             ExitFullScreen(..) => "ExitFullScreen",
@@ -784,6 +788,10 @@ pub struct WindowSizeData {
 
     /// The resolution of the window in dppx, not including any "pinch zoom" factor.
     pub device_pixel_ratio: Scale<f32, CSSPixel, DevicePixel>,
+
+    /// The preferences of the user and the capabilities of their device which
+    /// media queries are evaluated against.
+    pub media_environment: MediaEnvironment,
 }
 
 /// The type of window size change.
@@ -865,6 +873,8 @@ pub enum ConstellationMsg {
     WebDriverCommand(WebDriverCommandMsg),
     /// Reload a top-level browsing context.
     Reload(TopLevelBrowsingContextId),
+    /// Print the document of a top-level browsing context to a PDF document.
+    PrintToPdf(TopLevelBrowsingContextId),
     /// A log entry, with the top-level browsing context id and thread name
     LogEntry(Option<TopLevelBrowsingContextId>, Option<String>, LogEntry),
     /// Dispatch WebVR events to the subscribed script threads.
@@ -908,6 +918,7 @@ impl fmt::Debug for ConstellationMsg {
             TickAnimation(..) => "TickAnimation",
             WebDriverCommand(..) => "WebDriverCommand",
             Reload(..) => "Reload",
+            PrintToPdf(..) => "PrintToPdf",
             LogEntry(..) => "LogEntry",
             WebVREvents(..) => "WebVREvents",
             NewBrowser(..) => "NewBrowser",
@@ -1043,6 +1054,39 @@ pub struct StructuredSerializedData {
     pub ports: Option<HashMap<MessagePortId, MessagePortImpl>>,
 }
 
+impl StructuredSerializedData {
+    /// Clone the serialized data for use with broadcast-channels.
+    pub fn clone_for_broadcast(&self) -> StructuredSerializedData {
+        StructuredSerializedData {
+            serialized: self.serialized.clone(),
+            // Ports cannot be transferred through a broadcast-channel.
+            ports: None,
+        }
+    }
+}
+
+/// A message sent through a broadcast-channel.
+/// <https://html.spec.whatwg.org/multipage/#dom-broadcastchannel-postmessage>
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BroadcastMsg {
+    /// The origin of this message.
+    pub origin: ImmutableOrigin,
+    /// The name of the channel.
+    pub channel_name: String,
+    /// A data-holder for serialized data.
+    pub data: StructuredSerializedData,
+}
+
+impl Clone for BroadcastMsg {
+    fn clone(&self) -> BroadcastMsg {
+        BroadcastMsg {
+            data: self.data.clone_for_broadcast(),
+            origin: self.origin.clone(),
+            channel_name: self.channel_name.clone(),
+        }
+    }
+}
+
 /// A task on the https://html.spec.whatwg.org/multipage/#port-message-queue
 #[derive(Debug, Deserialize, MallocSizeOf, Serialize)]
 pub struct PortMessageTask {
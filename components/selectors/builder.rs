@@ -17,7 +17,7 @@
 //! is non-trivial. This module encapsulates those details and presents an
 //! easy-to-use API for the parser.
 
-use crate::parser::{Combinator, Component, SelectorImpl};
+use crate::parser::{Combinator, Component, Selector, SelectorImpl};
 use crate::sink::Push;
 use servo_arc::{Arc, HeaderWithLength, ThinArc};
 use smallvec::{self, SmallVec};
@@ -110,6 +110,13 @@ impl<Impl: SelectorImpl> SelectorBuilder<Impl> {
         if parsed_part {
             flags |= SelectorFlags::HAS_PART;
         }
+        if self
+            .simple_selectors
+            .iter()
+            .any(|s| s.has_parent_selector())
+        {
+            flags |= SelectorFlags::HAS_PARENT;
+        }
         self.build_with_specificity_and_flags(SpecificityAndFlags { specificity, flags })
     }
 
@@ -198,6 +205,7 @@ bitflags! {
         const HAS_PSEUDO = 1 << 0;
         const HAS_SLOTTED = 1 << 1;
         const HAS_PART = 1 << 2;
+        const HAS_PARENT = 1 << 3;
     }
 }
 
@@ -230,6 +238,11 @@ impl SpecificityAndFlags {
     pub fn is_part(&self) -> bool {
         self.flags.intersects(SelectorFlags::HAS_PART)
     }
+
+    #[inline]
+    pub fn has_parent_selector(&self) -> bool {
+        self.flags.intersects(SelectorFlags::HAS_PARENT)
+    }
 }
 
 const MAX_10BIT: u32 = (1u32 << 10) - 1;
@@ -323,6 +336,7 @@ where
             Component::FirstOfType |
             Component::LastOfType |
             Component::OnlyOfType |
+            Component::ParentSelector |
             Component::NonTSPseudoClass(..) => {
                 specificity.class_like_selectors += 1;
             },
@@ -338,9 +352,34 @@ where
                     simple_selector_specificity(&ss, specificity);
                 }
             },
+            Component::Is(ref list) => {
+                // https://drafts.csswg.org/selectors-4/#specificity-rules:
+                //
+                //     The specificity of an :is() pseudo-class is replaced by
+                //     the specificity of the most specific complex selector in
+                //     its selector list argument.
+                *specificity += max_selector_list_specificity(list.iter());
+            },
+            Component::Has(ref list) => {
+                // Same as :is(), the anchor doesn't contribute to it.
+                *specificity += max_selector_list_specificity(list.iter().map(|r| &r.selector));
+            },
+            Component::Where(..) | Component::RelativeSelectorAnchor => {
+                // Does not affect specificity
+            },
         }
     }
 
+    fn max_selector_list_specificity<'a, Impl, I>(list: I) -> Specificity
+    where
+        Impl: SelectorImpl + 'a,
+        I: Iterator<Item = &'a Selector<Impl>>,
+    {
+        list.map(|selector| Specificity::from(selector.specificity()))
+            .max()
+            .unwrap_or_default()
+    }
+
     let mut specificity = Default::default();
     for simple_selector in iter {
         simple_selector_specificity(&simple_selector, &mut specificity);
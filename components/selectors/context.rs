@@ -132,6 +132,10 @@ where
     /// Whether we're inside a negation or not.
     in_negation: bool,
 
+    /// The anchor element of the relative selector we're matching, if any,
+    /// that is, the element `:has()` is being matched against.
+    relative_selector_anchor: Option<OpaqueElement>,
+
     /// An optional hook function for checking whether a pseudo-element
     /// should match when matching_mode is ForStatelessPseudoElement.
     pub pseudo_element_matching_fn: Option<&'a dyn Fn(&Impl::PseudoElement) -> bool>,
@@ -183,6 +187,7 @@ where
             current_host: None,
             nesting_level: 0,
             in_negation: false,
+            relative_selector_anchor: None,
             pseudo_element_matching_fn: None,
             extra_data: Default::default(),
             _impl: ::std::marker::PhantomData,
@@ -233,18 +238,46 @@ where
 
     /// Runs F with a deeper nesting level, and marking ourselves in a negation,
     /// for a :not(..) selector, for example.
+    ///
+    /// Negations can end up nested via :is() or :where(), in which case we
+    /// restore the state of the outer one afterwards.
     #[inline]
     pub fn nest_for_negation<F, R>(&mut self, f: F) -> R
     where
         F: FnOnce(&mut Self) -> R,
     {
-        debug_assert!(!self.in_negation, "Someone messed up parsing?");
+        let original_in_negation = self.in_negation;
         self.in_negation = true;
         let result = self.nest(f);
-        self.in_negation = false;
+        self.in_negation = original_in_negation;
         result
     }
 
+    /// Runs F with a deeper nesting level, matching relative selectors
+    /// anchored at the given element, for a :has(..) selector.
+    ///
+    /// Visited-ness of links can't be observed through :has(), so all links
+    /// are treated as unvisited while matching the relative selectors.
+    #[inline]
+    pub fn nest_for_relative_selector<F, R>(&mut self, anchor: OpaqueElement, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let original_anchor = self.relative_selector_anchor.replace(anchor);
+        let result = self
+            .with_visited_handling_mode(VisitedHandlingMode::AllLinksUnvisited, |context| {
+                context.nest(f)
+            });
+        self.relative_selector_anchor = original_anchor;
+        result
+    }
+
+    /// Returns the anchor of the relative selector we're matching, if any.
+    #[inline]
+    pub fn relative_selector_anchor(&self) -> Option<OpaqueElement> {
+        self.relative_selector_anchor
+    }
+
     #[inline]
     pub fn visited_handling(&self) -> VisitedHandlingMode {
         self.visited_handling
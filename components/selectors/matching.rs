@@ -6,7 +6,8 @@ use crate::attr::{AttrSelectorOperation, NamespaceConstraint, ParsedAttrSelector
 use crate::bloom::{BloomFilter, BLOOM_HASH_MASK};
 use crate::nth_index_cache::NthIndexCacheInner;
 use crate::parser::{AncestorHashes, Combinator, Component, LocalName};
-use crate::parser::{NonTSPseudoClass, Selector, SelectorImpl, SelectorIter, SelectorList};
+use crate::parser::{NonTSPseudoClass, RelativeSelector, RelativeSelectorMatchHint};
+use crate::parser::{Selector, SelectorImpl, SelectorIter, SelectorList};
 use crate::tree::Element;
 use smallvec::SmallVec;
 use std::borrow::Borrow;
@@ -41,13 +42,39 @@ bitflags! {
         /// The element has an empty selector, so when a child is appended we
         /// might need to restyle the parent completely.
         const HAS_EMPTY_SELECTOR = 1 << 3;
+
+        /// The element has been matched against a `:has()` selector, so
+        /// mutations of the elements its relative selectors looked at may
+        /// need to restyle it.
+        const ANCHORS_RELATIVE_SELECTOR = 1 << 4;
+
+        /// The element has been looked at while matching a `:has()` selector
+        /// anchored at one of its ancestors.
+        const RELATIVE_SELECTOR_SEARCH_DIRECTION_ANCESTOR = 1 << 5;
+
+        /// The element has been looked at while matching a `:has()` selector
+        /// anchored at one of its previous siblings.
+        const RELATIVE_SELECTOR_SEARCH_DIRECTION_SIBLING = 1 << 6;
+
+        /// The element has been looked at while matching a `:has()` selector
+        /// anchored at a previous sibling of one of its ancestors.
+        const RELATIVE_SELECTOR_SEARCH_DIRECTION_ANCESTOR_SIBLING = 1 << 7;
     }
 }
 
 impl ElementSelectorFlags {
     /// Returns the subset of flags that apply to the element.
     pub fn for_self(self) -> ElementSelectorFlags {
-        self & (ElementSelectorFlags::HAS_EMPTY_SELECTOR)
+        self & (ElementSelectorFlags::HAS_EMPTY_SELECTOR |
+            ElementSelectorFlags::for_relative_selectors())
+    }
+
+    /// Returns the flags that relate to `:has()` matching.
+    pub fn for_relative_selectors() -> ElementSelectorFlags {
+        ElementSelectorFlags::ANCHORS_RELATIVE_SELECTOR |
+            ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_ANCESTOR |
+            ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_SIBLING |
+            ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_ANCESTOR_SIBLING
     }
 
     /// Returns the subset of flags that apply to the parent.
@@ -377,6 +404,9 @@ fn matches_hover_and_active_quirk<Impl: SelectorImpl>(
         Component::Class(_) |
         Component::PseudoElement(_) |
         Component::Negation(_) |
+        Component::Is(_) |
+        Component::Where(_) |
+        Component::Has(_) |
         Component::FirstChild |
         Component::LastChild |
         Component::OnlyChild |
@@ -822,7 +852,8 @@ where
                     })
                 })
         },
-        Component::Scope => match context.shared.scope_element {
+        // Outside of nested rules, `&` behaves like `:scope`.
+        Component::Scope | Component::ParentSelector => match context.shared.scope_element {
             Some(ref scope_element) => element.opaque() == *scope_element,
             None => element.is_root(),
         },
@@ -857,6 +888,177 @@ where
                 .iter()
                 .all(|ss| matches_simple_selector(ss, element, &mut local_context, flags_setter))
         }),
+        Component::Is(ref list) | Component::Where(ref list) => context.shared.nest(|context| {
+            list.iter().any(|selector| {
+                matches_complex_selector(selector.iter(), element, context, flags_setter)
+            })
+        }),
+        Component::Has(ref list) => matches_has(list, element, context.shared, flags_setter),
+        Component::RelativeSelectorAnchor => {
+            context.shared.relative_selector_anchor() == Some(element.opaque())
+        },
+    }
+}
+
+/// Matches a `:has()` selector against `anchor`, that is, looks for an
+/// element matching any of the relative selectors in the list.
+///
+/// Every element we look at gets flagged with the direction in which the
+/// anchor is, so that mutations of it can invalidate the anchor's style.
+fn matches_has<E, F>(
+    list: &[RelativeSelector<E::Impl>],
+    anchor: &E,
+    context: &mut MatchingContext<E::Impl>,
+    flags_setter: &mut F,
+) -> bool
+where
+    E: Element,
+    F: FnMut(&E, ElementSelectorFlags),
+{
+    flags_setter(anchor, ElementSelectorFlags::ANCHORS_RELATIVE_SELECTOR);
+    context.nest_for_relative_selector(anchor.opaque(), |context| {
+        list.iter()
+            .any(|relative| matches_relative_selector(relative, anchor, context, flags_setter))
+    })
+}
+
+fn matches_relative_selector<E, F>(
+    relative: &RelativeSelector<E::Impl>,
+    anchor: &E,
+    context: &mut MatchingContext<E::Impl>,
+    flags_setter: &mut F,
+) -> bool
+where
+    E: Element,
+    F: FnMut(&E, ElementSelectorFlags),
+{
+    let selector = &relative.selector;
+    match relative.match_hint {
+        RelativeSelectorMatchHint::InChild => {
+            let mut next = anchor.first_element_child();
+            while let Some(child) = next {
+                flags_setter(
+                    &child,
+                    ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_ANCESTOR,
+                );
+                if matches_complex_selector(selector.iter(), &child, context, flags_setter) {
+                    return true;
+                }
+                next = child.next_sibling_element();
+            }
+            false
+        },
+        RelativeSelectorMatchHint::InSubtree => matches_relative_selector_in_subtree(
+            selector,
+            anchor,
+            context,
+            flags_setter,
+            ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_ANCESTOR,
+        ),
+        hint => {
+            let next_sibling_only = matches!(
+                hint,
+                RelativeSelectorMatchHint::InNextSibling |
+                    RelativeSelectorMatchHint::InNextSiblingSubtree
+            );
+            let mut next = anchor.next_sibling_element();
+            while let Some(sibling) = next {
+                flags_setter(
+                    &sibling,
+                    ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_SIBLING,
+                );
+                let matched = if hint.is_subtree() {
+                    matches_relative_selector_in_subtree(
+                        selector,
+                        &sibling,
+                        context,
+                        flags_setter,
+                        ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_ANCESTOR_SIBLING,
+                    )
+                } else {
+                    matches_complex_selector(selector.iter(), &sibling, context, flags_setter)
+                };
+                if matched {
+                    return true;
+                }
+                if next_sibling_only {
+                    break;
+                }
+                next = sibling.next_sibling_element();
+            }
+            false
+        },
+    }
+}
+
+/// Looks for a descendant of `root` matching a relative selector, in tree
+/// order.
+///
+/// Descendant combinators in relative selectors can make this pretty
+/// expensive, so we keep a bloom filter of the ancestors of the element we're
+/// looking at (up to and including `root`) to fast-reject the selector, like
+/// we do during the style traversal.
+fn matches_relative_selector_in_subtree<E, F>(
+    selector: &Selector<E::Impl>,
+    root: &E,
+    context: &mut MatchingContext<E::Impl>,
+    flags_setter: &mut F,
+    search_flag: ElementSelectorFlags,
+) -> bool
+where
+    E: Element,
+    F: FnMut(&E, ElementSelectorFlags),
+{
+    let mut next = match root.first_element_child() {
+        Some(child) => child,
+        None => return false,
+    };
+
+    let hashes = AncestorHashes::new(selector, context.quirks_mode());
+    let mut bloom_filter = None;
+    if hashes.packed_hashes[0] != 0 {
+        let mut filter = Box::new(BloomFilter::new());
+        if root.each_bloom_hash(|hash| filter.insert_hash(hash)) {
+            bloom_filter = Some(filter);
+        }
+    }
+
+    let root = root.opaque();
+    loop {
+        let element = next;
+        flags_setter(&element, search_flag);
+        let may_match_element = bloom_filter
+            .as_ref()
+            .map_or(true, |filter| may_match(&hashes, filter));
+        if may_match_element &&
+            matches_complex_selector(selector.iter(), &element, context, flags_setter)
+        {
+            return true;
+        }
+
+        // Move to the next element in tree order, keeping the bloom filter
+        // up-to-date.
+        if let Some(child) = element.first_element_child() {
+            if let Some(ref mut filter) = bloom_filter {
+                element.each_bloom_hash(|hash| filter.insert_hash(hash));
+            }
+            next = child;
+            continue;
+        }
+
+        let mut current = element;
+        next = loop {
+            if let Some(sibling) = current.next_sibling_element() {
+                break sibling;
+            }
+            current = match current.parent_element() {
+                Some(parent) if parent.opaque() != root => parent,
+                _ => return false,
+            };
+            if let Some(ref mut filter) = bloom_filter {
+                current.each_bloom_hash(|hash| filter.remove_hash(hash));
+            }
+        };
     }
 }
 
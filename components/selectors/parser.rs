@@ -94,6 +94,13 @@ bitflags! {
         /// disallowed. If this flag is set, `AFTER_PSEUDO_ELEMENT` must be set
         /// as well.
         const AFTER_NON_STATEFUL_PSEUDO_ELEMENT = 1 << 4;
+        /// Whether we're inside a selector list argument, like the one of
+        /// `:is()`, `:where()` or `:has()`, where pseudo-elements are not
+        /// allowed.
+        const DISALLOW_PSEUDOS = 1 << 5;
+        /// Whether we're inside `:has()`, in which case relative selectors
+        /// can't be nested.
+        const DISALLOW_RELATIVE_SELECTOR = 1 << 6;
         /// Whether we are after any of the pseudo-like things.
         const AFTER_PSEUDO = Self::AFTER_PART.bits | Self::AFTER_SLOTTED.bits | Self::AFTER_PSEUDO_ELEMENT.bits;
     }
@@ -130,6 +137,23 @@ impl SelectorParsingState {
     fn allows_tree_structural_pseudo_classes(self) -> bool {
         !self.intersects(SelectorParsingState::AFTER_PSEUDO)
     }
+
+    #[inline]
+    fn allows_pseudos(self) -> bool {
+        !self.intersects(SelectorParsingState::DISALLOW_PSEUDOS)
+    }
+
+    #[inline]
+    fn allows_relative_selectors(self) -> bool {
+        !self.intersects(SelectorParsingState::DISALLOW_RELATIVE_SELECTOR)
+    }
+
+    /// The state that selectors nested in a selector list argument inherit.
+    #[inline]
+    fn for_nested_selector_list(self) -> Self {
+        (self & SelectorParsingState::DISALLOW_RELATIVE_SELECTOR) |
+            SelectorParsingState::DISALLOW_PSEUDOS
+    }
 }
 
 pub type SelectorParseError<'i> = ParseError<'i, SelectorParseErrorKind<'i>>;
@@ -175,11 +199,11 @@ macro_rules! with_all_bounds {
         pub trait SelectorImpl: Clone + Debug + Sized + 'static {
             type ExtraMatchingData: Sized + Default + 'static;
             type AttrValue: $($InSelector)*;
-            type Identifier: $($InSelector)*;
-            type ClassName: $($InSelector)*;
+            type Identifier: $($InSelector)* + PrecomputedHash;
+            type ClassName: $($InSelector)* + PrecomputedHash;
             type PartName: $($InSelector)*;
-            type LocalName: $($InSelector)* + Borrow<Self::BorrowedLocalName>;
-            type NamespaceUrl: $($CommonBounds)* + Default + Borrow<Self::BorrowedNamespaceUrl>;
+            type LocalName: $($InSelector)* + Borrow<Self::BorrowedLocalName> + PrecomputedHash;
+            type NamespaceUrl: $($CommonBounds)* + Default + Borrow<Self::BorrowedNamespaceUrl> + PrecomputedHash;
             type NamespacePrefix: $($InSelector)* + Default;
             type BorrowedNamespaceUrl: ?Sized + Eq;
             type BorrowedLocalName: ?Sized + Eq;
@@ -228,6 +252,21 @@ pub trait Parser<'i> {
         false
     }
 
+    /// Whether to parse the `:is()` and `:where()` pseudo-classes.
+    fn parse_is_and_where(&self) -> bool {
+        false
+    }
+
+    /// Whether to parse the `:has()` pseudo-class.
+    fn parse_has(&self) -> bool {
+        false
+    }
+
+    /// Whether to parse the nesting selector `&`.
+    fn parse_parent_selector(&self) -> bool {
+        false
+    }
+
     /// This function can return an "Err" pseudo-element in order to support CSS2.1
     /// pseudo-elements.
     fn parse_non_ts_pseudo_class(
@@ -310,10 +349,37 @@ impl<Impl: SelectorImpl> SelectorList<Impl> {
     {
         let mut values = SmallVec::new();
         loop {
-            values.push(
-                input
-                    .parse_until_before(Delimiter::Comma, |input| parse_selector(parser, input))?,
-            );
+            values.push(input.parse_until_before(Delimiter::Comma, |input| {
+                parse_selector(parser, input, SelectorParsingState::empty())
+            })?);
+            match input.next() {
+                Err(_) => return Ok(SelectorList(values)),
+                Ok(&Token::Comma) => continue,
+                Ok(_) => unreachable!(),
+            }
+        }
+    }
+
+    /// Parse a comma-separated list of selectors of a rule nested in a style
+    /// rule, which are relative to the selectors of the parent rule.
+    ///
+    /// A selector that doesn't contain the parent selector `&`, or starts
+    /// with a combinator, is made relative to it by prepending an implicit
+    /// `&` (and a descendant combinator if needed).
+    ///
+    /// <https://drafts.csswg.org/css-nesting/#syntax>
+    pub fn parse_for_nesting<'i, 't, P>(
+        parser: &P,
+        input: &mut CssParser<'i, 't>,
+    ) -> Result<Self, ParseError<'i, P::Error>>
+    where
+        P: Parser<'i, Impl = Impl>,
+    {
+        let mut values = SmallVec::new();
+        loop {
+            values.push(input.parse_until_before(Delimiter::Comma, |input| {
+                parse_nested_selector(parser, input, SelectorParsingState::empty())
+            })?);
             match input.next() {
                 Err(_) => return Ok(SelectorList(values)),
                 Ok(&Token::Comma) => continue,
@@ -322,6 +388,14 @@ impl<Impl: SelectorImpl> SelectorList<Impl> {
         }
     }
 
+    /// Returns a list with the parent selector `&` as its only selector.
+    pub fn parent_selector() -> Self {
+        let mut builder = SelectorBuilder::default();
+        builder.push_simple_selector(Component::ParentSelector);
+        let selector = Selector(builder.build(false, false, false));
+        SelectorList(SmallVec::from_vec(vec![selector]))
+    }
+
     /// Creates a SelectorList from a Vec of selectors. Used in tests.
     pub fn from_vec(v: Vec<Selector<Impl>>) -> Self {
         SelectorList(SmallVec::from_vec(v))
@@ -338,7 +412,7 @@ where
     Impl: SelectorImpl,
 {
     let location = input.current_source_location();
-    let selector = parse_selector(parser, input)?;
+    let selector = parse_selector(parser, input, SelectorParsingState::empty())?;
 
     // Ensure they're actually all compound selectors without pseudo-elements.
     if selector.has_pseudo_element() {
@@ -504,6 +578,16 @@ where
                     }
                 }
             },
+            Is(ref list) | Where(ref list) => {
+                if !visitor.visit_selector_list(list) {
+                    return false;
+                }
+            },
+            Has(ref list) => {
+                if !visitor.visit_relative_selector_list(list) {
+                    return false;
+                }
+            },
 
             AttributeInNoNamespaceExists {
                 ref local_name,
@@ -778,6 +862,51 @@ impl<Impl: SelectorImpl> Selector<Impl> {
     pub fn thin_arc_heap_ptr(&self) -> *const ::std::os::raw::c_void {
         self.0.heap_ptr()
     }
+
+    /// Whether this selector contains the parent selector `&`, including in
+    /// the arguments of its pseudo-classes.
+    #[inline]
+    pub fn has_parent_selector(&self) -> bool {
+        self.0.header.header.has_parent_selector()
+    }
+
+    /// Returns a copy of this selector where every occurrence of the parent
+    /// selector `&` is replaced by `:is()` of the given parent selectors, and
+    /// with its specificity recomputed accordingly.
+    ///
+    /// https://drafts.csswg.org/css-nesting/#nest-selector
+    pub fn replace_parent_selector(&self, parent: &[Selector<Impl>]) -> Self {
+        if !self.has_parent_selector() {
+            return self.clone();
+        }
+
+        // Collect the compound selectors, with the combinator to their left,
+        // so that we can feed them to the builder in parse order.
+        let mut compounds =
+            SmallVec::<[(SmallVec<[Component<Impl>; 8]>, Option<Combinator>); 4]>::new();
+        let mut iter = self.iter();
+        loop {
+            let compound = (&mut iter)
+                .map(|c| c.replace_parent_selector(parent))
+                .collect();
+            let combinator = iter.next_sequence();
+            compounds.push((compound, combinator));
+            if combinator.is_none() {
+                break;
+            }
+        }
+
+        let mut builder = SelectorBuilder::default();
+        for (compound, combinator) in compounds.into_iter().rev() {
+            if let Some(combinator) = combinator {
+                builder.push_combinator(combinator);
+            }
+            for component in compound {
+                builder.push_simple_selector(component);
+            }
+        }
+        Selector(builder.build(self.has_pseudo_element(), self.is_slotted(), self.is_part()))
+    }
 }
 
 #[derive(Clone)]
@@ -934,6 +1063,90 @@ impl Combinator {
     }
 }
 
+/// A hint about where the elements that may match a relative selector can be
+/// found, relative to the anchor element, computed from its combinators.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ToShmem)]
+pub enum RelativeSelectorMatchHint {
+    /// Only the children of the anchor, e.g. `:has(> .foo)`.
+    InChild,
+    /// Any descendant of the anchor, e.g. `:has(.foo)` or `:has(> .foo .bar)`.
+    InSubtree,
+    /// Only the next sibling of the anchor, e.g. `:has(+ .foo)`.
+    InNextSibling,
+    /// Any descendant of the next sibling of the anchor, e.g.
+    /// `:has(+ .foo .bar)`.
+    InNextSiblingSubtree,
+    /// Any later sibling of the anchor, e.g. `:has(~ .foo)`.
+    InSibling,
+    /// Any descendant of any later sibling of the anchor, e.g.
+    /// `:has(~ .foo .bar)`.
+    InSiblingSubtree,
+}
+
+impl RelativeSelectorMatchHint {
+    /// Computes the hint for a relative selector, whose leftmost compound is
+    /// the implicit anchor.
+    fn new<Impl: SelectorImpl>(selector: &Selector<Impl>) -> Self {
+        let mut combinators = selector
+            .iter_raw_match_order()
+            .rev()
+            .filter_map(|s| s.as_combinator());
+        let leading = combinators
+            .next()
+            .expect("Relative selectors should have a leading combinator");
+        let mut has_more_combinators = false;
+        let mut has_sibling = false;
+        let mut has_ancestor = false;
+        for combinator in combinators {
+            has_more_combinators = true;
+            if combinator.is_sibling() {
+                has_sibling = true;
+            } else {
+                has_ancestor = true;
+            }
+        }
+
+        match leading {
+            Combinator::Child if !has_more_combinators => RelativeSelectorMatchHint::InChild,
+            Combinator::NextSibling | Combinator::LaterSibling => {
+                let next_sibling_only = leading == Combinator::NextSibling && !has_sibling;
+                match (next_sibling_only, has_ancestor) {
+                    (true, false) => RelativeSelectorMatchHint::InNextSibling,
+                    (true, true) => RelativeSelectorMatchHint::InNextSiblingSubtree,
+                    (false, false) => RelativeSelectorMatchHint::InSibling,
+                    (false, true) => RelativeSelectorMatchHint::InSiblingSubtree,
+                }
+            },
+            _ => RelativeSelectorMatchHint::InSubtree,
+        }
+    }
+
+    /// Whether the elements that may match are descendants of the anchor, or
+    /// of one of its siblings, as opposed to siblings or children.
+    #[inline]
+    pub fn is_subtree(&self) -> bool {
+        matches!(
+            *self,
+            RelativeSelectorMatchHint::InSubtree |
+                RelativeSelectorMatchHint::InNextSiblingSubtree |
+                RelativeSelectorMatchHint::InSiblingSubtree
+        )
+    }
+}
+
+/// A relative selector, as found in the argument of `:has()`.
+///
+/// https://drafts.csswg.org/selectors-4/#relative
+#[derive(Clone, Debug, Eq, PartialEq, ToShmem)]
+#[shmem(no_bounds)]
+pub struct RelativeSelector<Impl: SelectorImpl> {
+    /// Where to look for elements that may match this selector.
+    pub match_hint: RelativeSelectorMatchHint,
+    /// The selector itself, whose leftmost compound selector is a
+    /// `Component::RelativeSelectorAnchor`.
+    pub selector: Selector<Impl>,
+}
+
 /// A CSS simple selector or combinator. We store both in the same enum for
 /// optimal packing and cache performance, see [1].
 ///
@@ -1024,6 +1237,36 @@ pub enum Component<Impl: SelectorImpl> {
     ///
     /// See https://github.com/w3c/csswg-drafts/issues/2158
     Host(Option<Selector<Impl>>),
+    /// The `:is()` pseudo-class, which matches if any of the selectors in its
+    /// (forgiving) argument list matches:
+    ///
+    /// https://drafts.csswg.org/selectors-4/#matches
+    ///
+    /// Its specificity is the one of its most specific argument.
+    Is(Box<[Selector<Impl>]>),
+    /// The `:where()` pseudo-class, which behaves like `:is()`, but has zero
+    /// specificity:
+    ///
+    /// https://drafts.csswg.org/selectors-4/#zero-matches
+    Where(Box<[Selector<Impl>]>),
+    /// The `:has()` relational pseudo-class, which matches if any of its
+    /// relative selectors matches when anchored at the element:
+    ///
+    /// https://drafts.csswg.org/selectors-4/#relational
+    Has(Box<[RelativeSelector<Impl>]>),
+    /// The implicit anchor of a relative selector, which only appears as the
+    /// leftmost compound selector of the selectors inside `:has()`, and
+    /// matches the element `:has()` is being matched against.
+    RelativeSelectorAnchor,
+    /// The nesting selector `&`, which represents the elements matched by
+    /// the parent rule:
+    ///
+    /// https://drafts.csswg.org/css-nesting/#nest-selector
+    ///
+    /// Nested rules get it replaced by `:is()` of the parent selectors when
+    /// added to the stylist, see `Selector::replace_parent_selector`. Outside
+    /// of a nested rule it is equivalent to `:scope`.
+    ParentSelector,
     PseudoElement(#[shmem(field_bound)] Impl::PseudoElement),
 }
 
@@ -1077,6 +1320,61 @@ impl<Impl: SelectorImpl> Component<Impl> {
             _ => None,
         }
     }
+
+    /// Returns whether this simple selector is, or contains, the parent
+    /// selector `&`.
+    pub(crate) fn has_parent_selector(&self) -> bool {
+        match *self {
+            Component::ParentSelector => true,
+            Component::Negation(ref list) => list.iter().any(|c| c.has_parent_selector()),
+            Component::Is(ref list) | Component::Where(ref list) => {
+                list.iter().any(|s| s.has_parent_selector())
+            },
+            Component::Has(ref list) => list.iter().any(|r| r.selector.has_parent_selector()),
+            _ => false,
+        }
+    }
+
+    /// Returns a copy of this simple selector where the parent selector `&`
+    /// is replaced by `:is()` of the given selectors.
+    fn replace_parent_selector(&self, parent: &[Selector<Impl>]) -> Self {
+        match *self {
+            Component::ParentSelector => Component::Is(
+                parent
+                    .iter()
+                    // `&` can't represent pseudo-elements.
+                    .filter(|s| !s.has_pseudo_element())
+                    .cloned()
+                    .collect(),
+            ),
+            Component::Negation(ref list) => Component::Negation(
+                list.iter()
+                    .map(|c| c.replace_parent_selector(parent))
+                    .collect::<Vec<_>>()
+                    .into_boxed_slice()
+                    .into(),
+            ),
+            Component::Is(ref list) => Component::Is(
+                list.iter()
+                    .map(|s| s.replace_parent_selector(parent))
+                    .collect(),
+            ),
+            Component::Where(ref list) => Component::Where(
+                list.iter()
+                    .map(|s| s.replace_parent_selector(parent))
+                    .collect(),
+            ),
+            Component::Has(ref list) => Component::Has(
+                list.iter()
+                    .map(|r| RelativeSelector {
+                        match_hint: r.match_hint,
+                        selector: r.selector.replace_parent_selector(parent),
+                    })
+                    .collect(),
+            ),
+            ref other => other.clone(),
+        }
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, ToShmem)]
@@ -1398,11 +1696,57 @@ impl<Impl: SelectorImpl> ToCss for Component<Impl> {
                 write_affine(dest, a, b)?;
                 dest.write_char(')')
             },
+            Is(ref list) | Where(ref list) => {
+                match *self {
+                    Is(..) => dest.write_str(":is(")?,
+                    Where(..) => dest.write_str(":where(")?,
+                    _ => unreachable!(),
+                }
+                serialize_selector_list(list.iter(), dest)?;
+                dest.write_char(')')
+            },
+            Has(ref list) => {
+                dest.write_str(":has(")?;
+                serialize_selector_list(list.iter(), dest)?;
+                dest.write_char(')')
+            },
+            RelativeSelectorAnchor => Ok(()),
+            ParentSelector => dest.write_char('&'),
             NonTSPseudoClass(ref pseudo) => pseudo.to_css(dest),
         }
     }
 }
 
+impl<Impl: SelectorImpl> ToCss for RelativeSelector<Impl> {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        // The anchor serializes as the empty string, so we only need to get
+        // rid of the whitespace before the leading combinator, if any.
+        let mut selector = String::new();
+        self.selector.to_css(&mut selector)?;
+        dest.write_str(selector.trim_start())
+    }
+}
+
+fn serialize_selector_list<'a, I, T, W>(iter: I, dest: &mut W) -> fmt::Result
+where
+    I: Iterator<Item = &'a T>,
+    T: ToCss + 'a,
+    W: fmt::Write,
+{
+    let mut first = true;
+    for item in iter {
+        if !first {
+            dest.write_str(", ")?;
+        }
+        first = false;
+        item.to_css(dest)?;
+    }
+    Ok(())
+}
+
 impl<Impl: SelectorImpl> ToCss for AttrSelectorWithOptionalNamespace<Impl> {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result
     where
@@ -1476,19 +1820,33 @@ fn display_to_css_identifier<T: Display, W: fmt::Write>(x: &T, dest: &mut W) ->
 fn parse_selector<'i, 't, P, Impl>(
     parser: &P,
     input: &mut CssParser<'i, 't>,
+    state: SelectorParsingState,
 ) -> Result<Selector<Impl>, ParseError<'i, P::Error>>
 where
     P: Parser<'i, Impl = Impl>,
     Impl: SelectorImpl,
 {
-    let mut builder = SelectorBuilder::default();
+    parse_selector_with_builder(parser, input, state, SelectorBuilder::default())
+}
 
+/// Like `parse_selector`, but appends to a builder that may already contain
+/// some components, like the implicit anchor of a relative selector.
+fn parse_selector_with_builder<'i, 't, P, Impl>(
+    parser: &P,
+    input: &mut CssParser<'i, 't>,
+    state: SelectorParsingState,
+    mut builder: SelectorBuilder<Impl>,
+) -> Result<Selector<Impl>, ParseError<'i, P::Error>>
+where
+    P: Parser<'i, Impl = Impl>,
+    Impl: SelectorImpl,
+{
     let mut has_pseudo_element = false;
     let mut slotted = false;
     let mut part = false;
     'outer_loop: loop {
         // Parse a sequence of simple selectors.
-        let state = match parse_compound_selector(parser, input, &mut builder)? {
+        let state = match parse_compound_selector(parser, input, state, &mut builder)? {
             Some(state) => state,
             None => {
                 return Err(input.new_custom_error(if builder.has_combinators() {
@@ -1544,6 +1902,136 @@ where
     Ok(Selector(builder.build(has_pseudo_element, slotted, part)))
 }
 
+/// Parses a forgiving selector list, like the argument of `:is()` and
+/// `:where()`, where invalid selectors are dropped instead of invalidating the
+/// whole list.
+///
+/// <https://drafts.csswg.org/selectors-4/#typedef-forgiving-selector-list>
+fn parse_forgiving_selector_list<'i, 't, P, Impl>(
+    parser: &P,
+    input: &mut CssParser<'i, 't>,
+    state: SelectorParsingState,
+) -> Result<Box<[Selector<Impl>]>, ParseError<'i, P::Error>>
+where
+    P: Parser<'i, Impl = Impl>,
+    Impl: SelectorImpl,
+{
+    let state = state.for_nested_selector_list();
+    let mut values = vec![];
+    loop {
+        let selector = input.parse_until_before(Delimiter::Comma, |input| {
+            let result = input.parse_entirely(|input| parse_selector(parser, input, state));
+            if result.is_err() {
+                // Skip the rest of the invalid selector.
+                while input.next().is_ok() {}
+            }
+            Ok::<_, ParseError<'i, P::Error>>(result.ok())
+        })?;
+        values.extend(selector);
+        match input.next() {
+            Err(_) => return Ok(values.into_boxed_slice()),
+            Ok(&Token::Comma) => continue,
+            Ok(_) => unreachable!(),
+        }
+    }
+}
+
+/// Parses a list of relative selectors, like the argument of `:has()`.
+///
+/// <https://drafts.csswg.org/selectors-4/#typedef-relative-selector-list>
+fn parse_relative_selector_list<'i, 't, P, Impl>(
+    parser: &P,
+    input: &mut CssParser<'i, 't>,
+    state: SelectorParsingState,
+) -> Result<Box<[RelativeSelector<Impl>]>, ParseError<'i, P::Error>>
+where
+    P: Parser<'i, Impl = Impl>,
+    Impl: SelectorImpl,
+{
+    let state = state.for_nested_selector_list() | SelectorParsingState::DISALLOW_RELATIVE_SELECTOR;
+    input
+        .parse_comma_separated(|input| parse_relative_selector(parser, input, state))
+        .map(|selectors| selectors.into_boxed_slice())
+}
+
+/// Parses a relative selector, that is, a selector that may start with a
+/// combinator, and which is matched relative to an anchor element.
+///
+/// The anchor is represented as an implicit leftmost compound selector, so
+/// that the rest of the selector can be matched as usual.
+fn parse_relative_selector<'i, 't, P, Impl>(
+    parser: &P,
+    input: &mut CssParser<'i, 't>,
+    state: SelectorParsingState,
+) -> Result<RelativeSelector<Impl>, ParseError<'i, P::Error>>
+where
+    P: Parser<'i, Impl = Impl>,
+    Impl: SelectorImpl,
+{
+    let mut builder = SelectorBuilder::default();
+    builder.push_simple_selector(Component::RelativeSelectorAnchor);
+
+    input.skip_whitespace();
+    let before_combinator = input.state();
+    let combinator = match input.next() {
+        Ok(&Token::Delim('>')) => Combinator::Child,
+        Ok(&Token::Delim('+')) => Combinator::NextSibling,
+        Ok(&Token::Delim('~')) => Combinator::LaterSibling,
+        _ => {
+            input.reset(&before_combinator);
+            Combinator::Descendant
+        },
+    };
+    builder.push_combinator(combinator);
+
+    let selector = parse_selector_with_builder(parser, input, state, builder)?;
+    let match_hint = RelativeSelectorMatchHint::new(&selector);
+    Ok(RelativeSelector {
+        match_hint,
+        selector,
+    })
+}
+
+/// Parses a selector of a nested style rule, making it relative to the parent
+/// selector `&` if it doesn't contain it already.
+///
+/// <https://drafts.csswg.org/css-nesting/#syntax>
+fn parse_nested_selector<'i, 't, P, Impl>(
+    parser: &P,
+    input: &mut CssParser<'i, 't>,
+    state: SelectorParsingState,
+) -> Result<Selector<Impl>, ParseError<'i, P::Error>>
+where
+    P: Parser<'i, Impl = Impl>,
+    Impl: SelectorImpl,
+{
+    input.skip_whitespace();
+    let start = input.state();
+    let leading_combinator = match input.next() {
+        Ok(&Token::Delim('>')) => Some(Combinator::Child),
+        Ok(&Token::Delim('+')) => Some(Combinator::NextSibling),
+        Ok(&Token::Delim('~')) => Some(Combinator::LaterSibling),
+        _ => {
+            input.reset(&start);
+            None
+        },
+    };
+
+    if leading_combinator.is_none() {
+        let selector = parse_selector(parser, input, state)?;
+        if selector.has_parent_selector() {
+            return Ok(selector);
+        }
+        // Parse it again, this time relative to the implicit `&`.
+        input.reset(&start);
+    }
+
+    let mut builder = SelectorBuilder::default();
+    builder.push_simple_selector(Component::ParentSelector);
+    builder.push_combinator(leading_combinator.unwrap_or(Combinator::Descendant));
+    parse_selector_with_builder(parser, input, state, builder)
+}
+
 impl<Impl: SelectorImpl> Selector<Impl> {
     /// Parse a selector, without any pseudo-element.
     #[inline]
@@ -1554,7 +2042,7 @@ impl<Impl: SelectorImpl> Selector<Impl> {
     where
         P: Parser<'i, Impl = Impl>,
     {
-        parse_selector(parser, input)
+        parse_selector(parser, input, SelectorParsingState::empty())
     }
 }
 
@@ -1949,6 +2437,7 @@ fn parse_attribute_flags<'i, 't>(
 fn parse_negation<'i, 't, P, Impl>(
     parser: &P,
     input: &mut CssParser<'i, 't>,
+    state: SelectorParsingState,
 ) -> Result<Component<Impl>, ParseError<'i, P::Error>>
 where
     P: Parser<'i, Impl = Impl>,
@@ -1970,7 +2459,8 @@ where
         Err(e) => return Err(e.into()),
     };
     if !is_type_sel {
-        match parse_one_simple_selector(parser, input, SelectorParsingState::INSIDE_NEGATION)? {
+        let state = state | SelectorParsingState::INSIDE_NEGATION;
+        match parse_one_simple_selector(parser, input, state)? {
             Some(SimpleSelectorParseResult::SimpleSelector(s)) => {
                 sequence.push(s);
             },
@@ -2001,6 +2491,7 @@ where
 fn parse_compound_selector<'i, 't, P, Impl>(
     parser: &P,
     input: &mut CssParser<'i, 't>,
+    mut state: SelectorParsingState,
     builder: &mut SelectorBuilder<Impl>,
 ) -> Result<Option<SelectorParsingState>, ParseError<'i, P::Error>>
 where
@@ -2021,7 +2512,6 @@ where
         empty = false;
     }
 
-    let mut state = SelectorParsingState::empty();
     loop {
         let parse_result = match parse_one_simple_selector(parser, input, state)? {
             None => break,
@@ -2088,8 +2578,27 @@ where
                     SelectorParseErrorKind::UnexpectedIdent("not".into())
                 ));
             }
-            debug_assert!(state.is_empty());
-            return parse_negation(parser, input)
+            return parse_negation(parser, input, state)
+        },
+        "is" => {
+            if parser.parse_is_and_where() {
+                return Ok(Component::Is(parse_forgiving_selector_list(parser, input, state)?))
+            }
+        },
+        "where" => {
+            if parser.parse_is_and_where() {
+                return Ok(Component::Where(parse_forgiving_selector_list(parser, input, state)?))
+            }
+        },
+        "has" => {
+            if parser.parse_has() {
+                if !state.allows_relative_selectors() {
+                    return Err(input.new_custom_error(
+                        SelectorParseErrorKind::UnexpectedIdent("has".into())
+                    ));
+                }
+                return Ok(Component::Has(parse_relative_selector_list(parser, input, state)?))
+            }
         },
         _ => {}
     }
@@ -2143,6 +2652,12 @@ where
     };
 
     Ok(Some(match token {
+        Token::Delim('&') if parser.parse_parent_selector() => {
+            if state.intersects(SelectorParsingState::AFTER_PSEUDO) {
+                return Err(input.new_custom_error(SelectorParseErrorKind::InvalidState));
+            }
+            SimpleSelectorParseResult::SimpleSelector(Component::ParentSelector)
+        },
         Token::IDHash(id) => {
             if state.intersects(SelectorParsingState::AFTER_PSEUDO) {
                 return Err(input.new_custom_error(SelectorParseErrorKind::InvalidState));
@@ -2188,7 +2703,9 @@ where
             };
             let is_pseudo_element = !is_single_colon || is_css2_pseudo_element(&name);
             if is_pseudo_element {
-                if state.intersects(SelectorParsingState::AFTER_PSEUDO_ELEMENT) {
+                if !state.allows_pseudos() ||
+                    state.intersects(SelectorParsingState::AFTER_PSEUDO_ELEMENT)
+                {
                     return Err(input.new_custom_error(SelectorParseErrorKind::InvalidState));
                 }
                 let pseudo_element = if is_functional {
@@ -2432,6 +2949,17 @@ pub mod tests {
         }
     }
 
+    impl PrecomputedHash for DummyAtom {
+        fn precomputed_hash(&self) -> u32 {
+            use std::collections::hash_map::DefaultHasher;
+            use std::hash::{Hash, Hasher};
+
+            let mut hasher = DefaultHasher::new();
+            self.0.hash(&mut hasher);
+            hasher.finish() as u32
+        }
+    }
+
     impl<'i> Parser<'i> for DummyParser {
         type Impl = DummySelectorImpl;
         type Error = SelectorParseErrorKind<'i>;
@@ -2444,6 +2972,18 @@ pub mod tests {
             true
         }
 
+        fn parse_is_and_where(&self) -> bool {
+            true
+        }
+
+        fn parse_has(&self) -> bool {
+            true
+        }
+
+        fn parse_parent_selector(&self) -> bool {
+            true
+        }
+
         fn parse_non_ts_pseudo_class(
             &self,
             location: SourceLocation,
@@ -3116,6 +3656,140 @@ pub mod tests {
         assert_eq!(iter.next_sequence(), None);
     }
 
+    #[test]
+    fn test_is_and_where() {
+        assert!(parse(":is(.foo, #bar)").is_ok());
+        assert!(parse(":where(.foo > .bar, baz)").is_ok());
+        assert!(parse(":is(:not(.foo))").is_ok());
+        assert!(parse(":not(:where(.foo))").is_ok());
+        assert!(parse(":is(:is(.foo), :where(.bar))").is_ok());
+        assert!(parse(":is()").is_ok());
+        assert!(parse(":is(.foo):where(.bar)::before").is_ok());
+
+        // Invalid selectors are dropped from the list, rather than making the
+        // whole selector invalid.
+        assert!(parse_expected(":is(.foo, ::before, 4, .bar)", Some(":is(.foo, .bar)")).is_ok());
+        assert!(parse_expected(":where(::before)", Some(":where()")).is_ok());
+        assert!(parse_expected(":is(.foo, .bar !)", Some(":is(.foo)")).is_ok());
+
+        assert_eq!(
+            parse(":is(.foo, #bar) baz").unwrap().0[0].specificity(),
+            specificity(1, 0, 1)
+        );
+        assert_eq!(
+            parse(":is(.foo, .bar .baz)").unwrap().0[0].specificity(),
+            specificity(0, 2, 0)
+        );
+        assert_eq!(
+            parse(":where(.foo, #bar) baz").unwrap().0[0].specificity(),
+            specificity(0, 0, 1)
+        );
+        assert_eq!(parse(":is()").unwrap().0[0].specificity(), 0);
+    }
+
+    #[test]
+    fn test_has() {
+        assert!(parse(":has(.foo)").is_ok());
+        assert!(parse(":has(> .foo, + .bar, ~ #baz .qux)").is_ok());
+        assert!(parse(":has(:is(.foo))").is_ok());
+        assert!(parse(":is(:has(.foo))").is_ok());
+        assert!(parse(":not(:has(.foo))").is_ok());
+        assert!(parse_expected(":has(>.foo)", Some(":has(> .foo)")).is_ok());
+
+        assert!(parse(":has()").is_err());
+        assert!(parse(":has(>)").is_err());
+        assert!(parse(":has(.foo,)").is_err());
+        assert!(parse(":has(.foo, 4)").is_err());
+        assert!(parse(":has(::before)").is_err());
+        assert!(parse(":has(:has(.foo))").is_err());
+        assert!(parse(":has(:is(:has(.foo)))").is_err());
+
+        assert_eq!(
+            parse(":has(> .foo, #bar)").unwrap().0[0].specificity(),
+            specificity(1, 0, 0)
+        );
+
+        let hint = |input| match parse(input).unwrap().0[0].iter().next() {
+            Some(&Component::Has(ref list)) => list[0].match_hint,
+            _ => unreachable!(),
+        };
+        assert_eq!(hint(":has(> .foo)"), RelativeSelectorMatchHint::InChild);
+        assert_eq!(hint(":has(.foo)"), RelativeSelectorMatchHint::InSubtree);
+        assert_eq!(
+            hint(":has(> .foo + .bar)"),
+            RelativeSelectorMatchHint::InSubtree
+        );
+        assert_eq!(
+            hint(":has(+ .foo)"),
+            RelativeSelectorMatchHint::InNextSibling
+        );
+        assert_eq!(
+            hint(":has(+ .foo .bar)"),
+            RelativeSelectorMatchHint::InNextSiblingSubtree
+        );
+        assert_eq!(
+            hint(":has(+ .foo ~ .bar)"),
+            RelativeSelectorMatchHint::InSibling
+        );
+        assert_eq!(hint(":has(~ .foo)"), RelativeSelectorMatchHint::InSibling);
+        assert_eq!(
+            hint(":has(~ .foo > .bar)"),
+            RelativeSelectorMatchHint::InSiblingSubtree
+        );
+    }
+
+    #[test]
+    fn test_nesting() {
+        fn parse_nested<'i>(
+            input: &'i str,
+        ) -> Result<SelectorList<DummySelectorImpl>, SelectorParseError<'i>> {
+            let mut parser_input = ParserInput::new(input);
+            let mut parser = CssParser::new(&mut parser_input);
+            SelectorList::parse_for_nesting(&DummyParser::default(), &mut parser)
+        }
+
+        let serialize = |list: &SelectorList<DummySelectorImpl>| {
+            list.0
+                .iter()
+                .map(|s| s.to_css_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        assert_eq!(serialize(&parse("&").unwrap()), "&");
+        assert_eq!(serialize(&parse(".foo &").unwrap()), ".foo &");
+        assert!(parse("&div").is_err());
+        assert!(parse("::before&").is_err());
+
+        assert_eq!(serialize(&parse_nested(".foo").unwrap()), "& .foo");
+        assert_eq!(serialize(&parse_nested("> .foo").unwrap()), "& > .foo");
+        assert_eq!(
+            serialize(&parse_nested("+ .foo, ~ .bar").unwrap()),
+            "& + .foo, & ~ .bar"
+        );
+        assert_eq!(serialize(&parse_nested("&:hover").unwrap()), "&:hover");
+        assert_eq!(serialize(&parse_nested(".foo &").unwrap()), ".foo &");
+        assert_eq!(
+            serialize(&parse_nested(":is(&) .foo").unwrap()),
+            ":is(&) .foo"
+        );
+        assert!(parse_nested(">").is_err());
+
+        let parent = parse(".foo, #bar, .baz::before").unwrap();
+        let nested = parse_nested("&:hover > .qux").unwrap();
+        let replaced = nested.0[0].replace_parent_selector(&parent.0);
+        assert!(!replaced.has_parent_selector());
+        assert_eq!(replaced.to_css_string(), ":is(.foo, #bar):hover > .qux");
+        // Like `:is()`, `&` has the specificity of its most specific argument.
+        assert_eq!(replaced.specificity(), specificity(1, 2, 0));
+
+        let nested = parse_nested("div").unwrap();
+        assert_eq!(nested.0[0].specificity(), specificity(0, 1, 1));
+        let replaced = nested.0[0].replace_parent_selector(&parent.0);
+        assert_eq!(replaced.to_css_string(), ":is(.foo, #bar) div");
+        assert_eq!(replaced.specificity(), specificity(1, 0, 1));
+    }
+
     struct TestVisitor {
         seen: Vec<String>,
     }
@@ -3140,5 +3814,10 @@ pub mod tests {
         let mut test_visitor = TestVisitor { seen: vec![] };
         parse("::before:hover").unwrap().0[0].visit(&mut test_visitor);
         assert!(test_visitor.seen.contains(&":hover".into()));
+
+        let mut test_visitor = TestVisitor { seen: vec![] };
+        parse(":is(.foo :hover) :has(> .bar)").unwrap().0[0].visit(&mut test_visitor);
+        assert!(test_visitor.seen.contains(&":hover".into()));
+        assert!(test_visitor.seen.contains(&".bar".into()));
     }
 }
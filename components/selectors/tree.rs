@@ -60,6 +60,9 @@ pub trait Element: Sized + Clone + Debug {
     /// Skips non-element nodes
     fn next_sibling_element(&self) -> Option<Self>;
 
+    /// Skips non-element nodes
+    fn first_element_child(&self) -> Option<Self>;
+
     fn is_html_element_in_html_document(&self) -> bool;
 
     fn has_local_name(&self, local_name: &<Self::Impl as SelectorImpl>::BorrowedLocalName) -> bool;
@@ -151,4 +154,18 @@ pub trait Element: Sized + Clone + Debug {
     fn ignores_nth_child_selectors(&self) -> bool {
         false
     }
+
+    /// Calls `f` with each of the hashes this element would insert in a
+    /// bloom filter of ancestors, that is, the hashes of its local name,
+    /// namespace, id and classes.
+    ///
+    /// This is used to fast-reject relative selectors when searching the
+    /// subtree of a `:has()` anchor. Returns false if the element doesn't
+    /// support it, in which case the bloom filter is not used.
+    fn each_bloom_hash<F>(&self, _f: F) -> bool
+    where
+        F: FnMut(u32),
+    {
+        false
+    }
 }
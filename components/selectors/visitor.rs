@@ -7,7 +7,7 @@
 #![deny(missing_docs)]
 
 use crate::attr::NamespaceConstraint;
-use crate::parser::{Combinator, Component, SelectorImpl};
+use crate::parser::{Combinator, Component, RelativeSelector, Selector, SelectorImpl};
 
 /// A trait to visit selector properties.
 ///
@@ -41,6 +41,39 @@ pub trait SelectorVisitor {
     fn visit_complex_selector(&mut self, _combinator_to_right: Option<Combinator>) -> bool {
         true
     }
+
+    /// Visits a list of selectors nested in a simple selector, like the
+    /// arguments of `:is()` or `:where()`.
+    ///
+    /// By default, visits each selector in the list.
+    fn visit_selector_list(&mut self, list: &[Selector<Self::Impl>]) -> bool
+    where
+        Self: Sized,
+        <Self::Impl as SelectorImpl>::NonTSPseudoClass: Visit<Impl = Self::Impl>,
+    {
+        for selector in list {
+            if !selector.visit(self) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Visits a list of relative selectors, like the arguments of `:has()`.
+    ///
+    /// By default, visits each selector in the list.
+    fn visit_relative_selector_list(&mut self, list: &[RelativeSelector<Self::Impl>]) -> bool
+    where
+        Self: Sized,
+        <Self::Impl as SelectorImpl>::NonTSPseudoClass: Visit<Impl = Self::Impl>,
+    {
+        for relative in list {
+            if !relative.selector.visit(self) {
+                return false;
+            }
+        }
+        true
+    }
 }
 
 /// Enables traversing selector components stored in various types
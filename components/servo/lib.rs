@@ -118,6 +118,7 @@ use std::cmp::max;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use style_traits::media_queries::{MediaEnvironment, PrefersColorScheme};
 #[cfg(not(target_os = "windows"))]
 use surfman::platform::default::device::Device as HWDevice;
 #[cfg(not(target_os = "windows"))]
@@ -502,6 +503,7 @@ where
         let window_size = WindowSizeData {
             initial_viewport: viewport_size / Scale::new(1.0),
             device_pixel_ratio: Scale::new(device_pixel_ratio),
+            media_environment: media_environment_from_prefs(),
         };
 
         // Create the constellation, which maintains the engine
@@ -552,6 +554,7 @@ where
                 webrender_api,
                 webvr_heartbeats,
                 webxr_main_thread,
+                media_environment: window_size.media_environment,
             },
             opts.output_file.clone(),
             opts.is_running_problem_test,
@@ -581,6 +584,11 @@ where
                 self.compositor.on_resize_window_event();
             },
 
+            WindowEvent::MediaEnvironmentChanged(media_environment) => {
+                self.compositor
+                    .on_media_environment_changed(media_environment);
+            },
+
             WindowEvent::AllowNavigationResponse(pipeline_id, allowed) => {
                 let msg = ConstellationMsg::AllowNavigationResponse(pipeline_id, allowed);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -665,6 +673,13 @@ where
                 }
             },
 
+            WindowEvent::PrintToPdf(top_level_browsing_context_id) => {
+                let msg = ConstellationMsg::PrintToPdf(top_level_browsing_context_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending print to constellation failed ({:?}).", e);
+                }
+            },
+
             WindowEvent::ToggleSamplingProfiler(rate, max_duration) => {
                 self.profiler_enabled = !self.profiler_enabled;
                 let msg = if self.profiler_enabled {
@@ -806,6 +821,18 @@ where
     }
 }
 
+/// The preferences of the user which media queries are evaluated against,
+/// until the embedder supplies its own.
+fn media_environment_from_prefs() -> MediaEnvironment {
+    let prefers_color_scheme = PrefersColorScheme::from_ident(&pref!(layout.prefers_color_scheme))
+        .unwrap_or(PrefersColorScheme::Light);
+    MediaEnvironment {
+        prefers_color_scheme,
+        prefers_reduced_motion: pref!(layout.prefers_reduced_motion),
+        ..MediaEnvironment::default()
+    }
+}
+
 fn create_embedder_channel(
     event_loop_waker: Box<dyn EventLoopWaker>,
 ) -> (EmbedderProxy, EmbedderReceiver) {
@@ -8,14 +8,16 @@
 // compile it out so that people remember it exists, thus the cfg'd Sender
 // import.
 
-use crate::bezier::Bezier;
 use crate::context::SharedStyleContext;
 use crate::dom::{OpaqueNode, TElement};
 use crate::font_metrics::FontMetricsProvider;
-use crate::properties::animated_properties::AnimatedProperty;
+use crate::media_queries::Device;
+use crate::properties::animated_properties::{AnimatedCustomProperty, AnimatedProperty};
 use crate::properties::longhands::animation_direction::computed_value::single_value::T as AnimationDirection;
 use crate::properties::longhands::animation_play_state::computed_value::single_value::T as AnimationPlayState;
-use crate::properties::{self, CascadeMode, ComputedValues, LonghandId};
+use crate::properties::{self, CascadeMode, ComputedValues, LonghandId, LonghandIdSet};
+use crate::properties::{PropertyDeclarationBlock, PropertyDeclarationId};
+use crate::shared_lock::{Locked, SharedRwLockReadGuard};
 use crate::stylesheets::keyframes_rule::{KeyframesAnimation, KeyframesStep, KeyframesStepValue};
 use crate::stylesheets::Origin;
 use crate::timer::Timer;
@@ -23,11 +25,12 @@ use crate::values::computed::box_::TransitionProperty;
 use crate::values::computed::Time;
 use crate::values::computed::TimingFunction;
 use crate::values::generics::box_::AnimationIterationCount;
-use crate::values::generics::easing::{StepPosition, TimingFunction as GenericTimingFunction};
+use crate::values::generics::easing::TimingKeyword;
 use crate::Atom;
 #[cfg(feature = "servo")]
 use crossbeam_channel::Sender;
 use servo_arc::Arc;
+use std::borrow::Cow;
 use std::fmt;
 #[cfg(feature = "gecko")]
 use std::sync::mpsc::Sender;
@@ -247,6 +250,61 @@ impl Animation {
     }
 }
 
+/// A keyframe of an effect created through the Web Animations API.
+#[derive(Clone, Debug)]
+pub struct EffectKeyframe {
+    /// The computed offset of this keyframe, from 0 to 1.
+    pub offset: f64,
+    /// The timing function used from this keyframe to the next one.
+    pub easing: TimingFunction,
+    /// The declarations of this keyframe.
+    pub block: Arc<Locked<PropertyDeclarationBlock>>,
+    /// The animatable longhands this keyframe declares.
+    pub properties: LonghandIdSet,
+}
+
+impl EffectKeyframe {
+    /// Creates a keyframe, computing the set of properties it animates.
+    pub fn new(
+        offset: f64,
+        easing: TimingFunction,
+        block: Arc<Locked<PropertyDeclarationBlock>>,
+        guard: &SharedRwLockReadGuard,
+    ) -> Self {
+        let mut properties = LonghandIdSet::new();
+        for declaration in block.read_with(guard).normal_declaration_iter() {
+            match declaration.id() {
+                PropertyDeclarationId::Longhand(LonghandId::Display) => {},
+                PropertyDeclarationId::Longhand(id) if id.is_animatable() => properties.insert(id),
+                _ => {},
+            }
+        }
+        EffectKeyframe {
+            offset,
+            easing,
+            block,
+            properties,
+        }
+    }
+}
+
+/// A sample of a keyframe effect created through the Web Animations API.
+///
+/// Script owns the timing model of these effects, and samples them at each
+/// animation frame, so all that is left to do here is interpolating between
+/// the keyframes of the effect at the sampled progress.
+#[derive(Clone, Debug)]
+pub struct KeyframeEffectSample {
+    /// The keyframes of the effect, sorted by offset.
+    pub keyframes: Arc<Vec<EffectKeyframe>>,
+    /// The progress through the current iteration of the effect, with its
+    /// easing already applied. Some timing functions may push this outside of
+    /// the [0, 1] range.
+    pub progress: f64,
+    /// The duration of a single iteration of the effect, in seconds.
+    pub iteration_duration: f64,
+}
+
 /// A single animation frame of a single property.
 #[derive(Clone, Debug)]
 pub struct AnimationFrame {
@@ -267,7 +325,7 @@ pub struct PropertyAnimation {
 
 impl PropertyAnimation {
     /// Returns the given property name.
-    pub fn property_name(&self) -> &'static str {
+    pub fn property_name(&self) -> Cow<'static, str> {
         self.property.name()
     }
 
@@ -279,6 +337,7 @@ impl PropertyAnimation {
         transition_index: usize,
         old_style: &ComputedValues,
         new_style: &mut ComputedValues,
+        device: &Device,
     ) -> Vec<PropertyAnimation> {
         let mut result = vec![];
         let box_style = new_style.get_box();
@@ -287,7 +346,19 @@ impl PropertyAnimation {
         let duration = box_style.transition_duration_mod(transition_index);
 
         match transition_property {
-            TransitionProperty::Custom(..) | TransitionProperty::Unsupported(..) => result,
+            TransitionProperty::Unsupported(..) => result,
+            TransitionProperty::Custom(ref name) => {
+                let property =
+                    AnimatedCustomProperty::from_styles(name, old_style, new_style, device);
+                if let Some(property) = property {
+                    result.extend(PropertyAnimation::new(
+                        AnimatedProperty::Custom(property),
+                        timing_function,
+                        duration,
+                    ));
+                }
+                result
+            },
             TransitionProperty::Shorthand(ref shorthand_id) => shorthand_id
                 .longhands()
                 .filter_map(|longhand| {
@@ -325,11 +396,18 @@ impl PropertyAnimation {
         new_style: &ComputedValues,
     ) -> Option<PropertyAnimation> {
         let animated_property = AnimatedProperty::from_longhand(longhand, old_style, new_style)?;
+        Self::new(animated_property, timing_function, duration)
+    }
 
+    fn new(
+        property: AnimatedProperty,
+        timing_function: TimingFunction,
+        duration: Time,
+    ) -> Option<PropertyAnimation> {
         let property_animation = PropertyAnimation {
-            property: animated_property,
-            timing_function: timing_function,
-            duration: duration,
+            property,
+            timing_function,
+            duration,
         };
 
         if property_animation.does_animate() {
@@ -342,51 +420,7 @@ impl PropertyAnimation {
     /// Update the given animation at a given point of progress.
     pub fn update(&self, style: &mut ComputedValues, time: f64) {
         let epsilon = 1. / (200. * (self.duration.seconds() as f64));
-        let progress = match self.timing_function {
-            GenericTimingFunction::CubicBezier { x1, y1, x2, y2 } => {
-                Bezier::new(x1, y1, x2, y2).solve(time, epsilon)
-            },
-            GenericTimingFunction::Steps(steps, pos) => {
-                let mut current_step = (time * (steps as f64)).floor() as i32;
-
-                if pos == StepPosition::Start ||
-                    pos == StepPosition::JumpStart ||
-                    pos == StepPosition::JumpBoth
-                {
-                    current_step = current_step + 1;
-                }
-
-                // FIXME: We should update current_step according to the "before flag".
-                // In order to get the before flag, we have to know the current animation phase
-                // and whether the iteration is reversed. For now, we skip this calculation.
-                // (i.e. Treat before_flag is unset,)
-                // https://drafts.csswg.org/css-easing/#step-timing-function-algo
-
-                if time >= 0.0 && current_step < 0 {
-                    current_step = 0;
-                }
-
-                let jumps = match pos {
-                    StepPosition::JumpBoth => steps + 1,
-                    StepPosition::JumpNone => steps - 1,
-                    StepPosition::JumpStart |
-                    StepPosition::JumpEnd |
-                    StepPosition::Start |
-                    StepPosition::End => steps,
-                };
-
-                if time <= 1.0 && current_step > jumps {
-                    current_step = jumps;
-                }
-
-                (current_step as f64) / (jumps as f64)
-            },
-            GenericTimingFunction::Keyword(keyword) => {
-                let (x1, x2, y1, y2) = keyword.to_bezier();
-                Bezier::new(x1, x2, y1, y2).solve(time, epsilon)
-            },
-        };
-
+        let progress = self.timing_function.calculate_output(time, epsilon);
         self.property.update(style, progress);
     }
 
@@ -410,6 +444,7 @@ pub fn start_transitions_if_applicable(
     opaque_node: OpaqueNode,
     old_style: &ComputedValues,
     new_style: &mut Arc<ComputedValues>,
+    device: &Device,
     timer: &Timer,
     possibly_expired_animations: &[PropertyAnimation],
 ) -> bool {
@@ -417,7 +452,7 @@ pub fn start_transitions_if_applicable(
     for i in 0..new_style.get_box().transition_property_count() {
         // Create any property animations, if applicable.
         let property_animations =
-            PropertyAnimation::from_transition(i, old_style, Arc::make_mut(new_style));
+            PropertyAnimation::from_transition(i, old_style, Arc::make_mut(new_style), device);
         for property_animation in property_animations {
             // Set the property to the initial value.
             //
@@ -480,43 +515,62 @@ where
         KeyframesStepValue::ComputedValues => style_from_cascade.clone(),
         KeyframesStepValue::Declarations {
             block: ref declarations,
-        } => {
-            let guard = declarations.read_with(context.guards.author);
-
-            let iter = || {
-                // It's possible to have !important properties in keyframes
-                // so we have to filter them out.
-                // See the spec issue https://github.com/w3c/csswg-drafts/issues/1824
-                // Also we filter our non-animatable properties.
-                guard
-                    .normal_declaration_iter()
-                    .filter(|declaration| declaration.is_animatable())
-                    .map(|decl| (decl, Origin::Author))
-            };
+        } => compute_style_for_declarations::<E>(
+            context,
+            declarations,
+            previous_style,
+            font_metrics_provider,
+        ),
+    }
+}
 
-            // This currently ignores visited styles, which seems acceptable,
-            // as existing browsers don't appear to animate visited styles.
-            let computed = properties::apply_declarations::<E, _, _>(
-                context.stylist.device(),
-                /* pseudo = */ None,
-                previous_style.rules(),
-                &context.guards,
-                iter,
-                Some(previous_style),
-                Some(previous_style),
-                Some(previous_style),
-                font_metrics_provider,
-                CascadeMode::Unvisited {
-                    visited_rules: None,
-                },
-                context.quirks_mode(),
-                /* rule_cache = */ None,
-                &mut Default::default(),
-                /* element = */ None,
-            );
-            computed
+fn compute_style_for_declarations<E>(
+    context: &SharedStyleContext,
+    declarations: &Locked<PropertyDeclarationBlock>,
+    previous_style: &ComputedValues,
+    font_metrics_provider: &dyn FontMetricsProvider,
+) -> Arc<ComputedValues>
+where
+    E: TElement,
+{
+    let guard = declarations.read_with(context.guards.author);
+
+    let iter = || {
+        // It's possible to have !important properties in keyframes
+        // so we have to filter them out.
+        // See the spec issue https://github.com/w3c/csswg-drafts/issues/1824
+        // Also we filter our non-animatable properties, but keep custom
+        // properties, which animate either by interpolation or discretely.
+        guard
+            .normal_declaration_iter()
+            .filter(|declaration| {
+                declaration.is_animatable() ||
+                    matches!(declaration.id(), PropertyDeclarationId::Custom(..))
+            })
+            .map(|decl| (decl, Origin::Author))
+    };
+
+    // This currently ignores visited styles, which seems acceptable,
+    // as existing browsers don't appear to animate visited styles.
+    let computed = properties::apply_declarations::<E, _, _>(
+        context.stylist.device(),
+        /* pseudo = */ None,
+        previous_style.rules(),
+        &context.guards,
+        iter,
+        Some(previous_style),
+        Some(previous_style),
+        Some(previous_style),
+        font_metrics_provider,
+        CascadeMode::Unvisited {
+            visited_rules: None,
         },
-    }
+        context.quirks_mode(),
+        /* rule_cache = */ None,
+        &mut Default::default(),
+        /* element = */ None,
+    );
+    computed
 }
 
 /// Triggers animations for a given node looking at the animation property
@@ -836,6 +890,26 @@ where
                 }
             }
 
+            for name in animation.custom_properties_changed.iter() {
+                let property = AnimatedCustomProperty::from_styles(
+                    name,
+                    &from_style,
+                    &target_style,
+                    context.stylist.device(),
+                );
+                let animation = property.and_then(|property| {
+                    PropertyAnimation::new(
+                        AnimatedProperty::Custom(property),
+                        timing_function,
+                        Time::from_seconds(relative_duration as f32),
+                    )
+                });
+                if let Some(property_animation) = animation {
+                    debug!("update_style_for_animation: {:?}", property_animation);
+                    property_animation.update(Arc::make_mut(&mut new_style), relative_progress);
+                }
+            }
+
             debug!(
                 "update_style_for_animation: got style change in animation \"{}\"",
                 name
@@ -846,6 +920,102 @@ where
     }
 }
 
+/// Updates the given style with a sample of a keyframe effect driven by
+/// script.
+///
+/// Each property is interpolated between the keyframes that specify it, and
+/// the underlying value stands in for the missing keyframes at offsets 0 and
+/// 1.
+///
+/// <https://drafts.csswg.org/web-animations/#the-effect-value-of-a-keyframe-animation-effect>
+pub fn update_style_for_keyframe_effect_sample<E>(
+    context: &SharedStyleContext,
+    sample: &KeyframeEffectSample,
+    style: &mut Arc<ComputedValues>,
+    font_metrics_provider: &dyn FontMetricsProvider,
+) where
+    E: TElement,
+{
+    let mut properties = LonghandIdSet::new();
+    for keyframe in sample.keyframes.iter() {
+        for property in keyframe.properties.iter() {
+            properties.insert(property);
+        }
+    }
+    if properties.is_empty() {
+        return;
+    }
+
+    let keyframe_styles: Vec<_> = sample
+        .keyframes
+        .iter()
+        .map(|keyframe| {
+            compute_style_for_declarations::<E>(
+                context,
+                &keyframe.block,
+                &**style,
+                font_metrics_provider,
+            )
+        })
+        .collect();
+
+    let linear = TimingFunction::Keyword(TimingKeyword::Linear);
+    let progress = sample.progress;
+    let mut new_style = (*style).clone();
+    for property in properties.iter() {
+        // The property-specific keyframes, as (offset, easing, style) tuples.
+        let mut keyframes: Vec<_> = sample
+            .keyframes
+            .iter()
+            .zip(keyframe_styles.iter())
+            .filter(|&(keyframe, _)| keyframe.properties.contains(property))
+            .map(|(keyframe, style)| (keyframe.offset, &keyframe.easing, &**style))
+            .collect();
+        if keyframes.first().map_or(true, |keyframe| keyframe.0 != 0.) {
+            keyframes.insert(0, (0., &linear, &**style));
+        }
+        if keyframes.last().map_or(true, |keyframe| keyframe.0 != 1.) {
+            keyframes.push((1., &linear, &**style));
+        }
+
+        let (from, to, interval_progress) = if progress < 0. &&
+            keyframes.iter().filter(|keyframe| keyframe.0 == 0.).count() > 1
+        {
+            (0, 0, 0.)
+        } else if progress >= 1. && keyframes.iter().filter(|keyframe| keyframe.0 == 1.).count() > 1
+        {
+            (keyframes.len() - 1, keyframes.len() - 1, 0.)
+        } else {
+            let from = if progress < 0. {
+                0
+            } else {
+                keyframes
+                    .iter()
+                    .rposition(|keyframe| keyframe.0 <= progress && keyframe.0 != 1.)
+                    .unwrap_or(0)
+            };
+            let to = from + 1;
+            let interval = keyframes[to].0 - keyframes[from].0;
+            let epsilon = 1. / (200. * interval * sample.iteration_duration);
+            let interval_progress = if interval > 0. {
+                keyframes[from]
+                    .1
+                    .calculate_output((progress - keyframes[from].0) / interval, epsilon)
+            } else {
+                1.
+            };
+            (from, to, interval_progress)
+        };
+
+        if let Some(animated_property) =
+            AnimatedProperty::from_longhand(property, keyframes[from].2, keyframes[to].2)
+        {
+            animated_property.update(Arc::make_mut(&mut new_style), interval_progress);
+        }
+    }
+    *style = new_style;
+}
+
 /// Update the style in the node when it finishes.
 #[cfg(feature = "servo")]
 pub fn complete_expired_transitions(
@@ -102,7 +102,9 @@ impl<E: TElement> PushedElement<E> {
     }
 }
 
-fn each_relevant_element_hash<E, F>(element: E, mut f: F)
+/// Calls `f` with each of the hashes of `element` that are relevant for
+/// fast-rejecting selectors with the bloom filter.
+pub fn each_relevant_element_hash<E, F>(element: E, mut f: F)
 where
     E: TElement,
     F: FnMut(u32),
@@ -8,6 +8,7 @@
 
 use crate::hash::map::Entry;
 use crate::properties::{CSSWideKeyword, CustomDeclaration, CustomDeclarationValue};
+use crate::properties_and_values::PropertyRegistry;
 use crate::selector_map::{PrecomputedHashMap, PrecomputedHashSet, PrecomputedHasher};
 use crate::stylesheets::{Origin, PerOrigin};
 use crate::Atom;
@@ -231,6 +232,35 @@ impl VariableValue {
         )
     }
 
+    /// Creates a value from the serialization of a computed value of a
+    /// registered custom property, which doesn't reference anything.
+    pub fn from_computed_css(css: String) -> Self {
+        let (first_token_type, last_token_type) = {
+            let mut input = ParserInput::new(&css);
+            let mut input = Parser::new(&mut input);
+            match parse_self_contained_declaration_value(&mut input, None) {
+                Ok((first, _, last)) => (first, last),
+                Err(..) => (
+                    TokenSerializationType::nothing(),
+                    TokenSerializationType::nothing(),
+                ),
+            }
+        };
+        VariableValue {
+            css,
+            first_token_type,
+            last_token_type,
+            references: Default::default(),
+            references_environment: false,
+        }
+    }
+
+    /// Returns the serialization of this value.
+    #[inline]
+    pub fn css(&self) -> &str {
+        &self.css
+    }
+
     /// Parse a custom property value.
     pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Arc<Self>, ParseError<'i>> {
         let mut references = VarOrEnvReferences::default();
@@ -498,6 +528,7 @@ pub struct CustomPropertiesBuilder<'a> {
     custom_properties: Option<CustomPropertiesMap>,
     inherited: Option<&'a Arc<CustomPropertiesMap>>,
     environment: &'a CssEnvironment,
+    registry: &'a PropertyRegistry,
 }
 
 impl<'a> CustomPropertiesBuilder<'a> {
@@ -505,6 +536,7 @@ impl<'a> CustomPropertiesBuilder<'a> {
     pub fn new(
         inherited: Option<&'a Arc<CustomPropertiesMap>>,
         environment: &'a CssEnvironment,
+        registry: &'a PropertyRegistry,
     ) -> Self {
         Self {
             seen: PrecomputedHashSet::default(),
@@ -513,6 +545,7 @@ impl<'a> CustomPropertiesBuilder<'a> {
             custom_properties: None,
             inherited,
             environment,
+            registry,
         }
     }
 
@@ -574,24 +607,32 @@ impl<'a> CustomPropertiesBuilder<'a> {
                         self.reverted.borrow_mut_for_origin(&origin).insert(name);
                     }
                 },
-                CSSWideKeyword::Initial => {
+                // The initial value of a registered property is filled in
+                // when computing values, and only properties that don't
+                // inherit get here with `unset`.
+                CSSWideKeyword::Initial | CSSWideKeyword::Unset => {
                     map.remove(name);
                 },
                 // handled in value_may_affect_style
-                CSSWideKeyword::Unset | CSSWideKeyword::Inherit => unreachable!(),
+                CSSWideKeyword::Inherit => unreachable!(),
             },
         }
     }
 
     fn value_may_affect_style(&self, name: &Name, value: &CustomDeclarationValue) -> bool {
         match *value {
-            CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Unset) |
             CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Inherit) => {
-                // Custom properties are inherited by default. So
-                // explicit 'inherit' or 'unset' means we can just use
-                // any existing value in the inherited CustomPropertiesMap.
+                // Explicit 'inherit' means we can just use any existing value
+                // in the inherited CustomPropertiesMap.
                 return false;
             },
+            CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Unset)
+                if self.inherits(name) =>
+            {
+                // Custom properties are inherited by default, so 'unset'
+                // behaves like 'inherit' unless registered otherwise.
+                return false;
+            }
             _ => {},
         }
 
@@ -602,7 +643,8 @@ impl<'a> CustomPropertiesBuilder<'a> {
             .or_else(|| self.inherited.and_then(|m| m.get(name)));
 
         match (existing_value, value) {
-            (None, &CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Initial)) => {
+            (None, &CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Initial)) |
+            (None, &CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Unset)) => {
                 // The initial value of a custom property is the same as it
                 // not existing in the map.
                 return false;
@@ -620,6 +662,31 @@ impl<'a> CustomPropertiesBuilder<'a> {
         true
     }
 
+    fn inherits(&self, name: &Name) -> bool {
+        self.registry.get(name).map_or(true, |r| r.inherits)
+    }
+
+    /// Removes the inherited values of registered properties that don't
+    /// inherit and weren't specified, so that they get their initial value.
+    fn reset_non_inherited_properties(&mut self) {
+        let inherited = match self.inherited {
+            Some(inherited) => inherited,
+            None => return,
+        };
+        let registry = self.registry;
+        for registration in registry.iter() {
+            if registration.inherits ||
+                self.seen.contains(&registration.name) ||
+                !inherited.contains_key(&registration.name)
+            {
+                continue;
+            }
+            self.custom_properties
+                .get_or_insert_with(|| (**inherited).clone())
+                .remove(&registration.name);
+        }
+    }
+
     /// Returns the final map of applicable custom properties.
     ///
     /// If there was any specified property, we've created a new map and now we
@@ -627,6 +694,7 @@ impl<'a> CustomPropertiesBuilder<'a> {
     ///
     /// Otherwise, just use the inherited custom properties map.
     pub fn build(mut self) -> Option<Arc<CustomPropertiesMap>> {
+        self.reset_non_inherited_properties();
         let mut map = match self.custom_properties.take() {
             Some(m) => m,
             None => return self.inherited.cloned(),
@@ -109,7 +109,7 @@ impl fmt::Debug for EagerPseudoArray {
 #[cfg(feature = "gecko")]
 const EMPTY_PSEUDO_ARRAY: &'static EagerPseudoArrayInner = &[None, None, None, None];
 #[cfg(feature = "servo")]
-const EMPTY_PSEUDO_ARRAY: &'static EagerPseudoArrayInner = &[None, None, None];
+const EMPTY_PSEUDO_ARRAY: &'static EagerPseudoArrayInner = &[None, None, None, None, None];
 
 impl EagerPseudoStyles {
     /// Returns whether there are any pseudo styles.
@@ -221,6 +221,12 @@ pub struct ElementData {
 
     /// Flags.
     pub flags: ElementDataFlags,
+
+    /// The primary style of the element before the effects of the animations
+    /// that script runs on it were applied, if there are any, so that these
+    /// effects don't start transitions.
+    #[cfg(feature = "servo")]
+    pub unanimated_primary: Option<Arc<ComputedValues>>,
 }
 
 /// The kind of restyle that a single element should do.
@@ -7,6 +7,8 @@
 #![allow(unsafe_code)]
 #![deny(missing_docs)]
 
+#[cfg(feature = "servo")]
+use crate::animation::KeyframeEffectSample;
 use crate::applicable_declarations::ApplicableDeclarationBlock;
 #[cfg(feature = "gecko")]
 use crate::context::PostAnimationTasks;
@@ -22,7 +24,9 @@ use crate::shared_lock::Locked;
 use crate::stylist::CascadeData;
 use crate::traversal_flags::TraversalFlags;
 use crate::{Atom, LocalName, Namespace, WeakAtom};
+use app_units::Au;
 use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
+use euclid::default::Size2D;
 use selectors::matching::{ElementSelectorFlags, QuirksMode, VisitedHandlingMode};
 use selectors::sink::Push;
 use selectors::Element as SelectorsElement;
@@ -514,6 +518,13 @@ pub trait TElement:
         None
     }
 
+    /// Get the samples of the keyframe effects that script is running on this
+    /// element, in composite order.
+    #[cfg(feature = "servo")]
+    fn keyframe_effect_samples(&self) -> &[KeyframeEffectSample] {
+        &[]
+    }
+
     /// Get this element's state, for non-tree-structural pseudos.
     fn state(&self) -> ElementState;
 
@@ -887,6 +898,21 @@ pub trait TElement:
     /// document it is on.
     fn is_html_document_body_element(&self) -> bool;
 
+    /// Returns the size of the content box of this element as of the last
+    /// layout, if it's a query container that has been laid out.
+    ///
+    /// This is what container queries and container-relative units are
+    /// evaluated against.
+    fn query_container_size(&self) -> Option<Size2D<Au>> {
+        None
+    }
+
+    /// Notes that the style of this element or one of its pseudo-elements
+    /// depends on the size of a query container, either because a container
+    /// query was evaluated for it or because it uses container-relative units,
+    /// so that it's restyled when that size changes.
+    fn note_query_container_size_dependency(&self) {}
+
     /// Generate the proper applicable declarations due to presentational hints,
     /// and insert them into `hints`.
     fn synthesize_presentational_hints_for_legacy_attributes<V>(
@@ -69,8 +69,6 @@ bitflags! {
         const IN_REQUIRED_STATE = 1 << 21;
         /// <https://html.spec.whatwg.org/multipage/#selector-optional>
         const IN_OPTIONAL_STATE = 1 << 22;
-        /// <https://html.spec.whatwg.org/multipage/#selector-read-write>
-        const IN_READ_WRITE_STATE = 1 << 22;
         /// <https://html.spec.whatwg.org/multipage/#selector-defined>
         const IN_DEFINED_STATE = 1 << 23;
         /// <https://html.spec.whatwg.org/multipage/#selector-visited>
@@ -90,6 +88,10 @@ bitflags! {
         const IN_MOZ_READONLY_STATE = 1 << 29;
         /// <https://html.spec.whatwg.org/multipage/#selector-read-write>
         const IN_MOZ_READWRITE_STATE = 1 << 30;
+        /// <https://html.spec.whatwg.org/multipage/#selector-read-write>
+        ///
+        /// Servo's name for `IN_MOZ_READWRITE_STATE`.
+        const IN_READ_WRITE_STATE = Self::IN_MOZ_READWRITE_STATE.bits;
         /// <https://html.spec.whatwg.org/multipage/#selector-default>
         const IN_DEFAULT_STATE = 1 << 31;
         /// Non-standard: https://developer.mozilla.org/en-US/docs/Web/CSS/:-moz-submit-invalid
@@ -107,6 +109,8 @@ bitflags! {
         /// Non-standard & undocumented.
         const IN_INCREMENT_SCRIPT_LEVEL_STATE = 1 << 38;
         /// Non-standard: https://developer.mozilla.org/en-US/docs/Web/CSS/:-moz-focusring
+        ///
+        /// Also used for <https://drafts.csswg.org/selectors-4/#the-focus-visible-pseudo>
         const IN_FOCUSRING_STATE = 1 << 39;
         /// Non-standard & undocumented.
         const IN_HANDLER_CLICK_TO_PLAY_STATE = 1 << 40;
@@ -137,6 +141,8 @@ bitflags! {
         const IN_AUTOFILL_STATE = 1 << 50;
         /// Non-standard & undocumented.
         const IN_AUTOFILL_PREVIEW_STATE = 1 << 51;
+        /// <https://drafts.csswg.org/selectors-4/#modal-state>
+        const IN_MODAL_DIALOG_STATE = 1 << 52;
     }
 }
 
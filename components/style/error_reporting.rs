@@ -50,6 +50,11 @@ pub enum ContextualParseError<'a> {
     InvalidCounterStyleExtendsWithSymbols,
     /// A counter style rule had extends with additive-symbols.
     InvalidCounterStyleExtendsWithAdditiveSymbols,
+    /// A property rule had an unsupported descriptor.
+    UnsupportedPropertyDescriptor(&'a str, ParseError<'a>),
+    /// A property rule was missing a descriptor, or had an invalid initial
+    /// value.
+    InvalidPropertyRule(&'static str),
     /// A media rule was invalid for some reason.
     InvalidMediaRule(&'a str, ParseError<'a>),
     /// A value was not recognized.
@@ -205,6 +210,17 @@ impl<'a> fmt::Display for ContextualParseError<'a> {
                 f,
                 "Invalid @counter-style rule: 'system: extends …' with 'additive-symbols'"
             ),
+            ContextualParseError::UnsupportedPropertyDescriptor(decl, ref err) => {
+                write!(
+                    f,
+                    "Unsupported @property descriptor declaration: '{}', ",
+                    decl
+                )?;
+                parse_error_to_str(err, f)
+            },
+            ContextualParseError::InvalidPropertyRule(reason) => {
+                write!(f, "Invalid @property rule: {}", reason)
+            },
             ContextualParseError::InvalidMediaRule(media_rule, ref err) => {
                 write!(f, "Invalid media rule: {}, ", media_rule)?;
                 parse_error_to_str(err, f)
@@ -156,7 +156,7 @@ impl_range!(FontWeightRange, AbsoluteFontWeight);
 /// specified::Number.
 #[repr(C)]
 #[allow(missing_docs)]
-pub struct ComputedFontWeightRange(f32, f32);
+pub struct ComputedFontWeightRange(pub f32, pub f32);
 
 #[inline]
 fn sort_range<T: PartialOrd>(a: T, b: T) -> (T, T) {
@@ -186,7 +186,7 @@ impl_range!(FontStretchRange, FontStretch);
 /// Gecko can read them easily.
 #[repr(C)]
 #[allow(missing_docs)]
-pub struct ComputedFontStretchRange(f32, f32);
+pub struct ComputedFontStretchRange(pub f32, pub f32);
 
 impl FontStretchRange {
     /// Returns a computed font-stretch range.
@@ -324,12 +324,20 @@ pub struct EffectiveSources(Vec<Source>);
 
 #[cfg(feature = "servo")]
 impl<'a> FontFace<'a> {
-    /// Returns the list of effective sources for that font-face, that is the
-    /// sources which don't list any format hint, or the ones which list at
-    /// least "truetype" or "opentype".
+    /// Returns the list of effective sources for that font-face.
     pub fn effective_sources(&self) -> EffectiveSources {
+        EffectiveSources::from_sources(self.sources())
+    }
+}
+
+#[cfg(feature = "servo")]
+impl EffectiveSources {
+    /// Returns the effective sources among the given ones, that is the sources
+    /// which don't list any format hint, or the ones which list at least
+    /// "truetype" or "opentype".
+    pub fn from_sources(sources: &[Source]) -> Self {
         EffectiveSources(
-            self.sources()
+            sources
                 .iter()
                 .rev()
                 .filter(|source| {
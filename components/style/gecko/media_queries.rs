@@ -10,6 +10,7 @@ use crate::gecko_bindings::bindings;
 use crate::gecko_bindings::structs;
 use crate::media_queries::MediaType;
 use crate::properties::ComputedValues;
+use crate::properties_and_values::PropertyRegistry;
 use crate::string_cache::Atom;
 use crate::values::computed::font::FontSize;
 use crate::values::{CustomIdent, KeyframesName};
@@ -54,6 +55,9 @@ pub struct Device {
     /// The CssEnvironment object responsible of getting CSS environment
     /// variables.
     environment: CssEnvironment,
+    /// The custom properties registered via `@property` rules or
+    /// `CSS.registerProperty()`.
+    custom_property_registry: Arc<PropertyRegistry>,
 }
 
 impl fmt::Debug for Device {
@@ -92,6 +96,7 @@ impl Device {
             used_root_font_size: AtomicBool::new(false),
             used_viewport_size: AtomicBool::new(false),
             environment: CssEnvironment,
+            custom_property_registry: Default::default(),
         }
     }
 
@@ -101,6 +106,19 @@ impl Device {
         &self.environment
     }
 
+    /// Get the custom properties registered via `@property` rules or
+    /// `CSS.registerProperty()`.
+    #[inline]
+    pub fn custom_property_registry(&self) -> &Arc<PropertyRegistry> {
+        &self.custom_property_registry
+    }
+
+    /// Set the custom properties registered via `@property` rules or
+    /// `CSS.registerProperty()`.
+    pub fn set_custom_property_registry(&mut self, registry: Arc<PropertyRegistry>) {
+        self.custom_property_registry = registry;
+    }
+
     /// Tells the device that a new viewport rule has been found, and stores the
     /// relevant viewport constraints.
     pub fn account_for_viewport_rule(&mut self, _constraints: &ViewportConstraints) {
@@ -43,6 +43,12 @@ pub trait ElementSnapshot: Sized {
     /// If this snapshot contains attribute information.
     fn has_attrs(&self) -> bool;
 
+    /// Whether the `:has()` selectors anchored at this element need to be
+    /// matched again, because of a change elsewhere in the document.
+    fn relative_selectors_invalidated(&self) -> bool {
+        false
+    }
+
     /// Gets the attribute information of the snapshot as a string.
     ///
     /// Only for debugging purposes.
@@ -242,6 +248,15 @@ where
                 }
             },
 
+            // :read-only is the negation of the :read-write state flag, so we
+            // can't just test whether the snapshot state intersects it.
+            #[cfg(feature = "servo")]
+            NonTSPseudoClass::ReadOnly => {
+                if let Some(snapshot_state) = self.snapshot().and_then(|s| s.state()) {
+                    return !snapshot_state.intersects(pseudo_class.state_flag());
+                }
+            },
+
             // :lang() needs to match using the closest ancestor xml:lang="" or
             // lang="" attribtue from snapshots.
             NonTSPseudoClass::Lang(ref lang_arg) => {
@@ -310,6 +325,11 @@ where
         Some(Self::new(sibling, self.snapshot_map))
     }
 
+    fn first_element_child(&self) -> Option<Self> {
+        let child = self.element.first_element_child()?;
+        Some(Self::new(child, self.snapshot_map))
+    }
+
     #[inline]
     fn is_html_element_in_html_document(&self) -> bool {
         self.element.is_html_element_in_html_document()
@@ -12,7 +12,7 @@ use crate::{Atom, LocalName, Namespace};
 use fallible::FallibleVec;
 use hashglobe::FailedAllocationError;
 use selectors::attr::NamespaceConstraint;
-use selectors::parser::{Combinator, Component};
+use selectors::parser::{Combinator, Component, RelativeSelector};
 use selectors::parser::{Selector, SelectorIter, Visit};
 use selectors::visitor::SelectorVisitor;
 use smallvec::SmallVec;
@@ -35,6 +35,12 @@ use smallvec::SmallVec;
 /// This allows us to quickly scan through the dependency sites of all style
 /// rules and determine the maximum effect that a given state or attribute
 /// change may have on the style of elements in the document.
+///
+/// Selectors nested in `:is()` or `:where()` get their own dependencies too,
+/// pointing to the dependency of the compound selector they're nested in. The
+/// rightmost compound selector of a nested selector is matched against the
+/// same element as the compound selector containing it, so it's considered
+/// part of it instead.
 #[derive(Clone, Debug, MallocSizeOf)]
 pub struct Dependency {
     /// The dependency selector.
@@ -47,6 +53,14 @@ pub struct Dependency {
 
     /// The offset into the selector that we should match on.
     pub selector_offset: usize,
+
+    /// The dependency of the compound selector `selector` is nested in, if
+    /// any.
+    ///
+    /// When `selector` fully matches an element, the compound selector
+    /// containing it may have started or stopped matching that element, so
+    /// invalidation continues from the parent dependency.
+    pub parent: Option<Box<Dependency>>,
 }
 
 /// The kind of elements down the tree this dependency may affect.
@@ -164,6 +178,10 @@ pub struct InvalidationMap {
     pub document_state_selectors: Vec<DocumentStateDependency>,
     /// A map of other attribute affecting selectors.
     pub other_attribute_affecting_selectors: SelectorMap<Dependency>,
+    /// A map of the compound selectors that contain `:has()`, which need to
+    /// be invalidated when a DOM mutation may change what the relative
+    /// selectors match.
+    pub relative_selector_dependencies: SelectorMap<Dependency>,
     /// Whether there are attribute rules of the form `[class~="foo"]` that may
     /// match. In that case, we need to look at
     /// `other_attribute_affecting_selectors` too even if only the `class` has
@@ -185,6 +203,7 @@ impl InvalidationMap {
             state_affecting_selectors: SelectorMap::new(),
             document_state_selectors: Vec::new(),
             other_attribute_affecting_selectors: SelectorMap::new(),
+            relative_selector_dependencies: SelectorMap::new(),
             has_class_attribute_selectors: false,
             has_id_attribute_selectors: false,
         }
@@ -195,6 +214,7 @@ impl InvalidationMap {
         self.state_affecting_selectors.len() +
             self.document_state_selectors.len() +
             self.other_attribute_affecting_selectors.len() +
+            self.relative_selector_dependencies.len() +
             self.id_to_selector
                 .iter()
                 .fold(0, |accum, (_, ref v)| accum + v.len()) +
@@ -210,6 +230,7 @@ impl InvalidationMap {
         self.state_affecting_selectors.clear();
         self.document_state_selectors.clear();
         self.other_attribute_affecting_selectors.clear();
+        self.relative_selector_dependencies.clear();
         self.has_id_attribute_selectors = false;
         self.has_class_attribute_selectors = false;
     }
@@ -223,11 +244,40 @@ impl InvalidationMap {
     ) -> Result<(), FailedAllocationError> {
         debug!("InvalidationMap::note_selector({:?})", selector);
 
+        let mut document_state = DocumentState::empty();
+        self.note_selector_internal(selector, None, &mut document_state, quirks_mode)?;
+
+        if !document_state.is_empty() {
+            self.document_state_selectors
+                .try_push(DocumentStateDependency {
+                    state: document_state,
+                    selector: selector.clone(),
+                })?;
+        }
+
+        Ok(())
+    }
+
+    fn note_selector_internal(
+        &mut self,
+        selector: &Selector<SelectorImpl>,
+        parent: Option<&Dependency>,
+        document_state: &mut DocumentState,
+        quirks_mode: QuirksMode,
+    ) -> Result<(), FailedAllocationError> {
         let mut iter = selector.iter();
         let mut combinator;
         let mut index = 0;
 
-        let mut document_state = DocumentState::empty();
+        // The rightmost compound selector of a nested selector has been
+        // collected as part of the compound selector containing it already.
+        if parent.is_some() {
+            index += iter.by_ref().count();
+            if iter.next_sequence().is_none() {
+                return Ok(());
+            }
+            index += 1; // Account for the combinator.
+        }
 
         loop {
             let sequence_start = index;
@@ -236,20 +286,19 @@ impl InvalidationMap {
                 classes: SmallVec::new(),
                 ids: SmallVec::new(),
                 state: ElementState::empty(),
-                document_state: &mut document_state,
+                document_state: &mut *document_state,
                 other_attributes: false,
                 has_id_attribute_selectors: false,
                 has_class_attribute_selectors: false,
+                has_relative_selector: false,
+                nested_selectors: SmallVec::new(),
             };
 
             // Visit all the simple selectors in this sequence.
             //
-            // Note that this works because we can't have combinators nested
-            // inside simple selectors (i.e. in :not() or :-moz-any()).
-            //
-            // If we ever support that we'll need to visit nested complex
-            // selectors as well, in order to mark them as affecting descendants
-            // at least.
+            // Selectors nested in :is() or :where() may contain combinators,
+            // the collector takes care of keeping track of them so we can
+            // note them afterwards.
             for ss in &mut iter {
                 ss.visit(&mut compound_visitor);
                 index += 1; // Account for the simple selector.
@@ -258,33 +307,30 @@ impl InvalidationMap {
             self.has_id_attribute_selectors |= compound_visitor.has_id_attribute_selectors;
             self.has_class_attribute_selectors |= compound_visitor.has_class_attribute_selectors;
 
+            let dependency = Dependency {
+                selector: selector.clone(),
+                selector_offset: sequence_start,
+                parent: parent.map(|parent| Box::new(parent.clone())),
+            };
+
             for class in compound_visitor.classes {
                 self.class_to_selector
                     .try_entry(class, quirks_mode)?
                     .or_insert_with(SmallVec::new)
-                    .try_push(Dependency {
-                        selector: selector.clone(),
-                        selector_offset: sequence_start,
-                    })?;
+                    .try_push(dependency.clone())?;
             }
 
             for id in compound_visitor.ids {
                 self.id_to_selector
                     .try_entry(id, quirks_mode)?
                     .or_insert_with(SmallVec::new)
-                    .try_push(Dependency {
-                        selector: selector.clone(),
-                        selector_offset: sequence_start,
-                    })?;
+                    .try_push(dependency.clone())?;
             }
 
             if !compound_visitor.state.is_empty() {
                 self.state_affecting_selectors.insert(
                     StateDependency {
-                        dep: Dependency {
-                            selector: selector.clone(),
-                            selector_offset: sequence_start,
-                        },
+                        dep: dependency.clone(),
                         state: compound_visitor.state,
                     },
                     quirks_mode,
@@ -292,11 +338,20 @@ impl InvalidationMap {
             }
 
             if compound_visitor.other_attributes {
-                self.other_attribute_affecting_selectors.insert(
-                    Dependency {
-                        selector: selector.clone(),
-                        selector_offset: sequence_start,
-                    },
+                self.other_attribute_affecting_selectors
+                    .insert(dependency.clone(), quirks_mode)?;
+            }
+
+            if compound_visitor.has_relative_selector {
+                self.relative_selector_dependencies
+                    .insert(dependency.clone(), quirks_mode)?;
+            }
+
+            for nested in compound_visitor.nested_selectors {
+                self.note_selector_internal(
+                    &nested,
+                    Some(&dependency),
+                    document_state,
                     quirks_mode,
                 )?;
             }
@@ -309,14 +364,6 @@ impl InvalidationMap {
             index += 1; // Account for the combinator.
         }
 
-        if !document_state.is_empty() {
-            self.document_state_selectors
-                .try_push(DocumentStateDependency {
-                    state: document_state,
-                    selector: selector.clone(),
-                })?;
-        }
-
         Ok(())
     }
 }
@@ -354,6 +401,14 @@ struct CompoundSelectorDependencyCollector<'a> {
 
     /// Whether there were attribute selectors with the class attribute.
     has_class_attribute_selectors: bool,
+
+    /// Whether this compound selector contains a `:has()` selector.
+    has_relative_selector: bool,
+
+    /// The selectors nested in this compound selector (in `:is()` or
+    /// `:where()`) that have more than one compound selector, and thus need
+    /// dependencies of their own.
+    nested_selectors: SmallVec<[Selector<SelectorImpl>; 1]>,
 }
 
 impl<'a> SelectorVisitor for CompoundSelectorDependencyCollector<'a> {
@@ -370,6 +425,9 @@ impl<'a> SelectorVisitor for CompoundSelectorDependencyCollector<'a> {
             Component::Class(ref class) => {
                 self.classes.push(class.clone());
             },
+            Component::Has(..) => {
+                self.has_relative_selector = true;
+            },
             Component::NonTSPseudoClass(ref pc) => {
                 self.other_attributes |= pc.is_attr_based();
                 self.state |= match *pc {
@@ -404,4 +462,28 @@ impl<'a> SelectorVisitor for CompoundSelectorDependencyCollector<'a> {
 
         true
     }
+
+    fn visit_selector_list(&mut self, list: &[Selector<SelectorImpl>]) -> bool {
+        for selector in list {
+            // The rightmost compound selector is matched against the same
+            // element as us, so it's part of this compound selector for
+            // invalidation purposes.
+            let mut iter = selector.iter();
+            for ss in &mut iter {
+                ss.visit(self);
+            }
+
+            if iter.next_sequence().is_some() {
+                self.nested_selectors.push(selector.clone());
+            }
+        }
+        true
+    }
+
+    fn visit_relative_selector_list(&mut self, _: &[RelativeSelector<SelectorImpl>]) -> bool {
+        // Relative selectors are never matched against the element itself.
+        // Mutations of the elements they match mark the anchor element
+        // instead, see `relative_selector_dependencies`.
+        true
+    }
 }
@@ -7,6 +7,7 @@
 
 use crate::context::StackLimitChecker;
 use crate::dom::{TElement, TNode, TShadowRoot};
+use crate::invalidation::element::invalidation_map::{Dependency, DependencyInvalidationKind};
 use crate::selector_parser::SelectorImpl;
 use selectors::matching::matches_compound_selector_from;
 use selectors::matching::{CompoundSelectorMatchingResult, MatchingContext};
@@ -146,6 +147,9 @@ pub struct Invalidation<'a> {
     /// this one if the generated invalidation is effective for all the siblings
     /// or descendants after us.
     matched_by_any_previous: bool,
+    /// The dependency of the compound selector this selector is nested in, if
+    /// any, which we continue invalidating from once `selector` fully matches.
+    parent: Option<&'a Dependency>,
 }
 
 impl<'a> Invalidation<'a> {
@@ -160,6 +164,22 @@ impl<'a> Invalidation<'a> {
             scope,
             offset,
             matched_by_any_previous: false,
+            parent: None,
+        }
+    }
+
+    /// Create a new invalidation for the compound selector to the left of the
+    /// one a given dependency represents.
+    pub fn for_dependency(dependency: &'a Dependency, scope: Option<OpaqueElement>) -> Self {
+        debug_assert_ne!(dependency.selector_offset, 0);
+        debug_assert_ne!(dependency.selector_offset, dependency.selector.len());
+
+        Self {
+            selector: &dependency.selector,
+            scope,
+            offset: dependency.selector.len() - dependency.selector_offset + 1,
+            matched_by_any_previous: false,
+            parent: dependency.parent.as_deref(),
         }
     }
 
@@ -740,6 +760,47 @@ where
         invalidated
     }
 
+    /// Continues invalidating from the dependency of a compound selector
+    /// containing a selector that matched the current element.
+    ///
+    /// Returns whether the current element itself was invalidated.
+    fn invalidate_for_parent_dependency(
+        &mut self,
+        parent: &'b Dependency,
+        scope: Option<OpaqueElement>,
+        descendant_invalidations: &mut DescendantInvalidationLists<'b>,
+        sibling_invalidations: &mut InvalidationVector<'b>,
+    ) -> bool {
+        let invalidation_kind = parent.invalidation_kind();
+        if matches!(invalidation_kind, DependencyInvalidationKind::Element) {
+            return true;
+        }
+
+        let invalidation = Invalidation::for_dependency(parent, scope);
+        match invalidation_kind {
+            DependencyInvalidationKind::Element => unreachable!(),
+            DependencyInvalidationKind::ElementAndDescendants => {
+                descendant_invalidations.dom_descendants.push(invalidation);
+                return true;
+            },
+            DependencyInvalidationKind::Descendants => {
+                descendant_invalidations.dom_descendants.push(invalidation);
+            },
+            DependencyInvalidationKind::Siblings => {
+                sibling_invalidations.push(invalidation);
+            },
+            DependencyInvalidationKind::Parts => {
+                descendant_invalidations.parts.push(invalidation);
+            },
+            DependencyInvalidationKind::SlottedElements => {
+                descendant_invalidations
+                    .slotted_descendants
+                    .push(invalidation);
+            },
+        }
+        false
+    }
+
     /// Processes a given invalidation, potentially invalidating the style of
     /// the current element.
     ///
@@ -776,7 +837,20 @@ where
             CompoundSelectorMatchingResult::FullyMatched => {
                 debug!(" > Invalidation matched completely");
                 matched = true;
-                invalidated_self = true;
+                match invalidation.parent {
+                    None => invalidated_self = true,
+                    Some(parent) => {
+                        // We've matched a selector nested in :is() or
+                        // :where(), so the compound selector containing it
+                        // may have changed for this element.
+                        invalidated_self = self.invalidate_for_parent_dependency(
+                            parent,
+                            invalidation.scope,
+                            descendant_invalidations,
+                            sibling_invalidations,
+                        );
+                    },
+                }
             },
             CompoundSelectorMatchingResult::Matched {
                 next_combinator_offset,
@@ -854,6 +928,7 @@ where
                     scope: invalidation.scope,
                     offset: next_combinator_offset + 1,
                     matched_by_any_previous: false,
+                    parent: invalidation.parent,
                 };
 
                 debug!(
@@ -0,0 +1,106 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Invalidation of `:has()` selectors due to DOM mutations.
+//!
+//! While matching a `:has()` selector, the anchor element and every element
+//! looked at get flagged (see `ElementSelectorFlags`), so that when one of the
+//! latter changes we can walk the tree back to the anchors that may need to be
+//! restyled. The anchors then get their relative selector dependencies
+//! invalidated, see `InvalidationMap::relative_selector_dependencies`.
+
+use selectors::matching::ElementSelectorFlags;
+use selectors::Element;
+
+/// Calls `invalidate` with all the elements anchoring a `:has()` selector that
+/// may have looked at `element`, and thus may need to be restyled after a
+/// state or attribute change on it.
+pub fn invalidate_for_element_change<E, F, I>(element: &E, flags: F, mut invalidate: I)
+where
+    E: Element,
+    F: Fn(&E) -> ElementSelectorFlags,
+    I: FnMut(&E),
+{
+    let element_flags = flags(element);
+    if element_flags.contains(ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_SIBLING) {
+        invalidate_previous_sibling_anchors(element, &flags, &mut invalidate);
+    }
+
+    let ancestor_flags = ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_ANCESTOR |
+        ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_ANCESTOR_SIBLING;
+    if !element_flags.intersects(ancestor_flags) {
+        return;
+    }
+
+    let mut current = element.parent_element();
+    while let Some(ancestor) = current {
+        let flags_for_ancestor = flags(&ancestor);
+        if flags_for_ancestor.contains(ElementSelectorFlags::ANCHORS_RELATIVE_SELECTOR) {
+            invalidate(&ancestor);
+        }
+        if flags_for_ancestor
+            .contains(ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_SIBLING)
+        {
+            invalidate_previous_sibling_anchors(&ancestor, &flags, &mut invalidate);
+        }
+        // If the ancestor wasn't looked at by any relative selector anchored
+        // further up, there's nothing else to invalidate.
+        if !flags_for_ancestor.intersects(ancestor_flags) {
+            break;
+        }
+        current = ancestor.parent_element();
+    }
+}
+
+/// Calls `invalidate` with all the elements anchoring a `:has()` selector that
+/// may need to be restyled after the children of `parent` change.
+///
+/// `prev_sibling` is the element child right before the inserted or removed
+/// nodes, if any.
+pub fn invalidate_for_children_change<E, F, I>(
+    parent: &E,
+    prev_sibling: Option<&E>,
+    flags: F,
+    mut invalidate: I,
+) where
+    E: Element,
+    F: Fn(&E) -> ElementSelectorFlags,
+    I: FnMut(&E),
+{
+    if flags(parent).contains(ElementSelectorFlags::ANCHORS_RELATIVE_SELECTOR) {
+        invalidate(parent);
+    }
+
+    invalidate_for_element_change(parent, &flags, &mut invalidate);
+
+    // Sibling relative selectors anchored before the mutation may have started
+    // or stopped matching. Anchors further back than `prev_sibling` have
+    // flagged it if they looked past it.
+    let prev_sibling = match prev_sibling {
+        Some(sibling) => sibling,
+        None => return,
+    };
+    let sibling_flags = flags(prev_sibling);
+    if sibling_flags.contains(ElementSelectorFlags::ANCHORS_RELATIVE_SELECTOR) {
+        invalidate(prev_sibling);
+    }
+    if sibling_flags.contains(ElementSelectorFlags::RELATIVE_SELECTOR_SEARCH_DIRECTION_SIBLING) {
+        invalidate_previous_sibling_anchors(prev_sibling, &flags, &mut invalidate);
+    }
+}
+
+fn invalidate_previous_sibling_anchors<E, F, I>(element: &E, flags: &F, invalidate: &mut I)
+where
+    E: Element,
+    F: Fn(&E) -> ElementSelectorFlags,
+    I: FnMut(&E),
+{
+    let mut current = element.prev_sibling_element();
+    while let Some(sibling) = current {
+        if flags(&sibling).contains(ElementSelectorFlags::ANCHORS_RELATIVE_SELECTOR) {
+            invalidate(&sibling);
+        }
+        current = sibling.prev_sibling_element();
+    }
+}
@@ -151,7 +151,8 @@ where
         let state_changes = wrapper.state_changes();
         let snapshot = wrapper.snapshot().expect("has_snapshot lied");
 
-        if !snapshot.has_attrs() && state_changes.is_empty() {
+        let relative_selectors_invalidated = snapshot.relative_selectors_invalidated();
+        if !snapshot.has_attrs() && state_changes.is_empty() && !relative_selectors_invalidated {
             return false;
         }
 
@@ -368,6 +369,31 @@ where
         if !state_changes.is_empty() {
             self.collect_state_dependencies(&map.state_affecting_selectors, state_changes)
         }
+
+        if self.snapshot.relative_selectors_invalidated() {
+            self.collect_relative_selector_dependencies(&map.relative_selector_dependencies)
+        }
+    }
+
+    /// Collects the dependencies of the `:has()` selectors anchored at this
+    /// element.
+    ///
+    /// The snapshot doesn't know about the state of the rest of the tree, so
+    /// we can't tell whether these dependencies matched before the mutation,
+    /// and we invalidate for all of them instead.
+    fn collect_relative_selector_dependencies(&mut self, map: &'selectors SelectorMap<Dependency>) {
+        map.lookup_with_additional(
+            self.lookup_element,
+            self.matching_context.quirks_mode(),
+            self.removed_id,
+            self.classes_removed,
+            |dependency| {
+                if self.dependency_may_be_relevant(dependency) {
+                    self.note_dependency(dependency);
+                }
+                true
+            },
+        );
     }
 
     fn collect_dependencies_in_map(&mut self, map: &'selectors SelectorMap<Dependency>) {
@@ -452,14 +478,8 @@ where
             return;
         }
 
-        debug_assert_ne!(dependency.selector_offset, 0);
-        debug_assert_ne!(dependency.selector_offset, dependency.selector.len());
-
-        let invalidation = Invalidation::new(
-            &dependency.selector,
-            self.matching_context.current_host.clone(),
-            dependency.selector.len() - dependency.selector_offset + 1,
-        );
+        let invalidation =
+            Invalidation::for_dependency(dependency, self.matching_context.current_host.clone());
 
         match invalidation_kind {
             DependencyInvalidationKind::Element => unreachable!(),
@@ -442,7 +442,8 @@ impl StylesheetInvalidationSet {
                     }
                 }
             },
-            Document(..) | Namespace(..) | Import(..) | Media(..) | Supports(..) => {
+            Document(..) | Namespace(..) | Import(..) | Media(..) | Supports(..) |
+            Container(..) => {
                 // Do nothing, relevant nested rules are visited as part of the
                 // iteration.
             },
@@ -476,6 +477,19 @@ impl StylesheetInvalidationSet {
                 // guess.
                 self.fully_invalid = true;
             },
+            LayerBlock(..) | LayerStatement(..) => {
+                // Layer rules can change the order of any of the layers that
+                // were already declared, which affects the cascade of every
+                // layered rule.
+                debug!(" > Found @layer rule, marking the whole subtree invalid.");
+                self.fully_invalid = true;
+            },
+            Property(..) => {
+                // Registering a custom property changes how its values are
+                // computed and inherited on every element.
+                debug!(" > Found @property rule, marking the whole subtree invalid.");
+                self.fully_invalid = true;
+            },
         }
     }
 }
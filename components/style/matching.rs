@@ -430,6 +430,7 @@ trait PrivateMatchMethods: TElement {
         &self,
         context: &mut StyleContext<Self>,
         old_values: &mut Option<Arc<ComputedValues>>,
+        unanimated_values: &mut Option<Arc<ComputedValues>>,
         new_values: &mut Arc<ComputedValues>,
         _restyle_hint: RestyleHint,
         _important_rules_changed: bool,
@@ -437,9 +438,18 @@ trait PrivateMatchMethods: TElement {
         use crate::animation;
         use crate::dom::TNode;
 
+        // Changes caused by the animations that script runs shouldn't start
+        // transitions, so these start from the style the element had before
+        // the effects of those animations were applied to it.
+        let mut unanimated_old_values = unanimated_values.take().filter(|_| old_values.is_some());
+        let mut before_change_values = match unanimated_old_values {
+            Some(ref mut values) => Some(values),
+            None => old_values.as_mut(),
+        };
+
         let mut possibly_expired_animations = vec![];
         let shared_context = context.shared;
-        if let Some(ref mut old) = *old_values {
+        if let Some(ref mut old) = before_change_values {
             // FIXME(emilio, #20116): This makes no sense.
             self.update_animations_for_cascade(
                 shared_context,
@@ -462,16 +472,33 @@ trait PrivateMatchMethods: TElement {
 
         // Trigger transitions if necessary. This will reset `new_values` back
         // to its old value if it did trigger a transition.
-        if let Some(ref values) = *old_values {
+        if let Some(values) = before_change_values {
             animation::start_transitions_if_applicable(
                 new_animations_sender,
                 this_opaque,
-                &values,
+                values,
                 new_values,
+                shared_context.stylist.device(),
                 &shared_context.timer,
                 &possibly_expired_animations,
             );
         }
+
+        let keyframe_effect_samples = self.keyframe_effect_samples();
+        if !keyframe_effect_samples.is_empty() {
+            *unanimated_values = Some(new_values.clone());
+        }
+
+        // Apply the effects of the animations driven by script on top of
+        // everything else.
+        for sample in keyframe_effect_samples {
+            animation::update_style_for_keyframe_effect_sample::<Self>(
+                shared_context,
+                sample,
+                new_values,
+                &context.thread_local.font_metrics_provider,
+            );
+        }
     }
 
     /// Computes and applies non-redundant damage.
@@ -691,9 +718,19 @@ pub trait MatchMethods: TElement {
     ) -> ChildCascadeRequirement {
         use std::cmp;
 
+        #[cfg(feature = "gecko")]
+        self.process_animations(
+            context,
+            &mut data.styles.primary,
+            &mut new_styles.primary.style.0,
+            data.hint,
+            important_rules_changed,
+        );
+        #[cfg(feature = "servo")]
         self.process_animations(
             context,
             &mut data.styles.primary,
+            &mut data.unanimated_primary,
             &mut new_styles.primary.style.0,
             data.hint,
             important_rules_changed,
@@ -6,7 +6,7 @@
 //!
 //! [mq]: https://drafts.csswg.org/mediaqueries/
 
-mod media_condition;
+pub mod media_condition;
 mod media_list;
 mod media_query;
 #[macro_use]
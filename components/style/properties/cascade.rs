@@ -5,7 +5,7 @@
 //! The main cascading algorithm of the style system.
 
 use crate::context::QuirksMode;
-use crate::custom_properties::CustomPropertiesBuilder;
+use crate::custom_properties::{CustomPropertiesBuilder, Name, VariableValue};
 use crate::dom::TElement;
 use crate::font_metrics::FontMetricsProvider;
 use crate::logical_geometry::WritingMode;
@@ -17,6 +17,7 @@ use crate::properties::CASCADE_PROPERTY;
 use crate::rule_cache::{RuleCache, RuleCacheConditions};
 use crate::rule_tree::StrongRuleNode;
 use crate::selector_parser::PseudoElement;
+use crate::stylesheets::container_rule::{ContainerSizeQuery, ContainerUnitSizes};
 use crate::stylesheets::{Origin, PerOrigin};
 use servo_arc::Arc;
 use crate::shared_lock::StylesheetGuards;
@@ -251,6 +252,7 @@ where
         let mut builder = CustomPropertiesBuilder::new(
             inherited_style.custom_properties(),
             device.environment(),
+            device.custom_property_registry(),
         );
 
         for (declaration, origin) in iter_declarations() {
@@ -263,6 +265,19 @@ where
         builder.build()
     };
 
+    // Pseudo-elements can query their originating element, whose style is
+    // the parent style, other elements only their ancestors.
+    let container_sizes_resolver = || match element {
+        Some(element) => {
+            let originating_element_style = match pseudo {
+                Some(..) => parent_style_ignoring_first_line,
+                None => None,
+            };
+            ContainerUnitSizes::for_element(element, originating_element_style)
+        },
+        None => ContainerUnitSizes::default(),
+    };
+
     let mut context = computed::Context {
         is_root_element: pseudo.is_none() && element.map_or(false, |e| e.is_root()),
         // We'd really like to own the rules here to avoid refcount traffic, but
@@ -283,6 +298,7 @@ where
         font_metrics_provider,
         quirks_mode,
         rule_cache_conditions: RefCell::new(rule_cache_conditions),
+        container_size_query: ContainerSizeQuery::new(&container_sizes_resolver),
     };
 
     let using_cached_reset_properties = {
@@ -291,6 +307,8 @@ where
         cascade
             .apply_properties::<EarlyProperties, _>(ApplyResetProperties::Yes, declarations.iter().cloned());
 
+        cascade.compute_registered_custom_properties();
+
         cascade.compute_visited_style_if_needed(
             element,
             parent_style,
@@ -572,6 +590,79 @@ impl<'a, 'b: 'a> Cascade<'a, 'b> {
         self.context.builder.writing_mode = writing_mode;
     }
 
+    /// Computes the values of registered custom properties, now that the
+    /// early properties their values may depend on, like font-size, are
+    /// known.
+    ///
+    /// Values that don't match the syntax of their registration are invalid
+    /// at computed-value time, and behave as `unset`.
+    fn compute_registered_custom_properties(&mut self) {
+        let device = self.context.builder.device;
+        let registry = device.custom_property_registry();
+        if registry.is_empty() {
+            return;
+        }
+
+        let parent = self.context.builder.inherited_style.custom_properties();
+        let mut changes = SmallVec::<[(Name, Option<Arc<VariableValue>>); 4]>::new();
+        {
+            let context = &*self.context;
+            let map = context.builder.custom_properties.as_ref();
+            for registration in registry.iter() {
+                let name = &registration.name;
+                let initial_value = || {
+                    registration.initial_value.as_ref().map(|value| {
+                        registration
+                            .compute(value, context)
+                            .unwrap_or_else(|()| value.clone())
+                    })
+                };
+                let parent_value = parent.and_then(|p| p.get(name));
+                let new_value = match map.and_then(|m| m.get(name)) {
+                    Some(value) => {
+                        // Inherited values are already computed.
+                        if parent_value.map_or(false, |p| Arc::ptr_eq(p, value)) {
+                            continue;
+                        }
+                        match registration.compute(value, context) {
+                            Ok(ref computed) if Arc::ptr_eq(computed, value) => continue,
+                            Ok(computed) => Some(computed),
+                            Err(()) if registration.inherits && parent_value.is_some() => {
+                                parent_value.cloned()
+                            },
+                            Err(()) => initial_value(),
+                        }
+                    },
+                    None => match initial_value() {
+                        Some(value) => Some(value),
+                        None => continue,
+                    },
+                };
+                changes.push((name.clone(), new_value));
+            }
+        }
+
+        if changes.is_empty() {
+            return;
+        }
+        let map = Arc::make_mut(
+            self.context
+                .builder
+                .custom_properties
+                .get_or_insert_with(Default::default),
+        );
+        for (name, value) in changes {
+            match value {
+                Some(value) => {
+                    map.insert(name, value);
+                },
+                None => {
+                    map.remove(&name);
+                },
+            }
+        }
+    }
+
     fn compute_visited_style_if_needed<E>(
         &mut self,
         element: Option<E>,
@@ -63,6 +63,17 @@ bitflags! {
         ///
         /// Only used in Servo.
         const CAN_BE_FRAGMENTED = 1 << 10;
+
+        /// Whether the style or any of the ancestors is a query container.
+        ///
+        /// Only used in Servo.
+        const IS_IN_QUERY_CONTAINER_SUBTREE = 1 << 11;
+
+        /// Whether any value on our style is relative to the size of a query
+        /// container.
+        ///
+        /// Only used in Servo.
+        const DEPENDS_ON_QUERY_CONTAINER_SIZE = 1 << 12;
     }
 }
 
@@ -72,6 +83,7 @@ impl ComputedValueFlags {
     fn inherited_flags() -> Self {
         ComputedValueFlags::IS_RELEVANT_LINK_VISITED |
         ComputedValueFlags::CAN_BE_FRAGMENTED |
+        ComputedValueFlags::IS_IN_QUERY_CONTAINER_SUBTREE |
         ComputedValueFlags::IS_IN_PSEUDO_ELEMENT_SUBTREE |
         ComputedValueFlags::HAS_TEXT_DECORATION_LINES
     }
@@ -336,6 +336,7 @@ class Longhand(object):
                 "Clear",
                 "ColumnCount",
                 "Contain",
+                "ContainerType",
                 "Display",
                 "FillRule",
                 "Float",
@@ -12,13 +12,15 @@ use crate::custom_properties::{CssEnvironment, CustomPropertiesBuilder};
 use crate::error_reporting::{ContextualParseError, ParseErrorReporter};
 use crate::parser::ParserContext;
 use crate::properties::animated_properties::{AnimationValue, AnimationValueMap};
+use crate::properties_and_values::PropertyRegistry;
 use crate::selector_parser::SelectorImpl;
 use crate::shared_lock::Locked;
 use crate::str::{CssString, CssStringBorrow, CssStringWriter};
 use crate::stylesheets::{CssRuleType, Origin, UrlExtraData};
 use crate::values::computed::Context;
 use cssparser::{parse_important, CowRcStr, DeclarationListParser, ParserInput};
-use cssparser::{AtRuleParser, DeclarationParser, Delimiter, ParseErrorKind, Parser};
+use cssparser::{AtRuleParser, AtRuleType, QualifiedRuleParser, Token};
+use cssparser::{DeclarationParser, Delimiter, ParseErrorKind, Parser, ParserState};
 use itertools::Itertools;
 use selectors::SelectorList;
 use smallbitvec::{self, SmallBitVec};
@@ -795,6 +797,7 @@ impl PropertyDeclarationBlock {
         // meaningful out of here... All this code path is so terribly hacky
         // ;_;.
         let env = CssEnvironment;
+        let registry = PropertyRegistry::default();
 
         let custom_properties = if let Some(cv) = computed_values {
             // If there are extra custom properties for this declaration block,
@@ -802,7 +805,7 @@ impl PropertyDeclarationBlock {
             if let Some(block) = custom_properties_block {
                 // FIXME(emilio): This is not super-efficient here, and all this
                 // feels like a hack anyway...
-                block.cascade_custom_properties(cv.custom_properties(), &env)
+                block.cascade_custom_properties(cv.custom_properties(), &env, &registry)
             } else {
                 cv.custom_properties().cloned()
             }
@@ -874,6 +877,7 @@ impl PropertyDeclarationBlock {
         self.cascade_custom_properties(
             context.style().custom_properties(),
             context.device().environment(),
+            context.device().custom_property_registry(),
         )
     }
 
@@ -884,8 +888,10 @@ impl PropertyDeclarationBlock {
         &self,
         inherited_custom_properties: Option<&Arc<crate::custom_properties::CustomPropertiesMap>>,
         environment: &CssEnvironment,
+        registry: &PropertyRegistry,
     ) -> Option<Arc<crate::custom_properties::CustomPropertiesMap>> {
-        let mut builder = CustomPropertiesBuilder::new(inherited_custom_properties, environment);
+        let mut builder =
+            CustomPropertiesBuilder::new(inherited_custom_properties, environment, registry);
 
         for declaration in self.normal_declaration_iter() {
             if let PropertyDeclaration::Custom(ref declaration) = *declaration {
@@ -1476,3 +1482,154 @@ pub fn parse_property_declaration_list(
 
     block
 }
+
+/// Parse the contents of a style rule, which per CSS Nesting may contain
+/// nested rules besides property declarations. The nested rules are parsed
+/// with `rule_parser`, and returned along with the declaration block.
+///
+/// Declarations are tried first, and anything that fails to parse as one is
+/// parsed as a rule instead, so that something like `div:hover { .. }` works.
+///
+/// <https://drafts.csswg.org/css-nesting/#syntax>
+pub fn parse_property_declaration_list_with_rules<'i, 't, R>(
+    context: &ParserContext,
+    input: &mut Parser<'i, 't>,
+    selectors: Option<&SelectorList<SelectorImpl>>,
+    rule_parser: &mut R,
+) -> (PropertyDeclarationBlock, Vec<R::QualifiedRule>)
+where
+    R: QualifiedRuleParser<'i, Error = StyleParseErrorKind<'i>>,
+    R: AtRuleParser<
+        'i,
+        AtRule = <R as QualifiedRuleParser<'i>>::QualifiedRule,
+        Error = StyleParseErrorKind<'i>,
+    >,
+{
+    let mut declarations = SourcePropertyDeclaration::new();
+    let mut block = PropertyDeclarationBlock::new();
+    let mut rules = Vec::new();
+    let mut parser = PropertyDeclarationParser {
+        context,
+        last_parsed_property_id: None,
+        declarations: &mut declarations,
+    };
+    let mut errors = SmallParseErrorVec::new();
+    loop {
+        let start = input.state();
+        let token = match input.next() {
+            Ok(token) => token.clone(),
+            Err(..) => break,
+        };
+        let result = match token {
+            Token::Semicolon => continue,
+            Token::AtKeyword(name) => parse_nested_at_rule(name, &start, input, rule_parser),
+            Token::Ident(name) => {
+                let result = input.parse_until_after(Delimiter::Semicolon, |input| {
+                    input.expect_colon()?;
+                    parser.parse_value(name, input)
+                });
+                let error = match result {
+                    Ok(importance) => {
+                        block.extend(parser.declarations.drain(), importance);
+                        continue;
+                    },
+                    Err(error) => error,
+                };
+                parser.declarations.clear();
+
+                // It may be a nested rule rather than a declaration.
+                let after_declaration = input.state();
+                input.reset(&start);
+                if let Ok(rule) = parse_nested_qualified_rule(input, rule_parser) {
+                    rules.push(rule);
+                    continue;
+                }
+                input.reset(&after_declaration);
+
+                if context.error_reporting_enabled() {
+                    let slice = input.slice_from(start.position());
+                    let property = parser.last_parsed_property_id.take();
+                    errors.push((error, slice, property));
+                }
+                continue;
+            },
+            _ => {
+                input.reset(&start);
+                parse_nested_qualified_rule(input, rule_parser)
+            },
+        };
+
+        match result {
+            Ok(rule) => rules.push(rule),
+            Err(error) => {
+                let location = error.location;
+                let slice = input.slice_from(start.position());
+                let error = ContextualParseError::InvalidRule(slice, error);
+                context.log_css_error(location, error);
+            },
+        }
+    }
+
+    if !errors.is_empty() {
+        report_css_errors(context, &block, selectors, &mut errors)
+    }
+
+    (block, rules)
+}
+
+/// Parses a qualified rule nested in a style rule, whose prelude, unlike at
+/// the top level, ends at a semicolon.
+fn parse_nested_qualified_rule<'i, 't, R>(
+    input: &mut Parser<'i, 't>,
+    parser: &mut R,
+) -> Result<R::QualifiedRule, ParseError<'i>>
+where
+    R: QualifiedRuleParser<'i, Error = StyleParseErrorKind<'i>>,
+{
+    let location = input.current_source_location();
+    let delimiters = Delimiter::CurlyBracketBlock | Delimiter::Semicolon;
+    let prelude = input.parse_until_before(delimiters, |input| parser.parse_prelude(input));
+    match *input.next()? {
+        Token::CurlyBracketBlock => {},
+        ref t => return Err(location.new_unexpected_token_error(t.clone())),
+    }
+    let prelude = prelude?;
+    input.parse_nested_block(|input| parser.parse_block(prelude, location, input))
+}
+
+/// Parses an at-rule nested in a style rule, whose at-keyword has already
+/// been consumed.
+fn parse_nested_at_rule<'i, 't, R>(
+    name: CowRcStr<'i>,
+    start: &ParserState,
+    input: &mut Parser<'i, 't>,
+    parser: &mut R,
+) -> Result<<R as AtRuleParser<'i>>::AtRule, ParseError<'i>>
+where
+    R: AtRuleParser<'i, Error = StyleParseErrorKind<'i>>,
+{
+    let location = start.source_location();
+    let delimiters = Delimiter::Semicolon | Delimiter::CurlyBracketBlock;
+    let prelude = input.parse_until_before(delimiters, |input| parser.parse_prelude(name, input));
+    let prelude = match prelude {
+        Ok(prelude) => prelude,
+        Err(error) => {
+            // Skip the rest of the rule, if any.
+            let _ = input.next();
+            return Err(error);
+        },
+    };
+    match prelude {
+        AtRuleType::WithoutBlock(prelude) => match input.next() {
+            Ok(&Token::Semicolon) | Err(..) => Ok(parser.rule_without_block(prelude, location)),
+            Ok(t) => Err(location.new_unexpected_token_error(t.clone())),
+        },
+        AtRuleType::WithBlock(prelude) => match input.next() {
+            Ok(&Token::CurlyBracketBlock) => {
+                input.parse_nested_block(|input| parser.parse_block(prelude, location, input))
+            },
+            Ok(t) => Err(location.new_unexpected_token_error(t.clone())),
+            Err(e) => Err(e.into()),
+        },
+    }
+}
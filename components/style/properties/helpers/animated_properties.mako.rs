@@ -20,6 +20,10 @@ use smallvec::SmallVec;
 use std::ptr;
 use std::mem;
 use crate::hash::FxHashMap;
+use crate::media_queries::Device;
+use crate::properties_and_values::value::ComputedValue as ComputedCustomValue;
+use std::borrow::Cow;
+use style_traits::ToCss;
 use super::ComputedValues;
 use crate::values::animated::{Animate, Procedure, ToAnimatedValue, ToAnimatedZero};
 use crate::values::animated::effects::AnimatedFilter;
@@ -73,25 +77,83 @@ pub enum AnimatedProperty {
             ${prop.camel_case}(${value_type}, ${value_type}),
         % endif
     % endfor
+    /// A custom property.
+    Custom(AnimatedCustomProperty),
+}
+
+/// An interpolation between two computed values of a custom property.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(MallocSizeOf))]
+pub struct AnimatedCustomProperty {
+    /// The name of the property, without the leading `--`.
+    pub name: crate::custom_properties::Name,
+    /// The value to animate from.
+    #[cfg_attr(feature = "servo", ignore_malloc_size_of = "Arc")]
+    pub from: Arc<crate::custom_properties::VariableValue>,
+    /// The value to animate to.
+    #[cfg_attr(feature = "servo", ignore_malloc_size_of = "Arc")]
+    pub to: Arc<crate::custom_properties::VariableValue>,
+    /// The typed values to interpolate between, if the property is registered
+    /// with a syntax both values match. Otherwise the property animates
+    /// discretely.
+    #[cfg_attr(feature = "servo", ignore_malloc_size_of = "Computed values are small")]
+    pub typed: Option<(ComputedCustomValue, ComputedCustomValue)>,
+}
+
+impl AnimatedCustomProperty {
+    /// Creates an interpolation between two values of the given custom
+    /// property, using its registration in `device`, if any.
+    pub fn new(
+        name: crate::custom_properties::Name,
+        from: Arc<crate::custom_properties::VariableValue>,
+        to: Arc<crate::custom_properties::VariableValue>,
+        device: &Device,
+    ) -> Self {
+        let typed = device.custom_property_registry().get(&name).and_then(|registration| {
+            Some((registration.typed_value(&from, device)?, registration.typed_value(&to, device)?))
+        });
+        AnimatedCustomProperty { name, from, to, typed }
+    }
+
+    /// Creates an interpolation between the values of the given custom property
+    /// in two styles, if the property has a value in both.
+    pub fn from_styles(
+        name: &crate::custom_properties::Name,
+        old_style: &ComputedValues,
+        new_style: &ComputedValues,
+        device: &Device,
+    ) -> Option<Self> {
+        let from = old_style.custom_properties()?.get(name)?;
+        let to = new_style.custom_properties()?.get(name)?;
+        Some(Self::new(name.clone(), from.clone(), to.clone(), device))
+    }
+
+    /// Returns the value of the property at `progress`.
+    pub fn value_at(&self, progress: f64) -> Arc<crate::custom_properties::VariableValue> {
+        if let Some((ref from, ref to)) = self.typed {
+            if let Ok(value) = from.animate(to, Procedure::Interpolate { progress }) {
+                let css = value.to_css_string();
+                return Arc::new(crate::custom_properties::VariableValue::from_computed_css(css));
+            }
+        }
+        // https://drafts.csswg.org/web-animations/#discrete-animation-type
+        if progress < 0.5 { self.from.clone() } else { self.to.clone() }
+    }
 }
 
 impl AnimatedProperty {
-    /// Get the id of the property we're animating.
-    pub fn id(&self) -> LonghandId {
+    /// Get the name of this property.
+    pub fn name(&self) -> Cow<'static, str> {
         match *self {
             % for prop in data.longhands:
             % if prop.animatable and not prop.logical:
-            AnimatedProperty::${prop.camel_case}(..) => LonghandId::${prop.camel_case},
+            AnimatedProperty::${prop.camel_case}(..) => LonghandId::${prop.camel_case}.name().into(),
             % endif
             % endfor
+            AnimatedProperty::Custom(ref custom) => format!("--{}", custom.name).into(),
         }
     }
 
-    /// Get the name of this property.
-    pub fn name(&self) -> &'static str {
-        self.id().name()
-    }
-
     /// Whether this interpolation does animate, that is, whether the start and
     /// end values are different.
     pub fn does_animate(&self) -> bool {
@@ -101,6 +163,7 @@ impl AnimatedProperty {
                     AnimatedProperty::${prop.camel_case}(ref from, ref to) => from != to,
                 % endif
             % endfor
+            AnimatedProperty::Custom(ref custom) => custom.from != custom.to,
         }
     }
 
@@ -115,6 +178,9 @@ impl AnimatedProperty {
                     }
                 % endif
             % endfor
+            (&AnimatedProperty::Custom(ref this), &AnimatedProperty::Custom(ref other)) => {
+                this.name == other.name && this.to == other.to
+            }
             _ => false,
         }
     }
@@ -146,6 +212,9 @@ impl AnimatedProperty {
                     }
                 % endif
                 % endfor
+                AnimatedProperty::Custom(ref custom) => {
+                    style.set_custom_property(custom.name.clone(), custom.value_at(progress));
+                }
             }
         }
     }
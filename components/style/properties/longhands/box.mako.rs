@@ -498,7 +498,7 @@ ${helpers.predefined_type(
     "break-after",
     "BreakBetween",
     "computed::BreakBetween::Auto",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     needs_context=False,
     spec="https://drafts.csswg.org/css-break/#propdef-break-after",
     animation_value_type="discrete",
@@ -508,7 +508,7 @@ ${helpers.predefined_type(
     "break-before",
     "BreakBetween",
     "computed::BreakBetween::Auto",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     needs_context=False,
     spec="https://drafts.csswg.org/css-break/#propdef-break-before",
     animation_value_type="discrete",
@@ -518,7 +518,7 @@ ${helpers.predefined_type(
     "break-inside",
     "BreakWithin",
     "computed::BreakWithin::Auto",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     needs_context=False,
     alias="page-break-inside",
     spec="https://drafts.csswg.org/css-break/#propdef-break-inside",
@@ -622,6 +622,24 @@ ${helpers.predefined_type(
     enabled_in="chrome",
 )}
 
+${helpers.predefined_type(
+    "container-type",
+    "ContainerType",
+    "computed::ContainerType::Normal",
+    engines="servo-2013 servo-2020",
+    animation_value_type="none",
+    spec="https://drafts.csswg.org/css-contain-3/#container-type",
+)}
+
+${helpers.predefined_type(
+    "container-name",
+    "ContainerName",
+    "computed::ContainerName::none()",
+    engines="servo-2013 servo-2020",
+    animation_value_type="none",
+    spec="https://drafts.csswg.org/css-contain-3/#container-name",
+)}
+
 // Non-standard
 ${helpers.predefined_type(
     "-moz-appearance",
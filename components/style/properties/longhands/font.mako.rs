@@ -39,6 +39,8 @@ ${helpers.single_keyword_system(
     "normal small-caps",
     engines="gecko servo-2013 servo-2020",
     extra_gecko_values="all-small-caps petite-caps all-petite-caps unicase titling-caps",
+    extra_servo_2013_values="all-small-caps petite-caps all-petite-caps unicase titling-caps",
+    extra_servo_2020_values="all-small-caps petite-caps all-petite-caps unicase titling-caps",
     gecko_constant_prefix="NS_FONT_VARIANT_CAPS",
     gecko_ffi_name="mFont.variantCaps",
     spec="https://drafts.csswg.org/css-fonts/#propdef-font-variant-caps",
@@ -113,11 +115,12 @@ ${helpers.single_keyword_system(
 ${helpers.predefined_type(
     "font-variant-alternates",
     "FontVariantAlternates",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     initial_value="computed::FontVariantAlternates::get_initial_value()",
     initial_specified_value="specified::FontVariantAlternates::get_initial_specified_value()",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-fonts/#propdef-font-variant-alternates",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 ${helpers.predefined_type(
@@ -133,21 +136,23 @@ ${helpers.predefined_type(
 ${helpers.predefined_type(
     "font-variant-ligatures",
     "FontVariantLigatures",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     initial_value="computed::FontVariantLigatures::empty()",
     initial_specified_value="specified::FontVariantLigatures::empty()",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-fonts/#propdef-font-variant-ligatures",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 ${helpers.predefined_type(
     "font-variant-numeric",
     "FontVariantNumeric",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     initial_value="computed::FontVariantNumeric::empty()",
     initial_specified_value="specified::FontVariantNumeric::empty()",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-fonts/#propdef-font-variant-numeric",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 ${helpers.single_keyword_system(
@@ -163,24 +168,26 @@ ${helpers.single_keyword_system(
 ${helpers.predefined_type(
     "font-feature-settings",
     "FontFeatureSettings",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     initial_value="computed::FontFeatureSettings::normal()",
     initial_specified_value="specified::FontFeatureSettings::normal()",
     extra_prefixes="moz:layout.css.prefixes.font-features",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-fonts/#propdef-font-feature-settings",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 ${helpers.predefined_type(
     "font-variation-settings",
     "FontVariationSettings",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     gecko_pref="layout.css.font-variations.enabled",
     has_effect_on_gecko_scrollbars=False,
     initial_value="computed::FontVariationSettings::normal()",
     initial_specified_value="specified::FontVariationSettings::normal()",
     animation_value_type="ComputedValue",
-    spec="https://drafts.csswg.org/css-fonts-4/#propdef-font-variation-settings"
+    spec="https://drafts.csswg.org/css-fonts-4/#propdef-font-variation-settings",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 ${helpers.predefined_type(
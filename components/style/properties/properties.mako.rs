@@ -3043,6 +3043,17 @@ impl ComputedValues {
             }
         }
     }
+
+    /// Sets the computed value of a custom property, used to apply animated
+    /// values.
+    pub fn set_custom_property(
+        &mut self,
+        name: crate::custom_properties::Name,
+        value: Arc<crate::custom_properties::VariableValue>,
+    ) {
+        let map = self.custom_properties.get_or_insert_with(Default::default);
+        Arc::make_mut(map).insert(name, value);
+    }
 }
 
 #[cfg(feature = "servo")]
@@ -319,7 +319,7 @@ ${helpers.two_properties_shorthand(
 )}
 
 <%helpers:shorthand
-    engines="gecko"
+    engines="gecko servo-2013 servo-2020"
     name="page-break-before"
     flags="SHORTHAND_IN_GETCS IS_LEGACY_SHORTHAND"
     sub_properties="break-before"
@@ -343,7 +343,7 @@ ${helpers.two_properties_shorthand(
 </%helpers:shorthand>
 
 <%helpers:shorthand
-    engines="gecko"
+    engines="gecko servo-2013 servo-2020"
     name="page-break-after"
     flags="SHORTHAND_IN_GETCS IS_LEGACY_SHORTHAND"
     sub_properties="break-after"
@@ -498,3 +498,40 @@ ${helpers.two_properties_shorthand(
         }
     }
 </%helpers:shorthand>
+
+<%helpers:shorthand
+    engines="servo-2013 servo-2020"
+    name="container"
+    sub_properties="container-name container-type"
+    spec="https://drafts.csswg.org/css-contain-3/#container-shorthand"
+>
+    use crate::parser::Parse;
+    use crate::values::specified::box_::{ContainerName, ContainerType};
+
+    pub fn parse_value<'i>(
+        context: &ParserContext,
+        input: &mut Parser<'i, '_>,
+    ) -> Result<Longhands, ParseError<'i>> {
+        let container_name = ContainerName::parse(context, input)?;
+        let container_type = if input.try(|input| input.expect_delim('/')).is_ok() {
+            ContainerType::parse(context, input)?
+        } else {
+            ContainerType::Normal
+        };
+        Ok(expanded! {
+            container_name: container_name,
+            container_type: container_type,
+        })
+    }
+
+    impl<'a> ToCss for LonghandsToSerialize<'a> {
+        fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result where W: fmt::Write {
+            self.container_name.to_css(dest)?;
+            if *self.container_type != ContainerType::Normal {
+                dest.write_str(" / ")?;
+                self.container_type.to_css(dest)?;
+            }
+            Ok(())
+        }
+    }
+</%helpers:shorthand>
@@ -19,14 +19,14 @@
         ${'font-size-adjust' if engine == 'gecko' else ''}
         ${'font-kerning' if engine == 'gecko' else ''}
         ${'font-optical-sizing' if engine == 'gecko' else ''}
-        ${'font-variant-alternates' if engine == 'gecko' else ''}
+        font-variant-alternates
         ${'font-variant-east-asian' if engine == 'gecko' else ''}
-        ${'font-variant-ligatures' if engine == 'gecko' else ''}
-        ${'font-variant-numeric' if engine == 'gecko' else ''}
+        font-variant-ligatures
+        font-variant-numeric
         ${'font-variant-position' if engine == 'gecko' else ''}
         ${'font-language-override' if engine == 'gecko' else ''}
-        ${'font-feature-settings' if engine == 'gecko' else ''}
-        ${'font-variation-settings' if engine == 'gecko' else ''}
+        font-feature-settings
+        font-variation-settings
     "
     derive_value_info="False"
     spec="https://drafts.csswg.org/css-fonts-3/#propdef-font"
@@ -46,12 +46,14 @@
                                 variant_ligatures variant_numeric \
                                 variant_position feature_settings \
                                 variation_settings optical_sizing".split()
+        servo_sub_properties = "variant_alternates variant_ligatures \
+                                variant_numeric feature_settings \
+                                variation_settings".split()
+        reset_sub_properties = gecko_sub_properties if engine == "gecko" else servo_sub_properties
     %>
-    % if engine == "gecko":
-        % for prop in gecko_sub_properties:
-            use crate::properties::longhands::font_${prop};
-        % endfor
-    % endif
+    % for prop in reset_sub_properties:
+        use crate::properties::longhands::font_${prop};
+    % endfor
     use self::font_family::SpecifiedValue as FontFamily;
 
     pub fn parse_value<'i, 't>(
@@ -145,11 +147,9 @@
             font_size: size,
             line_height: line_height.unwrap_or(LineHeight::normal()),
             font_family: family,
-            % if engine == "gecko":
-                % for name in gecko_sub_properties:
-                    font_${name}: font_${name}::get_initial_specified_value(),
-                % endfor
-            % endif
+            % for name in reset_sub_properties:
+                font_${name}: font_${name}::get_initial_specified_value(),
+            % endfor
         })
     }
 
@@ -190,6 +190,12 @@
             }
             % endif
             % endfor
+            % else:
+            % for name in servo_sub_properties:
+            if self.font_${name} != &font_${name}::get_initial_specified_value() {
+                return Ok(());
+            }
+            % endfor
             % endif
 
             // Only font-stretch keywords are allowed as part as the font
@@ -298,10 +304,10 @@
                     engines="gecko servo-2013"
                     flags="SHORTHAND_IN_GETCS"
                     sub_properties="font-variant-caps
-                                    ${'font-variant-alternates' if engine == 'gecko' else ''}
+                                    font-variant-alternates
                                     ${'font-variant-east-asian' if engine == 'gecko' else ''}
-                                    ${'font-variant-ligatures' if engine == 'gecko' else ''}
-                                    ${'font-variant-numeric' if engine == 'gecko' else ''}
+                                    font-variant-ligatures
+                                    font-variant-numeric
                                     ${'font-variant-position' if engine == 'gecko' else ''}"
                     spec="https://drafts.csswg.org/css-fonts-3/#propdef-font-variant">
     <% gecko_sub_properties = "alternates east_asian ligatures numeric position".split() %>
@@ -309,12 +315,13 @@
         sub_properties = ["caps"]
         if engine == "gecko":
             sub_properties += gecko_sub_properties
+        else:
+            sub_properties += ["alternates", "ligatures", "numeric"]
     %>
 
 % for prop in sub_properties:
     use crate::properties::longhands::font_variant_${prop};
 % endfor
-    #[allow(unused_imports)]
     use crate::values::specified::FontVariantLigatures;
 
     pub fn parse_value<'i, 't>(
@@ -330,9 +337,7 @@
         } else if input.try(|input| input.expect_ident_matching("none")).is_ok() {
             // The 'none' value sets 'font-variant-ligatures' to 'none' and resets all other sub properties
             // to their initial value.
-        % if engine == "gecko":
             ligatures = Some(FontVariantLigatures::none());
-        % endif
         } else {
             let mut has_custom_value: bool = false;
             loop {
@@ -370,11 +375,7 @@
         fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result where W: fmt::Write {
 
             let has_none_ligatures =
-            % if engine == "gecko":
                 self.font_variant_ligatures == &FontVariantLigatures::none();
-            % else:
-                false;
-            % endif
 
             const TOTAL_SUBPROPS: usize = ${len(sub_properties)};
             let mut nb_normals = 0;
@@ -0,0 +1,14 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Support for [registered custom properties][registration], via `@property`
+//! rules and `CSS.registerProperty()`.
+//!
+//! [registration]: https://drafts.css-houdini.org/css-properties-values-api/
+
+pub mod registry;
+pub mod syntax;
+pub mod value;
+
+pub use self::registry::{PropertyRegistration, PropertyRegistry};
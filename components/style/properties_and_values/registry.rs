@@ -0,0 +1,211 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Registrations of custom properties.
+//!
+//! <https://drafts.css-houdini.org/css-properties-values-api/#the-registered-property-set>
+
+use super::syntax::Descriptor;
+use super::value::{ComputedValue, SpecifiedValue};
+use crate::context::QuirksMode;
+use crate::custom_properties::{Name, VariableValue};
+use crate::media_queries::Device;
+use crate::parser::ParserContext;
+use crate::selector_map::PrecomputedHashMap;
+use crate::stylesheets::{CssRuleType, Origin, UrlExtraData};
+use crate::values::computed::{Context, ToComputedValue};
+use cssparser::{ParseError as CssParseError, Parser, ParserInput, Token};
+use servo_arc::Arc;
+use style_traits::{ParsingMode, ToCss};
+
+/// The registration of a custom property, from either an `@property` rule or
+/// a call to `CSS.registerProperty()`.
+#[derive(Clone, Debug, ToShmem)]
+pub struct PropertyRegistration {
+    /// The name of the property, without the leading `--`.
+    pub name: Name,
+    /// The syntax the values of this property must match.
+    pub syntax: Descriptor,
+    /// Whether this property inherits by default.
+    pub inherits: bool,
+    /// The initial value of this property. It's only optional for the
+    /// universal syntax.
+    pub initial_value: Option<Arc<VariableValue>>,
+    /// The url data used to resolve relative urls in values of this property.
+    pub url_data: UrlExtraData,
+}
+
+impl PropertyRegistration {
+    /// Parses `css` as a specified value of this property.
+    fn parse_specified(&self, css: &str, quirks_mode: QuirksMode) -> Result<SpecifiedValue, ()> {
+        let context = ParserContext::new(
+            Origin::Author,
+            &self.url_data,
+            Some(CssRuleType::Style),
+            ParsingMode::DEFAULT,
+            quirks_mode,
+            None,
+            None,
+        );
+        let mut input = ParserInput::new(css);
+        let mut input = Parser::new(&mut input);
+        SpecifiedValue::parse(&context, &self.syntax, &mut input).map_err(|_| ())
+    }
+
+    /// Computes a substituted value of this property, returning an error if
+    /// it doesn't match the syntax of the registration, which makes the
+    /// property invalid at computed-value time.
+    ///
+    /// The same value is returned if its serialization doesn't change.
+    pub fn compute(
+        &self,
+        value: &Arc<VariableValue>,
+        context: &Context,
+    ) -> Result<Arc<VariableValue>, ()> {
+        if self.syntax.is_universal() {
+            return Ok(value.clone());
+        }
+        let specified = self.parse_specified(value.css(), context.quirks_mode)?;
+        let css = specified.to_computed_value(context).to_css_string();
+        if css == value.css() {
+            return Ok(value.clone());
+        }
+        Ok(Arc::new(VariableValue::from_computed_css(css)))
+    }
+
+    /// Returns the typed value of an already computed value of this property,
+    /// for interpolation, or `None` if the property has the universal syntax
+    /// or the value doesn't match it.
+    ///
+    /// Computed values only contain absolute units, so they don't depend on
+    /// the style they're computed in.
+    pub fn typed_value(&self, value: &VariableValue, device: &Device) -> Option<ComputedValue> {
+        if self.syntax.is_universal() {
+            return None;
+        }
+        let quirks_mode = QuirksMode::NoQuirks;
+        let specified = self.parse_specified(value.css(), quirks_mode).ok()?;
+        Some(Context::for_media_query_evaluation(
+            device,
+            quirks_mode,
+            |context| specified.to_computed_value(context),
+        ))
+    }
+
+    /// Returns whether `value` is a valid initial value for a property with
+    /// the given syntax, that is, whether it matches the syntax and is
+    /// computationally independent.
+    ///
+    /// <https://drafts.css-houdini.org/css-properties-values-api/#computationally-independent>
+    pub fn is_valid_initial_value(
+        syntax: &Descriptor,
+        value: &VariableValue,
+        url_data: &UrlExtraData,
+    ) -> bool {
+        if syntax.is_universal() {
+            return true;
+        }
+        if !is_computationally_independent(value.css()) {
+            return false;
+        }
+        let context = ParserContext::new(
+            Origin::Author,
+            url_data,
+            Some(CssRuleType::Style),
+            ParsingMode::DEFAULT,
+            QuirksMode::NoQuirks,
+            None,
+            None,
+        );
+        let mut input = ParserInput::new(value.css());
+        let mut input = Parser::new(&mut input);
+        SpecifiedValue::parse(&context, syntax, &mut input).is_ok()
+    }
+}
+
+/// Returns whether a value can be computed without any style context, that
+/// is, whether it only uses absolute units and doesn't reference any other
+/// property.
+fn is_computationally_independent(css: &str) -> bool {
+    fn is_absolute_unit(unit: &str) -> bool {
+        static ABSOLUTE_UNITS: &[&str] = &[
+            "px", "cm", "mm", "q", "in", "pt", "pc", "deg", "grad", "rad", "turn", "s", "ms",
+            "dpi", "dpcm", "dppx", "x",
+        ];
+        ABSOLUTE_UNITS
+            .iter()
+            .any(|absolute| unit.eq_ignore_ascii_case(absolute))
+    }
+
+    fn check<'i, 't>(input: &mut Parser<'i, 't>) -> bool {
+        while let Ok(token) = input.next() {
+            let nested = match *token {
+                Token::Dimension { ref unit, .. } => {
+                    if !is_absolute_unit(unit) {
+                        return false;
+                    }
+                    false
+                },
+                Token::Function(ref name) => {
+                    if name.eq_ignore_ascii_case("var") || name.eq_ignore_ascii_case("env") {
+                        return false;
+                    }
+                    true
+                },
+                Token::ParenthesisBlock | Token::SquareBracketBlock | Token::CurlyBracketBlock => {
+                    true
+                },
+                _ => false,
+            };
+            if nested {
+                let result =
+                    input.parse_nested_block(|input| Ok::<_, CssParseError<()>>(check(input)));
+                if !result.unwrap_or(false) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    let mut input = ParserInput::new(css);
+    check(&mut Parser::new(&mut input))
+}
+
+/// The set of registered custom properties, keyed by name.
+#[derive(Clone, Debug, Default)]
+pub struct PropertyRegistry {
+    registrations: PrecomputedHashMap<Name, PropertyRegistration>,
+}
+
+impl PropertyRegistry {
+    /// Returns the registration of the given property, if any.
+    #[inline]
+    pub fn get(&self, name: &Name) -> Option<&PropertyRegistration> {
+        self.registrations.get(name)
+    }
+
+    /// Returns whether no property has been registered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.registrations.is_empty()
+    }
+
+    /// Registers a property, replacing any previous registration with the
+    /// same name.
+    pub fn insert(&mut self, registration: PropertyRegistration) {
+        self.registrations
+            .insert(registration.name.clone(), registration);
+    }
+
+    /// Removes all the registrations.
+    pub fn clear(&mut self) {
+        self.registrations.clear();
+    }
+
+    /// Iterates over all the registrations.
+    pub fn iter(&self) -> impl Iterator<Item = &PropertyRegistration> {
+        self.registrations.values()
+    }
+}
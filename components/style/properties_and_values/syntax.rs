@@ -0,0 +1,264 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The syntax strings of registered custom properties.
+//!
+//! <https://drafts.css-houdini.org/css-properties-values-api/#syntax-strings>
+
+use crate::values::CustomIdent;
+use cssparser::{Parser, ParserInput};
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ToCss};
+
+/// A data type name that can appear in a syntax string.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api/#supported-names>
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
+pub enum DataType {
+    /// `<length>`
+    Length,
+    /// `<number>`
+    Number,
+    /// `<percentage>`
+    Percentage,
+    /// `<length-percentage>`
+    LengthPercentage,
+    /// `<color>`
+    Color,
+    /// `<image>`
+    Image,
+    /// `<url>`
+    Url,
+    /// `<integer>`
+    Integer,
+    /// `<angle>`
+    Angle,
+    /// `<time>`
+    Time,
+    /// `<resolution>`
+    Resolution,
+    /// `<transform-function>`
+    TransformFunction,
+    /// `<custom-ident>`
+    CustomIdent,
+    /// `<transform-list>`, which is a pre-multiplied data type.
+    TransformList,
+}
+
+impl DataType {
+    fn from_str(name: &str) -> Option<Self> {
+        // Data type names are case-sensitive.
+        Some(match name {
+            "length" => DataType::Length,
+            "number" => DataType::Number,
+            "percentage" => DataType::Percentage,
+            "length-percentage" => DataType::LengthPercentage,
+            "color" => DataType::Color,
+            "image" => DataType::Image,
+            "url" => DataType::Url,
+            "integer" => DataType::Integer,
+            "angle" => DataType::Angle,
+            "time" => DataType::Time,
+            "resolution" => DataType::Resolution,
+            "transform-function" => DataType::TransformFunction,
+            "custom-ident" => DataType::CustomIdent,
+            "transform-list" => DataType::TransformList,
+            _ => return None,
+        })
+    }
+
+    fn as_str(&self) -> &'static str {
+        match *self {
+            DataType::Length => "length",
+            DataType::Number => "number",
+            DataType::Percentage => "percentage",
+            DataType::LengthPercentage => "length-percentage",
+            DataType::Color => "color",
+            DataType::Image => "image",
+            DataType::Url => "url",
+            DataType::Integer => "integer",
+            DataType::Angle => "angle",
+            DataType::Time => "time",
+            DataType::Resolution => "resolution",
+            DataType::TransformFunction => "transform-function",
+            DataType::CustomIdent => "custom-ident",
+            DataType::TransformList => "transform-list",
+        }
+    }
+}
+
+/// The multiplier of a syntax component, which makes it accept a list of
+/// values.
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
+pub enum Multiplier {
+    /// A space-separated list, with the `+` multiplier.
+    Space,
+    /// A comma-separated list, with the `#` multiplier.
+    Comma,
+}
+
+impl Multiplier {
+    /// Returns the separator used to serialize a list with this multiplier.
+    pub fn separator(&self) -> &'static str {
+        match *self {
+            Multiplier::Space => " ",
+            Multiplier::Comma => ", ",
+        }
+    }
+}
+
+/// The name of a syntax component.
+#[derive(Clone, Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
+pub enum ComponentName {
+    /// A data type name, like `<length>`.
+    DataType(DataType),
+    /// A literal identifier.
+    Ident(CustomIdent),
+}
+
+/// A single component of a syntax string.
+#[derive(Clone, Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
+pub struct Component {
+    /// The name of this component.
+    pub name: ComponentName,
+    /// The multiplier of this component, if any.
+    pub multiplier: Option<Multiplier>,
+}
+
+impl Component {
+    /// Returns the multiplier of this component, taking into account that
+    /// `<transform-list>` is a space-separated list of transform functions.
+    pub fn effective_multiplier(&self) -> Option<Multiplier> {
+        match self.name {
+            ComponentName::DataType(DataType::TransformList) => Some(Multiplier::Space),
+            _ => self.multiplier,
+        }
+    }
+
+    /// Returns the data type of the individual values of this component, if
+    /// it's not a literal identifier.
+    pub fn data_type(&self) -> Option<DataType> {
+        match self.name {
+            ComponentName::DataType(DataType::TransformList) => Some(DataType::TransformFunction),
+            ComponentName::DataType(data_type) => Some(data_type),
+            ComponentName::Ident(..) => None,
+        }
+    }
+
+    fn parse(component: &str) -> Result<Self, ()> {
+        let (name, multiplier) = match component.as_bytes().last() {
+            Some(b'+') => (&component[..component.len() - 1], Some(Multiplier::Space)),
+            Some(b'#') => (&component[..component.len() - 1], Some(Multiplier::Comma)),
+            _ => (component, None),
+        };
+
+        let name = if name.starts_with('<') && name.ends_with('>') && name.len() > 2 {
+            let data_type = DataType::from_str(&name[1..name.len() - 1]).ok_or(())?;
+            // <transform-list> is already a list, and can't be multiplied.
+            if data_type == DataType::TransformList && multiplier.is_some() {
+                return Err(());
+            }
+            ComponentName::DataType(data_type)
+        } else {
+            // No whitespace is allowed between the name and the multiplier,
+            // nor are escaped multipliers a multiplier.
+            if name.ends_with(|c: char| c.is_ascii_whitespace() || c == '\\') {
+                return Err(());
+            }
+            let mut input = ParserInput::new(name);
+            let mut input = Parser::new(&mut input);
+            let location = input.current_source_location();
+            let ident = input
+                .expect_ident_cloned()
+                .map_err(|_| ())
+                .and_then(|ident| CustomIdent::from_ident(location, &ident, &[]).map_err(|_| ()))?;
+            input.expect_exhausted().map_err(|_| ())?;
+            ComponentName::Ident(ident)
+        };
+
+        Ok(Component { name, multiplier })
+    }
+}
+
+impl ToCss for Component {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        match self.name {
+            ComponentName::DataType(data_type) => {
+                dest.write_char('<')?;
+                dest.write_str(data_type.as_str())?;
+                dest.write_char('>')?;
+            },
+            ComponentName::Ident(ref ident) => ident.to_css(dest)?,
+        }
+        match self.multiplier {
+            Some(Multiplier::Space) => dest.write_char('+'),
+            Some(Multiplier::Comma) => dest.write_char('#'),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A parsed syntax string. An empty list of components represents the
+/// universal syntax `*`, which accepts any token sequence.
+#[derive(Clone, Debug, Default, Eq, MallocSizeOf, PartialEq, ToShmem)]
+pub struct Descriptor(pub Box<[Component]>);
+
+impl Descriptor {
+    /// Returns the universal syntax definition.
+    pub fn universal() -> Self {
+        Descriptor::default()
+    }
+
+    /// Returns whether this is the universal syntax definition.
+    #[inline]
+    pub fn is_universal(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Parses a syntax string.
+    ///
+    /// <https://drafts.css-houdini.org/css-properties-values-api/#consume-syntax-definition>
+    pub fn from_str(syntax: &str) -> Result<Self, ()> {
+        let syntax = syntax.trim_matches(|c: char| c.is_ascii_whitespace());
+        if syntax.is_empty() {
+            return Err(());
+        }
+        if syntax == "*" {
+            return Ok(Self::universal());
+        }
+
+        let components = syntax
+            .split('|')
+            .map(|component| {
+                let component = component.trim_matches(|c: char| c.is_ascii_whitespace());
+                if component.is_empty() {
+                    return Err(());
+                }
+                Component::parse(component)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Descriptor(components.into_boxed_slice()))
+    }
+}
+
+impl ToCss for Descriptor {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        if self.is_universal() {
+            return dest.write_char('*');
+        }
+        for (i, component) in self.0.iter().enumerate() {
+            if i != 0 {
+                dest.write_str(" | ")?;
+            }
+            component.to_css(dest)?;
+        }
+        Ok(())
+    }
+}
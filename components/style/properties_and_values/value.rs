@@ -0,0 +1,414 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Typed values of registered custom properties.
+//!
+//! <https://drafts.css-houdini.org/css-properties-values-api/#calculation-of-computed-values>
+
+use super::syntax::{Component, ComponentName, DataType, Descriptor, Multiplier};
+use crate::parser::{Parse, ParserContext};
+use crate::values::animated::{Animate, Procedure, ToAnimatedValue};
+use crate::values::computed::{self, Context, ToComputedValue};
+use crate::values::specified;
+use crate::values::CustomIdent;
+use cssparser::Parser;
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
+
+/// A single value matching a data type of a syntax string.
+#[derive(Clone, Debug, PartialEq, ToComputedValue, ToCss)]
+pub enum GenericComponent<
+    Length,
+    Number,
+    Percentage,
+    LengthPercentage,
+    Color,
+    Image,
+    Url,
+    Integer,
+    Angle,
+    Time,
+    Resolution,
+    TransformFunction,
+> {
+    /// A `<length>` value.
+    Length(Length),
+    /// A `<number>` value.
+    Number(Number),
+    /// A `<percentage>` value.
+    Percentage(Percentage),
+    /// A `<length-percentage>` value.
+    LengthPercentage(LengthPercentage),
+    /// A `<color>` value.
+    Color(Color),
+    /// An `<image>` value.
+    Image(Image),
+    /// A `<url>` value.
+    Url(Url),
+    /// An `<integer>` value.
+    Integer(Integer),
+    /// An `<angle>` value.
+    Angle(Angle),
+    /// A `<time>` value.
+    Time(Time),
+    /// A `<resolution>` value.
+    Resolution(Resolution),
+    /// A `<transform-function>` value.
+    TransformFunction(TransformFunction),
+    /// A `<custom-ident>` value, or one of the literal identifiers of the
+    /// syntax.
+    CustomIdent(CustomIdent),
+}
+
+/// A specified component value.
+pub type SpecifiedComponent = GenericComponent<
+    specified::Length,
+    specified::Number,
+    specified::Percentage,
+    specified::LengthPercentage,
+    specified::Color,
+    specified::Image,
+    specified::url::SpecifiedUrl,
+    specified::Integer,
+    specified::Angle,
+    specified::Time,
+    specified::Resolution,
+    specified::transform::TransformOperation,
+>;
+
+/// A computed component value.
+pub type ComputedComponent = GenericComponent<
+    computed::Length,
+    computed::Number,
+    computed::Percentage,
+    computed::LengthPercentage,
+    computed::Color,
+    computed::Image,
+    computed::url::ComputedUrl,
+    computed::Integer,
+    computed::Angle,
+    computed::Time,
+    computed::Resolution,
+    computed::TransformOperation,
+>;
+
+/// A list of component values, matching a component of a syntax string with
+/// a multiplier.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenericValueList<Component> {
+    /// The values of the list.
+    pub components: Box<[Component]>,
+    /// The multiplier the list was matched with.
+    pub multiplier: Multiplier,
+}
+
+/// The value of a registered custom property with a non-universal syntax.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GenericValue<Component> {
+    /// A single component value.
+    Component(Component),
+    /// A list of component values.
+    List(GenericValueList<Component>),
+}
+
+/// A specified value of a registered custom property.
+pub type SpecifiedValue = GenericValue<SpecifiedComponent>;
+
+/// A computed value of a registered custom property.
+pub type ComputedValue = GenericValue<ComputedComponent>;
+
+impl SpecifiedComponent {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        name: &ComponentName,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let data_type = match *name {
+            ComponentName::DataType(data_type) => data_type,
+            ComponentName::Ident(ref ident) => {
+                // Literal identifiers are matched case-sensitively.
+                let location = input.current_source_location();
+                if **input.expect_ident()? != *ident.0 {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+                return Ok(GenericComponent::CustomIdent(ident.clone()));
+            },
+        };
+
+        Ok(match data_type {
+            DataType::Length => GenericComponent::Length(specified::Length::parse(context, input)?),
+            DataType::Number => GenericComponent::Number(specified::Number::parse(context, input)?),
+            DataType::Percentage => {
+                GenericComponent::Percentage(specified::Percentage::parse(context, input)?)
+            },
+            DataType::LengthPercentage => GenericComponent::LengthPercentage(
+                specified::LengthPercentage::parse(context, input)?,
+            ),
+            DataType::Color => GenericComponent::Color(specified::Color::parse(context, input)?),
+            DataType::Image => GenericComponent::Image(specified::Image::parse(context, input)?),
+            DataType::Url => {
+                GenericComponent::Url(specified::url::SpecifiedUrl::parse(context, input)?)
+            },
+            DataType::Integer => {
+                GenericComponent::Integer(specified::Integer::parse(context, input)?)
+            },
+            DataType::Angle => GenericComponent::Angle(specified::Angle::parse(context, input)?),
+            DataType::Time => GenericComponent::Time(specified::Time::parse(context, input)?),
+            DataType::Resolution => {
+                GenericComponent::Resolution(specified::Resolution::parse(context, input)?)
+            },
+            DataType::TransformFunction | DataType::TransformList => {
+                let location = input.current_source_location();
+                let transform = specified::Transform::parse(context, input)?;
+                let mut operations = transform.0.into_vec();
+                if operations.len() != 1 {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+                GenericComponent::TransformFunction(operations.pop().unwrap())
+            },
+            DataType::CustomIdent => {
+                let location = input.current_source_location();
+                let ident = input.expect_ident()?;
+                GenericComponent::CustomIdent(CustomIdent::from_ident(location, ident, &[])?)
+            },
+        })
+    }
+}
+
+impl SpecifiedValue {
+    /// Parses a value against the given, non-universal, syntax. The first
+    /// component of the syntax that matches the whole input is used.
+    pub fn parse<'i, 't>(
+        context: &ParserContext,
+        syntax: &Descriptor,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        debug_assert!(!syntax.is_universal());
+        for component in syntax.0.iter() {
+            let result = input.try(|input| {
+                input.parse_entirely(|input| Self::parse_component(context, component, input))
+            });
+            if let Ok(value) = result {
+                return Ok(value);
+            }
+        }
+        Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError))
+    }
+
+    fn parse_component<'i, 't>(
+        context: &ParserContext,
+        component: &Component,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let multiplier = match component.effective_multiplier() {
+            Some(multiplier) => multiplier,
+            None => {
+                return Ok(GenericValue::Component(SpecifiedComponent::parse(
+                    context,
+                    &component.name,
+                    input,
+                )?));
+            },
+        };
+
+        let components = match multiplier {
+            Multiplier::Space if component.data_type() == Some(DataType::TransformFunction) => {
+                // Transform functions aren't delimited, so parse them as a
+                // whole transform list instead.
+                let location = input.current_source_location();
+                let transform = specified::Transform::parse(context, input)?;
+                if transform.0.is_empty() {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+                transform
+                    .0
+                    .into_vec()
+                    .into_iter()
+                    .map(GenericComponent::TransformFunction)
+                    .collect()
+            },
+            Multiplier::Space => {
+                let mut components = vec![];
+                loop {
+                    components.push(SpecifiedComponent::parse(context, &component.name, input)?);
+                    if input.is_exhausted() {
+                        break;
+                    }
+                }
+                components
+            },
+            Multiplier::Comma => input.parse_comma_separated(|input| {
+                SpecifiedComponent::parse(context, &component.name, input)
+            })?,
+        };
+
+        Ok(GenericValue::List(GenericValueList {
+            components: components.into_boxed_slice(),
+            multiplier,
+        }))
+    }
+}
+
+impl<C> ToComputedValue for GenericValue<C>
+where
+    C: ToComputedValue,
+{
+    type ComputedValue = GenericValue<C::ComputedValue>;
+
+    fn to_computed_value(&self, context: &Context) -> Self::ComputedValue {
+        match *self {
+            GenericValue::Component(ref component) => {
+                GenericValue::Component(component.to_computed_value(context))
+            },
+            GenericValue::List(ref list) => GenericValue::List(GenericValueList {
+                components: list.components.to_computed_value(context),
+                multiplier: list.multiplier,
+            }),
+        }
+    }
+
+    fn from_computed_value(computed: &Self::ComputedValue) -> Self {
+        match *computed {
+            GenericValue::Component(ref component) => {
+                GenericValue::Component(C::from_computed_value(component))
+            },
+            GenericValue::List(ref list) => GenericValue::List(GenericValueList {
+                components: ToComputedValue::from_computed_value(&list.components),
+                multiplier: list.multiplier,
+            }),
+        }
+    }
+}
+
+impl<C> ToCss for GenericValue<C>
+where
+    C: ToCss,
+{
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        match *self {
+            GenericValue::Component(ref component) => component.to_css(dest),
+            GenericValue::List(ref list) => {
+                for (i, component) in list.components.iter().enumerate() {
+                    if i != 0 {
+                        dest.write_str(list.multiplier.separator())?;
+                    }
+                    component.to_css(dest)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl Animate for ComputedComponent {
+    fn animate(&self, other: &Self, procedure: Procedure) -> Result<Self, ()> {
+        Ok(match (self, other) {
+            (&GenericComponent::Length(ref this), &GenericComponent::Length(ref other)) => {
+                GenericComponent::Length(this.animate(other, procedure)?)
+            },
+            (&GenericComponent::Number(ref this), &GenericComponent::Number(ref other)) => {
+                GenericComponent::Number(this.animate(other, procedure)?)
+            },
+            (&GenericComponent::Percentage(ref this), &GenericComponent::Percentage(ref other)) => {
+                GenericComponent::Percentage(this.animate(other, procedure)?)
+            },
+            (
+                &GenericComponent::LengthPercentage(ref this),
+                &GenericComponent::LengthPercentage(ref other),
+            ) => GenericComponent::LengthPercentage(this.animate(other, procedure)?),
+            (&GenericComponent::Color(ref this), &GenericComponent::Color(ref other)) => {
+                let this = this.clone().to_animated_value();
+                let other = other.clone().to_animated_value();
+                GenericComponent::Color(ToAnimatedValue::from_animated_value(
+                    this.animate(&other, procedure)?,
+                ))
+            },
+            (&GenericComponent::Integer(ref this), &GenericComponent::Integer(ref other)) => {
+                GenericComponent::Integer(this.animate(other, procedure)?)
+            },
+            (&GenericComponent::Angle(ref this), &GenericComponent::Angle(ref other)) => {
+                GenericComponent::Angle(this.animate(other, procedure)?)
+            },
+            (&GenericComponent::Time(ref this), &GenericComponent::Time(ref other)) => {
+                let seconds = this.seconds().animate(&other.seconds(), procedure)?;
+                GenericComponent::Time(computed::Time::from_seconds(seconds))
+            },
+            (&GenericComponent::Resolution(ref this), &GenericComponent::Resolution(ref other)) => {
+                let dppx = this.dppx().animate(&other.dppx(), procedure)?;
+                GenericComponent::Resolution(computed::Resolution::from_dppx(dppx))
+            },
+            (
+                &GenericComponent::TransformFunction(ref this),
+                &GenericComponent::TransformFunction(ref other),
+            ) => GenericComponent::TransformFunction(this.animate(other, procedure)?),
+            // Images, urls and identifiers animate discretely.
+            _ => return Err(()),
+        })
+    }
+}
+
+impl ComputedValue {
+    fn as_transform(&self) -> Option<computed::Transform> {
+        let list = match *self {
+            GenericValue::List(ref list) if list.multiplier == Multiplier::Space => list,
+            _ => return None,
+        };
+        list.components
+            .iter()
+            .map(|component| match *component {
+                GenericComponent::TransformFunction(ref operation) => Some(operation.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|operations| computed::Transform(operations.into()))
+    }
+}
+
+impl Animate for ComputedValue {
+    fn animate(&self, other: &Self, procedure: Procedure) -> Result<Self, ()> {
+        match (self, other) {
+            (&GenericValue::Component(ref this), &GenericValue::Component(ref other)) => {
+                Ok(GenericValue::Component(this.animate(other, procedure)?))
+            },
+            (&GenericValue::List(ref this_list), &GenericValue::List(ref other_list)) => {
+                if this_list.multiplier != other_list.multiplier {
+                    return Err(());
+                }
+
+                // Transform lists interpolate like the transform property,
+                // even when their functions don't match pairwise.
+                if let (Some(this), Some(other)) = (self.as_transform(), other.as_transform()) {
+                    let transform = this.animate(&other, procedure)?;
+                    return Ok(GenericValue::List(GenericValueList {
+                        components: transform
+                            .0
+                            .into_vec()
+                            .into_iter()
+                            .map(GenericComponent::TransformFunction)
+                            .collect(),
+                        multiplier: Multiplier::Space,
+                    }));
+                }
+
+                if this_list.components.len() != other_list.components.len() {
+                    return Err(());
+                }
+                let components = this_list
+                    .components
+                    .iter()
+                    .zip(other_list.components.iter())
+                    .map(|(this, other)| this.animate(other, procedure))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(GenericValue::List(GenericValueList {
+                    components: components.into_boxed_slice(),
+                    multiplier: this_list.multiplier,
+                }))
+            },
+            _ => Err(()),
+        }
+    }
+}
@@ -6,13 +6,14 @@
 
 use crate::applicable_declarations::{ApplicableDeclarationBlock, ApplicableDeclarationList};
 use crate::dom::{TElement, TNode, TShadowRoot};
-use crate::properties::{AnimationRules, PropertyDeclarationBlock};
+use crate::properties::{AnimationRules, ComputedValues, PropertyDeclarationBlock};
 use crate::rule_tree::{CascadeLevel, ShadowCascadeOrder};
 use crate::selector_map::SelectorMap;
 use crate::selector_parser::PseudoElement;
 use crate::shared_lock::Locked;
+use crate::stylesheets::layer_rule::LayerOrder;
 use crate::stylesheets::Origin;
-use crate::stylist::{AuthorStylesEnabled, Rule, RuleInclusion, Stylist};
+use crate::stylist::{AuthorStylesEnabled, CascadeData, Rule, RuleInclusion, Stylist};
 use crate::Atom;
 use selectors::matching::{ElementSelectorFlags, MatchingContext, MatchingMode};
 use servo_arc::ArcBorrow;
@@ -55,9 +56,12 @@ pub fn containing_shadow_ignoring_svg_use<E: TElement>(
     }
 }
 
+/// Sorts the rules from `start` on, which must all come from the same cascade
+/// origin, by cascade layer, then specificity, then source order.
 #[inline]
-fn sort_rules_from(rules: &mut ApplicableDeclarationList, start: usize) {
-    rules[start..].sort_unstable_by_key(|block| (block.specificity, block.source_order()));
+pub fn sort_rules_from(rules: &mut ApplicableDeclarationList, start: usize) {
+    rules[start..]
+        .sort_unstable_by_key(|block| (block.layer_order, block.specificity, block.source_order()));
 }
 
 /// An object that we use with all the intermediate state needed for the
@@ -73,6 +77,7 @@ where
     rule_hash_target: E,
     stylist: &'a Stylist,
     pseudo_element: Option<&'a PseudoElement>,
+    originating_element_style: Option<&'a ComputedValues>,
     style_attribute: Option<ArcBorrow<'a, Locked<PropertyDeclarationBlock>>>,
     smil_override: Option<ArcBorrow<'a, Locked<PropertyDeclarationBlock>>>,
     animation_rules: AnimationRules,
@@ -94,6 +99,7 @@ where
         stylist: &'a Stylist,
         element: E,
         pseudo_element: Option<&'a PseudoElement>,
+        originating_element_style: Option<&'a ComputedValues>,
         style_attribute: Option<ArcBorrow<'a, Locked<PropertyDeclarationBlock>>>,
         smil_override: Option<ArcBorrow<'a, Locked<PropertyDeclarationBlock>>>,
         animation_rules: AnimationRules,
@@ -125,6 +131,7 @@ where
             rule_hash_target,
             stylist,
             pseudo_element,
+            originating_element_style,
             style_attribute,
             smil_override,
             animation_rules,
@@ -150,7 +157,7 @@ where
             None => return,
         };
 
-        self.collect_rules_internal(None, map, cascade_level);
+        self.collect_rules_internal(None, map, cascade_level, cascade_data);
     }
 
     fn collect_user_agent_rules(&mut self) {
@@ -194,9 +201,10 @@ where
         shadow_host: E,
         map: &SelectorMap<Rule>,
         cascade_level: CascadeLevel,
+        cascade_data: &CascadeData,
     ) {
         debug_assert!(shadow_host.shadow_root().is_some());
-        self.collect_rules_internal(Some(shadow_host), map, cascade_level);
+        self.collect_rules_internal(Some(shadow_host), map, cascade_level, cascade_data);
     }
 
     #[inline]
@@ -205,11 +213,14 @@ where
         shadow_host: Option<E>,
         map: &SelectorMap<Rule>,
         cascade_level: CascadeLevel,
+        cascade_data: &CascadeData,
     ) {
         let element = self.element;
         let rule_hash_target = self.rule_hash_target;
         let rules = &mut self.rules;
         let flags_setter = &mut self.flags_setter;
+        let device = self.stylist.device();
+        let originating_element_style = self.originating_element_style;
         let start = rules.len();
         self.context.with_shadow_host(shadow_host, |context| {
             map.get_all_matching_rules(
@@ -219,6 +230,9 @@ where
                 context,
                 flags_setter,
                 cascade_level,
+                cascade_data,
+                device,
+                originating_element_style,
             );
         });
         sort_rules_from(rules, start);
@@ -264,6 +278,7 @@ where
                 CascadeLevel::AuthorNormal {
                     shadow_cascade_order,
                 },
+                data,
             );
         }
     }
@@ -281,10 +296,18 @@ where
 
         self.matches_document_author_rules = false;
 
-        let cascade_data = containing_shadow.style_data();
+        let cascade_data = match containing_shadow.style_data() {
+            Some(data) => data,
+            None => return,
+        };
         let host = containing_shadow.host();
-        if let Some(map) = cascade_data.and_then(|data| data.normal_rules(self.pseudo_element)) {
-            self.collect_rules_in_shadow_tree(host, map, CascadeLevel::same_tree_author_normal());
+        if let Some(map) = cascade_data.normal_rules(self.pseudo_element) {
+            self.collect_rules_in_shadow_tree(
+                host,
+                map,
+                CascadeLevel::same_tree_author_normal(),
+                cascade_data,
+            );
         }
     }
 
@@ -317,6 +340,7 @@ where
             CascadeLevel::AuthorNormal {
                 shadow_cascade_order,
             },
+            style_data,
         );
     }
 
@@ -349,22 +373,27 @@ where
             }
 
             let outer_shadow = inner_shadow.host().containing_shadow();
-            let part_rules = match outer_shadow {
-                Some(shadow) => shadow
-                    .style_data()
-                    .and_then(|data| data.part_rules(self.pseudo_element)),
-                None => self
-                    .stylist
-                    .cascade_data()
-                    .borrow_for_origin(Origin::Author)
-                    .part_rules(self.pseudo_element),
+            let cascade_data = match outer_shadow {
+                Some(shadow) => shadow.style_data(),
+                None => Some(
+                    self.stylist
+                        .cascade_data()
+                        .borrow_for_origin(Origin::Author),
+                ),
             };
+            let pseudo_element = self.pseudo_element;
+            let part_rules = cascade_data.and_then(|data| {
+                data.part_rules(pseudo_element)
+                    .map(|part_rules| (data, part_rules))
+            });
 
-            if let Some(part_rules) = part_rules {
+            if let Some((cascade_data, part_rules)) = part_rules {
                 let containing_host = outer_shadow.map(|s| s.host());
                 let element = self.element;
                 let rules = &mut self.rules;
                 let flags_setter = &mut self.flags_setter;
+                let device = self.stylist.device();
+                let originating_element_style = self.originating_element_style;
                 let cascade_level = CascadeLevel::AuthorNormal {
                     shadow_cascade_order,
                 };
@@ -379,6 +408,9 @@ where
                                 context,
                                 flags_setter,
                                 cascade_level,
+                                cascade_data,
+                                device,
+                                originating_element_style,
                             );
                         }
                     }
@@ -411,6 +443,7 @@ where
                 .push(ApplicableDeclarationBlock::from_declarations(
                     sa.clone_arc(),
                     CascadeLevel::same_tree_author_normal(),
+                    LayerOrder::style_attribute(),
                 ));
         }
     }
@@ -421,6 +454,7 @@ where
                 .push(ApplicableDeclarationBlock::from_declarations(
                     so.clone_arc(),
                     CascadeLevel::SMILOverride,
+                    LayerOrder::root(),
                 ));
         }
 
@@ -432,6 +466,7 @@ where
                 .push(ApplicableDeclarationBlock::from_declarations(
                     anim,
                     CascadeLevel::Animations,
+                    LayerOrder::root(),
                 ));
         }
 
@@ -442,6 +477,7 @@ where
                 .push(ApplicableDeclarationBlock::from_declarations(
                     anim,
                     CascadeLevel::Transitions,
+                    LayerOrder::root(),
                 ));
         }
     }
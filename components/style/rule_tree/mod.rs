@@ -12,6 +12,7 @@ use crate::gecko::selector_parser::PseudoElement;
 use crate::hash::{self, FxHashMap};
 use crate::properties::{Importance, LonghandIdSet, PropertyDeclarationBlock};
 use crate::shared_lock::{Locked, SharedRwLockReadGuard, StylesheetGuards};
+use crate::stylesheets::layer_rule::LayerOrder;
 use crate::stylesheets::{Origin, StyleRule};
 use crate::thread_state;
 use malloc_size_of::{MallocShallowSizeOf, MallocSizeOf, MallocSizeOfOps};
@@ -224,6 +225,17 @@ impl std::ops::Neg for ShadowCascadeOrder {
     }
 }
 
+/// Sorts the !important rules of a single origin so that rules in earlier
+/// cascade layers come last, keeping the source order within each layer.
+fn sort_important_rules_by_layer(rules: &mut [(StyleSource, LayerOrder)]) {
+    if rules
+        .windows(2)
+        .any(|w| w[0].1.important_sort_key() > w[1].1.important_sort_key())
+    {
+        rules.sort_by_key(|&(_, layer_order)| layer_order.important_sort_key());
+    }
+}
+
 impl RuleTree {
     /// Construct a new rule tree.
     pub fn new() -> Self {
@@ -254,26 +266,30 @@ impl RuleTree {
     /// !important rules are detected and inserted into the appropriate position
     /// in the rule tree. This allows selector matching to ignore importance,
     /// while still maintaining the appropriate cascade order in the rule tree.
+    ///
+    /// The layer order of each rule is needed to reverse the order of cascade
+    /// layers for !important rules.
     pub fn insert_ordered_rules_with_important<'a, I>(
         &self,
         iter: I,
         guards: &StylesheetGuards,
     ) -> StrongRuleNode
     where
-        I: Iterator<Item = (StyleSource, CascadeLevel)>,
+        I: Iterator<Item = (StyleSource, CascadeLevel, LayerOrder)>,
     {
         use self::CascadeLevel::*;
         let mut current = self.root.clone();
 
         let mut found_important = false;
 
-        let mut important_author = SmallVec::<[(StyleSource, ShadowCascadeOrder); 4]>::new();
+        let mut important_author =
+            SmallVec::<[(StyleSource, ShadowCascadeOrder, LayerOrder); 4]>::new();
 
-        let mut important_user = SmallVec::<[StyleSource; 4]>::new();
-        let mut important_ua = SmallVec::<[StyleSource; 4]>::new();
+        let mut important_user = SmallVec::<[(StyleSource, LayerOrder); 4]>::new();
+        let mut important_ua = SmallVec::<[(StyleSource, LayerOrder); 4]>::new();
         let mut transition = None;
 
-        for (source, level) in iter {
+        for (source, level, layer_order) in iter {
             debug_assert!(!level.is_important(), "Important levels handled internally");
             let any_important = {
                 let pdb = source.read(level.guard(guards));
@@ -286,10 +302,10 @@ impl RuleTree {
                     AuthorNormal {
                         shadow_cascade_order,
                     } => {
-                        important_author.push((source.clone(), shadow_cascade_order));
+                        important_author.push((source.clone(), shadow_cascade_order, layer_order));
                     },
-                    UANormal => important_ua.push(source.clone()),
-                    UserNormal => important_user.push(source.clone()),
+                    UANormal => important_ua.push((source.clone(), layer_order)),
+                    UserNormal => important_user.push((source.clone(), layer_order)),
                     _ => {},
                 };
             }
@@ -327,11 +343,14 @@ impl RuleTree {
         //
         // We negate the shadow cascade order to preserve the right PartialOrd
         // behavior.
-        if !important_author.is_empty() &&
-            important_author.first().unwrap().1 != important_author.last().unwrap().1
-        {
+        //
+        // Within the same tree, !important rules in earlier cascade layers win
+        // over the ones in later layers.
+        if important_author.windows(2).any(|w| {
+            (-w[0].1, w[0].2.important_sort_key()) > (-w[1].1, w[1].2.important_sort_key())
+        }) {
             // We only need to sort if the important rules come from
-            // different trees, but we need this sort to be stable.
+            // different trees or layers, but we need this sort to be stable.
             //
             // FIXME(emilio): This could maybe be smarter, probably by chunking
             // the important rules while inserting, and iterating the outer
@@ -342,10 +361,14 @@ impl RuleTree {
             // inside the same chunk already sorted. Seems like we could try to
             // keep a SmallVec-of-SmallVecs with the chunks and just iterate the
             // outer in reverse.
-            important_author.sort_by_key(|&(_, order)| -order);
+            important_author
+                .sort_by_key(|&(_, order, layer_order)| (-order, layer_order.important_sort_key()));
         }
 
-        for (source, shadow_cascade_order) in important_author.drain() {
+        sort_important_rules_by_layer(&mut important_user);
+        sort_important_rules_by_layer(&mut important_ua);
+
+        for (source, shadow_cascade_order, _) in important_author.drain() {
             current = current.ensure_child(
                 self.root.downgrade(),
                 source,
@@ -355,11 +378,11 @@ impl RuleTree {
             );
         }
 
-        for source in important_user.drain() {
+        for (source, _) in important_user.drain() {
             current = current.ensure_child(self.root.downgrade(), source, UserImportant);
         }
 
-        for source in important_ua.drain() {
+        for (source, _) in important_ua.drain() {
             current = current.ensure_child(self.root.downgrade(), source, UAImportant);
         }
 
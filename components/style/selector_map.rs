@@ -10,9 +10,11 @@ use crate::context::QuirksMode;
 use crate::dom::TElement;
 use crate::hash::map as hash_map;
 use crate::hash::{HashMap, HashSet};
+use crate::media_queries::Device;
+use crate::properties::ComputedValues;
 use crate::rule_tree::CascadeLevel;
 use crate::selector_parser::SelectorImpl;
-use crate::stylist::Rule;
+use crate::stylist::{CascadeData, Rule};
 use crate::{Atom, LocalName, Namespace, WeakAtom};
 use fallible::FallibleVec;
 use hashglobe::FailedAllocationError;
@@ -171,6 +173,9 @@ impl SelectorMap<Rule> {
         context: &mut MatchingContext<E::Impl>,
         flags_setter: &mut F,
         cascade_level: CascadeLevel,
+        cascade_data: &CascadeData,
+        device: &Device,
+        originating_element_style: Option<&ComputedValues>,
     ) where
         E: TElement,
         F: FnMut(&E, ElementSelectorFlags),
@@ -189,6 +194,9 @@ impl SelectorMap<Rule> {
                 context,
                 flags_setter,
                 cascade_level,
+                cascade_data,
+                device,
+                originating_element_style,
             );
         }
 
@@ -201,6 +209,9 @@ impl SelectorMap<Rule> {
                     context,
                     flags_setter,
                     cascade_level,
+                    cascade_data,
+                    device,
+                    originating_element_style,
                 )
             }
         }
@@ -214,6 +225,9 @@ impl SelectorMap<Rule> {
                     context,
                     flags_setter,
                     cascade_level,
+                    cascade_data,
+                    device,
+                    originating_element_style,
                 )
             }
         });
@@ -226,6 +240,9 @@ impl SelectorMap<Rule> {
                 context,
                 flags_setter,
                 cascade_level,
+                cascade_data,
+                device,
+                originating_element_style,
             )
         }
 
@@ -237,6 +254,9 @@ impl SelectorMap<Rule> {
                 context,
                 flags_setter,
                 cascade_level,
+                cascade_data,
+                device,
+                originating_element_style,
             )
         }
 
@@ -247,6 +267,9 @@ impl SelectorMap<Rule> {
             context,
             flags_setter,
             cascade_level,
+            cascade_data,
+            device,
+            originating_element_style,
         );
     }
 
@@ -258,6 +281,9 @@ impl SelectorMap<Rule> {
         context: &mut MatchingContext<E::Impl>,
         flags_setter: &mut F,
         cascade_level: CascadeLevel,
+        cascade_data: &CascadeData,
+        device: &Device,
+        originating_element_style: Option<&ComputedValues>,
     ) where
         E: TElement,
         F: FnMut(&E, ElementSelectorFlags),
@@ -270,8 +296,15 @@ impl SelectorMap<Rule> {
                 &element,
                 context,
                 flags_setter,
+            ) && cascade_data.container_condition_matches(
+                rule.container_condition_id,
+                device,
+                element,
+                originating_element_style,
+                context.quirks_mode(),
             ) {
-                matching_rules.push(rule.to_applicable_declaration_block(cascade_level));
+                matching_rules
+                    .push(rule.to_applicable_declaration_block(cascade_level, cascade_data));
             }
         }
     }
@@ -500,6 +533,12 @@ fn specific_bucket_for<'a>(component: &'a Component<SelectorImpl>) -> Bucket<'a>
         // match the slotted <span>.
         Component::Slotted(ref selector) => find_bucket(selector.iter()),
         Component::Host(Some(ref selector)) => find_bucket(selector.iter()),
+        // An element matching `:is(.foo .bar)` needs to match `.bar`, so we
+        // can bucket it in the same way. With multiple selectors we'd need to
+        // insert it in multiple buckets, so we don't bother.
+        Component::Is(ref list) | Component::Where(ref list) if list.len() == 1 => {
+            find_bucket(list[0].iter())
+        },
         _ => Bucket::Universal,
     }
 }
@@ -5,21 +5,26 @@
 //! Servo's media-query device and expression representation.
 
 use crate::custom_properties::CssEnvironment;
-use crate::media_queries::media_feature::{AllowsRanges, ParsingRequirements};
-use crate::media_queries::media_feature::{Evaluator, MediaFeatureDescription};
-use crate::media_queries::media_feature_expression::RangeOrOperator;
+use crate::media_queries::media_feature::{AllowsRanges, Evaluator, KeywordDiscriminant};
+use crate::media_queries::media_feature::{MediaFeatureDescription, ParsingRequirements};
+use crate::media_queries::media_feature_expression::{AspectRatio, RangeOrOperator};
 use crate::media_queries::MediaType;
+use crate::parser::ParserContext;
 use crate::properties::ComputedValues;
+use crate::properties_and_values::PropertyRegistry;
 use crate::values::computed::font::FontSize;
-use crate::values::computed::CSSPixelLength;
+use crate::values::computed::{CSSPixelLength, Resolution};
 use crate::values::KeyframesName;
 use app_units::Au;
-use cssparser::RGBA;
+use cssparser::{Parser, RGBA};
 use euclid::default::Size2D as UntypedSize2D;
 use euclid::{Scale, Size2D};
+use servo_arc::Arc;
 use std::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
+use style_traits::media_queries::{self, DisplayMode, MediaEnvironment};
+use style_traits::media_queries::{PointerCapabilities, PrefersColorScheme};
 use style_traits::viewport::ViewportConstraints;
-use style_traits::{CSSPixel, DevicePixel};
+use style_traits::{CSSPixel, DevicePixel, ParseError, ToCss};
 
 /// A device is a structure that represents the current media a given document
 /// is displayed in.
@@ -51,9 +56,19 @@ pub struct Device {
     /// Whether any styles computed in the document relied on the viewport size.
     #[ignore_malloc_size_of = "Pure stack type"]
     used_viewport_units: AtomicBool,
+    /// Whether any styles computed in the document relied on the size of
+    /// query containers by using container-relative units.
+    #[ignore_malloc_size_of = "Pure stack type"]
+    used_container_units: AtomicBool,
     /// The CssEnvironment object responsible of getting CSS environment
     /// variables.
     environment: CssEnvironment,
+    /// The custom properties registered via `@property` rules or
+    /// `CSS.registerProperty()`.
+    #[ignore_malloc_size_of = "Arc"]
+    custom_property_registry: Arc<PropertyRegistry>,
+    /// The preferences of the user and the capabilities of their device.
+    media_environment: MediaEnvironment,
 }
 
 impl Device {
@@ -71,16 +86,44 @@ impl Device {
             root_font_size: AtomicIsize::new(FontSize::medium().size().0 as isize),
             used_root_font_size: AtomicBool::new(false),
             used_viewport_units: AtomicBool::new(false),
+            used_container_units: AtomicBool::new(false),
             environment: CssEnvironment,
+            custom_property_registry: Default::default(),
+            media_environment: MediaEnvironment::default(),
         }
     }
 
+    /// Set the preferences of the user and the capabilities of their device
+    /// which media features are evaluated against.
+    pub fn set_media_environment(&mut self, media_environment: MediaEnvironment) {
+        self.media_environment = media_environment;
+    }
+
+    /// Get the preferences of the user and the capabilities of their device.
+    #[inline]
+    pub fn media_environment(&self) -> &MediaEnvironment {
+        &self.media_environment
+    }
+
     /// Get the relevant environment to resolve `env()` functions.
     #[inline]
     pub fn environment(&self) -> &CssEnvironment {
         &self.environment
     }
 
+    /// Get the custom properties registered via `@property` rules or
+    /// `CSS.registerProperty()`.
+    #[inline]
+    pub fn custom_property_registry(&self) -> &Arc<PropertyRegistry> {
+        &self.custom_property_registry
+    }
+
+    /// Set the custom properties registered via `@property` rules or
+    /// `CSS.registerProperty()`.
+    pub fn set_custom_property_registry(&mut self, registry: Arc<PropertyRegistry>) {
+        self.custom_property_registry = registry;
+    }
+
     /// Return the default computed values for this device.
     pub fn default_computed_values(&self) -> &ComputedValues {
         // FIXME(bz): This isn't really right, but it's no more wrong
@@ -140,6 +183,17 @@ impl Device {
         self.used_viewport_units.load(Ordering::Relaxed)
     }
 
+    /// Records that container-relative units were used.
+    pub fn set_used_container_units(&self) {
+        self.used_container_units.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether container-relative units were used since the last device
+    /// change.
+    pub fn used_container_units(&self) -> bool {
+        self.used_container_units.load(Ordering::Relaxed)
+    }
+
     /// Returns the device pixel ratio.
     pub fn device_pixel_ratio(&self) -> Scale<f32, CSSPixel, DevicePixel> {
         self.device_pixel_ratio
@@ -179,6 +233,72 @@ fn eval_width(
     )
 }
 
+/// https://drafts.csswg.org/mediaqueries-4/#height
+fn eval_height(
+    device: &Device,
+    value: Option<CSSPixelLength>,
+    range_or_operator: Option<RangeOrOperator>,
+) -> bool {
+    RangeOrOperator::evaluate(
+        range_or_operator,
+        value.map(Au::from),
+        device.au_viewport_size().height,
+    )
+}
+
+/// https://drafts.csswg.org/mediaqueries-4/#aspect-ratio
+fn eval_aspect_ratio(
+    device: &Device,
+    query_value: Option<AspectRatio>,
+    range_or_operator: Option<RangeOrOperator>,
+) -> bool {
+    let query_value = match query_value {
+        Some(v) => v,
+        None => return true,
+    };
+
+    let size = device.au_viewport_size();
+    let value = AspectRatio(size.width.0 as f32, size.height.0 as f32);
+    RangeOrOperator::evaluate_with_query_value(range_or_operator, query_value, value)
+}
+
+#[derive(Clone, Copy, Debug, FromPrimitive, Parse, ToCss)]
+#[repr(u8)]
+enum Orientation {
+    Landscape,
+    Portrait,
+}
+
+/// https://drafts.csswg.org/mediaqueries-4/#orientation
+fn eval_orientation(device: &Device, value: Option<Orientation>) -> bool {
+    let query_orientation = match value {
+        Some(v) => v,
+        None => return true,
+    };
+
+    let size = device.au_viewport_size();
+
+    // Per spec, square viewports should be 'portrait'
+    let is_landscape = size.width > size.height;
+    match query_orientation {
+        Orientation::Landscape => is_landscape,
+        Orientation::Portrait => !is_landscape,
+    }
+}
+
+/// https://drafts.csswg.org/mediaqueries-4/#resolution
+fn eval_resolution(
+    device: &Device,
+    query_value: Option<Resolution>,
+    range_or_operator: Option<RangeOrOperator>,
+) -> bool {
+    RangeOrOperator::evaluate(
+        range_or_operator,
+        query_value.map(|r| r.dppx()),
+        device.device_pixel_ratio().get(),
+    )
+}
+
 #[derive(Clone, Copy, Debug, FromPrimitive, Parse, ToCss)]
 #[repr(u8)]
 enum Scan {
@@ -193,20 +313,257 @@ fn eval_scan(_: &Device, _: Option<Scan>) -> bool {
     false
 }
 
+/// https://drafts.csswg.org/mediaqueries-4/#color
+fn eval_color(
+    device: &Device,
+    query_value: Option<u32>,
+    range_or_operator: Option<RangeOrOperator>,
+) -> bool {
+    let color_bits_per_channel = device.media_environment().color_bits;
+    RangeOrOperator::evaluate(range_or_operator, query_value, color_bits_per_channel)
+}
+
+/// Like `keyword_evaluator!`, for the keywords of `style_traits::media_queries`
+/// that the embedder supplies the values of, which are listed in the order
+/// they are declared in.
+macro_rules! environment_keyword_evaluator {
+    ($actual_evaluator:ident, $keyword_type:ident, [$($variant:ident),+]) => {{
+        fn __keyword(kw: KeywordDiscriminant) -> media_queries::$keyword_type {
+            // This indexing is ok because the only discriminants that get back
+            // to us are the ones that `parse` produces.
+            [$(media_queries::$keyword_type::$variant),+][kw as usize]
+        }
+
+        fn __parse<'i, 't>(
+            _: &ParserContext,
+            input: &mut Parser<'i, 't>,
+        ) -> Result<KeywordDiscriminant, ParseError<'i>> {
+            Ok(media_queries::$keyword_type::parse(input)? as KeywordDiscriminant)
+        }
+
+        fn __serialize(kw: KeywordDiscriminant) -> String {
+            __keyword(kw).to_css_string()
+        }
+
+        fn __evaluate(
+            device: &Device,
+            value: Option<KeywordDiscriminant>,
+            range_or_operator: Option<RangeOrOperator>,
+        ) -> bool {
+            debug_assert!(
+                range_or_operator.is_none(),
+                "Since when do keywords accept ranges?"
+            );
+            $actual_evaluator(device, value.map(__keyword))
+        }
+
+        Evaluator::Enumerated {
+            parser: __parse,
+            serializer: __serialize,
+            evaluator: __evaluate,
+        }
+    }};
+}
+
+/// https://drafts.csswg.org/mediaqueries-5/#prefers-color-scheme
+fn eval_prefers_color_scheme(device: &Device, query_value: Option<PrefersColorScheme>) -> bool {
+    let prefers_color_scheme = device.media_environment().prefers_color_scheme;
+    match query_value {
+        Some(v) => prefers_color_scheme == v,
+        None => prefers_color_scheme != PrefersColorScheme::NoPreference,
+    }
+}
+
+#[derive(Clone, Copy, Debug, FromPrimitive, Parse, ToCss)]
+#[repr(u8)]
+enum PrefersReducedMotion {
+    NoPreference,
+    Reduce,
+}
+
+/// https://drafts.csswg.org/mediaqueries-5/#prefers-reduced-motion
+fn eval_prefers_reduced_motion(device: &Device, query_value: Option<PrefersReducedMotion>) -> bool {
+    let prefers_reduced = device.media_environment().prefers_reduced_motion;
+    let query_value = match query_value {
+        Some(v) => v,
+        None => return prefers_reduced,
+    };
+
+    match query_value {
+        PrefersReducedMotion::NoPreference => !prefers_reduced,
+        PrefersReducedMotion::Reduce => prefers_reduced,
+    }
+}
+
+/// https://w3c.github.io/manifest/#the-display-mode-media-feature
+fn eval_display_mode(device: &Device, query_value: Option<DisplayMode>) -> bool {
+    match query_value {
+        Some(v) => device.media_environment().display_mode == v,
+        None => true,
+    }
+}
+
+#[derive(Clone, Copy, Debug, FromPrimitive, Parse, ToCss)]
+#[repr(u8)]
+enum Pointer {
+    None,
+    Coarse,
+    Fine,
+}
+
+fn eval_pointer_capabilities(
+    query_value: Option<Pointer>,
+    pointer_capabilities: PointerCapabilities,
+) -> bool {
+    let query_value = match query_value {
+        Some(v) => v,
+        None => return !pointer_capabilities.is_empty(),
+    };
+
+    match query_value {
+        Pointer::None => pointer_capabilities.is_empty(),
+        Pointer::Coarse => pointer_capabilities.intersects(PointerCapabilities::COARSE),
+        Pointer::Fine => pointer_capabilities.intersects(PointerCapabilities::FINE),
+    }
+}
+
+/// https://drafts.csswg.org/mediaqueries-4/#pointer
+fn eval_pointer(device: &Device, query_value: Option<Pointer>) -> bool {
+    eval_pointer_capabilities(query_value, device.media_environment().primary_pointer)
+}
+
+/// https://drafts.csswg.org/mediaqueries-4/#descdef-media-any-pointer
+fn eval_any_pointer(device: &Device, query_value: Option<Pointer>) -> bool {
+    eval_pointer_capabilities(query_value, device.media_environment().all_pointers)
+}
+
+#[derive(Clone, Copy, Debug, FromPrimitive, Parse, ToCss)]
+#[repr(u8)]
+enum Hover {
+    None,
+    Hover,
+}
+
+fn eval_hover_capabilities(
+    query_value: Option<Hover>,
+    pointer_capabilities: PointerCapabilities,
+) -> bool {
+    let can_hover = pointer_capabilities.intersects(PointerCapabilities::HOVER);
+    let query_value = match query_value {
+        Some(v) => v,
+        None => return can_hover,
+    };
+
+    match query_value {
+        Hover::None => !can_hover,
+        Hover::Hover => can_hover,
+    }
+}
+
+/// https://drafts.csswg.org/mediaqueries-4/#hover
+fn eval_hover(device: &Device, query_value: Option<Hover>) -> bool {
+    eval_hover_capabilities(query_value, device.media_environment().primary_pointer)
+}
+
+/// https://drafts.csswg.org/mediaqueries-4/#descdef-media-any-hover
+fn eval_any_hover(device: &Device, query_value: Option<Hover>) -> bool {
+    eval_hover_capabilities(query_value, device.media_environment().all_pointers)
+}
+
 lazy_static! {
     /// A list with all the media features that Servo supports.
-    pub static ref MEDIA_FEATURES: [MediaFeatureDescription; 2] = [
+    pub static ref MEDIA_FEATURES: [MediaFeatureDescription; 14] = [
         feature!(
             atom!("width"),
             AllowsRanges::Yes,
             Evaluator::Length(eval_width),
             ParsingRequirements::empty(),
         ),
+        feature!(
+            atom!("height"),
+            AllowsRanges::Yes,
+            Evaluator::Length(eval_height),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("aspect-ratio"),
+            AllowsRanges::Yes,
+            Evaluator::NumberRatio(eval_aspect_ratio),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("orientation"),
+            AllowsRanges::No,
+            keyword_evaluator!(eval_orientation, Orientation),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("resolution"),
+            AllowsRanges::Yes,
+            Evaluator::Resolution(eval_resolution),
+            ParsingRequirements::empty(),
+        ),
         feature!(
             atom!("scan"),
             AllowsRanges::No,
             keyword_evaluator!(eval_scan, Scan),
             ParsingRequirements::empty(),
         ),
+        feature!(
+            atom!("color"),
+            AllowsRanges::Yes,
+            Evaluator::Integer(eval_color),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("prefers-color-scheme"),
+            AllowsRanges::No,
+            environment_keyword_evaluator!(
+                eval_prefers_color_scheme,
+                PrefersColorScheme,
+                [Light, Dark, NoPreference]
+            ),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("prefers-reduced-motion"),
+            AllowsRanges::No,
+            keyword_evaluator!(eval_prefers_reduced_motion, PrefersReducedMotion),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("display-mode"),
+            AllowsRanges::No,
+            environment_keyword_evaluator!(
+                eval_display_mode,
+                DisplayMode,
+                [Browser, MinimalUi, Standalone, Fullscreen]
+            ),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("pointer"),
+            AllowsRanges::No,
+            keyword_evaluator!(eval_pointer, Pointer),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("any-pointer"),
+            AllowsRanges::No,
+            keyword_evaluator!(eval_any_pointer, Pointer),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("hover"),
+            AllowsRanges::No,
+            keyword_evaluator!(eval_hover, Hover),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("any-hover"),
+            AllowsRanges::No,
+            keyword_evaluator!(eval_any_hover, Hover),
+            ParsingRequirements::empty(),
+        ),
     ];
 }
@@ -37,16 +37,13 @@ pub enum PseudoElement {
     After = 0,
     Before,
     Selection,
-    // If/when :first-letter is added, update is_first_letter accordingly.
-
-    // If/when :first-line is added, update is_first_line accordingly.
-
-    // If/when ::first-letter, ::first-line, or ::placeholder are added, adjust
-    // our property_restriction implementation to do property filtering for
-    // them.  Also, make sure the UA sheet has the !important rules some of the
-    // APPLIES_TO_PLACEHOLDER properties expect!
+    FirstLine,
+    FirstLetter,
 
     // Non-eager pseudos.
+    Backdrop,
+    Marker,
+    Placeholder,
     DetailsSummary,
     DetailsContent,
     ServoText,
@@ -78,6 +75,11 @@ impl ToCss for PseudoElement {
             After => "::after",
             Before => "::before",
             Selection => "::selection",
+            FirstLine => "::first-line",
+            FirstLetter => "::first-letter",
+            Backdrop => "::backdrop",
+            Marker => "::marker",
+            Placeholder => "::placeholder",
             DetailsSummary => "::-servo-details-summary",
             DetailsContent => "::-servo-details-content",
             ServoText => "::-servo-text",
@@ -95,7 +97,7 @@ impl ToCss for PseudoElement {
 }
 
 /// The number of eager pseudo-elements. Keep this in sync with cascade_type.
-pub const EAGER_PSEUDO_COUNT: usize = 3;
+pub const EAGER_PSEUDO_COUNT: usize = 5;
 
 impl PseudoElement {
     /// Gets the canonical index of this eagerly-cascaded pseudo-element.
@@ -140,7 +142,7 @@ impl PseudoElement {
     /// Whether this pseudo-element is the ::marker pseudo.
     #[inline]
     pub fn is_marker(&self) -> bool {
-        false
+        *self == PseudoElement::Marker
     }
 
     /// Whether this pseudo-element is the ::selection pseudo.
@@ -164,13 +166,13 @@ impl PseudoElement {
     /// Whether the current pseudo element is :first-letter
     #[inline]
     pub fn is_first_letter(&self) -> bool {
-        false
+        *self == PseudoElement::FirstLetter
     }
 
     /// Whether the current pseudo element is :first-line
     #[inline]
     pub fn is_first_line(&self) -> bool {
-        false
+        *self == PseudoElement::FirstLine
     }
 
     /// Whether this pseudo-element is the ::-moz-color-swatch pseudo.
@@ -217,9 +219,14 @@ impl PseudoElement {
     #[inline]
     pub fn cascade_type(&self) -> PseudoElementCascadeType {
         match *self {
-            PseudoElement::After | PseudoElement::Before | PseudoElement::Selection => {
-                PseudoElementCascadeType::Eager
-            },
+            PseudoElement::After |
+            PseudoElement::Before |
+            PseudoElement::Selection |
+            PseudoElement::FirstLine |
+            PseudoElement::FirstLetter => PseudoElementCascadeType::Eager,
+            PseudoElement::Backdrop |
+            PseudoElement::Marker |
+            PseudoElement::Placeholder |
             PseudoElement::DetailsSummary => PseudoElementCascadeType::Lazy,
             PseudoElement::DetailsContent |
             PseudoElement::ServoText |
@@ -249,7 +256,13 @@ impl PseudoElement {
     /// Property flag that properties must have to apply to this pseudo-element.
     #[inline]
     pub fn property_restriction(&self) -> Option<PropertyFlags> {
-        None
+        Some(match *self {
+            PseudoElement::FirstLetter => PropertyFlags::APPLIES_TO_FIRST_LETTER,
+            PseudoElement::FirstLine => PropertyFlags::APPLIES_TO_FIRST_LINE,
+            PseudoElement::Placeholder => PropertyFlags::APPLIES_TO_PLACEHOLDER,
+            PseudoElement::Marker => PropertyFlags::APPLIES_TO_MARKER,
+            _ => return None,
+        })
     }
 
     /// Whether this pseudo-element should actually exist if it has
@@ -278,19 +291,30 @@ pub enum NonTSPseudoClass {
     Active,
     AnyLink,
     Checked,
+    Default,
+    Defined,
     Disabled,
     Enabled,
     Focus,
+    FocusVisible,
+    FocusWithin,
     Fullscreen,
     Hover,
+    InRange,
     Indeterminate,
+    Invalid,
     Lang(Lang),
     Link,
+    Modal,
+    Optional,
+    OutOfRange,
     PlaceholderShown,
     ReadWrite,
     ReadOnly,
+    Required,
     ServoNonZeroBorder,
     Target,
+    Valid,
     Visited,
 }
 
@@ -306,7 +330,11 @@ impl ::selectors::parser::NonTSPseudoClass for NonTSPseudoClass {
     fn is_user_action_state(&self) -> bool {
         matches!(
             *self,
-            NonTSPseudoClass::Active | NonTSPseudoClass::Hover | NonTSPseudoClass::Focus
+            NonTSPseudoClass::Active |
+                NonTSPseudoClass::Hover |
+                NonTSPseudoClass::Focus |
+                NonTSPseudoClass::FocusVisible |
+                NonTSPseudoClass::FocusWithin
         )
     }
 }
@@ -327,18 +355,29 @@ impl ToCss for NonTSPseudoClass {
             Active => ":active",
             AnyLink => ":any-link",
             Checked => ":checked",
+            Default => ":default",
+            Defined => ":defined",
             Disabled => ":disabled",
             Enabled => ":enabled",
             Focus => ":focus",
+            FocusVisible => ":focus-visible",
+            FocusWithin => ":focus-within",
             Fullscreen => ":fullscreen",
             Hover => ":hover",
+            InRange => ":in-range",
             Indeterminate => ":indeterminate",
+            Invalid => ":invalid",
             Link => ":link",
+            Modal => ":modal",
+            Optional => ":optional",
+            OutOfRange => ":out-of-range",
             PlaceholderShown => ":placeholder-shown",
             ReadWrite => ":read-write",
             ReadOnly => ":read-only",
+            Required => ":required",
             ServoNonZeroBorder => ":-servo-nonzero-border",
             Target => ":target",
+            Valid => ":valid",
             Visited => ":visited",
             Lang(_) => unreachable!(),
         })
@@ -364,15 +403,26 @@ impl NonTSPseudoClass {
         match *self {
             Active => ElementState::IN_ACTIVE_STATE,
             Focus => ElementState::IN_FOCUS_STATE,
+            FocusVisible => ElementState::IN_FOCUSRING_STATE,
+            FocusWithin => ElementState::IN_FOCUS_WITHIN_STATE,
             Fullscreen => ElementState::IN_FULLSCREEN_STATE,
             Hover => ElementState::IN_HOVER_STATE,
             Enabled => ElementState::IN_ENABLED_STATE,
             Disabled => ElementState::IN_DISABLED_STATE,
             Checked => ElementState::IN_CHECKED_STATE,
             Indeterminate => ElementState::IN_INDETERMINATE_STATE,
+            Modal => ElementState::IN_MODAL_DIALOG_STATE,
             ReadOnly | ReadWrite => ElementState::IN_READ_WRITE_STATE,
             PlaceholderShown => ElementState::IN_PLACEHOLDER_SHOWN_STATE,
             Target => ElementState::IN_TARGET_STATE,
+            Valid => ElementState::IN_VALID_STATE,
+            Invalid => ElementState::IN_INVALID_STATE,
+            Required => ElementState::IN_REQUIRED_STATE,
+            Optional => ElementState::IN_OPTIONAL_STATE,
+            InRange => ElementState::IN_INRANGE_STATE,
+            OutOfRange => ElementState::IN_OUTOFRANGE_STATE,
+            Default => ElementState::IN_DEFAULT_STATE,
+            Defined => ElementState::IN_DEFINED_STATE,
 
             AnyLink | Lang(_) | Link | Visited | ServoNonZeroBorder => ElementState::empty(),
         }
@@ -421,6 +471,21 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
     type Impl = SelectorImpl;
     type Error = StyleParseErrorKind<'i>;
 
+    #[inline]
+    fn parse_is_and_where(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn parse_has(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn parse_parent_selector(&self) -> bool {
+        true
+    }
+
     fn parse_non_ts_pseudo_class(
         &self,
         location: SourceLocation,
@@ -431,17 +496,28 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
             "active" => Active,
             "any-link" => AnyLink,
             "checked" => Checked,
+            "default" => Default,
+            "defined" => Defined,
             "disabled" => Disabled,
             "enabled" => Enabled,
             "focus" => Focus,
+            "focus-visible" => FocusVisible,
+            "focus-within" => FocusWithin,
             "fullscreen" => Fullscreen,
             "hover" => Hover,
+            "in-range" => InRange,
             "indeterminate" => Indeterminate,
+            "invalid" => Invalid,
             "link" => Link,
+            "modal" => Modal,
+            "optional" => Optional,
+            "out-of-range" => OutOfRange,
             "placeholder-shown" => PlaceholderShown,
             "read-write" => ReadWrite,
             "read-only" => ReadOnly,
+            "required" => Required,
             "target" => Target,
+            "valid" => Valid,
             "visited" => Visited,
             "-servo-nonzero-border" => {
                 if !self.in_user_agent_stylesheet() {
@@ -483,6 +559,11 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
             "before" => Before,
             "after" => After,
             "selection" => Selection,
+            "first-line" => FirstLine,
+            "first-letter" => FirstLetter,
+            "backdrop" => Backdrop,
+            "marker" => Marker,
+            "placeholder" => Placeholder,
             "-servo-details-summary" => {
                 if !self.in_user_agent_stylesheet() {
                     return Err(location.new_custom_error(SelectorParseErrorKind::UnexpectedIdent(name.clone())))
@@ -631,6 +712,9 @@ pub struct ServoElementSnapshot {
     pub id_changed: bool,
     /// Whether other attributes other than id or class changed or not.
     pub other_attributes_changed: bool,
+    /// Whether a mutation elsewhere in the document may have changed what
+    /// the `:has()` selectors anchored at this element match.
+    pub relative_selectors_invalidated: bool,
 }
 
 impl ServoElementSnapshot {
@@ -643,6 +727,7 @@ impl ServoElementSnapshot {
             class_changed: false,
             id_changed: false,
             other_attributes_changed: false,
+            relative_selectors_invalidated: false,
         }
     }
 
@@ -691,6 +776,10 @@ impl ElementSnapshot for ServoElementSnapshot {
         self.attrs.is_some()
     }
 
+    fn relative_selectors_invalidated(&self) -> bool {
+        self.relative_selectors_invalidated
+    }
+
     fn id_attr(&self) -> Option<&Atom> {
         self.get_attr(&ns!(), &local_name!("id"))
             .map(|v| v.as_atom())
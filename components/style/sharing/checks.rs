@@ -9,6 +9,7 @@
 use crate::bloom::StyleBloom;
 use crate::context::{SelectorFlagsMap, SharedStyleContext};
 use crate::dom::TElement;
+use crate::properties::computed_value_flags::ComputedValueFlags;
 use crate::sharing::{StyleSharingCandidate, StyleSharingTarget};
 use selectors::NthIndexCache;
 
@@ -52,6 +53,18 @@ where
         return false;
     }
 
+    // Container queries and container-relative units depend on the size of
+    // the query containers up the respective parent chains, which may differ
+    // even if the styles are the same.
+    if parent_data
+        .styles
+        .primary()
+        .flags
+        .contains(ComputedValueFlags::IS_IN_QUERY_CONTAINER_SUBTREE)
+    {
+        return false;
+    }
+
     true
 }
 
@@ -607,6 +607,17 @@ impl<E: TElement> StyleSharingCache<E> {
             return;
         }
 
+        // Pseudo-elements of query containers can match container queries
+        // against their size, which may be different even if the styles are
+        // the same.
+        #[cfg(feature = "servo")]
+        {
+            if box_style.clone_container_type().is_query_container() {
+                debug!("Failing to insert to the cache: query container");
+                return;
+            }
+        }
+
         debug!(
             "Inserting into cache: {:?} with parent {:?}",
             element, parent
@@ -249,6 +249,19 @@ impl<'a, 'b: 'a> StyleAdjuster<'a, 'b> {
                 self.style.add_flags(ComputedValueFlags::CAN_BE_FRAGMENTED);
             }
         }
+
+        #[cfg(feature = "servo")]
+        {
+            if self
+                .style
+                .get_box()
+                .clone_container_type()
+                .is_query_container()
+            {
+                self.style
+                    .add_flags(ComputedValueFlags::IS_IN_QUERY_CONTAINER_SUBTREE);
+            }
+        }
     }
 
     /// Adjust the style for text style.
@@ -431,6 +431,7 @@ where
             stylist.push_applicable_declarations(
                 self.element,
                 implemented_pseudo.as_ref(),
+                None,
                 self.element.style_attribute(),
                 self.element.smil_override(),
                 self.element.animation_rules(),
@@ -488,6 +489,12 @@ where
             return None;
         }
 
+        // Most documents don't style most eager pseudo-elements, so avoid
+        // matching all the rules against them.
+        if !stylist.may_have_rules_for_pseudo(self.element, pseudo_element) {
+            return None;
+        }
+
         let bloom_filter = self.context.thread_local.bloom_filter.filter();
         let nth_index_cache = &mut self.context.thread_local.nth_index_cache;
 
@@ -510,6 +517,7 @@ where
         stylist.push_applicable_declarations(
             self.element,
             Some(pseudo_element),
+            Some(originating_element_style),
             None,
             None,
             AnimationRules(None, None),
@@ -524,6 +524,19 @@ where
         origins
     }
 
+    /// Flush stylesheets without running any of the invalidation passes, but
+    /// returning a flusher to rebuild the cascade data with.
+    ///
+    /// Used to test the cascade without a DOM.
+    pub fn flush_for_testing(&mut self) -> DocumentStylesheetFlusher<S> {
+        self.invalidations.clear();
+
+        DocumentStylesheetFlusher {
+            collections: &mut self.collections,
+            had_invalidations: false,
+        }
+    }
+
     /// Return an iterator over the flattened view of all the stylesheets.
     pub fn iter(&self) -> StylesheetIterator<S> {
         StylesheetIterator {
@@ -0,0 +1,808 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A [`@container`][container] rule, and the machinery to evaluate container
+//! queries against the size of query containers.
+//!
+//! [container]: https://drafts.csswg.org/css-contain-3/#container-rule
+
+use crate::context::QuirksMode;
+use crate::dom::TElement;
+use crate::logical_geometry::WritingMode;
+use crate::media_queries::media_condition::Operator;
+use crate::media_queries::media_feature_expression::{consume_operation_or_colon, AspectRatio};
+use crate::media_queries::media_feature_expression::{Range, RangeOrOperator};
+use crate::media_queries::Device;
+use crate::parser::ParserContext;
+use crate::properties::ComputedValues;
+use crate::shared_lock::{DeepCloneParams, DeepCloneWithLock, Locked};
+use crate::shared_lock::{SharedRwLock, SharedRwLockReadGuard, ToCssWithGuard};
+use crate::str::{starts_with_ignore_ascii_case, CssStringWriter};
+use crate::stylesheets::CssRules;
+use crate::values::computed::{self, ToComputedValue};
+use crate::values::specified::{ContainerType, Integer, Length};
+use crate::values::{CSSFloat, CustomIdent};
+use app_units::Au;
+use cssparser::{Parser, SourceLocation, Token};
+use euclid::default::Size2D;
+#[cfg(feature = "gecko")]
+use malloc_size_of::{MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
+use servo_arc::Arc;
+use std::cell::Cell;
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
+
+/// A [`@container`][container] rule.
+///
+/// [container]: https://drafts.csswg.org/css-contain-3/#container-rule
+#[derive(Debug, ToShmem)]
+pub struct ContainerRule {
+    /// The container name and query of this rule.
+    pub condition: Arc<ContainerCondition>,
+    /// The nested rules to this container rule.
+    pub rules: Arc<Locked<CssRules>>,
+    /// The source position where this container rule was found.
+    pub source_location: SourceLocation,
+}
+
+impl ContainerRule {
+    /// Measure heap usage.
+    #[cfg(feature = "gecko")]
+    pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
+        // Measurement of other fields may be added later.
+        self.rules.unconditional_shallow_size_of(ops) +
+            self.rules.read_with(guard).size_of(guard, ops)
+    }
+}
+
+impl ToCssWithGuard for ContainerRule {
+    fn to_css(&self, guard: &SharedRwLockReadGuard, dest: &mut CssStringWriter) -> fmt::Result {
+        dest.write_str("@container ")?;
+        self.condition.to_css(&mut CssWriter::new(dest))?;
+        self.rules.read_with(guard).to_css_block(guard, dest)
+    }
+}
+
+impl DeepCloneWithLock for ContainerRule {
+    fn deep_clone_with_lock(
+        &self,
+        lock: &SharedRwLock,
+        guard: &SharedRwLockReadGuard,
+        params: &DeepCloneParams,
+    ) -> Self {
+        let rules = self.rules.read_with(guard);
+        ContainerRule {
+            condition: self.condition.clone(),
+            rules: Arc::new(lock.wrap(rules.deep_clone_with_lock(lock, guard, params))),
+            source_location: self.source_location.clone(),
+        }
+    }
+}
+
+/// The prelude of a `@container` rule: an optional container name, and a
+/// container query.
+#[derive(Debug, ToShmem)]
+pub struct ContainerCondition {
+    /// The name of the query containers this condition applies to, if any.
+    pub name: Option<CustomIdent>,
+    /// The query to evaluate against the query container.
+    pub condition: QueryCondition,
+}
+
+impl ToCss for ContainerCondition {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        if let Some(ref name) = self.name {
+            name.to_css(dest)?;
+            dest.write_char(' ')?;
+        }
+        self.condition.to_css(dest)
+    }
+}
+
+impl ContainerCondition {
+    /// Parse the prelude of a `@container` rule.
+    pub fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let name = input
+            .try(|input| {
+                let location = input.current_source_location();
+                let ident = input.expect_ident()?;
+                CustomIdent::from_ident(location, ident, &["none", "and", "or", "not"])
+            })
+            .ok();
+        let condition = QueryCondition::parse(context, input)?;
+        Ok(Self { name, condition })
+    }
+
+    /// Returns whether this condition matches for `element`.
+    ///
+    /// The query is evaluated against the nearest ancestor of `element` in
+    /// the flat tree that is a query container with the right name. When
+    /// matching a pseudo-element, `element` is its originating element, which
+    /// is itself considered first, with `originating_element_style` as its
+    /// style. Queries without a query container, or whose result is unknown,
+    /// don't match.
+    pub fn matches<E>(
+        &self,
+        device: &Device,
+        quirks_mode: QuirksMode,
+        element: E,
+        originating_element_style: Option<&ComputedValues>,
+    ) -> bool
+    where
+        E: TElement,
+    {
+        if let Some(style) = originating_element_style {
+            if self.applies_to(style) {
+                return self.evaluate_for_container(
+                    device,
+                    quirks_mode,
+                    style,
+                    element.query_container_size(),
+                );
+            }
+        }
+
+        let container =
+            find_query_container(element.traversal_parent(), |style| self.applies_to(style));
+        match container {
+            Some((_, style, size)) => self.evaluate(device, quirks_mode, &style, &size),
+            None => false,
+        }
+    }
+
+    /// Evaluates the query of this condition against a query container with
+    /// the given style, whose content box had the given size as of the last
+    /// layout, if it has been laid out.
+    pub fn evaluate_for_container(
+        &self,
+        device: &Device,
+        quirks_mode: QuirksMode,
+        container_style: &ComputedValues,
+        size: Option<Size2D<Au>>,
+    ) -> bool {
+        let size = ContainerSize::new(container_style, size);
+        self.evaluate(device, quirks_mode, container_style, &size)
+    }
+
+    /// Whether an element with the given style is a query container this
+    /// condition can be evaluated against.
+    fn applies_to(&self, style: &ComputedValues) -> bool {
+        if !container_type(style).is_query_container() {
+            return false;
+        }
+        match self.name {
+            Some(ref name) => has_container_name(style, name),
+            None => true,
+        }
+    }
+
+    fn evaluate(
+        &self,
+        device: &Device,
+        quirks_mode: QuirksMode,
+        container_style: &ComputedValues,
+        size: &ContainerSize,
+    ) -> bool {
+        computed::Context::for_container_query_evaluation(
+            device,
+            quirks_mode,
+            container_style,
+            |context| self.condition.matches(context, size),
+        )
+        .unwrap_or(false)
+    }
+}
+
+/// A container query, that is, a condition on the size of a query container.
+///
+/// <https://drafts.csswg.org/css-contain-3/#typedef-container-condition>
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToShmem)]
+pub enum QueryCondition {
+    /// A size feature expression, implicitly parenthesized.
+    Feature(SizeFeatureExpression),
+    /// A negation of a condition.
+    Not(Box<QueryCondition>),
+    /// A set of joint operations.
+    Operation(Box<[QueryCondition]>, Operator),
+    /// A condition wrapped in parenthesis.
+    InParens(Box<QueryCondition>),
+}
+
+impl ToCss for QueryCondition {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        match *self {
+            // NOTE: SizeFeatureExpression already includes the parenthesis.
+            QueryCondition::Feature(ref f) => f.to_css(dest),
+            QueryCondition::Not(ref c) => {
+                dest.write_str("not ")?;
+                c.to_css(dest)
+            },
+            QueryCondition::InParens(ref c) => {
+                dest.write_char('(')?;
+                c.to_css(dest)?;
+                dest.write_char(')')
+            },
+            QueryCondition::Operation(ref list, op) => {
+                let mut iter = list.iter();
+                iter.next().unwrap().to_css(dest)?;
+                for item in iter {
+                    dest.write_char(' ')?;
+                    op.to_css(dest)?;
+                    dest.write_char(' ')?;
+                    item.to_css(dest)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl QueryCondition {
+    /// Parse a container query condition.
+    pub fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let location = input.current_source_location();
+
+        let is_negation = match *input.next()? {
+            Token::ParenthesisBlock => false,
+            Token::Ident(ref ident) if ident.eq_ignore_ascii_case("not") => true,
+            ref t => return Err(location.new_unexpected_token_error(t.clone())),
+        };
+
+        if is_negation {
+            let inner_condition = Self::parse_in_parens(context, input)?;
+            return Ok(QueryCondition::Not(Box::new(inner_condition)));
+        }
+
+        let first_condition = Self::parse_paren_block(context, input)?;
+        let operator = match input.try(|input| {
+            let location = input.current_source_location();
+            let ident = input.expect_ident()?;
+            match_ignore_ascii_case! { &ident,
+                "and" => Ok(Operator::And),
+                "or" => Ok(Operator::Or),
+                _ => Err(location.new_custom_error(
+                    StyleParseErrorKind::UnexpectedIdent(ident.clone())
+                )),
+            }
+        }) {
+            Ok(op) => op,
+            Err(..) => return Ok(first_condition),
+        };
+
+        let mut conditions = vec![];
+        conditions.push(first_condition);
+        conditions.push(Self::parse_in_parens(context, input)?);
+
+        let delim = match operator {
+            Operator::And => "and",
+            Operator::Or => "or",
+        };
+
+        loop {
+            if input.try(|i| i.expect_ident_matching(delim)).is_err() {
+                return Ok(QueryCondition::Operation(
+                    conditions.into_boxed_slice(),
+                    operator,
+                ));
+            }
+
+            conditions.push(Self::parse_in_parens(context, input)?);
+        }
+    }
+
+    fn parse_in_parens<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        input.expect_parenthesis_block()?;
+        Self::parse_paren_block(context, input)
+    }
+
+    fn parse_paren_block<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        input.parse_nested_block(|input| {
+            if let Ok(inner) = input.try(|i| Self::parse(context, i)) {
+                return Ok(QueryCondition::InParens(Box::new(inner)));
+            }
+            let expr = SizeFeatureExpression::parse_in_parenthesis_block(context, input)?;
+            Ok(QueryCondition::Feature(expr))
+        })
+    }
+
+    /// Evaluates this condition against a query container of the given size,
+    /// returning `None` if the result is unknown.
+    ///
+    /// <https://drafts.csswg.org/css-contain-3/#evaluate-a-container-query>
+    fn matches(&self, context: &computed::Context, size: &ContainerSize) -> Option<bool> {
+        match *self {
+            QueryCondition::Feature(ref f) => f.matches(context, size),
+            QueryCondition::InParens(ref c) => c.matches(context, size),
+            QueryCondition::Not(ref c) => c.matches(context, size).map(|result| !result),
+            QueryCondition::Operation(ref conditions, op) => {
+                let mut result = Some(op == Operator::And);
+                for condition in conditions.iter() {
+                    match (op, condition.matches(context, size)) {
+                        (Operator::And, Some(false)) => return Some(false),
+                        (Operator::Or, Some(true)) => return Some(true),
+                        (_, None) => result = None,
+                        _ => {},
+                    }
+                }
+                result
+            },
+        }
+    }
+}
+
+/// A size feature that can be queried in a container query.
+///
+/// <https://drafts.csswg.org/css-contain-3/#container-size-query>
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
+#[allow(missing_docs)]
+pub enum SizeFeature {
+    Width,
+    Height,
+    InlineSize,
+    BlockSize,
+    AspectRatio,
+    Orientation,
+}
+
+impl SizeFeature {
+    fn name(self) -> &'static str {
+        match self {
+            SizeFeature::Width => "width",
+            SizeFeature::Height => "height",
+            SizeFeature::InlineSize => "inline-size",
+            SizeFeature::BlockSize => "block-size",
+            SizeFeature::AspectRatio => "aspect-ratio",
+            SizeFeature::Orientation => "orientation",
+        }
+    }
+
+    fn allows_ranges(self) -> bool {
+        self != SizeFeature::Orientation
+    }
+}
+
+/// The value of the `orientation` size feature.
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, Parse, PartialEq, ToCss, ToShmem)]
+#[allow(missing_docs)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+/// A value found in a size feature expression.
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToShmem)]
+#[allow(missing_docs)]
+pub enum SizeFeatureValue {
+    Length(Length),
+    Ratio(AspectRatio),
+    Orientation(Orientation),
+}
+
+impl ToCss for SizeFeatureValue {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        match *self {
+            SizeFeatureValue::Length(ref l) => l.to_css(dest),
+            SizeFeatureValue::Ratio(ref r) => r.to_css(dest),
+            SizeFeatureValue::Orientation(ref o) => o.to_css(dest),
+        }
+    }
+}
+
+/// A size feature expression, like `(width >= 400px)` or `(orientation:
+/// portrait)`.
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToShmem)]
+pub struct SizeFeatureExpression {
+    feature: SizeFeature,
+    value: Option<SizeFeatureValue>,
+    range_or_operator: Option<RangeOrOperator>,
+}
+
+impl ToCss for SizeFeatureExpression {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        dest.write_char('(')?;
+
+        if let Some(RangeOrOperator::Range(range)) = self.range_or_operator {
+            match range {
+                Range::Min => dest.write_str("min-")?,
+                Range::Max => dest.write_str("max-")?,
+            }
+        }
+
+        dest.write_str(self.feature.name())?;
+
+        if let Some(RangeOrOperator::Operator(op)) = self.range_or_operator {
+            dest.write_char(' ')?;
+            op.to_css(dest)?;
+            dest.write_char(' ')?;
+        } else if self.value.is_some() {
+            dest.write_str(": ")?;
+        }
+
+        if let Some(ref value) = self.value {
+            value.to_css(dest)?;
+        }
+
+        dest.write_char(')')
+    }
+}
+
+impl SizeFeatureExpression {
+    /// Parse a size feature expression where we've already consumed the
+    /// parenthesis.
+    fn parse_in_parenthesis_block<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let location = input.current_source_location();
+        let ident = input.expect_ident()?;
+
+        let mut feature_name = &**ident;
+        let range = if starts_with_ignore_ascii_case(feature_name, "min-") {
+            feature_name = &feature_name[4..];
+            Some(Range::Min)
+        } else if starts_with_ignore_ascii_case(feature_name, "max-") {
+            feature_name = &feature_name[4..];
+            Some(Range::Max)
+        } else {
+            None
+        };
+
+        let feature = match_ignore_ascii_case! { feature_name,
+            "width" => SizeFeature::Width,
+            "height" => SizeFeature::Height,
+            "inline-size" => SizeFeature::InlineSize,
+            "block-size" => SizeFeature::BlockSize,
+            "aspect-ratio" => SizeFeature::AspectRatio,
+            "orientation" => SizeFeature::Orientation,
+            _ => return Err(location.new_custom_error(
+                StyleParseErrorKind::MediaQueryExpectedFeatureName(ident.clone())
+            )),
+        };
+
+        if range.is_some() && !feature.allows_ranges() {
+            return Err(location.new_custom_error(
+                StyleParseErrorKind::MediaQueryExpectedFeatureName(ident.clone()),
+            ));
+        }
+
+        let operator = match input.try(consume_operation_or_colon) {
+            Ok(operator) => operator,
+            Err(..) => {
+                if range.is_some() {
+                    return Err(
+                        input.new_custom_error(StyleParseErrorKind::RangedExpressionWithNoValue)
+                    );
+                }
+                return Ok(Self {
+                    feature,
+                    value: None,
+                    range_or_operator: None,
+                });
+            },
+        };
+
+        let range_or_operator = match (range, operator) {
+            (Some(..), Some(..)) => {
+                return Err(
+                    input.new_custom_error(StyleParseErrorKind::MediaQueryUnexpectedOperator)
+                );
+            },
+            (Some(range), None) => Some(RangeOrOperator::Range(range)),
+            (None, Some(operator)) => {
+                if !feature.allows_ranges() {
+                    return Err(
+                        input.new_custom_error(StyleParseErrorKind::MediaQueryUnexpectedOperator)
+                    );
+                }
+                Some(RangeOrOperator::Operator(operator))
+            },
+            (None, None) => None,
+        };
+
+        let value = Self::parse_value(feature, context, input).map_err(|err| {
+            err.location
+                .new_custom_error(StyleParseErrorKind::MediaQueryExpectedFeatureValue)
+        })?;
+
+        Ok(Self {
+            feature,
+            value: Some(value),
+            range_or_operator,
+        })
+    }
+
+    fn parse_value<'i, 't>(
+        feature: SizeFeature,
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<SizeFeatureValue, ParseError<'i>> {
+        Ok(match feature {
+            SizeFeature::Width |
+            SizeFeature::Height |
+            SizeFeature::InlineSize |
+            SizeFeature::BlockSize => {
+                SizeFeatureValue::Length(Length::parse_non_negative(context, input)?)
+            },
+            SizeFeature::AspectRatio => {
+                let a = Integer::parse_positive(context, input)?;
+                input.expect_delim('/')?;
+                let b = Integer::parse_positive(context, input)?;
+                SizeFeatureValue::Ratio(AspectRatio(a.value() as CSSFloat, b.value() as CSSFloat))
+            },
+            SizeFeature::Orientation => SizeFeatureValue::Orientation(Orientation::parse(input)?),
+        })
+    }
+
+    fn matches(&self, context: &computed::Context, size: &ContainerSize) -> Option<bool> {
+        let value = match self.feature {
+            SizeFeature::Width => size.width,
+            SizeFeature::Height => size.height,
+            SizeFeature::InlineSize => size.inline_size(),
+            SizeFeature::BlockSize => size.block_size(),
+            SizeFeature::AspectRatio => {
+                let (width, height) = (size.width?, size.height?);
+                let query_value = match self.value {
+                    Some(SizeFeatureValue::Ratio(ratio)) => ratio,
+                    _ => return Some(true),
+                };
+                let value = AspectRatio(width.0 as f32, height.0 as f32);
+                return Some(RangeOrOperator::evaluate_with_query_value(
+                    self.range_or_operator,
+                    query_value,
+                    value,
+                ));
+            },
+            SizeFeature::Orientation => {
+                let (width, height) = (size.width?, size.height?);
+                // Per spec, square containers are 'portrait'.
+                let is_landscape = width > height;
+                return Some(match self.value {
+                    Some(SizeFeatureValue::Orientation(Orientation::Landscape)) => is_landscape,
+                    Some(SizeFeatureValue::Orientation(Orientation::Portrait)) => !is_landscape,
+                    _ => true,
+                });
+            },
+        };
+
+        let value = value?;
+        let query_value = match self.value {
+            Some(SizeFeatureValue::Length(ref length)) => {
+                Au::from(length.to_computed_value(context))
+            },
+            _ => return Some(value != Au(0)),
+        };
+        Some(RangeOrOperator::evaluate_with_query_value(
+            self.range_or_operator,
+            query_value,
+            value,
+        ))
+    }
+}
+
+#[cfg(feature = "servo")]
+fn container_type(style: &ComputedValues) -> ContainerType {
+    style.get_box().clone_container_type()
+}
+
+#[cfg(feature = "servo")]
+fn has_container_name(style: &ComputedValues, name: &CustomIdent) -> bool {
+    style.get_box().container_name.contains(name)
+}
+
+// Gecko doesn't support the `container-type` and `container-name` properties
+// yet, so there are no query containers there.
+#[cfg(feature = "gecko")]
+fn container_type(_: &ComputedValues) -> ContainerType {
+    ContainerType::Normal
+}
+
+#[cfg(feature = "gecko")]
+fn has_container_name(_: &ComputedValues, _: &CustomIdent) -> bool {
+    false
+}
+
+/// The size of a query container in each physical axis, or `None` in the axes
+/// the container can't be queried in, either because of its `container-type`,
+/// or because it hasn't been laid out yet.
+#[derive(Clone, Copy, Debug)]
+struct ContainerSize {
+    width: Option<Au>,
+    height: Option<Au>,
+    writing_mode: WritingMode,
+}
+
+impl ContainerSize {
+    fn new(style: &ComputedValues, size: Option<Size2D<Au>>) -> Self {
+        let writing_mode = style.writing_mode;
+        let (width, height) = match size {
+            None => (None, None),
+            Some(size) => match container_type(style) {
+                ContainerType::Normal => (None, None),
+                ContainerType::Size => (Some(size.width), Some(size.height)),
+                ContainerType::InlineSize if writing_mode.is_vertical() => {
+                    (None, Some(size.height))
+                },
+                ContainerType::InlineSize => (Some(size.width), None),
+            },
+        };
+        Self {
+            width,
+            height,
+            writing_mode,
+        }
+    }
+
+    fn inline_size(&self) -> Option<Au> {
+        if self.writing_mode.is_vertical() {
+            self.height
+        } else {
+            self.width
+        }
+    }
+
+    fn block_size(&self) -> Option<Au> {
+        if self.writing_mode.is_vertical() {
+            self.width
+        } else {
+            self.height
+        }
+    }
+}
+
+/// Finds the nearest query container that is an inclusive ancestor of
+/// `element` in the flat tree and for which `filter` returns true, and returns
+/// it along with its style and size.
+fn find_query_container<E, F>(
+    mut element: Option<E>,
+    mut filter: F,
+) -> Option<(E, Arc<ComputedValues>, ContainerSize)>
+where
+    E: TElement,
+    F: FnMut(&ComputedValues) -> bool,
+{
+    while let Some(current) = element {
+        element = current.traversal_parent();
+        let data = match current.borrow_data() {
+            Some(data) => data,
+            None => continue,
+        };
+        let style = match data.styles.get_primary() {
+            Some(style) => style,
+            None => continue,
+        };
+        if !filter(style) {
+            continue;
+        }
+        let size = ContainerSize::new(style, current.query_container_size());
+        return Some((current, style.clone(), size));
+    }
+    None
+}
+
+/// The sizes that container-relative units resolve against, that is, those of
+/// the nearest query container that can be queried in each axis, if any.
+///
+/// <https://drafts.csswg.org/css-contain-3/#container-lengths>
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ContainerUnitSizes {
+    /// The size `cqw` units resolve against.
+    pub width: Option<Au>,
+    /// The size `cqh` units resolve against.
+    pub height: Option<Au>,
+    /// The size `cqi` units resolve against.
+    pub inline_size: Option<Au>,
+    /// The size `cqb` units resolve against.
+    pub block_size: Option<Au>,
+}
+
+impl ContainerUnitSizes {
+    /// Looks up the container sizes for the style of `element`, which are
+    /// those of the query containers among its ancestors in the flat tree.
+    ///
+    /// For a pseudo-element, `element` is its originating element, which is
+    /// looked at too, with `originating_element_style` as its style.
+    pub fn for_element<E>(element: E, originating_element_style: Option<&ComputedValues>) -> Self
+    where
+        E: TElement,
+    {
+        element.note_query_container_size_dependency();
+
+        let mut sizes = Self::default();
+        if let Some(style) = originating_element_style {
+            if container_type(style).is_query_container() {
+                sizes.merge(&ContainerSize::new(style, element.query_container_size()));
+            }
+        }
+
+        let mut ancestor = element.traversal_parent();
+        while !sizes.is_complete() {
+            let (container, _, size) = match find_query_container(ancestor, |style| {
+                container_type(style).is_query_container()
+            }) {
+                Some(container) => container,
+                None => break,
+            };
+            sizes.merge(&size);
+            ancestor = container.traversal_parent();
+        }
+        sizes
+    }
+
+    fn merge(&mut self, size: &ContainerSize) {
+        self.width = self.width.or(size.width);
+        self.height = self.height.or(size.height);
+        self.inline_size = self.inline_size.or(size.inline_size());
+        self.block_size = self.block_size.or(size.block_size());
+    }
+
+    fn is_complete(&self) -> bool {
+        self.width.is_some() &&
+            self.height.is_some() &&
+            self.inline_size.is_some() &&
+            self.block_size.is_some()
+    }
+}
+
+/// A lazily-evaluated query for the `ContainerUnitSizes` of the element whose
+/// style is being computed.
+pub struct ContainerSizeQuery<'a> {
+    resolver: Option<&'a dyn Fn() -> ContainerUnitSizes>,
+    sizes: Cell<Option<ContainerUnitSizes>>,
+}
+
+impl<'a> ContainerSizeQuery<'a> {
+    /// A query for when there's no element to look up query containers for.
+    pub fn none() -> Self {
+        Self {
+            resolver: None,
+            sizes: Cell::new(None),
+        }
+    }
+
+    /// A query that resolves the sizes with `resolver` the first time they're
+    /// needed.
+    pub fn new(resolver: &'a dyn Fn() -> ContainerUnitSizes) -> Self {
+        Self {
+            resolver: Some(resolver),
+            sizes: Cell::new(None),
+        }
+    }
+
+    /// Returns the container sizes.
+    pub fn get(&self) -> ContainerUnitSizes {
+        if let Some(sizes) = self.sizes.get() {
+            return sizes;
+        }
+        let sizes = match self.resolver {
+            Some(resolver) => resolver(),
+            None => ContainerUnitSizes::default(),
+        };
+        self.sizes.set(Some(sizes));
+        sizes
+    }
+}
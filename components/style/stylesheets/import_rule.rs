@@ -8,15 +8,17 @@
 
 use crate::context::QuirksMode;
 use crate::media_queries::MediaList;
+use crate::parser::{Parse, ParserContext};
 use crate::shared_lock::{DeepCloneParams, DeepCloneWithLock};
 use crate::shared_lock::{SharedRwLock, SharedRwLockReadGuard, ToCssWithGuard};
 use crate::str::CssStringWriter;
+use crate::stylesheets::layer_rule::LayerName;
 use crate::stylesheets::{CssRule, Origin, StylesheetInDocument};
 use crate::values::CssUrl;
-use cssparser::SourceLocation;
+use cssparser::{Parser, SourceLocation};
 use std::fmt::{self, Write};
 use std::mem::ManuallyDrop;
-use style_traits::{CssWriter, ToCss};
+use style_traits::{CssWriter, ParseError, ToCss};
 use to_shmem::{SharedMemoryBuilder, ToShmem};
 
 /// With asynchronous stylesheet parsing, we can't synchronously create a
@@ -166,6 +168,55 @@ impl DeepCloneWithLock for ImportSheet {
     }
 }
 
+/// The cascade layer an `@import` rule imports its stylesheet into.
+///
+/// https://drafts.csswg.org/css-cascade-5/#at-import
+#[derive(Clone, Debug)]
+pub struct ImportLayer {
+    /// The layer name, or `None` for an anonymous layer.
+    pub name: Option<LayerName>,
+}
+
+impl ImportLayer {
+    /// Parses the optional `layer` or `layer(<layer-name>)` part of an
+    /// `@import` prelude.
+    pub fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Option<Self>, ParseError<'i>> {
+        if input
+            .try(|input| input.expect_ident_matching("layer"))
+            .is_ok()
+        {
+            return Ok(Some(ImportLayer { name: None }));
+        }
+        if input
+            .try(|input| input.expect_function_matching("layer"))
+            .is_err()
+        {
+            return Ok(None);
+        }
+        let name = input.parse_nested_block(|input| LayerName::parse(context, input))?;
+        Ok(Some(ImportLayer { name: Some(name) }))
+    }
+}
+
+impl ToCss for ImportLayer {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        match self.name {
+            None => dest.write_str("layer"),
+            Some(ref name) => {
+                dest.write_str("layer(")?;
+                name.to_css(dest)?;
+                dest.write_char(')')
+            },
+        }
+    }
+}
+
 /// The [`@import`][import] at-rule.
 ///
 /// [import]: https://drafts.csswg.org/css-cascade-3/#at-import
@@ -174,6 +225,9 @@ pub struct ImportRule {
     /// The `<url>` this `@import` rule is loading.
     pub url: CssUrl,
 
+    /// The cascade layer the stylesheet is imported into, if any.
+    pub layer: Option<ImportLayer>,
+
     /// The stylesheet is always present. However, in the case of gecko async
     /// parsing, we don't actually have a Gecko sheet at first, and so the
     /// ImportSheet just has stub behavior until it appears.
@@ -198,6 +252,7 @@ impl DeepCloneWithLock for ImportRule {
     ) -> Self {
         ImportRule {
             url: self.url.clone(),
+            layer: self.layer.clone(),
             stylesheet: self.stylesheet.deep_clone_with_lock(lock, guard, params),
             source_location: self.source_location.clone(),
         }
@@ -209,6 +264,11 @@ impl ToCssWithGuard for ImportRule {
         dest.write_str("@import ")?;
         self.url.to_css(&mut CssWriter::new(dest))?;
 
+        if let Some(ref layer) = self.layer {
+            dest.write_str(" ")?;
+            layer.to_css(&mut CssWriter::new(dest))?;
+        }
+
         match self.stylesheet.media(guard) {
             Some(media) if !media.is_empty() => {
                 dest.write_str(" ")?;
@@ -0,0 +1,222 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A [`@layer`][layer] rule.
+//!
+//! [layer]: https://drafts.csswg.org/css-cascade-5/#layering
+
+use crate::parser::{Parse, ParserContext};
+use crate::shared_lock::{DeepCloneParams, DeepCloneWithLock, Locked};
+use crate::shared_lock::{SharedRwLock, SharedRwLockReadGuard, ToCssWithGuard};
+use crate::str::CssStringWriter;
+use crate::stylesheets::CssRules;
+use crate::values::{serialize_atom_identifier, CustomIdent};
+use crate::Atom;
+use cssparser::{Parser, SourceLocation, Token};
+#[cfg(feature = "gecko")]
+use malloc_size_of::{MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
+use servo_arc::Arc;
+use smallvec::SmallVec;
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ParseError, ToCss};
+
+/// The order of a given cascade layer, relative to the rest of the layers in
+/// the same cascade origin and tree.
+///
+/// Rules in later layers win over rules in earlier layers, except for
+/// `!important` declarations, for which the order is reversed.
+#[derive(Clone, Copy, Debug, Eq, Hash, MallocSizeOf, Ord, PartialEq, PartialOrd)]
+pub struct LayerOrder(u16);
+
+impl LayerOrder {
+    /// The order of the implicit outer layer, that is, of the rules that
+    /// aren't in any layer.
+    #[inline]
+    pub const fn root() -> Self {
+        Self(std::u16::MAX - 1)
+    }
+
+    /// The order of the style attribute, which wins over all the layers, even
+    /// for `!important` declarations.
+    #[inline]
+    pub const fn style_attribute() -> Self {
+        Self(std::u16::MAX)
+    }
+
+    /// The order of the first layer.
+    #[inline]
+    pub const fn first() -> Self {
+        Self(0)
+    }
+
+    /// Moves to the next layer order, saturating right before the root layer.
+    #[inline]
+    pub fn inc(&mut self) {
+        if self.0 < Self::root().0 - 1 {
+            self.0 += 1;
+        }
+    }
+
+    /// Returns a key to sort `!important` declarations by, which reverses the
+    /// order of the layers, but keeps the style attribute last.
+    ///
+    /// https://drafts.csswg.org/css-cascade-5/#cascade-layering
+    #[inline]
+    pub fn important_sort_key(self) -> u16 {
+        if self == Self::style_attribute() {
+            return self.0;
+        }
+        Self::root().0 - self.0
+    }
+}
+
+/// A `<layer-name>`: a list of dot-separated identifiers.
+#[derive(Clone, Debug, Eq, Hash, MallocSizeOf, PartialEq, ToShmem)]
+pub struct LayerName(pub SmallVec<[Atom; 1]>);
+
+impl LayerName {
+    /// Returns an empty layer name, which is not a valid name on its own.
+    pub fn new_empty() -> Self {
+        Self(SmallVec::new())
+    }
+
+    /// Returns the names of the layers this name is composed of.
+    pub fn layer_names(&self) -> &[Atom] {
+        &self.0
+    }
+}
+
+impl Parse for LayerName {
+    fn parse<'i, 't>(
+        _: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let mut result = SmallVec::new();
+        let location = input.current_source_location();
+        result.push(CustomIdent::from_ident(location, input.expect_ident()?, &[])?.0);
+        loop {
+            // No whitespace is allowed between the names and the dots.
+            let next_name = input.try(|input| -> Result<Atom, ParseError<'i>> {
+                match input.next_including_whitespace()? {
+                    Token::Delim('.') => {},
+                    other => {
+                        let t = other.clone();
+                        return Err(input.new_unexpected_token_error(t));
+                    },
+                }
+
+                let location = input.current_source_location();
+                let name = match input.next_including_whitespace()? {
+                    Token::Ident(ref ident) => ident.clone(),
+                    other => {
+                        let t = other.clone();
+                        return Err(input.new_unexpected_token_error(t));
+                    },
+                };
+                Ok(CustomIdent::from_ident(location, &name, &[])?.0)
+            });
+
+            match next_name {
+                Ok(name) => result.push(name),
+                Err(..) => break,
+            }
+        }
+        Ok(LayerName(result))
+    }
+}
+
+impl ToCss for LayerName {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        let mut first = true;
+        for name in self.0.iter() {
+            if !first {
+                dest.write_char('.')?;
+            }
+            first = false;
+            serialize_atom_identifier(name, dest)?;
+        }
+        Ok(())
+    }
+}
+
+/// A block `@layer` rule, which may be anonymous.
+///
+/// https://drafts.csswg.org/css-cascade-5/#layer-block
+#[derive(Debug, ToShmem)]
+pub struct LayerBlockRule {
+    /// The layer name, or `None` if anonymous.
+    pub name: Option<LayerName>,
+    /// The nested rules.
+    pub rules: Arc<Locked<CssRules>>,
+    /// The source position where this rule was found.
+    pub source_location: SourceLocation,
+}
+
+impl LayerBlockRule {
+    /// Measure heap usage.
+    #[cfg(feature = "gecko")]
+    pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
+        // Measurement of other fields may be added later.
+        self.rules.unconditional_shallow_size_of(ops) +
+            self.rules.read_with(guard).size_of(guard, ops)
+    }
+}
+
+impl ToCssWithGuard for LayerBlockRule {
+    fn to_css(&self, guard: &SharedRwLockReadGuard, dest: &mut CssStringWriter) -> fmt::Result {
+        dest.write_str("@layer")?;
+        if let Some(ref name) = self.name {
+            dest.write_char(' ')?;
+            name.to_css(&mut CssWriter::new(dest))?;
+        }
+        self.rules.read_with(guard).to_css_block(guard, dest)
+    }
+}
+
+impl DeepCloneWithLock for LayerBlockRule {
+    fn deep_clone_with_lock(
+        &self,
+        lock: &SharedRwLock,
+        guard: &SharedRwLockReadGuard,
+        params: &DeepCloneParams,
+    ) -> Self {
+        let rules = self.rules.read_with(guard);
+        Self {
+            name: self.name.clone(),
+            rules: Arc::new(lock.wrap(rules.deep_clone_with_lock(lock, guard, params))),
+            source_location: self.source_location.clone(),
+        }
+    }
+}
+
+/// A statement `@layer` rule, which only declares the order of the given
+/// layers.
+///
+/// https://drafts.csswg.org/css-cascade-5/#layer-empty
+#[derive(Clone, Debug, ToShmem)]
+pub struct LayerStatementRule {
+    /// The list of layers to declare.
+    pub names: Vec<LayerName>,
+    /// The source position where this rule was found.
+    pub source_location: SourceLocation,
+}
+
+impl ToCssWithGuard for LayerStatementRule {
+    fn to_css(&self, _: &SharedRwLockReadGuard, dest: &mut CssStringWriter) -> fmt::Result {
+        let mut writer = CssWriter::new(dest);
+        writer.write_str("@layer ")?;
+        let mut first = true;
+        for name in &self.names {
+            if !first {
+                writer.write_str(", ")?;
+            }
+            first = false;
+            name.to_css(&mut writer)?;
+        }
+        writer.write_char(';')
+    }
+}
@@ -4,17 +4,20 @@
 
 //! Style sheets and their CSS rules.
 
+pub mod container_rule;
 mod counter_style_rule;
 mod document_rule;
 mod font_face_rule;
 pub mod font_feature_values_rule;
 pub mod import_rule;
 pub mod keyframes_rule;
+pub mod layer_rule;
 mod loader;
 mod media_rule;
 mod namespace_rule;
 pub mod origin;
 mod page_rule;
+mod property_rule;
 mod rule_list;
 mod rule_parser;
 mod rules_iterator;
@@ -42,17 +45,20 @@ use style_traits::ParsingMode;
 #[cfg(feature = "gecko")]
 use to_shmem::{SharedMemoryBuilder, ToShmem};
 
+pub use self::container_rule::ContainerRule;
 pub use self::counter_style_rule::CounterStyleRule;
 pub use self::document_rule::DocumentRule;
 pub use self::font_face_rule::FontFaceRule;
 pub use self::font_feature_values_rule::FontFeatureValuesRule;
 pub use self::import_rule::ImportRule;
 pub use self::keyframes_rule::KeyframesRule;
+pub use self::layer_rule::{LayerBlockRule, LayerStatementRule};
 pub use self::loader::StylesheetLoader;
 pub use self::media_rule::MediaRule;
 pub use self::namespace_rule::NamespaceRule;
 pub use self::origin::{Origin, OriginSet, OriginSetIterator, PerOrigin, PerOriginIter};
 pub use self::page_rule::PageRule;
+pub use self::property_rule::PropertyRule;
 pub use self::rule_list::{CssRules, CssRulesHelpers};
 pub use self::rule_parser::{InsertRuleContext, State, TopLevelRuleParser};
 pub use self::rules_iterator::{AllRules, EffectiveRules};
@@ -248,6 +254,10 @@ pub enum CssRule {
     Supports(Arc<Locked<SupportsRule>>),
     Page(Arc<Locked<PageRule>>),
     Document(Arc<Locked<DocumentRule>>),
+    LayerBlock(Arc<Locked<LayerBlockRule>>),
+    LayerStatement(Arc<Locked<LayerStatementRule>>),
+    Container(Arc<Locked<ContainerRule>>),
+    Property(Arc<Locked<PropertyRule>>),
 }
 
 impl CssRule {
@@ -288,6 +298,18 @@ impl CssRule {
             CssRule::Document(ref lock) => {
                 lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(guard, ops)
             },
+
+            CssRule::LayerBlock(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(guard, ops)
+            },
+
+            CssRule::LayerStatement(_) => 0,
+
+            CssRule::Container(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(guard, ops)
+            },
+
+            CssRule::Property(_) => 0,
         }
     }
 }
@@ -318,6 +340,12 @@ pub enum CssRuleType {
     FontFeatureValues = 14,
     // https://drafts.csswg.org/css-device-adapt/#css-rule-interface
     Viewport = 15,
+    // After viewport, all rules should return 0 from the API, but we still
+    // need a constant somewhere.
+    LayerBlock = 16,
+    LayerStatement = 17,
+    Container = 18,
+    Property = 19,
 }
 
 #[allow(missing_docs)]
@@ -344,6 +372,10 @@ impl CssRule {
             CssRule::Supports(_) => CssRuleType::Supports,
             CssRule::Page(_) => CssRuleType::Page,
             CssRule::Document(_) => CssRuleType::Document,
+            CssRule::LayerBlock(_) => CssRuleType::LayerBlock,
+            CssRule::LayerStatement(_) => CssRuleType::LayerStatement,
+            CssRule::Container(_) => CssRuleType::Container,
+            CssRule::Property(_) => CssRuleType::Property,
         }
     }
 
@@ -352,6 +384,7 @@ impl CssRule {
             // CssRule::Charset(..) => State::Start,
             CssRule::Import(..) => State::Imports,
             CssRule::Namespace(..) => State::Namespaces,
+            CssRule::LayerStatement(..) => State::EarlyLayers,
             _ => State::Body,
         }
     }
@@ -394,9 +427,11 @@ impl CssRule {
             dom_error: None,
             namespaces: &mut *guard,
             insert_rule_context: Some(insert_rule_context),
+            pending_layer_statement: Default::default(),
         };
 
         parse_one_rule(&mut input, &mut rule_parser)
+            .or_else(|error| rule_parser.take_layer_statement().ok_or(error))
             .map_err(|_| rule_parser.dom_error.unwrap_or(RulesMutateError::Syntax))
     }
 }
@@ -472,6 +507,26 @@ impl DeepCloneWithLock for CssRule {
                     lock.wrap(rule.deep_clone_with_lock(lock, guard, params)),
                 ))
             },
+            CssRule::LayerBlock(ref arc) => {
+                let rule = arc.read_with(guard);
+                CssRule::LayerBlock(Arc::new(
+                    lock.wrap(rule.deep_clone_with_lock(lock, guard, params)),
+                ))
+            },
+            CssRule::LayerStatement(ref arc) => {
+                let rule = arc.read_with(guard);
+                CssRule::LayerStatement(Arc::new(lock.wrap(rule.clone())))
+            },
+            CssRule::Container(ref arc) => {
+                let rule = arc.read_with(guard);
+                CssRule::Container(Arc::new(
+                    lock.wrap(rule.deep_clone_with_lock(lock, guard, params)),
+                ))
+            },
+            CssRule::Property(ref arc) => {
+                let rule = arc.read_with(guard);
+                CssRule::Property(Arc::new(lock.wrap(rule.clone())))
+            },
         }
     }
 }
@@ -492,6 +547,10 @@ impl ToCssWithGuard for CssRule {
             CssRule::Supports(ref lock) => lock.read_with(guard).to_css(guard, dest),
             CssRule::Page(ref lock) => lock.read_with(guard).to_css(guard, dest),
             CssRule::Document(ref lock) => lock.read_with(guard).to_css(guard, dest),
+            CssRule::LayerBlock(ref lock) => lock.read_with(guard).to_css(guard, dest),
+            CssRule::LayerStatement(ref lock) => lock.read_with(guard).to_css(guard, dest),
+            CssRule::Container(ref lock) => lock.read_with(guard).to_css(guard, dest),
+            CssRule::Property(ref lock) => lock.read_with(guard).to_css(guard, dest),
         }
     }
 }
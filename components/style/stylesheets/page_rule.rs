@@ -6,22 +6,29 @@
 //!
 //! [page]: https://drafts.csswg.org/css2/page.html#page-box
 
-use crate::properties::PropertyDeclarationBlock;
+use crate::error_reporting::ContextualParseError;
+use crate::parser::{Parse, ParserContext};
+use crate::properties::{Importance, PropertyDeclaration};
+use crate::properties::{PropertyDeclarationBlock, PropertyId, SourcePropertyDeclaration};
 use crate::shared_lock::{DeepCloneParams, DeepCloneWithLock, Locked};
 use crate::shared_lock::{SharedRwLock, SharedRwLockReadGuard, ToCssWithGuard};
 use crate::str::CssStringWriter;
-use cssparser::SourceLocation;
+use crate::values::specified::PageSize;
+use cssparser::{parse_important, AtRuleParser, CowRcStr, DeclarationListParser};
+use cssparser::{DeclarationParser, Delimiter, Parser, SourceLocation};
 #[cfg(feature = "gecko")]
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
 use servo_arc::Arc;
 use std::fmt::{self, Write};
+use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
 
 /// A [`@page`][page] rule.
 ///
 /// This implements only a limited subset of the CSS
 /// 2.2 syntax.
 ///
-/// In this subset, [page selectors][page-selectors] are not implemented.
+/// In this subset, [page selectors][page-selectors] are not implemented, and
+/// the only supported descriptor besides the margin properties is `size`.
 ///
 /// [page]: https://drafts.csswg.org/css2/page.html#page-box
 /// [page-selectors]: https://drafts.csswg.org/css2/page.html#page-selectors
@@ -29,11 +36,51 @@ use std::fmt::{self, Write};
 pub struct PageRule {
     /// The declaration block this page rule contains.
     pub block: Arc<Locked<PropertyDeclarationBlock>>,
+    /// The `size` descriptor of this page rule, if any.
+    pub size: Option<PageSize>,
     /// The source position this rule was found at.
     pub source_location: SourceLocation,
 }
 
 impl PageRule {
+    /// Parse the body of a `@page` rule.
+    pub fn parse(
+        context: &ParserContext,
+        input: &mut Parser,
+        lock: &SharedRwLock,
+        source_location: SourceLocation,
+    ) -> Self {
+        let mut declarations = SourcePropertyDeclaration::new();
+        let mut block = PropertyDeclarationBlock::new();
+        let mut size = None;
+        let parser = PageRuleParser {
+            context,
+            declarations: &mut declarations,
+        };
+        let mut iter = DeclarationListParser::new(input, parser);
+        while let Some(declaration) = iter.next() {
+            match declaration {
+                Ok(PageDeclaration::Size(value)) => size = Some(value),
+                Ok(PageDeclaration::Property(importance)) => {
+                    block.extend(iter.parser.declarations.drain(), importance);
+                },
+                Err((error, slice)) => {
+                    iter.parser.declarations.clear();
+                    let location = error.location;
+                    let error =
+                        ContextualParseError::UnsupportedPropertyDeclaration(slice, error, None);
+                    context.log_css_error(location, error);
+                },
+            }
+        }
+
+        PageRule {
+            block: Arc::new(lock.wrap(block)),
+            size,
+            source_location,
+        }
+    }
+
     /// Measure heap usage.
     #[cfg(feature = "gecko")]
     pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
@@ -47,6 +94,11 @@ impl ToCssWithGuard for PageRule {
     /// StyleRule.
     fn to_css(&self, guard: &SharedRwLockReadGuard, dest: &mut CssStringWriter) -> fmt::Result {
         dest.write_str("@page { ")?;
+        if let Some(ref size) = self.size {
+            dest.write_str("size: ")?;
+            size.to_css(&mut CssWriter::new(dest))?;
+            dest.write_str("; ")?;
+        }
         let declaration_block = self.block.read_with(guard);
         declaration_block.to_css(dest)?;
         if !declaration_block.declarations().is_empty() {
@@ -65,7 +117,64 @@ impl DeepCloneWithLock for PageRule {
     ) -> Self {
         PageRule {
             block: Arc::new(lock.wrap(self.block.read_with(&guard).clone())),
+            size: self.size.clone(),
             source_location: self.source_location.clone(),
         }
     }
 }
+
+/// A declaration inside a `@page` rule.
+enum PageDeclaration {
+    /// The `size` descriptor.
+    Size(PageSize),
+    /// A property declaration, which has been pushed to the parser's
+    /// declarations, with its importance.
+    Property(Importance),
+}
+
+struct PageRuleParser<'a, 'b: 'a> {
+    context: &'a ParserContext<'b>,
+    declarations: &'a mut SourcePropertyDeclaration,
+}
+
+/// Default methods reject all at rules.
+impl<'a, 'b, 'i> AtRuleParser<'i> for PageRuleParser<'a, 'b> {
+    type PreludeNoBlock = ();
+    type PreludeBlock = ();
+    type AtRule = PageDeclaration;
+    type Error = StyleParseErrorKind<'i>;
+}
+
+impl<'a, 'b, 'i> DeclarationParser<'i> for PageRuleParser<'a, 'b> {
+    type Declaration = PageDeclaration;
+    type Error = StyleParseErrorKind<'i>;
+
+    fn parse_value<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<PageDeclaration, ParseError<'i>> {
+        if name.eq_ignore_ascii_case("size") {
+            return input
+                .parse_entirely(|input| PageSize::parse(self.context, input))
+                .map(PageDeclaration::Size);
+        }
+
+        let id = match PropertyId::parse(&name, self.context) {
+            Ok(id) => id,
+            Err(()) => {
+                return Err(input.new_custom_error(StyleParseErrorKind::UnknownProperty(name)));
+            },
+        };
+        input.parse_until_before(Delimiter::Bang, |input| {
+            PropertyDeclaration::parse_into(self.declarations, id, self.context, input)
+        })?;
+        let importance = match input.try(parse_important) {
+            Ok(()) => Importance::Important,
+            Err(_) => Importance::Normal,
+        };
+        // In case there is still unparsed text in the declaration, we should roll back.
+        input.expect_exhausted()?;
+        Ok(PageDeclaration::Property(importance))
+    }
+}
@@ -0,0 +1,189 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The [`@property`][property] rule.
+//!
+//! [property]: https://drafts.css-houdini.org/css-properties-values-api/#at-property-rule
+
+use crate::custom_properties::{self, Name, VariableValue};
+use crate::error_reporting::ContextualParseError;
+use crate::parser::ParserContext;
+use crate::properties_and_values::syntax::Descriptor;
+use crate::properties_and_values::PropertyRegistration;
+use crate::shared_lock::{SharedRwLockReadGuard, ToCssWithGuard};
+use crate::str::CssStringWriter;
+use crate::values::serialize_atom_name;
+use cssparser::{serialize_string, AtRuleParser, DeclarationListParser, DeclarationParser};
+use cssparser::{CowRcStr, Parser, SourceLocation};
+use selectors::parser::SelectorParseErrorKind;
+use servo_arc::Arc;
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
+
+/// A [`@property`][property] rule.
+///
+/// [property]: https://drafts.css-houdini.org/css-properties-values-api/#at-property-rule
+#[derive(Clone, Debug, ToShmem)]
+pub struct PropertyRule {
+    /// The registration of the custom property this rule defines.
+    pub registration: PropertyRegistration,
+    /// The source position where this property rule was found.
+    pub source_location: SourceLocation,
+}
+
+impl PropertyRule {
+    /// Parses the prelude of an `@property` rule, which is a custom property
+    /// name.
+    pub fn parse_prelude<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Name, ParseError<'i>> {
+        let location = input.current_source_location();
+        let ident = input.expect_ident()?;
+        match custom_properties::parse_name(ident) {
+            Ok(name) => Ok(Name::from(name)),
+            Err(()) => {
+                Err(location
+                    .new_custom_error(SelectorParseErrorKind::UnexpectedIdent(ident.clone())))
+            },
+        }
+    }
+
+    /// Parses the block of an `@property` rule. Returns an error, after
+    /// reporting it, if the rule is invalid, in which case it's ignored.
+    pub fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+        name: Name,
+        source_location: SourceLocation,
+    ) -> Result<Self, ParseError<'i>> {
+        let mut descriptors = PropertyDescriptors::default();
+        {
+            let parser = PropertyRuleParser {
+                descriptors: &mut descriptors,
+            };
+            let mut iter = DeclarationListParser::new(input, parser);
+            while let Some(declaration) = iter.next() {
+                if let Err((error, slice)) = declaration {
+                    let location = error.location;
+                    let error = ContextualParseError::UnsupportedPropertyDescriptor(slice, error);
+                    context.log_css_error(location, error);
+                }
+            }
+        }
+
+        let error = match (
+            &descriptors.syntax,
+            descriptors.inherits,
+            &descriptors.initial_value,
+        ) {
+            (None, ..) => Some("missing 'syntax' descriptor"),
+            (_, None, _) => Some("missing 'inherits' descriptor"),
+            (Some(syntax), _, None) if !syntax.is_universal() => {
+                Some("missing 'initial-value' descriptor")
+            },
+            (Some(syntax), _, Some(value))
+                if !PropertyRegistration::is_valid_initial_value(
+                    syntax,
+                    value,
+                    context.url_data,
+                ) =>
+            {
+                Some("invalid 'initial-value' descriptor")
+            },
+            _ => None,
+        };
+        if let Some(error) = error {
+            context.log_css_error(
+                source_location,
+                ContextualParseError::InvalidPropertyRule(error),
+            );
+            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+
+        Ok(PropertyRule {
+            registration: PropertyRegistration {
+                name,
+                syntax: descriptors.syntax.unwrap(),
+                inherits: descriptors.inherits.unwrap(),
+                initial_value: descriptors.initial_value,
+                url_data: context.url_data.clone(),
+            },
+            source_location,
+        })
+    }
+}
+
+impl ToCssWithGuard for PropertyRule {
+    fn to_css(&self, _guard: &SharedRwLockReadGuard, dest: &mut CssStringWriter) -> fmt::Result {
+        let registration = &self.registration;
+        dest.write_str("@property --")?;
+        serialize_atom_name(&registration.name, dest)?;
+        dest.write_str(" { syntax: ")?;
+        serialize_string(&registration.syntax.to_css_string(), dest)?;
+        dest.write_str("; inherits: ")?;
+        dest.write_str(if registration.inherits {
+            "true"
+        } else {
+            "false"
+        })?;
+        dest.write_str("; ")?;
+        if let Some(ref value) = registration.initial_value {
+            dest.write_str("initial-value: ")?;
+            value.to_css(&mut CssWriter::new(dest))?;
+            dest.write_str("; ")?;
+        }
+        dest.write_str("}")
+    }
+}
+
+#[derive(Default)]
+struct PropertyDescriptors {
+    syntax: Option<Descriptor>,
+    inherits: Option<bool>,
+    initial_value: Option<Arc<VariableValue>>,
+}
+
+struct PropertyRuleParser<'a> {
+    descriptors: &'a mut PropertyDescriptors,
+}
+
+/// Default methods reject all at rules.
+impl<'a, 'i> AtRuleParser<'i> for PropertyRuleParser<'a> {
+    type PreludeNoBlock = ();
+    type PreludeBlock = ();
+    type AtRule = ();
+    type Error = StyleParseErrorKind<'i>;
+}
+
+impl<'a, 'i> DeclarationParser<'i> for PropertyRuleParser<'a> {
+    type Declaration = ();
+    type Error = StyleParseErrorKind<'i>;
+
+    fn parse_value<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<(), ParseError<'i>> {
+        match_ignore_ascii_case! { &*name,
+            "syntax" => {
+                let location = input.current_source_location();
+                let syntax = input.expect_string()?;
+                let syntax = Descriptor::from_str(syntax).map_err(|()| {
+                    location.new_custom_error(StyleParseErrorKind::UnspecifiedError)
+                })?;
+                self.descriptors.syntax = Some(syntax);
+            },
+            "inherits" => {
+                let inherits = try_match_ident_ignore_ascii_case! { input,
+                    "true" => true,
+                    "false" => false,
+                };
+                self.descriptors.inherits = Some(inherits);
+            },
+            "initial-value" => {
+                self.descriptors.initial_value = Some(VariableValue::parse(input)?);
+            },
+            _ => return Err(input.new_custom_error(SelectorParseErrorKind::UnexpectedIdent(name.clone()))),
+        }
+        Ok(())
+    }
+}
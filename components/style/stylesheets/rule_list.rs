@@ -127,6 +127,7 @@ pub trait CssRulesHelpers {
         parent_stylesheet_contents: &StylesheetContents,
         index: usize,
         nested: bool,
+        in_style_rule: bool,
         loader: Option<&dyn StylesheetLoader>,
     ) -> Result<CssRule, RulesMutateError>;
 }
@@ -139,6 +140,7 @@ impl CssRulesHelpers for RawOffsetArc<Locked<CssRules>> {
         parent_stylesheet_contents: &StylesheetContents,
         index: usize,
         nested: bool,
+        in_style_rule: bool,
         loader: Option<&dyn StylesheetLoader>,
     ) -> Result<CssRule, RulesMutateError> {
         let new_rule = {
@@ -166,6 +168,7 @@ impl CssRulesHelpers for RawOffsetArc<Locked<CssRules>> {
             let insert_rule_context = InsertRuleContext {
                 rule_list: &rules.0,
                 index,
+                in_style_rule,
             };
 
             // Steps 3, 4, 5, 6
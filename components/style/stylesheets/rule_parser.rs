@@ -5,23 +5,31 @@
 //! Parsing of the stylesheet contents.
 
 use crate::counter_style::{parse_counter_style_body, parse_counter_style_name_definition};
+use crate::custom_properties::Name;
 use crate::error_reporting::ContextualParseError;
 use crate::font_face::parse_font_face_block;
 use crate::media_queries::MediaList;
 use crate::parser::{Parse, ParserContext};
-use crate::properties::parse_property_declaration_list;
+use crate::properties::parse_property_declaration_list_with_rules;
 use crate::selector_parser::{SelectorImpl, SelectorParser};
 use crate::shared_lock::{Locked, SharedRwLock};
 use crate::str::starts_with_ignore_ascii_case;
+use crate::stylesheets::container_rule::ContainerCondition;
 use crate::stylesheets::document_rule::DocumentCondition;
 use crate::stylesheets::font_feature_values_rule::parse_family_name_list;
+use crate::stylesheets::import_rule::ImportLayer;
 use crate::stylesheets::keyframes_rule::parse_keyframe_list;
+use crate::stylesheets::layer_rule::LayerName;
 use crate::stylesheets::stylesheet::Namespaces;
 use crate::stylesheets::supports_rule::SupportsCondition;
 use crate::stylesheets::viewport_rule;
-use crate::stylesheets::{CorsMode, DocumentRule, FontFeatureValuesRule, KeyframesRule, MediaRule};
+use crate::stylesheets::{
+    ContainerRule, CorsMode, DocumentRule, FontFeatureValuesRule, KeyframesRule, MediaRule,
+};
 use crate::stylesheets::{CssRule, CssRuleType, CssRules, RulesMutateError, StylesheetLoader};
-use crate::stylesheets::{NamespaceRule, PageRule, StyleRule, SupportsRule, ViewportRule};
+use crate::stylesheets::{LayerBlockRule, LayerStatementRule};
+use crate::stylesheets::{NamespaceRule, PageRule, PropertyRule, StyleRule};
+use crate::stylesheets::{SupportsRule, ViewportRule};
 use crate::values::computed::font::FamilyName;
 use crate::values::{CssUrl, CustomIdent, KeyframesName};
 use crate::{Namespace, Prefix};
@@ -37,6 +45,9 @@ pub struct InsertRuleContext<'a> {
     pub rule_list: &'a [CssRule],
     /// The index we're about to get inserted at.
     pub index: usize,
+    /// Whether the rule list belongs to a style rule, in which case the rule
+    /// is parsed as a nested rule.
+    pub in_style_rule: bool,
 }
 
 /// The parser for the top-level rules in a stylesheet.
@@ -62,6 +73,9 @@ pub struct TopLevelRuleParser<'a> {
     pub namespaces: &'a mut Namespaces,
     /// The info we need insert a rule in a list.
     pub insert_rule_context: Option<InsertRuleContext<'a>>,
+    /// The prelude of the last `@layer` rule we parsed, in case it turns out
+    /// to be a statement.
+    pub pending_layer_statement: PendingLayerStatement,
 }
 
 impl<'b> TopLevelRuleParser<'b> {
@@ -70,9 +84,26 @@ impl<'b> TopLevelRuleParser<'b> {
             shared_lock: self.shared_lock,
             context: &self.context,
             namespaces: &self.namespaces,
+            pending_layer_statement: PendingLayerStatement::default(),
+            in_style_rule: self
+                .insert_rule_context
+                .as_ref()
+                .map_or(false, |context| context.in_style_rule),
         }
     }
 
+    /// Returns the `@layer` statement rule that the last parse error came
+    /// from, if any. See `PendingLayerStatement`.
+    pub fn take_layer_statement(&mut self) -> Option<CssRule> {
+        let rule = self.pending_layer_statement.take(self.shared_lock)?;
+        if self.state <= State::EarlyLayers {
+            self.state = State::EarlyLayers;
+        } else {
+            self.state = State::Body;
+        }
+        Some(rule)
+    }
+
     /// Returns the current state of the parser.
     pub fn state(&self) -> State {
         self.state
@@ -125,12 +156,62 @@ impl<'b> TopLevelRuleParser<'b> {
 pub enum State {
     /// We haven't started parsing rules.
     Start = 1,
+    /// We're parsing `@layer` statement rules before `@import` rules.
+    EarlyLayers = 2,
     /// We're parsing `@import` rules.
-    Imports = 2,
+    Imports = 3,
     /// We're parsing `@namespace` rules.
-    Namespaces = 3,
+    Namespaces = 4,
     /// We're parsing the main body of the stylesheet.
-    Body = 4,
+    Body = 5,
+}
+
+/// cssparser needs to know whether an at-rule has a block while parsing its
+/// prelude, but `@layer` rules may or may not have one.
+///
+/// So we parse them as block rules, and remember the prelude here, so that if
+/// the rule turns out to be terminated by a semicolon (or the end of the
+/// input) instead, the caller can turn the resulting parse error into a
+/// statement rule.
+///
+/// FIXME: Remove this once cssparser supports at-rules with optional blocks.
+#[derive(Clone, Default)]
+pub struct PendingLayerStatement(Option<(Vec<LayerName>, SourceLocation)>);
+
+impl PendingLayerStatement {
+    fn set(&mut self, names: &[LayerName], location: SourceLocation) {
+        self.0 = Some((names.to_vec(), location));
+    }
+
+    fn clear(&mut self) {
+        self.0 = None;
+    }
+
+    fn take(&mut self, shared_lock: &SharedRwLock) -> Option<CssRule> {
+        let (names, source_location) = self.0.take()?;
+        // An anonymous layer needs a block.
+        if names.is_empty() {
+            return None;
+        }
+        Some(CssRule::LayerStatement(Arc::new(shared_lock.wrap(
+            LayerStatementRule {
+                names,
+                source_location,
+            },
+        ))))
+    }
+}
+
+/// Parses the prelude of a `@layer` rule, that is, a possibly-empty list of
+/// layer names.
+fn parse_layer_prelude<'i, 't>(
+    context: &ParserContext,
+    input: &mut Parser<'i, 't>,
+) -> Result<Vec<LayerName>, ParseError<'i>> {
+    if input.is_exhausted() {
+        return Ok(vec![]);
+    }
+    input.parse_comma_separated(|input| LayerName::parse(context, input))
 }
 
 #[derive(Clone, Debug, MallocSizeOf, ToShmem)]
@@ -162,12 +243,18 @@ pub enum AtRuleBlockPrelude {
     Page,
     /// A @document rule, with its conditional.
     Document(DocumentCondition),
+    /// A @layer rule prelude, with its (possibly empty) list of layer names.
+    Layer(Vec<LayerName>),
+    /// A @container rule prelude, with its container condition.
+    Container(Arc<ContainerCondition>),
+    /// A @property rule prelude, with its custom property name.
+    Property(Name),
 }
 
 /// A rule prelude for at-rule without block.
 pub enum AtRuleNonBlockPrelude {
     /// A @import rule prelude.
-    Import(CssUrl, Arc<Locked<MediaList>>),
+    Import(CssUrl, Arc<Locked<MediaList>>, Option<ImportLayer>),
     /// A @namespace rule prelude.
     Namespace(Option<Prefix>, Namespace),
 }
@@ -199,12 +286,31 @@ impl<'a, 'i> AtRuleParser<'i> for TopLevelRuleParser<'a> {
                 let url_string = input.expect_url_or_string()?.as_ref().to_owned();
                 let url = CssUrl::parse_from_string(url_string, &self.context, CorsMode::None);
 
+                let layer = ImportLayer::parse(&self.context, input)?;
+
                 let media = MediaList::parse(&self.context, input);
                 let media = Arc::new(self.shared_lock.wrap(media));
 
-                let prelude = AtRuleNonBlockPrelude::Import(url, media);
+                let prelude = AtRuleNonBlockPrelude::Import(url, media, layer);
                 return Ok(AtRuleType::WithoutBlock(prelude));
             },
+            "layer" => {
+                // Whether this can come before `@import` rules depends on
+                // whether the rule has a block, which we'll know later.
+                let state_to_check = if self.state <= State::EarlyLayers {
+                    State::EarlyLayers
+                } else {
+                    State::Body
+                };
+                if !self.check_state(state_to_check) {
+                    return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+
+                let location = input.current_source_location();
+                let names = parse_layer_prelude(&self.context, input)?;
+                self.pending_layer_statement.set(&names, location);
+                return Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::Layer(names)));
+            },
             "namespace" => {
                 if !self.check_state(State::Namespaces) {
                     return Err(input.new_custom_error(StyleParseErrorKind::UnexpectedNamespaceRule))
@@ -246,6 +352,10 @@ impl<'a, 'i> AtRuleParser<'i> for TopLevelRuleParser<'a> {
         location: SourceLocation,
         input: &mut Parser<'i, 't>,
     ) -> Result<CssRule, ParseError<'i>> {
+        self.pending_layer_statement.clear();
+        if matches!(prelude, AtRuleBlockPrelude::Layer(..)) && !self.check_state(State::Body) {
+            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
         AtRuleParser::parse_block(&mut self.nested(), prelude, location, input).map(|rule| {
             self.state = State::Body;
             rule
@@ -259,7 +369,7 @@ impl<'a, 'i> AtRuleParser<'i> for TopLevelRuleParser<'a> {
         source_location: SourceLocation,
     ) -> CssRule {
         match prelude {
-            AtRuleNonBlockPrelude::Import(url, media) => {
+            AtRuleNonBlockPrelude::Import(url, media, layer) => {
                 let loader = self
                     .loader
                     .expect("Expected a stylesheet loader for @import");
@@ -270,6 +380,7 @@ impl<'a, 'i> AtRuleParser<'i> for TopLevelRuleParser<'a> {
                     &self.context,
                     &self.shared_lock,
                     media,
+                    layer,
                 );
 
                 self.state = State::Imports;
@@ -333,6 +444,10 @@ struct NestedRuleParser<'a, 'b: 'a> {
     shared_lock: &'a SharedRwLock,
     context: &'a ParserContext<'b>,
     namespaces: &'a Namespaces,
+    pending_layer_statement: PendingLayerStatement,
+    /// Whether we're parsing the contents of a style rule, where only some
+    /// rules are allowed, and selectors are relative to the parent rule.
+    in_style_rule: bool,
 }
 
 impl<'a, 'b> NestedRuleParser<'a, 'b> {
@@ -343,18 +458,30 @@ impl<'a, 'b> NestedRuleParser<'a, 'b> {
     ) -> Arc<Locked<CssRules>> {
         let context = ParserContext::new_with_rule_type(self.context, rule_type, self.namespaces);
 
-        let nested_parser = NestedRuleParser {
+        let mut nested_parser = NestedRuleParser {
             shared_lock: self.shared_lock,
             context: &context,
             namespaces: self.namespaces,
+            pending_layer_statement: PendingLayerStatement::default(),
+            in_style_rule: self.in_style_rule,
         };
 
+        if self.in_style_rule {
+            let rules = nested_parser.parse_declarations_and_nested_rules(input);
+            return CssRules::new(rules, self.shared_lock);
+        }
+
         let mut iter = RuleListParser::new_for_nested_rule(input, nested_parser);
         let mut rules = Vec::new();
         while let Some(result) = iter.next() {
             match result {
                 Ok(rule) => rules.push(rule),
                 Err((error, slice)) => {
+                    if let Some(rule) = iter.parser.take_layer_statement() {
+                        rules.push(rule);
+                        continue;
+                    }
+
                     let location = error.location;
                     let error = ContextualParseError::InvalidRule(slice, error);
                     self.context.log_css_error(location, error);
@@ -363,6 +490,38 @@ impl<'a, 'b> NestedRuleParser<'a, 'b> {
         }
         CssRules::new(rules, self.shared_lock)
     }
+
+    /// Parses the contents of a conditional rule nested in a style rule.
+    /// These can contain declarations too, which apply to the elements
+    /// matched by the style rule, as if they were wrapped in a `& { .. }`
+    /// rule.
+    ///
+    /// https://drafts.csswg.org/css-nesting/#conditionals
+    fn parse_declarations_and_nested_rules(&mut self, input: &mut Parser) -> Vec<CssRule> {
+        let source_location = input.current_source_location();
+        let context =
+            ParserContext::new_with_rule_type(self.context, CssRuleType::Style, self.namespaces);
+        let (declarations, rules) =
+            parse_property_declaration_list_with_rules(&context, input, None, self);
+        if declarations.declarations().is_empty() {
+            return rules;
+        }
+
+        let parent_rule = CssRule::Style(Arc::new(self.shared_lock.wrap(StyleRule {
+            selectors: SelectorList::parent_selector(),
+            block: Arc::new(self.shared_lock.wrap(declarations)),
+            rules: None,
+            source_location,
+        })));
+        let mut result = Vec::with_capacity(rules.len() + 1);
+        result.push(parent_rule);
+        result.extend(rules);
+        result
+    }
+
+    fn take_layer_statement(&mut self) -> Option<CssRule> {
+        self.pending_layer_statement.take(self.shared_lock)
+    }
 }
 
 impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
@@ -376,6 +535,22 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
         name: CowRcStr<'i>,
         input: &mut Parser<'i, 't>,
     ) -> Result<AtRuleType<AtRuleNonBlockPrelude, AtRuleBlockPrelude>, ParseError<'i>> {
+        // Only conditional group rules and layers can be nested in style
+        // rules.
+        //
+        // https://drafts.csswg.org/css-nesting/#conditionals
+        if self.in_style_rule {
+            let allowed = match_ignore_ascii_case! { &*name,
+                "media" | "supports" | "container" | "layer" => true,
+                _ => false,
+            };
+            if !allowed {
+                return Err(
+                    input.new_custom_error(StyleParseErrorKind::UnsupportedAtRule(name.clone()))
+                );
+            }
+        }
+
         match_ignore_ascii_case! { &*name,
             "media" => {
                 let media_queries = MediaList::parse(self.context, input);
@@ -446,6 +621,26 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
                 let cond = DocumentCondition::parse(self.context, input)?;
                 Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::Document(cond)))
             },
+            "layer" => {
+                let location = input.current_source_location();
+                let names = parse_layer_prelude(self.context, input)?;
+                self.pending_layer_statement.set(&names, location);
+                Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::Layer(names)))
+            },
+            "container" => {
+                if !cfg!(feature = "servo") {
+                    return Err(input.new_custom_error(
+                        StyleParseErrorKind::UnsupportedAtRule(name.clone())
+                    ))
+                }
+
+                let condition = Arc::new(ContainerCondition::parse(self.context, input)?);
+                Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::Container(condition)))
+            },
+            "property" => {
+                let name = PropertyRule::parse_prelude(input)?;
+                Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::Property(name)))
+            },
             _ => Err(input.new_custom_error(StyleParseErrorKind::UnsupportedAtRule(name.clone())))
         }
     }
@@ -456,6 +651,7 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
         source_location: SourceLocation,
         input: &mut Parser<'i, 't>,
     ) -> Result<CssRule, ParseError<'i>> {
+        self.pending_layer_statement.clear();
         match prelude {
             AtRuleBlockPrelude::FontFace => {
                 let context = ParserContext::new_with_rule_type(
@@ -548,11 +744,9 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
                     self.namespaces,
                 );
 
-                let declarations = parse_property_declaration_list(&context, input, None);
-                Ok(CssRule::Page(Arc::new(self.shared_lock.wrap(PageRule {
-                    block: Arc::new(self.shared_lock.wrap(declarations)),
-                    source_location,
-                }))))
+                Ok(CssRule::Page(Arc::new(self.shared_lock.wrap(
+                    PageRule::parse(&context, input, self.shared_lock, source_location),
+                ))))
             },
             AtRuleBlockPrelude::Document(condition) => {
                 if !cfg!(feature = "gecko") {
@@ -566,6 +760,39 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
                     },
                 ))))
             },
+            AtRuleBlockPrelude::Layer(names) => {
+                // Only a single layer name (or none) is allowed with a block.
+                let name = match names.len() {
+                    0 => None,
+                    1 => names.into_iter().next(),
+                    _ => return Err(input.new_error(BasicParseErrorKind::AtRuleBodyInvalid)),
+                };
+                Ok(CssRule::LayerBlock(Arc::new(self.shared_lock.wrap(
+                    LayerBlockRule {
+                        name,
+                        rules: self.parse_nested_rules(input, CssRuleType::LayerBlock),
+                        source_location,
+                    },
+                ))))
+            },
+            AtRuleBlockPrelude::Container(condition) => Ok(CssRule::Container(Arc::new(
+                self.shared_lock.wrap(ContainerRule {
+                    condition,
+                    rules: self.parse_nested_rules(input, CssRuleType::Container),
+                    source_location,
+                }),
+            ))),
+            AtRuleBlockPrelude::Property(name) => {
+                let context = ParserContext::new_with_rule_type(
+                    self.context,
+                    CssRuleType::Property,
+                    self.namespaces,
+                );
+
+                Ok(CssRule::Property(Arc::new(self.shared_lock.wrap(
+                    PropertyRule::parse(&context, input, name, source_location)?,
+                ))))
+            },
         }
     }
 }
@@ -584,6 +811,9 @@ impl<'a, 'b, 'i> QualifiedRuleParser<'i> for NestedRuleParser<'a, 'b> {
             namespaces: self.namespaces,
             url_data: Some(self.context.url_data),
         };
+        if self.in_style_rule {
+            return SelectorList::parse_for_nesting(&selector_parser, input);
+        }
         SelectorList::parse(&selector_parser, input)
     }
 
@@ -596,11 +826,29 @@ impl<'a, 'b, 'i> QualifiedRuleParser<'i> for NestedRuleParser<'a, 'b> {
         let context =
             ParserContext::new_with_rule_type(self.context, CssRuleType::Style, self.namespaces);
 
-        let declarations = parse_property_declaration_list(&context, input, Some(&selectors));
+        let mut nested_parser = NestedRuleParser {
+            shared_lock: self.shared_lock,
+            context: &context,
+            namespaces: self.namespaces,
+            pending_layer_statement: PendingLayerStatement::default(),
+            in_style_rule: true,
+        };
+        let (declarations, rules) = parse_property_declaration_list_with_rules(
+            &context,
+            input,
+            Some(&selectors),
+            &mut nested_parser,
+        );
         let block = Arc::new(self.shared_lock.wrap(declarations));
+        let rules = if rules.is_empty() {
+            None
+        } else {
+            Some(CssRules::new(rules, self.shared_lock))
+        };
         Ok(CssRule::Style(Arc::new(self.shared_lock.wrap(StyleRule {
             selectors,
             block,
+            rules,
             source_location,
         }))))
     }
@@ -9,6 +9,7 @@ use crate::selector_parser::SelectorImpl;
 use crate::shared_lock::{DeepCloneParams, DeepCloneWithLock, Locked};
 use crate::shared_lock::{SharedRwLock, SharedRwLockReadGuard, ToCssWithGuard};
 use crate::str::CssStringWriter;
+use crate::stylesheets::CssRules;
 use cssparser::SourceLocation;
 #[cfg(feature = "gecko")]
 use malloc_size_of::MallocUnconditionalShallowSizeOf;
@@ -25,6 +26,10 @@ pub struct StyleRule {
     pub selectors: SelectorList<SelectorImpl>,
     /// The declaration block with the properties it contains.
     pub block: Arc<Locked<PropertyDeclarationBlock>>,
+    /// The rules nested in this rule, if any.
+    ///
+    /// https://drafts.csswg.org/css-nesting/#nesting
+    pub rules: Option<Arc<Locked<CssRules>>>,
     /// The location in the sheet where it was found.
     pub source_location: SourceLocation,
 }
@@ -35,11 +40,15 @@ impl DeepCloneWithLock for StyleRule {
         &self,
         lock: &SharedRwLock,
         guard: &SharedRwLockReadGuard,
-        _params: &DeepCloneParams,
+        params: &DeepCloneParams,
     ) -> StyleRule {
         StyleRule {
             selectors: self.selectors.clone(),
             block: Arc::new(lock.wrap(self.block.read_with(guard).clone())),
+            rules: self.rules.as_ref().map(|rules| {
+                let rules = rules.read_with(guard);
+                Arc::new(lock.wrap(rules.deep_clone_with_lock(lock, guard, params)))
+            }),
             source_location: self.source_location.clone(),
         }
     }
@@ -53,6 +62,10 @@ impl StyleRule {
         n += self.selectors.0.size_of(ops);
         n += self.block.unconditional_shallow_size_of(ops) +
             self.block.read_with(guard).size_of(ops);
+        if let Some(ref rules) = self.rules {
+            n += rules.unconditional_shallow_size_of(ops) +
+                rules.read_with(guard).size_of(guard, ops);
+        }
         n
     }
 }
@@ -64,16 +77,39 @@ impl ToCssWithGuard for StyleRule {
 
         // Step 1
         self.selectors.to_css(dest)?;
-        // Step 2
-        dest.write_str(" { ")?;
-        // Step 3
         let declaration_block = self.block.read_with(guard);
-        declaration_block.to_css(dest)?;
-        // Step 4
+        let rules = match self.rules {
+            Some(ref rules) if !rules.read_with(guard).is_empty() => rules.read_with(guard),
+            _ => {
+                // Step 2
+                dest.write_str(" { ")?;
+                // Step 3
+                declaration_block.to_css(dest)?;
+                // Step 4
+                if !declaration_block.declarations().is_empty() {
+                    dest.write_str(" ")?;
+                }
+                // Step 5
+                return dest.write_str("}");
+            },
+        };
+
+        // Step 6: with nested rules, the declarations and each of the rules
+        // go in their own line, and the lines of the rules are indented one
+        // level deeper than this rule.
+        dest.write_str(" {")?;
         if !declaration_block.declarations().is_empty() {
-            dest.write_str(" ")?;
+            dest.write_str("\n  ")?;
+            declaration_block.to_css(dest)?;
+        }
+        for rule in rules.0.iter() {
+            let rule = rule.to_css_string(guard).to_string();
+            if rule.is_empty() {
+                continue;
+            }
+            dest.write_str("\n  ")?;
+            dest.write_str(&rule.replace('\n', "\n  "))?;
         }
-        // Step 5
-        dest.write_str("}")
+        dest.write_str("\n}")
     }
 }
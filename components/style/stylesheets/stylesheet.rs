@@ -414,6 +414,7 @@ impl Stylesheet {
             dom_error: None,
             insert_rule_context: None,
             namespaces,
+            pending_layer_statement: Default::default(),
         };
 
         {
@@ -430,6 +431,12 @@ impl Stylesheet {
                         }
                     },
                     Err((error, slice)) => {
+                        if let Some(rule) = iter.parser.take_layer_statement() {
+                            if rules.try_push(rule).is_err() {
+                                break;
+                            }
+                            continue;
+                        }
                         let location = error.location;
                         let error = ContextualParseError::InvalidRule(slice, error);
                         iter.parser.context.log_css_error(location, error);
@@ -16,6 +16,7 @@ use crate::properties::StyleBuilder;
 use crate::rule_cache::RuleCacheConditions;
 use crate::shared_lock::{SharedRwLockReadGuard, StylesheetGuards, ToCssWithGuard};
 use crate::str::CssStringWriter;
+use crate::stylesheets::container_rule::ContainerSizeQuery;
 use crate::stylesheets::{Origin, StylesheetInDocument};
 use crate::values::computed::{Context, ToComputedValue};
 use crate::values::generics::length::LengthPercentageOrAuto;
@@ -744,6 +745,7 @@ impl MaybeNew for ViewportConstraints {
             for_smil_animation: false,
             for_non_inherited_property: None,
             rule_cache_conditions: RefCell::new(&mut conditions),
+            container_size_query: ContainerSizeQuery::none(),
         };
 
         // DEVICE-ADAPT § 9.3 Resolving 'extend-to-zoom'
@@ -16,6 +16,7 @@ use crate::invalidation::media_queries::{EffectiveMediaQueryResults, ToMediaList
 use crate::media_queries::Device;
 use crate::properties::{self, CascadeMode, ComputedValues};
 use crate::properties::{AnimationRules, PropertyDeclarationBlock};
+use crate::properties_and_values::{PropertyRegistration, PropertyRegistry};
 use crate::rule_cache::{RuleCache, RuleCacheConditions};
 use crate::rule_collector::{containing_shadow_ignoring_svg_use, RuleCollector};
 use crate::rule_tree::{CascadeLevel, RuleTree, StrongRuleNode, StyleSource};
@@ -24,16 +25,21 @@ use crate::selector_parser::{PerPseudoElementMap, PseudoElement, SelectorImpl, S
 use crate::shared_lock::{Locked, SharedRwLockReadGuard, StylesheetGuards};
 use crate::stylesheet_set::{DataValidity, DocumentStylesheetSet, SheetRebuildKind};
 use crate::stylesheet_set::{DocumentStylesheetFlusher, SheetCollectionFlusher};
+use crate::stylesheets::container_rule::ContainerCondition;
 use crate::stylesheets::keyframes_rule::KeyframesAnimation;
+use crate::stylesheets::layer_rule::{LayerName, LayerOrder};
+use crate::stylesheets::rules_iterator::EffectiveRulesIterator;
 use crate::stylesheets::viewport_rule::{self, MaybeNew, ViewportRule};
 use crate::stylesheets::StyleRule;
 use crate::stylesheets::StylesheetInDocument;
 #[cfg(feature = "gecko")]
-use crate::stylesheets::{CounterStyleRule, FontFaceRule, FontFeatureValuesRule, PageRule};
-use crate::stylesheets::{CssRule, Origin, OriginSet, PerOrigin, PerOriginIter};
+use crate::stylesheets::{CounterStyleRule, FontFaceRule};
+use crate::stylesheets::{CssRule, FontFeatureValuesRule, Origin, OriginSet, PageRule};
+use crate::stylesheets::{PerOrigin, PerOriginIter};
 use crate::thread_state::{self, ThreadState};
 use crate::{Atom, LocalName, Namespace, WeakAtom};
 use fallible::FallibleVec;
+use fxhash::FxHashMap;
 use hashglobe::FailedAllocationError;
 use malloc_size_of::MallocSizeOf;
 #[cfg(feature = "gecko")]
@@ -42,7 +48,7 @@ use selectors::attr::{CaseSensitivity, NamespaceConstraint};
 use selectors::bloom::BloomFilter;
 use selectors::matching::VisitedHandlingMode;
 use selectors::matching::{matches_selector, ElementSelectorFlags, MatchingContext, MatchingMode};
-use selectors::parser::{AncestorHashes, Combinator, Component, Selector};
+use selectors::parser::{AncestorHashes, Combinator, Component, RelativeSelector, Selector};
 use selectors::parser::{SelectorIter, Visit};
 use selectors::visitor::SelectorVisitor;
 use selectors::NthIndexCache;
@@ -50,7 +56,7 @@ use servo_arc::{Arc, ArcBorrow};
 use smallbitvec::SmallBitVec;
 use smallvec::SmallVec;
 use std::sync::Mutex;
-use std::{mem, ops};
+use std::{mem, ops, slice};
 use style_traits::viewport::ViewportConstraints;
 
 /// The type of the stylesheets that the stylist contains.
@@ -123,6 +129,7 @@ impl UserAgentCascadeDataCache {
                 Some(&mut new_data.precomputed_pseudo_element_decls),
             )?;
         }
+        new_data.cascade_data.compute_layer_order();
 
         let new_data = Arc::new(new_data);
         self.entries.push(new_data.clone());
@@ -387,6 +394,11 @@ pub struct Stylist {
     /// The rule tree, that stores the results of selector matching.
     rule_tree: RuleTree,
 
+    /// The custom properties registered via `CSS.registerProperty()`, which
+    /// take precedence over the ones registered by `@property` rules.
+    #[cfg_attr(feature = "servo", ignore_malloc_size_of = "Few and small")]
+    script_custom_property_registry: PropertyRegistry,
+
     /// The total number of times the stylist has been rebuilt.
     num_rebuilds: usize,
 }
@@ -426,6 +438,7 @@ impl Stylist {
             cascade_data: Default::default(),
             author_styles_enabled: AuthorStylesEnabled::Yes,
             rule_tree: RuleTree::new(),
+            script_custom_property_registry: PropertyRegistry::default(),
             num_rebuilds: 0,
         }
     }
@@ -505,6 +518,14 @@ impl Stylist {
             .any(|(d, _)| d.document_state_dependencies.intersects(state))
     }
 
+    /// Returns whether there are any `@container` rules in the document
+    /// stylesheets.
+    pub fn has_container_rules(&self) -> bool {
+        self.cascade_data
+            .iter_origins()
+            .any(|(d, _)| d.has_container_rules())
+    }
+
     /// Flush the list of stylesheets if they changed, ensuring the stylist is
     /// up-to-date.
     pub fn flush<E>(
@@ -561,9 +582,50 @@ impl Stylist {
             .rebuild(&self.device, self.quirks_mode, flusher, guards)
             .unwrap_or_else(|_| warn!("OOM in Stylist::flush"));
 
+        self.update_custom_property_registry();
+
         had_invalidations
     }
 
+    /// Flush the list of stylesheets like `flush`, but without invalidating
+    /// the style of any element.
+    ///
+    /// Used to test the cascade without a DOM.
+    pub fn flush_for_testing(&mut self, guards: &StylesheetGuards) {
+        let flusher = self.stylesheets.flush_for_testing();
+
+        self.cascade_data
+            .rebuild(&self.device, self.quirks_mode, flusher, guards)
+            .unwrap_or_else(|_| warn!("OOM in Stylist::flush_for_testing"));
+
+        self.update_custom_property_registry();
+    }
+
+    /// Registers a custom property via `CSS.registerProperty()`.
+    ///
+    /// This affects the computed value of the property on every element, so
+    /// it forces a full restyle on the next flush.
+    pub fn register_custom_property(&mut self, registration: PropertyRegistration) {
+        self.script_custom_property_registry.insert(registration);
+        self.force_stylesheet_origins_dirty(OriginSet::ORIGIN_AUTHOR);
+    }
+
+    /// Rebuilds the set of registered custom properties the device computes
+    /// styles with, from the `@property` rules of every origin, in increasing
+    /// order of precedence, and the registrations from script.
+    fn update_custom_property_registry(&mut self) {
+        let mut registry = PropertyRegistry::default();
+        for (data, _) in self.cascade_data.iter_origins_rev() {
+            for registration in data.custom_property_registrations.iter() {
+                registry.insert(registration.clone());
+            }
+        }
+        for registration in self.script_custom_property_registry.iter() {
+            registry.insert(registration.clone());
+        }
+        self.device.set_custom_property_registry(Arc::new(registry));
+    }
+
     /// Insert a given stylesheet before another stylesheet in the document.
     pub fn insert_stylesheet_before(
         &mut self,
@@ -643,6 +705,20 @@ impl Stylist {
         doc_author_rules_apply && f(&self.cascade_data.author)
     }
 
+    /// Returns whether any rules may apply to the given pseudo-element of
+    /// `element`.
+    pub fn may_have_rules_for_pseudo<E>(&self, element: E, pseudo: &PseudoElement) -> bool
+    where
+        E: TElement,
+    {
+        // The ::part() rules for `element` come from the trees of its
+        // shadow-including ancestors, so don't bother looking for them.
+        if element.has_part_attr() {
+            return true;
+        }
+        self.any_applicable_rule_data(element, |data| data.has_rules_for_pseudo(pseudo))
+    }
+
     /// Computes the style for a given "precomputed" pseudo-element, taking the
     /// universal rules and applying them.
     pub fn precomputed_values_for_pseudo<E>(
@@ -758,6 +834,46 @@ impl Stylist {
         )
     }
 
+    /// Returns the style of an inline box or a run of text on the first line
+    /// of a block, by cascading the rules of its `style` again so that it
+    /// inherits from `parent_style`, the style of its parent on the first
+    /// line.
+    ///
+    /// `parent_style_ignoring_first_line` is the style of its parent otherwise,
+    /// which is what `style` inherits from.
+    ///
+    /// https://drafts.csswg.org/css-pseudo/#first-line-inheritance
+    #[cfg(feature = "servo")]
+    pub fn style_for_first_line_descendant<E>(
+        &self,
+        guards: &StylesheetGuards,
+        pseudo: Option<&PseudoElement>,
+        style: &ComputedValues,
+        parent_style: &ComputedValues,
+        parent_style_ignoring_first_line: &ComputedValues,
+    ) -> Arc<ComputedValues>
+    where
+        E: TElement,
+    {
+        use crate::font_metrics::ServoMetricsProvider;
+        let visited_rules = style.visited_style().and_then(|style| style.rules.as_ref());
+        properties::cascade::<E>(
+            &self.device,
+            pseudo,
+            style.rules.as_ref().unwrap_or(self.rule_tree.root()),
+            guards,
+            Some(parent_style),
+            Some(parent_style_ignoring_first_line),
+            Some(parent_style),
+            visited_rules,
+            &ServoMetricsProvider,
+            self.quirks_mode,
+            /* rule_cache = */ None,
+            &mut Default::default(),
+            /* element = */ None,
+        )
+    }
+
     /// Computes a pseudo-element style lazily during layout.
     ///
     /// This can only be done for a certain set of pseudo-elements, like
@@ -973,6 +1089,7 @@ impl Stylist {
         self.push_applicable_declarations(
             element,
             Some(&pseudo),
+            Some(parent_style),
             None,
             None,
             AnimationRules(None, None),
@@ -1003,6 +1120,7 @@ impl Stylist {
             self.push_applicable_declarations(
                 element,
                 Some(&pseudo),
+                Some(parent_style),
                 None,
                 None,
                 AnimationRules(None, None),
@@ -1061,6 +1179,9 @@ impl Stylist {
             }
         }
 
+        // The device is recreated on every change on Servo, but the registered
+        // custom properties only change when flushing stylesheets.
+        device.set_custom_property_registry(self.device.custom_property_registry().clone());
         self.device = device;
         self.media_features_change_changed_style(guards, &self.device)
     }
@@ -1122,10 +1243,14 @@ impl Stylist {
     }
 
     /// Returns the applicable CSS declarations for the given element.
+    ///
+    /// When matching a pseudo-element, `originating_element_style` is the
+    /// style of `element`, which container queries may be evaluated against.
     pub fn push_applicable_declarations<E, F>(
         &self,
         element: E,
         pseudo_element: Option<&PseudoElement>,
+        originating_element_style: Option<&ComputedValues>,
         style_attribute: Option<ArcBorrow<Locked<PropertyDeclarationBlock>>>,
         smil_override: Option<ArcBorrow<Locked<PropertyDeclarationBlock>>>,
         animation_rules: AnimationRules,
@@ -1141,6 +1266,7 @@ impl Stylist {
             self,
             element,
             pseudo_element,
+            originating_element_style,
             style_attribute,
             smil_override,
             animation_rules,
@@ -1396,7 +1522,7 @@ pub struct ExtraStyleData {
     pub font_faces: Vec<Arc<Locked<FontFaceRule>>>,
 
     /// A list of effective font-feature-values rules.
-    #[cfg(feature = "gecko")]
+    #[cfg_attr(feature = "servo", ignore_malloc_size_of = "Arc")]
     pub font_feature_values: Vec<Arc<Locked<FontFeatureValuesRule>>>,
 
     /// A map of effective counter-style rules.
@@ -1404,7 +1530,7 @@ pub struct ExtraStyleData {
     pub counter_styles: PrecomputedHashMap<Atom, Arc<Locked<CounterStyleRule>>>,
 
     /// A map of effective page rules.
-    #[cfg(feature = "gecko")]
+    #[cfg_attr(feature = "servo", ignore_malloc_size_of = "Arc")]
     pub pages: Vec<Arc<Locked<PageRule>>>,
 }
 
@@ -1420,11 +1546,6 @@ impl ExtraStyleData {
         self.font_faces.push(rule.clone());
     }
 
-    /// Add the given @font-feature-values rule.
-    fn add_font_feature_values(&mut self, rule: &Arc<Locked<FontFeatureValuesRule>>) {
-        self.font_feature_values.push(rule.clone());
-    }
-
     /// Add the given @counter-style rule.
     fn add_counter_style(
         &mut self,
@@ -1434,22 +1555,27 @@ impl ExtraStyleData {
         let name = rule.read_with(guard).name().0.clone();
         self.counter_styles.insert(name, rule.clone());
     }
+}
+
+impl ExtraStyleData {
+    /// Add the given @font-feature-values rule.
+    fn add_font_feature_values(&mut self, rule: &Arc<Locked<FontFeatureValuesRule>>) {
+        self.font_feature_values.push(rule.clone());
+    }
 
     /// Add the given @page rule.
     fn add_page(&mut self, rule: &Arc<Locked<PageRule>>) {
         self.pages.push(rule.clone());
     }
-}
 
-impl ExtraStyleData {
     fn clear(&mut self) {
         #[cfg(feature = "gecko")]
         {
             self.font_faces.clear();
-            self.font_feature_values.clear();
             self.counter_styles.clear();
-            self.pages.clear();
         }
+        self.font_feature_values.clear();
+        self.pages.clear();
     }
 }
 
@@ -1572,7 +1698,8 @@ fn component_needs_revalidation(
         Component::NthLastOfType(..) |
         Component::FirstOfType |
         Component::LastOfType |
-        Component::OnlyOfType => true,
+        Component::OnlyOfType |
+        Component::Has(..) => true,
         Component::NonTSPseudoClass(ref p) => p.needs_cache_revalidation(),
         _ => false,
     }
@@ -1585,9 +1712,9 @@ impl<'a> SelectorVisitor for StylistSelectorVisitor<'a> {
         self.needs_revalidation =
             self.needs_revalidation || combinator.map_or(false, |c| c.is_sibling());
 
-        // NOTE(emilio): This works properly right now because we can't store
-        // complex selectors in nested selectors, otherwise we may need to
-        // rethink this.
+        // NOTE(emilio): Complex selectors nested in :is(), :where() or :has()
+        // restore this when they're done, see visit_selector_list and
+        // visit_relative_selector_list.
         //
         // Also, note that this call happens before we visit any of the simple
         // selectors in the next ComplexSelector, so we can use this to skip
@@ -1598,6 +1725,30 @@ impl<'a> SelectorVisitor for StylistSelectorVisitor<'a> {
         true
     }
 
+    fn visit_selector_list(&mut self, list: &[Selector<SelectorImpl>]) -> bool {
+        // The rightmost compound of each selector in an `:is()` or `:where()`
+        // is matched against the same element as the outer compound, so we
+        // just need to restore the state after visiting each of them.
+        for selector in list {
+            let passed_rightmost_selector = self.passed_rightmost_selector;
+            let result = selector.visit(self);
+            self.passed_rightmost_selector = passed_rightmost_selector;
+            if !result {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn visit_relative_selector_list(&mut self, list: &[RelativeSelector<SelectorImpl>]) -> bool {
+        // Relative selectors are never matched against the element itself.
+        let passed_rightmost_selector = self.passed_rightmost_selector;
+        self.passed_rightmost_selector = true;
+        let result = list.iter().all(|relative| relative.selector.visit(self));
+        self.passed_rightmost_selector = passed_rightmost_selector;
+        result
+    }
+
     fn visit_attribute_selector(
         &mut self,
         _ns: &NamespaceConstraint<&Namespace>,
@@ -1682,6 +1833,11 @@ impl<Map: Default + MallocSizeOf> GenericElementAndPseudoRules<Map> {
         }
     }
 
+    #[inline]
+    fn has_rules_for_pseudo(&self, pseudo: &PseudoElement) -> bool {
+        self.pseudos_map.get(pseudo).is_some()
+    }
+
     /// Measures heap usage.
     #[cfg(feature = "gecko")]
     fn add_size_of(&self, ops: &mut MallocSizeOfOps, sizes: &mut ServoStyleSetSizes) {
@@ -1780,12 +1936,32 @@ pub struct CascadeData {
     /// by name.
     animations: PrecomputedHashMap<Atom, KeyframesAnimation>,
 
+    /// The custom properties registered by `@property` rules at this
+    /// `CascadeData`'s origin.
+    #[ignore_malloc_size_of = "CssRules have primary refs, we measure there"]
+    custom_property_registrations: PropertyRegistry,
+
     /// Effective media query results cached from the last rebuild.
     effective_media_query_results: EffectiveMediaQueryResults,
 
     /// Extra data, like different kinds of rules, etc.
     extra_data: ExtraStyleData,
 
+    /// The cascade layers in this origin, indexed by `LayerId`. The first
+    /// entry is the implicit outer layer, which contains the unlayered rules.
+    layers: Vec<CascadeLayer>,
+
+    /// A map from a parent layer and a layer name to the `LayerId` of the
+    /// corresponding layer. Anonymous layers are not present here, since they
+    /// can't be referenced again.
+    layer_ids: FxHashMap<(LayerId, Atom), LayerId>,
+
+    /// The container conditions of the `@container` rules in this origin,
+    /// indexed by `ContainerConditionId`. The first entry is the implicit
+    /// condition that always matches, for rules outside any `@container` rule.
+    #[ignore_malloc_size_of = "Arc"]
+    container_conditions: Vec<ContainerConditionReference>,
+
     /// A monotonically increasing counter to represent the order on which a
     /// style rule appears in a stylesheet, needed to sort them by source order.
     rules_source_order: u32,
@@ -1812,8 +1988,12 @@ impl CascadeData {
             mapped_ids: PrecomputedHashSet::default(),
             selectors_for_cache_revalidation: SelectorMap::new(),
             animations: Default::default(),
+            custom_property_registrations: PropertyRegistry::default(),
             extra_data: ExtraStyleData::default(),
             effective_media_query_results: EffectiveMediaQueryResults::new(),
+            layers: vec![CascadeLayer::root()],
+            layer_ids: FxHashMap::default(),
+            container_conditions: vec![ContainerConditionReference::none()],
             rules_source_order: 0,
             num_selectors: 0,
             num_declarations: 0,
@@ -1854,10 +2034,155 @@ impl CascadeData {
                 /* precomputed_pseudo_element_decls = */ None,
             )?;
         }
+        self.compute_layer_order();
 
         Ok(())
     }
 
+    /// Returns the order of the given cascade layer.
+    #[inline]
+    pub fn layer_order_for(&self, id: LayerId) -> LayerOrder {
+        self.layers[id.0 as usize].order
+    }
+
+    /// Computes the order of all the cascade layers, which is only known once
+    /// all the stylesheets have been added, since sublayers can be declared
+    /// after their parents' rules.
+    ///
+    /// https://drafts.csswg.org/css-cascade-5/#layer-ordering
+    fn compute_layer_order(&mut self) {
+        fn compute_layer_order_for_subtree(
+            layers: &mut [CascadeLayer],
+            id: LayerId,
+            order: &mut LayerOrder,
+        ) {
+            for i in 0..layers[id.0 as usize].children.len() {
+                let child = layers[id.0 as usize].children[i];
+                compute_layer_order_for_subtree(layers, child, order);
+            }
+            // The rules directly in a layer come after the rules of its
+            // sublayers. The order of the outer layer never changes.
+            if id != LayerId::root() {
+                layers[id.0 as usize].order = *order;
+                order.inc();
+            }
+        }
+
+        let mut order = LayerOrder::first();
+        compute_layer_order_for_subtree(&mut self.layers, LayerId::root(), &mut order);
+    }
+
+    /// Returns the layer with the given name inside `parent`, registering it
+    /// if needed. `None` means an anonymous layer, which is always new.
+    fn maybe_register_layer(&mut self, parent: LayerId, name: Option<&Atom>) -> LayerId {
+        if let Some(name) = name {
+            if let Some(id) = self.layer_ids.get(&(parent, name.clone())) {
+                return *id;
+            }
+        }
+
+        // We don't support more layers than fit in a `LayerId`, put the rules
+        // in the parent layer instead.
+        if self.layers.len() > ::std::u16::MAX as usize {
+            return parent;
+        }
+
+        let id = LayerId(self.layers.len() as u16);
+        self.layers.push(CascadeLayer {
+            order: LayerOrder::first(),
+            children: vec![],
+        });
+        self.layers[parent.0 as usize].children.push(id);
+        if let Some(name) = name {
+            self.layer_ids.insert((parent, name.clone()), id);
+        }
+        id
+    }
+
+    /// Enters the layer with the given (possibly dotted) name, or a new
+    /// anonymous layer, inside `parent`, returning its id.
+    fn enter_layer(&mut self, parent: LayerId, name: Option<&LayerName>) -> LayerId {
+        let name = match name {
+            Some(name) => name,
+            None => return self.maybe_register_layer(parent, None),
+        };
+        let mut id = parent;
+        for segment in name.layer_names() {
+            id = self.maybe_register_layer(id, Some(segment));
+        }
+        id
+    }
+
+    /// Registers the condition of a `@container` rule nested in the
+    /// condition `parent`, returning its id.
+    fn add_container_condition(
+        &mut self,
+        parent: ContainerConditionId,
+        condition: Arc<ContainerCondition>,
+    ) -> ContainerConditionId {
+        let id = ContainerConditionId(self.container_conditions.len() as u32);
+        self.container_conditions.push(ContainerConditionReference {
+            parent,
+            condition: Some(condition),
+        });
+        id
+    }
+
+    /// Returns whether the container condition with the given id, and all the
+    /// conditions it's nested in, match for `element`.
+    ///
+    /// When matching a pseudo-element, `element` is its originating element,
+    /// and `originating_element_style` the style of it. Either way, `element`
+    /// is noted as depending on the size of query containers.
+    pub fn container_condition_matches<E>(
+        &self,
+        mut id: ContainerConditionId,
+        device: &Device,
+        element: E,
+        originating_element_style: Option<&ComputedValues>,
+        quirks_mode: QuirksMode,
+    ) -> bool
+    where
+        E: TElement,
+    {
+        if id == ContainerConditionId::none() {
+            return true;
+        }
+        element.note_query_container_size_dependency();
+
+        while id != ContainerConditionId::none() {
+            let reference = &self.container_conditions[id.0 as usize];
+            let condition = reference.condition.as_ref().unwrap();
+            if !condition.matches(device, quirks_mode, element, originating_element_style) {
+                return false;
+            }
+            id = reference.parent;
+        }
+        true
+    }
+
+    /// Returns whether there are any `@container` rules in this origin.
+    #[inline]
+    pub fn has_container_rules(&self) -> bool {
+        self.container_conditions.len() > 1
+    }
+
+    /// Returns whether there are any rules for the given pseudo-element in
+    /// this origin.
+    #[inline]
+    pub fn has_rules_for_pseudo(&self, pseudo: &PseudoElement) -> bool {
+        self.normal_rules.has_rules_for_pseudo(pseudo) ||
+            self.host_rules
+                .as_ref()
+                .map_or(false, |rules| rules.has_rules_for_pseudo(pseudo)) ||
+            self.slotted_rules
+                .as_ref()
+                .map_or(false, |rules| rules.has_rules_for_pseudo(pseudo)) ||
+            self.part_rules
+                .as_ref()
+                .map_or(false, |rules| rules.has_rules_for_pseudo(pseudo))
+    }
+
     /// Returns the invalidation map.
     pub fn invalidation_map(&self) -> &InvalidationMap {
         &self.invalidation_map
@@ -1949,7 +2274,7 @@ impl CascadeData {
         stylesheet: &S,
         guard: &SharedRwLockReadGuard,
         rebuild_kind: SheetRebuildKind,
-        mut precomputed_pseudo_element_decls: Option<&mut PrecomputedPseudoElementDeclarations>,
+        precomputed_pseudo_element_decls: Option<&mut PrecomputedPseudoElementDeclarations>,
     ) -> Result<(), FailedAllocationError>
     where
         S: StylesheetInDocument + ToMediaListKey + 'static,
@@ -1958,18 +2283,72 @@ impl CascadeData {
             return Ok(());
         }
 
-        let origin = stylesheet.origin(guard);
-
         if rebuild_kind.should_rebuild_invalidation() {
             self.effective_media_query_results.saw_effective(stylesheet);
         }
 
-        for rule in stylesheet.effective_rules(device, guard) {
+        self.add_rule_list(
+            stylesheet.rules(guard).iter(),
+            device,
+            quirks_mode,
+            stylesheet,
+            guard,
+            rebuild_kind,
+            LayerId::root(),
+            ContainerConditionId::none(),
+            None,
+            precomputed_pseudo_element_decls,
+        )
+    }
+
+    // Adds the given rules, and recursively their effective nested rules, in
+    // the given cascade layer and container condition.
+    //
+    // Returns Err(..) to signify OOM
+    fn add_rule_list<S>(
+        &mut self,
+        rules: slice::Iter<CssRule>,
+        device: &Device,
+        quirks_mode: QuirksMode,
+        stylesheet: &S,
+        guard: &SharedRwLockReadGuard,
+        rebuild_kind: SheetRebuildKind,
+        containing_layer_id: LayerId,
+        containing_container_condition_id: ContainerConditionId,
+        containing_rule_selectors: Option<&[Selector<SelectorImpl>]>,
+        mut precomputed_pseudo_element_decls: Option<&mut PrecomputedPseudoElementDeclarations>,
+    ) -> Result<(), FailedAllocationError>
+    where
+        S: StylesheetInDocument + ToMediaListKey + 'static,
+    {
+        let origin = stylesheet.origin(guard);
+        let stylesheet_quirks_mode = stylesheet.quirks_mode(guard);
+
+        for rule in rules {
+            // The selectors of this rule with `&` resolved, which the rules
+            // nested in it are relative to.
+            let mut resolved_selectors = None;
             match *rule {
                 CssRule::Style(ref locked) => {
                     let style_rule = locked.read_with(&guard);
                     self.num_declarations += style_rule.block.read_with(&guard).len();
-                    for selector in &style_rule.selectors.0 {
+                    let replaced_selectors: SmallVec<[Selector<SelectorImpl>; 1]>;
+                    let selectors = match containing_rule_selectors {
+                        Some(parent) => {
+                            replaced_selectors = style_rule
+                                .selectors
+                                .0
+                                .iter()
+                                .map(|selector| selector.replace_parent_selector(parent))
+                                .collect();
+                            &replaced_selectors
+                        },
+                        None => &style_rule.selectors.0,
+                    };
+                    if style_rule.rules.is_some() {
+                        resolved_selectors = Some(selectors.clone());
+                    }
+                    for selector in selectors.iter() {
                         self.num_selectors += 1;
 
                         let pseudo_element = selector.pseudo_element();
@@ -1988,6 +2367,7 @@ impl CascadeData {
                                         self.rules_source_order,
                                         CascadeLevel::UANormal,
                                         selector.specificity(),
+                                        LayerOrder::root(),
                                     ));
                                 continue;
                             }
@@ -2003,6 +2383,8 @@ impl CascadeData {
                             hashes,
                             locked.clone(),
                             self.rules_source_order,
+                            containing_layer_id,
+                            containing_container_condition_id,
                         );
 
                         if rebuild_kind.should_rebuild_invalidation() {
@@ -2072,7 +2454,7 @@ impl CascadeData {
                             .saw_effective(import_rule);
                     }
 
-                    // NOTE: effective_rules visits the inner stylesheet if
+                    // NOTE: The inner stylesheet is visited below, if
                     // appropriate.
                 },
                 CssRule::Media(ref lock) => {
@@ -2105,7 +2487,6 @@ impl CascadeData {
                 CssRule::FontFace(ref rule) => {
                     self.extra_data.add_font_face(rule);
                 },
-                #[cfg(feature = "gecko")]
                 CssRule::FontFeatureValues(ref rule) => {
                     self.extra_data.add_font_feature_values(rule);
                 },
@@ -2113,13 +2494,62 @@ impl CascadeData {
                 CssRule::CounterStyle(ref rule) => {
                     self.extra_data.add_counter_style(guard, rule);
                 },
-                #[cfg(feature = "gecko")]
                 CssRule::Page(ref rule) => {
                     self.extra_data.add_page(rule);
                 },
+                CssRule::LayerStatement(ref lock) => {
+                    for name in &lock.read_with(guard).names {
+                        self.enter_layer(containing_layer_id, Some(name));
+                    }
+                },
+                CssRule::Property(ref lock) => {
+                    // Later rules win over earlier ones with the same name.
+                    let registration = &lock.read_with(guard).registration;
+                    self.custom_property_registrations
+                        .insert(registration.clone());
+                },
                 // We don't care about any other rule.
                 _ => {},
             }
+
+            let children =
+                EffectiveRulesIterator::children(rule, device, stylesheet_quirks_mode, guard);
+            let children = match children {
+                Some(children) => children,
+                None => continue,
+            };
+
+            let layer_id = match *rule {
+                CssRule::Import(ref lock) => match lock.read_with(guard).layer {
+                    Some(ref layer) => self.enter_layer(containing_layer_id, layer.name.as_ref()),
+                    None => containing_layer_id,
+                },
+                CssRule::LayerBlock(ref lock) => {
+                    self.enter_layer(containing_layer_id, lock.read_with(guard).name.as_ref())
+                },
+                _ => containing_layer_id,
+            };
+
+            let container_condition_id = match *rule {
+                CssRule::Container(ref lock) => {
+                    let condition = lock.read_with(guard).condition.clone();
+                    self.add_container_condition(containing_container_condition_id, condition)
+                },
+                _ => containing_container_condition_id,
+            };
+
+            self.add_rule_list(
+                children,
+                device,
+                quirks_mode,
+                stylesheet,
+                guard,
+                rebuild_kind,
+                layer_id,
+                container_condition_id,
+                resolved_selectors.as_deref().or(containing_rule_selectors),
+                precomputed_pseudo_element_decls.as_deref_mut(),
+            )?;
         }
 
         Ok(())
@@ -2170,6 +2600,10 @@ impl CascadeData {
                 CssRule::Page(..) |
                 CssRule::Viewport(..) |
                 CssRule::Document(..) |
+                CssRule::LayerBlock(..) |
+                CssRule::LayerStatement(..) |
+                CssRule::Container(..) |
+                CssRule::Property(..) |
                 CssRule::FontFeatureValues(..) => {
                     // Not affected by device changes.
                     continue;
@@ -2234,7 +2668,12 @@ impl CascadeData {
             host_rules.clear();
         }
         self.animations.clear();
+        self.custom_property_registrations.clear();
         self.extra_data.clear();
+        self.layers.truncate(1);
+        self.layers[0].children.clear();
+        self.layer_ids.clear();
+        self.container_conditions.truncate(1);
         self.rules_source_order = 0;
         self.num_selectors = 0;
         self.num_declarations = 0;
@@ -2269,6 +2708,9 @@ impl CascadeData {
         sizes.mOther += self.animations.size_of(ops);
         sizes.mOther += self.effective_media_query_results.size_of(ops);
         sizes.mOther += self.extra_data.size_of(ops);
+        sizes.mOther += self.layers.size_of(ops);
+        sizes.mOther += self.layer_ids.size_of(ops);
+        sizes.mOther += self.container_conditions.size_of(ops);
     }
 }
 
@@ -2278,6 +2720,67 @@ impl Default for CascadeData {
     }
 }
 
+/// An identifier for a cascade layer, unique within a given `CascadeData`.
+#[derive(Clone, Copy, Debug, Eq, Hash, MallocSizeOf, PartialEq)]
+pub struct LayerId(u16);
+
+impl LayerId {
+    /// The id of the implicit outer layer.
+    #[inline]
+    pub const fn root() -> Self {
+        LayerId(0)
+    }
+}
+
+/// An identifier for a container condition, unique within a given
+/// `CascadeData`.
+#[derive(Clone, Copy, Debug, Eq, Hash, MallocSizeOf, PartialEq)]
+pub struct ContainerConditionId(u32);
+
+impl ContainerConditionId {
+    /// The id of the implicit condition of rules outside any `@container`
+    /// rule, which always matches.
+    #[inline]
+    pub const fn none() -> Self {
+        ContainerConditionId(0)
+    }
+}
+
+/// A container condition, as stored in the `CascadeData`, along with the
+/// condition of the `@container` rule it's nested in, if any.
+#[derive(Clone, Debug)]
+struct ContainerConditionReference {
+    parent: ContainerConditionId,
+    condition: Option<Arc<ContainerCondition>>,
+}
+
+impl ContainerConditionReference {
+    fn none() -> Self {
+        Self {
+            parent: ContainerConditionId::none(),
+            condition: None,
+        }
+    }
+}
+
+/// A cascade layer, as stored in the `CascadeData`.
+#[derive(Clone, Debug, MallocSizeOf)]
+struct CascadeLayer {
+    /// The order of this layer, computed once all the layers are known.
+    order: LayerOrder,
+    /// The sublayers of this layer, in declaration order.
+    children: Vec<LayerId>,
+}
+
+impl CascadeLayer {
+    fn root() -> Self {
+        Self {
+            order: LayerOrder::root(),
+            children: vec![],
+        }
+    }
+}
+
 /// A rule, that wraps a style rule, but represents a single selector of the
 /// rule.
 #[derive(Clone, Debug, MallocSizeOf)]
@@ -2297,6 +2800,12 @@ pub struct Rule {
     /// we could repurpose that storage here if we needed to.
     pub source_order: u32,
 
+    /// The cascade layer this rule is in.
+    pub layer_id: LayerId,
+
+    /// The container condition this rule is subject to.
+    pub container_condition_id: ContainerConditionId,
+
     /// The actual style rule.
     #[cfg_attr(
         feature = "gecko",
@@ -2319,13 +2828,21 @@ impl Rule {
     }
 
     /// Turns this rule into an `ApplicableDeclarationBlock` for the given
-    /// cascade level.
+    /// cascade level, using `cascade_data` (the data this rule comes from) to
+    /// resolve its layer order.
     pub fn to_applicable_declaration_block(
         &self,
         level: CascadeLevel,
+        cascade_data: &CascadeData,
     ) -> ApplicableDeclarationBlock {
         let source = StyleSource::from_rule(self.style_rule.clone());
-        ApplicableDeclarationBlock::new(source, self.source_order, level, self.specificity())
+        ApplicableDeclarationBlock::new(
+            source,
+            self.source_order,
+            level,
+            self.specificity(),
+            cascade_data.layer_order_for(self.layer_id),
+        )
     }
 
     /// Creates a new Rule.
@@ -2334,12 +2851,16 @@ impl Rule {
         hashes: AncestorHashes,
         style_rule: Arc<Locked<StyleRule>>,
         source_order: u32,
+        layer_id: LayerId,
+        container_condition_id: ContainerConditionId,
     ) -> Self {
         Rule {
             selector: selector,
             hashes: hashes,
             style_rule: style_rule,
             source_order: source_order,
+            layer_id: layer_id,
+            container_condition_id: container_condition_id,
         }
     }
 }
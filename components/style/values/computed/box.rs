@@ -13,7 +13,8 @@ use crate::values::specified::box_ as specified;
 
 pub use crate::values::specified::box_::{AnimationName, Appearance, BreakBetween, BreakWithin};
 pub use crate::values::specified::box_::{Clear as SpecifiedClear, Float as SpecifiedFloat};
-pub use crate::values::specified::box_::{Contain, Display, Overflow};
+pub use crate::values::specified::box_::{Contain, ContainerName, ContainerType};
+pub use crate::values::specified::box_::{Display, Overflow};
 pub use crate::values::specified::box_::{OverflowAnchor, OverflowClipBox, OverscrollBehavior};
 pub use crate::values::specified::box_::{
     ScrollSnapAlign, ScrollSnapAxis, ScrollSnapStrictness, ScrollSnapType,
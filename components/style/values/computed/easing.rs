@@ -4,11 +4,67 @@
 
 //! Computed types for CSS Easing functions.
 
+use crate::bezier::Bezier;
 use crate::values::computed::{Integer, Number};
-use crate::values::generics::easing;
+use crate::values::generics::easing::{
+    self, StepPosition, TimingFunction as GenericTimingFunction,
+};
 
 /// A computed timing function.
 pub type ComputedTimingFunction = easing::TimingFunction<Integer, Number>;
 
 /// An alias of the computed timing function.
 pub type TimingFunction = ComputedTimingFunction;
+
+impl ComputedTimingFunction {
+    /// Calculates the output of this timing function for the given input
+    /// progress, solving bezier curves with the given precision.
+    ///
+    /// <https://drafts.csswg.org/css-easing/#timing-functions>
+    pub fn calculate_output(&self, progress: f64, epsilon: f64) -> f64 {
+        match *self {
+            GenericTimingFunction::CubicBezier { x1, y1, x2, y2 } => {
+                Bezier::new(x1, y1, x2, y2).solve(progress, epsilon)
+            },
+            GenericTimingFunction::Steps(steps, pos) => {
+                let mut current_step = (progress * (steps as f64)).floor() as i32;
+
+                if pos == StepPosition::Start ||
+                    pos == StepPosition::JumpStart ||
+                    pos == StepPosition::JumpBoth
+                {
+                    current_step = current_step + 1;
+                }
+
+                // FIXME: We should update current_step according to the "before flag".
+                // In order to get the before flag, we have to know the current animation phase
+                // and whether the iteration is reversed. For now, we skip this calculation.
+                // (i.e. Treat before_flag is unset,)
+                // https://drafts.csswg.org/css-easing/#step-timing-function-algo
+
+                if progress >= 0.0 && current_step < 0 {
+                    current_step = 0;
+                }
+
+                let jumps = match pos {
+                    StepPosition::JumpBoth => steps + 1,
+                    StepPosition::JumpNone => steps - 1,
+                    StepPosition::JumpStart |
+                    StepPosition::JumpEnd |
+                    StepPosition::Start |
+                    StepPosition::End => steps,
+                };
+
+                if progress <= 1.0 && current_step > jumps {
+                    current_step = jumps;
+                }
+
+                (current_step as f64) / (jumps as f64)
+            },
+            GenericTimingFunction::Keyword(keyword) => {
+                let (x1, x2, y1, y2) = keyword.to_bezier();
+                Bezier::new(x1, x2, y1, y2).solve(progress, epsilon)
+            },
+        }
+    }
+}
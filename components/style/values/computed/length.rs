@@ -13,8 +13,8 @@ use crate::values::generics::length::{
     GenericLengthOrNumber, GenericLengthPercentageOrNormal, GenericMaxSize, GenericSize,
 };
 use crate::values::generics::NonNegative;
-use crate::values::specified::length::ViewportPercentageLength;
 use crate::values::specified::length::{AbsoluteLength, FontBaseSize, FontRelativeLength};
+use crate::values::specified::length::{ContainerRelativeLength, ViewportPercentageLength};
 use crate::values::{specified, CSSFloat};
 use crate::Zero;
 use app_units::Au;
@@ -41,6 +41,7 @@ impl ToComputedValue for specified::NoCalcLength {
             specified::NoCalcLength::ViewportPercentage(length) => {
                 length.to_computed_value(context.viewport_size_for_viewport_unit_resolution())
             },
+            specified::NoCalcLength::ContainerRelative(length) => length.to_computed_value(context),
             specified::NoCalcLength::ServoCharacterWidth(length) => {
                 length.to_computed_value(context.style().get_font().clone_font_size().size())
             },
@@ -276,6 +277,17 @@ impl specified::CalcLengthPercentage {
             }
         }
 
+        for val in &[
+            self.cqw.map(ContainerRelativeLength::Cqw),
+            self.cqh.map(ContainerRelativeLength::Cqh),
+            self.cqi.map(ContainerRelativeLength::Cqi),
+            self.cqb.map(ContainerRelativeLength::Cqb),
+        ] {
+            if let Some(val) = *val {
+                length += val.to_computed_value(context).px();
+            }
+        }
+
         for val in &[
             self.ch.map(FontRelativeLength::Ch),
             self.em.map(FontRelativeLength::Em),
@@ -314,6 +326,10 @@ impl specified::CalcLengthPercentage {
             self.vh.is_some() ||
             self.vmin.is_some() ||
             self.vmax.is_some() ||
+            self.cqw.is_some() ||
+            self.cqh.is_some() ||
+            self.cqi.is_some() ||
+            self.cqb.is_some() ||
             self.em.is_some() ||
             self.ex.is_some() ||
             self.ch.is_some() ||
@@ -21,6 +21,7 @@ use crate::media_queries::Device;
 use crate::properties;
 use crate::properties::{ComputedValues, LonghandId, StyleBuilder};
 use crate::rule_cache::RuleCacheConditions;
+use crate::stylesheets::container_rule::ContainerSizeQuery;
 use crate::Atom;
 #[cfg(feature = "servo")]
 use crate::Prefix;
@@ -41,6 +42,7 @@ pub use self::border::{BorderImageRepeat, BorderImageSideWidth};
 pub use self::border::{BorderImageSlice, BorderImageWidth};
 pub use self::box_::{AnimationIterationCount, AnimationName, Contain};
 pub use self::box_::{Appearance, BreakBetween, BreakWithin, Clear, Float};
+pub use self::box_::{ContainerName, ContainerType};
 pub use self::box_::{Display, Overflow, OverflowAnchor, TransitionProperty};
 pub use self::box_::{OverflowClipBox, OverscrollBehavior, Perspective, Resize};
 pub use self::box_::{ScrollSnapAlign, ScrollSnapAxis, ScrollSnapStrictness, ScrollSnapType};
@@ -171,6 +173,10 @@ pub struct Context<'a> {
     ///
     /// FIXME(emilio): Drop the refcell.
     pub rule_cache_conditions: RefCell<&'a mut RuleCacheConditions>,
+
+    /// The sizes of the query containers that container-relative units
+    /// resolve against.
+    pub container_size_query: ContainerSizeQuery<'a>,
 }
 
 impl<'a> Context<'a> {
@@ -194,6 +200,38 @@ impl<'a> Context<'a> {
             for_smil_animation: false,
             for_non_inherited_property: None,
             rule_cache_conditions: RefCell::new(&mut conditions),
+            container_size_query: ContainerSizeQuery::none(),
+        };
+
+        f(&context)
+    }
+
+    /// Creates a suitable context for container query evaluation, in which
+    /// font-relative units compute against the style of the query container,
+    /// and executes `f` with it.
+    pub fn for_container_query_evaluation<F, R>(
+        device: &Device,
+        quirks_mode: QuirksMode,
+        container_style: &ComputedValues,
+        f: F,
+    ) -> R
+    where
+        F: FnOnce(&Context) -> R,
+    {
+        let mut conditions = RuleCacheConditions::default();
+        let provider = get_metrics_provider_for_product();
+
+        let context = Context {
+            is_root_element: false,
+            builder: StyleBuilder::for_inheritance(device, Some(container_style), None),
+            font_metrics_provider: &provider,
+            cached_system_font: None,
+            in_media_query: false,
+            quirks_mode,
+            for_smil_animation: false,
+            for_non_inherited_property: None,
+            rule_cache_conditions: RefCell::new(&mut conditions),
+            container_size_query: ContainerSizeQuery::none(),
         };
 
         f(&context)
@@ -13,6 +13,7 @@ use std::fmt::{self, Write};
 use style_traits::{CssWriter, ToCss};
 
 /// A computed `<resolution>`.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Resolution(CSSFloat);
 
 impl Resolution {
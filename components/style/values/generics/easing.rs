@@ -117,6 +117,12 @@ impl<Integer, Number> TimingFunction<Integer, Number> {
     pub fn ease() -> Self {
         TimingFunction::Keyword(TimingKeyword::Ease)
     }
+
+    /// `linear`
+    #[inline]
+    pub fn linear() -> Self {
+        TimingFunction::Keyword(TimingKeyword::Linear)
+    }
 }
 
 impl TimingKeyword {
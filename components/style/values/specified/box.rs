@@ -1225,6 +1225,107 @@ impl Parse for WillChange {
     }
 }
 
+/// The value for the `container-type` property.
+///
+/// <https://drafts.csswg.org/css-contain-3/#container-type>
+#[allow(missing_docs)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(u8)]
+pub enum ContainerType {
+    Normal,
+    Size,
+    InlineSize,
+}
+
+impl ContainerType {
+    /// Whether this value makes the element a query container.
+    #[inline]
+    pub fn is_query_container(self) -> bool {
+        self != ContainerType::Normal
+    }
+}
+
+/// The specified (and computed) value for the `container-name` property.
+///
+/// `none` is represented by an empty list.
+///
+/// <https://drafts.csswg.org/css-contain-3/#container-name>
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    MallocSizeOf,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(transparent)]
+pub struct ContainerName(#[css(iterable, if_empty = "none")] pub crate::OwnedSlice<CustomIdent>);
+
+impl ContainerName {
+    /// Returns the `none` value.
+    #[inline]
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether this is the `none` value.
+    #[inline]
+    pub fn is_none(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns whether one of the names of this container is `name`.
+    #[inline]
+    pub fn contains(&self, name: &CustomIdent) -> bool {
+        self.0.iter().any(|n| n == name)
+    }
+}
+
+impl Parse for ContainerName {
+    /// none | <custom-ident>+
+    fn parse<'i, 't>(
+        _: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        if input.try(|i| i.expect_ident_matching("none")).is_ok() {
+            return Ok(Self::none());
+        }
+
+        let mut idents = vec![];
+        loop {
+            let location = input.current_source_location();
+            let ident = match input.try(|i| i.expect_ident_cloned()) {
+                Ok(ident) => ident,
+                Err(..) if !idents.is_empty() => break,
+                Err(e) => return Err(e.into()),
+            };
+            idents.push(CustomIdent::from_ident(
+                location,
+                &ident,
+                &["none", "and", "or", "not"],
+            )?);
+        }
+        Ok(ContainerName(idents.into()))
+    }
+}
+
 bitflags! {
     /// Values for the `touch-action` property.
     #[derive(MallocSizeOf, SpecifiedValueInfo, ToComputedValue, ToResolvedValue, ToShmem)]
@@ -8,8 +8,8 @@
 
 use crate::parser::ParserContext;
 use crate::values::computed;
-use crate::values::specified::length::ViewportPercentageLength;
 use crate::values::specified::length::{AbsoluteLength, FontRelativeLength, NoCalcLength};
+use crate::values::specified::length::{ContainerRelativeLength, ViewportPercentageLength};
 use crate::values::specified::{Angle, Time};
 use crate::values::{CSSFloat, CSSInteger};
 use cssparser::{AngleOrNumber, NumberOrPercentage, Parser, Token};
@@ -74,6 +74,10 @@ pub struct CalcLengthPercentage {
     pub vh: Option<CSSFloat>,
     pub vmin: Option<CSSFloat>,
     pub vmax: Option<CSSFloat>,
+    pub cqw: Option<CSSFloat>,
+    pub cqh: Option<CSSFloat>,
+    pub cqi: Option<CSSFloat>,
+    pub cqb: Option<CSSFloat>,
     pub em: Option<CSSFloat>,
     pub ex: Option<CSSFloat>,
     pub ch: Option<CSSFloat>,
@@ -140,6 +144,7 @@ impl ToCss for CalcLengthPercentage {
         // per the spec linked above.
         serialize!(ch);
         serialize_abs!(Cm);
+        serialize!(cqb, cqh, cqi, cqw);
         serialize!(em, ex);
         serialize_abs!(In, Mm, Pc, Pt, Px, Q);
         serialize!(rem, vh, vmax, vmin, vw);
@@ -392,6 +397,20 @@ impl CalcNode {
                         ret.vmin = Some(ret.vmin.unwrap_or(0.) + vmin * factor)
                     },
                 },
+                NoCalcLength::ContainerRelative(rel) => match rel {
+                    ContainerRelativeLength::Cqw(cqw) => {
+                        ret.cqw = Some(ret.cqw.unwrap_or(0.) + cqw * factor)
+                    },
+                    ContainerRelativeLength::Cqh(cqh) => {
+                        ret.cqh = Some(ret.cqh.unwrap_or(0.) + cqh * factor)
+                    },
+                    ContainerRelativeLength::Cqi(cqi) => {
+                        ret.cqi = Some(ret.cqi.unwrap_or(0.) + cqi * factor)
+                    },
+                    ContainerRelativeLength::Cqb(cqb) => {
+                        ret.cqb = Some(ret.cqb.unwrap_or(0.) + cqb * factor)
+                    },
+                },
                 NoCalcLength::ServoCharacterWidth(..) => unreachable!(),
             },
             CalcNode::Sub(ref a, ref b) => {
@@ -284,6 +284,78 @@ impl ViewportPercentageLength {
     }
 }
 
+/// A container query length.
+///
+/// <https://drafts.csswg.org/css-contain-3/#container-lengths>
+#[derive(Clone, Copy, Debug, MallocSizeOf, PartialEq, ToCss, ToShmem)]
+pub enum ContainerRelativeLength {
+    /// 1% of the query container's width.
+    #[css(dimension)]
+    Cqw(CSSFloat),
+    /// 1% of the query container's height.
+    #[css(dimension)]
+    Cqh(CSSFloat),
+    /// 1% of the query container's inline size.
+    #[css(dimension)]
+    Cqi(CSSFloat),
+    /// 1% of the query container's block size.
+    #[css(dimension)]
+    Cqb(CSSFloat),
+}
+
+impl ContainerRelativeLength {
+    /// Return true if this is a zero value.
+    fn is_zero(&self) -> bool {
+        match *self {
+            ContainerRelativeLength::Cqw(v) |
+            ContainerRelativeLength::Cqh(v) |
+            ContainerRelativeLength::Cqi(v) |
+            ContainerRelativeLength::Cqb(v) => v == 0.0,
+        }
+    }
+
+    /// Computes the given container-relative length.
+    ///
+    /// Each axis resolves against the nearest query container that can be
+    /// queried in that axis, or the viewport if there's none.
+    pub fn to_computed_value(&self, context: &Context) -> CSSPixelLength {
+        // The result depends on the size of the ancestor query containers,
+        // which isn't captured by the rule cache.
+        context.rule_cache_conditions.borrow_mut().set_uncacheable();
+        context
+            .builder
+            .add_flags(ComputedValueFlags::DEPENDS_ON_QUERY_CONTAINER_SIZE);
+        #[cfg(feature = "servo")]
+        context.device().set_used_container_units();
+
+        let sizes = context.container_size_query.get();
+        let viewport_size = context.viewport_size_for_viewport_unit_resolution();
+        let (viewport_inline_size, viewport_block_size) =
+            if context.builder.writing_mode.is_vertical() {
+                (viewport_size.height, viewport_size.width)
+            } else {
+                (viewport_size.width, viewport_size.height)
+            };
+        let (factor, length) = match *self {
+            ContainerRelativeLength::Cqw(length) => {
+                (length, sizes.width.unwrap_or(viewport_size.width))
+            },
+            ContainerRelativeLength::Cqh(length) => {
+                (length, sizes.height.unwrap_or(viewport_size.height))
+            },
+            ContainerRelativeLength::Cqi(length) => {
+                (length, sizes.inline_size.unwrap_or(viewport_inline_size))
+            },
+            ContainerRelativeLength::Cqb(length) => {
+                (length, sizes.block_size.unwrap_or(viewport_block_size))
+            },
+        };
+
+        let trunc_scaled = ((length.0 as f64) * factor as f64 / 100.).trunc();
+        Au::from_f64_au(trunc_scaled).into()
+    }
+}
+
 /// HTML5 "character width", as defined in HTML5 § 14.5.4.
 #[derive(Clone, Copy, Debug, MallocSizeOf, PartialEq, ToCss, ToShmem)]
 pub struct CharacterWidth(pub i32);
@@ -426,6 +498,11 @@ pub enum NoCalcLength {
     /// <https://drafts.csswg.org/css-values/#viewport-relative-lengths>
     ViewportPercentage(ViewportPercentageLength),
 
+    /// A container query length.
+    ///
+    /// <https://drafts.csswg.org/css-contain-3/#container-lengths>
+    ContainerRelative(ContainerRelativeLength),
+
     /// HTML5 "character width", as defined in HTML5 § 14.5.4.
     ///
     /// This cannot be specified by the user directly and is only generated by
@@ -443,6 +520,7 @@ impl Mul<CSSFloat> for NoCalcLength {
             NoCalcLength::Absolute(v) => NoCalcLength::Absolute(v * scalar),
             NoCalcLength::FontRelative(v) => NoCalcLength::FontRelative(v * scalar),
             NoCalcLength::ViewportPercentage(v) => NoCalcLength::ViewportPercentage(v * scalar),
+            NoCalcLength::ContainerRelative(v) => NoCalcLength::ContainerRelative(v * scalar),
             NoCalcLength::ServoCharacterWidth(_) => panic!("Can't multiply ServoCharacterWidth!"),
         }
     }
@@ -481,6 +559,19 @@ impl NoCalcLength {
             "vmax" if !context.in_page_rule() => {
                 NoCalcLength::ViewportPercentage(ViewportPercentageLength::Vmax(value))
             },
+            // container query lengths
+            "cqw" if !context.in_page_rule() => {
+                NoCalcLength::ContainerRelative(ContainerRelativeLength::Cqw(value))
+            },
+            "cqh" if !context.in_page_rule() => {
+                NoCalcLength::ContainerRelative(ContainerRelativeLength::Cqh(value))
+            },
+            "cqi" if !context.in_page_rule() => {
+                NoCalcLength::ContainerRelative(ContainerRelativeLength::Cqi(value))
+            },
+            "cqb" if !context.in_page_rule() => {
+                NoCalcLength::ContainerRelative(ContainerRelativeLength::Cqb(value))
+            },
             _ => return Err(()),
         })
     }
@@ -513,6 +604,7 @@ impl Zero for NoCalcLength {
             NoCalcLength::Absolute(v) => v.is_zero(),
             NoCalcLength::FontRelative(v) => v.is_zero(),
             NoCalcLength::ViewportPercentage(v) => v.is_zero(),
+            NoCalcLength::ContainerRelative(v) => v.is_zero(),
             NoCalcLength::ServoCharacterWidth(v) => v.0 == 0,
         }
     }
@@ -579,6 +671,20 @@ impl Mul<CSSFloat> for ViewportPercentageLength {
     }
 }
 
+impl Mul<CSSFloat> for ContainerRelativeLength {
+    type Output = ContainerRelativeLength;
+
+    #[inline]
+    fn mul(self, scalar: CSSFloat) -> ContainerRelativeLength {
+        match self {
+            ContainerRelativeLength::Cqw(v) => ContainerRelativeLength::Cqw(v * scalar),
+            ContainerRelativeLength::Cqh(v) => ContainerRelativeLength::Cqh(v * scalar),
+            ContainerRelativeLength::Cqi(v) => ContainerRelativeLength::Cqi(v * scalar),
+            ContainerRelativeLength::Cqb(v) => ContainerRelativeLength::Cqb(v * scalar),
+        }
+    }
+}
+
 impl Length {
     #[inline]
     fn parse_internal<'i, 't>(
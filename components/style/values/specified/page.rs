@@ -0,0 +1,150 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Specified @page at-rule properties.
+//!
+//! https://drafts.csswg.org/css-page-3/#page-size-prop
+
+use crate::parser::{Parse, ParserContext};
+use crate::values::specified::length::{Length, NonNegativeLength};
+use crate::values::CSSFloat;
+use cssparser::Parser;
+use euclid::Size2D;
+use style_traits::{CSSPixel, ParseError, StyleParseErrorKind};
+
+/// The number of CSS pixels in a millimeter.
+const PX_PER_MM: CSSFloat = 96. / 25.4;
+
+/// A standard paper size.
+///
+/// https://drafts.csswg.org/css-page-3/#typedef-page-size-page-size
+#[allow(missing_docs)]
+#[derive(
+    Clone, Copy, Debug, Eq, MallocSizeOf, Parse, PartialEq, SpecifiedValueInfo, ToCss, ToShmem,
+)]
+pub enum PaperSize {
+    A5,
+    A4,
+    A3,
+    B5,
+    B4,
+    JisB5,
+    JisB4,
+    Letter,
+    Legal,
+    Ledger,
+}
+
+impl PaperSize {
+    /// The size of this paper in portrait orientation, in CSS pixels.
+    pub fn size(&self) -> Size2D<CSSFloat, CSSPixel> {
+        let (width, height) = match *self {
+            PaperSize::A5 => (148., 210.),
+            PaperSize::A4 => (210., 297.),
+            PaperSize::A3 => (297., 420.),
+            PaperSize::B5 => (176., 250.),
+            PaperSize::B4 => (250., 353.),
+            PaperSize::JisB5 => (182., 257.),
+            PaperSize::JisB4 => (257., 364.),
+            PaperSize::Letter => (215.9, 279.4),
+            PaperSize::Legal => (215.9, 355.6),
+            PaperSize::Ledger => (279.4, 431.8),
+        };
+        Size2D::new(width * PX_PER_MM, height * PX_PER_MM)
+    }
+}
+
+/// The orientation of a page.
+#[allow(missing_docs)]
+#[derive(
+    Clone, Copy, Debug, Eq, MallocSizeOf, Parse, PartialEq, SpecifiedValueInfo, ToCss, ToShmem,
+)]
+pub enum PageOrientation {
+    Portrait,
+    Landscape,
+}
+
+impl PageOrientation {
+    /// Rotate the given size so that it matches this orientation.
+    fn apply(&self, size: Size2D<CSSFloat, CSSPixel>) -> Size2D<CSSFloat, CSSPixel> {
+        let is_landscape = size.width > size.height;
+        match (*self, is_landscape) {
+            (PageOrientation::Portrait, true) | (PageOrientation::Landscape, false) => {
+                Size2D::new(size.height, size.width)
+            },
+            _ => size,
+        }
+    }
+}
+
+/// The `size` descriptor of an @page rule.
+///
+/// https://drafts.csswg.org/css-page-3/#descdef-page-size
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, SpecifiedValueInfo, ToCss, ToShmem)]
+pub enum PageSize {
+    /// The page has the size of the target medium.
+    Auto,
+    /// The target medium's size in the given orientation.
+    Orientation(PageOrientation),
+    /// An explicit width and height.
+    Size(NonNegativeLength, NonNegativeLength),
+    /// A standard paper size, optionally rotated.
+    Paper(PaperSize, Option<PageOrientation>),
+}
+
+impl PageSize {
+    /// Resolve the size of the page box, given the size of the target medium.
+    pub fn resolve(&self, default_size: Size2D<CSSFloat, CSSPixel>) -> Size2D<CSSFloat, CSSPixel> {
+        match *self {
+            PageSize::Auto => default_size,
+            PageSize::Orientation(orientation) => orientation.apply(default_size),
+            PageSize::Size(ref width, ref height) => {
+                // Only absolute lengths make sense without an element to resolve
+                // font-relative and viewport-relative units against.
+                let resolve = |length: &NonNegativeLength| match length.0 {
+                    Length::NoCalc(ref length) => {
+                        length.to_computed_pixel_length_without_context().ok()
+                    },
+                    _ => None,
+                };
+                match (resolve(width), resolve(height)) {
+                    (Some(width), Some(height)) => Size2D::new(width, height),
+                    _ => default_size,
+                }
+            },
+            PageSize::Paper(paper, None) => paper.size(),
+            PageSize::Paper(paper, Some(orientation)) => orientation.apply(paper.size()),
+        }
+    }
+}
+
+impl Parse for PageSize {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        if input.try(|i| i.expect_ident_matching("auto")).is_ok() {
+            return Ok(PageSize::Auto);
+        }
+
+        if let Ok(width) = input.try(|i| NonNegativeLength::parse(context, i)) {
+            let height = input
+                .try(|i| NonNegativeLength::parse(context, i))
+                .unwrap_or_else(|_| width.clone());
+            return Ok(PageSize::Size(width, height));
+        }
+
+        // `<page-size> || [ portrait | landscape ]`
+        let mut paper = input.try(|i| PaperSize::parse(context, i)).ok();
+        let orientation = input.try(|i| PageOrientation::parse(context, i)).ok();
+        if paper.is_none() && orientation.is_some() {
+            paper = input.try(|i| PaperSize::parse(context, i)).ok();
+        }
+        match (paper, orientation) {
+            (Some(paper), orientation) => Ok(PageSize::Paper(paper, orientation)),
+            (None, Some(orientation)) => Ok(PageSize::Orientation(orientation)),
+            (None, None) => Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError)),
+        }
+    }
+}
@@ -91,6 +91,7 @@ pub mod specified_value_info;
 pub mod values;
 #[macro_use]
 pub mod viewport;
+pub mod media_queries;
 pub mod owned_slice;
 pub mod owned_str;
 
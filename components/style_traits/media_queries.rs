@@ -0,0 +1,71 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Helper types for the media features which depend on the user and their
+//! device rather than on the viewport.
+
+define_css_keyword_enum! {
+    pub enum PrefersColorScheme {
+        Light = "light",
+        Dark = "dark",
+        NoPreference = "no-preference",
+    }
+}
+
+define_css_keyword_enum! {
+    pub enum DisplayMode {
+        Browser = "browser",
+        MinimalUi = "minimal-ui",
+        Standalone = "standalone",
+        Fullscreen = "fullscreen",
+    }
+}
+
+bitflags! {
+    /// The capabilities of a set of pointing devices.
+    ///
+    /// <https://drafts.csswg.org/mediaqueries-4/#mf-interaction>
+    #[cfg_attr(feature = "servo", derive(Deserialize, Serialize, MallocSizeOf))]
+    pub struct PointerCapabilities: u8 {
+        /// The pointing devices have limited accuracy, like touchscreens.
+        const COARSE = 1 << 0;
+        /// The pointing devices are accurate, like mice.
+        const FINE = 1 << 1;
+        /// The pointing devices can hover over elements.
+        const HOVER = 1 << 2;
+    }
+}
+
+/// The preferences of the user and the capabilities of their device which
+/// media features are evaluated against, as supplied by the embedder.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize, MallocSizeOf))]
+pub struct MediaEnvironment {
+    /// <https://drafts.csswg.org/mediaqueries-5/#prefers-color-scheme>
+    pub prefers_color_scheme: PrefersColorScheme,
+    /// <https://drafts.csswg.org/mediaqueries-5/#prefers-reduced-motion>
+    pub prefers_reduced_motion: bool,
+    /// <https://w3c.github.io/manifest/#the-display-mode-media-feature>
+    pub display_mode: DisplayMode,
+    /// The capabilities of the primary pointing device, if any.
+    pub primary_pointer: PointerCapabilities,
+    /// The union of the capabilities of all the pointing devices.
+    pub all_pointers: PointerCapabilities,
+    /// The number of bits per color component of the output device.
+    pub color_bits: u32,
+}
+
+impl Default for MediaEnvironment {
+    /// The environment of a desktop browser with a mouse.
+    fn default() -> Self {
+        MediaEnvironment {
+            prefers_color_scheme: PrefersColorScheme::Light,
+            prefers_reduced_motion: false,
+            display_mode: DisplayMode::Browser,
+            primary_pointer: PointerCapabilities::FINE | PointerCapabilities::HOVER,
+            all_pointers: PointerCapabilities::FINE | PointerCapabilities::HOVER,
+            color_bits: 8,
+        }
+    }
+}
@@ -399,6 +399,11 @@ where
                 },
                 EmbedderMsg::LoadComplete => {
                     self.loading_state = Some(LoadingState::Loaded);
+                    if opts::get().print_to_pdf.is_some() {
+                        if let Some(browser_id) = browser_id {
+                            self.event_queue.push(WindowEvent::PrintToPdf(browser_id));
+                        }
+                    }
                 },
                 EmbedderMsg::CloseBrowser => {
                     // TODO: close the appropriate "tab".
@@ -449,6 +454,15 @@ where
                         error!("Failed to store profile: {}", e);
                     }
                 },
+                EmbedderMsg::PdfPrinted(bytes) => {
+                    if let Some(ref filename) = opts::get().print_to_pdf {
+                        let result = File::create(filename).and_then(|mut f| f.write_all(&bytes));
+                        if let Err(e) = result {
+                            error!("Failed to store printed PDF: {}", e);
+                        }
+                        self.event_queue.push(WindowEvent::Quit);
+                    }
+                },
                 EmbedderMsg::MediaSessionEvent(_) => {
                     debug!("MediaSessionEvent received");
                     // TODO(ferjm): MediaSession support for Glutin based browsers.
@@ -137,6 +137,8 @@ pub trait HostTrait {
     fn on_media_session_playback_state_change(&self, state: MediaSessionPlaybackState);
     /// Called when the media session position state is set.
     fn on_media_session_set_position_state(&self, duration: f64, position: f64, playback_rate: f64);
+    /// The current page has been printed to the given PDF document.
+    fn on_pdf_printed(&self, pdf: Vec<u8>);
 }
 
 pub struct ServoGlue {
@@ -304,6 +306,15 @@ impl ServoGlue {
         self.process_event(event)
     }
 
+    /// Print the current page to a PDF document, which is handed to
+    /// `HostTrait::on_pdf_printed`.
+    pub fn print_to_pdf(&mut self) -> Result<(), &'static str> {
+        info!("print_to_pdf");
+        let browser_id = self.get_browser_id()?;
+        let event = WindowEvent::PrintToPdf(browser_id);
+        self.process_event(event)
+    }
+
     /// Redraw the page.
     pub fn refresh(&mut self) -> Result<(), &'static str> {
         info!("refresh");
@@ -610,6 +621,9 @@ impl ServoGlue {
                             ),
                     };
                 },
+                EmbedderMsg::PdfPrinted(pdf) => {
+                    self.callbacks.host_callbacks.on_pdf_printed(pdf);
+                },
                 EmbedderMsg::Status(..) |
                 EmbedderMsg::SelectFiles(..) |
                 EmbedderMsg::MoveTo(..) |
@@ -777,6 +791,7 @@ impl ResourceReaderMethods for ResourceReaderInstance {
             Resource::MediaControlsJS => {
                 &include_bytes!("../../../../resources/media-controls.js")[..]
             },
+            Resource::DebuggerJS => &include_bytes!("../../../../resources/debugger.js")[..],
         })
     }
 
@@ -222,6 +222,7 @@ pub struct CHostCallbacks {
     pub on_media_session_playback_state_change: extern "C" fn(state: CMediaSessionPlaybackState),
     pub on_media_session_set_position_state:
         extern "C" fn(duration: f64, position: f64, playback_rate: f64),
+    pub on_pdf_printed: extern "C" fn(data: *const u8, len: usize),
 }
 
 /// Servo options
@@ -510,6 +511,14 @@ pub extern "C" fn reload() {
     });
 }
 
+#[no_mangle]
+pub extern "C" fn print_to_pdf() {
+    catch_any_panic(|| {
+        debug!("print_to_pdf");
+        call(|s| s.print_to_pdf());
+    });
+}
+
 #[no_mangle]
 pub extern "C" fn stop() {
     catch_any_panic(|| {
@@ -797,4 +806,9 @@ impl HostTrait for HostCallbacks {
         );
         (self.0.on_media_session_set_position_state)(duration, position, playback_rate);
     }
+
+    fn on_pdf_printed(&self, pdf: Vec<u8>) {
+        debug!("on_pdf_printed ({} bytes)", pdf.len());
+        (self.0.on_pdf_printed)(pdf.as_ptr(), pdf.len());
+    }
 }
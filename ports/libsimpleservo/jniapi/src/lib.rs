@@ -521,6 +521,8 @@ impl HostTrait for HostCallbacks {
 
     fn set_clipboard_contents(&self, _contents: String) {}
 
+    fn on_pdf_printed(&self, _pdf: Vec<u8>) {}
+
     fn on_media_session_metadata(&self, title: String, artist: String, album: String) {
         info!("on_media_session_metadata");
         let env = self.jvm.get_env().unwrap();
@@ -0,0 +1,453 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+// The script debugger of a single script thread. This runs in a global of its own,
+// since a `Debugger` may not share a compartment with its debuggees. Requests from
+// the devtools server arrive as JSON through `handleRequest`, and `servoDebuggerPause`
+// suspends the debuggee until the devtools client asks to resume.
+
+"use strict";
+
+const dbg = new Debugger();
+
+// Debuggee globals, mapped to the serialized id of their pipeline.
+const pipelines = new Map();
+
+const sourceIds = new Map();
+const sourcesById = new Map();
+let nextSourceId = 0;
+
+// Breakpoints by "url:line:column", along with their hit handlers.
+const breakpoints = new Map();
+
+let pauseOnExceptions = false;
+let ignoreCaughtExceptions = true;
+let lastException = undefined;
+
+// Pipelines that should pause as soon as script runs in them.
+const interrupted = new Set();
+
+// Set while a resume limit of "step into" is active.
+let stepIntoPipeline = null;
+const steppingFrames = [];
+
+// The state of the current pause, if any. Objects are only inspectable while paused.
+let paused = null;
+
+function addDebuggee(global, pipeline) {
+  const debuggee = dbg.addDebuggee(global);
+  pipelines.set(debuggee, pipeline);
+  for (const script of dbg.findScripts({ global: debuggee })) {
+    onNewScript(script);
+  }
+}
+
+function removeDebuggee(pipeline) {
+  for (const [debuggee, id] of pipelines) {
+    if (id === pipeline) {
+      pipelines.delete(debuggee);
+      dbg.removeDebuggee(debuggee);
+    }
+  }
+  interrupted.delete(pipeline);
+}
+
+function pipelineOf(frame) {
+  if (!frame.script) {
+    return undefined;
+  }
+  return pipelines.get(frame.script.global);
+}
+
+function describeSource(source) {
+  let id = sourceIds.get(source);
+  if (id === undefined) {
+    id = String(nextSourceId++);
+    sourceIds.set(source, id);
+    sourcesById.set(id, source);
+  }
+  return {
+    id,
+    url: source.url || "",
+    introduction_type: source.introductionType || null,
+  };
+}
+
+function objectId(object) {
+  for (const [id, candidate] of paused.objects) {
+    if (candidate === object) {
+      return id;
+    }
+  }
+  const id = String(paused.objects.size);
+  paused.objects.set(id, object);
+  return id;
+}
+
+// Describes a debuggee value in the form expected by `JsGrip` in `debugger.rs`.
+function grip(value) {
+  switch (typeof value) {
+    case "undefined":
+      return { type: "undefined" };
+    case "boolean":
+      return { type: "boolean", value };
+    case "number":
+      if (Number.isNaN(value)) {
+        return { type: "specialNumber", value: "NaN" };
+      }
+      if (!Number.isFinite(value)) {
+        return { type: "specialNumber", value: value > 0 ? "inf" : "-inf" };
+      }
+      if (Object.is(value, -0)) {
+        return { type: "specialNumber", value: "-0" };
+      }
+      return { type: "number", value };
+    case "string":
+      return { type: "string", value };
+    case "symbol":
+      return { type: "string", value: value.toString() };
+  }
+  if (value === null) {
+    return { type: "null" };
+  }
+  if (value instanceof Debugger.Object) {
+    if (!paused) {
+      return { type: "object", class: value.class, id: "" };
+    }
+    return { type: "object", class: value.class, id: objectId(value) };
+  }
+  // Placeholders such as `{ optimizedOut: true }` for variables the engine discarded.
+  return { type: "undefined" };
+}
+
+function currentLocation(frame) {
+  const location = frame.script.getOffsetLocation(frame.offset);
+  return {
+    source: describeSource(frame.script.source),
+    line: location.lineNumber,
+    column: location.columnNumber,
+  };
+}
+
+function describeFrame(frame, depth) {
+  let callee = null;
+  if (frame.callee) {
+    callee = frame.callee.displayName || frame.callee.name || null;
+  }
+  let thisValue;
+  try {
+    thisValue = grip(frame.this);
+  } catch (e) {
+    thisValue = grip(undefined);
+  }
+  return {
+    depth,
+    kind: frame.type,
+    display_name: callee,
+    location: currentLocation(frame),
+    this: thisValue,
+  };
+}
+
+function describeEnvironment(environment) {
+  if (!environment) {
+    return null;
+  }
+  const info = {
+    kind: environment.type,
+    function_name: null,
+    object: null,
+    bindings: [],
+    parent: describeEnvironment(environment.parent),
+  };
+  if (environment.callee) {
+    info.function_name = environment.callee.displayName || environment.callee.name || null;
+  }
+  if (environment.type === "declarative") {
+    for (const name of environment.names()) {
+      info.bindings.push([name, grip(environment.getVariable(name))]);
+    }
+  } else {
+    info.object = grip(environment.object);
+  }
+  return info;
+}
+
+function describeObject(id) {
+  const object = paused && paused.objects.get(id);
+  if (!object) {
+    return null;
+  }
+  const properties = [];
+  for (const name of object.getOwnPropertyNames()) {
+    const descriptor = object.getOwnPropertyDescriptor(name);
+    if (!descriptor) {
+      continue;
+    }
+    properties.push({
+      name,
+      value: "value" in descriptor ? grip(descriptor.value) : null,
+      enumerable: !!descriptor.enumerable,
+      writable: !!descriptor.writable,
+      configurable: !!descriptor.configurable,
+    });
+  }
+  return { prototype: grip(object.proto), properties };
+}
+
+function frameStack(frame) {
+  const frames = [];
+  for (let current = frame; current; current = current.older) {
+    if (current.script) {
+      frames.push(current);
+    }
+  }
+  return frames;
+}
+
+function pause(frame, reason, exception) {
+  const pipeline = pipelineOf(frame);
+  if (pipeline === undefined) {
+    return undefined;
+  }
+  clearStepping();
+  paused = { pipeline, frames: frameStack(frame), objects: new Map() };
+  let limit = null;
+  try {
+    const info = {
+      pipeline,
+      reason,
+      exception: exception === undefined ? null : grip(exception),
+      frame: describeFrame(frame, 0),
+    };
+    limit = JSON.parse(servoDebuggerPause(JSON.stringify(info)));
+  } finally {
+    paused = null;
+  }
+  if (limit !== null && frame.live) {
+    setupStepping(frame, pipeline, limit);
+  }
+  updateEnterFrameHook();
+  return undefined;
+}
+
+function isEntryPoint(frame) {
+  return frame.script.getOffsetLocation(frame.offset).isEntryPoint;
+}
+
+function stepInto(frame) {
+  frame.onStep = function() {
+    if (!isEntryPoint(this)) {
+      return undefined;
+    }
+    return pause(this, "ResumeLimit");
+  };
+  steppingFrames.push(frame);
+}
+
+function setupStepping(frame, pipeline, limit) {
+  const startLine = frame.script.getOffsetLocation(frame.offset).lineNumber;
+  // Once the frame returns, pause at the next statement of its caller.
+  const onPop = function() {
+    if (this.older && this.older.script) {
+      stepInto(this.older);
+    }
+    return undefined;
+  };
+  frame.onPop = onPop;
+  if (limit !== "Finish") {
+    frame.onStep = function() {
+      const location = this.script.getOffsetLocation(this.offset);
+      if (location.lineNumber === startLine || !location.isEntryPoint) {
+        return undefined;
+      }
+      return pause(this, "ResumeLimit");
+    };
+  }
+  if (limit === "Step") {
+    stepIntoPipeline = pipeline;
+  }
+  steppingFrames.push(frame);
+}
+
+function clearStepping() {
+  for (const frame of steppingFrames) {
+    if (frame.live) {
+      frame.onStep = undefined;
+      frame.onPop = undefined;
+    }
+  }
+  steppingFrames.length = 0;
+  stepIntoPipeline = null;
+  updateEnterFrameHook();
+}
+
+function onEnterFrame(frame) {
+  const pipeline = pipelineOf(frame);
+  if (pipeline === undefined) {
+    return undefined;
+  }
+  if (interrupted.has(pipeline)) {
+    interrupted.delete(pipeline);
+    return pause(frame, "Interrupted");
+  }
+  if (stepIntoPipeline === pipeline) {
+    return pause(frame, "ResumeLimit");
+  }
+  return undefined;
+}
+
+function updateEnterFrameHook() {
+  dbg.onEnterFrame = interrupted.size || stepIntoPipeline !== null ? onEnterFrame : undefined;
+}
+
+function isCaught(frame) {
+  for (let current = frame; current; current = current.older) {
+    const script = current.script;
+    if (script && script.isInCatchScope && script.isInCatchScope(current.offset)) {
+      return true;
+    }
+  }
+  return false;
+}
+
+function allScripts(script) {
+  const scripts = [script];
+  for (const child of script.getChildScripts()) {
+    scripts.push(...allScripts(child));
+  }
+  return scripts;
+}
+
+function setBreakpointInScripts(breakpoint, scripts) {
+  for (const script of scripts) {
+    if (script.url !== breakpoint.url) {
+      continue;
+    }
+    if (breakpoint.line < script.startLine ||
+        breakpoint.line >= script.startLine + script.lineCount) {
+      continue;
+    }
+    for (const offset of script.getLineOffsets(breakpoint.line)) {
+      script.setBreakpoint(offset, breakpoint.handler);
+    }
+  }
+}
+
+function onNewScript(script) {
+  describeSource(script.source);
+  const scripts = allScripts(script);
+  for (const breakpoint of breakpoints.values()) {
+    setBreakpointInScripts(breakpoint, scripts);
+  }
+}
+
+function setBreakpoint(url, line, column, condition) {
+  const key = `${url}:${line}:${column}`;
+  removeBreakpoint(url, line, column);
+  const breakpoint = { url, line, column, condition };
+  breakpoint.handler = {
+    hit(frame) {
+      if (breakpoint.condition) {
+        const completion = frame.eval(breakpoint.condition);
+        if (!completion || !("return" in completion) || !completion.return) {
+          return undefined;
+        }
+      }
+      return pause(frame, "Breakpoint");
+    },
+  };
+  breakpoints.set(key, breakpoint);
+  setBreakpointInScripts(breakpoint, dbg.findScripts({ url, line }));
+}
+
+function removeBreakpoint(url, line, column) {
+  const key = `${url}:${line}:${column}`;
+  const breakpoint = breakpoints.get(key);
+  if (!breakpoint) {
+    return;
+  }
+  breakpoints.delete(key);
+  for (const script of dbg.findScripts({ url })) {
+    script.clearBreakpoint(breakpoint.handler);
+  }
+}
+
+dbg.onNewScript = onNewScript;
+
+dbg.onDebuggerStatement = function(frame) {
+  return pause(frame, "DebuggerStatement");
+};
+
+dbg.onExceptionUnwind = function(frame, value) {
+  if (!pauseOnExceptions || value === lastException) {
+    return undefined;
+  }
+  lastException = value;
+  if (ignoreCaughtExceptions && isCaught(frame)) {
+    return undefined;
+  }
+  return pause(frame, "Exception", value);
+};
+
+function handleRequest(json) {
+  const request = JSON.parse(json);
+  let result = null;
+  switch (request.type) {
+    case "removeDebuggee":
+      removeDebuggee(request.pipeline);
+      break;
+    case "getSources": {
+      const sources = new Set();
+      for (const script of dbg.findScripts()) {
+        if (pipelines.get(script.global) === request.pipeline) {
+          sources.add(script.source);
+        }
+      }
+      result = Array.from(sources, describeSource);
+      break;
+    }
+    case "getSourceText": {
+      const source = sourcesById.get(request.id);
+      result = source ? source.text : null;
+      break;
+    }
+    case "setBreakpoint":
+      setBreakpoint(request.url, request.line, request.column, request.condition);
+      break;
+    case "removeBreakpoint":
+      removeBreakpoint(request.url, request.line, request.column);
+      break;
+    case "pauseOnExceptions":
+      pauseOnExceptions = request.pause;
+      ignoreCaughtExceptions = request.ignoreCaught;
+      lastException = undefined;
+      break;
+    case "interrupt":
+      interrupted.add(request.pipeline);
+      updateEnterFrameHook();
+      break;
+    case "getFrames":
+      result = [];
+      if (paused && paused.pipeline === request.pipeline) {
+        const frames = paused.frames.slice(request.start, request.start + request.count);
+        result = frames.map((frame, index) => describeFrame(frame, request.start + index));
+      }
+      break;
+    case "getFrameEnvironment":
+      if (paused && paused.pipeline === request.pipeline) {
+        const frame = paused.frames[request.depth];
+        if (frame) {
+          result = describeEnvironment(frame.environment);
+        }
+      }
+      break;
+    case "getObjectProperties":
+      if (paused && paused.pipeline === request.pipeline) {
+        result = describeObject(request.id);
+      }
+      break;
+  }
+  return JSON.stringify(result);
+}
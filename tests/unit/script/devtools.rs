@@ -0,0 +1,35 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::devtools::{parse_rule_id, style_rule_ids};
+
+#[test]
+fn test_style_rule_ids_of_top_level_rules() {
+    let ids = style_rule_ids("@namespace svg url(http://www.w3.org/2000/svg); p {} div {}");
+    assert_eq!(ids, vec!["0:1", "0:2"]);
+}
+
+#[test]
+fn test_style_rule_ids_of_grouping_rules() {
+    let css = "p {} \
+               @media screen { div {} @supports (display: grid) { span {} } } \
+               @supports (color: red) { a {} }";
+    let ids = style_rule_ids(css);
+    assert_eq!(ids, vec!["0:0", "0:1.0", "0:1.1.0", "0:2.0"]);
+}
+
+#[test]
+fn test_style_rule_ids_of_nested_style_rules() {
+    let ids = style_rule_ids("p { color: red; & span { color: blue; } } div {}");
+    assert_eq!(ids, vec!["0:0", "0:0.0", "0:1"]);
+}
+
+#[test]
+fn test_parse_rule_id() {
+    assert_eq!(parse_rule_id("0:3"), Some(("0", vec![3])));
+    assert_eq!(parse_rule_id("1.2:0.4.1"), Some(("1.2", vec![0, 4, 1])));
+    assert_eq!(parse_rule_id("3"), None);
+    assert_eq!(parse_rule_id("0:"), None);
+    assert_eq!(parse_rule_id("0:1.x"), None);
+}
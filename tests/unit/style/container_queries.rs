@@ -0,0 +1,184 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::{device, parse_in_rule, serialize_rules};
+use app_units::Au;
+use euclid::Size2D;
+use style::context::QuirksMode;
+use style::properties::StyleBuilder;
+use style::stylesheets::container_rule::ContainerCondition;
+use style::stylesheets::CssRuleType;
+use style::values::computed::ContainerType;
+use style_traits::ToCss;
+
+fn parse(css: &str) -> Result<ContainerCondition, ()> {
+    parse_in_rule(CssRuleType::Container, css, |context, parser| {
+        let condition = ContainerCondition::parse(context, parser).map_err(|_| ())?;
+        parser.expect_exhausted().map_err(|_| ())?;
+        Ok(condition)
+    })
+}
+
+fn matches(css: &str, container_type: ContainerType, size: Option<(i32, i32)>) -> bool {
+    let device = device();
+    let mut builder = StyleBuilder::for_inheritance(&device, None, None);
+    builder.set_container_type(container_type);
+    let style = builder.build();
+    let size = size.map(|(width, height)| Size2D::new(Au::from_px(width), Au::from_px(height)));
+    parse(css)
+        .unwrap()
+        .evaluate_for_container(&device, QuirksMode::NoQuirks, &style, size)
+}
+
+#[test]
+fn container_condition_serialization() {
+    for css in &[
+        "(width >= 400px)",
+        "(min-inline-size: 10em)",
+        "(max-block-size: 0px)",
+        "(height)",
+        "(orientation: portrait)",
+        "(aspect-ratio: 16 / 9)",
+        "sidebar (width > 400px)",
+        "not (width < 400px)",
+        "(width > 1px) and (height > 1px) and (orientation: landscape)",
+        "(width > 1px) or ((height > 1px) and (height < 10px))",
+    ] {
+        assert_eq!(parse(css).unwrap().to_css_string(), *css);
+    }
+
+    assert_eq!(
+        parse("(WIDTH: 10PX)").unwrap().to_css_string(),
+        "(width: 10px)"
+    );
+    assert_eq!(
+        parse("(aspect-ratio: 16/9)").unwrap().to_css_string(),
+        "(aspect-ratio: 16 / 9)"
+    );
+}
+
+#[test]
+fn invalid_container_conditions() {
+    for css in &[
+        "",
+        "sidebar",
+        "none (width > 1px)",
+        "and (width > 1px)",
+        "(color)",
+        "(width: -1px)",
+        "(width: 10)",
+        "(min-width > 10px)",
+        "(min-width)",
+        "(min-orientation: portrait)",
+        "(orientation > portrait)",
+        "(width > 1px) and (height > 1px) or (height < 1px)",
+        "(width > 1px) (height > 1px)",
+    ] {
+        assert!(parse(css).is_err(), "{:?} should be invalid", css);
+    }
+}
+
+#[test]
+fn container_rule_serialization() {
+    let css = "@container (width > 400px) { a { color: red } }
+               @container sidebar not (orientation: portrait) {
+                   @container (height) { b { color: blue } }
+               }";
+    assert_eq!(
+        serialize_rules(css),
+        [
+            "@container (width > 400px) {\n  a { color: red; }\n}",
+            "@container sidebar not (orientation: portrait) {\n  \
+             @container (height) {\n  b { color: blue; }\n}\n}",
+        ]
+    );
+}
+
+#[test]
+fn size_queries() {
+    let size = Some((400, 300));
+    assert!(matches("(width >= 400px)", ContainerType::Size, size));
+    assert!(!matches("(width > 400px)", ContainerType::Size, size));
+    assert!(matches("(max-height: 300px)", ContainerType::Size, size));
+    assert!(matches("(inline-size: 400px)", ContainerType::Size, size));
+    assert!(matches("(block-size < 400px)", ContainerType::Size, size));
+    assert!(matches(
+        "(orientation: landscape)",
+        ContainerType::Size,
+        size
+    ));
+    assert!(matches("(aspect-ratio > 1/1)", ContainerType::Size, size));
+    assert!(matches("(width)", ContainerType::Size, size));
+    assert!(!matches("(height)", ContainerType::Size, Some((400, 0))));
+    assert!(matches(
+        "(orientation: portrait)",
+        ContainerType::Size,
+        Some((300, 300))
+    ));
+
+    // Font-relative units resolve against the style of the container.
+    assert!(matches("(width > 20em)", ContainerType::Size, size));
+    assert!(!matches("(width > 30em)", ContainerType::Size, size));
+}
+
+#[test]
+fn query_conditions() {
+    let size = Some((400, 300));
+    assert!(matches("not (width < 100px)", ContainerType::Size, size));
+    assert!(!matches(
+        "(width > 100px) and (height > 300px)",
+        ContainerType::Size,
+        size
+    ));
+    assert!(matches(
+        "(width > 500px) or (height > 200px)",
+        ContainerType::Size,
+        size
+    ));
+    assert!(matches(
+        "(width > 100px) and ((height > 500px) or (orientation: landscape))",
+        ContainerType::Size,
+        size
+    ));
+}
+
+#[test]
+fn unknown_size_queries() {
+    let size = Some((400, 300));
+
+    // The block size of an inline-size container can't be queried, which
+    // makes the result unknown even if the query is negated.
+    assert!(matches("(width > 100px)", ContainerType::InlineSize, size));
+    assert!(!matches(
+        "(height > 100px)",
+        ContainerType::InlineSize,
+        size
+    ));
+    assert!(!matches(
+        "not (height > 100px)",
+        ContainerType::InlineSize,
+        size
+    ));
+    assert!(!matches(
+        "(orientation: landscape)",
+        ContainerType::InlineSize,
+        size
+    ));
+    assert!(!matches(
+        "(width > 100px) and (height > 100px)",
+        ContainerType::InlineSize,
+        size
+    ));
+    assert!(matches(
+        "(width > 100px) or (height > 100px)",
+        ContainerType::InlineSize,
+        size
+    ));
+
+    // Nothing can be queried before the container has been laid out, or on
+    // elements that aren't query containers.
+    assert!(!matches("(width >= 0px)", ContainerType::Size, None));
+    assert!(!matches("not (width >= 0px)", ContainerType::Size, None));
+    assert!(!matches("(width >= 0px)", ContainerType::Normal, size));
+}
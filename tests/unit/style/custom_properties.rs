@@ -8,6 +8,7 @@ use style::custom_properties::{
     CssEnvironment, CustomPropertiesBuilder, CustomPropertiesMap, Name, SpecifiedValue,
 };
 use style::properties::{CustomDeclaration, CustomDeclarationValue};
+use style::properties_and_values::PropertyRegistry;
 use style::stylesheets::Origin;
 use test::{self, Bencher};
 
@@ -27,7 +28,8 @@ fn cascade(
         .collect::<Vec<_>>();
 
     let env = CssEnvironment;
-    let mut builder = CustomPropertiesBuilder::new(inherited, &env);
+    let registry = PropertyRegistry::default();
+    let mut builder = CustomPropertiesBuilder::new(inherited, &env, &registry);
 
     for declaration in &declarations {
         builder.cascade(declaration, Origin::Author);
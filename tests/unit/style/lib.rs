@@ -26,10 +26,14 @@ extern crate test;
 
 mod animated_properties;
 mod attr;
+mod container_queries;
 mod custom_properties;
 mod logical_geometry;
+mod media_queries;
+mod nesting;
 mod parsing;
 mod properties;
+mod properties_and_values;
 mod rule_tree;
 mod size_of;
 mod specified_values;
@@ -37,3 +41,82 @@ mod str;
 mod stylesheets;
 mod stylist;
 mod viewport;
+
+use cssparser::{Parser, ParserInput};
+use euclid::{Scale, Size2D};
+use servo_arc::Arc;
+use servo_url::ServoUrl;
+use style::context::QuirksMode;
+use style::media_queries::{Device, MediaList, MediaType};
+use style::parser::ParserContext;
+use style::shared_lock::{SharedRwLock, ToCssWithGuard};
+use style::stylesheets::{CssRuleType, Origin, Stylesheet, StylesheetLoader};
+use style_traits::ParsingMode;
+
+/// Parses `css` with `f`, in the context of an author rule of the given type.
+fn parse_in_rule<T, F>(rule_type: CssRuleType, css: &str, f: F) -> T
+where
+    F: FnOnce(&ParserContext, &mut Parser) -> T,
+{
+    let url = ServoUrl::parse("http://localhost").unwrap();
+    let context = ParserContext::new(
+        Origin::Author,
+        &url,
+        Some(rule_type),
+        ParsingMode::DEFAULT,
+        QuirksMode::NoQuirks,
+        None,
+        None,
+    );
+    let mut input = ParserInput::new(css);
+    f(&context, &mut Parser::new(&mut input))
+}
+
+/// A screen device with a 800x600 viewport.
+fn device() -> Device {
+    Device::new(
+        MediaType::screen(),
+        Size2D::new(800., 600.),
+        Scale::new(1.0),
+    )
+}
+
+/// Parses `css` as a stylesheet of the given origin, loading the stylesheets
+/// of its `@import` rules with `loader`.
+fn stylesheet_with_loader(
+    css: &str,
+    origin: Origin,
+    lock: &SharedRwLock,
+    loader: Option<&dyn StylesheetLoader>,
+) -> Stylesheet {
+    let url = ServoUrl::parse("about::test").unwrap();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    Stylesheet::from_str(
+        css,
+        url,
+        origin,
+        media,
+        lock.clone(),
+        loader,
+        None,
+        QuirksMode::NoQuirks,
+        0,
+    )
+}
+
+/// Parses `css` as an author stylesheet.
+fn stylesheet(css: &str) -> Stylesheet {
+    stylesheet_with_loader(css, Origin::Author, &SharedRwLock::new(), None)
+}
+
+/// Serializes the top-level rules of the author stylesheet `css`.
+fn serialize_rules(css: &str) -> Vec<String> {
+    let stylesheet = stylesheet(css);
+    let guard = stylesheet.shared_lock.read();
+    stylesheet
+        .contents
+        .rules(&guard)
+        .iter()
+        .map(|rule| rule.to_css_string(&guard).to_string())
+        .collect()
+}
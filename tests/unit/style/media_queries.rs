@@ -0,0 +1,129 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::parse_in_rule;
+use style::context::QuirksMode;
+use style::media_queries::{Device, MediaList};
+use style::stylesheets::CssRuleType;
+use style_traits::media_queries::{DisplayMode, MediaEnvironment};
+use style_traits::media_queries::{PointerCapabilities, PrefersColorScheme};
+use style_traits::ToCss;
+
+fn parse(css: &str) -> MediaList {
+    parse_in_rule(CssRuleType::Media, css, MediaList::parse)
+}
+
+fn device(media_environment: MediaEnvironment) -> Device {
+    let mut device = super::device();
+    device.set_media_environment(media_environment);
+    device
+}
+
+fn matches(css: &str, media_environment: MediaEnvironment) -> bool {
+    parse(css).evaluate(&device(media_environment), QuirksMode::NoQuirks)
+}
+
+#[test]
+fn environment_features_serialization() {
+    for css in &[
+        "(prefers-color-scheme: light)",
+        "(prefers-color-scheme: dark)",
+        "(prefers-color-scheme: no-preference)",
+        "(prefers-color-scheme)",
+        "(display-mode: minimal-ui)",
+        "(display-mode: fullscreen)",
+        "(prefers-reduced-motion: reduce)",
+        "(any-pointer: coarse)",
+        "(hover: none)",
+    ] {
+        assert_eq!(parse(css).to_css_string(), *css);
+    }
+
+    assert_eq!(
+        parse("(prefers-color-scheme: DARK)").to_css_string(),
+        "(prefers-color-scheme: dark)"
+    );
+    assert_eq!(
+        parse("(prefers-color-scheme: dim)").to_css_string(),
+        "not all"
+    );
+    assert_eq!(parse("(display-mode: window)").to_css_string(), "not all");
+}
+
+#[test]
+fn prefers_color_scheme() {
+    let light = MediaEnvironment::default();
+    let dark = MediaEnvironment {
+        prefers_color_scheme: PrefersColorScheme::Dark,
+        ..MediaEnvironment::default()
+    };
+    let no_preference = MediaEnvironment {
+        prefers_color_scheme: PrefersColorScheme::NoPreference,
+        ..MediaEnvironment::default()
+    };
+
+    assert!(matches("(prefers-color-scheme: light)", light));
+    assert!(!matches("(prefers-color-scheme: dark)", light));
+    assert!(matches("(prefers-color-scheme: dark)", dark));
+    assert!(!matches("(prefers-color-scheme: no-preference)", dark));
+    assert!(matches(
+        "(prefers-color-scheme: no-preference)",
+        no_preference
+    ));
+
+    assert!(matches("(prefers-color-scheme)", light));
+    assert!(matches("(prefers-color-scheme)", dark));
+    assert!(!matches("(prefers-color-scheme)", no_preference));
+}
+
+#[test]
+fn display_mode() {
+    let browser = MediaEnvironment::default();
+    let fullscreen = MediaEnvironment {
+        display_mode: DisplayMode::Fullscreen,
+        ..MediaEnvironment::default()
+    };
+
+    assert!(matches("(display-mode: browser)", browser));
+    assert!(!matches("(display-mode: standalone)", browser));
+    assert!(matches("(display-mode: fullscreen)", fullscreen));
+    assert!(!matches("(display-mode: minimal-ui)", fullscreen));
+    assert!(matches("(display-mode)", fullscreen));
+}
+
+#[test]
+fn prefers_reduced_motion() {
+    let motion = MediaEnvironment::default();
+    let reduced_motion = MediaEnvironment {
+        prefers_reduced_motion: true,
+        ..MediaEnvironment::default()
+    };
+
+    assert!(matches("(prefers-reduced-motion: no-preference)", motion));
+    assert!(!matches("(prefers-reduced-motion)", motion));
+    assert!(matches("(prefers-reduced-motion: reduce)", reduced_motion));
+    assert!(matches("(prefers-reduced-motion)", reduced_motion));
+}
+
+#[test]
+fn pointers() {
+    let mouse = MediaEnvironment::default();
+    let touchscreen = MediaEnvironment {
+        primary_pointer: PointerCapabilities::COARSE,
+        all_pointers: PointerCapabilities::COARSE | PointerCapabilities::FINE,
+        ..MediaEnvironment::default()
+    };
+    let no_pointer = MediaEnvironment {
+        primary_pointer: PointerCapabilities::empty(),
+        all_pointers: PointerCapabilities::empty(),
+        ..MediaEnvironment::default()
+    };
+
+    assert!(matches("(pointer: fine) and (hover)", mouse));
+    assert!(matches("(pointer: coarse) and (hover: none)", touchscreen));
+    assert!(matches("(any-pointer: fine)", touchscreen));
+    assert!(!matches("(any-hover)", touchscreen));
+    assert!(matches("(pointer: none)", no_pointer));
+    assert!(!matches("(any-pointer)", no_pointer));
+}
@@ -0,0 +1,188 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::{serialize_rules, stylesheet};
+use cssparser::ToCss;
+use selectors::parser::Selector;
+use style::selector_parser::SelectorImpl;
+use style::shared_lock::SharedRwLockReadGuard;
+use style::stylesheets::CssRule;
+
+fn specificity(a: u32, b: u32, c: u32) -> u32 {
+    a << 20 | b << 10 | c
+}
+
+/// Collects the selectors of the style rules in `rules` with `&` resolved
+/// against the selectors of their parent rules, like the stylist does.
+fn collect_resolved_selectors(
+    rules: &[CssRule],
+    guard: &SharedRwLockReadGuard,
+    parent: Option<&[Selector<SelectorImpl>]>,
+    result: &mut Vec<Selector<SelectorImpl>>,
+) {
+    for rule in rules {
+        match *rule {
+            CssRule::Style(ref lock) => {
+                let style_rule = lock.read_with(guard);
+                let selectors: Vec<_> = style_rule
+                    .selectors
+                    .0
+                    .iter()
+                    .map(|selector| match parent {
+                        Some(parent) => selector.replace_parent_selector(parent),
+                        None => selector.clone(),
+                    })
+                    .collect();
+                result.extend(selectors.iter().cloned());
+                if let Some(ref rules) = style_rule.rules {
+                    let rules = &rules.read_with(guard).0;
+                    collect_resolved_selectors(rules, guard, Some(&selectors), result);
+                }
+            },
+            CssRule::Media(ref lock) => {
+                let rules = &lock.read_with(guard).rules.read_with(guard).0;
+                collect_resolved_selectors(rules, guard, parent, result);
+            },
+            _ => {},
+        }
+    }
+}
+
+fn resolved_selectors(css: &str) -> Vec<(String, u32)> {
+    let stylesheet = stylesheet(css);
+    let guard = stylesheet.shared_lock.read();
+    let mut selectors = vec![];
+    collect_resolved_selectors(
+        &stylesheet.contents.rules(&guard),
+        &guard,
+        None,
+        &mut selectors,
+    );
+    selectors
+        .iter()
+        .map(|selector| (selector.to_css_string(), selector.specificity()))
+        .collect()
+}
+
+#[test]
+fn nested_style_rules() {
+    assert_eq!(
+        serialize_rules(
+            ".foo {
+                color: red;
+                .bar { color: blue }
+                > .baz { color: green }
+                .qux & { color: yellow }
+                &:hover, + p { color: orange }
+            }"
+        ),
+        [".foo {\n  \
+          color: red;\n  \
+          & .bar { color: blue; }\n  \
+          & > .baz { color: green; }\n  \
+          .qux & { color: yellow; }\n  \
+          &:hover, & + p { color: orange; }\n\
+          }"]
+    );
+}
+
+#[test]
+fn declarations_after_nested_rules() {
+    assert_eq!(
+        serialize_rules(".foo { .bar { color: blue } color: red; div { } margin: 0 }"),
+        [".foo {\n  \
+          color: red; margin: 0px;\n  \
+          & .bar { color: blue; }\n  \
+          & div { }\n\
+          }"]
+    );
+}
+
+#[test]
+fn deeply_nested_rules_are_indented() {
+    assert_eq!(
+        serialize_rules(".a { .b { color: red; .c { color: blue } } }"),
+        [".a {\n  \
+          & .b {\n    \
+          color: red;\n    \
+          & .c { color: blue; }\n  \
+          }\n\
+          }"]
+    );
+}
+
+#[test]
+fn nested_conditional_rules() {
+    assert_eq!(
+        serialize_rules(".foo { @media screen { color: red; .bar { color: blue } } }"),
+        [".foo {\n  \
+          @media screen {\n    \
+          & { color: red; }\n    \
+          & .bar { color: blue; }\n  \
+          }\n\
+          }"]
+    );
+}
+
+#[test]
+fn invalid_nested_rules() {
+    assert_eq!(
+        serialize_rules(
+            ".foo {
+                @font-face { font-family: foo }
+                @keyframes foo { }
+                color: red;
+            }"
+        ),
+        [".foo { color: red; }"]
+    );
+    assert_eq!(serialize_rules(".foo { }"), [".foo { }"]);
+    // A nested rule with an invalid selector is dropped, but the declarations
+    // around it are kept.
+    assert_eq!(
+        serialize_rules(".foo { color: red; .bar:invalid-pseudo { } margin: 0 }"),
+        [".foo { color: red; margin: 0px; }"]
+    );
+}
+
+#[test]
+fn nested_selectors_specificity() {
+    assert_eq!(
+        resolved_selectors(".foo, #bar { div { } & & { } }"),
+        [
+            (".foo".to_owned(), specificity(0, 1, 0)),
+            ("#bar".to_owned(), specificity(1, 0, 0)),
+            (":is(.foo, #bar) div".to_owned(), specificity(1, 0, 1)),
+            (
+                ":is(.foo, #bar) :is(.foo, #bar)".to_owned(),
+                specificity(2, 0, 0)
+            ),
+        ]
+    );
+
+    // `&` can't represent pseudo-elements, so they don't add to it.
+    assert_eq!(
+        resolved_selectors(".foo, p::before { &:hover { } }")[2],
+        (":is(.foo):hover".to_owned(), specificity(0, 2, 0))
+    );
+
+    // Selectors nested more than one level deep are relative to the resolved
+    // selectors of their parent rule.
+    assert_eq!(
+        resolved_selectors(".a { .b { .c { } } }")[2],
+        (":is(:is(.a) .b) .c".to_owned(), specificity(0, 3, 0))
+    );
+
+    // The declarations of conditional rules apply to the parent selectors.
+    assert_eq!(
+        resolved_selectors("#a { @media screen { color: red } }")[1],
+        (":is(#a)".to_owned(), specificity(1, 0, 0))
+    );
+
+    // Outside of nested rules, `&` is equivalent to `:scope`.
+    assert_eq!(
+        resolved_selectors("& { }"),
+        [("&".to_owned(), specificity(0, 1, 0))]
+    );
+}
@@ -120,6 +120,7 @@ mod effects;
 mod image;
 mod inherited_text;
 mod outline;
+mod page;
 mod selectors;
 mod supports;
 mod text_overflow;
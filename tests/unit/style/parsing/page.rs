@@ -0,0 +1,41 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::parsing::parse;
+use euclid::Size2D;
+use style::parser::Parse;
+use style::values::specified::PageSize;
+use style_traits::ToCss;
+
+#[test]
+fn test_page_size() {
+    assert_roundtrip_with_context!(PageSize::parse, "auto");
+    assert_roundtrip_with_context!(PageSize::parse, "landscape");
+    assert_roundtrip_with_context!(PageSize::parse, "8.5in 11in");
+    assert_roundtrip_with_context!(PageSize::parse, "10cm", "10cm 10cm");
+    assert_roundtrip_with_context!(PageSize::parse, "a4");
+    assert_roundtrip_with_context!(PageSize::parse, "jis-b5 landscape");
+    assert_roundtrip_with_context!(PageSize::parse, "landscape letter", "letter landscape");
+
+    assert!(parse(PageSize::parse, "-1in").is_err());
+    assert!(parse(PageSize::parse, "none").is_err());
+}
+
+#[test]
+fn test_page_size_resolve() {
+    let screen = Size2D::new(800., 600.);
+
+    let size = parse(PageSize::parse, "auto").unwrap();
+    assert_eq!(size.resolve(screen), screen);
+
+    let size = parse(PageSize::parse, "portrait").unwrap();
+    assert_eq!(size.resolve(screen), Size2D::new(600., 800.));
+
+    let size = parse(PageSize::parse, "1in 2in").unwrap();
+    assert_eq!(size.resolve(screen), Size2D::new(96., 192.));
+
+    let size = parse(PageSize::parse, "a5 landscape").unwrap();
+    let resolved = size.resolve(screen);
+    assert!(resolved.width > resolved.height);
+}
@@ -0,0 +1,132 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use cssparser::{Parser, ParserInput};
+use servo_arc::Arc;
+use servo_url::ServoUrl;
+use style::custom_properties::{
+    CssEnvironment, CustomPropertiesBuilder, CustomPropertiesMap, Name, SpecifiedValue,
+};
+use style::properties::{CSSWideKeyword, CustomDeclaration, CustomDeclarationValue};
+use style::properties_and_values::syntax::Descriptor;
+use style::properties_and_values::{PropertyRegistration, PropertyRegistry};
+use style::stylesheets::Origin;
+use style_traits::ToCss;
+
+fn parse_value(value: &str) -> Arc<SpecifiedValue> {
+    let mut input = ParserInput::new(value);
+    let mut parser = Parser::new(&mut input);
+    SpecifiedValue::parse(&mut parser).unwrap()
+}
+
+fn registration(name: &str, syntax: &str, inherits: bool, initial: &str) -> PropertyRegistration {
+    PropertyRegistration {
+        name: Name::from(name),
+        syntax: Descriptor::from_str(syntax).unwrap(),
+        inherits,
+        initial_value: Some(parse_value(initial)),
+        url_data: ServoUrl::parse("http://localhost").unwrap(),
+    }
+}
+
+fn cascade(
+    declarations: Vec<CustomDeclaration>,
+    inherited: Option<&Arc<CustomPropertiesMap>>,
+    registry: &PropertyRegistry,
+) -> Option<Arc<CustomPropertiesMap>> {
+    let env = CssEnvironment;
+    let mut builder = CustomPropertiesBuilder::new(inherited, &env, registry);
+    for declaration in &declarations {
+        builder.cascade(declaration, Origin::Author);
+    }
+    builder.build()
+}
+
+fn declaration(name: &str, value: &str) -> CustomDeclaration {
+    CustomDeclaration {
+        name: Name::from(name),
+        value: CustomDeclarationValue::Value(parse_value(value)),
+    }
+}
+
+#[test]
+fn syntax_strings() {
+    let valid = [
+        ("*", "*"),
+        ("<length>", "<length>"),
+        ("  <length> | <percentage>  ", "<length> | <percentage>"),
+        ("<color>#", "<color>#"),
+        ("<integer>+ | auto", "<integer>+ | auto"),
+        ("<transform-list>", "<transform-list>"),
+    ];
+    for &(syntax, serialization) in valid.iter() {
+        let descriptor = Descriptor::from_str(syntax).expect(syntax);
+        assert_eq!(descriptor.to_css_string(), serialization);
+    }
+
+    let invalid = [
+        "",
+        "<length",
+        "<unknown>",
+        "<Length>",
+        "<length> |",
+        "<length> +",
+        "<transform-list>+",
+        "initial",
+        "a b",
+        "* | <length>",
+    ];
+    for syntax in invalid.iter() {
+        assert!(Descriptor::from_str(syntax).is_err(), "{}", syntax);
+    }
+}
+
+#[test]
+fn initial_values_must_be_computationally_independent() {
+    let url_data = ServoUrl::parse("http://localhost").unwrap();
+    let length = Descriptor::from_str("<length>").unwrap();
+    let is_valid = |syntax: &Descriptor, value: &str| {
+        PropertyRegistration::is_valid_initial_value(syntax, &parse_value(value), &url_data)
+    };
+
+    assert!(is_valid(&length, "10px"));
+    assert!(is_valid(&length, "calc(1in + 2px)"));
+    assert!(!is_valid(&length, "1em"));
+    assert!(!is_valid(&length, "calc(1px + 1vw)"));
+    assert!(!is_valid(&length, "red"));
+    assert!(is_valid(&Descriptor::universal(), "1em"));
+}
+
+#[test]
+fn non_inherited_registered_properties() {
+    let mut registry = PropertyRegistry::default();
+    registry.insert(registration("reset", "<length>", false, "0px"));
+    registry.insert(registration("inherited", "<length>", true, "0px"));
+
+    let parent = cascade(
+        vec![
+            declaration("reset", "10px"),
+            declaration("inherited", "10px"),
+            declaration("unregistered", "10px"),
+        ],
+        None,
+        &registry,
+    );
+
+    let child = cascade(vec![], parent.as_ref(), &registry).unwrap();
+    assert!(child.get(&Name::from("reset")).is_none());
+    assert!(child.get(&Name::from("inherited")).is_some());
+    assert!(child.get(&Name::from("unregistered")).is_some());
+
+    let child = cascade(
+        vec![CustomDeclaration {
+            name: Name::from("reset"),
+            value: CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Inherit),
+        }],
+        parent.as_ref(),
+        &registry,
+    )
+    .unwrap();
+    assert!(child.get(&Name::from("reset")).is_some());
+}
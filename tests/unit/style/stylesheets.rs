@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use super::serialize_rules;
 use cssparser::SourceLocation;
 use servo_arc::Arc;
 use servo_config::set_pref;
@@ -234,3 +235,22 @@ fn test_source_url() {
         assert_eq!(*url_opt, test.1);
     }
 }
+
+#[test]
+fn test_layer_rules() {
+    let css = "@layer reset, base.components;
+               @layer { a { color: red } }
+               @layer base.components { b { color: blue } }
+               @layer a, b { c { color: green } }
+               @layer;
+               @layer theme";
+    assert_eq!(
+        serialize_rules(css),
+        [
+            "@layer reset, base.components;",
+            "@layer {\n  a { color: red; }\n}",
+            "@layer base.components {\n  b { color: blue; }\n}",
+            "@layer theme;",
+        ]
+    );
+}
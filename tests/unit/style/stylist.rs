@@ -2,23 +2,30 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use super::stylesheet_with_loader;
 use cssparser::SourceLocation;
 use euclid::Scale;
 use euclid::Size2D;
 use selectors::parser::{AncestorHashes, Selector};
 use servo_arc::Arc;
 use servo_atoms::Atom;
+use style::applicable_declarations::ApplicableDeclarationList;
 use style::context::QuirksMode;
-use style::media_queries::{Device, MediaType};
+use style::media_queries::{Device, MediaList, MediaType};
+use style::parser::ParserContext;
 use style::properties::{longhands, Importance};
 use style::properties::{PropertyDeclaration, PropertyDeclarationBlock};
+use style::rule_collector::sort_rules_from;
+use style::rule_tree::CascadeLevel;
 use style::selector_map::SelectorMap;
 use style::selector_parser::{SelectorImpl, SelectorParser};
-use style::shared_lock::SharedRwLock;
-use style::stylesheets::StyleRule;
+use style::shared_lock::{Locked, SharedRwLock, StylesheetGuards};
+use style::stylesheets::import_rule::{ImportLayer, ImportSheet};
+use style::stylesheets::{DocumentStyleSheet, ImportRule, Origin, StyleRule, StylesheetLoader};
 use style::stylist::needs_revalidation_for_testing;
-use style::stylist::{Rule, Stylist};
+use style::stylist::{ContainerConditionId, LayerId, Rule, Stylist};
 use style::thread_state::{self, ThreadState};
+use style::values::CssUrl;
 
 /// Helper method to get some Rules from selector strings.
 /// Each sublist of the result contains the Rules for one StyleRule.
@@ -39,6 +46,7 @@ fn get_mock_rules(css_selectors: &[&str]) -> (Vec<Vec<Rule>>, SharedRwLock) {
                         Importance::Normal,
                     ))),
                     source_location: SourceLocation { line: 0, column: 0 },
+                    rules: None,
                 }));
 
                 let guard = shared_lock.read();
@@ -52,6 +60,8 @@ fn get_mock_rules(css_selectors: &[&str]) -> (Vec<Vec<Rule>>, SharedRwLock) {
                             AncestorHashes::new(s, QuirksMode::NoQuirks),
                             locked.clone(),
                             i as u32,
+                            LayerId::root(),
+                            ContainerConditionId::none(),
                         )
                     })
                     .collect()
@@ -236,3 +246,167 @@ fn test_stylist_rule_tree_accessors() {
     stylist.rule_tree();
     stylist.rule_tree().root();
 }
+
+/// Loads the stylesheet of every `@import` rule from the same CSS.
+struct ImportLoader(&'static str);
+
+impl StylesheetLoader for ImportLoader {
+    fn request_stylesheet(
+        &self,
+        url: CssUrl,
+        source_location: SourceLocation,
+        context: &ParserContext,
+        lock: &SharedRwLock,
+        _media: Arc<Locked<MediaList>>,
+        layer: Option<ImportLayer>,
+    ) -> Arc<Locked<ImportRule>> {
+        let stylesheet = stylesheet_with_loader(self.0, context.stylesheet_origin, lock, None);
+        Arc::new(lock.wrap(ImportRule {
+            url,
+            layer,
+            stylesheet: ImportSheet(Arc::new(stylesheet)),
+            source_location,
+        }))
+    }
+}
+
+/// Returns the `z-index` declared by each of the `div` rules of the given
+/// stylesheets, in the order they're cascaded in, suffixed with `!important`
+/// for the rule nodes of the important cascade levels.
+fn cascade_order(sheets: &[(&str, Origin)], imported: &'static str) -> Vec<String> {
+    thread_state::initialize(ThreadState::LAYOUT);
+    let lock = SharedRwLock::new();
+    let mut stylist = mock_stylist();
+    for &(css, origin) in sheets {
+        let loader = ImportLoader(imported);
+        let stylesheet = stylesheet_with_loader(css, origin, &lock, Some(&loader));
+        stylist.append_stylesheet(DocumentStyleSheet(Arc::new(stylesheet)), &lock.read());
+    }
+
+    let guard = lock.read();
+    let guards = StylesheetGuards::same(&guard);
+    stylist.flush_for_testing(&guards);
+
+    // Collect the rules of each origin like the rule collector does.
+    let mut rules = ApplicableDeclarationList::new();
+    for &(origin, level) in &[
+        (Origin::UserAgent, CascadeLevel::UANormal),
+        (Origin::User, CascadeLevel::UserNormal),
+        (Origin::Author, CascadeLevel::same_tree_author_normal()),
+    ] {
+        let start = rules.len();
+        let cascade_data = stylist.cascade_data().borrow_for_origin(origin);
+        let div_rules = cascade_data
+            .normal_rules(None)
+            .and_then(|map| map.local_name_hash.get(&local_name!("div")));
+        for rule in div_rules.into_iter().flatten() {
+            rules.push(rule.to_applicable_declaration_block(level, cascade_data));
+        }
+        sort_rules_from(&mut rules, start);
+    }
+
+    let rule_node = stylist.rule_tree().insert_ordered_rules_with_important(
+        rules.into_iter().map(|block| block.for_rule_tree()),
+        &guards,
+    );
+    let mut order: Vec<String> = rule_node
+        .self_and_ancestors()
+        .filter_map(|node| {
+            let level = node.cascade_level();
+            let block = node.style_source()?.read(level.guard(&guards));
+            let mut z_index = String::new();
+            block.declarations()[0].to_css(&mut z_index).unwrap();
+            if level.is_important() {
+                z_index.push_str(" !important");
+            }
+            Some(z_index)
+        })
+        .collect();
+    order.reverse();
+    order
+}
+
+#[test]
+fn test_layer_order_normal_rules() {
+    let css = "div { z-index: 1 }
+               @layer b { body div { z-index: 2 } }
+               @layer b { div { z-index: 3 } }
+               @layer a { div { z-index: 4 } }
+               @layer b { div { z-index: 5 } }
+               @layer a.inner { div { z-index: 6 } }";
+    assert_eq!(
+        cascade_order(&[(css, Origin::Author)], ""),
+        ["3", "5", "2", "6", "4", "1"]
+    );
+}
+
+#[test]
+fn test_layer_order_important_rules() {
+    let css = "@layer a, b;
+               div { z-index: 1 !important }
+               @layer b { div { z-index: 2 !important } }
+               @layer a { div { z-index: 3 !important } }
+               div { z-index: 4 }
+               @layer a { div { z-index: 5 } }";
+    assert_eq!(
+        cascade_order(&[(css, Origin::Author)], ""),
+        [
+            "3",
+            "5",
+            "2",
+            "1",
+            "4",
+            "1 !important",
+            "2 !important",
+            "3 !important",
+        ]
+    );
+}
+
+#[test]
+fn test_layer_order_important_rules_across_origins() {
+    let ua_css = "@layer a { div { z-index: 1 !important } }
+                  div { z-index: 2 !important }";
+    let author_css = "div { z-index: 3 !important }
+                      @layer a { div { z-index: 4 !important } }";
+    assert_eq!(
+        cascade_order(
+            &[(ua_css, Origin::UserAgent), (author_css, Origin::Author)],
+            ""
+        ),
+        [
+            "1",
+            "2",
+            "4",
+            "3",
+            "3 !important",
+            "4 !important",
+            "2 !important",
+            "1 !important",
+        ]
+    );
+}
+
+#[test]
+fn test_layer_order_of_imported_rules() {
+    let css = "@import url(imported.css) layer(imported);
+               @import url(imported.css);
+               @layer first { div { z-index: 1 !important } }
+               div { z-index: 2 }";
+    let imported_css = "div { z-index: 3 !important }
+                        @layer inner { div { z-index: 4 } }";
+    assert_eq!(
+        cascade_order(&[(css, Origin::Author)], imported_css),
+        [
+            "4",
+            "3",
+            "4",
+            "1",
+            "3",
+            "2",
+            "3 !important",
+            "1 !important",
+            "3 !important",
+        ]
+    );
+}
@@ -8,9 +8,11 @@ importScripts("interfaces.js");
 // IMPORTANT: Do not change the list below without review from a DOM peer!
 test_interfaces([
   "Blob",
+  "BroadcastChannel",
   "CanvasGradient",
   "CanvasPattern",
   "CloseEvent",
+  "CryptoKey",
   "DOMMatrix",
   "DOMMatrixReadOnly",
   "DOMPoint",
@@ -49,6 +51,7 @@ test_interfaces([
   "PromiseRejectionEvent",
   "Request",
   "Response",
+  "SubtleCrypto",
   "TextDecoder",
   "TextEncoder",
   "URL",
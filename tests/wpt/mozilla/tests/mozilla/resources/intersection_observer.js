@@ -0,0 +1,25 @@
+// Observes `target` with a new IntersectionObserver, and returns the observer along with a
+// function that resolves with the next batch of entries it reports.
+function observeIntersections(target, options) {
+  var batches = [];
+  var waiting = [];
+  var observer = new IntersectionObserver(function(entries) {
+    if (waiting.length) {
+      waiting.shift()(entries);
+    } else {
+      batches.push(entries);
+    }
+  }, options);
+  observer.observe(target);
+  return {
+    observer: observer,
+    nextEntries: function() {
+      if (batches.length) {
+        return Promise.resolve(batches.shift());
+      }
+      return new Promise(function(resolve) {
+        waiting.push(resolve);
+      });
+    },
+  };
+}
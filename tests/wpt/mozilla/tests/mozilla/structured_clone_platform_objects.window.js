@@ -0,0 +1,122 @@
+function check_point(point, ctor) {
+  assert_true(point instanceof ctor);
+  assert_array_equals([point.x, point.y, point.z, point.w], [1, 2, 3, 4]);
+}
+
+function check_rect(rect, ctor) {
+  assert_true(rect instanceof ctor);
+  assert_array_equals([rect.x, rect.y, rect.width, rect.height], [5, 6, 7, 8]);
+}
+
+function check_matrix(matrix, ctor) {
+  assert_true(matrix instanceof ctor);
+  assert_true(matrix.is2D);
+  assert_array_equals(Array.from(matrix.toFloat64Array()),
+                      Array.from(new DOMMatrixReadOnly([1, 2, 3, 4, 5, 6]).toFloat64Array()));
+}
+
+function check_image_data(data) {
+  assert_true(data instanceof ImageData);
+  assert_equals(data.width, 2);
+  assert_equals(data.height, 1);
+  assert_array_equals(Array.from(data.data), [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+function check_file(file) {
+  assert_true(file instanceof File);
+  assert_equals(file.name, "name.txt");
+  assert_equals(file.type, "text/plain");
+  assert_equals(file.lastModified, 42);
+  assert_equals(file.size, 5);
+}
+
+function make_values() {
+  let data = new ImageData(2, 1);
+  data.data.set([1, 2, 3, 4, 5, 6, 7, 8]);
+  return [
+    new DOMPointReadOnly(1, 2, 3, 4),
+    new DOMPoint(1, 2, 3, 4),
+    new DOMRectReadOnly(5, 6, 7, 8),
+    new DOMRect(5, 6, 7, 8),
+    new DOMMatrixReadOnly([1, 2, 3, 4, 5, 6]),
+    new DOMMatrix([1, 2, 3, 4, 5, 6]),
+    data,
+    new File(["hello"], "name.txt", { type: "text/plain", lastModified: 42 }),
+  ];
+}
+
+function check_values(values) {
+  check_point(values[0], DOMPointReadOnly);
+  assert_false(values[0] instanceof DOMPoint);
+  check_point(values[1], DOMPoint);
+  check_rect(values[2], DOMRectReadOnly);
+  assert_false(values[2] instanceof DOMRect);
+  check_rect(values[3], DOMRect);
+  check_matrix(values[4], DOMMatrixReadOnly);
+  assert_false(values[4] instanceof DOMMatrix);
+  check_matrix(values[5], DOMMatrix);
+  check_image_data(values[6]);
+  check_file(values[7]);
+}
+
+test(function() {
+  let values = make_values();
+  let clones = structuredClone(values);
+  for (let i = 0; i < values.length; i++) {
+    assert_not_equals(clones[i], values[i]);
+  }
+  check_values(clones);
+}, "structuredClone() round-trips platform objects");
+
+test(function() {
+  let quad = new DOMQuad({ x: 1, y: 2 }, { x: 3, y: 4 }, { x: 5, y: 6 }, { x: 7, y: 8 });
+  let clone = structuredClone(quad);
+  assert_true(clone instanceof DOMQuad);
+  assert_equals(clone.p1.x, 1);
+  assert_equals(clone.p4.y, 8);
+}, "structuredClone() round-trips DOMQuad");
+
+test(function() {
+  assert_throws_dom("DataCloneError", function() {
+    structuredClone(document.body);
+  });
+}, "structuredClone() throws for non-serializable platform objects");
+
+test(function() {
+  let buffer = new Uint8Array([1, 2, 3]).buffer;
+  let clone = structuredClone(buffer, { transfer: [buffer] });
+  assert_equals(buffer.byteLength, 0);
+  assert_array_equals(Array.from(new Uint8Array(clone)), [1, 2, 3]);
+}, "structuredClone() transfers ArrayBuffers");
+
+test(function() {
+  let canvas = new OffscreenCanvas(10, 20);
+  let clone = structuredClone(canvas, { transfer: [canvas] });
+  assert_true(clone instanceof OffscreenCanvas);
+  assert_equals(clone.width, 10);
+  assert_equals(clone.height, 20);
+  assert_equals(canvas.width, 0);
+  assert_equals(canvas.height, 0);
+  assert_throws_dom("DataCloneError", function() {
+    structuredClone(canvas, { transfer: [canvas] });
+  });
+}, "structuredClone() transfers OffscreenCanvas and detaches the source");
+
+async_test(function(t) {
+  let channel = new MessageChannel();
+  channel.port2.onmessage = t.step_func_done(function(e) {
+    check_values(e.data);
+  });
+  channel.port1.postMessage(make_values());
+}, "Platform objects round-trip through a MessageChannel");
+
+async_test(function(t) {
+  let worker = new Worker("resources/structured_clone_echo.js");
+  worker.onmessage = t.step_func_done(function(e) {
+    check_values(e.data.values);
+    assert_array_equals(Array.from(new Uint8Array(e.data.buffer)), [1, 2, 3]);
+  });
+  let buffer = new Uint8Array([1, 2, 3]).buffer;
+  worker.postMessage({ values: make_values(), buffer: buffer }, [buffer]);
+  assert_equals(buffer.byteLength, 0);
+}, "Platform objects and transferred ArrayBuffers round-trip through a Worker");